@@ -5,7 +5,7 @@ use syn::{parse_macro_input, Data, DeriveInput, Error, Visibility};
 /// Attribute macro to automatically register MCP tools
 ///
 /// Usage:
-/// ```rust
+/// ```rust,ignore
 /// #[mcp_tool]
 /// pub struct MyTool;
 ///
@@ -89,6 +89,7 @@ fn generate_tool_registration(input: &DeriveInput) -> Result<TokenStream, Error>
         ::inventory::submit! {
             crate::tools::ToolEntry {
                 constructor: <#name as crate::tools::ToolRegistration>::tool_instance,
+                module: ::core::module_path!(),
             }
         }
     };
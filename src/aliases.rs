@@ -0,0 +1,74 @@
+//! Alternative tool names and suggestions for unknown ones
+//!
+//! Models often invoke a tool by a plausible name that is not quite the
+//! registered one. `[tools.aliases]` maps such names to the tool they mean,
+//! and invocations of an unknown name are answered with the closest tool
+//! names so the model can correct itself.
+
+use crate::tenant::ToolSet;
+use std::collections::HashMap;
+
+/// Most suggestions returned for an unknown tool name
+pub const MAX_SUGGESTIONS: usize = 3;
+
+/// The tool an invocation of `name` runs: `name` itself when the tool set
+/// has such a tool, otherwise the tool it is an alias of (if the set has it)
+pub fn resolve(aliases: &HashMap<String, String>, tools: &ToolSet, name: String) -> String {
+    if tools.functions.contains_key(&name) {
+        return name;
+    }
+    match aliases.get(&name) {
+        Some(target) if tools.functions.contains_key(target) => target.clone(),
+        _ => name,
+    }
+}
+
+/// Tools of the set whose name (or an alias of it) is within a few edits of
+/// `name`, closest first
+pub fn suggestions(aliases: &HashMap<String, String>, tools: &ToolSet, name: &str) -> Vec<String> {
+    let name = name.to_lowercase();
+    let threshold = (name.chars().count() / 3).max(2);
+
+    let candidates = tools
+        .definitions
+        .iter()
+        .map(|def| (def.name.as_str(), def.name.as_str()))
+        .chain(
+            aliases
+                .iter()
+                .filter(|(_, target)| tools.functions.contains_key(*target))
+                .map(|(alias, target)| (alias.as_str(), target.as_str())),
+        );
+
+    let mut closest: HashMap<&str, usize> = HashMap::new();
+    for (candidate, tool) in candidates {
+        let distance = edit_distance(&name, &candidate.to_lowercase());
+        if distance <= threshold {
+            let best = closest.entry(tool).or_insert(distance);
+            *best = (*best).min(distance);
+        }
+    }
+
+    let mut ranked: Vec<(&str, usize)> = closest.into_iter().collect();
+    ranked.sort_by(|a, b| a.1.cmp(&b.1).then(a.0.cmp(b.0)));
+    ranked
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(tool, _)| tool.to_string())
+        .collect()
+}
+
+/// Levenshtein distance between two strings, in characters
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
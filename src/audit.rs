@@ -0,0 +1,65 @@
+use crate::session::ClientInfo;
+use crate::tools::sanitize::SanitizerFinding;
+use chrono::Utc;
+use serde::Serialize;
+
+/// Tracing target for audit records, filter with `RUST_LOG=mcp_server::audit=info`
+pub const AUDIT_TARGET: &str = "mcp_server::audit";
+
+/// Outcome of an audited invocation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditStatus {
+    Success,
+    Error,
+    Rejected,
+    /// Aborted because the client disconnected
+    Cancelled,
+    /// Aborted after exceeding its time limit
+    TimedOut,
+}
+
+/// Audit record for a single tool invocation
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditRecord {
+    pub timestamp: String,
+    pub username: String,
+    pub tool_name: String,
+    pub status: AuditStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client: Option<ClientInfo>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub findings: Vec<SanitizerFinding>,
+}
+
+impl AuditRecord {
+    /// Create a record timestamped now
+    pub fn new(username: &str, tool_name: &str, status: AuditStatus) -> Self {
+        Self {
+            timestamp: Utc::now().to_rfc3339(),
+            username: username.to_string(),
+            tool_name: tool_name.to_string(),
+            status,
+            client: None,
+            findings: Vec::new(),
+        }
+    }
+
+    /// Attach the calling client's identification
+    pub fn with_client(mut self, client: Option<ClientInfo>) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Attach sanitizer findings to the record
+    pub fn with_findings(mut self, findings: Vec<SanitizerFinding>) -> Self {
+        self.findings = findings;
+        self
+    }
+}
+
+/// Emit an audit record as a structured tracing event
+pub fn record(entry: &AuditRecord) {
+    let json = serde_json::to_string(entry).unwrap_or_default();
+    tracing::info!(target: AUDIT_TARGET, record = %json, "tool invocation");
+}
@@ -0,0 +1,94 @@
+use super::types::UserCredentials;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// TTL-bounded token→user cache for expensive auth providers
+///
+/// Only successful lookups are cached, so a newly issued key works immediately.
+/// Revoked keys stay valid until their entry expires unless the cache is
+/// invalidated, which happens on every credentials reload.
+#[derive(Debug, Default)]
+pub struct AuthCache {
+    ttl: Duration,
+    max_entries: usize,
+    entries: Mutex<HashMap<String, (UserCredentials, Instant)>>,
+}
+
+impl AuthCache {
+    /// Create a cache; a zero TTL or capacity disables caching
+    pub fn new(ttl: Duration, max_entries: usize) -> Self {
+        Self {
+            ttl,
+            max_entries,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.ttl.is_zero() && self.max_entries > 0
+    }
+
+    /// Cached user for a token, if present and not expired
+    pub fn get(&self, token: &str) -> Option<UserCredentials> {
+        if !self.is_enabled() {
+            return None;
+        }
+
+        let mut entries = self.lock();
+        match entries.get(token) {
+            Some((user, expires)) if *expires > Instant::now() => Some(user.clone()),
+            Some(_) => {
+                entries.remove(token);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Cache a successful lookup
+    pub fn insert(&self, token: &str, user: UserCredentials) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let now = Instant::now();
+        let mut entries = self.lock();
+        if entries.len() >= self.max_entries && !entries.contains_key(token) {
+            entries.retain(|_, (_, expires)| *expires > now);
+            if entries.len() >= self.max_entries {
+                // Still full: evict the entry closest to expiry
+                if let Some(oldest) = entries
+                    .iter()
+                    .min_by_key(|(_, (_, expires))| *expires)
+                    .map(|(key, _)| key.clone())
+                {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+        entries.insert(token.to_string(), (user, now + self.ttl));
+    }
+
+    /// Drop a single token, e.g. after revoking it
+    pub fn invalidate(&self, token: &str) {
+        self.lock().remove(token);
+    }
+
+    /// Drop every cached entry
+    pub fn invalidate_all(&self) {
+        self.lock().clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, (UserCredentials, Instant)>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -16,6 +16,20 @@ pub enum AuthError {
     InvalidFormat,
     /// API key is invalid or expired
     InvalidToken,
+    /// Authenticated user lacks admin privileges
+    Forbidden,
+    /// The auth provider could not be reached
+    Unavailable,
+    /// Replay protection headers are missing or malformed
+    MissingReplayHeaders,
+    /// Request timestamp is outside the accepted clock skew
+    StaleRequest,
+    /// Request nonce was already used
+    ReplayedNonce,
+    /// Request signature does not match
+    InvalidSignature,
+    /// Request body signature is required but missing
+    MissingSignature,
 }
 
 impl IntoResponse for AuthError {
@@ -26,6 +40,20 @@ impl IntoResponse for AuthError {
                 "Invalid Authorization header format. Expected: Bearer <token>"
             }
             AuthError::InvalidToken => "Invalid or expired API key",
+            AuthError::Forbidden => "Admin privileges required",
+            AuthError::Unavailable => "Authentication provider unavailable",
+            AuthError::MissingReplayHeaders => {
+                "Missing or malformed request timestamp, nonce or signature header"
+            }
+            AuthError::StaleRequest => "Request timestamp outside the accepted window",
+            AuthError::ReplayedNonce => "Request nonce already used",
+            AuthError::InvalidSignature => "Invalid request signature",
+            AuthError::MissingSignature => "Missing X-Signature header",
+        };
+        let status = match self {
+            AuthError::Forbidden => StatusCode::FORBIDDEN,
+            AuthError::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::UNAUTHORIZED,
         };
 
         let error_details = ErrorDetails {
@@ -35,7 +63,7 @@ impl IntoResponse for AuthError {
         };
 
         (
-            StatusCode::UNAUTHORIZED,
+            status,
             Json(json!({
                 "jsonrpc": "2.0",
                 "error": error_details,
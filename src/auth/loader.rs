@@ -1,37 +1,295 @@
-use super::types::{CredentialsConfig, CredentialsStore, UserCredentials};
-use anyhow::{Context, Result};
-use std::collections::HashMap;
+use super::types::{CredentialsConfig, CredentialsStore, UserConfig, UserCredentials};
+use anyhow::{Context, Result, anyhow};
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::sync::Arc;
 
+/// Header of a binary age-encrypted file
+const AGE_BINARY_HEADER: &[u8] = b"age-encryption.org/v1";
+
+/// Header of an ASCII-armored age-encrypted file
+const AGE_ARMOR_HEADER: &[u8] = b"-----BEGIN AGE ENCRYPTED FILE-----";
+
+/// Supported credentials file formats
+/// Detected from the file extension, TOML is the default
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CredentialsFormat {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl CredentialsFormat {
+    /// Detect the format from a file path (`.yaml`/`.yml` and `.json`, anything else is TOML)
+    /// A trailing `.age` extension is ignored, so `credentials.yaml.age` is YAML
+    pub fn from_path(path: &str) -> Self {
+        let path = path.strip_suffix(".age").unwrap_or(path);
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase());
+
+        match extension.as_deref() {
+            Some("yaml") | Some("yml") => CredentialsFormat::Yaml,
+            Some("json") => CredentialsFormat::Json,
+            _ => CredentialsFormat::Toml,
+        }
+    }
+
+    /// Parse credentials file contents in this format
+    pub fn parse(self, contents: &str) -> Result<CredentialsConfig> {
+        let config = match self {
+            CredentialsFormat::Toml => toml::from_str(contents)?,
+            // An empty YAML document is null, treat it like an empty TOML file
+            CredentialsFormat::Yaml if contents.trim().is_empty() => CredentialsConfig::default(),
+            CredentialsFormat::Yaml => serde_yaml::from_str(contents)?,
+            CredentialsFormat::Json => serde_json::from_str(contents)?,
+        };
+        Ok(config)
+    }
+}
+
 /// Get the path to the credentials file
 /// Checks MCP_CREDENTIALS_PATH env var, defaults to config/credentials.toml
 pub fn get_credentials_path() -> String {
     env::var("MCP_CREDENTIALS_PATH").unwrap_or_else(|_| "config/credentials.toml".to_string())
 }
 
-/// Load credentials from TOML file
+/// Check whether file contents are an age-encrypted envelope (binary or armored)
+pub fn is_age_encrypted(bytes: &[u8]) -> bool {
+    let trimmed = bytes.trim_ascii_start();
+    trimmed.starts_with(AGE_BINARY_HEADER) || trimmed.starts_with(AGE_ARMOR_HEADER)
+}
+
+/// Check whether file contents look like a SOPS-managed document
+fn is_sops_encrypted(contents: &str) -> bool {
+    contents.contains("ENC[AES256_GCM,") && contents.contains("sops")
+}
+
+/// Load the age identities used to decrypt credentials
+/// Checks MCP_CREDENTIALS_AGE_KEY (inline secret key), then MCP_CREDENTIALS_AGE_KEY_FILE
+fn load_age_identities() -> Result<Vec<age::x25519::Identity>> {
+    let keys = if let Ok(key) = env::var("MCP_CREDENTIALS_AGE_KEY") {
+        key
+    } else if let Ok(key_file) = env::var("MCP_CREDENTIALS_AGE_KEY_FILE") {
+        fs::read_to_string(&key_file)
+            .with_context(|| format!("Failed to read age key file at: {}", key_file))?
+    } else {
+        anyhow::bail!(
+            "Credentials file is age-encrypted but neither MCP_CREDENTIALS_AGE_KEY nor MCP_CREDENTIALS_AGE_KEY_FILE is set"
+        );
+    };
+
+    // Identity files may contain several keys and `#` comments (age-keygen output)
+    let identities = keys
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            age::x25519::Identity::from_str(line).map_err(|e| anyhow!("Invalid age key: {}", e))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    if identities.is_empty() {
+        anyhow::bail!("No age keys found for decrypting credentials");
+    }
+
+    Ok(identities)
+}
+
+/// Decrypt an age-encrypted credentials file in memory
+/// The plaintext is never written to disk
+fn decrypt_credentials(ciphertext: &[u8]) -> Result<String> {
+    let identities = load_age_identities()?;
+    let decryptor = age::Decryptor::new_buffered(age::armor::ArmoredReader::new(ciphertext))?;
+    let mut reader = decryptor.decrypt(identities.iter().map(|i| i as &dyn age::Identity))?;
+
+    let mut plaintext = String::new();
+    reader
+        .read_to_string(&mut plaintext)
+        .context("Decrypted credentials are not valid UTF-8")?;
+    Ok(plaintext)
+}
+
+/// Read the credentials file, transparently decrypting age envelopes
+fn read_credentials_file(path: &str) -> Result<String> {
+    let bytes =
+        fs::read(path).with_context(|| format!("Failed to read credentials file at: {}", path))?;
+
+    if is_age_encrypted(&bytes) {
+        return decrypt_credentials(&bytes)
+            .with_context(|| format!("Failed to decrypt credentials file at: {}", path));
+    }
+
+    let contents = String::from_utf8(bytes)
+        .with_context(|| format!("Failed to read credentials file at: {}", path))?;
+
+    if is_sops_encrypted(&contents) {
+        anyhow::bail!(
+            "Credentials file at {} is SOPS-encrypted. Decrypt it at startup with `sops exec-file` or re-encrypt it with age",
+            path
+        );
+    }
+
+    Ok(contents)
+}
+
+/// Check whether a directory entry should be loaded as a credentials file
+/// Accepts `.toml`, `.yaml`, `.yml` and `.json` (optionally with `.age`), skipping hidden files
+fn is_credentials_file(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+        return false;
+    };
+    let name = name.to_ascii_lowercase();
+    let name = name.strip_suffix(".age").unwrap_or(&name);
+
+    !name.starts_with('.')
+        && [".toml", ".yaml", ".yml", ".json"]
+            .iter()
+            .any(|ext| name.ends_with(ext))
+}
+
+/// User entry collected from a credentials file, with the file it came from
+struct LoadedUser {
+    username: String,
+    config: UserConfig,
+    source: PathBuf,
+}
+
+/// Collect users from a credentials file or directory of credentials files
+fn collect_users(
+    path: &Path,
+    users: &mut Vec<LoadedUser>,
+    visited: &mut HashSet<PathBuf>,
+) -> Result<()> {
+    if !path.is_dir() {
+        return collect_users_from_file(path, users, visited);
+    }
+
+    let entries = fs::read_dir(path).with_context(|| {
+        format!(
+            "Failed to read credentials directory at: {}",
+            path.display()
+        )
+    })?;
+    let mut files = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.is_file() && is_credentials_file(p))
+        .collect::<Vec<_>>();
+    // Sorted so load order (and error messages) are deterministic
+    files.sort();
+
+    for file in files {
+        collect_users_from_file(&file, users, visited)?;
+    }
+
+    Ok(())
+}
+
+/// Collect users from a single credentials file, following its `include` directives
+fn collect_users_from_file(
+    path: &Path,
+    users: &mut Vec<LoadedUser>,
+    visited: &mut HashSet<PathBuf>,
+) -> Result<()> {
+    let display = path.display().to_string();
+    let canonical = fs::canonicalize(path)
+        .with_context(|| format!("Failed to read credentials file at: {}", display))?;
+    if !visited.insert(canonical) {
+        anyhow::bail!("Credentials file included more than once: {}", display);
+    }
+
+    let contents = read_credentials_file(&display)?;
+    let config = CredentialsFormat::from_path(&display)
+        .parse(&contents)
+        .with_context(|| format!("Failed to parse credentials file at: {}", display))?;
+
+    let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+    for include in &config.include {
+        collect_users(&base_dir.join(include), users, visited)
+            .with_context(|| format!("Failed to include '{}' from {}", include, display))?;
+    }
+
+    let mut file_users = config.users.into_iter().collect::<Vec<_>>();
+    file_users.sort_by(|a, b| a.0.cmp(&b.0));
+    users.extend(file_users.into_iter().map(|(username, config)| LoadedUser {
+        username,
+        config,
+        source: path.to_path_buf(),
+    }));
+
+    Ok(())
+}
+
+/// Load credentials from a TOML, YAML or JSON file, optionally age-encrypted
+/// MCP_CREDENTIALS_PATH may also point at a directory, in which case every
+/// credentials file inside it is merged; files can pull in others via `include`
 /// Returns Arc-wrapped HashMap indexed by API key
 pub fn load_credentials() -> Result<CredentialsStore> {
     let path = get_credentials_path();
 
-    let contents = fs::read_to_string(&path)
-        .with_context(|| format!("Failed to read credentials file at: {}", path))?;
-    let config: CredentialsConfig = toml::from_str(&contents)
-        .with_context(|| format!("Failed to parse credentials file at: {}", path))?;
+    let mut users = Vec::new();
+    collect_users(Path::new(&path), &mut users, &mut HashSet::new())?;
 
-    if config.users.is_empty() {
+    if users.is_empty() {
         anyhow::bail!("No users found in credentials file at: {}", path);
     }
 
+    let mut sources: HashMap<String, PathBuf> = HashMap::new();
     let mut store = HashMap::new();
-    for (username, user_config) in config.users {
+    for LoadedUser {
+        username,
+        config: user_config,
+        source,
+    } in users
+    {
+        if let Some(previous) = sources.get(&username) {
+            anyhow::bail!(
+                "Duplicate user '{}' defined in {} and {}",
+                username,
+                previous.display(),
+                source.display()
+            );
+        }
+        sources.insert(username.clone(), source);
+
+        for (tool, arguments) in &user_config.tool_arguments {
+            arguments.check_templates().map_err(|e| {
+                anyhow::anyhow!(
+                    "Invalid tool_arguments.{} for user '{}': {}",
+                    tool,
+                    username,
+                    e
+                )
+            })?;
+        }
+
+        if user_config
+            .budget
+            .is_some_and(|budget| budget.window_secs == 0)
+        {
+            anyhow::bail!(
+                "Invalid budget for user '{}': window_secs must be positive",
+                username
+            );
+        }
+
         let credentials = UserCredentials::new(
             username.clone(),
             user_config.api_key.clone(),
             user_config.external_keys,
-        );
+        )
+        .with_admin(user_config.admin)
+        .with_priority(user_config.priority)
+        .with_tenant(user_config.tenant)
+        .with_roles(user_config.roles)
+        .with_signing_secret(user_config.signing_secret)
+        .with_tool_arguments(user_config.tool_arguments)
+        .with_budget(user_config.budget);
 
         if store.contains_key(&user_config.api_key) {
             anyhow::bail!(
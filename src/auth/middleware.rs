@@ -1,12 +1,16 @@
+use super::cache::AuthCache;
 use super::error::AuthError;
-use super::types::{AuthenticatedUser, CredentialsStore, validate_api_key};
+use super::provider::{Authenticator, StaticAuthProvider};
+use super::types::{AuthenticatedUser, CredentialsStore, UserCredentials};
 use axum::{
-    extract::Request,
-    http::HeaderMap,
+    extract::{Query, Request},
+    http::{HeaderMap, HeaderName},
     response::{IntoResponse, Response},
 };
+use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
 use tower::{Layer, Service};
 
@@ -14,13 +18,60 @@ use tower::{Layer, Service};
 /// Creates AuthMiddleware instances
 #[derive(Clone)]
 pub struct AuthLayer {
-    credentials: CredentialsStore,
+    authenticator: Authenticator,
+    token_sources: Arc<TokenSources>,
+}
+
+/// Fallback locations for the API key, for clients that cannot set
+/// `Authorization` (e.g. browser `EventSource`)
+/// `Authorization: Bearer` always takes precedence when present
+#[derive(Debug, Clone, Default)]
+pub struct TokenSources {
+    /// Header carrying the raw API key, e.g. `X-Api-Key`
+    pub header: Option<HeaderName>,
+    /// Query parameter carrying the API key, e.g. `access_token`
+    pub query_param: Option<String>,
 }
 
 impl AuthLayer {
-    /// Create a new authentication layer
+    /// Create a new authentication layer validating against a static credentials store
     pub fn new(credentials: CredentialsStore) -> Self {
-        Self { credentials }
+        Self::from_authenticator(Authenticator::new(
+            Arc::new(StaticAuthProvider::new(credentials)),
+            Arc::new(AuthCache::default()),
+        ))
+    }
+
+    /// Create an authentication layer from a provider and cache
+    pub fn from_authenticator(authenticator: Authenticator) -> Self {
+        Self {
+            authenticator,
+            token_sources: Arc::new(TokenSources::default()),
+        }
+    }
+
+    /// Also accept the API key from an alternative header and/or query parameter
+    pub fn with_token_sources(mut self, token_sources: TokenSources) -> Self {
+        self.token_sources = Arc::new(token_sources);
+        self
+    }
+
+    /// Resolve the API key carried in headers (`Authorization: Bearer` or the
+    /// configured header) to a user, for transports outside the HTTP router
+    pub async fn authenticate_headers(
+        &self,
+        headers: &HeaderMap,
+    ) -> Result<UserCredentials, AuthError> {
+        let token = token_from_headers(headers, &self.token_sources)
+            .unwrap_or(Err(AuthError::MissingToken));
+        authenticate(token, &self.authenticator).await
+    }
+}
+
+/// Rejects every token until configured with credentials or a provider
+impl Default for AuthLayer {
+    fn default() -> Self {
+        Self::new(Arc::new(HashMap::new()))
     }
 }
 
@@ -30,7 +81,8 @@ impl<S> Layer<S> for AuthLayer {
     fn layer(&self, inner: S) -> Self::Service {
         AuthMiddleware {
             inner,
-            credentials: self.credentials.clone(),
+            authenticator: self.authenticator.clone(),
+            token_sources: self.token_sources.clone(),
         }
     }
 }
@@ -40,7 +92,8 @@ impl<S> Layer<S> for AuthLayer {
 #[derive(Clone)]
 pub struct AuthMiddleware<S> {
     inner: S,
-    credentials: CredentialsStore,
+    authenticator: Authenticator,
+    token_sources: Arc<TokenSources>,
 }
 
 impl<S> Service<Request> for AuthMiddleware<S>
@@ -57,40 +110,80 @@ where
     }
 
     fn call(&mut self, mut req: Request) -> Self::Future {
-        let credentials = self.credentials.clone();
-        let auth_result = authenticate(req.headers(), &credentials);
-
-        match auth_result {
-            Ok(user_credentials) => {
-                // Inject authenticated user into request extensions
-                req.extensions_mut()
-                    .insert(AuthenticatedUser(user_credentials));
-
-                // Forward to inner service
-                let future = self.inner.call(req);
-                Box::pin(future)
-            }
-            Err(auth_error) => {
-                // Return 401 Unauthorized
-                Box::pin(async move { Ok(auth_error.into_response()) })
+        let authenticator = self.authenticator.clone();
+        let token = extract_token(&req, &self.token_sources);
+        // Take the service that was driven to readiness, leaving a clone behind
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        Box::pin(async move {
+            match authenticate(token, &authenticator).await {
+                Ok(user_credentials) => {
+                    // Inject authenticated user into request extensions
+                    req.extensions_mut()
+                        .insert(AuthenticatedUser(user_credentials));
+
+                    // Forward to inner service
+                    inner.call(req).await
+                }
+                // Return 401 Unauthorized (or 503 when the provider failed)
+                Err(auth_error) => Ok(auth_error.into_response()),
             }
+        })
+    }
+}
+
+/// Resolve the extracted API key to a user
+async fn authenticate(
+    token: Result<String, AuthError>,
+    authenticator: &Authenticator,
+) -> Result<UserCredentials, AuthError> {
+    let token = token?;
+
+    match authenticator.authenticate(&token).await {
+        Ok(user) => user.ok_or(AuthError::InvalidToken),
+        Err(e) => {
+            tracing::warn!(error = %e, "auth provider lookup failed");
+            Err(AuthError::Unavailable)
         }
     }
 }
 
-/// Extract and validate Bearer token from request headers
-fn authenticate(
-    headers: &HeaderMap,
-    credentials: &CredentialsStore,
-) -> Result<super::types::UserCredentials, AuthError> {
-    let auth_header = headers
-        .get("authorization")
-        .and_then(|v| v.to_str().ok())
-        .ok_or(AuthError::MissingToken)?;
+/// Find the API key: `Authorization: Bearer` first, then the configured fallbacks
+fn extract_token(req: &Request, token_sources: &TokenSources) -> Result<String, AuthError> {
+    if let Some(token) = token_from_headers(req.headers(), token_sources) {
+        return token;
+    }
 
-    let token = auth_header
-        .strip_prefix("Bearer ")
-        .ok_or(AuthError::InvalidFormat)?;
+    if let Some(param) = &token_sources.query_param
+        && let Ok(Query(mut query)) = Query::<HashMap<String, String>>::try_from_uri(req.uri())
+        && let Some(token) = query.remove(param)
+    {
+        return Ok(token);
+    }
+
+    Err(AuthError::MissingToken)
+}
 
-    validate_api_key(token, credentials).ok_or(AuthError::InvalidToken)
+/// The API key from `Authorization: Bearer` or the configured header, `None`
+/// when neither is present
+fn token_from_headers(
+    headers: &HeaderMap,
+    token_sources: &TokenSources,
+) -> Option<Result<String, AuthError>> {
+    if let Some(auth_header) = headers.get("authorization").and_then(|v| v.to_str().ok()) {
+        return Some(
+            auth_header
+                .strip_prefix("Bearer ")
+                .map(str::to_string)
+                .ok_or(AuthError::InvalidFormat),
+        );
+    }
+
+    token_sources
+        .header
+        .as_ref()
+        .and_then(|name| headers.get(name))
+        .and_then(|v| v.to_str().ok())
+        .map(|token| Ok(token.to_string()))
 }
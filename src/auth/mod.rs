@@ -1,17 +1,26 @@
+mod cache;
 mod error;
 mod loader;
 pub mod middleware; // Make public for testing
+mod provider;
+pub mod templates;
 mod types;
 
 // Re-export public types
 pub use types::{
-    AuthenticatedUser, CredentialsConfig, CredentialsStore, UserConfig, UserCredentials,
-    validate_api_key,
+    AuthenticatedUser, Budget, CredentialsConfig, CredentialsStore, ToolArguments, UserConfig,
+    UserCredentials, validate_api_key,
 };
 
+// Secret and URL types returned by the typed external key accessors
+pub use secrecy::{ExposeSecret, SecretString};
+pub use url::Url;
+
 // Re-export middleware types
-pub use middleware::AuthLayer;
+pub use cache::AuthCache;
+pub use middleware::{AuthLayer, TokenSources};
+pub use provider::{AuthFuture, AuthProvider, Authenticator, StaticAuthProvider};
 pub use error::AuthError; // Re-export for testing
 
 // Re-export loader
-pub use loader::{get_credentials_path, load_credentials};
+pub use loader::{CredentialsFormat, get_credentials_path, is_age_encrypted, load_credentials};
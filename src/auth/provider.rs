@@ -0,0 +1,82 @@
+use super::cache::AuthCache;
+use super::types::{CredentialsStore, UserCredentials, validate_api_key};
+use anyhow::Result;
+use arc_swap::ArcSwap;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+pub type AuthFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+/// Source of truth for API key validation
+///
+/// Implement this to back authentication with a database, JWKS endpoint or
+/// secrets manager instead of the credentials file.
+pub trait AuthProvider: Send + Sync {
+    /// Resolve a bearer token to a user; `Ok(None)` means the token is invalid
+    fn authenticate<'a>(&'a self, token: &'a str) -> AuthFuture<'a, Option<UserCredentials>>;
+}
+
+/// Provider backed by the credentials loaded from file, replaceable on reload
+///
+/// Lookups read the current snapshot without locking; a reload swaps in the
+/// complete new store atomically.
+pub struct StaticAuthProvider {
+    credentials: ArcSwap<HashMap<String, UserCredentials>>,
+}
+
+impl StaticAuthProvider {
+    pub fn new(credentials: CredentialsStore) -> Self {
+        Self {
+            credentials: ArcSwap::new(credentials),
+        }
+    }
+
+    /// Swap in a freshly loaded credentials store
+    pub fn replace(&self, credentials: CredentialsStore) {
+        self.credentials.store(credentials);
+    }
+
+    /// Snapshot of the current credentials store
+    pub fn credentials(&self) -> CredentialsStore {
+        self.credentials.load_full()
+    }
+}
+
+impl AuthProvider for StaticAuthProvider {
+    fn authenticate<'a>(&'a self, token: &'a str) -> AuthFuture<'a, Option<UserCredentials>> {
+        let user = validate_api_key(token, &self.credentials.load());
+        Box::pin(async move { Ok(user) })
+    }
+}
+
+/// Auth provider fronted by a TTL-bounded cache of successful lookups
+#[derive(Clone)]
+pub struct Authenticator {
+    provider: Arc<dyn AuthProvider>,
+    cache: Arc<AuthCache>,
+}
+
+impl Authenticator {
+    pub fn new(provider: Arc<dyn AuthProvider>, cache: Arc<AuthCache>) -> Self {
+        Self { provider, cache }
+    }
+
+    /// Resolve a token, consulting the cache before the provider
+    pub async fn authenticate(&self, token: &str) -> Result<Option<UserCredentials>> {
+        if let Some(user) = self.cache.get(token) {
+            return Ok(Some(user));
+        }
+
+        let user = self.provider.authenticate(token).await?;
+        if let Some(user) = &user {
+            self.cache.insert(token, user.clone());
+        }
+        Ok(user)
+    }
+
+    pub fn cache(&self) -> &Arc<AuthCache> {
+        &self.cache
+    }
+}
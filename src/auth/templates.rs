@@ -0,0 +1,110 @@
+//! `{{...}}` placeholders in configured tool arguments, filled in from the
+//! invoking user's credentials so clients never have to send them
+//!
+//! Available variables: `username`, `tenant` and `external_keys.<name>`.
+//! Only values from the credentials file are rendered, never what the client
+//! sent, so a client cannot read a secret by sending a placeholder.
+
+use super::types::UserCredentials;
+use serde_json::Value;
+use std::fmt;
+
+/// Why a placeholder could not be filled in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateError {
+    /// `{{external_keys.<name>}}` for a key the user does not have
+    MissingExternalKey(String),
+    /// `{{tenant}}` for a user without a tenant
+    NoTenant,
+    UnknownVariable(String),
+    /// `{{` without a matching `}}`
+    Unterminated,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingExternalKey(key) => write!(f, "user missing credential '{}'", key),
+            Self::NoTenant => write!(f, "user has no tenant"),
+            Self::UnknownVariable(name) => write!(f, "unknown template variable '{}'", name),
+            Self::Unterminated => write!(f, "unterminated '{{{{' in template"),
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+/// Render every string inside `value`, recursing into arrays and objects
+pub fn render_value(value: &Value, user: &UserCredentials) -> Result<Value, TemplateError> {
+    Ok(match value {
+        Value::String(text) => Value::String(render(text, user)?),
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| render_value(item, user))
+                .collect::<Result<_, _>>()?,
+        ),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, item)| Ok((key.clone(), render_value(item, user)?)))
+                .collect::<Result<_, _>>()?,
+        ),
+        other => other.clone(),
+    })
+}
+
+/// Replace the placeholders of a string
+pub fn render(text: &str, user: &UserCredentials) -> Result<String, TemplateError> {
+    expand(text, |variable| lookup(variable, user))
+}
+
+/// Check the placeholders of `value` without a user: syntax and variable
+/// names, but not whether a given user has the keys they name
+pub fn check_value(value: &Value) -> Result<(), TemplateError> {
+    match value {
+        Value::String(text) => expand(text, |variable| {
+            match variable {
+                "username" | "tenant" => {}
+                other if other.strip_prefix("external_keys.").is_some() => {}
+                other => return Err(TemplateError::UnknownVariable(other.to_string())),
+            }
+            Ok(String::new())
+        })
+        .map(drop),
+        Value::Array(items) => items.iter().try_for_each(check_value),
+        Value::Object(map) => map.values().try_for_each(check_value),
+        _ => Ok(()),
+    }
+}
+
+pub(crate) fn lookup(variable: &str, user: &UserCredentials) -> Result<String, TemplateError> {
+    match variable {
+        "username" => Ok(user.username.clone()),
+        "tenant" => user.tenant.clone().ok_or(TemplateError::NoTenant),
+        other => match other.strip_prefix("external_keys.") {
+            Some(key) => user
+                .get_external_key(key)
+                .cloned()
+                .ok_or_else(|| TemplateError::MissingExternalKey(key.to_string())),
+            None => Err(TemplateError::UnknownVariable(other.to_string())),
+        },
+    }
+}
+
+/// Replace each `{{ variable }}` with `resolve(variable)`
+pub(crate) fn expand(
+    text: &str,
+    mut resolve: impl FnMut(&str) -> Result<String, TemplateError>,
+) -> Result<String, TemplateError> {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after.find("}}").ok_or(TemplateError::Unterminated)?;
+        output.push_str(&resolve(after[..end].trim())?);
+        rest = &after[end + 2..];
+    }
+    output.push_str(rest);
+    Ok(output)
+}
@@ -1,11 +1,22 @@
+use super::templates::{self, TemplateError};
+use anyhow::{Result, anyhow};
+use secrecy::SecretString;
 use serde::Deserialize;
+use serde_json::{Map, Value};
 use std::collections::HashMap;
+use std::convert::Infallible;
+use std::fmt;
+use std::str::FromStr;
 use std::sync::Arc;
+use url::Url;
 
 /// TOML configuration structure for credentials file
 /// Uses flatten to map username keys directly to UserConfig
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 pub struct CredentialsConfig {
+    /// Additional credentials files or directories to merge, relative to this file
+    #[serde(default)]
+    pub include: Vec<String>,
     #[serde(flatten)]
     pub users: HashMap<String, UserConfig>,
 }
@@ -14,34 +25,213 @@ pub struct CredentialsConfig {
 #[derive(Debug, Deserialize)]
 pub struct UserConfig {
     pub api_key: String,
+    /// Grants access to the `/admin/*` endpoints
+    #[serde(default)]
+    pub admin: bool,
+    /// Default and maximum scheduling priority for this user's invocations
+    #[serde(default)]
+    pub priority: i32,
+    /// Tenant whose tool set and configuration this user sees
+    #[serde(default)]
+    pub tenant: Option<String>,
+    /// Roles matched by authorization policies
+    #[serde(default)]
+    pub roles: Vec<String>,
+    /// Secret for HMAC request body signatures (`X-Signature`)
+    #[serde(default)]
+    pub signing_secret: Option<String>,
     #[serde(default)]
     pub external_keys: HashMap<String, String>,
+    /// Per-tool argument defaults and forced values, keyed by tool name
+    #[serde(default)]
+    pub tool_arguments: HashMap<String, ToolArguments>,
+    /// Limit on the summed cost of this user's invocations
+    #[serde(default)]
+    pub budget: Option<Budget>,
+}
+
+/// Cost units a user may spend per window, see `budget::Budgets`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Budget {
+    pub limit: u64,
+    /// Length of the window; spending is reset at every multiple of it
+    #[serde(default = "default_budget_window_secs")]
+    pub window_secs: u64,
+}
+
+fn default_budget_window_secs() -> u64 {
+    86_400
+}
+
+/// Arguments merged into a user's invocations of one tool before validation
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ToolArguments {
+    /// Used when the client leaves the argument out
+    #[serde(default)]
+    pub defaults: Map<String, Value>,
+    /// Always used, replacing whatever the client sent
+    #[serde(default)]
+    pub forced: Map<String, Value>,
+}
+
+impl ToolArguments {
+    /// Merge into invocation arguments; missing arguments become an object,
+    /// non-object arguments are left for validation to reject
+    pub fn apply(&self, arguments: &mut Option<Value>) {
+        let merged: Result<(), Infallible> = self.merge(arguments, |value| Ok(value.clone()));
+        let Ok(()) = merged;
+    }
+
+    /// Merge like `apply`, filling in the `{{...}}` placeholders of the values
+    /// used from `user`'s credentials (see `auth::templates`)
+    pub fn apply_for_user(
+        &self,
+        user: &UserCredentials,
+        arguments: &mut Option<Value>,
+    ) -> Result<(), TemplateError> {
+        self.merge(arguments, |value| templates::render_value(value, user))
+    }
+
+    /// Check the placeholders of every value
+    pub fn check_templates(&self) -> Result<(), TemplateError> {
+        self.defaults
+            .values()
+            .chain(self.forced.values())
+            .try_for_each(templates::check_value)
+    }
+
+    fn merge<E>(
+        &self,
+        arguments: &mut Option<Value>,
+        mut render: impl FnMut(&Value) -> Result<Value, E>,
+    ) -> Result<(), E> {
+        if self.defaults.is_empty() && self.forced.is_empty() {
+            return Ok(());
+        }
+        let Some(args) = arguments
+            .get_or_insert_with(|| Value::Object(Map::new()))
+            .as_object_mut()
+        else {
+            return Ok(());
+        };
+        for (name, value) in &self.defaults {
+            if !args.contains_key(name) {
+                args.insert(name.clone(), render(value)?);
+            }
+        }
+        for (name, value) in &self.forced {
+            args.insert(name.clone(), render(value)?);
+        }
+        Ok(())
+    }
 }
 
 /// Runtime user credentials with username, API key, and external service keys
-#[derive(Debug, Clone)]
+///
+/// `Debug` output redacts the API key, signing secret and external key values.
+#[derive(Clone)]
 pub struct UserCredentials {
     pub username: String,
     pub api_key: String,
+    pub admin: bool,
+    pub priority: i32,
+    pub tenant: Option<String>,
+    pub roles: Vec<String>,
+    pub signing_secret: Option<String>,
     pub external_keys: HashMap<String, String>,
+    pub tool_arguments: HashMap<String, ToolArguments>,
+    pub budget: Option<Budget>,
 }
 
 impl UserCredentials {
-    /// Create new UserCredentials
+    /// Create new (non-admin) UserCredentials
     pub fn new(username: String, api_key: String, external_keys: HashMap<String, String>) -> Self {
         Self {
             username,
             api_key,
+            admin: false,
+            priority: 0,
+            tenant: None,
+            roles: Vec::new(),
+            signing_secret: None,
             external_keys,
+            tool_arguments: HashMap::new(),
+            budget: None,
         }
     }
 
+    /// Set whether this user has admin privileges
+    pub fn with_admin(mut self, admin: bool) -> Self {
+        self.admin = admin;
+        self
+    }
+
+    /// Set the user's default (and maximum) invocation priority
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Assign the user to a tenant
+    pub fn with_tenant(mut self, tenant: Option<String>) -> Self {
+        self.tenant = tenant;
+        self
+    }
+
+    /// Set the roles authorization policies match the user by
+    pub fn with_roles(mut self, roles: Vec<String>) -> Self {
+        self.roles = roles;
+        self
+    }
+
+    /// Require this user's request bodies to be signed with the secret
+    pub fn with_signing_secret(mut self, secret: Option<String>) -> Self {
+        self.signing_secret = secret;
+        self
+    }
+
+    /// Set the user's per-tool argument defaults and forced values
+    pub fn with_tool_arguments(mut self, tool_arguments: HashMap<String, ToolArguments>) -> Self {
+        self.tool_arguments = tool_arguments;
+        self
+    }
+
+    /// Limit the summed cost of the user's invocations
+    pub fn with_budget(mut self, budget: Option<Budget>) -> Self {
+        self.budget = budget;
+        self
+    }
+
     /// Get an external service key (e.g., "postgres_url", "stripe_key")
     pub fn get_external_key(&self, key: &str) -> Option<&String> {
         self.external_keys.get(key)
     }
 }
 
+impl fmt::Debug for UserCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut external_keys: Vec<&str> = self.external_keys.keys().map(String::as_str).collect();
+        external_keys.sort_unstable();
+        f.debug_struct("UserCredentials")
+            .field("username", &self.username)
+            .field("api_key", &"[REDACTED]")
+            .field("admin", &self.admin)
+            .field("priority", &self.priority)
+            .field("tenant", &self.tenant)
+            .field("roles", &self.roles)
+            .field(
+                "signing_secret",
+                &self.signing_secret.as_ref().map(|_| "[REDACTED]"),
+            )
+            .field("external_keys", &external_keys)
+            .field("tool_arguments", &self.tool_arguments)
+            .field("budget", &self.budget)
+            .finish()
+    }
+}
+
 /// Credentials store indexed by API key for O(1) lookups
 /// HashMap<api_key, UserCredentials>
 pub type CredentialsStoreInner = HashMap<String, UserCredentials>;
@@ -64,6 +254,56 @@ impl AuthenticatedUser {
     pub fn get_external_key(&self, key: &str) -> Option<&String> {
         self.0.get_external_key(key)
     }
+
+    /// Get an external key that must be present
+    fn require_external_key(&self, key: &str) -> Result<&String> {
+        self.get_external_key(key)
+            .ok_or_else(|| anyhow!("User missing credential '{}'", key))
+    }
+
+    /// Parse an external key, e.g. `get_external_key_parsed::<u16>("smtp_port")`
+    /// The error names the key but never includes its value
+    pub fn get_external_key_parsed<T>(&self, key: &str) -> Result<T>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        self.require_external_key(key)?
+            .parse()
+            .map_err(|e| anyhow!("Invalid credential '{}': {}", key, e))
+    }
+
+    /// External key holding a URL
+    pub fn external_url(&self, key: &str) -> Result<Url> {
+        self.get_external_key_parsed(key)
+    }
+
+    /// The `postgres_url` external key
+    pub fn postgres_url(&self) -> Result<Url> {
+        self.external_url("postgres_url")
+    }
+
+    /// External key wrapped so it is redacted from `Debug` output and zeroed on drop
+    /// Read it with `secrecy::ExposeSecret::expose_secret`
+    pub fn external_secret(&self, key: &str) -> Result<SecretString> {
+        self.require_external_key(key)
+            .map(|value| SecretString::from(value.as_str()))
+    }
+
+    /// Tenant the user belongs to, if any
+    pub fn tenant(&self) -> Option<&str> {
+        self.0.tenant.as_deref()
+    }
+
+    /// Roles the user holds
+    pub fn roles(&self) -> &[String] {
+        &self.0.roles
+    }
+
+    /// Whether the user has admin privileges
+    pub fn is_admin(&self) -> bool {
+        self.0.admin
+    }
 }
 
 /// Validate API key against credentials store
@@ -0,0 +1,117 @@
+//! Per-user cost budgets
+//!
+//! Tools declare how many cost units an invocation consumes (`McpTool::cost`,
+//! 1 by default). Users with a `budget` in the credentials file may spend at
+//! most `limit` units per window; windows are aligned to multiples of
+//! `window_secs` since the Unix epoch. Spending is kept in the shared store so
+//! replicas enforce one budget.
+
+use crate::auth::Budget;
+use crate::store::SharedStore;
+use anyhow::Result;
+use serde::Serialize;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Cost of tools that do not declare one
+pub const DEFAULT_TOOL_COST: u64 = 1;
+
+/// A user's spending in the current window
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Usage {
+    pub limit: u64,
+    pub used: u64,
+    pub remaining: u64,
+    pub window_secs: u64,
+    /// Seconds until spending is reset
+    pub resets_in_secs: u64,
+}
+
+/// Tracks what users spent in their current budget window
+#[derive(Clone, Default)]
+pub struct Budgets {
+    store: SharedStore,
+}
+
+impl Budgets {
+    pub fn new(store: SharedStore) -> Self {
+        Self { store }
+    }
+
+    /// Spend `cost` units of `budget`, or return the unchanged usage when
+    /// they are not left
+    pub async fn charge(
+        &self,
+        username: &str,
+        budget: &Budget,
+        cost: u64,
+    ) -> Result<Result<Usage, Usage>> {
+        let window = Window::current(budget);
+        let key = window.key(username);
+        let amount = i64::try_from(cost)?;
+        let used = self
+            .store
+            .increment_by(&key, amount, window.duration())
+            .await?;
+        if used > budget.limit as i64 {
+            // Give the units back so a refused invocation costs nothing
+            let used = self
+                .store
+                .increment_by(&key, -amount, window.duration())
+                .await?;
+            return Ok(Err(window.usage(budget, used)));
+        }
+        Ok(Ok(window.usage(budget, used)))
+    }
+
+    /// What `username` has spent of `budget` in the current window
+    pub async fn usage(&self, username: &str, budget: &Budget) -> Result<Usage> {
+        let window = Window::current(budget);
+        let used = match self.store.get(&window.key(username)).await? {
+            Some(value) => value.parse()?,
+            None => 0,
+        };
+        Ok(window.usage(budget, used))
+    }
+}
+
+/// The budget window containing the current time
+struct Window {
+    index: u64,
+    length: u64,
+    elapsed: u64,
+}
+
+impl Window {
+    fn current(budget: &Budget) -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let length = budget.window_secs.max(1);
+        Self {
+            index: now / length,
+            length,
+            elapsed: now % length,
+        }
+    }
+
+    fn key(&self, username: &str) -> String {
+        format!("budget:{}:{}:{}", username, self.length, self.index)
+    }
+
+    /// Expiry of the window's counter
+    fn duration(&self) -> Duration {
+        Duration::from_secs(self.length - self.elapsed)
+    }
+
+    fn usage(&self, budget: &Budget, used: i64) -> Usage {
+        let used = used.max(0) as u64;
+        Usage {
+            limit: budget.limit,
+            used,
+            remaining: budget.limit.saturating_sub(used),
+            window_secs: self.length,
+            resets_in_secs: self.length - self.elapsed,
+        }
+    }
+}
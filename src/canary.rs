@@ -0,0 +1,86 @@
+use crate::config::CanaryConfig;
+use crate::tools::ToolFunction;
+use anyhow::{Result, bail};
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// Which implementation served an invocation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    Stable,
+    Canary,
+}
+
+impl Variant {
+    /// Label used in metrics
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Variant::Stable => "stable",
+            Variant::Canary => "canary",
+        }
+    }
+}
+
+/// Routes a share of each tool's invocations to a canary implementation
+#[derive(Debug, Clone, Default)]
+pub struct CanaryRouter {
+    routes: HashMap<String, CanaryConfig>,
+}
+
+impl CanaryRouter {
+    /// Validate the canary routes against the registered tools
+    pub fn new(
+        routes: HashMap<String, CanaryConfig>,
+        registry: &HashMap<String, ToolFunction>,
+    ) -> Result<Self> {
+        for (tool_name, route) in &routes {
+            if !registry.contains_key(tool_name) {
+                bail!("Canary configured for unknown tool '{}'", tool_name);
+            }
+            if !registry.contains_key(&route.variant) {
+                bail!(
+                    "Canary variant '{}' for tool '{}' is not a registered tool",
+                    route.variant,
+                    tool_name
+                );
+            }
+            if route.variant == *tool_name {
+                bail!(
+                    "Canary variant for tool '{}' must be a different tool",
+                    tool_name
+                );
+            }
+            if !(0.0..=100.0).contains(&route.percent) {
+                bail!(
+                    "Canary percent for tool '{}' must be between 0 and 100",
+                    tool_name
+                );
+            }
+        }
+
+        Ok(Self { routes })
+    }
+
+    /// Pick the implementation for an invocation, returning the tool name to execute
+    ///
+    /// Sticky routes hash the username so a user consistently sees the same variant.
+    pub fn select<'a>(&'a self, tool_name: &'a str, username: &str) -> (&'a str, Variant) {
+        let Some(route) = self.routes.get(tool_name) else {
+            return (tool_name, Variant::Stable);
+        };
+
+        let roll = if route.sticky {
+            let mut hasher = DefaultHasher::new();
+            (tool_name, username).hash(&mut hasher);
+            (hasher.finish() % 10_000) as f64 / 100.0
+        } else {
+            rand::random_range(0.0..100.0)
+        };
+
+        if roll < route.percent {
+            (&route.variant, Variant::Canary)
+        } else {
+            (tool_name, Variant::Stable)
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use anyhow::{Context, Result};
+use axum::{
+    RequestExt,
+    extract::{ConnectInfo, Request, State},
+    http::HeaderMap,
+    middleware::Next,
+    response::Response,
+};
+use ipnet::IpNet;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+/// Address of the client behind a request, inserted into the request
+/// extensions by `resolve_client_ip`
+///
+/// Absent when the server was not started with connection info (see
+/// `Router::into_make_service_with_connect_info`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientIp(pub IpAddr);
+
+/// Proxies whose `Forwarded` / `X-Forwarded-For` headers are believed
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies {
+    networks: Vec<IpNet>,
+}
+
+impl TrustedProxies {
+    /// Parse addresses (`10.0.0.1`) and networks (`10.0.0.0/8`)
+    pub fn new(entries: &[String]) -> Result<Self> {
+        let networks = entries
+            .iter()
+            .map(|entry| {
+                entry
+                    .parse::<IpNet>()
+                    .or_else(|_| entry.parse::<IpAddr>().map(IpNet::from))
+                    .with_context(|| format!("Invalid trusted proxy '{}'", entry))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { networks })
+    }
+
+    pub fn is_trusted(&self, ip: IpAddr) -> bool {
+        let ip = ip.to_canonical();
+        self.networks.iter().any(|network| network.contains(&ip))
+    }
+
+    /// The real client address of a request received from `peer`
+    ///
+    /// Forwarding headers are only read when the peer is a trusted proxy.
+    /// Their hops are then walked from the nearest one, and the first address
+    /// that is not itself a trusted proxy is the client. An unparseable hop
+    /// stops the walk at the last address reached, so a client cannot hide
+    /// behind a forged entry.
+    pub fn client_ip(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        let mut client = peer.to_canonical();
+        if !self.is_trusted(client) {
+            return client;
+        }
+        for hop in forwarded_chain(headers).into_iter().rev() {
+            match hop {
+                Some(ip) => {
+                    client = ip.to_canonical();
+                    if !self.is_trusted(client) {
+                        break;
+                    }
+                }
+                None => break,
+            }
+        }
+        client
+    }
+}
+
+/// Hops of the `Forwarded` header, or `X-Forwarded-For` without one, from the
+/// original client to the nearest proxy; `None` for hops that are not an IP
+/// (`unknown`, obfuscated identifiers)
+pub fn forwarded_chain(headers: &HeaderMap) -> Vec<Option<IpAddr>> {
+    let values = |name: &str| {
+        headers
+            .get_all(name)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .map(str::trim)
+            .filter(|hop| !hop.is_empty())
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    };
+
+    let forwarded = values("forwarded");
+    if !forwarded.is_empty() {
+        return forwarded
+            .iter()
+            .map(|element| {
+                element
+                    .split(';')
+                    .filter_map(|pair| pair.split_once('='))
+                    .find(|(key, _)| key.trim().eq_ignore_ascii_case("for"))
+                    .and_then(|(_, node)| parse_node(node.trim().trim_matches('"')))
+            })
+            .collect();
+    }
+
+    values("x-forwarded-for")
+        .iter()
+        .map(|hop| parse_node(hop))
+        .collect()
+}
+
+/// `192.0.2.1`, `192.0.2.1:443`, `2001:db8::1` or `[2001:db8::1]:443`
+fn parse_node(node: &str) -> Option<IpAddr> {
+    if let Ok(ip) = node.parse() {
+        return Some(ip);
+    }
+    if let Some(rest) = node.strip_prefix('[') {
+        return rest.split(']').next()?.parse().ok();
+    }
+    node.parse::<SocketAddr>().ok().map(|addr| addr.ip())
+}
+
+/// Middleware resolving the `ClientIp` of requests from the connection's peer
+/// address and, behind trusted proxies, the forwarding headers
+pub async fn resolve_client_ip(
+    State(proxies): State<Arc<TrustedProxies>>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    if let Ok(ConnectInfo(peer)) = req.extract_parts::<ConnectInfo<SocketAddr>>().await {
+        let ip = proxies.client_ip(peer.ip(), req.headers());
+        req.extensions_mut().insert(ClientIp(ip));
+    }
+    next.run(req).await
+}
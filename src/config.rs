@@ -0,0 +1,1220 @@
+use crate::logging::LogLevel;
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Default location of the server configuration file
+const DEFAULT_CONFIG_PATH: &str = "config/server.toml";
+
+/// Address the HTTP endpoint listens on when no listener is configured
+const DEFAULT_BIND: &str = "0.0.0.0:3000";
+
+/// Server configuration loaded from TOML
+/// Every section is optional; a missing file yields the defaults
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    /// Guidance on using the toolset as a whole, returned by `initialize`
+    /// and `discover` for clients to pass on to the model
+    pub instructions: Option<String>,
+    /// String argument sanitization applied before tool execution
+    pub sanitization: SanitizationConfig,
+    /// Recent invocation history exposed via `history/list`
+    pub history: HistoryConfig,
+    /// Concurrency limit and priority queue for tool invocations
+    pub scheduling: SchedulingConfig,
+    /// Tenant-specific tool sets and configuration keyed by tenant name
+    pub tenants: HashMap<String, TenantConfig>,
+    /// Backend for state shared between replicas
+    pub store: StoreConfig,
+    /// Gradual rollouts keyed by the tool name clients invoke
+    pub canary: HashMap<String, CanaryConfig>,
+    /// Server-requested user input during streamed invocations
+    pub elicitation: ElicitationConfig,
+    /// JSON-RPC envelope handling
+    pub jsonrpc: JsonRpcConfig,
+    /// Authentication settings
+    pub auth: AuthConfig,
+    /// Client sessions opened with `initialize`
+    pub sessions: SessionConfig,
+    /// Handling of large tool results
+    pub results: ResultsConfig,
+    /// Files uploaded through `POST /uploads`
+    pub uploads: UploadsConfig,
+    /// Signed timestamp and nonce required on every authenticated request
+    pub replay: ReplayConfig,
+    /// HMAC signatures over request bodies
+    pub signing: SigningConfig,
+    /// Graceful draining through `POST /admin/drain`
+    pub drain: DrainConfig,
+    /// Declarative tools loaded from a watched directory
+    pub plugins: PluginsConfig,
+    /// Replay of results for invocations retried with the same `idempotency_key`
+    pub idempotency: IdempotencyConfig,
+    /// Tool registration
+    pub tools: ToolsConfig,
+    /// Refusal of state-changing tools, also toggled through `POST /admin/read-only`
+    pub read_only: ReadOnlyConfig,
+    /// Tool health checks
+    pub health: HealthConfig,
+    /// `web_search` tool provider and limits
+    pub web_search: WebSearchConfig,
+    /// `send_email` tool recipients and limits
+    pub email: EmailConfig,
+    /// `storage_*` tools settings
+    pub storage: StorageConfig,
+    /// `git_*` tools workspaces and limits
+    pub git: GitConfig,
+    /// `cache_get`/`cache_set` tools (`redis` feature) namespacing and limits
+    pub cache: CacheConfig,
+    /// `vector_upsert`/`vector_query` tools collection and embeddings
+    pub vectors: VectorsConfig,
+    /// `k8s_*` tools (`k8s` feature) limits
+    pub k8s: K8sConfig,
+    /// `docker_*` tools daemon, limits and allowed mutations
+    pub docker: DockerConfig,
+    /// `get_weather` tool endpoint and response cache
+    pub weather: WeatherConfig,
+    /// gRPC service (`grpc` feature) exposing discover and invoke
+    pub grpc: GrpcConfig,
+    /// Size limit and decompression of `POST /mcp` bodies
+    pub request_body: RequestBodyConfig,
+    /// Resolution of the client address behind reverse proxies
+    pub client_ip: ClientIpConfig,
+    /// Aborting invocations whose client disconnected
+    pub cancellation: CancellationConfig,
+    /// Role-based tool authorization
+    pub policies: PoliciesConfig,
+    /// Log notifications sent to clients
+    pub logging: LoggingConfig,
+    /// Invocation time limits
+    pub timeouts: TimeoutsConfig,
+    /// Outbound HTTP client shared by tools
+    pub http: HttpConfig,
+    /// Results of repeated identical invocations and HTTP `ETag`s
+    pub response_cache: ResponseCacheConfig,
+    /// Per-user, per-tool usage reported by `GET /admin/usage`
+    pub usage: UsageConfig,
+    /// Deprecated tools keyed by tool name, warned about on every invocation
+    pub deprecations: HashMap<String, DeprecationConfig>,
+    /// Embedded SQLite database shared by persistent server state
+    pub database: DatabaseConfig,
+    /// Listeners serving the HTTP endpoint; `0.0.0.0:3000` when none is set
+    pub listeners: Vec<ListenerConfig>,
+}
+
+impl ServerConfig {
+    /// The configured listeners, or the default one
+    pub fn listeners(&self) -> Vec<ListenerConfig> {
+        if self.listeners.is_empty() {
+            vec![ListenerConfig {
+                bind: Some(DEFAULT_BIND.to_string()),
+                ..Default::default()
+            }]
+        } else {
+            self.listeners.clone()
+        }
+    }
+}
+
+/// HTTP listener settings (`[[listeners]]`), either a TCP address or a Unix
+/// domain socket
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ListenerConfig {
+    /// TCP address to listen on, e.g. `127.0.0.1:3000`
+    pub bind: Option<String>,
+    /// Unix domain socket to listen on instead of `bind`
+    pub socket_path: Option<String>,
+    /// Permissions of the socket file, e.g. `0o660`
+    pub socket_mode: Option<u32>,
+    /// Serve HTTPS on `bind` with this certificate
+    pub tls: Option<TlsConfig>,
+}
+
+/// Certificate of an HTTPS listener
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TlsConfig {
+    /// PEM file with the certificate chain, leaf first
+    pub cert_path: String,
+    /// PEM file with the private key (PKCS#8, PKCS#1 or SEC1)
+    pub key_path: String,
+}
+
+/// Shared database settings
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    /// SQLite database file (`sqlite` feature), migrated on startup; state
+    /// is kept in memory or where each subsystem is configured when unset
+    pub path: Option<String>,
+}
+
+/// Client address settings
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClientIpConfig {
+    /// Addresses or networks (`10.0.0.0/8`) of reverse proxies whose
+    /// `Forwarded` / `X-Forwarded-For` headers are believed. Empty uses the
+    /// connection's peer address as is
+    pub trusted_proxies: Vec<String>,
+}
+
+/// Authorization policy settings
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PoliciesConfig {
+    /// Policies TOML granting or denying roles access to tools; every
+    /// authenticated user may invoke every tool they can see when unset
+    pub path: Option<PathBuf>,
+    /// Open Policy Agent consulted after the policies file
+    pub opa: OpaConfig,
+}
+
+/// Open Policy Agent settings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OpaConfig {
+    /// Decision endpoint, e.g. `http://localhost:8181/v1/data/mcp/allow`;
+    /// OPA is not consulted when unset
+    pub url: Option<String>,
+    pub timeout_ms: u64,
+    /// Allow invocations when OPA cannot be reached or answers an error,
+    /// instead of refusing them
+    pub fail_open: bool,
+}
+
+impl Default for OpaConfig {
+    fn default() -> Self {
+        Self {
+            url: None,
+            timeout_ms: 2000,
+            fail_open: false,
+        }
+    }
+}
+
+/// Client disconnect settings with per-tool overrides
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CancellationConfig {
+    /// Abort a tool when the client that invoked it disconnects, instead of
+    /// running it to completion and discarding the result
+    pub abort_on_disconnect: bool,
+    /// Per-tool overrides keyed by tool name, e.g. to let non-idempotent
+    /// tools finish what they started
+    pub tools: HashMap<String, ToolCancellationConfig>,
+}
+
+impl Default for CancellationConfig {
+    fn default() -> Self {
+        Self {
+            abort_on_disconnect: true,
+            tools: HashMap::new(),
+        }
+    }
+}
+
+impl CancellationConfig {
+    /// Whether `tool_name` is aborted when its client disconnects
+    pub fn aborts(&self, tool_name: &str) -> bool {
+        self.tools
+            .get(tool_name)
+            .and_then(|tool| tool.abort_on_disconnect)
+            .unwrap_or(self.abort_on_disconnect)
+    }
+}
+
+/// Per-tool disconnect overrides, unset fields inherit the global setting
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ToolCancellationConfig {
+    pub abort_on_disconnect: Option<bool>,
+}
+
+/// `POST /mcp` body settings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RequestBodyConfig {
+    /// Decompress bodies sent with `Content-Encoding: gzip` or `deflate`
+    pub decompress: bool,
+    /// Largest body accepted, measured after decompression
+    pub max_bytes: usize,
+    /// Deepest nesting of arrays and objects in a body or in gRPC
+    /// `arguments_json` (0 = unlimited)
+    pub max_depth: usize,
+    /// Most keys in any one object of a body or of gRPC `arguments_json`
+    /// (0 = unlimited)
+    pub max_object_keys: usize,
+}
+
+impl Default for RequestBodyConfig {
+    fn default() -> Self {
+        Self {
+            decompress: true,
+            max_bytes: 2 * 1024 * 1024,
+            max_depth: 64,
+            max_object_keys: 1024,
+        }
+    }
+}
+
+/// gRPC transport settings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GrpcConfig {
+    /// Serve the gRPC service next to the HTTP endpoint
+    pub enabled: bool,
+    /// Address the gRPC server listens on
+    pub bind: String,
+    /// Largest request or response message accepted
+    pub max_message_bytes: usize,
+}
+
+impl Default for GrpcConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind: "0.0.0.0:50051".to_string(),
+            max_message_bytes: 4 * 1024 * 1024,
+        }
+    }
+}
+
+/// Weather tool settings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WeatherConfig {
+    /// Override of the OpenWeatherMap current weather endpoint
+    pub endpoint: Option<String>,
+    /// How long a location's conditions are served from the cache; 0
+    /// disables caching
+    pub cache_ttl_secs: u64,
+    /// Locations kept in the cache
+    pub cache_max_entries: usize,
+    pub timeout_ms: u64,
+}
+
+impl Default for WeatherConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: None,
+            cache_ttl_secs: 600,
+            cache_max_entries: 1000,
+            timeout_ms: 10_000,
+        }
+    }
+}
+
+/// Docker tools settings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DockerConfig {
+    /// Daemon address: `unix:///var/run/docker.sock` or `tcp://host:2375`;
+    /// the docker tools are disabled while unset
+    pub host: Option<String>,
+    /// Operations `docker_container_action` may perform. Empty refuses every
+    /// mutation
+    pub allowed_operations: Vec<DockerOperation>,
+    /// Containers returned by a single `docker_list_containers`
+    pub max_containers: u32,
+    /// Log lines returned when the caller does not pass `tail_lines`
+    pub default_tail_lines: u32,
+    /// Upper bound of the `tail_lines` argument
+    pub max_tail_lines: u32,
+    /// Logs beyond this size are truncated
+    pub max_log_bytes: usize,
+    /// Limit of a single Docker API request
+    pub timeout_secs: u64,
+}
+
+impl Default for DockerConfig {
+    fn default() -> Self {
+        Self {
+            host: None,
+            allowed_operations: Vec::new(),
+            max_containers: 200,
+            default_tail_lines: 100,
+            max_tail_lines: 2000,
+            max_log_bytes: 256 * 1024,
+            timeout_secs: 30,
+        }
+    }
+}
+
+/// Container mutation performed by `docker_container_action`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DockerOperation {
+    Start,
+    Stop,
+    Restart,
+    Kill,
+    Pause,
+    Unpause,
+    Remove,
+}
+
+impl DockerOperation {
+    pub const ALL: [Self; 7] = [
+        Self::Start,
+        Self::Stop,
+        Self::Restart,
+        Self::Kill,
+        Self::Pause,
+        Self::Unpause,
+        Self::Remove,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Start => "start",
+            Self::Stop => "stop",
+            Self::Restart => "restart",
+            Self::Kill => "kill",
+            Self::Pause => "pause",
+            Self::Unpause => "unpause",
+            Self::Remove => "remove",
+        }
+    }
+}
+
+/// Kubernetes inspection tools settings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct K8sConfig {
+    /// Pods returned by a single `k8s_list_pods`
+    pub max_pods: u32,
+    /// Log lines returned when the caller does not pass `tail_lines`
+    pub default_tail_lines: u32,
+    /// Upper bound of the `tail_lines` argument
+    pub max_tail_lines: u32,
+    /// Logs beyond this size are cut off by the API server
+    pub max_log_bytes: usize,
+    /// Limit of a single Kubernetes API request
+    pub timeout_secs: u64,
+}
+
+impl Default for K8sConfig {
+    fn default() -> Self {
+        Self {
+            max_pods: 200,
+            default_tail_lines: 100,
+            max_tail_lines: 2000,
+            max_log_bytes: 256 * 1024,
+            timeout_secs: 30,
+        }
+    }
+}
+
+/// Redis cache tools settings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CacheConfig {
+    /// Keys are stored as `<key_prefix>:<username>:<key>`
+    pub key_prefix: String,
+    /// TTL of values stored without `ttl_secs`
+    pub default_ttl_secs: u64,
+    /// Longest TTL a caller may ask for; values never live forever
+    pub max_ttl_secs: u64,
+    pub max_value_bytes: usize,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            key_prefix: "mcp-cache".to_string(),
+            default_ttl_secs: 3600,
+            max_ttl_secs: 86400,
+            max_value_bytes: 1024 * 1024,
+        }
+    }
+}
+
+/// Git tools settings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GitConfig {
+    /// Directory holding a subdirectory of workspaces per user; the git tools
+    /// are disabled while unset
+    pub workspace_root: Option<PathBuf>,
+    /// URL prefixes `git_clone` accepts (e.g. `https://github.com/acme/`).
+    /// Empty refuses every remote
+    pub allowed_remotes: Vec<String>,
+    /// Limit of a single git command
+    pub timeout_secs: u64,
+    /// File contents and diffs beyond this size are truncated
+    pub max_output_bytes: usize,
+}
+
+impl Default for GitConfig {
+    fn default() -> Self {
+        Self {
+            workspace_root: None,
+            allowed_remotes: Vec::new(),
+            timeout_secs: 120,
+            max_output_bytes: 512 * 1024,
+        }
+    }
+}
+
+/// Object storage tools settings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StorageConfig {
+    /// Objects up to this size are returned inline by `storage_get`; larger
+    /// ones as a presigned URL
+    pub inline_max_bytes: u64,
+    /// Validity of presigned download URLs
+    pub presign_expiry_secs: u64,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            inline_max_bytes: 256 * 1024,
+            presign_expiry_secs: 900,
+        }
+    }
+}
+
+/// `send_email` tool settings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EmailConfig {
+    /// Addresses mail may be sent to: `alice@example.com`, `@example.com` for a
+    /// whole domain or `*` for anyone. Empty refuses every recipient
+    pub allowed_recipients: Vec<String>,
+    /// Messages a user may send per hour (0 = unlimited); dry runs don't count
+    pub max_per_hour: u64,
+    /// Only ever draft messages, whatever the caller asks
+    pub dry_run: bool,
+    pub timeout_ms: u64,
+}
+
+impl Default for EmailConfig {
+    fn default() -> Self {
+        Self {
+            allowed_recipients: Vec::new(),
+            max_per_hour: 20,
+            dry_run: false,
+            timeout_ms: 30_000,
+        }
+    }
+}
+
+/// `web_search` tool settings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WebSearchConfig {
+    pub provider: SearchProvider,
+    /// Override of the provider's API endpoint (e.g. a proxy or a mock)
+    pub endpoint: Option<String>,
+    /// Upper bound of the `count` argument
+    pub max_results: usize,
+    /// Results returned when the caller does not ask for a count
+    pub default_results: usize,
+    pub timeout_ms: u64,
+}
+
+impl Default for WebSearchConfig {
+    fn default() -> Self {
+        Self {
+            provider: SearchProvider::default(),
+            endpoint: None,
+            max_results: 20,
+            default_results: 5,
+            timeout_ms: 10_000,
+        }
+    }
+}
+
+/// Web search API used by the `web_search` tool
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchProvider {
+    #[default]
+    Brave,
+    #[serde(rename = "serpapi")]
+    SerpApi,
+    Bing,
+}
+
+/// Vector search tools settings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VectorsConfig {
+    /// Qdrant collection, created on the first upsert
+    pub collection: String,
+    /// Similarity metric of a newly created collection
+    pub distance: VectorDistance,
+    /// OpenAI-compatible `/v1/embeddings` URL used for `text` arguments;
+    /// without it callers must pass vectors
+    pub embedding_endpoint: Option<String>,
+    pub embedding_model: String,
+    /// Upper bound of the `limit` argument
+    pub max_results: usize,
+    /// Results returned when the caller does not ask for a limit
+    pub default_results: usize,
+    pub timeout_ms: u64,
+}
+
+impl Default for VectorsConfig {
+    fn default() -> Self {
+        Self {
+            collection: "mcp_vectors".to_string(),
+            distance: VectorDistance::default(),
+            embedding_endpoint: None,
+            embedding_model: "text-embedding-3-small".to_string(),
+            max_results: 50,
+            default_results: 5,
+            timeout_ms: 30_000,
+        }
+    }
+}
+
+/// Distance between vectors in a Qdrant collection
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VectorDistance {
+    #[default]
+    Cosine,
+    Dot,
+    Euclid,
+}
+
+impl VectorDistance {
+    /// Name in the Qdrant API
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Cosine => "Cosine",
+            Self::Dot => "Dot",
+            Self::Euclid => "Euclid",
+        }
+    }
+}
+
+/// Tool health check settings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HealthConfig {
+    /// Run tool health checks on every `/ready` probe and fail it while one is unhealthy
+    pub check_tools_on_ready: bool,
+    /// How long a single tool's check may take before it counts as unhealthy
+    pub timeout_ms: u64,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            check_tools_on_ready: false,
+            timeout_ms: 2000,
+        }
+    }
+}
+
+/// Read-only mode settings
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReadOnlyConfig {
+    /// Start in read-only mode
+    pub enabled: bool,
+}
+
+/// Tool registration settings
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ToolsConfig {
+    /// What to do when several registered tools share a name
+    pub duplicate_names: DuplicateToolNames,
+    /// Refuse credentials in which a user lacks an external key required by
+    /// one of their tools, instead of logging a warning
+    pub strict_external_keys: bool,
+    /// Alternative names invoking a tool, mapped to the tool's name;
+    /// registered tool names take precedence
+    pub aliases: HashMap<String, String>,
+}
+
+/// Resolution of tool name collisions at startup
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateToolNames {
+    /// Refuse to start, listing every duplicated name
+    #[default]
+    Error,
+    /// Register each colliding tool as `<module>_<name>`
+    PrefixModule,
+}
+
+/// Idempotency key settings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IdempotencyConfig {
+    /// How long a successful result is returned to retries (0 = until the store is cleared)
+    pub ttl_secs: u64,
+    /// How long a key stays locked when its invocation never finishes (e.g. a crash)
+    pub pending_ttl_secs: u64,
+}
+
+impl Default for IdempotencyConfig {
+    fn default() -> Self {
+        Self {
+            ttl_secs: 24 * 60 * 60,
+            pending_ttl_secs: 300,
+        }
+    }
+}
+
+/// Plugin directory settings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PluginsConfig {
+    /// Directory of `*.toml`/`*.yaml` tool manifests; plugins are disabled when unset
+    pub dir: Option<PathBuf>,
+    /// How often the directory is checked for added, changed or removed manifests
+    pub poll_interval_secs: u64,
+}
+
+impl Default for PluginsConfig {
+    fn default() -> Self {
+        Self {
+            dir: None,
+            poll_interval_secs: 5,
+        }
+    }
+}
+
+/// Drain settings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DrainConfig {
+    /// How long `POST /admin/drain` waits for in-flight invocations by default
+    pub timeout_secs: u64,
+}
+
+impl Default for DrainConfig {
+    fn default() -> Self {
+        Self { timeout_secs: 30 }
+    }
+}
+
+/// Request body signing settings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SigningConfig {
+    /// Reject users without a `signing_secret` instead of letting them skip signing
+    pub required: bool,
+    /// Largest body buffered for signature verification
+    pub max_body_bytes: usize,
+}
+
+impl Default for SigningConfig {
+    fn default() -> Self {
+        Self {
+            required: false,
+            max_body_bytes: 32 * 1024 * 1024,
+        }
+    }
+}
+
+/// Replay protection settings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReplayConfig {
+    pub enabled: bool,
+    /// Maximum difference between the request timestamp and the server clock
+    pub max_skew_secs: u64,
+}
+
+impl Default for ReplayConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_skew_secs: 300,
+        }
+    }
+}
+
+/// File upload settings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UploadsConfig {
+    /// Maximum size of an uploaded file (0 = unlimited)
+    pub max_bytes: usize,
+    /// How long an upload stays available to tools
+    pub ttl_secs: u64,
+}
+
+impl Default for UploadsConfig {
+    fn default() -> Self {
+        Self {
+            max_bytes: 25 * 1024 * 1024,
+            ttl_secs: 3600,
+        }
+    }
+}
+
+/// Tool result settings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ResultsConfig {
+    /// Results larger than this many bytes of JSON are stored as a resource and
+    /// returned as a resource link (0 = always inline)
+    pub offload_threshold_bytes: usize,
+    /// How long an offloaded result can be read back
+    pub offload_ttl_secs: u64,
+    /// Largest result, in bytes of JSON, returned to clients (0 = unlimited)
+    pub max_bytes: usize,
+    /// What happens to results larger than `max_bytes`
+    pub oversized: OversizedResult,
+    /// Maximum decoded size of a single binary content block (0 = unlimited)
+    pub max_binary_bytes: usize,
+    /// Add a `meta` object (duration, tool version, cache hit, request id) to
+    /// successful invocation responses
+    pub include_meta: bool,
+}
+
+impl Default for ResultsConfig {
+    fn default() -> Self {
+        Self {
+            offload_threshold_bytes: 0,
+            offload_ttl_secs: 3600,
+            max_bytes: 0,
+            oversized: OversizedResult::Error,
+            max_binary_bytes: 10 * 1024 * 1024,
+            include_meta: false,
+        }
+    }
+}
+
+/// Handling of results above `[results] max_bytes`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OversizedResult {
+    /// Fail the invocation with -32010
+    #[default]
+    Error,
+    /// Shorten strings and arrays until the result fits, marked `truncated: true`
+    Truncate,
+    /// Store the result as a resource and return a link to it
+    Offload,
+}
+
+/// Client session settings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SessionConfig {
+    /// How long an idle session is kept (0 = until the store is cleared)
+    pub ttl_secs: u64,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self { ttl_secs: 3600 }
+    }
+}
+
+/// Authentication settings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthConfig {
+    /// How long a successful token lookup is cached (0 = no caching)
+    pub cache_ttl_secs: u64,
+    /// Maximum number of cached tokens
+    pub cache_max_entries: usize,
+    /// Also accept the raw API key from this header, e.g. `X-Api-Key`
+    pub api_key_header: Option<String>,
+    /// Also accept the API key from this query parameter, e.g. `access_token`
+    pub query_param: Option<String>,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            cache_ttl_secs: 0,
+            cache_max_entries: 10_000,
+            api_key_header: None,
+            query_param: None,
+        }
+    }
+}
+
+/// JSON-RPC envelope settings
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JsonRpcConfig {
+    /// Require `"jsonrpc": "2.0"` and a string or number `id` on every request
+    pub strict: bool,
+    /// Accept invoke `arguments` sent as a JSON-encoded string, as some
+    /// clients do, by parsing it into the object it encodes
+    pub lenient_arguments: bool,
+}
+
+/// Log notification settings
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoggingConfig {
+    /// Least severe level of the tool log entries sent to clients
+    pub level: LogLevel,
+}
+
+/// Invocation time limit settings
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TimeoutsConfig {
+    /// Longest any invocation may run (0 = unlimited); tools and clients can
+    /// only shorten it
+    pub max_ms: u64,
+}
+
+/// Response cache settings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ResponseCacheConfig {
+    /// Cache results of tools annotated with `cacheTtlSecs` and send `ETag`s
+    /// on discover and cacheable invoke responses
+    pub enabled: bool,
+    /// Results larger than this, serialized, are not cached
+    pub max_entry_bytes: usize,
+}
+
+impl Default for ResponseCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_entry_bytes: 1024 * 1024,
+        }
+    }
+}
+
+/// Usage report settings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UsageConfig {
+    /// SQLite database usage is written to (`sqlite` feature); the shared
+    /// `[database]` when unset and configured, memory otherwise
+    pub path: Option<String>,
+    /// How often counted usage is written out (0 = only when a report is requested)
+    pub flush_interval_secs: u64,
+    /// How long hourly usage is kept (0 = forever)
+    pub retention_days: u64,
+}
+
+impl Default for UsageConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            flush_interval_secs: 60,
+            retention_days: 90,
+        }
+    }
+}
+
+/// Outbound HTTP client settings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HttpConfig {
+    /// Limit on a whole request unless the tool sets its own (0 = none)
+    pub timeout_ms: u64,
+    pub connect_timeout_ms: u64,
+    /// Idle connections kept open per host
+    pub pool_max_idle_per_host: usize,
+    /// How long an idle connection is kept open
+    pub pool_idle_timeout_secs: u64,
+    pub user_agent: String,
+    /// PEM files of root certificates trusted in addition to the built-in
+    /// ones, e.g. an internal CA
+    pub ca_certs: Vec<PathBuf>,
+    /// Skip certificate verification; only for testing against self-signed
+    /// servers
+    pub accept_invalid_certs: bool,
+    /// Proxy for all outbound requests, e.g. `http://proxy.internal:3128`;
+    /// without one the `HTTP_PROXY`/`HTTPS_PROXY` environment variables apply
+    pub proxy: Option<String>,
+    /// Hosts reached without the proxy, e.g. `localhost` or `.internal`
+    pub no_proxy: Vec<String>,
+    /// Hosts tools may send requests to
+    pub egress: EgressConfig,
+}
+
+/// Outbound destination rules, matched against the request host with `*`
+/// wildcards, e.g. `*.example.com` or `169.254.169.254`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EgressConfig {
+    /// Hosts requests may go to; empty allows every host not denied
+    pub allow: Vec<String>,
+    /// Hosts requests may never go to, even when allowed
+    pub deny: Vec<String>,
+    /// Refuse loopback, private and link-local addresses, whether given
+    /// directly or resolved from a host name
+    pub block_private_addresses: bool,
+    /// Private addresses (`10.0.0.5`) and networks (`10.1.0.0/16`) reachable
+    /// despite `block_private_addresses`, e.g. an internal vector database
+    pub private_allow: Vec<String>,
+}
+
+impl Default for EgressConfig {
+    fn default() -> Self {
+        Self {
+            allow: Vec::new(),
+            deny: Vec::new(),
+            block_private_addresses: true,
+            private_allow: Vec::new(),
+        }
+    }
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            timeout_ms: 30_000,
+            connect_timeout_ms: 10_000,
+            pool_max_idle_per_host: 32,
+            pool_idle_timeout_secs: 90,
+            user_agent: concat!("mcp-server/", env!("CARGO_PKG_VERSION")).to_string(),
+            ca_certs: Vec::new(),
+            accept_invalid_certs: false,
+            proxy: None,
+            no_proxy: Vec::new(),
+            egress: EgressConfig::default(),
+        }
+    }
+}
+
+/// Elicitation settings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ElicitationConfig {
+    /// How long a tool waits for the client's answer
+    pub timeout_secs: u64,
+}
+
+impl Default for ElicitationConfig {
+    fn default() -> Self {
+        Self { timeout_secs: 300 }
+    }
+}
+
+/// Deprecation of a tool, or of some of its versions
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DeprecationConfig {
+    /// Deprecated versions; empty deprecates every version
+    pub versions: Vec<String>,
+    /// Warning text, generated from the other fields when unset
+    pub message: Option<String>,
+    /// Tool to use instead
+    pub replacement: Option<String>,
+    /// Date after which the tool may be removed
+    pub sunset: Option<NaiveDate>,
+}
+
+/// Route a share of a tool's invocations to another implementation
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CanaryConfig {
+    /// Registered tool that serves the canary traffic
+    pub variant: String,
+    /// Share of invocations routed to the variant, 0 to 100
+    pub percent: f64,
+    /// Route by username hash so each user consistently sees one variant
+    #[serde(default)]
+    pub sticky: bool,
+}
+
+/// Shared state backend settings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StoreConfig {
+    pub backend: StoreBackend,
+    /// Connection URL for the Redis backend, e.g. `redis://127.0.0.1:6379`
+    pub url: Option<String>,
+    /// Prefix for every key, so several deployments can share one Redis
+    pub key_prefix: String,
+    /// Database file for the SQLite backend; the shared `[database]` when unset
+    pub path: Option<String>,
+    /// How often expired entries are removed (0 = only when they are read)
+    pub cleanup_interval_secs: u64,
+}
+
+impl Default for StoreConfig {
+    fn default() -> Self {
+        Self {
+            backend: StoreBackend::default(),
+            url: None,
+            key_prefix: "mcp:".to_string(),
+            path: None,
+            cleanup_interval_secs: 300,
+        }
+    }
+}
+
+/// Available shared state backends
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StoreBackend {
+    /// Process-local state; suitable for a single replica
+    #[default]
+    Memory,
+    /// Redis, requires the `redis` cargo feature
+    Redis,
+    /// SQLite file, requires the `sqlite` cargo feature; survives restarts of a single replica
+    Sqlite,
+}
+
+/// Tool set and shared configuration for one tenant
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TenantConfig {
+    /// Tools visible to the tenant's users; all tools when unset
+    pub tools: Option<Vec<String>>,
+    /// External keys shared by the tenant's users; user-level keys take precedence
+    pub external_keys: HashMap<String, String>,
+}
+
+/// Invocation scheduling settings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SchedulingConfig {
+    /// Maximum number of concurrently executing invocations (0 = unlimited)
+    pub max_concurrent: usize,
+    /// Maximum number of invocations waiting for a slot before rejecting
+    pub max_queue: usize,
+}
+
+impl Default for SchedulingConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 0,
+            max_queue: 100,
+        }
+    }
+}
+
+/// In-memory invocation history settings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HistoryConfig {
+    pub enabled: bool,
+    /// Maximum number of invocations kept per user
+    pub capacity: usize,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            capacity: 100,
+        }
+    }
+}
+
+/// Global sanitization settings with per-tool overrides
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SanitizationConfig {
+    /// Strip control characters (except newline, carriage return and tab)
+    pub strip_control_chars: bool,
+    /// Screen string arguments for prompt-injection patterns
+    pub screen_injections: bool,
+    /// Reject the invocation when a pattern matches, instead of only flagging it
+    pub reject_injections: bool,
+    /// Case-insensitive regular expressions; built-in patterns are used when empty
+    pub injection_patterns: Vec<String>,
+    /// Per-tool overrides keyed by tool name
+    pub tools: HashMap<String, ToolSanitizationConfig>,
+}
+
+impl Default for SanitizationConfig {
+    fn default() -> Self {
+        Self {
+            strip_control_chars: true,
+            screen_injections: false,
+            reject_injections: false,
+            injection_patterns: Vec::new(),
+            tools: HashMap::new(),
+        }
+    }
+}
+
+/// Per-tool sanitization overrides, unset fields inherit the global setting
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ToolSanitizationConfig {
+    /// Disable all sanitizers (built-in and custom) for this tool
+    pub enabled: bool,
+    pub strip_control_chars: Option<bool>,
+    pub screen_injections: Option<bool>,
+    pub reject_injections: Option<bool>,
+}
+
+impl Default for ToolSanitizationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            strip_control_chars: None,
+            screen_injections: None,
+            reject_injections: None,
+        }
+    }
+}
+
+impl SanitizationConfig {
+    /// Whether sanitizers run at all for a tool
+    pub fn enabled_for(&self, tool_name: &str) -> bool {
+        self.tools.get(tool_name).is_none_or(|t| t.enabled)
+    }
+
+    /// Effective control-character stripping setting for a tool
+    pub fn strip_control_chars_for(&self, tool_name: &str) -> bool {
+        self.tools
+            .get(tool_name)
+            .and_then(|t| t.strip_control_chars)
+            .unwrap_or(self.strip_control_chars)
+    }
+
+    /// Effective injection screening setting for a tool
+    pub fn screen_injections_for(&self, tool_name: &str) -> bool {
+        self.tools
+            .get(tool_name)
+            .and_then(|t| t.screen_injections)
+            .unwrap_or(self.screen_injections)
+    }
+
+    /// Effective injection rejection setting for a tool
+    pub fn reject_injections_for(&self, tool_name: &str) -> bool {
+        self.tools
+            .get(tool_name)
+            .and_then(|t| t.reject_injections)
+            .unwrap_or(self.reject_injections)
+    }
+}
+
+/// Get the path to the server configuration file
+/// Checks MCP_CONFIG_PATH env var, defaults to config/server.toml
+pub fn get_config_path() -> String {
+    env::var("MCP_CONFIG_PATH").unwrap_or_else(|_| DEFAULT_CONFIG_PATH.to_string())
+}
+
+/// Load server configuration from TOML
+/// The default path is optional; an explicitly configured path must exist
+pub fn load_config() -> Result<ServerConfig> {
+    let path = get_config_path();
+
+    if env::var("MCP_CONFIG_PATH").is_err() && !Path::new(&path).exists() {
+        return Ok(ServerConfig::default());
+    }
+
+    let contents = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read config file at: {}", path))?;
+    let config: ServerConfig = toml::from_str(&contents)
+        .with_context(|| format!("Failed to parse config file at: {}", path))?;
+
+    Ok(config)
+}
@@ -0,0 +1,129 @@
+//! Versioned schema changes of the shared database
+//!
+//! Each migration has a version one above the previous one. The versions a
+//! database has seen are recorded in `schema_migrations`; opening a database
+//! newer than this build is refused rather than risking writes in a shape
+//! this build does not know.
+//!
+//! Add new migrations at the end of [`MIGRATIONS`]; never edit or reorder
+//! released ones. Tables existing from before migrations were tracked are
+//! created with `IF NOT EXISTS`, so such databases are adopted as they are.
+
+#[cfg(feature = "sqlite")]
+use anyhow::{Context, Result, bail};
+
+/// One schema change
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    pub sql: &'static str,
+}
+
+/// Every migration, oldest first
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "store entries",
+        sql: "CREATE TABLE IF NOT EXISTS entries (
+                  key TEXT PRIMARY KEY,
+                  value TEXT NOT NULL,
+                  expires_at INTEGER
+              );",
+    },
+    Migration {
+        version: 2,
+        description: "hourly usage",
+        sql: "CREATE TABLE IF NOT EXISTS usage (
+                  bucket INTEGER NOT NULL,
+                  user TEXT NOT NULL,
+                  tool TEXT NOT NULL,
+                  invocations INTEGER NOT NULL,
+                  succeeded INTEGER NOT NULL,
+                  failed INTEGER NOT NULL,
+                  rejected INTEGER NOT NULL,
+                  duration_ms INTEGER NOT NULL,
+                  PRIMARY KEY (bucket, user, tool)
+              );",
+    },
+];
+
+/// Latest schema version this build knows
+pub fn latest_version(migrations: &[Migration]) -> u32 {
+    migrations.last().map_or(0, |migration| migration.version)
+}
+
+/// Highest migration applied to the database, 0 for a new one
+#[cfg(feature = "sqlite")]
+pub fn current_version(connection: &rusqlite::Connection) -> Result<u32> {
+    create_history(connection)?;
+    Ok(connection.query_row(
+        "SELECT COALESCE(MAX(version), 0) FROM schema_migrations",
+        [],
+        |row| row.get(0),
+    )?)
+}
+
+#[cfg(feature = "sqlite")]
+fn create_history(connection: &rusqlite::Connection) -> rusqlite::Result<()> {
+    connection.execute_batch(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+             version INTEGER PRIMARY KEY,
+             description TEXT NOT NULL,
+             applied_at TEXT NOT NULL
+         );",
+    )
+}
+
+/// Apply the migrations newer than the database, each in a transaction;
+/// returns how many were applied
+#[cfg(feature = "sqlite")]
+pub fn migrate(connection: &mut rusqlite::Connection, migrations: &[Migration]) -> Result<usize> {
+    for (expected, migration) in (1..).zip(migrations) {
+        if migration.version != expected {
+            bail!(
+                "Migration '{}' has version {}, expected {}",
+                migration.description,
+                migration.version,
+                expected
+            );
+        }
+    }
+
+    let current = current_version(connection)?;
+    let latest = latest_version(migrations);
+    if current > latest {
+        bail!(
+            "Database schema version {} is newer than this server supports ({})",
+            current,
+            latest
+        );
+    }
+
+    let pending = &migrations[current as usize..];
+    for migration in pending {
+        apply(connection, migration).with_context(|| {
+            format!(
+                "Migration {} ({}) failed",
+                migration.version, migration.description
+            )
+        })?;
+    }
+    Ok(pending.len())
+}
+
+#[cfg(feature = "sqlite")]
+fn apply(connection: &mut rusqlite::Connection, migration: &Migration) -> rusqlite::Result<()> {
+    let tx = connection.transaction()?;
+    tx.execute_batch(migration.sql)?;
+    tx.execute(
+        "INSERT INTO schema_migrations (version, description, applied_at)
+         VALUES (?1, ?2, ?3)",
+        rusqlite::params![
+            migration.version,
+            migration.description,
+            chrono::Utc::now().to_rfc3339()
+        ],
+    )?;
+    tx.commit()
+}
@@ -0,0 +1,88 @@
+//! Embedded SQLite database shared by the server's persistent state
+//!
+//! Subsystems keeping state across restarts (the `sqlite` store backend,
+//! usage snapshots, ...) use the one database at `[database] path` instead
+//! of each managing a file of their own. Its schema is versioned: on open,
+//! the [`migrations`] this build knows and the database has not seen yet are
+//! applied in order, each in its own transaction.
+//!
+//! Requires the `sqlite` feature; without a path the handle is disabled and
+//! subsystems fall back to memory or their own settings.
+
+pub mod migrations;
+
+use crate::config::DatabaseConfig;
+use anyhow::Result;
+#[cfg(feature = "sqlite")]
+use std::sync::{Arc, Mutex};
+
+/// Handle on the shared database; cheap to clone, disabled by default
+#[derive(Clone, Default)]
+pub struct Storage {
+    #[cfg(feature = "sqlite")]
+    connection: Option<Arc<Mutex<rusqlite::Connection>>>,
+}
+
+impl Storage {
+    /// Open the database at `[database] path`, if set, and bring its schema
+    /// up to date
+    pub fn open(config: &DatabaseConfig) -> Result<Self> {
+        match &config.path {
+            None => Ok(Self::default()),
+            #[cfg(feature = "sqlite")]
+            Some(path) => Self::open_path(path),
+            #[cfg(not(feature = "sqlite"))]
+            Some(_) => {
+                anyhow::bail!("`[database] path` requires building with the `sqlite` feature")
+            }
+        }
+    }
+
+    /// Open (creating if needed) the database file at `path` and apply
+    /// pending migrations
+    #[cfg(feature = "sqlite")]
+    pub fn open_path(path: &str) -> Result<Self> {
+        use anyhow::Context;
+
+        let mut connection = rusqlite::Connection::open(path)
+            .with_context(|| format!("Failed to open SQLite database {}", path))?;
+        connection.execute_batch("PRAGMA journal_mode = WAL;")?;
+        let applied = migrations::migrate(&mut connection, migrations::MIGRATIONS)
+            .with_context(|| format!("Failed to migrate SQLite database {}", path))?;
+        if applied > 0 {
+            tracing::info!(
+                "Applied {} migration(s) to {}, now at schema version {}",
+                applied,
+                path,
+                migrations::current_version(&connection)?
+            );
+        }
+        Ok(Self {
+            connection: Some(Arc::new(Mutex::new(connection))),
+        })
+    }
+
+    /// Whether a database is configured
+    pub fn is_enabled(&self) -> bool {
+        #[cfg(feature = "sqlite")]
+        return self.connection.is_some();
+        #[cfg(not(feature = "sqlite"))]
+        false
+    }
+
+    /// Connection to the database, `None` when disabled
+    #[cfg(feature = "sqlite")]
+    pub fn connection(&self) -> Option<Arc<Mutex<rusqlite::Connection>>> {
+        self.connection.clone()
+    }
+
+    /// Schema version of the database, `None` when disabled
+    #[cfg(feature = "sqlite")]
+    pub fn schema_version(&self) -> Result<Option<u32>> {
+        let Some(connection) = &self.connection else {
+            return Ok(None);
+        };
+        let connection = connection.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(Some(migrations::current_version(&connection)?))
+    }
+}
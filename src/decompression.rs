@@ -0,0 +1,110 @@
+use crate::config::RequestBodyConfig;
+use axum::{
+    body::{Body, to_bytes},
+    extract::{Request, State},
+    http::{StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use flate2::read::{MultiGzDecoder, ZlibDecoder};
+use std::io::Read;
+use std::sync::Arc;
+
+/// Why a compressed body was refused
+#[derive(Debug, PartialEq, Eq)]
+pub enum DecompressionError {
+    /// The body does not fit in the limit once decompressed
+    TooLarge,
+    /// The body is not valid for its encoding
+    Invalid(String),
+}
+
+/// Decompress a `gzip` or `deflate` (zlib) body, failing as soon as the output
+/// exceeds `max_bytes`
+pub fn decompress(
+    encoding: &str,
+    compressed: &[u8],
+    max_bytes: usize,
+) -> Result<Vec<u8>, DecompressionError> {
+    let decoder: Box<dyn Read + '_> = match encoding {
+        "gzip" | "x-gzip" => Box::new(MultiGzDecoder::new(compressed)),
+        "deflate" => Box::new(ZlibDecoder::new(compressed)),
+        other => {
+            return Err(DecompressionError::Invalid(format!(
+                "unsupported encoding '{}'",
+                other
+            )));
+        }
+    };
+
+    // One byte past the limit tells an oversized body from one that just fits
+    let mut decompressed = Vec::new();
+    decoder
+        .take(max_bytes as u64 + 1)
+        .read_to_end(&mut decompressed)
+        .map_err(|e| DecompressionError::Invalid(e.to_string()))?;
+    if decompressed.len() > max_bytes {
+        return Err(DecompressionError::TooLarge);
+    }
+    Ok(decompressed)
+}
+
+/// Middleware replacing `gzip` / `deflate` request bodies with their
+/// decompressed content, so extractors and handlers see the plain body
+///
+/// Runs after signature verification: signatures cover the body as sent.
+/// Other encodings are refused with 415.
+pub async fn decompress_request(
+    State(config): State<Arc<RequestBodyConfig>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let Some(encoding) = req
+        .headers()
+        .get(header::CONTENT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim().to_ascii_lowercase())
+    else {
+        return next.run(req).await;
+    };
+    if encoding == "identity" {
+        return next.run(req).await;
+    }
+    if !matches!(encoding.as_str(), "gzip" | "x-gzip" | "deflate") {
+        return (
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            format!(
+                "Unsupported Content-Encoding '{}': expected gzip or deflate",
+                encoding
+            ),
+        )
+            .into_response();
+    }
+
+    let (mut parts, body) = req.into_parts();
+    let Ok(compressed) = to_bytes(body, config.max_bytes).await else {
+        return (StatusCode::PAYLOAD_TOO_LARGE, "Request body too large").into_response();
+    };
+    let decompressed = match decompress(&encoding, &compressed, config.max_bytes) {
+        Ok(decompressed) => decompressed,
+        Err(DecompressionError::TooLarge) => {
+            return (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "Decompressed request body too large",
+            )
+                .into_response();
+        }
+        Err(DecompressionError::Invalid(message)) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                format!("Failed to decompress the request body: {}", message),
+            )
+                .into_response();
+        }
+    };
+
+    parts.headers.remove(header::CONTENT_ENCODING);
+    parts.headers.remove(header::CONTENT_LENGTH);
+    next.run(Request::from_parts(parts, Body::from(decompressed)))
+        .await
+}
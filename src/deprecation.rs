@@ -0,0 +1,70 @@
+//! Warnings for invocations of deprecated tools
+//!
+//! Tools (or some of their versions) listed under `[deprecations]` keep
+//! working, but every invocation of them carries a warning in the response
+//! `meta`, is logged and is counted in `mcp_deprecated_invocations_total`, so
+//! operators can see who still depends on a tool before removing it.
+
+use crate::ToolDefinition;
+use crate::config::DeprecationConfig;
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Warning attached to the responses of a deprecated tool's invocations
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeprecationWarning {
+    /// Always `deprecated`, distinguishing it from other kinds of warnings
+    pub code: &'static str,
+    pub message: String,
+    pub tool: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replacement: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sunset: Option<NaiveDate>,
+}
+
+/// The warning for an invocation of `definition`, if it (or its version) is
+/// deprecated
+pub fn deprecation_warning(
+    deprecations: &HashMap<String, DeprecationConfig>,
+    definition: &ToolDefinition,
+) -> Option<DeprecationWarning> {
+    let deprecation = deprecations.get(&definition.name)?;
+    if !deprecation.versions.is_empty()
+        && !definition
+            .version
+            .as_ref()
+            .is_some_and(|version| deprecation.versions.contains(version))
+    {
+        return None;
+    }
+
+    let message = deprecation.message.clone().unwrap_or_else(|| {
+        let mut message = match (&definition.version, deprecation.versions.is_empty()) {
+            (Some(version), false) => format!(
+                "Version {} of tool '{}' is deprecated",
+                version, definition.name
+            ),
+            _ => format!("Tool '{}' is deprecated", definition.name),
+        };
+        if let Some(sunset) = deprecation.sunset {
+            message.push_str(&format!(" and will be removed on {}", sunset));
+        }
+        if let Some(replacement) = &deprecation.replacement {
+            message.push_str(&format!("; use '{}' instead", replacement));
+        }
+        message
+    });
+
+    Some(DeprecationWarning {
+        code: "deprecated",
+        message,
+        tool: definition.name.clone(),
+        version: definition.version.clone(),
+        replacement: deprecation.replacement.clone(),
+        sunset: deprecation.sunset,
+    })
+}
@@ -0,0 +1,81 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// Tracks in-flight invocations and whether the instance is draining
+///
+/// Once draining, the instance reports not-ready and refuses new invocations,
+/// while those already running are allowed to finish.
+#[derive(Debug, Default)]
+pub struct DrainController {
+    draining: AtomicBool,
+    in_flight: AtomicUsize,
+    idle: Notify,
+}
+
+/// Marks an invocation as in flight until dropped
+pub struct InFlightGuard {
+    controller: Arc<DrainController>,
+}
+
+impl DrainController {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop accepting new invocations
+    pub fn start(&self) {
+        self.draining.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::SeqCst)
+    }
+
+    /// Number of invocations currently running
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Register a new invocation; `None` once draining has started
+    pub fn begin(self: &Arc<Self>) -> Option<InFlightGuard> {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        // Checked after incrementing so a concurrent drain either sees this
+        // invocation or the invocation sees the drain
+        if self.is_draining() {
+            self.finish();
+            return None;
+        }
+        Some(InFlightGuard {
+            controller: self.clone(),
+        })
+    }
+
+    /// Wait until no invocation is running; returns `false` on timeout
+    pub async fn wait_idle(&self, timeout: Duration) -> bool {
+        tokio::time::timeout(timeout, async {
+            loop {
+                let idle = self.idle.notified();
+                if self.in_flight() == 0 {
+                    return;
+                }
+                idle.await;
+            }
+        })
+        .await
+        .is_ok()
+    }
+
+    fn finish(&self) {
+        if self.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.idle.notify_waiters();
+        }
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.controller.finish();
+    }
+}
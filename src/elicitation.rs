@@ -0,0 +1,115 @@
+use crate::tools::validate_tool_args;
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tokio::sync::oneshot;
+
+/// How the user answered an elicitation request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ElicitationAction {
+    /// The user submitted the requested data
+    Accept,
+    /// The user explicitly refused
+    Decline,
+    /// The user dismissed the request without choosing
+    Cancel,
+}
+
+/// The client's answer to an `elicitation/create` request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ElicitationResponse {
+    pub action: ElicitationAction,
+    /// Submitted data matching the requested schema, present on `accept`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<Value>,
+}
+
+impl ElicitationResponse {
+    /// Whether the user accepted the request
+    pub fn is_accepted(&self) -> bool {
+        self.action == ElicitationAction::Accept
+    }
+}
+
+/// Elicitation awaiting the client's answer
+struct Pending {
+    username: String,
+    schema: Value,
+    reply: oneshot::Sender<ElicitationResponse>,
+}
+
+/// Elicitation requests waiting for an `elicitation/respond` from the client
+#[derive(Default)]
+pub struct PendingElicitations {
+    pending: Mutex<HashMap<String, Pending>>,
+}
+
+impl PendingElicitations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a new elicitation for a user, returning its id and the answer receiver
+    pub fn register(
+        &self,
+        username: &str,
+        schema: Value,
+    ) -> (String, oneshot::Receiver<ElicitationResponse>) {
+        let id = format!("{:032x}", rand::random::<u128>());
+        let (reply, answer) = oneshot::channel();
+        self.lock().insert(
+            id.clone(),
+            Pending {
+                username: username.to_string(),
+                schema,
+                reply,
+            },
+        );
+        (id, answer)
+    }
+
+    /// Deliver the client's answer to the waiting tool
+    ///
+    /// Only the user the request was sent to may answer it. Accepted content is
+    /// validated against the requested schema; invalid content leaves the request
+    /// pending so the client can retry.
+    pub fn resolve(&self, username: &str, id: &str, response: ElicitationResponse) -> Result<()> {
+        let mut pending = self.lock();
+        let Some(entry) = pending.get(id).filter(|p| p.username == username) else {
+            bail!("Unknown elicitation '{}'", id);
+        };
+
+        if response.is_accepted() {
+            if response.content.is_none() {
+                bail!("Missing required content for accepted elicitation");
+            }
+            validate_tool_args(&entry.schema, &response.content)?;
+        }
+
+        let entry = pending.remove(id).expect("entry checked above");
+        // The tool may have timed out in the meantime
+        let _ = entry.reply.send(response);
+        Ok(())
+    }
+
+    /// Drop a pending request, e.g. after a timeout
+    pub fn remove(&self, id: &str) {
+        self.lock().remove(id);
+    }
+
+    /// Number of requests still waiting for an answer
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Pending>> {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,319 @@
+//! Wire formats of `POST /mcp`
+//!
+//! JSON is the default; clients sending `Content-Type: application/msgpack` or
+//! `application/cbor` get their response in the same format unless `Accept`
+//! asks for another one. Streamed invocations always use JSON server-sent
+//! events.
+//!
+//! Bodies are decoded under `DecodeLimits`, so deeply nested or extremely
+//! wide documents are refused while they are parsed instead of exhausting the
+//! stack or CPU once built.
+
+use axum::{
+    body::Bytes,
+    extract::{FromRef, FromRequest, Request},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Json, Response},
+};
+use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
+use std::cell::Cell;
+use std::fmt;
+
+/// Body encoding negotiated with the client
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WireFormat {
+    #[default]
+    Json,
+    MessagePack,
+    Cbor,
+}
+
+impl WireFormat {
+    /// Format of a media type such as `application/msgpack; charset=binary`,
+    /// `None` when unsupported
+    pub fn from_media_type(media_type: &str) -> Option<Self> {
+        let essence = media_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        match essence.as_str() {
+            "application/json" => Some(Self::Json),
+            "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack" => {
+                Some(Self::MessagePack)
+            }
+            "application/cbor" => Some(Self::Cbor),
+            other if other.starts_with("application/") && other.ends_with("+json") => {
+                Some(Self::Json)
+            }
+            _ => None,
+        }
+    }
+
+    /// Response format: the first supported type listed in `Accept`, otherwise
+    /// the format of the request
+    pub fn negotiate(headers: &HeaderMap, request_format: Self) -> Self {
+        headers
+            .get_all(header::ACCEPT)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .find_map(Self::from_media_type)
+            .unwrap_or(request_format)
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::MessagePack => "application/msgpack",
+            Self::Cbor => "application/cbor",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Json => "JSON",
+            Self::MessagePack => "MessagePack",
+            Self::Cbor => "CBOR",
+        }
+    }
+
+    pub fn decode(self, bytes: &[u8]) -> Result<Value, String> {
+        self.decode_limited(bytes, DecodeLimits::default())
+    }
+
+    /// Decode a document, failing as soon as it nests or widens past `limits`
+    pub fn decode_limited(self, bytes: &[u8], limits: DecodeLimits) -> Result<Value, String> {
+        LIMITS.set(limits);
+        let decoded = match self {
+            Self::Json => serde_json::from_slice(bytes).map_err(|e| e.to_string()),
+            Self::MessagePack => rmp_serde::from_slice(bytes).map_err(|e| e.to_string()),
+            Self::Cbor => ciborium::from_reader(bytes).map_err(|e| e.to_string()),
+        };
+        LIMITS.set(DecodeLimits::default());
+        decoded.map(|LimitedValue(value)| value)
+    }
+
+    pub fn encode<T: Serialize>(self, value: &T) -> Result<Vec<u8>, String> {
+        match self {
+            Self::Json => serde_json::to_vec(value).map_err(|e| e.to_string()),
+            // Structs as maps: the compact array form loses field names
+            Self::MessagePack => rmp_serde::to_vec_named(value).map_err(|e| e.to_string()),
+            Self::Cbor => {
+                let mut bytes = Vec::new();
+                ciborium::into_writer(value, &mut bytes).map_err(|e| e.to_string())?;
+                Ok(bytes)
+            }
+        }
+    }
+}
+
+/// Caps on decoded documents (0 = unlimited)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DecodeLimits {
+    /// Deepest nesting of arrays and objects
+    pub max_depth: usize,
+    /// Most keys in any one object
+    pub max_object_keys: usize,
+}
+
+thread_local! {
+    /// Limits of the decode running on this thread. ciborium only decodes
+    /// `DeserializeOwned` types, so they cannot be passed as a seed.
+    static LIMITS: Cell<DecodeLimits> = const {
+        Cell::new(DecodeLimits {
+            max_depth: 0,
+            max_object_keys: 0,
+        })
+    };
+}
+
+/// A `Value` decoded under the limits in `LIMITS`
+struct LimitedValue(Value);
+
+impl<'de> Deserialize<'de> for LimitedValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let root = Limited {
+            limits: LIMITS.get(),
+            depth: 0,
+        };
+        root.deserialize(deserializer).map(Self)
+    }
+}
+
+/// Builds a `Value` like its own `Deserialize` does, checking the limits
+/// before descending into each array or object and before each key
+#[derive(Clone, Copy)]
+struct Limited {
+    limits: DecodeLimits,
+    depth: usize,
+}
+
+impl Limited {
+    /// The seed for the contents of an array or object at this level
+    fn nested<E: de::Error>(self) -> Result<Self, E> {
+        let depth = self.depth + 1;
+        let max_depth = self.limits.max_depth;
+        if max_depth > 0 && depth > max_depth {
+            return Err(E::custom(format!(
+                "nesting deeper than {} levels",
+                max_depth
+            )));
+        }
+        Ok(Self { depth, ..self })
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for Limited {
+    type Value = Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Limited {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any valid JSON value")
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<Value, E> {
+        Ok(Value::Bool(value))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Value, E> {
+        Ok(value.into())
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Value, E> {
+        Ok(value.into())
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<Value, E> {
+        Ok(Number::from_f64(value).map_or(Value::Null, Value::Number))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Value, E> {
+        Ok(Value::String(value.to_string()))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<Value, E> {
+        Ok(Value::String(value))
+    }
+
+    fn visit_none<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        DeserializeSeed::deserialize(self, deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let element = self.nested()?;
+        let mut values = Vec::new();
+        while let Some(value) = seq.next_element_seed(element)? {
+            values.push(value);
+        }
+        Ok(Value::Array(values))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        let value = self.nested()?;
+        let max_keys = self.limits.max_object_keys;
+        let mut object = Map::new();
+        while let Some(key) = map.next_key::<String>()? {
+            if max_keys > 0 && object.len() >= max_keys {
+                return Err(de::Error::custom(format!(
+                    "object with more than {} keys",
+                    max_keys
+                )));
+            }
+            object.insert(key, map.next_value_seed(value)?);
+        }
+        Ok(Value::Object(object))
+    }
+}
+
+/// Response body serialized in a wire format
+pub struct Encoded<T>(pub WireFormat, pub T);
+
+impl<T: Serialize> IntoResponse for Encoded<T> {
+    fn into_response(self) -> Response {
+        let Encoded(format, value) = self;
+        if format == WireFormat::Json {
+            return Json(value).into_response();
+        }
+        match format.encode(&value) {
+            Ok(bytes) => ([(header::CONTENT_TYPE, format.content_type())], bytes).into_response(),
+            Err(e) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to encode the {} response: {}", format.name(), e),
+            )
+                .into_response(),
+        }
+    }
+}
+
+/// `POST /mcp` body decoded according to its `Content-Type`, with the format
+/// to answer in
+///
+/// Bodies are rejected like `Json<Value>` rejects them: 415 without a
+/// supported `Content-Type`, 400 when they cannot be decoded, which includes
+/// exceeding the state's `DecodeLimits`.
+pub struct McpBody {
+    pub body: Value,
+    pub format: WireFormat,
+}
+
+impl<S> FromRequest<S> for McpBody
+where
+    S: Send + Sync,
+    DecodeLimits: FromRef<S>,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Some(request_format) = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(WireFormat::from_media_type)
+        else {
+            return Err((
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "Expected request with `Content-Type: application/json`",
+            )
+                .into_response());
+        };
+        let format = WireFormat::negotiate(req.headers(), request_format);
+
+        let bytes = Bytes::from_request(req, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+        let body = request_format
+            .decode_limited(&bytes, DecodeLimits::from_ref(state))
+            .map_err(|e| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    format!(
+                        "Failed to parse the request body as {}: {}",
+                        request_format.name(),
+                        e
+                    ),
+                )
+                    .into_response()
+            })?;
+
+        Ok(Self { body, format })
+    }
+}
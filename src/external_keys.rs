@@ -0,0 +1,81 @@
+use crate::ToolDefinition;
+use crate::auth::CredentialsStore;
+use crate::tenant::ToolSet;
+use anyhow::{Result, bail};
+use std::collections::HashMap;
+use std::fmt;
+
+/// A user who can see a tool but lacks one of the external keys it requires
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MissingExternalKey {
+    pub username: String,
+    pub tool: String,
+    pub key: String,
+}
+
+impl fmt::Display for MissingExternalKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "user '{}' is missing external key '{}' required by tool '{}'",
+            self.username, self.key, self.tool
+        )
+    }
+}
+
+/// Every user, tool and key combination that would fail when invoked
+///
+/// Users of a tenant are checked against the tenant's tool set, everyone else
+/// against the global tools. Sorted by user, then tool.
+pub fn find_missing_external_keys(
+    credentials: &CredentialsStore,
+    global: &[ToolDefinition],
+    tenants: &HashMap<String, ToolSet>,
+) -> Vec<MissingExternalKey> {
+    let mut missing = Vec::new();
+    for user in credentials.values() {
+        let tools = user
+            .tenant
+            .as_ref()
+            .and_then(|tenant| tenants.get(tenant))
+            .map_or(global, |set| set.definitions.as_slice());
+        for tool in tools {
+            for key in &tool.required_external_keys {
+                if user.get_external_key(key).is_none() {
+                    missing.push(MissingExternalKey {
+                        username: user.username.clone(),
+                        tool: tool.name.clone(),
+                        key: key.clone(),
+                    });
+                }
+            }
+        }
+    }
+    missing.sort();
+    missing
+}
+
+/// Warn about each missing external key, or fail listing all of them in strict mode
+pub fn check_external_keys(
+    credentials: &CredentialsStore,
+    global: &[ToolDefinition],
+    tenants: &HashMap<String, ToolSet>,
+    strict: bool,
+) -> Result<()> {
+    let missing = find_missing_external_keys(credentials, global, tenants);
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    if strict {
+        let list: Vec<String> = missing.iter().map(ToString::to_string).collect();
+        bail!(
+            "Users are missing external keys required by their tools: {}",
+            list.join("; ")
+        );
+    }
+    for entry in &missing {
+        tracing::warn!("{}; invocations will fail", entry);
+    }
+    Ok(())
+}
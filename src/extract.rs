@@ -0,0 +1,172 @@
+//! Extraction of values from JSON documents
+//!
+//! Declarative tools reshape upstream responses with a mapping whose strings
+//! are path expressions, either jq-style or JSON pointers:
+//!
+//! - `.` is the whole document, `.name` and `."a name"` an object field,
+//!   `[2]` an array element (`[-1]` the last one)
+//! - `[]` continues with every element of an array; the expression then
+//!   yields an array of what it selected for each of them
+//! - `/a/0/b` is a JSON pointer (RFC 6901)
+//!
+//! Missing fields and elements yield `null` rather than an error, so an
+//! upstream leaving out an optional field does not fail the tool.
+
+use anyhow::{Result, anyhow, bail};
+use serde_json::{Map, Value};
+
+/// Step of a jq-style path
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    Field(String),
+    Index(i64),
+    Iterate,
+}
+
+/// A parsed path expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum Path {
+    Pointer(String),
+    Steps(Vec<Step>),
+}
+
+impl Path {
+    pub fn parse(expression: &str) -> Result<Self> {
+        let expression = expression.trim();
+        if expression.is_empty() || expression.starts_with('/') {
+            return Ok(Self::Pointer(expression.to_string()));
+        }
+        if !expression.starts_with('.') {
+            bail!(
+                "Invalid path '{}': expected a jq-style path starting with '.' or a JSON pointer starting with '/'",
+                expression
+            );
+        }
+
+        let chars: Vec<char> = expression.chars().collect();
+        let mut steps = Vec::new();
+        let mut i = 0;
+        while i < chars.len() {
+            match chars[i] {
+                '.' if chars.get(i + 1) == Some(&'"') => {
+                    let end = chars[i + 2..]
+                        .iter()
+                        .position(|&c| c == '"')
+                        .map(|end| i + 2 + end)
+                        .ok_or_else(|| {
+                            anyhow!("Invalid path '{}': unterminated quote", expression)
+                        })?;
+                    steps.push(Step::Field(chars[i + 2..end].iter().collect()));
+                    i = end + 1;
+                }
+                '.' => {
+                    let end = chars[i + 1..]
+                        .iter()
+                        .position(|&c| c == '.' || c == '[')
+                        .map_or(chars.len(), |end| i + 1 + end);
+                    let name: String = chars[i + 1..end].iter().collect();
+                    if !name.is_empty() {
+                        steps.push(Step::Field(name));
+                    } else if !(chars.get(end) == Some(&'[') || (i == 0 && end == chars.len())) {
+                        // Only a lone `.` (the document itself) and `.[...]` have no name
+                        bail!("Invalid path '{}': empty field name", expression);
+                    }
+                    i = end;
+                }
+                '[' => {
+                    let end = chars[i..]
+                        .iter()
+                        .position(|&c| c == ']')
+                        .map(|end| i + end)
+                        .ok_or_else(|| {
+                            anyhow!("Invalid path '{}': unterminated '['", expression)
+                        })?;
+                    let inner: String = chars[i + 1..end].iter().collect();
+                    let inner = inner.trim();
+                    if inner.is_empty() {
+                        steps.push(Step::Iterate);
+                    } else {
+                        let index = inner.parse().map_err(|_| {
+                            anyhow!("Invalid path '{}': bad index '{}'", expression, inner)
+                        })?;
+                        steps.push(Step::Index(index));
+                    }
+                    i = end + 1;
+                }
+                other => bail!("Invalid path '{}': unexpected '{}'", expression, other),
+            }
+        }
+        Ok(Self::Steps(steps))
+    }
+
+    /// The value the path selects in `document`
+    pub fn select(&self, document: &Value) -> Value {
+        match self {
+            Self::Pointer(pointer) => document.pointer(pointer).cloned().unwrap_or(Value::Null),
+            Self::Steps(steps) => select_steps(steps, document),
+        }
+    }
+}
+
+fn select_steps(steps: &[Step], value: &Value) -> Value {
+    let Some((step, rest)) = steps.split_first() else {
+        return value.clone();
+    };
+    match step {
+        Step::Field(name) => select_steps(rest, value.get(name).unwrap_or(&Value::Null)),
+        Step::Index(index) => {
+            let element = value.as_array().and_then(|items| {
+                let index = if *index < 0 {
+                    items.len().checked_sub(index.unsigned_abs() as usize)?
+                } else {
+                    *index as usize
+                };
+                items.get(index)
+            });
+            select_steps(rest, element.unwrap_or(&Value::Null))
+        }
+        Step::Iterate => {
+            let items: Vec<&Value> = match value {
+                Value::Array(items) => items.iter().collect(),
+                Value::Object(map) => map.values().collect(),
+                _ => Vec::new(),
+            };
+            Value::Array(
+                items
+                    .into_iter()
+                    .map(|item| select_steps(rest, item))
+                    .collect(),
+            )
+        }
+    }
+}
+
+/// Check every path expression of a mapping
+pub fn check_mapping(mapping: &Value) -> Result<()> {
+    match mapping {
+        Value::String(expression) => Path::parse(expression).map(drop),
+        Value::Array(items) => items.iter().try_for_each(check_mapping),
+        Value::Object(map) => map.values().try_for_each(check_mapping),
+        _ => Ok(()),
+    }
+}
+
+/// Build a value shaped like `mapping` whose strings are replaced by what
+/// they select in `document`; other values are kept as they are
+pub fn apply_mapping(mapping: &Value, document: &Value) -> Result<Value> {
+    Ok(match mapping {
+        Value::String(expression) => Path::parse(expression)?.select(document),
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| apply_mapping(item, document))
+                .collect::<Result<_>>()?,
+        ),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, item)| Ok((key.clone(), apply_mapping(item, document)?)))
+                .collect::<Result<Map<String, Value>>>()?,
+        ),
+        other => other.clone(),
+    })
+}
@@ -0,0 +1,359 @@
+//! gRPC transport (`grpc` feature) for infrastructure that standardizes on
+//! gRPC rather than HTTP JSON
+//!
+//! Serves `Discover` and `Invoke` from `proto/mcp.proto` against the same
+//! tool registry, credentials and invocation pipeline as `POST /mcp`. The
+//! messages below are kept in sync with the proto by hand, so building the
+//! server needs no `protoc`.
+
+use crate::auth::{AuthError, AuthenticatedUser};
+use crate::encoding::{DecodeLimits, WireFormat};
+use crate::session::ClientInfo;
+use crate::{
+    AppState, ERROR_AUTH, ERROR_BUDGET_EXCEEDED, ERROR_FORBIDDEN, ERROR_INVALID_PARAMS,
+    ERROR_METHOD_NOT_FOUND, ERROR_MISSING_CREDENTIAL, ERROR_READ_ONLY, ERROR_RESULT_TOO_LARGE,
+    ERROR_SERVER_BUSY, ERROR_TIMEOUT, ErrorDetails, InvokeOptions, McpRequest, McpResponse,
+    dispatch,
+};
+use axum::extract::FromRef;
+use serde_json::Value;
+use tokio::net::TcpListener;
+use tonic::codegen::{Body, BoxFuture, Bytes, Context, Poll, Service, StdError, http};
+use tonic::metadata::MetadataValue;
+use tonic::server::{Grpc, NamedService, UnaryService};
+use tonic::transport::Channel;
+use tonic::transport::server::TcpIncoming;
+use tonic::{Code, IntoRequest, Request, Response, Status};
+use tonic_prost::ProstCodec;
+
+/// Trailer carrying the MCP error code of a failed call
+pub const ERROR_CODE_METADATA: &str = "mcp-error-code";
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct DiscoverRequest {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct DiscoverResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub tools: Vec<Tool>,
+    #[prost(string, optional, tag = "2")]
+    pub instructions: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Tool {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, tag = "2")]
+    pub description: String,
+    #[prost(string, tag = "3")]
+    pub parameters_json: String,
+    #[prost(string, optional, tag = "4")]
+    pub output_schema_json: Option<String>,
+    #[prost(string, optional, tag = "5")]
+    pub annotations_json: Option<String>,
+    #[prost(string, optional, tag = "6")]
+    pub version: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct InvokeRequest {
+    #[prost(string, tag = "1")]
+    pub tool_name: String,
+    /// JSON object of arguments; empty for none
+    #[prost(string, tag = "2")]
+    pub arguments_json: String,
+    #[prost(string, optional, tag = "3")]
+    pub idempotency_key: Option<String>,
+    #[prost(int32, optional, tag = "4")]
+    pub priority: Option<i32>,
+    /// Time limit in milliseconds, capped by the tool's and the server's limits
+    #[prost(uint64, optional, tag = "5")]
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct InvokeResponse {
+    #[prost(string, tag = "1")]
+    pub result_json: String,
+    #[prost(string, optional, tag = "2")]
+    pub meta_json: Option<String>,
+}
+
+/// The `mcp.v1.Mcp` service
+#[derive(Clone)]
+pub struct McpService {
+    state: AppState,
+}
+
+impl McpService {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+}
+
+impl NamedService for McpService {
+    const NAME: &'static str = "mcp.v1.Mcp";
+}
+
+impl<B> Service<http::Request<B>> for McpService
+where
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+{
+    type Response = http::Response<tonic::body::Body>;
+    type Error = std::convert::Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        let state = self.state.clone();
+        let max_message_bytes = state.config.grpc.max_message_bytes;
+        match req.uri().path() {
+            "/mcp.v1.Mcp/Discover" => Box::pin(async move {
+                let mut grpc = Grpc::new(ProstCodec::default())
+                    .max_decoding_message_size(max_message_bytes)
+                    .max_encoding_message_size(max_message_bytes);
+                Ok(grpc.unary(Discover(state), req).await)
+            }),
+            "/mcp.v1.Mcp/Invoke" => Box::pin(async move {
+                let mut grpc = Grpc::new(ProstCodec::default())
+                    .max_decoding_message_size(max_message_bytes)
+                    .max_encoding_message_size(max_message_bytes);
+                Ok(grpc.unary(Invoke(state), req).await)
+            }),
+            _ => Box::pin(async { Ok(Status::unimplemented("").into_http()) }),
+        }
+    }
+}
+
+struct Discover(AppState);
+
+impl UnaryService<DiscoverRequest> for Discover {
+    type Response = DiscoverResponse;
+    type Future = BoxFuture<Response<DiscoverResponse>, Status>;
+
+    fn call(&mut self, request: Request<DiscoverRequest>) -> Self::Future {
+        let state = self.0.clone();
+        Box::pin(async move {
+            let (user, client) = authenticate(&state, &request).await?;
+            let result = into_result(dispatch(&state, user, client, McpRequest::Discover).await)?;
+            let tools = discovered_tools(&result);
+            let instructions = result["instructions"].as_str().map(str::to_string);
+            Ok(Response::new(DiscoverResponse {
+                tools,
+                instructions,
+            }))
+        })
+    }
+}
+
+struct Invoke(AppState);
+
+impl UnaryService<InvokeRequest> for Invoke {
+    type Response = InvokeResponse;
+    type Future = BoxFuture<Response<InvokeResponse>, Status>;
+
+    fn call(&mut self, request: Request<InvokeRequest>) -> Self::Future {
+        let state = self.0.clone();
+        Box::pin(async move {
+            let (user, client) = authenticate(&state, &request).await?;
+            let message = request.into_inner();
+            let arguments = match message.arguments_json.trim() {
+                "" => None,
+                json => Some(
+                    WireFormat::Json
+                        .decode_limited(json.as_bytes(), DecodeLimits::from_ref(&state))
+                        .map_err(|e| {
+                            with_error_code(
+                                Status::invalid_argument(format!("Invalid arguments_json: {}", e)),
+                                ERROR_INVALID_PARAMS,
+                            )
+                        })?,
+                ),
+            };
+            let payload = McpRequest::Invoke {
+                tool_name: message.tool_name,
+                arguments,
+                options: InvokeOptions {
+                    priority: message.priority,
+                    meta: None,
+                    idempotency_key: message.idempotency_key,
+                    timeout_ms: message.timeout_ms,
+                },
+            };
+
+            let response = dispatch(&state, user, client, payload).await;
+            let meta_json = response.meta.as_ref().map(Value::to_string);
+            let result = into_result(response)?;
+            Ok(Response::new(InvokeResponse {
+                result_json: result.to_string(),
+                meta_json,
+            }))
+        })
+    }
+}
+
+/// Resolve the `authorization: Bearer` metadata to a user, with the caller's
+/// `user-agent` and address as client info
+async fn authenticate<T>(
+    state: &AppState,
+    request: &Request<T>,
+) -> Result<(AuthenticatedUser, Option<ClientInfo>), Status> {
+    let headers = request.metadata().clone().into_headers();
+    let user = state
+        .auth
+        .authenticate_headers(&headers)
+        .await
+        .map_err(auth_status)?;
+    let user_agent = headers
+        .get(http::header::USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let ip = request
+        .remote_addr()
+        .map(|peer| state.trusted_proxies.client_ip(peer.ip(), &headers));
+    let client = (user_agent.is_some() || ip.is_some()).then_some(ClientInfo {
+        user_agent,
+        ip,
+        ..Default::default()
+    });
+    Ok((AuthenticatedUser(user), client))
+}
+
+fn auth_status(error: AuthError) -> Status {
+    let status = match error {
+        AuthError::Unavailable => Status::unavailable("Authentication provider unavailable"),
+        AuthError::MissingToken => Status::unauthenticated("Missing authorization metadata"),
+        AuthError::InvalidFormat => {
+            Status::unauthenticated("Invalid authorization metadata. Expected: Bearer <token>")
+        }
+        _ => Status::unauthenticated("Invalid or expired API key"),
+    };
+    with_error_code(status, ERROR_AUTH)
+}
+
+fn discovered_tools(result: &Value) -> Vec<Tool> {
+    let json = |value: &Value| (!value.is_null()).then(|| value.to_string());
+    result["tools"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|tool| Tool {
+            name: tool["name"].as_str().unwrap_or_default().to_string(),
+            description: tool["description"].as_str().unwrap_or_default().to_string(),
+            parameters_json: tool["parameters"].to_string(),
+            output_schema_json: json(&tool["output_schema"]),
+            annotations_json: json(&tool["annotations"]),
+            version: tool["version"].as_str().map(str::to_string),
+        })
+        .collect()
+}
+
+/// The result of a successful response, or the status its error maps to
+fn into_result(response: McpResponse) -> Result<Value, Status> {
+    match (response.result, response.error) {
+        (_, Some(error)) => Err(error_status(error)),
+        (Some(result), None) => Ok(result),
+        (None, None) => Ok(Value::Null),
+    }
+}
+
+/// gRPC status of an MCP error, keeping its code as metadata and its data as
+/// JSON details
+pub fn error_status(error: ErrorDetails) -> Status {
+    let code = match error.code {
+        ERROR_INVALID_PARAMS => Code::InvalidArgument,
+        ERROR_METHOD_NOT_FOUND => Code::NotFound,
+        ERROR_AUTH => Code::Unauthenticated,
+        ERROR_FORBIDDEN => Code::PermissionDenied,
+        ERROR_MISSING_CREDENTIAL | ERROR_READ_ONLY => Code::FailedPrecondition,
+        ERROR_TIMEOUT => Code::DeadlineExceeded,
+        ERROR_SERVER_BUSY | ERROR_BUDGET_EXCEEDED | ERROR_RESULT_TOO_LARGE => {
+            Code::ResourceExhausted
+        }
+        _ => Code::Internal,
+    };
+    let status = match &error.data {
+        Some(data) => Status::with_details(code, error.message, Bytes::from(data.to_string())),
+        None => Status::new(code, error.message),
+    };
+    with_error_code(status, error.code)
+}
+
+fn with_error_code(mut status: Status, code: i32) -> Status {
+    status
+        .metadata_mut()
+        .insert(ERROR_CODE_METADATA, MetadataValue::from(code));
+    status
+}
+
+/// Serve the gRPC service on `listener` until the process exits
+pub async fn serve(state: AppState, listener: TcpListener) -> anyhow::Result<()> {
+    tonic::transport::Server::builder()
+        .add_service(McpService::new(state))
+        .serve_with_incoming(TcpIncoming::from(listener))
+        .await?;
+    Ok(())
+}
+
+/// Client for the `mcp.v1.Mcp` service
+#[derive(Debug, Clone)]
+pub struct McpClient {
+    inner: tonic::client::Grpc<Channel>,
+}
+
+impl McpClient {
+    /// Connect to a server, e.g. `http://127.0.0.1:50051`
+    pub async fn connect(endpoint: String) -> Result<Self, tonic::transport::Error> {
+        let channel = tonic::transport::Endpoint::new(endpoint)?.connect().await?;
+        Ok(Self::new(channel))
+    }
+
+    pub fn new(channel: Channel) -> Self {
+        Self {
+            inner: tonic::client::Grpc::new(channel),
+        }
+    }
+
+    pub async fn discover(
+        &mut self,
+        request: impl IntoRequest<DiscoverRequest>,
+    ) -> Result<Response<DiscoverResponse>, Status> {
+        self.unary(request.into_request(), "/mcp.v1.Mcp/Discover")
+            .await
+    }
+
+    pub async fn invoke(
+        &mut self,
+        request: impl IntoRequest<InvokeRequest>,
+    ) -> Result<Response<InvokeResponse>, Status> {
+        self.unary(request.into_request(), "/mcp.v1.Mcp/Invoke")
+            .await
+    }
+
+    async fn unary<M1, M2>(
+        &mut self,
+        request: Request<M1>,
+        path: &'static str,
+    ) -> Result<Response<M2>, Status>
+    where
+        M1: prost::Message + Send + Sync + 'static,
+        M2: prost::Message + Default + Send + Sync + 'static,
+    {
+        self.inner
+            .ready()
+            .await
+            .map_err(|e| Status::unavailable(format!("Service was not ready: {}", e)))?;
+        self.inner
+            .unary(
+                request,
+                http::uri::PathAndQuery::from_static(path),
+                ProstCodec::default(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,75 @@
+use crate::audit::AuditStatus;
+use crate::config::HistoryConfig;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+/// Summary of a single tool invocation
+#[derive(Debug, Clone, Serialize)]
+pub struct InvocationRecord {
+    pub tool_name: String,
+    pub username: String,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    pub status: AuditStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Recent invocations kept in memory, one bounded ring buffer per user
+#[derive(Debug, Default)]
+pub struct InvocationHistory {
+    config: HistoryConfig,
+    entries: Mutex<HashMap<String, VecDeque<InvocationRecord>>>,
+}
+
+impl InvocationHistory {
+    pub fn new(config: HistoryConfig) -> Self {
+        Self {
+            config,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Record an invocation, evicting the user's oldest entry when full
+    pub fn record(&self, record: InvocationRecord) {
+        if !self.config.enabled || self.config.capacity == 0 {
+            return;
+        }
+
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let user_entries = entries.entry(record.username.clone()).or_default();
+        if user_entries.len() >= self.config.capacity {
+            user_entries.pop_front();
+        }
+        user_entries.push_back(record);
+    }
+
+    /// List a user's invocations, newest first
+    pub fn list(&self, username: &str, limit: Option<usize>) -> Vec<InvocationRecord> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries
+            .get(username)
+            .map(|user_entries| {
+                user_entries
+                    .iter()
+                    .rev()
+                    .take(limit.unwrap_or(usize::MAX))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// List invocations across all users, newest first
+    pub fn list_all(&self, limit: Option<usize>) -> Vec<InvocationRecord> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let mut all: Vec<InvocationRecord> = entries.values().flatten().cloned().collect();
+        all.sort_by_key(|r| Reverse(r.started_at));
+        all.truncate(limit.unwrap_or(usize::MAX));
+        all
+    }
+}
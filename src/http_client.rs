@@ -0,0 +1,289 @@
+//! Shared outbound HTTP client for tools
+//!
+//! One client per process, configured from `[http]`, so tools calling out
+//! reuse pooled connections and TLS sessions across invocations instead of
+//! building a client each time. Tools reach it through `ctx.http()`.
+//!
+//! Every request, and every redirect it follows, is checked against the
+//! `[http.egress]` host allowlist and denylist, so operators can constrain
+//! what tools call out to.
+//!
+//! Requests to private, loopback and link-local addresses are refused too
+//! (`block_private_addresses`), since tool arguments often come from a model
+//! that can be talked into fetching internal URLs. Host names are checked
+//! after resolution, on the addresses actually connected to, so a name that
+//! resolves to a public address when checked and a private one when used
+//! (DNS rebinding) cannot slip through.
+
+use crate::config::{EgressConfig, HttpConfig};
+use crate::policy::matches_pattern;
+use anyhow::{Context, Result, bail};
+use ipnet::IpNet;
+use reqwest::dns::{Name, Resolve, Resolving};
+use reqwest::redirect::{Attempt, Policy};
+use reqwest::{Certificate, Method, NoProxy, Proxy, RequestBuilder, Url};
+use std::fmt;
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Redirects followed before a request fails, as reqwest does by default
+const MAX_REDIRECTS: usize = 10;
+
+/// Cheap to clone: clones share the connection pool
+#[derive(Clone, Default)]
+pub struct HttpClient {
+    client: reqwest::Client,
+    egress: Arc<EgressPolicy>,
+}
+
+impl HttpClient {
+    pub fn new(config: &HttpConfig) -> Result<Self> {
+        let egress = Arc::new(EgressPolicy::new(&config.egress)?);
+        let redirects = egress.clone();
+        let mut builder = reqwest::Client::builder()
+            .user_agent(config.user_agent.as_str())
+            .connect_timeout(Duration::from_millis(config.connect_timeout_ms))
+            .pool_max_idle_per_host(config.pool_max_idle_per_host)
+            .pool_idle_timeout(Duration::from_secs(config.pool_idle_timeout_secs))
+            .danger_accept_invalid_certs(config.accept_invalid_certs)
+            .redirect(Policy::custom(move |attempt| redirects.follow(attempt)));
+        if config.timeout_ms > 0 {
+            builder = builder.timeout(Duration::from_millis(config.timeout_ms));
+        }
+        match &config.proxy {
+            Some(url) => {
+                let proxy = Proxy::all(url)
+                    .with_context(|| format!("Invalid http.proxy '{}'", url))?
+                    .no_proxy(NoProxy::from_string(&config.no_proxy.join(",")));
+                builder = builder.proxy(proxy);
+            }
+            // Through a proxy only the proxy's own name is resolved here, and
+            // it may well be private: the proxy has to guard what it resolves
+            None if egress.block_private => {
+                builder = builder.dns_resolver(Arc::new(GuardedResolver {
+                    egress: egress.clone(),
+                }));
+            }
+            None => {}
+        }
+        for path in &config.ca_certs {
+            let pem = fs::read(path)
+                .with_context(|| format!("Failed to read CA certificate {}", path.display()))?;
+            let certificates = Certificate::from_pem_bundle(&pem)
+                .with_context(|| format!("Invalid CA certificate {}", path.display()))?;
+            if certificates.is_empty() {
+                bail!("No certificate found in {}", path.display());
+            }
+            for certificate in certificates {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+        Ok(Self {
+            client: builder.build().context("Failed to build the HTTP client")?,
+            egress,
+        })
+    }
+
+    pub fn get(&self, url: &str) -> Result<RequestBuilder> {
+        self.request(Method::GET, url)
+    }
+
+    pub fn post(&self, url: &str) -> Result<RequestBuilder> {
+        self.request(Method::POST, url)
+    }
+
+    /// Start a request, failing with `EgressDenied` when the egress rules do
+    /// not allow its host; `RequestBuilder::timeout` overrides
+    /// `[http] timeout_ms`
+    pub fn request(&self, method: Method, url: &str) -> Result<RequestBuilder> {
+        let url = Url::parse(url).with_context(|| format!("Invalid URL '{}'", url))?;
+        self.egress.check(&url)?;
+        Ok(self.client.request(method, url))
+    }
+}
+
+/// Why an outbound request was refused
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EgressDenied {
+    pub host: String,
+    /// Denylist pattern that matched, absent when no allowlist entry did
+    pub rule: Option<String>,
+    /// Private address the host is or resolved to, when that is the reason
+    pub address: Option<IpAddr>,
+}
+
+impl fmt::Display for EgressDenied {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.rule, self.address) {
+            (_, Some(address)) => write!(
+                f,
+                "Egress to '{}' denied: {} is a private address",
+                self.host, address
+            ),
+            (Some(rule), None) => write!(f, "Egress to '{}' denied by '{}'", self.host, rule),
+            (None, None) => write!(f, "Egress to '{}' is not allowed", self.host),
+        }
+    }
+}
+
+impl std::error::Error for EgressDenied {}
+
+/// Hosts and addresses outbound requests may reach; allows everything when
+/// empty
+#[derive(Debug, Clone, Default)]
+pub struct EgressPolicy {
+    allow: Vec<String>,
+    deny: Vec<String>,
+    block_private: bool,
+    /// Private networks reachable despite `block_private`
+    private_allow: Vec<IpNet>,
+}
+
+impl EgressPolicy {
+    pub fn new(config: &EgressConfig) -> Result<Self> {
+        let lowercase = |patterns: &[String]| patterns.iter().map(|p| p.to_lowercase()).collect();
+        let private_allow = config
+            .private_allow
+            .iter()
+            .map(|entry| {
+                entry
+                    .parse::<IpNet>()
+                    .or_else(|_| entry.parse::<IpAddr>().map(IpNet::from))
+                    .with_context(|| format!("Invalid http.egress.private_allow '{}'", entry))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            allow: lowercase(&config.allow),
+            deny: lowercase(&config.deny),
+            block_private: config.block_private_addresses,
+            private_allow,
+        })
+    }
+
+    /// Whether a request may go to `url`'s host; denylist entries win
+    pub fn check(&self, url: &Url) -> Result<(), EgressDenied> {
+        let host = url
+            .host_str()
+            .unwrap_or_default()
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_lowercase();
+        let denied = |rule: Option<&String>| EgressDenied {
+            host: host.clone(),
+            rule: rule.cloned(),
+            address: None,
+        };
+        if let Some(rule) = self.deny.iter().find(|p| matches_pattern(p, &host)) {
+            return Err(denied(Some(rule)));
+        }
+        if host.is_empty()
+            || !self.allow.is_empty() && !self.allow.iter().any(|p| matches_pattern(p, &host))
+        {
+            return Err(denied(None));
+        }
+        // Names are checked once resolved, by `GuardedResolver`
+        match host.parse() {
+            Ok(address) => self.check_address(&host, address),
+            Err(_) => Ok(()),
+        }
+    }
+
+    /// Whether a connection to `address`, which `host` is or resolved to, is
+    /// allowed
+    pub fn check_address(&self, host: &str, address: IpAddr) -> Result<(), EgressDenied> {
+        let address = address.to_canonical();
+        if self.block_private
+            && is_private(address)
+            && !self.private_allow.iter().any(|net| net.contains(&address))
+        {
+            return Err(EgressDenied {
+                host: host.to_string(),
+                rule: None,
+                address: Some(address),
+            });
+        }
+        Ok(())
+    }
+
+    fn follow(&self, attempt: Attempt) -> reqwest::redirect::Action {
+        if attempt.previous().len() >= MAX_REDIRECTS {
+            return attempt.error("too many redirects");
+        }
+        match self.check(attempt.url()) {
+            Ok(()) => attempt.follow(),
+            Err(denied) => {
+                tracing::warn!(host = %denied.host, "Redirect refused: {}", denied);
+                attempt.error(denied)
+            }
+        }
+    }
+}
+
+/// Resolves host names, dropping the addresses `EgressPolicy` refuses so the
+/// connection can only use checked ones
+struct GuardedResolver {
+    egress: Arc<EgressPolicy>,
+}
+
+impl Resolve for GuardedResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let egress = self.egress.clone();
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let resolved: Vec<SocketAddr> =
+                tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+            let mut refused = None;
+            let allowed: Vec<SocketAddr> = resolved
+                .into_iter()
+                .filter(|addr| match egress.check_address(&host, addr.ip()) {
+                    Ok(()) => true,
+                    Err(denied) => {
+                        refused = Some(denied);
+                        false
+                    }
+                })
+                .collect();
+            match refused {
+                Some(denied) if allowed.is_empty() => {
+                    tracing::warn!(host = %host, "Request refused: {}", denied);
+                    Err(denied.into())
+                }
+                _ => Ok(Box::new(allowed.into_iter()) as _),
+            }
+        })
+    }
+}
+
+/// Loopback, private, link-local, shared (CGNAT), unspecified and other
+/// non-public addresses
+pub fn is_private(address: IpAddr) -> bool {
+    match address.to_canonical() {
+        IpAddr::V4(ip) => is_private_v4(ip),
+        IpAddr::V6(ip) => is_private_v6(ip),
+    }
+}
+
+fn is_private_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        // 0.0.0.0/8 "this network" and 100.64.0.0/10 shared address space
+        || a == 0
+        || a == 100 && (64..128).contains(&b)
+}
+
+fn is_private_v6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    ip.is_loopback()
+        || ip.is_unspecified()
+        || ip.is_multicast()
+        // fc00::/7 unique local and fe80::/10 link-local
+        || first & 0xfe00 == 0xfc00
+        || first & 0xffc0 == 0xfe80
+}
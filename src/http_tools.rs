@@ -0,0 +1,266 @@
+//! Declarative HTTP tools
+//!
+//! A plugin manifest with an `[http]` table instead of a `command` wraps an
+//! HTTP API without any Rust: the request is built from templates whose
+//! `{{...}}` placeholders are filled in from the invocation's arguments
+//! (`args.<name>`) and the user's credentials (`username`, `tenant`,
+//! `external_keys.<name>`), and sent through the shared HTTP client, so the
+//! `[http]` egress rules apply.
+//!
+//! Values placed in the URL are percent-encoded, so an argument cannot add
+//! path segments or query parameters. A query parameter or body string
+//! consisting of a single `args.<name>` placeholder is left out when the
+//! argument is missing, and in the body keeps the argument's JSON type.
+//!
+//! A `response` mapping reshapes JSON responses into the tool's result, see
+//! [`crate::extract`] for its path expressions.
+
+use crate::auth::UserCredentials;
+use crate::auth::templates::{TemplateError, expand, lookup};
+use crate::extract::{apply_mapping, check_mapping};
+use crate::tools::{InvalidInput, ToolContext};
+use anyhow::{Context, Result, anyhow, bail};
+use reqwest::Method;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Longest upstream error body quoted in a tool error
+const MAX_ERROR_BODY_CHARS: usize = 500;
+
+/// Request sent by an HTTP tool, with `{{...}}` placeholders
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HttpRequestTemplate {
+    #[serde(default = "default_method")]
+    pub method: String,
+    pub url: String,
+    #[serde(default)]
+    pub query: BTreeMap<String, String>,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// JSON body; placeholders are filled in within its strings
+    #[serde(default)]
+    pub body: Option<Value>,
+    /// Shape of the result, whose strings are paths into the JSON response;
+    /// the response is returned as it is when unset
+    #[serde(default)]
+    pub response: Option<Value>,
+}
+
+fn default_method() -> String {
+    "GET".to_string()
+}
+
+impl HttpRequestTemplate {
+    /// Check the method and the placeholders of every template
+    pub fn check(&self) -> Result<()> {
+        self.method()?;
+        if let Some(mapping) = &self.response {
+            check_mapping(mapping)?;
+        }
+        for variable in self.variables()? {
+            match variable.as_str() {
+                "username" | "tenant" => {}
+                other if other.starts_with("external_keys.") || other.starts_with("args.") => {}
+                other => bail!("Unknown template variable '{}'", other),
+            }
+        }
+        Ok(())
+    }
+
+    /// External keys the templates use, which every user of the tool needs
+    pub fn external_keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self
+            .variables()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|variable| variable.strip_prefix("external_keys."))
+            .map(str::to_string)
+            .collect();
+        keys.sort();
+        keys.dedup();
+        keys
+    }
+
+    fn method(&self) -> Result<Method> {
+        Method::from_bytes(self.method.to_ascii_uppercase().as_bytes())
+            .map_err(|_| anyhow!("Invalid HTTP method '{}'", self.method))
+    }
+
+    /// Every placeholder used, in order of appearance
+    fn variables(&self) -> Result<Vec<String>, TemplateError> {
+        let mut texts: Vec<&str> = vec![self.url.as_str()];
+        texts.extend(self.query.values().map(String::as_str));
+        texts.extend(self.headers.values().map(String::as_str));
+        if let Some(body) = &self.body {
+            collect_strings(body, &mut texts);
+        }
+
+        let mut variables = Vec::new();
+        for text in texts {
+            expand(text, |variable| {
+                variables.push(variable.to_string());
+                Ok(String::new())
+            })?;
+        }
+        Ok(variables)
+    }
+}
+
+fn collect_strings<'a>(value: &'a Value, texts: &mut Vec<&'a str>) {
+    match value {
+        Value::String(text) => texts.push(text),
+        Value::Array(items) => items.iter().for_each(|item| collect_strings(item, texts)),
+        Value::Object(map) => map.values().for_each(|item| collect_strings(item, texts)),
+        _ => {}
+    }
+}
+
+/// What placeholders are filled in from: the arguments and the credentials
+struct Variables<'a> {
+    arguments: &'a Map<String, Value>,
+    user: &'a UserCredentials,
+}
+
+impl Variables<'_> {
+    /// The argument a template consisting of only `{{args.<name>}}` names
+    fn sole_argument(text: &str) -> Option<&str> {
+        let inner = text.trim().strip_prefix("{{")?.strip_suffix("}}")?;
+        if inner.contains("{{") || inner.contains("}}") {
+            return None;
+        }
+        inner.trim().strip_prefix("args.")
+    }
+
+    fn argument(&self, name: &str) -> Option<&Value> {
+        self.arguments.get(name).filter(|value| !value.is_null())
+    }
+
+    /// Fill in a template, passing every value through `encode`
+    fn render(&self, text: &str, encode: fn(&str) -> String) -> Result<String> {
+        let mut missing = None;
+        let rendered = expand(text, |variable| match variable.strip_prefix("args.") {
+            Some(name) => match self.argument(name) {
+                Some(Value::String(value)) => Ok(encode(value)),
+                Some(value) => Ok(encode(&value.to_string())),
+                None => {
+                    missing.get_or_insert_with(|| name.to_string());
+                    Ok(String::new())
+                }
+            },
+            None => lookup(variable, self.user).map(|value| encode(&value)),
+        })?;
+        if let Some(name) = missing {
+            return Err(InvalidInput::new(format!("missing argument '{}'", name)).into());
+        }
+        Ok(rendered)
+    }
+
+    /// Fill in the strings of a JSON body; `None` when it is a sole
+    /// placeholder of a missing argument
+    fn render_json(&self, template: &Value) -> Result<Option<Value>> {
+        Ok(Some(match template {
+            Value::String(text) => match Self::sole_argument(text) {
+                Some(name) => match self.argument(name) {
+                    Some(value) => value.clone(),
+                    None => return Ok(None),
+                },
+                None => Value::String(self.render(text, str::to_string)?),
+            },
+            Value::Array(items) => {
+                let mut rendered = Vec::with_capacity(items.len());
+                for item in items {
+                    rendered.extend(self.render_json(item)?);
+                }
+                Value::Array(rendered)
+            }
+            Value::Object(map) => {
+                let mut rendered = Map::new();
+                for (key, item) in map {
+                    if let Some(item) = self.render_json(item)? {
+                        rendered.insert(key.clone(), item);
+                    }
+                }
+                Value::Object(rendered)
+            }
+            other => other.clone(),
+        }))
+    }
+}
+
+/// Percent-encode everything but the characters unreserved in URLs
+pub fn encode_url_component(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// Send the tool's request and return the response, parsed when it is JSON
+pub async fn run_http_tool(
+    name: &str,
+    template: &HttpRequestTemplate,
+    timeout: Duration,
+    args: Option<Value>,
+    ctx: &ToolContext,
+) -> Result<Value> {
+    let arguments = match args {
+        Some(Value::Object(arguments)) => arguments,
+        _ => Map::new(),
+    };
+    let variables = Variables {
+        arguments: &arguments,
+        user: ctx.user().credentials(),
+    };
+
+    let url = variables.render(&template.url, encode_url_component)?;
+    let mut query = Vec::new();
+    for (key, value) in &template.query {
+        if let Some(argument) = Variables::sole_argument(value)
+            && variables.argument(argument).is_none()
+        {
+            continue;
+        }
+        query.push((key, variables.render(value, str::to_string)?));
+    }
+    let mut request = ctx
+        .http()
+        .request(template.method()?, &url)?
+        .timeout(timeout)
+        .query(&query);
+    for (header, value) in &template.headers {
+        request = request.header(header, variables.render(value, str::to_string)?);
+    }
+    if let Some(body) = &template.body {
+        request = request.json(&variables.render_json(body)?.unwrap_or(Value::Null));
+    }
+
+    let response = request
+        .send()
+        .await
+        .with_context(|| format!("HTTP tool '{}' request failed", name))?;
+    let status = response.status();
+    let text = response
+        .text()
+        .await
+        .with_context(|| format!("HTTP tool '{}' failed to read the response", name))?;
+    if !status.is_success() {
+        let excerpt: String = text.trim().chars().take(MAX_ERROR_BODY_CHARS).collect();
+        bail!("HTTP tool '{}' got {}: {}", name, status, excerpt);
+    }
+
+    let Some(mapping) = &template.response else {
+        return Ok(serde_json::from_str(&text)
+            .unwrap_or_else(|_| Value::String(text.trim_end().to_string())));
+    };
+    let document: Value = serde_json::from_str(&text)
+        .with_context(|| format!("HTTP tool '{}' expected a JSON response to map", name))?;
+    apply_mapping(mapping, &document)
+}
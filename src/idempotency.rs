@@ -0,0 +1,105 @@
+use crate::store::SharedStore;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+
+/// State of an idempotency key in the store
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+enum Entry {
+    /// An invocation with this key is running
+    Pending { tool_name: String },
+    /// The invocation succeeded; retries get this result
+    Completed { tool_name: String, result: Value },
+}
+
+/// Outcome of claiming an idempotency key
+#[derive(Debug, PartialEq)]
+pub enum Claim {
+    /// First use of the key: run the tool, then `complete` or `release`
+    Acquired,
+    /// The key already produced this result
+    Completed(Value),
+    /// Another invocation with the key has not finished yet
+    InProgress,
+    /// The key was used for a different tool
+    Conflict { tool_name: String },
+}
+
+/// Results of invocations sent with an `idempotency_key`, kept in the shared
+/// store so retries after a restart or on another replica are not re-executed
+#[derive(Clone, Default)]
+pub struct IdempotencyCache {
+    store: SharedStore,
+    ttl: Option<Duration>,
+    pending_ttl: Option<Duration>,
+}
+
+impl IdempotencyCache {
+    /// `ttl` bounds how long results are replayed and `pending_ttl` how long a key
+    /// stays locked if its invocation never finishes (0 = no expiry)
+    pub fn new(store: SharedStore, ttl: Duration, pending_ttl: Duration) -> Self {
+        Self {
+            store,
+            ttl: (!ttl.is_zero()).then_some(ttl),
+            pending_ttl: (!pending_ttl.is_zero()).then_some(pending_ttl),
+        }
+    }
+
+    fn key(username: &str, key: &str) -> String {
+        format!("idempotency:{}:{}", username, key)
+    }
+
+    /// Lock a key for an invocation, or report what it is already bound to
+    pub async fn claim(&self, username: &str, key: &str, tool_name: &str) -> Result<Claim> {
+        let store_key = Self::key(username, key);
+        let pending = serde_json::to_string(&Entry::Pending {
+            tool_name: tool_name.to_string(),
+        })?;
+        loop {
+            if self
+                .store
+                .set_if_absent(&store_key, pending.clone(), self.pending_ttl)
+                .await?
+            {
+                return Ok(Claim::Acquired);
+            }
+            // Expired between the two calls: try to claim it again
+            let Some(existing) = self.store.get(&store_key).await? else {
+                continue;
+            };
+            return Ok(match serde_json::from_str(&existing)? {
+                Entry::Pending { tool_name: claimed }
+                | Entry::Completed {
+                    tool_name: claimed, ..
+                } if claimed != tool_name => Claim::Conflict { tool_name: claimed },
+                Entry::Pending { .. } => Claim::InProgress,
+                Entry::Completed { result, .. } => Claim::Completed(result),
+            });
+        }
+    }
+
+    /// Store the result of a successful invocation for later retries
+    pub async fn complete(
+        &self,
+        username: &str,
+        key: &str,
+        tool_name: &str,
+        result: &Value,
+    ) -> Result<()> {
+        let entry = serde_json::to_string(&Entry::Completed {
+            tool_name: tool_name.to_string(),
+            result: result.clone(),
+        })?;
+        self.store
+            .set(&Self::key(username, key), entry, self.ttl)
+            .await
+    }
+
+    /// Unlock a key after a failed invocation, so a retry runs the tool again
+    pub async fn release(&self, username: &str, key: &str) -> Result<()> {
+        self.store.delete(&Self::key(username, key)).await?;
+        Ok(())
+    }
+}
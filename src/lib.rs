@@ -1,20 +1,109 @@
+use arc_swap::ArcSwap;
 use axum::{
-    extract::State,
-    http::StatusCode,
-    response::Json,
+    extract::{DefaultBodyLimit, FromRef, Multipart, Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Json, Response,
+    },
     routing::{get, post},
     Extension, Router,
 };
 use serde::{Deserialize, Serialize};
-use serde_json::{json, Value};
+use serde_json::{json, Map, Value};
 use std::collections::HashMap;
+use std::convert::Infallible;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast::error::RecvError, mpsc};
+use tokio_stream::{wrappers::UnboundedReceiverStream, StreamExt};
+use tokio_util::sync::CancellationToken;
 
+pub mod aliases;
+pub mod audit;
 pub mod auth;
+pub mod budget;
+pub mod canary;
+pub mod client_ip;
+pub mod config;
+pub mod database;
+pub mod decompression;
+pub mod deprecation;
+pub mod drain;
+pub mod elicitation;
+pub mod encoding;
+pub mod external_keys;
+pub mod extract;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod history;
+pub mod http_client;
+pub mod http_tools;
+pub mod idempotency;
+pub mod listeners;
+pub mod logging;
+pub mod metrics;
+pub mod notifications;
+pub mod plugins;
+pub mod policy;
+pub mod pools;
+pub mod read_only;
+pub mod replay;
+pub mod resources;
+pub mod response_cache;
+pub mod scheduler;
+pub mod session;
+pub mod signing;
+pub mod startup;
+pub mod store;
+pub mod tenant;
 pub mod tools;
+pub mod uploads;
+pub mod usage;
 
-use auth::{AuthLayer, AuthenticatedUser, CredentialsStore};
-use tools::{initialize_all_tools, ToolFunction};
+use audit::{AuditRecord, AuditStatus};
+use auth::templates::TemplateError;
+use auth::{
+    load_credentials, AuthCache, AuthError, AuthLayer, AuthProvider, AuthenticatedUser,
+    Authenticator, CredentialsStore, StaticAuthProvider, TokenSources,
+};
+use budget::{Budgets, DEFAULT_TOOL_COST};
+use canary::{CanaryRouter, Variant};
+use chrono::Utc;
+use client_ip::{resolve_client_ip, ClientIp, TrustedProxies};
+use config::{OversizedResult, ServerConfig};
+use database::Storage;
+use decompression::decompress_request;
+use deprecation::deprecation_warning;
+use drain::DrainController;
+use elicitation::{ElicitationResponse, PendingElicitations};
+use encoding::{DecodeLimits, Encoded, McpBody, WireFormat};
+use external_keys::check_external_keys;
+use history::{InvocationHistory, InvocationRecord};
+use http_client::HttpClient;
+use idempotency::{Claim, IdempotencyCache};
+use logging::LogLevel;
+use metrics::Metrics;
+use notifications::Notifier;
+use plugins::{reload_plugins, watch_plugins};
+use policy::{AuthorizationRequest, Authorizer, OpaAuthorizer, PolicyEngine};
+use read_only::ReadOnlyMode;
+use replay::{replay_protection, ReplayGuard};
+use resources::ResourceStore;
+use response_cache::{cache_ttl, etag_matches, ResponseCache};
+use scheduler::InvocationScheduler;
+use session::{ClientInfo, Session, SessionManager, SessionTurn, SESSION_HEADER};
+use signing::verify_signature;
+use store::{DistributedStore, SharedStore};
+use tenant::{apply_tenant_config, build_tenant_tool_sets, ToolSet};
+use tools::content::check_binary_size;
+use tools::context::PARTIAL_RESULT_METHOD;
+use tools::health::{check_tools, overall_status};
+use tools::sanitize::{is_rejected, ArgumentSanitizer, SanitizerChain};
+use tools::transform::{truncate_to_size, ResultTransformer, TransformerChain};
+use tools::{initialize_tools, HealthCheck, HealthStatus, InvalidInput, ToolContext, ToolFunction};
+use uploads::UploadStore;
+use usage::{parse_time, to_csv, UsageCounts, UsageRecorder};
 
 // ============================================================================
 // Error Codes (JSON-RPC 2.0)
@@ -23,8 +112,19 @@ use tools::{initialize_all_tools, ToolFunction};
 pub const ERROR_AUTH: i32 = -32001;
 pub const ERROR_INVALID_PARAMS: i32 = -32002;
 pub const ERROR_TOOL_EXECUTION: i32 = -32003;
+pub const ERROR_SERVER_BUSY: i32 = -32005;
+pub const ERROR_MISSING_CREDENTIAL: i32 = -32006;
+pub const ERROR_READ_ONLY: i32 = -32007;
+pub const ERROR_FORBIDDEN: i32 = -32008;
+pub const ERROR_BUDGET_EXCEEDED: i32 = -32009;
+pub const ERROR_RESULT_TOO_LARGE: i32 = -32010;
+pub const ERROR_TIMEOUT: i32 = -32011;
 pub const ERROR_INVALID_REQUEST: i32 = -32600;
 pub const ERROR_METHOD_NOT_FOUND: i32 = -32601;
+pub const ERROR_INTERNAL: i32 = -32603;
+
+/// MCP protocol version answered to clients that do not request one
+pub const PROTOCOL_VERSION: &str = "2025-06-18";
 
 // ============================================================================
 // Request/Response Types
@@ -34,13 +134,96 @@ pub const ERROR_METHOD_NOT_FOUND: i32 = -32601;
 #[derive(Debug, Deserialize)]
 #[serde(tag = "method", content = "params")]
 pub enum McpRequest {
+    #[serde(rename = "initialize")]
+    Initialize(Option<InitializeParams>),
     #[serde(rename = "discover")]
     Discover,
     #[serde(rename = "invoke")]
     Invoke {
         tool_name: String,
         arguments: Option<Value>,
+        #[serde(flatten)]
+        options: InvokeOptions,
     },
+    #[serde(rename = "history/list")]
+    HistoryList(Option<HistoryListParams>),
+    #[serde(rename = "elicitation/respond")]
+    ElicitationRespond(ElicitationRespondParams),
+    #[serde(rename = "resources/read")]
+    ResourcesRead(ResourcesReadParams),
+    #[serde(rename = "usage")]
+    Usage,
+    #[serde(rename = "logging/setLevel")]
+    LoggingSetLevel(SetLevelParams),
+}
+
+/// JSON-RPC envelope around an MCP request
+/// `jsonrpc` and `id` are optional unless `[jsonrpc] strict` is enabled
+#[derive(Debug, Deserialize)]
+pub struct McpEnvelope {
+    #[serde(default)]
+    pub jsonrpc: Option<String>,
+    /// Request id, echoed in the response
+    #[serde(default)]
+    pub id: Option<Value>,
+    #[serde(flatten)]
+    pub request: McpRequest,
+}
+
+/// Params for `initialize`
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InitializeParams {
+    pub protocol_version: Option<String>,
+    /// Client name and version, recorded in the session
+    pub client_info: Option<ClientInfo>,
+    pub capabilities: Option<Value>,
+    /// Run the session's invocations one at a time, in the order they arrive
+    #[serde(default)]
+    pub ordered_invocations: bool,
+}
+
+/// Params for `logging/setLevel`
+#[derive(Debug, Deserialize)]
+pub struct SetLevelParams {
+    pub level: LogLevel,
+}
+
+/// Params for `resources/read`
+#[derive(Debug, Deserialize)]
+pub struct ResourcesReadParams {
+    pub uri: String,
+}
+
+/// Params for `elicitation/respond`: the client's answer to `elicitation/create`
+#[derive(Debug, Deserialize)]
+pub struct ElicitationRespondParams {
+    pub elicitation_id: String,
+    #[serde(flatten)]
+    pub response: ElicitationResponse,
+}
+
+/// Optional invoke params beyond the tool name and arguments
+#[derive(Debug, Default, Deserialize)]
+pub struct InvokeOptions {
+    /// Scheduling priority, capped at the user's configured priority
+    pub priority: Option<i32>,
+    /// Request metadata (progress tokens, client correlation data), passed to the tool
+    #[serde(rename = "_meta")]
+    pub meta: Option<Map<String, Value>>,
+    /// Retries with the same key get the first successful result instead of
+    /// running the tool again
+    pub idempotency_key: Option<String>,
+    /// Time limit requested by the client, capped by the tool's and the
+    /// server's limits
+    pub timeout_ms: Option<u64>,
+}
+
+/// Params for `history/list`
+#[derive(Debug, Default, Deserialize)]
+pub struct HistoryListParams {
+    /// Maximum number of entries to return (newest first)
+    pub limit: Option<usize>,
 }
 
 /// MCP response structure
@@ -51,6 +234,12 @@ pub struct McpResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<ErrorDetails>,
     pub jsonrpc: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Value>,
+    /// Invocation details (timing, tool version, ...) when `results.include_meta`
+    /// is set, and `warnings` such as deprecation notices
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Value>,
 }
 
 impl McpResponse {
@@ -60,6 +249,8 @@ impl McpResponse {
             result: Some(result),
             error: None,
             jsonrpc: "2.0".to_string(),
+            id: None,
+            meta: None,
         }
     }
 
@@ -73,8 +264,22 @@ impl McpResponse {
                 data,
             }),
             jsonrpc: "2.0".to_string(),
+            id: None,
+            meta: None,
         }
     }
+
+    /// Set the id of the request this response answers
+    pub fn with_id(mut self, id: Option<Value>) -> Self {
+        self.id = id;
+        self
+    }
+
+    /// Attach invocation metadata
+    pub fn with_meta(mut self, meta: Value) -> Self {
+        self.meta = Some(meta);
+        self
+    }
 }
 
 /// Error details for JSON-RPC responses
@@ -87,84 +292,1553 @@ pub struct ErrorDetails {
 }
 
 /// Tool definition for discovery
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, Default)]
 pub struct ToolDefinition {
     pub name: String,
     pub description: String,
     pub parameters: Value,
+    /// JSON Schema of the result, when the tool declares one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<Value>,
+    /// Behaviour hints such as `readOnlyHint` or `destructiveHint`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Budget units an invocation consumes, `budget::DEFAULT_TOOL_COST` when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost: Option<u64>,
+    /// Longest an invocation may run in milliseconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    /// External keys a user needs to run the tool
+    #[serde(skip)]
+    pub required_external_keys: Vec<String>,
+    #[serde(skip)]
+    pub health_check: Option<HealthCheck>,
 }
 
+/// Format version of the `GET /schema` bundle, bumped on incompatible changes
+pub const SCHEMA_BUNDLE_VERSION: u32 = 1;
+
 // ============================================================================
 // Application State
 // ============================================================================
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct AppState {
-    pub tool_registry: Arc<HashMap<String, ToolFunction>>,
-    pub tool_definitions: Arc<Vec<ToolDefinition>>,
+    /// Registered tools; swapped as a whole when tools are reloaded
+    pub tool_registry: Arc<ArcSwap<HashMap<String, ToolFunction>>>,
+    pub tool_definitions: Arc<ArcSwap<Vec<ToolDefinition>>>,
+    pub config: Arc<ServerConfig>,
+    pub sanitizers: Arc<SanitizerChain>,
+    pub result_transformers: Arc<TransformerChain>,
+    pub history: Arc<InvocationHistory>,
+    pub metrics: Arc<Metrics>,
+    pub scheduler: Arc<InvocationScheduler>,
+    /// Per-tenant tool sets; users without a tenant see the global registry
+    pub tenant_tools: Arc<HashMap<String, ToolSet>>,
+    /// State shared between replicas (rate limits, idempotency keys, sessions, jobs)
+    pub store: SharedStore,
+    pub canary: Arc<CanaryRouter>,
+    /// Elicitation requests waiting for the client's answer
+    pub elicitations: Arc<PendingElicitations>,
+    /// File-backed credentials, `None` when a custom auth provider is used
+    pub credentials: Option<Arc<StaticAuthProvider>>,
+    pub auth_cache: Arc<AuthCache>,
+    /// Authentication applied by `mcp_router`; reuse it to protect custom routes
+    pub auth: AuthLayer,
+    pub sessions: SessionManager,
+    /// Offloaded tool results, readable with `resources/read`
+    pub resources: ResourceStore,
+    /// Files uploaded through `POST /uploads`, available to tools by handle
+    pub uploads: UploadStore,
+    pub drain: Arc<DrainController>,
+    pub read_only: Arc<ReadOnlyMode>,
+    /// Notifications for clients listening on `GET /mcp`
+    pub notifications: Notifier,
+    pub idempotency: IdempotencyCache,
+    /// Reverse proxies trusted to report the client address
+    pub trusted_proxies: Arc<TrustedProxies>,
+    /// Role-based authorization checked before every invocation
+    pub policies: Arc<PolicyEngine>,
+    /// External policy engine consulted after `policies` allowed an invocation
+    pub authorizer: Option<Arc<dyn Authorizer>>,
+    /// Spending against the users' cost budgets
+    pub budgets: Budgets,
+    /// Outbound HTTP client shared by tools
+    pub http: HttpClient,
+    /// Results of tools opted into `[response_cache]`
+    pub response_cache: ResponseCache,
+    /// Per-user, per-tool usage reported by `GET /admin/usage`
+    pub usage: Arc<UsageRecorder>,
+    /// Shared embedded database (`[database]`), disabled unless configured
+    pub storage: Storage,
+}
+
+impl AppState {
+    /// Resolve the tool set visible to a user
+    pub fn tools_for(&self, user: &AuthenticatedUser) -> ToolSet {
+        user.tenant()
+            .and_then(|tenant| self.tenant_tools.get(tenant))
+            .cloned()
+            .unwrap_or_else(|| ToolSet {
+                functions: self.tool_registry.load_full(),
+                definitions: self.tool_definitions.load_full(),
+            })
+    }
+
+    /// Resolve the tool a user invoking `tool_name` runs, following
+    /// `[tools.aliases]`
+    pub fn resolve_tool_name(&self, user: &AuthenticatedUser, tool_name: String) -> String {
+        aliases::resolve(&self.config.tools.aliases, &self.tools_for(user), tool_name)
+    }
+
+    /// Atomically replace the global tool registry and its definitions
+    /// In-flight invocations keep the snapshot they started with
+    pub fn replace_tools(
+        &self,
+        registry: HashMap<String, ToolFunction>,
+        definitions: Vec<ToolDefinition>,
+    ) {
+        self.tool_registry.store(Arc::new(registry));
+        self.tool_definitions.store(Arc::new(definitions));
+    }
+
+    /// Report users lacking external keys required by the tools they can see
+    /// Fails instead of warning when `tools.strict_external_keys` is set
+    pub fn check_external_keys(&self, credentials: &CredentialsStore) -> anyhow::Result<()> {
+        check_external_keys(
+            credentials,
+            &self.tool_definitions.load(),
+            &self.tenant_tools,
+            self.config.tools.strict_external_keys,
+        )
+    }
+}
+
+impl FromRef<AppState> for DecodeLimits {
+    fn from_ref(state: &AppState) -> Self {
+        Self {
+            max_depth: state.config.request_body.max_depth,
+            max_object_keys: state.config.request_body.max_object_keys,
+        }
+    }
+}
+
+/// Options for building the application: configuration plus pluggable hooks
+#[derive(Clone, Default)]
+pub struct AppOptions {
+    pub config: ServerConfig,
+    /// Custom sanitizers, applied after the built-in ones
+    pub sanitizers: Vec<Arc<dyn ArgumentSanitizer>>,
+    /// Result post-processing hooks, applied in registration order
+    pub result_transformers: Vec<Arc<dyn ResultTransformer>>,
+    /// Custom shared state backend, overriding `[store]` in the config
+    pub store: Option<Arc<dyn DistributedStore>>,
+    /// Custom auth provider replacing the credentials file (DB, JWKS, Vault, ...)
+    pub auth_provider: Option<Arc<dyn AuthProvider>>,
+    /// External policy engine, overriding `[policies.opa]` in the config
+    pub authorizer: Option<Arc<dyn Authorizer>>,
+}
+
+impl AppOptions {
+    /// Create options from a loaded configuration with no custom hooks
+    pub fn new(config: ServerConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    /// Register a custom argument sanitizer
+    pub fn with_sanitizer(mut self, sanitizer: impl ArgumentSanitizer + 'static) -> Self {
+        self.sanitizers.push(Arc::new(sanitizer));
+        self
+    }
+
+    /// Register a result transformer
+    pub fn with_result_transformer(
+        mut self,
+        transformer: impl ResultTransformer + 'static,
+    ) -> Self {
+        self.result_transformers.push(Arc::new(transformer));
+        self
+    }
+
+    /// Authenticate against a custom provider instead of the credentials file
+    pub fn with_auth_provider(mut self, provider: impl AuthProvider + 'static) -> Self {
+        self.auth_provider = Some(Arc::new(provider));
+        self
+    }
+
+    /// Delegate tool-access decisions to an external policy engine
+    pub fn with_authorizer(mut self, authorizer: impl Authorizer + 'static) -> Self {
+        self.authorizer = Some(Arc::new(authorizer));
+        self
+    }
+
+    /// Use a custom shared state backend
+    pub fn with_store(mut self, store: impl DistributedStore + 'static) -> Self {
+        self.store = Some(Arc::new(store));
+        self
+    }
 }
 
 // ============================================================================
 // Request Handler
 // ============================================================================
 
+/// `POST /mcp` entry point
+/// Invocations requested with `Accept: text/event-stream` are streamed, so the
+/// tool can send requests (e.g. `elicitation/create`) back to the client
+/// Bodies may also be MessagePack or CBOR, answered in the negotiated format
+pub async fn mcp_endpoint(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    client_ip: Option<Extension<ClientIp>>,
+    headers: HeaderMap,
+    McpBody { body, format }: McpBody,
+) -> Response {
+    let envelope = match parse_envelope(body, state.config.jsonrpc.strict) {
+        Ok(envelope) => envelope,
+        Err(rejection) => return rejection.into_response(format),
+    };
+    let id = envelope.id;
+
+    let wants_stream = headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains("text/event-stream"));
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+
+    if let McpRequest::Initialize(params) = envelope.request {
+        let response =
+            initialize_session(&state, &user, params.unwrap_or_default(), user_agent).await;
+        let session_id = response
+            .result
+            .as_ref()
+            .and_then(|result| result.get("sessionId"))
+            .and_then(Value::as_str)
+            .and_then(|id| HeaderValue::from_str(id).ok());
+        let mut response = Encoded(format, response.with_id(id)).into_response();
+        if let Some(session_id) = session_id {
+            response.headers_mut().insert(SESSION_HEADER, session_id);
+        }
+        return response;
+    }
+
+    // Requests within a session carry the client info captured at initialize
+    let session = match headers.get(SESSION_HEADER) {
+        Some(session_id) => {
+            let session_id = session_id.to_str().unwrap_or_default();
+            match state
+                .sessions
+                .get(session_id, &user.credentials().username)
+                .await
+            {
+                Ok(Some(session)) => Some(session),
+                Ok(None) => {
+                    let response = McpResponse::error(
+                        ERROR_INVALID_REQUEST,
+                        "Invalid request: unknown or expired session".to_string(),
+                        None,
+                    );
+                    return (StatusCode::NOT_FOUND, Encoded(format, response.with_id(id))).into_response();
+                }
+                Err(e) => {
+                    let response = McpResponse::error(
+                        ERROR_INTERNAL,
+                        format!("Failed to load session: {:#}", e),
+                        None,
+                    );
+                    return Encoded(format, response.with_id(id)).into_response();
+                }
+            }
+        }
+        None => None,
+    };
+    let mut client = match &session {
+        Some(session) => Some(session.client.clone()),
+        None => user_agent.map(|user_agent| ClientInfo {
+            user_agent: Some(user_agent),
+            ..ClientInfo::default()
+        }),
+    };
+    if let Some(Extension(ClientIp(ip))) = client_ip {
+        client.get_or_insert_with(ClientInfo::default).ip = Some(ip);
+    }
+
+    // Discover and cacheable invoke responses can be revalidated with `ETag`s
+    let tagged = state.config.response_cache.enabled
+        && match &envelope.request {
+            McpRequest::Discover => true,
+            McpRequest::Invoke { tool_name, .. } => {
+                let tool_name = state.resolve_tool_name(&user, tool_name.clone());
+                state
+                    .tools_for(&user)
+                    .definitions
+                    .iter()
+                    .any(|def| def.name == tool_name && cache_ttl(def).is_some())
+            }
+            _ => false,
+        };
+
+    // Ordered sessions queue their invocations until earlier ones finished
+    let turn = match (&session, &envelope.request) {
+        (Some(session), McpRequest::Invoke { .. }) => state.sessions.acquire_turn(session).await,
+        _ => None,
+    };
+
+    match envelope.request {
+        McpRequest::Invoke {
+            tool_name,
+            arguments,
+            options,
+        } if wants_stream => {
+            let log_level = session
+                .and_then(|session| session.log_level)
+                .unwrap_or(state.config.logging.level);
+            let ctx = ToolContext::new(user)
+                .with_client(client)
+                .with_log_level(log_level);
+            stream_invocation(state, ctx, tool_name, arguments, options, id, turn)
+        }
+        McpRequest::LoggingSetLevel(params) => {
+            let response = set_log_level(&state, session.as_ref(), params.level).await;
+            Encoded(format, response.with_id(id)).into_response()
+        }
+        payload => {
+            let response = dispatch(&state, user, client, payload).await;
+            drop(turn);
+            let etag = response
+                .result
+                .as_ref()
+                .filter(|_| tagged)
+                .map(response_cache::etag);
+            let Some(etag) = etag else {
+                return Encoded(format, response.with_id(id)).into_response();
+            };
+            let etag_header = HeaderValue::from_str(&etag).expect("entity tags are ASCII");
+            let not_modified = headers
+                .get(header::IF_NONE_MATCH)
+                .and_then(|v| v.to_str().ok())
+                .is_some_and(|v| etag_matches(v, &etag));
+            let mut response = if not_modified {
+                StatusCode::NOT_MODIFIED.into_response()
+            } else {
+                Encoded(format, response.with_id(id)).into_response()
+            };
+            response.headers_mut().insert(header::ETAG, etag_header);
+            response
+        }
+    }
+}
+
+/// Parse the request envelope
+/// Lenient mode ignores `jsonrpc` and `id` and rejects undecodable bodies with
+/// 422 like a plain JSON extractor; strict mode answers every violation with -32600
+fn parse_envelope(body: Value, strict: bool) -> Result<McpEnvelope, EnvelopeRejection> {
+    // Only a string or integer id can be echoed in a strict-mode rejection
+    let id = body
+        .get("id")
+        .filter(|id| id.is_string() || id.is_i64() || id.is_u64())
+        .cloned();
+
+    if strict {
+        if body.get("jsonrpc").and_then(|v| v.as_str()) != Some("2.0") {
+            return Err(EnvelopeRejection::InvalidRequest(
+                "\"jsonrpc\" must be \"2.0\"".to_string(),
+                id,
+            ));
+        }
+        if id.is_none() {
+            return Err(EnvelopeRejection::InvalidRequest(
+                "\"id\" must be a string or an integer".to_string(),
+                None,
+            ));
+        }
+    }
+
+    serde_json::from_value(body).map_err(|e| {
+        if strict {
+            EnvelopeRejection::InvalidRequest(e.to_string(), id)
+        } else {
+            EnvelopeRejection::Unprocessable(e.to_string())
+        }
+    })
+}
+
+/// Why a request envelope was rejected
+enum EnvelopeRejection {
+    /// Strict mode violation, answered with a JSON-RPC -32600 error
+    InvalidRequest(String, Option<Value>),
+    /// Lenient mode decode failure, answered with HTTP 422
+    Unprocessable(String),
+}
+
+impl EnvelopeRejection {
+    fn into_response(self, format: WireFormat) -> Response {
+        match self {
+            EnvelopeRejection::InvalidRequest(message, id) => Encoded(
+                format,
+                McpResponse::error(
+                    ERROR_INVALID_REQUEST,
+                    format!("Invalid request: {}", message),
+                    None,
+                )
+                .with_id(id),
+            )
+            .into_response(),
+            EnvelopeRejection::Unprocessable(message) => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!(
+                    "Failed to deserialize the {} body into the target type: {}",
+                    format.name(),
+                    message
+                ),
+            )
+                .into_response(),
+        }
+    }
+}
+
+/// `GET /mcp`: server-sent stream of notifications such as
+/// `notifications/tools/list_changed`
+pub async fn notifications_stream(State(state): State<AppState>) -> Response {
+    let mut notifications = state.notifications.subscribe();
+    let (tx, rx) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        loop {
+            match notifications.recv().await {
+                Ok(message) => {
+                    if tx.send(message).is_err() {
+                        break;
+                    }
+                }
+                // A slow listener only misses notifications it can recover from
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+
+    let events = UnboundedReceiverStream::new(rx)
+        .map(|message| Ok::<_, Infallible>(Event::default().data(message.to_string())));
+    Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+/// Server-sent event carrying `message`: `partial` for partial results,
+/// `result` for the final response and unnamed for other server messages
+fn invocation_event(message: Value) -> Event {
+    let event = Event::default().data(message.to_string());
+    match message.get("method").and_then(Value::as_str) {
+        Some(PARTIAL_RESULT_METHOD) => event.event("partial"),
+        Some(_) => event,
+        None => event.event("result"),
+    }
+}
+
+/// Run an invocation in the background, streaming server requests, partial
+/// results and the final response as server-sent events
+fn stream_invocation(
+    state: AppState,
+    ctx: ToolContext,
+    tool_name: String,
+    arguments: Option<Value>,
+    options: InvokeOptions,
+    id: Option<Value>,
+    turn: Option<SessionTurn>,
+) -> Response {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let cancellation = CancellationToken::new();
+    let ctx = ctx
+        .with_client_channel(
+            tx.clone(),
+            state.elicitations.clone(),
+            Duration::from_secs(state.config.elicitation.timeout_secs),
+        )
+        .with_cancellation(cancellation.clone());
+
+    // The event stream is dropped when the client disconnects
+    let disconnect = {
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            tx.closed().await;
+            cancellation.cancel();
+        })
+    };
+
+    tokio::spawn(async move {
+        let response = invoke_tool(&state, ctx, tool_name, arguments, options).await;
+        disconnect.abort();
+        drop(turn);
+        let _ = tx.send(json!(response.with_id(id)));
+    });
+
+    // The stream ends once the invocation finished and every sender is dropped
+    let events = UnboundedReceiverStream::new(rx)
+        .map(|message| Ok::<_, Infallible>(invocation_event(message)));
+    Sse::new(events).into_response()
+}
+
 /// Main MCP request handler
 pub async fn handle_mcp_request(
     State(state): State<AppState>,
     Extension(user): Extension<AuthenticatedUser>,
     Json(payload): Json<McpRequest>,
 ) -> Json<McpResponse> {
+    Json(dispatch(&state, user, None, payload).await)
+}
+
+/// Handle a decoded request on behalf of a user and client
+async fn dispatch(
+    state: &AppState,
+    user: AuthenticatedUser,
+    client: Option<ClientInfo>,
+    payload: McpRequest,
+) -> McpResponse {
     match payload {
+        McpRequest::Initialize(params) => {
+            let user_agent = client.and_then(|client| client.user_agent);
+            initialize_session(state, &user, params.unwrap_or_default(), user_agent).await
+        }
         McpRequest::Discover => {
-            // Return list of all registered tools
-            let tools_vec = (*state.tool_definitions).clone();
-            Json(McpResponse::success(json!({ "tools": tools_vec })))
+            // Return list of all tools visible to the user
+            let tools_vec = (*state.tools_for(&user).definitions).clone();
+            let mut result = json!({ "tools": tools_vec });
+            if let Some(instructions) = &state.config.instructions {
+                result["instructions"] = json!(instructions);
+            }
+            McpResponse::success(result)
         }
         McpRequest::Invoke {
             tool_name,
             arguments,
+            options,
         } => {
-            // Lookup tool in registry
-            if let Some(tool_func) = state.tool_registry.get(&tool_name) {
-                // Execute tool
-                match tool_func(arguments, user).await {
-                    Ok(result) => Json(McpResponse::success(result)),
-                    Err(e) => {
-                        let error_msg = e.to_string();
-
-                        // Classify error based on message content
-                        let (error_code, error_prefix) = if is_param_validation_error(&error_msg)
-                        {
-                            (ERROR_INVALID_PARAMS, "Invalid parameters")
-                        } else {
-                            (ERROR_TOOL_EXECUTION, "Tool execution error")
-                        };
-
-                        Json(McpResponse::error(
-                            error_code,
-                            format!("{}: {}", error_prefix, error_msg),
-                            None,
-                        ))
+            // The invocation runs in its own task so that it can finish its
+            // bookkeeping when the client disconnects and this future is dropped
+            let cancellation = CancellationToken::new();
+            let _disconnect = cancellation.clone().drop_guard();
+            let ctx = ToolContext::new(user)
+                .with_client(client)
+                .with_cancellation(cancellation);
+            let state = state.clone();
+            let invocation = tokio::spawn(async move {
+                invoke_tool(&state, ctx, tool_name, arguments, options).await
+            });
+            match invocation.await {
+                Ok(response) => response,
+                Err(e) => std::panic::resume_unwind(e.into_panic()),
+            }
+        }
+        McpRequest::HistoryList(params) => {
+            // Users only ever see their own invocations
+            let limit = params.unwrap_or_default().limit;
+            let entries = state.history.list(&user.credentials().username, limit);
+            McpResponse::success(json!({ "invocations": entries }))
+        }
+        McpRequest::ElicitationRespond(params) => {
+            let result = state.elicitations.resolve(
+                &user.credentials().username,
+                &params.elicitation_id,
+                params.response,
+            );
+            match result {
+                Ok(()) => McpResponse::success(json!({})),
+                Err(e) => McpResponse::error(
+                    ERROR_INVALID_PARAMS,
+                    format!("Invalid parameters: {}", e),
+                    None,
+                ),
+            }
+        }
+        McpRequest::ResourcesRead(params) => {
+            match state
+                .resources
+                .read(&params.uri, &user.credentials().username)
+                .await
+            {
+                Ok(Some(resource)) => McpResponse::success(json!({
+                    "contents": [{
+                        "uri": params.uri,
+                        "mimeType": resource.mime_type,
+                        "text": resource.text,
+                    }]
+                })),
+                Ok(None) => McpResponse::error(
+                    ERROR_INVALID_PARAMS,
+                    format!("Invalid parameters: Resource '{}' not found", params.uri),
+                    None,
+                ),
+                Err(e) => McpResponse::error(
+                    ERROR_INTERNAL,
+                    format!("Failed to read resource: {:#}", e),
+                    None,
+                ),
+            }
+        }
+        McpRequest::LoggingSetLevel(params) => set_log_level(state, None, params.level).await,
+        McpRequest::Usage => {
+            // Users without a budget are not limited
+            let Some(budget) = &user.credentials().budget else {
+                return McpResponse::success(json!({ "budget": null }));
+            };
+            match state
+                .budgets
+                .usage(&user.credentials().username, budget)
+                .await
+            {
+                Ok(usage) => McpResponse::success(json!({ "budget": usage })),
+                Err(e) => McpResponse::error(
+                    ERROR_INTERNAL,
+                    format!("Failed to read usage: {:#}", e),
+                    None,
+                ),
+            }
+        }
+    }
+}
+
+/// Open a session recording the client's name, version and user agent
+async fn initialize_session(
+    state: &AppState,
+    user: &AuthenticatedUser,
+    params: InitializeParams,
+    user_agent: Option<String>,
+) -> McpResponse {
+    let client = ClientInfo {
+        user_agent,
+        ..params.client_info.unwrap_or_default()
+    };
+    let protocol_version = params
+        .protocol_version
+        .as_deref()
+        .unwrap_or(PROTOCOL_VERSION);
+
+    match state
+        .sessions
+        .create_with_ordering(
+            &user.credentials().username,
+            protocol_version,
+            client,
+            params.ordered_invocations,
+        )
+        .await
+    {
+        Ok(session) => {
+            let mut result = json!({
+                "protocolVersion": session.protocol_version,
+                "capabilities": { "tools": { "listChanged": true }, "logging": {} },
+                "serverInfo": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+                "sessionId": session.id,
+            });
+            if let Some(instructions) = &state.config.instructions {
+                result["instructions"] = json!(instructions);
+            }
+            McpResponse::success(result)
+        }
+        Err(e) => McpResponse::error(
+            ERROR_INTERNAL,
+            format!("Failed to create session: {:#}", e),
+            None,
+        ),
+    }
+}
+
+/// Store the session's log notification level; the level belongs to a
+/// session, so requests without one are refused
+async fn set_log_level(
+    state: &AppState,
+    session: Option<&Session>,
+    level: LogLevel,
+) -> McpResponse {
+    let Some(session) = session else {
+        return McpResponse::error(
+            ERROR_INVALID_REQUEST,
+            "Invalid request: logging/setLevel requires a session, call initialize first"
+                .to_string(),
+            None,
+        );
+    };
+    match state.sessions.set_log_level(session, level).await {
+        Ok(_) => McpResponse::success(json!({})),
+        Err(e) => McpResponse::error(
+            ERROR_INTERNAL,
+            format!("Failed to update session: {:#}", e),
+            None,
+        ),
+    }
+}
+
+/// Execute an invoke request, adding the `meta` envelope to successful
+/// responses when enabled, and deprecation warnings to every response of a
+/// deprecated tool
+async fn invoke_tool(
+    state: &AppState,
+    ctx: ToolContext,
+    tool_name: String,
+    arguments: Option<Value>,
+    options: InvokeOptions,
+) -> McpResponse {
+    let tool_name = state.resolve_tool_name(ctx.user(), tool_name);
+    let (tool_version, warning) = {
+        let tools = state.tools_for(ctx.user());
+        let definition = tools.definitions.iter().find(|def| def.name == tool_name);
+        (
+            definition.and_then(|def| def.version.clone()),
+            definition.and_then(|def| deprecation_warning(&state.config.deprecations, def)),
+        )
+    };
+    if let Some(warning) = &warning {
+        tracing::warn!(
+            user = %ctx.user().credentials().username,
+            tool = %warning.tool,
+            version = warning.version.as_deref().unwrap_or_default(),
+            "Deprecated tool invoked"
+        );
+        state.metrics.increment_counter(
+            "mcp_deprecated_invocations_total",
+            &[
+                ("tool", warning.tool.as_str()),
+                ("version", warning.version.as_deref().unwrap_or_default()),
+            ],
+        );
+    }
+
+    let started = Instant::now();
+    let request_id = ctx.request_id().to_string();
+    let (response, cache_hit) = invoke_idempotent(state, ctx, tool_name, arguments, options).await;
+
+    let mut meta = Map::new();
+    if state.config.results.include_meta && response.result.is_some() {
+        meta.insert("request_id".to_string(), json!(request_id));
+        meta.insert(
+            "duration_ms".to_string(),
+            json!(started.elapsed().as_secs_f64() * 1000.0),
+        );
+        meta.insert("tool_version".to_string(), json!(tool_version));
+        meta.insert("cache_hit".to_string(), json!(cache_hit));
+    }
+    if let Some(warning) = warning {
+        meta.insert("warnings".to_string(), json!([warning]));
+    }
+    if meta.is_empty() {
+        response
+    } else {
+        response.with_meta(Value::Object(meta))
+    }
+}
+
+/// Execute an invoke request, replaying the stored result when its
+/// idempotency key was already used successfully (reported as a cache hit)
+async fn invoke_idempotent(
+    state: &AppState,
+    ctx: ToolContext,
+    tool_name: String,
+    arguments: Option<Value>,
+    mut options: InvokeOptions,
+) -> (McpResponse, bool) {
+    let Some(key) = options.idempotency_key.take() else {
+        return (
+            execute_invocation(state, ctx, tool_name, arguments, options).await,
+            false,
+        );
+    };
+    let username = ctx.user().credentials().username.clone();
+
+    match state.idempotency.claim(&username, &key, &tool_name).await {
+        Ok(Claim::Acquired) => {}
+        Ok(Claim::Completed(result)) => {
+            state
+                .metrics
+                .increment_counter("mcp_idempotency_hits_total", &[("tool", &tool_name)]);
+            return (McpResponse::success(result), true);
+        }
+        Ok(Claim::InProgress) => {
+            return (
+                McpResponse::error(
+                    ERROR_SERVER_BUSY,
+                    "Server busy: an invocation with this idempotency key is still running"
+                        .to_string(),
+                    None,
+                ),
+                false,
+            );
+        }
+        Ok(Claim::Conflict { tool_name: claimed }) => {
+            return (
+                McpResponse::error(
+                    ERROR_INVALID_PARAMS,
+                    format!(
+                        "Invalid parameters: idempotency key already used for tool '{}'",
+                        claimed
+                    ),
+                    None,
+                ),
+                false,
+            );
+        }
+        Err(e) => {
+            return (
+                McpResponse::error(
+                    ERROR_INTERNAL,
+                    format!("Failed to check idempotency key: {:#}", e),
+                    None,
+                ),
+                false,
+            );
+        }
+    }
+
+    let response = execute_invocation(state, ctx, tool_name.clone(), arguments, options).await;
+    let stored = match &response.result {
+        Some(result) => {
+            state
+                .idempotency
+                .complete(&username, &key, &tool_name, result)
+                .await
+        }
+        None => state.idempotency.release(&username, &key).await,
+    };
+    if let Err(e) = stored {
+        tracing::warn!("Failed to update idempotency key: {:#}", e);
+    }
+    (response, false)
+}
+
+/// Run an invoke request: sanitize, run the tool, post-process and audit
+async fn execute_invocation(
+    state: &AppState,
+    ctx: ToolContext,
+    tool_name: String,
+    mut arguments: Option<Value>,
+    mut options: InvokeOptions,
+) -> McpResponse {
+    // Draining instances let running invocations finish but take no new ones
+    let Some(_in_flight) = state.drain.begin() else {
+        return McpResponse::error(
+            ERROR_SERVER_BUSY,
+            "Server busy: instance is draining".to_string(),
+            None,
+        );
+    };
+
+    if options.timeout_ms == Some(0) {
+        return McpResponse::error(
+            ERROR_INVALID_PARAMS,
+            "Invalid parameters: timeout_ms must be positive".to_string(),
+            None,
+        );
+    }
+
+    let user = ctx.user().clone();
+    let client = ctx.client().cloned();
+    let binary_limit = state.config.results.max_binary_bytes;
+    let ctx = match options.meta.take() {
+        Some(meta) => ctx.with_meta(meta),
+        None => ctx,
+    }
+    .with_binary_limit(binary_limit)
+    .with_uploads(state.uploads.clone())
+    .with_config(state.config.clone())
+    .with_store(state.store.clone())
+    .with_http(state.http.clone())
+    .with_tool_name(&tool_name);
+
+    // Lookup tool in the user's registry
+    let tools = state.tools_for(&user);
+    let Some(tool_func) = tools.functions.get(&tool_name) else {
+        // Tool not found - return available tools and the likely intended ones
+        let available_tools: Vec<String> =
+            tools.definitions.iter().map(|t| t.name.clone()).collect();
+        let suggestions = aliases::suggestions(&state.config.tools.aliases, &tools, &tool_name);
+        let message = match suggestions.as_slice() {
+            [] => format!("Tool '{}' not found", tool_name),
+            names => format!(
+                "Tool '{}' not found; did you mean {}?",
+                tool_name,
+                names
+                    .iter()
+                    .map(|name| format!("'{}'", name))
+                    .collect::<Vec<_>>()
+                    .join(" or ")
+            ),
+        };
+
+        return McpResponse::error(
+            ERROR_METHOD_NOT_FOUND,
+            message,
+            Some(json!({ "available_tools": available_tools, "suggestions": suggestions })),
+        );
+    };
+
+    let username = user.credentials().username.clone();
+    let started_at = Utc::now();
+
+    let definition = tools.definitions.iter().find(|def| def.name == tool_name);
+
+    if let Err(denial) = state.policies.evaluate(user.roles(), &tool_name) {
+        let message = format!(
+            "Forbidden: tool '{}' denied by policy: {}",
+            tool_name, denial.reason
+        );
+        audit::record(
+            &AuditRecord::new(&username, &tool_name, AuditStatus::Rejected).with_client(client),
+        );
+        record_history(
+            state,
+            &username,
+            &tool_name,
+            started_at,
+            AuditStatus::Rejected,
+            Some(message.clone()),
+        );
+        return McpResponse::error(ERROR_FORBIDDEN, message, Some(json!(denial)));
+    }
+
+    if state.read_only.blocks(definition) {
+        let message = format!(
+            "Server is in read-only mode: tool '{}' may modify data",
+            tool_name
+        );
+        audit::record(
+            &AuditRecord::new(&username, &tool_name, AuditStatus::Rejected).with_client(client),
+        );
+        record_history(
+            state,
+            &username,
+            &tool_name,
+            started_at,
+            AuditStatus::Rejected,
+            Some(message.clone()),
+        );
+        return McpResponse::error(ERROR_READ_ONLY, message, None);
+    }
+
+    // Fail before running the tool when the user lacks a key it declared
+    let missing_keys: Vec<&str> = definition
+        .map(|def| {
+            def.required_external_keys
+                .iter()
+                .map(String::as_str)
+                .filter(|key| user.get_external_key(key).is_none())
+                .collect()
+        })
+        .unwrap_or_default();
+    if !missing_keys.is_empty() {
+        let message = format!(
+            "User missing credential {}",
+            missing_keys
+                .iter()
+                .map(|key| format!("'{}'", key))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        audit::record(
+            &AuditRecord::new(&username, &tool_name, AuditStatus::Rejected).with_client(client),
+        );
+        record_history(
+            state,
+            &username,
+            &tool_name,
+            started_at,
+            AuditStatus::Rejected,
+            Some(message.clone()),
+        );
+        return McpResponse::error(
+            ERROR_MISSING_CREDENTIAL,
+            message,
+            Some(json!({ "tool": tool_name, "missing_keys": missing_keys })),
+        );
+    }
+
+    // Clients serializing arguments twice send them as a JSON string
+    if state.config.jsonrpc.lenient_arguments
+        && let Some(Value::String(encoded)) = &arguments
+    {
+        let limits = DecodeLimits::from_ref(state);
+        match WireFormat::Json.decode_limited(encoded.as_bytes(), limits) {
+            Ok(parsed @ Value::Object(_)) => {
+                tracing::warn!(
+                    user = %username,
+                    tool = %tool_name,
+                    client = ?client,
+                    "Client sent arguments as a JSON string; send them as an object"
+                );
+                arguments = Some(parsed);
+            }
+            result => {
+                let message = match result {
+                    Ok(_) => {
+                        "Invalid parameters: arguments string must encode an object".to_string()
                     }
-                }
+                    Err(e) => format!(
+                        "Invalid parameters: arguments string is not valid JSON: {}",
+                        e
+                    ),
+                };
+                audit::record(
+                    &AuditRecord::new(&username, &tool_name, AuditStatus::Rejected)
+                        .with_client(client),
+                );
+                record_history(
+                    state,
+                    &username,
+                    &tool_name,
+                    started_at,
+                    AuditStatus::Rejected,
+                    Some(message.clone()),
+                );
+                return McpResponse::error(ERROR_INVALID_PARAMS, message, None);
+            }
+        }
+    }
+
+    // Per-user defaults and forced values, applied before sanitization and validation
+    if let Some(user_arguments) = user.credentials().tool_arguments.get(&tool_name)
+        && let Err(e) = user_arguments.apply_for_user(user.credentials(), &mut arguments)
+    {
+        let (code, message, data) = match e {
+            TemplateError::MissingExternalKey(key) => (
+                ERROR_MISSING_CREDENTIAL,
+                format!("User missing credential '{}'", key),
+                Some(json!({ "tool": tool_name, "missing_keys": [key] })),
+            ),
+            other => (
+                ERROR_INTERNAL,
+                format!("Invalid argument template for tool '{}': {}", tool_name, other),
+                None,
+            ),
+        };
+        audit::record(
+            &AuditRecord::new(&username, &tool_name, AuditStatus::Rejected).with_client(client),
+        );
+        record_history(
+            state,
+            &username,
+            &tool_name,
+            started_at,
+            AuditStatus::Rejected,
+            Some(message.clone()),
+        );
+        return McpResponse::error(code, message, data);
+    }
+
+    // Sanitize string arguments before the tool sees them
+    let findings = state.sanitizers.apply(&tool_name, &mut arguments);
+    if is_rejected(&findings) {
+        let rejected: Vec<&str> = findings.iter().map(|f| f.argument.as_str()).collect();
+        audit::record(
+            &AuditRecord::new(&username, &tool_name, AuditStatus::Rejected)
+                .with_client(client)
+                .with_findings(findings.clone()),
+        );
+        record_history(
+            state,
+            &username,
+            &tool_name,
+            started_at,
+            AuditStatus::Rejected,
+            Some("Rejected by argument sanitizer".to_string()),
+        );
+        return McpResponse::error(
+            ERROR_INVALID_PARAMS,
+            format!(
+                "Invalid parameters: argument rejected by sanitizer: {}",
+                rejected.join(", ")
+            ),
+            Some(json!({ "findings": findings })),
+        );
+    }
+
+    // External policy engine, deciding on the arguments the tool would receive
+    if let Some(authorizer) = &state.authorizer {
+        let no_arguments = Value::Object(Map::new());
+        let request = AuthorizationRequest::new(
+            &user,
+            &tool_name,
+            arguments.as_ref().unwrap_or(&no_arguments),
+            client.as_ref(),
+        );
+        let refusal = match authorizer.authorize(&request).await {
+            Ok(Ok(())) => None,
+            Ok(Err(denial)) => Some((
+                ERROR_FORBIDDEN,
+                format!(
+                    "Forbidden: tool '{}' denied by policy: {}",
+                    tool_name, denial.reason
+                ),
+                Some(json!(denial)),
+            )),
+            Err(e) => Some((
+                ERROR_INTERNAL,
+                format!("Authorization failed: {:#}", e),
+                None,
+            )),
+        };
+        if let Some((code, message, data)) = refusal {
+            audit::record(
+                &AuditRecord::new(&username, &tool_name, AuditStatus::Rejected)
+                    .with_client(client)
+                    .with_findings(findings),
+            );
+            record_history(
+                state,
+                &username,
+                &tool_name,
+                started_at,
+                AuditStatus::Rejected,
+                Some(message.clone()),
+            );
+            return McpResponse::error(code, message, data);
+        }
+    }
+
+    // Identical invocations of cacheable tools get the stored result
+    let cache_entry = definition
+        .filter(|_| state.config.response_cache.enabled)
+        .and_then(|def| {
+            let ttl = cache_ttl(def)?;
+            Some((ResponseCache::key(&username, def, arguments.as_ref()), ttl))
+        });
+    if let Some((key, _)) = &cache_entry {
+        match state.response_cache.get(key).await {
+            Ok(Some(result)) => {
+                state
+                    .metrics
+                    .increment_counter("mcp_response_cache_hits_total", &[("tool", &tool_name)]);
+                return finish_result(state, &username, &tool_name, result).await;
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Failed to read the response cache: {:#}", e),
+        }
+    }
+
+    // Wait for an execution slot; clients may lower but never raise their priority
+    let user_priority = user.credentials().priority;
+    let priority = options
+        .priority
+        .map_or(user_priority, |p| p.min(user_priority));
+    let Ok(_permit) = state.scheduler.acquire(priority).await else {
+        audit::record(
+            &AuditRecord::new(&username, &tool_name, AuditStatus::Rejected).with_client(client),
+        );
+        record_history(
+            state,
+            &username,
+            &tool_name,
+            started_at,
+            AuditStatus::Rejected,
+            Some("Server busy".to_string()),
+        );
+        return McpResponse::error(
+            ERROR_SERVER_BUSY,
+            "Server busy: invocation queue is full".to_string(),
+            Some(json!({ "queue_depth": state.scheduler.queue_depth() })),
+        );
+    };
+
+    // Spend the tool's cost from the user's budget; refused invocations spend nothing
+    if let Some(budget) = &user.credentials().budget {
+        let cost = definition
+            .and_then(|def| def.cost)
+            .unwrap_or(DEFAULT_TOOL_COST);
+        let refusal = match state.budgets.charge(&username, budget, cost).await {
+            Ok(Ok(_)) => None,
+            Ok(Err(usage)) => Some((
+                ERROR_BUDGET_EXCEEDED,
+                format!(
+                    "Budget exceeded: tool '{}' costs {} but {} of {} remain",
+                    tool_name, cost, usage.remaining, usage.limit
+                ),
+                Some(json!({ "tool": tool_name, "cost": cost, "usage": usage })),
+            )),
+            Err(e) => Some((
+                ERROR_INTERNAL,
+                format!("Budget check failed: {:#}", e),
+                None,
+            )),
+        };
+        if let Some((code, message, data)) = refusal {
+            audit::record(
+                &AuditRecord::new(&username, &tool_name, AuditStatus::Rejected)
+                    .with_client(client)
+                    .with_findings(findings),
+            );
+            record_history(
+                state,
+                &username,
+                &tool_name,
+                started_at,
+                AuditStatus::Rejected,
+                Some(message.clone()),
+            );
+            return McpResponse::error(code, message, data);
+        }
+    }
+
+    // Route a share of the traffic to the canary implementation, if configured
+    // (tenants that cannot see the variant stay on the stable implementation)
+    let (tool_func, variant) = match state.canary.select(&tool_name, &username) {
+        (variant_name, Variant::Canary) => match tools.functions.get(variant_name) {
+            Some(canary_func) => (canary_func, Variant::Canary),
+            None => (tool_func, Variant::Stable),
+        },
+        (_, Variant::Stable) => (tool_func, Variant::Stable),
+    };
+
+    // Tools that must not be interrupted never see the disconnect
+    let cancellation = if state.config.cancellation.aborts(&tool_name) {
+        ctx.cancellation().clone()
+    } else {
+        CancellationToken::new()
+    };
+    let ctx = ctx.with_cancellation(cancellation.clone());
+
+    let timeout = TimeoutLimit::tightest(
+        options.timeout_ms,
+        definition.and_then(|def| def.timeout_ms),
+        state.config.timeouts.max_ms,
+    );
+
+    // Execute tool, abandoning it when the client goes away or it runs too long
+    let execution = tool_func(arguments, ctx.clone());
+    let execution = async {
+        match timeout {
+            Some(limit) => tokio::time::timeout(limit.duration(), execution)
+                .await
+                .map_err(|_| limit),
+            None => Ok(execution.await),
+        }
+    };
+    let Some(result) = cancellation.run_until_cancelled(execution).await else {
+        state.metrics.increment_counter(
+            "mcp_tool_invocations_total",
+            &[
+                ("tool", &tool_name),
+                ("variant", variant.as_str()),
+                ("status", "cancelled"),
+                (
+                    "client",
+                    client.as_ref().map_or("unknown", ClientInfo::label),
+                ),
+            ],
+        );
+        audit::record(
+            &AuditRecord::new(&username, &tool_name, AuditStatus::Cancelled)
+                .with_client(client)
+                .with_findings(findings),
+        );
+        record_history(
+            state,
+            &username,
+            &tool_name,
+            started_at,
+            AuditStatus::Cancelled,
+            Some("Client disconnected".to_string()),
+        );
+        return McpResponse::error(
+            ERROR_TOOL_EXECUTION,
+            "Tool execution error: invocation cancelled, client disconnected".to_string(),
+            None,
+        );
+    };
+    let result = match result {
+        Ok(result) => result,
+        Err(limit) => {
+            state.metrics.increment_counter(
+                "mcp_tool_invocations_total",
+                &[
+                    ("tool", &tool_name),
+                    ("variant", variant.as_str()),
+                    ("status", "timeout"),
+                    (
+                        "client",
+                        client.as_ref().map_or("unknown", ClientInfo::label),
+                    ),
+                ],
+            );
+            let message = format!(
+                "Timed out: tool '{}' exceeded the {} time limit of {} ms",
+                tool_name, limit.source, limit.ms
+            );
+            audit::record(
+                &AuditRecord::new(&username, &tool_name, AuditStatus::TimedOut)
+                    .with_client(client)
+                    .with_findings(findings),
+            );
+            record_history(
+                state,
+                &username,
+                &tool_name,
+                started_at,
+                AuditStatus::TimedOut,
+                Some(message.clone()),
+            );
+            return McpResponse::error(
+                ERROR_TIMEOUT,
+                message,
+                Some(json!({ "tool": tool_name, "timeout_ms": limit.ms, "limit": limit.source })),
+            );
+        }
+    };
+
+    // Post-process successful results
+    let result = result
+        .and_then(|value| check_binary_size(&value, binary_limit).map(|()| value))
+        .map(|value| attach_result_meta(value, ctx.take_result_meta()))
+        .map_err(ToolFailure::Execution)
+        .and_then(|value| {
+            state
+                .result_transformers
+                .apply(&tool_name, &user, value)
+                .map_err(ToolFailure::Transform)
+        });
+
+    let (status, error) = match &result {
+        Ok(_) => (AuditStatus::Success, None),
+        Err(ToolFailure::Execution(e) | ToolFailure::Transform(e)) => {
+            (AuditStatus::Error, Some(e.to_string()))
+        }
+    };
+    let status_label = if result.is_ok() { "success" } else { "error" };
+    state.metrics.increment_counter(
+        "mcp_tool_invocations_total",
+        &[
+            ("tool", &tool_name),
+            ("variant", variant.as_str()),
+            ("status", status_label),
+            (
+                "client",
+                client.as_ref().map_or("unknown", ClientInfo::label),
+            ),
+        ],
+    );
+    audit::record(
+        &AuditRecord::new(&username, &tool_name, status)
+            .with_client(client)
+            .with_findings(findings),
+    );
+    record_history(state, &username, &tool_name, started_at, status, error);
+
+    match result {
+        Ok(result) => {
+            if let Some((key, ttl)) = &cache_entry
+                && let Err(e) = state.response_cache.put(key, &result, *ttl).await
+            {
+                tracing::warn!("Failed to update the response cache: {:#}", e);
+            }
+            finish_result(state, &username, &tool_name, result).await
+        }
+        Err(ToolFailure::Transform(e)) => McpResponse::error(
+            ERROR_TOOL_EXECUTION,
+            format!("Result transformation error: {}", e),
+            None,
+        ),
+        Err(ToolFailure::Execution(e)) => {
+            if let Some(input) = e.downcast_ref::<InvalidInput>() {
+                return McpResponse::error(
+                    ERROR_INVALID_PARAMS,
+                    format!("Invalid parameters: {}", input.message),
+                    input.data.clone(),
+                );
+            }
+            let error_msg = e.to_string();
+
+            // Classify error based on message content
+            let (error_code, error_prefix) = if is_param_validation_error(&error_msg) {
+                (ERROR_INVALID_PARAMS, "Invalid parameters")
             } else {
-                // Tool not found - return available tools
-                let available_tools: Vec<String> = state
-                    .tool_definitions
-                    .iter()
-                    .map(|t| t.name.clone())
-                    .collect();
+                (ERROR_TOOL_EXECUTION, "Tool execution error")
+            };
+
+            McpResponse::error(error_code, format!("{}: {}", error_prefix, error_msg), None)
+        }
+    }
+}
+
+/// The tightest time limit on an invocation and where it comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TimeoutLimit {
+    /// `client`, `tool` or `server`
+    source: &'static str,
+    ms: u64,
+}
+
+impl TimeoutLimit {
+    /// The smallest of the client's, the tool's and the server's limits, if
+    /// any; a tie is reported as the server's or the tool's limit since the
+    /// client could not have asked for more
+    fn tightest(client: Option<u64>, tool: Option<u64>, server: u64) -> Option<Self> {
+        let server = (server > 0).then_some(server);
+        [("server", server), ("tool", tool), ("client", client)]
+            .into_iter()
+            .filter_map(|(source, ms)| Some(Self { source, ms: ms? }))
+            // `min_by_key` keeps the first of equal limits
+            .min_by_key(|limit| limit.ms)
+    }
+
+    fn duration(self) -> Duration {
+        Duration::from_millis(self.ms)
+    }
+}
+
+/// Add a finished invocation to the history and the usage counts
+fn record_history(
+    state: &AppState,
+    username: &str,
+    tool_name: &str,
+    started_at: chrono::DateTime<Utc>,
+    status: AuditStatus,
+    error: Option<String>,
+) {
+    let finished_at = Utc::now();
+    let duration_ms = (finished_at - started_at).num_milliseconds().max(0) as u64;
+    state
+        .usage
+        .record(username, tool_name, finished_at, status, duration_ms);
+    state.history.record(InvocationRecord {
+        tool_name: tool_name.to_string(),
+        username: username.to_string(),
+        started_at,
+        finished_at,
+        duration_ms,
+        status,
+        error,
+    });
+}
+
+/// Respond with a successful result, offloading it past
+/// `[results] offload_threshold_bytes` and applying `[results] oversized` to
+/// results that are still larger than `[results] max_bytes`
+async fn finish_result(
+    state: &AppState,
+    username: &str,
+    tool_name: &str,
+    result: Value,
+) -> McpResponse {
+    let config = &state.config.results;
+    let result = offload_large_result(state, username, tool_name, result).await;
+    if config.max_bytes == 0 {
+        return McpResponse::success(result);
+    }
+    let text = result.to_string();
+    let size = text.len();
+    if size <= config.max_bytes {
+        return McpResponse::success(result);
+    }
+
+    let action = match config.oversized {
+        OversizedResult::Error => "error",
+        OversizedResult::Truncate => "truncate",
+        OversizedResult::Offload => "offload",
+    };
+    state.metrics.increment_counter(
+        "mcp_oversized_results_total",
+        &[("tool", tool_name), ("action", action)],
+    );
+    let too_large = || {
+        McpResponse::error(
+            ERROR_RESULT_TOO_LARGE,
+            format!(
+                "Result too large: {} bytes exceeds the limit of {} bytes",
+                size, config.max_bytes
+            ),
+            Some(json!({ "tool": tool_name, "size_bytes": size, "max_bytes": config.max_bytes })),
+        )
+    };
 
-                Json(McpResponse::error(
-                    ERROR_METHOD_NOT_FOUND,
-                    format!("Tool '{}' not found", tool_name),
-                    Some(json!({ "available_tools": available_tools })),
-                ))
+    match config.oversized {
+        OversizedResult::Error => too_large(),
+        OversizedResult::Offload => match state.resources.offload(username, tool_name, text).await {
+            Ok(link) => McpResponse::success(json!({ "content": [link] })),
+            Err(e) => McpResponse::error(
+                ERROR_INTERNAL,
+                format!("Failed to offload oversized result: {:#}", e),
+                None,
+            ),
+        },
+        OversizedResult::Truncate => {
+            // The envelope counts towards the limit too
+            let envelope = json!({ "truncated": true, "size_bytes": size, "result": null });
+            let overhead = envelope.to_string().len() - "null".len();
+            let truncated = config
+                .max_bytes
+                .checked_sub(overhead)
+                .and_then(|max_bytes| truncate_to_size(&result, max_bytes));
+            match truncated {
+                Some(truncated) => McpResponse::success(json!({
+                    "truncated": true,
+                    "size_bytes": size,
+                    "result": truncated,
+                })),
+                None => too_large(),
             }
         }
     }
 }
 
+/// Replace a result above `[results] offload_threshold_bytes` with a link to a
+/// resource holding it; the result is returned inline if it cannot be stored
+async fn offload_large_result(
+    state: &AppState,
+    username: &str,
+    tool_name: &str,
+    result: Value,
+) -> Value {
+    let threshold = state.config.results.offload_threshold_bytes;
+    if threshold == 0 {
+        return result;
+    }
+
+    let text = result.to_string();
+    if text.len() <= threshold {
+        return result;
+    }
+
+    match state.resources.offload(username, tool_name, text).await {
+        Ok(link) => json!({ "content": [link] }),
+        Err(e) => {
+            tracing::warn!(tool = %tool_name, "Failed to offload large result: {:#}", e);
+            result
+        }
+    }
+}
+
+/// Merge `_meta` entries attached through the tool context into an object result
+fn attach_result_meta(mut result: Value, attached: Map<String, Value>) -> Value {
+    if attached.is_empty() {
+        return result;
+    }
+
+    if let Value::Object(map) = &mut result {
+        let meta = map
+            .entry("_meta")
+            .or_insert_with(|| Value::Object(Map::new()));
+        if let Value::Object(meta) = meta {
+            meta.extend(attached);
+        }
+    }
+    result
+}
+
+/// Where an invocation failed
+enum ToolFailure {
+    Execution(anyhow::Error),
+    Transform(anyhow::Error),
+}
+
 // ============================================================================
 // Helper Functions
 // ============================================================================
@@ -188,29 +1862,625 @@ pub fn is_param_validation_error(error_msg: &str) -> bool {
         .any(|keyword| error_msg.contains(keyword))
 }
 
+/// `GET /schema`: definitions of every tool visible to the user in a stable,
+/// versioned format for generating client bindings or documentation
+pub async fn schema_export(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+) -> Json<Value> {
+    let mut definitions = (*state.tools_for(&user).definitions).clone();
+    definitions.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let tools: Vec<Value> = definitions
+        .into_iter()
+        .map(|def| {
+            let mut tool = json!({
+                "name": def.name,
+                "description": def.description,
+                "inputSchema": def.parameters,
+            });
+            for (key, value) in [
+                ("version", def.version.map(Value::String)),
+                ("outputSchema", def.output_schema),
+                ("annotations", def.annotations),
+            ] {
+                if let Some(value) = value {
+                    tool[key] = value;
+                }
+            }
+            tool
+        })
+        .collect();
+
+    Json(json!({
+        "schemaVersion": SCHEMA_BUNDLE_VERSION,
+        "server": {
+            "name": env!("CARGO_PKG_NAME"),
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "protocolVersion": PROTOCOL_VERSION,
+        "tools": tools,
+    }))
+}
+
 /// Health check endpoint
 pub async fn health_check() -> (StatusCode, &'static str) {
     (StatusCode::OK, "OK")
 }
 
+/// Readiness endpoint for load balancers, failing once the instance is draining
+/// or, with `health.check_tools_on_ready`, while a tool reports itself unhealthy
+pub async fn readiness_check(State(state): State<AppState>) -> (StatusCode, &'static str) {
+    if state.drain.is_draining() {
+        return (StatusCode::SERVICE_UNAVAILABLE, "DRAINING");
+    }
+    if state.config.health.check_tools_on_ready {
+        let timeout = Duration::from_millis(state.config.health.timeout_ms);
+        let results = check_tools(&state.tool_definitions.load(), timeout).await;
+        if results
+            .iter()
+            .any(|result| matches!(result.status, HealthStatus::Unhealthy(_)))
+        {
+            return (StatusCode::SERVICE_UNAVAILABLE, "UNHEALTHY");
+        }
+    }
+    (StatusCode::OK, "READY")
+}
+
+/// Admin endpoint running every tool's health check
+pub async fn admin_health_detailed(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+) -> Result<Json<McpResponse>, AuthError> {
+    require_admin(&user)?;
+
+    let timeout = Duration::from_millis(state.config.health.timeout_ms);
+    let results = check_tools(&state.tool_definitions.load(), timeout).await;
+    Ok(Json(McpResponse::success(json!({
+        "status": overall_status(&results),
+        "draining": state.drain.is_draining(),
+        "tools": results,
+    }))))
+}
+
+/// Reject non-admin users on `/admin/*` endpoints
+pub fn require_admin(user: &AuthenticatedUser) -> Result<(), AuthError> {
+    if user.is_admin() {
+        Ok(())
+    } else {
+        Err(AuthError::Forbidden)
+    }
+}
+
+/// Query parameters for `GET /admin/history`
+#[derive(Debug, Deserialize)]
+pub struct AdminHistoryQuery {
+    /// Restrict to a single user
+    pub user: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// `POST /uploads`: store the multipart `file` field and return a handle that
+/// can be passed to tools as an argument
+pub async fn upload_file(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    mut multipart: Multipart,
+) -> Response {
+    let max_bytes = state.uploads.max_bytes();
+
+    loop {
+        let mut field = match multipart.next_field().await {
+            Ok(Some(field)) => field,
+            Ok(None) => break,
+            Err(e) => return upload_error(e.status(), e.body_text()),
+        };
+        if field.name() != Some("file") {
+            continue;
+        }
+
+        let filename = field.file_name().map(str::to_string);
+        let mime_type = field
+            .content_type()
+            .unwrap_or("application/octet-stream")
+            .to_string();
+
+        // Enforce the limit while reading instead of buffering oversized files
+        let mut data = Vec::new();
+        loop {
+            match field.chunk().await {
+                Ok(Some(chunk)) => {
+                    if max_bytes > 0 && data.len() + chunk.len() > max_bytes {
+                        return upload_error(
+                            StatusCode::PAYLOAD_TOO_LARGE,
+                            format!("Upload is larger than the {} byte limit", max_bytes),
+                        );
+                    }
+                    data.extend_from_slice(&chunk);
+                }
+                Ok(None) => break,
+                Err(e) => return upload_error(e.status(), e.body_text()),
+            }
+        }
+
+        let saved = state
+            .uploads
+            .save(
+                &user.credentials().username,
+                filename.clone(),
+                &mime_type,
+                &data,
+            )
+            .await;
+        return match saved {
+            Ok(handle) => Json(McpResponse::success(json!({
+                "handle": handle,
+                "filename": filename,
+                "mime_type": mime_type,
+                "size": data.len(),
+            })))
+            .into_response(),
+            Err(e) => upload_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to store upload: {:#}", e),
+            ),
+        };
+    }
+
+    upload_error(
+        StatusCode::BAD_REQUEST,
+        "Missing multipart field 'file'".to_string(),
+    )
+}
+
+fn upload_error(status: StatusCode, message: String) -> Response {
+    (
+        status,
+        Json(McpResponse::error(ERROR_INVALID_PARAMS, message, None)),
+    )
+        .into_response()
+}
+
+/// Admin endpoint listing recent invocations across users
+pub async fn admin_history(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Query(query): Query<AdminHistoryQuery>,
+) -> Result<Json<McpResponse>, AuthError> {
+    require_admin(&user)?;
+
+    let entries = match &query.user {
+        Some(username) => state.history.list(username, query.limit),
+        None => state.history.list_all(query.limit),
+    };
+    Ok(Json(McpResponse::success(
+        json!({ "invocations": entries }),
+    )))
+}
+
+/// Admin endpoint exposing metrics in the Prometheus text format
+pub async fn admin_metrics(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+) -> Result<String, AuthError> {
+    require_admin(&user)?;
+    Ok(state.metrics.render())
+}
+
+/// Query parameters for `GET /admin/usage`
+#[derive(Debug, Deserialize)]
+pub struct AdminUsageQuery {
+    /// Start of the range: an RFC 3339 timestamp or a `YYYY-MM-DD` date (UTC)
+    pub from: Option<String>,
+    /// End of the range, exclusive, in the same formats
+    pub to: Option<String>,
+    /// Restrict to a single user
+    pub user: Option<String>,
+    #[serde(default)]
+    pub format: UsageFormat,
+}
+
+/// Body format of `GET /admin/usage`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+/// Admin endpoint reporting invocations per user and tool in the hours
+/// starting within a time range
+pub async fn admin_usage(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Query(query): Query<AdminUsageQuery>,
+) -> Result<Response, AuthError> {
+    require_admin(&user)?;
+
+    let bound = |value: &Option<String>| value.as_deref().map(parse_time).transpose();
+    let (from, to) = match (bound(&query.from), bound(&query.to)) {
+        (Ok(from), Ok(to)) => (from, to),
+        (Err(e), _) | (_, Err(e)) => {
+            let response = McpResponse::error(
+                ERROR_INVALID_PARAMS,
+                format!("Invalid parameters: {:#}", e),
+                None,
+            );
+            return Ok((StatusCode::BAD_REQUEST, Json(response)).into_response());
+        }
+    };
+
+    let rows = match state.usage.report(from, to, query.user.as_deref()).await {
+        Ok(rows) => rows,
+        Err(e) => {
+            let response = McpResponse::error(
+                ERROR_INTERNAL,
+                format!("Failed to read usage: {:#}", e),
+                None,
+            );
+            return Ok((StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response());
+        }
+    };
+
+    Ok(match query.format {
+        UsageFormat::Csv => (
+            [(header::CONTENT_TYPE, "text/csv; charset=utf-8")],
+            to_csv(&rows),
+        )
+            .into_response(),
+        UsageFormat::Json => {
+            let mut total = UsageCounts::default();
+            for row in &rows {
+                total.add(&row.counts);
+            }
+            Json(McpResponse::success(json!({
+                "from": from,
+                "to": to,
+                "usage": rows,
+                "total": total,
+            })))
+            .into_response()
+        }
+    })
+}
+
+/// Query parameters for `POST /admin/drain`
+#[derive(Debug, Deserialize)]
+pub struct AdminDrainQuery {
+    /// Override `[drain] timeout_secs`
+    pub timeout_secs: Option<u64>,
+}
+
+/// Admin endpoint starting a drain: the instance reports not-ready, refuses new
+/// invocations and the request returns once in-flight ones finished (or timed out)
+pub async fn admin_drain(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Query(query): Query<AdminDrainQuery>,
+) -> Result<Json<McpResponse>, AuthError> {
+    require_admin(&user)?;
+
+    state.drain.start();
+    let timeout = query
+        .timeout_secs
+        .unwrap_or(state.config.drain.timeout_secs);
+    let drained = state.drain.wait_idle(Duration::from_secs(timeout)).await;
+
+    Ok(Json(McpResponse::success(json!({
+        "draining": true,
+        "drained": drained,
+        "in_flight": state.drain.in_flight(),
+    }))))
+}
+
+/// Query parameters for `POST /admin/read-only`
+#[derive(Debug, Deserialize)]
+pub struct AdminReadOnlyQuery {
+    pub enabled: bool,
+}
+
+/// Admin endpoint switching read-only mode on or off
+pub async fn admin_read_only(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Query(query): Query<AdminReadOnlyQuery>,
+) -> Result<Json<McpResponse>, AuthError> {
+    require_admin(&user)?;
+
+    state.read_only.set(query.enabled);
+    tracing::info!(admin = %user.credentials().username, "Read-only mode {}", if query.enabled { "enabled" } else { "disabled" });
+
+    Ok(Json(McpResponse::success(
+        json!({ "read_only": state.read_only.is_enabled() }),
+    )))
+}
+
+/// Admin endpoint reloading the credentials file and invalidating the auth cache
+/// With a custom auth provider only the cache is invalidated
+pub async fn admin_reload_credentials(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+) -> Result<Json<McpResponse>, AuthError> {
+    require_admin(&user)?;
+
+    let Some(provider) = &state.credentials else {
+        state.auth_cache.invalidate_all();
+        return Ok(Json(McpResponse::success(
+            json!({ "reloaded": false, "cache_invalidated": true }),
+        )));
+    };
+
+    let loaded = load_credentials()
+        .and_then(|store| apply_tenant_config(store, &state.config.tenants))
+        .and_then(|store| state.check_external_keys(&store).map(|()| store));
+    let response = match loaded {
+        Ok(store) => {
+            let users = store.len();
+            provider.replace(store);
+            state.auth_cache.invalidate_all();
+            McpResponse::success(
+                json!({ "reloaded": true, "users": users, "cache_invalidated": true }),
+            )
+        }
+        // Keep serving the previous credentials
+        Err(e) => McpResponse::error(
+            ERROR_INTERNAL,
+            format!("Failed to reload credentials: {:#}", e),
+            None,
+        ),
+    };
+    Ok(Json(response))
+}
+
 // ============================================================================
 // Application Factory
 // ============================================================================
 
-/// Create and configure the Axum application
-pub fn create_app(credentials: CredentialsStore) -> Router {
-    // Initialize tools
-    let (func_registry, tool_definitions) = initialize_all_tools();
+/// Create and configure the Axum application with default options
+/// Fails when registered tools share a name
+pub fn create_app(credentials: CredentialsStore) -> anyhow::Result<Router> {
+    create_app_with_options(credentials, AppOptions::default())
+}
+
+/// Create and configure the Axum application from a configuration and hooks
+pub fn create_app_with_options(
+    credentials: CredentialsStore,
+    options: AppOptions,
+) -> anyhow::Result<Router> {
+    Ok(mcp_router(AppState::new(credentials, options)?))
+}
+
+impl AppState {
+    /// Build the application state: tools, tenants, stores, authentication and
+    /// the background tasks (store cleanup, plugin watching) they need
+    ///
+    /// Use together with `mcp_router` to serve the MCP endpoints next to an
+    /// application's own routes sharing the same state.
+    pub fn new(credentials: CredentialsStore, options: AppOptions) -> anyhow::Result<Self> {
+        // Initialize tools
+        let (func_registry, tool_definitions) =
+            initialize_tools(options.config.tools.duplicate_names)?;
+
+        let tenant_tools = build_tenant_tool_sets(&options.config)?;
+        let credentials = apply_tenant_config(credentials, &options.config.tenants)?;
+
+        let sanitizers =
+            SanitizerChain::new(options.config.sanitization.clone(), options.sanitizers)?;
+
+        let storage = Storage::open(&options.config.database)?;
+        let store = match options.store {
+            Some(store) => SharedStore::new(store),
+            None => SharedStore::from_config(&options.config.store, &storage)?,
+        };
+
+        let canary = CanaryRouter::new(options.config.canary.clone(), &func_registry)?;
+
+        let auth_cache = Arc::new(AuthCache::new(
+            Duration::from_secs(options.config.auth.cache_ttl_secs),
+            options.config.auth.cache_max_entries,
+        ));
+        let (provider, static_credentials): (Arc<dyn AuthProvider>, _) = match options.auth_provider
+        {
+            Some(provider) => (provider, None),
+            None => {
+                let provider = Arc::new(StaticAuthProvider::new(credentials));
+                (provider.clone(), Some(provider))
+            }
+        };
+        let authenticator = Authenticator::new(provider, auth_cache.clone());
+        let token_sources = TokenSources {
+            header: options
+                .config
+                .auth
+                .api_key_header
+                .as_deref()
+                .map(axum::http::HeaderName::try_from)
+                .transpose()
+                .map_err(|e| anyhow::anyhow!("Invalid auth.api_key_header: {}", e))?,
+            query_param: options.config.auth.query_param.clone(),
+        };
+
+        let sessions = SessionManager::new(
+            store.clone(),
+            Duration::from_secs(options.config.sessions.ttl_secs),
+        );
+
+        let resources = ResourceStore::new(
+            store.clone(),
+            Duration::from_secs(options.config.results.offload_ttl_secs),
+        );
+
+        let uploads = UploadStore::new(
+            store.clone(),
+            Duration::from_secs(options.config.uploads.ttl_secs),
+            options.config.uploads.max_bytes,
+        );
+
+        let idempotency = IdempotencyCache::new(
+            store.clone(),
+            Duration::from_secs(options.config.idempotency.ttl_secs),
+            Duration::from_secs(options.config.idempotency.pending_ttl_secs),
+        );
+        let budgets = Budgets::new(store.clone());
+        let response_cache = ResponseCache::new(store.clone(), &options.config.response_cache);
+        let usage = Arc::new(UsageRecorder::new(&options.config.usage, &storage)?);
+        if options.config.usage.flush_interval_secs > 0
+            && tokio::runtime::Handle::try_current().is_ok()
+        {
+            usage.spawn_flush(Duration::from_secs(
+                options.config.usage.flush_interval_secs,
+            ));
+        }
+        let http = HttpClient::new(&options.config.http)?;
+        // Expired entries are also dropped when read, so states built outside a
+        // runtime still behave correctly without the background cleanup
+        if options.config.store.cleanup_interval_secs > 0
+            && tokio::runtime::Handle::try_current().is_ok()
+        {
+            store.spawn_cleanup(Duration::from_secs(
+                options.config.store.cleanup_interval_secs,
+            ));
+        }
+
+        let policies = match &options.config.policies.path {
+            Some(path) => PolicyEngine::load(path)?,
+            None => PolicyEngine::default(),
+        };
+        let opa = &options.config.policies.opa;
+        let authorizer = match (options.authorizer, &opa.url) {
+            (Some(authorizer), _) => Some(authorizer),
+            (None, Some(url)) => {
+                Some(Arc::new(OpaAuthorizer::new(url, opa)?) as Arc<dyn Authorizer>)
+            }
+            (None, None) => None,
+        };
+
+        let metrics = Arc::new(Metrics::new());
+        let scheduler =
+            InvocationScheduler::new(options.config.scheduling.clone(), metrics.clone());
+
+        let state = Self {
+            tool_registry: Arc::new(ArcSwap::from_pointee(func_registry)),
+            tool_definitions: Arc::new(ArcSwap::from_pointee(tool_definitions)),
+            sanitizers: Arc::new(sanitizers),
+            result_transformers: Arc::new(TransformerChain::new(options.result_transformers)),
+            history: Arc::new(InvocationHistory::new(options.config.history.clone())),
+            metrics,
+            scheduler: Arc::new(scheduler),
+            tenant_tools: Arc::new(tenant_tools),
+            store,
+            canary: Arc::new(canary),
+            elicitations: Arc::new(PendingElicitations::new()),
+            credentials: static_credentials,
+            auth_cache,
+            auth: AuthLayer::from_authenticator(authenticator).with_token_sources(token_sources),
+            sessions,
+            resources,
+            uploads,
+            drain: Arc::new(DrainController::new()),
+            read_only: Arc::new(ReadOnlyMode::new(options.config.read_only.enabled)),
+            notifications: Notifier::new(),
+            idempotency,
+            trusted_proxies: Arc::new(TrustedProxies::new(
+                &options.config.client_ip.trusted_proxies,
+            )?),
+            policies: Arc::new(policies),
+            authorizer,
+            budgets,
+            http,
+            response_cache,
+            usage,
+            storage,
+            config: Arc::new(options.config),
+        };
+
+        if let Some(dir) = state.config.plugins.dir.clone() {
+            let loaded = reload_plugins(&state, &dir)?;
+            tracing::info!("Loaded {} plugins from {}", loaded, dir.display());
+            watch_plugins(
+                state.clone(),
+                dir,
+                Duration::from_secs(state.config.plugins.poll_interval_secs.max(1)),
+            );
+        }
 
-    let app_state = AppState {
-        tool_registry: Arc::new(func_registry),
-        tool_definitions: Arc::new(tool_definitions),
+        // Custom auth providers resolve users lazily, so only the file can be checked
+        if let Some(provider) = &state.credentials {
+            state.check_external_keys(&provider.credentials())?;
+        }
+
+        Ok(state)
+    }
+}
+
+/// The MCP, upload and admin endpoints behind authentication, plus the
+/// unauthenticated `/health` and `/ready` checks
+///
+/// The returned router has its state applied, so it can be merged into or
+/// nested under an existing application; routes added to it afterwards are
+/// not authenticated.
+pub fn mcp_router(state: AppState) -> Router {
+    let config = state.config.clone();
+    let auth = state.auth.clone();
+    let ready_state = state.clone();
+
+    // Leave room for the multipart framing around the file
+    let upload_body_limit = match config.uploads.max_bytes {
+        0 => DefaultBodyLimit::disable(),
+        max_bytes => DefaultBodyLimit::max(max_bytes.saturating_add(64 * 1024)),
     };
 
-    // Build router
-    Router::new()
-        .route("/mcp", post(handle_mcp_request))
-        .with_state(app_state)
-        .layer(AuthLayer::new(credentials))
+    let replay_guard = config.replay.enabled.then(|| {
+        ReplayGuard::new(
+            state.store.clone(),
+            Duration::from_secs(config.replay.max_skew_secs),
+        )
+    });
+
+    let signing = Arc::new(config.signing.clone());
+    let trusted_proxies = state.trusted_proxies.clone();
+
+    // Limits the body as handlers see it, i.e. after decompression
+    let request_body = Arc::new(config.request_body.clone());
+    let mut mcp_post = post(mcp_endpoint).layer(DefaultBodyLimit::max(request_body.max_bytes));
+    if request_body.decompress {
+        mcp_post = mcp_post.layer(axum::middleware::from_fn_with_state(
+            request_body,
+            decompress_request,
+        ));
+    }
+
+    let mut router = Router::new()
+        .route("/mcp", mcp_post.get(notifications_stream))
+        .route("/schema", get(schema_export))
+        .route("/uploads", post(upload_file).layer(upload_body_limit))
+        .route("/admin/history", get(admin_history))
+        .route("/admin/metrics", get(admin_metrics))
+        .route("/admin/usage", get(admin_usage))
+        .route("/admin/credentials/reload", post(admin_reload_credentials))
+        .route("/admin/drain", post(admin_drain))
+        .route("/admin/read-only", post(admin_read_only))
+        .route("/admin/health/detailed", get(admin_health_detailed))
+        .with_state(state)
+        .layer(axum::middleware::from_fn_with_state(
+            signing,
+            verify_signature,
+        ));
+    // Runs after authentication, which identifies the key to verify against
+    if let Some(guard) = replay_guard {
+        router = router.layer(axum::middleware::from_fn_with_state(
+            guard,
+            replay_protection,
+        ));
+    }
+
+    router
+        .layer(auth)
+        .layer(axum::middleware::from_fn_with_state(
+            trusted_proxies,
+            resolve_client_ip,
+        ))
         .route("/health", get(health_check))
+        .route("/ready", get(readiness_check).with_state(ready_state))
 }
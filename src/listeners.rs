@@ -0,0 +1,338 @@
+//! Listeners serving the HTTP endpoint
+//!
+//! The router can be served on several listeners at once, e.g. plain HTTP on
+//! localhost, HTTPS on every interface and a Unix socket for a local agent
+//! (`[[listeners]]`). Every listener is bound before any of them serves, so a
+//! taken port or a bad certificate fails startup, and they shut down
+//! together: once the shutdown token is cancelled they stop accepting
+//! connections and finish the requests in progress.
+
+use crate::config::{ListenerConfig, TlsConfig};
+use anyhow::{Context, Result, anyhow, bail};
+use axum::Router;
+use axum::serve::ListenerExt;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+use tokio_rustls::TlsAcceptor;
+use tokio_rustls::rustls;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::server::TlsStream;
+use tokio_util::sync::CancellationToken;
+
+/// Longest a client may take to complete the TLS handshake
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Handshaken connections waiting to be served, per HTTPS listener
+const PENDING_CONNECTIONS: usize = 64;
+
+impl ListenerConfig {
+    /// `http`, `https` or `unix`
+    pub fn scheme(&self) -> &'static str {
+        match (&self.socket_path, &self.tls) {
+            (Some(_), _) => "unix",
+            (None, Some(_)) => "https",
+            (None, None) => "http",
+        }
+    }
+
+    /// The configured address, `unix:<path>` for sockets
+    pub fn address(&self) -> String {
+        match (&self.socket_path, &self.bind) {
+            (Some(path), _) => format!("unix:{}", path),
+            (None, Some(bind)) => bind.clone(),
+            (None, None) => String::new(),
+        }
+    }
+
+    /// Check that the listener has exactly one address and TLS only over TCP
+    pub fn check(&self) -> Result<()> {
+        match (&self.bind, &self.socket_path) {
+            (Some(_), Some(_)) => bail!("Listener sets both `bind` and `socket_path`"),
+            (None, None) => bail!("Listener needs `bind` or `socket_path`"),
+            (Some(_), None) if self.socket_mode.is_some() => {
+                bail!("`socket_mode` only applies to `socket_path` listeners")
+            }
+            (None, Some(_)) if self.tls.is_some() => {
+                bail!("`tls` only applies to `bind` listeners")
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A listener ready to serve
+pub struct BoundListener {
+    scheme: &'static str,
+    address: String,
+    kind: Kind,
+}
+
+enum Kind {
+    Tcp(TcpListener),
+    Tls(TlsListener),
+    #[cfg(unix)]
+    Unix(tokio::net::UnixListener, std::path::PathBuf),
+}
+
+/// Bind the listener `config` describes
+pub async fn bind(config: &ListenerConfig) -> Result<BoundListener> {
+    config.check()?;
+    let scheme = config.scheme();
+
+    if let Some(path) = &config.socket_path {
+        return bind_unix(path, config.socket_mode).await;
+    }
+
+    let bind = config.bind.as_deref().unwrap_or_default();
+    let listener = TcpListener::bind(bind)
+        .await
+        .with_context(|| format!("Failed to bind {}", bind))?;
+    let address = listener.local_addr()?.to_string();
+    let kind = match &config.tls {
+        Some(tls) => Kind::Tls(TlsListener::new(listener, tls_acceptor(tls)?)?),
+        None => Kind::Tcp(listener),
+    };
+    Ok(BoundListener {
+        scheme,
+        address,
+        kind,
+    })
+}
+
+#[cfg(unix)]
+async fn bind_unix(path: &str, mode: Option<u32>) -> Result<BoundListener> {
+    use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+
+    // A socket left behind by a previous run would make binding fail
+    if let Ok(metadata) = std::fs::symlink_metadata(path) {
+        if !metadata.file_type().is_socket() {
+            bail!("{} exists and is not a socket", path);
+        }
+        std::fs::remove_file(path).with_context(|| format!("Failed to remove {}", path))?;
+    }
+    let listener =
+        tokio::net::UnixListener::bind(path).with_context(|| format!("Failed to bind {}", path))?;
+    if let Some(mode) = mode {
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+            .with_context(|| format!("Failed to set the permissions of {}", path))?;
+    }
+    Ok(BoundListener {
+        scheme: "unix",
+        address: format!("unix:{}", path),
+        kind: Kind::Unix(listener, path.into()),
+    })
+}
+
+#[cfg(not(unix))]
+async fn bind_unix(_path: &str, _mode: Option<u32>) -> Result<BoundListener> {
+    bail!("Unix socket listeners are not supported on this platform")
+}
+
+/// TLS server settings from the PEM files of `config`
+fn tls_acceptor(config: &TlsConfig) -> Result<TlsAcceptor> {
+    let certificates = CertificateDer::pem_file_iter(&config.cert_path)
+        .and_then(|certificates| certificates.collect::<Result<Vec<_>, _>>())
+        .with_context(|| format!("Failed to read certificates from {}", config.cert_path))?;
+    if certificates.is_empty() {
+        bail!("No certificate found in {}", config.cert_path);
+    }
+    let key = PrivateKeyDer::from_pem_file(&config.key_path)
+        .with_context(|| format!("Failed to read a private key from {}", config.key_path))?;
+
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let mut server = rustls::ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()?
+        .with_no_client_auth()
+        .with_single_cert(certificates, key)
+        .with_context(|| format!("Invalid certificate or key in {}", config.cert_path))?;
+    server.alpn_protocols = vec![b"http/1.1".to_vec()];
+    Ok(TlsAcceptor::from(Arc::new(server)))
+}
+
+impl BoundListener {
+    /// `http`, `https` or `unix`
+    pub fn scheme(&self) -> &'static str {
+        self.scheme
+    }
+
+    /// The bound address, with the actual port when binding port 0
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// `http://<address>`, `https://<address>` or `unix:<path>`
+    pub fn url(&self) -> String {
+        match self.scheme {
+            "unix" => self.address.clone(),
+            scheme => format!("{}://{}", scheme, self.address),
+        }
+    }
+
+    /// Serve `router` until `shutdown` is cancelled and the connections in
+    /// progress have finished
+    pub async fn serve(self, router: Router, shutdown: CancellationToken) -> io::Result<()> {
+        let shutdown = shutdown.cancelled_owned();
+        match self.kind {
+            // The peer address feeds client IP resolution (`[client_ip]`)
+            Kind::Tcp(listener) => {
+                axum::serve(
+                    listener,
+                    router.into_make_service_with_connect_info::<SocketAddr>(),
+                )
+                .with_graceful_shutdown(shutdown)
+                .await
+            }
+            // `tap_io` makes axum provide the peer address of custom listeners
+            Kind::Tls(listener) => {
+                axum::serve(
+                    listener.tap_io(|_| {}),
+                    router.into_make_service_with_connect_info::<SocketAddr>(),
+                )
+                .with_graceful_shutdown(shutdown)
+                .await
+            }
+            #[cfg(unix)]
+            Kind::Unix(listener, path) => {
+                let result = axum::serve(listener, router.into_make_service())
+                    .with_graceful_shutdown(shutdown)
+                    .await;
+                let _ = std::fs::remove_file(&path);
+                result
+            }
+        }
+    }
+}
+
+/// Serve `router` on every listener until `shutdown` is cancelled, then wait
+/// up to `grace` for the requests in progress
+///
+/// A listener failing cancels `shutdown`, stopping the others, and is
+/// returned as the error.
+pub async fn serve_all(
+    listeners: Vec<BoundListener>,
+    router: Router,
+    shutdown: CancellationToken,
+    grace: Duration,
+) -> Result<()> {
+    let mut tasks = JoinSet::new();
+    for listener in listeners {
+        let router = router.clone();
+        let shutdown = shutdown.clone();
+        tasks.spawn(async move {
+            let address = listener.address().to_string();
+            let result = listener.serve(router, shutdown.clone()).await;
+            match result {
+                Err(e) => Err(anyhow!(e).context(format!("Listener {} failed", address))),
+                Ok(()) if !shutdown.is_cancelled() => {
+                    Err(anyhow!("Listener {} stopped unexpectedly", address))
+                }
+                Ok(()) => Ok(()),
+            }
+        });
+    }
+
+    let mut failure = None;
+    tokio::select! {
+        _ = shutdown.cancelled() => {}
+        Some(joined) = tasks.join_next() => {
+            failure = joined.map_err(anyhow::Error::from).and_then(|result| result).err();
+            shutdown.cancel();
+        }
+    }
+
+    let finished = tokio::time::timeout(grace, async {
+        while let Some(joined) = tasks.join_next().await {
+            if let Err(e) = joined
+                .map_err(anyhow::Error::from)
+                .and_then(|result| result)
+            {
+                failure.get_or_insert(e);
+            }
+        }
+    })
+    .await
+    .is_ok();
+    if !finished {
+        tracing::warn!(
+            listeners = tasks.len(),
+            "Connections still open after {}s, closing them",
+            grace.as_secs()
+        );
+        tasks.abort_all();
+    }
+
+    match failure {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+/// TCP listener completing TLS handshakes in the background, so a slow
+/// client does not hold up the ones behind it
+struct TlsListener {
+    local_addr: SocketAddr,
+    connections: mpsc::Receiver<(TlsStream<TcpStream>, SocketAddr)>,
+}
+
+impl TlsListener {
+    fn new(listener: TcpListener, acceptor: TlsAcceptor) -> io::Result<Self> {
+        let local_addr = listener.local_addr()?;
+        let (sender, connections) = mpsc::channel(PENDING_CONNECTIONS);
+        tokio::spawn(async move {
+            loop {
+                let (stream, peer) = tokio::select! {
+                    // The listener was dropped: stop accepting
+                    _ = sender.closed() => return,
+                    accepted = listener.accept() => match accepted {
+                        Ok(accepted) => accepted,
+                        Err(e) => {
+                            tracing::debug!(error = %e, "Failed to accept a TLS connection");
+                            tokio::time::sleep(Duration::from_millis(50)).await;
+                            continue;
+                        }
+                    },
+                };
+                let acceptor = acceptor.clone();
+                let sender = sender.clone();
+                tokio::spawn(async move {
+                    match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                        Ok(Ok(stream)) => {
+                            let _ = sender.send((stream, peer)).await;
+                        }
+                        Ok(Err(e)) => {
+                            tracing::debug!(peer = %peer, error = %e, "TLS handshake failed")
+                        }
+                        Err(_) => tracing::debug!(peer = %peer, "TLS handshake timed out"),
+                    }
+                });
+            }
+        });
+        Ok(Self {
+            local_addr,
+            connections,
+        })
+    }
+}
+
+impl axum::serve::Listener for TlsListener {
+    type Io = TlsStream<TcpStream>;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        match self.connections.recv().await {
+            Some(connection) => connection,
+            // The accept loop only ends once this listener is dropped
+            None => std::future::pending().await,
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<Self::Addr> {
+        Ok(self.local_addr)
+    }
+}
@@ -1,31 +1,133 @@
 use anyhow::{Context, Result};
 use mcp_server::auth::load_credentials;
-use mcp_server::create_app;
-use std::net::{Ipv4Addr, SocketAddr};
-use tokio::net::TcpListener;
+use mcp_server::config::load_config;
+use mcp_server::listeners;
+use mcp_server::startup::StartupReport;
+use mcp_server::{mcp_router, AppOptions, AppState};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+
+/// Load credentials and configuration and build the application state
+pub fn setup_state() -> Result<AppState> {
+    let credentials = load_credentials().context("Failed to load credentials")?;
+    let config = load_config().context("Failed to load server configuration")?;
+    for (index, listener) in config.listeners().iter().enumerate() {
+        listener
+            .check()
+            .with_context(|| format!("Invalid [[listeners]] entry {}", index + 1))?;
+    }
+    AppState::new(credentials, AppOptions::new(config))
+}
 
 /// Setup and configure the MCP server application
 ///
-/// Handles loading credentials and creating the configured Axum router.
+/// Handles loading credentials and configuration and creating the configured Axum router.
 pub fn setup_server() -> Result<axum::Router> {
-    let credentials = load_credentials().context("Failed to load credentials")?;
-    let app = create_app(credentials);
-    Ok(app)
+    Ok(mcp_router(setup_state()?))
+}
+
+/// `--check`: print the startup report as JSON and exit without serving,
+/// failing when the configuration cannot be loaded
+fn check() -> ! {
+    match setup_state() {
+        Ok(state) => {
+            let report = StartupReport::new(&state);
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&report).expect("Failed to serialize the report")
+            );
+            std::process::exit(0);
+        }
+        Err(e) => {
+            eprintln!("Configuration check failed: {:#}", e);
+            std::process::exit(1);
+        }
+    }
 }
 
 #[tokio::main]
 async fn main() {
-    let app = setup_server().expect("Failed to setup server");
+    // `--check` keeps stdout for the report
+    let check_only = std::env::args().skip(1).any(|arg| arg == "--check");
+    let writer = if check_only {
+        BoxMakeWriter::new(std::io::stderr)
+    } else {
+        BoxMakeWriter::new(std::io::stdout)
+    };
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
+        )
+        .with_writer(writer)
+        .init();
 
-    let addr = SocketAddr::from((Ipv4Addr::UNSPECIFIED, 3000));
-    let listener = TcpListener::bind(&addr)
-        .await
-        .expect("Failed to bind address");
+    if check_only {
+        check();
+    }
 
-    println!("MCP Server listening on http://{}", addr);
-    axum::serve(listener, app)
+    let state = setup_state().expect("Failed to setup server");
+    StartupReport::new(&state).log();
+
+    #[cfg(feature = "grpc")]
+    if state.config.grpc.enabled {
+        let listener = tokio::net::TcpListener::bind(&state.config.grpc.bind)
+            .await
+            .expect("Failed to bind gRPC address");
+        println!("MCP gRPC service listening on {}", state.config.grpc.bind);
+        let grpc_state = state.clone();
+        tokio::spawn(async move {
+            if let Err(e) = mcp_server::grpc::serve(grpc_state, listener).await {
+                tracing::error!(error = %e, "gRPC server stopped");
+            }
+        });
+    }
+
+    // Bind every listener before serving, so a bad one fails startup
+    let mut bound = Vec::new();
+    for config in state.config.listeners() {
+        let listener = listeners::bind(&config)
+            .await
+            .expect("Failed to bind listener");
+        println!("MCP Server listening on {}", listener.url());
+        bound.push(listener);
+    }
+
+    let shutdown = CancellationToken::new();
+    tokio::spawn(stop_on_signal(state.clone(), shutdown.clone()));
+    let grace = Duration::from_secs(state.config.drain.timeout_secs);
+
+    let app = mcp_router(state);
+    listeners::serve_all(bound, app, shutdown, grace)
         .await
-        .expect("Failed to start server");
+        .expect("Server failed");
+}
+
+/// Drain and cancel `shutdown` on Ctrl-C or SIGTERM
+async fn stop_on_signal(state: AppState, shutdown: CancellationToken) {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(_) => std::future::pending().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+    tracing::info!("Shutting down, waiting for requests in progress");
+    state.drain.start();
+    shutdown.cancel();
 }
 
 #[cfg(test)]
@@ -0,0 +1,97 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+
+/// Metric name plus sorted label pairs
+type MetricKey = (String, Vec<(String, String)>);
+
+/// Minimal in-process metrics registry rendered in the Prometheus text format
+#[derive(Debug, Default)]
+pub struct Metrics {
+    counters: Mutex<BTreeMap<MetricKey, u64>>,
+    gauges: Mutex<BTreeMap<MetricKey, i64>>,
+}
+
+fn key(name: &str, labels: &[(&str, &str)]) -> MetricKey {
+    let mut labels: Vec<(String, String)> = labels
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    labels.sort();
+    (name.to_string(), labels)
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Increment a counter by one
+    pub fn increment_counter(&self, name: &str, labels: &[(&str, &str)]) {
+        self.add_counter(name, labels, 1);
+    }
+
+    /// Increment a counter by an arbitrary amount
+    pub fn add_counter(&self, name: &str, labels: &[(&str, &str)], amount: u64) {
+        let mut counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+        *counters.entry(key(name, labels)).or_default() += amount;
+    }
+
+    /// Set a gauge to an absolute value
+    pub fn set_gauge(&self, name: &str, labels: &[(&str, &str)], value: i64) {
+        let mut gauges = self.gauges.lock().unwrap_or_else(|e| e.into_inner());
+        gauges.insert(key(name, labels), value);
+    }
+
+    /// Current value of a counter (0 if never incremented)
+    pub fn counter(&self, name: &str, labels: &[(&str, &str)]) -> u64 {
+        let counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+        counters.get(&key(name, labels)).copied().unwrap_or(0)
+    }
+
+    /// Current value of a gauge (0 if never set)
+    pub fn gauge(&self, name: &str, labels: &[(&str, &str)]) -> i64 {
+        let gauges = self.gauges.lock().unwrap_or_else(|e| e.into_inner());
+        gauges.get(&key(name, labels)).copied().unwrap_or(0)
+    }
+
+    /// Render all metrics in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+        render_family(
+            &mut out,
+            "counter",
+            counters.iter().map(|(k, v)| (k, *v as i64)),
+        );
+        drop(counters);
+
+        let gauges = self.gauges.lock().unwrap_or_else(|e| e.into_inner());
+        render_family(&mut out, "gauge", gauges.iter().map(|(k, v)| (k, *v)));
+        out
+    }
+}
+
+fn render_family<'a>(
+    out: &mut String,
+    kind: &str,
+    samples: impl Iterator<Item = (&'a MetricKey, i64)>,
+) {
+    let mut last_name: Option<&str> = None;
+    for ((name, labels), value) in samples {
+        if last_name != Some(name.as_str()) {
+            let _ = writeln!(out, "# TYPE {} {}", name, kind);
+            last_name = Some(name.as_str());
+        }
+
+        if labels.is_empty() {
+            let _ = writeln!(out, "{} {}", name, value);
+        } else {
+            let labels: Vec<String> = labels
+                .iter()
+                .map(|(k, v)| format!("{}=\"{}\"", k, v.replace('\\', "\\\\").replace('"', "\\\"")))
+                .collect();
+            let _ = writeln!(out, "{}{{{}}} {}", name, labels.join(","), value);
+        }
+    }
+}
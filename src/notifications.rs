@@ -0,0 +1,43 @@
+use serde_json::{Value, json};
+use tokio::sync::broadcast;
+
+/// Notifications buffered per subscriber before slow listeners start missing some
+const CHANNEL_CAPACITY: usize = 64;
+
+/// Server-initiated notifications delivered to clients listening on `GET /mcp`
+#[derive(Clone)]
+pub struct Notifier {
+    sender: broadcast::Sender<Value>,
+}
+
+impl Default for Notifier {
+    fn default() -> Self {
+        Self {
+            sender: broadcast::channel(CHANNEL_CAPACITY).0,
+        }
+    }
+}
+
+impl Notifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Listen for notifications sent from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<Value> {
+        self.sender.subscribe()
+    }
+
+    /// Send a JSON-RPC notification to every listener
+    pub fn notify(&self, method: &str) {
+        // Nobody listening is not an error
+        let _ = self
+            .sender
+            .send(json!({ "jsonrpc": "2.0", "method": method }));
+    }
+
+    /// Tell clients to re-run `discover`
+    pub fn tools_list_changed(&self) {
+        self.notify("notifications/tools/list_changed");
+    }
+}
@@ -0,0 +1,304 @@
+use crate::http_tools::{HttpRequestTemplate, run_http_tool};
+use crate::tools::{ToolContext, ToolFunction, initialize_tools, validate_tool_args};
+use crate::{AppState, ToolDefinition};
+use anyhow::{Context, Result, anyhow, bail};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::task::JoinHandle;
+
+/// Declarative tool read from a `*.toml`, `*.yaml` or `*.yml` file in the
+/// plugins directory
+///
+/// The tool either runs a `command`, which receives the arguments as JSON on
+/// stdin and answers on stdout, or sends the `http` request template. JSON
+/// output is returned as-is, anything else as a string.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PluginManifest {
+    pub name: String,
+    pub description: String,
+    /// JSON Schema for parameters, checked before the command runs
+    #[serde(default = "default_parameters")]
+    pub parameters: Value,
+    #[serde(default)]
+    pub output_schema: Option<Value>,
+    #[serde(default)]
+    pub annotations: Option<Value>,
+    #[serde(default)]
+    pub version: Option<String>,
+    /// Budget units an invocation consumes
+    #[serde(default)]
+    pub cost: Option<u64>,
+    /// Program and arguments; relative paths are resolved against the plugins directory
+    #[serde(default)]
+    pub command: Vec<String>,
+    /// HTTP request sent instead of running a command
+    #[serde(default)]
+    pub http: Option<HttpRequestTemplate>,
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_parameters() -> Value {
+    json!({ "type": "object", "properties": {} })
+}
+
+fn default_timeout_secs() -> u64 {
+    30
+}
+
+impl PluginManifest {
+    /// Parse a manifest file
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read plugin manifest {}", path.display()))?;
+        let manifest: Self = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("yaml") | Some("yml") => serde_yaml::from_str(&contents).map_err(Into::into),
+            _ => toml::from_str(&contents).map_err(anyhow::Error::from),
+        }
+        .with_context(|| format!("Failed to parse plugin manifest {}", path.display()))?;
+        match (&manifest.http, manifest.command.is_empty()) {
+            (None, true) => bail!("Plugin '{}' has an empty command", manifest.name),
+            (Some(_), false) => bail!(
+                "Plugin '{}' has both a command and an HTTP request",
+                manifest.name
+            ),
+            (Some(http), true) => http.check().with_context(|| {
+                format!("Plugin '{}' has an invalid HTTP request", manifest.name)
+            })?,
+            (None, false) => {}
+        }
+        Ok(manifest)
+    }
+}
+
+/// Manifest files in the plugins directory, sorted by path
+fn manifest_paths(dir: &Path) -> Result<Vec<PathBuf>> {
+    let entries = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read plugins directory {}", dir.display()))?;
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext == "toml" || ext == "yaml" || ext == "yml")
+        })
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+/// Load every manifest in the plugins directory
+///
+/// Invalid manifests, names in `reserved` (the built-in tools) and repeated
+/// names are skipped with a warning instead of failing the whole directory.
+pub fn load_plugins(
+    dir: &Path,
+    reserved: &HashSet<String>,
+) -> Result<(HashMap<String, ToolFunction>, Vec<ToolDefinition>)> {
+    let mut functions = HashMap::new();
+    let mut definitions = Vec::new();
+
+    for path in manifest_paths(dir)? {
+        let manifest = match PluginManifest::load(&path) {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                tracing::warn!("Skipping plugin: {:#}", e);
+                continue;
+            }
+        };
+        if reserved.contains(&manifest.name) {
+            tracing::warn!(
+                "Skipping plugin {}: '{}' is a built-in tool",
+                path.display(),
+                manifest.name
+            );
+            continue;
+        }
+        if functions.contains_key(&manifest.name) {
+            tracing::warn!(
+                "Skipping plugin {}: tool '{}' is already defined by another plugin",
+                path.display(),
+                manifest.name
+            );
+            continue;
+        }
+
+        definitions.push(ToolDefinition {
+            name: manifest.name.clone(),
+            description: manifest.description.clone(),
+            parameters: manifest.parameters.clone(),
+            output_schema: manifest.output_schema.clone(),
+            annotations: manifest.annotations.clone(),
+            version: manifest.version.clone(),
+            cost: manifest.cost,
+            required_external_keys: manifest
+                .http
+                .as_ref()
+                .map(HttpRequestTemplate::external_keys)
+                .unwrap_or_default(),
+            ..Default::default()
+        });
+        functions.insert(manifest.name.clone(), plugin_function(manifest, dir));
+    }
+
+    Ok((functions, definitions))
+}
+
+/// Rebuild the global tool registry from the built-in tools and the plugins
+/// directory, then notify listening clients; returns the number of plugins loaded
+pub fn reload_plugins(state: &AppState, dir: &Path) -> Result<usize> {
+    let (mut functions, mut definitions) = initialize_tools(state.config.tools.duplicate_names)?;
+    let reserved = functions.keys().cloned().collect();
+    let (plugin_functions, plugin_definitions) = load_plugins(dir, &reserved)?;
+    let loaded = plugin_definitions.len();
+
+    functions.extend(plugin_functions);
+    definitions.extend(plugin_definitions);
+    state.replace_tools(functions, definitions);
+    state.notifications.tools_list_changed();
+    Ok(loaded)
+}
+
+/// Modification time and size of every manifest, used to detect changes
+fn snapshot(dir: &Path) -> Result<Vec<(PathBuf, Option<SystemTime>, u64)>> {
+    Ok(manifest_paths(dir)?
+        .into_iter()
+        .map(|path| {
+            let metadata = fs::metadata(&path).ok();
+            let modified = metadata.as_ref().and_then(|m| m.modified().ok());
+            let len = metadata.map_or(0, |m| m.len());
+            (path, modified, len)
+        })
+        .collect())
+}
+
+/// Poll the plugins directory and reload the tools whenever a manifest is
+/// added, changed or removed; a failed reload keeps the current tools
+pub fn watch_plugins(state: AppState, dir: PathBuf, interval: Duration) -> JoinHandle<()> {
+    // Taken before spawning, so changes made right after the call are not missed
+    let mut last = snapshot(&dir).ok();
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.tick().await;
+
+        loop {
+            ticker.tick().await;
+            let current = match snapshot(&dir) {
+                Ok(current) => current,
+                Err(e) => {
+                    tracing::warn!("Failed to scan plugins: {:#}", e);
+                    continue;
+                }
+            };
+            if last.as_ref() == Some(&current) {
+                continue;
+            }
+            match reload_plugins(&state, &dir) {
+                Ok(loaded) => tracing::info!("Reloaded plugins: {} loaded", loaded),
+                Err(e) => tracing::warn!("Failed to reload plugins: {:#}", e),
+            }
+            last = Some(current);
+        }
+    })
+}
+
+/// A manifest ready to run, with its program resolved
+struct Plugin {
+    manifest: PluginManifest,
+    program: PathBuf,
+    dir: PathBuf,
+}
+
+fn plugin_function(manifest: PluginManifest, dir: &Path) -> ToolFunction {
+    if manifest.http.is_some() {
+        let manifest = Arc::new(manifest);
+        return Box::new(move |args, ctx| {
+            let manifest = manifest.clone();
+            Box::pin(async move {
+                validate_tool_args(&manifest.parameters, &args)?;
+                let Some(http) = &manifest.http else {
+                    unreachable!("HTTP plugins have a request template");
+                };
+                let timeout = Duration::from_secs(manifest.timeout_secs);
+                run_http_tool(&manifest.name, http, timeout, args, &ctx).await
+            })
+        });
+    }
+
+    // Absolute, so the program does not depend on how the child resolves paths
+    let dir = std::path::absolute(dir).unwrap_or_else(|_| dir.to_path_buf());
+    let program = Path::new(&manifest.command[0]);
+    // Bare program names are looked up on PATH
+    let program = if program.components().count() > 1 && program.is_relative() {
+        dir.join(program)
+    } else {
+        program.to_path_buf()
+    };
+    let plugin = Arc::new(Plugin {
+        manifest,
+        program,
+        dir,
+    });
+
+    Box::new(move |args, ctx| {
+        let plugin = plugin.clone();
+        Box::pin(async move { run_plugin(&plugin, args, ctx).await })
+    })
+}
+
+async fn run_plugin(plugin: &Plugin, args: Option<Value>, ctx: ToolContext) -> Result<Value> {
+    let name = &plugin.manifest.name;
+    validate_tool_args(&plugin.manifest.parameters, &args)?;
+    let input = serde_json::to_vec(&args.unwrap_or_else(|| json!({})))?;
+
+    let mut child = Command::new(&plugin.program)
+        .args(&plugin.manifest.command[1..])
+        .current_dir(&plugin.dir)
+        .env("MCP_USERNAME", &ctx.user().credentials().username)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("Failed to start plugin '{}'", name))?;
+
+    let timeout = Duration::from_secs(plugin.manifest.timeout_secs);
+    let output = tokio::time::timeout(timeout, async move {
+        // Plugins are free to exit without reading their input
+        if let Some(mut stdin) = child.stdin.take()
+            && let Err(e) = stdin.write_all(&input).await
+            && e.kind() != std::io::ErrorKind::BrokenPipe
+        {
+            return Err(e);
+        }
+        child.wait_with_output().await
+    })
+    .await
+    .map_err(|_| anyhow!("Plugin '{}' timed out after {:?}", name, timeout))?
+    .with_context(|| format!("Plugin '{}' failed to run", name))?;
+
+    if !output.status.success() {
+        bail!(
+            "Plugin '{}' exited with {}: {}",
+            name,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(serde_json::from_slice(&output.stdout).unwrap_or_else(|_| {
+        Value::String(
+            String::from_utf8_lossy(&output.stdout)
+                .trim_end()
+                .to_string(),
+        )
+    }))
+}
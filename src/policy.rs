@@ -0,0 +1,316 @@
+//! Role-based tool authorization policies
+//!
+//! A policies file lists rules granting or denying roles access to tool
+//! namespaces, e.g. role `analyst` may invoke `db.*` but not `admin.*`:
+//!
+//! ```toml
+//! default = "deny"
+//!
+//! [[rules]]
+//! roles = ["analyst"]
+//! tools = ["db.*"]
+//! effect = "allow"
+//!
+//! [[rules]]
+//! roles = ["analyst"]
+//! tools = ["admin.*"]
+//! effect = "deny"
+//! reason = "Analysts have read-only access"
+//! ```
+//!
+//! Deny rules win over allow rules; tools no rule matches get the default.
+//!
+//! Decisions can also be delegated to an external engine through an
+//! `Authorizer`, such as Open Policy Agent (`OpaAuthorizer`).
+
+use crate::auth::{AuthFuture, AuthenticatedUser};
+use crate::config::OpaConfig;
+use crate::session::ClientInfo;
+use anyhow::{Context, Result, anyhow, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+/// Outcome of a rule, or of the evaluation as a whole
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Effect {
+    #[default]
+    Allow,
+    Deny,
+}
+
+/// Contents of a policies file
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PolicyFile {
+    /// Effect for invocations no rule matches
+    pub default: Effect,
+    pub rules: Vec<PolicyRule>,
+}
+
+/// Grants or denies the users holding one of `roles` access to `tools`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PolicyRule {
+    /// Roles from the credentials file; `*` matches every user
+    pub roles: Vec<String>,
+    /// Tool names or namespaces, with `*` matching any run of characters
+    pub tools: Vec<String>,
+    pub effect: Effect,
+    /// Explanation returned to denied clients
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// Why an invocation was denied, returned as the error data
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Denial {
+    pub tool: String,
+    pub roles: Vec<String>,
+    /// Tool pattern of the deny rule, absent when the default denied
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+    pub reason: String,
+}
+
+/// Evaluates the rules of a policies file; allows everything when empty
+#[derive(Debug, Clone, Default)]
+pub struct PolicyEngine {
+    policies: PolicyFile,
+}
+
+impl PolicyEngine {
+    pub fn new(policies: PolicyFile) -> Result<Self> {
+        for (index, rule) in policies.rules.iter().enumerate() {
+            if rule.roles.is_empty() || rule.tools.is_empty() {
+                bail!(
+                    "Policy rule {} must list at least one role and one tool",
+                    index + 1
+                );
+            }
+        }
+        Ok(Self { policies })
+    }
+
+    /// Load the policies file at `path`
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read policies file {}", path.display()))?;
+        let policies = toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse policies file {}", path.display()))?;
+        Self::new(policies)
+    }
+
+    /// Decide whether a user holding `roles` may invoke `tool_name`
+    pub fn evaluate(&self, roles: &[String], tool_name: &str) -> Result<(), Denial> {
+        let mut allowed = false;
+        for rule in &self.policies.rules {
+            if !rule
+                .roles
+                .iter()
+                .any(|role| role == "*" || roles.contains(role))
+            {
+                continue;
+            }
+            let Some(pattern) = rule
+                .tools
+                .iter()
+                .find(|pattern| matches_pattern(pattern, tool_name))
+            else {
+                continue;
+            };
+            match rule.effect {
+                Effect::Deny => {
+                    return Err(Denial {
+                        tool: tool_name.to_string(),
+                        roles: roles.to_vec(),
+                        rule: Some(pattern.clone()),
+                        reason: rule
+                            .reason
+                            .clone()
+                            .unwrap_or_else(|| format!("Denied by policy for '{}'", pattern)),
+                    });
+                }
+                Effect::Allow => allowed = true,
+            }
+        }
+
+        if allowed || self.policies.default == Effect::Allow {
+            return Ok(());
+        }
+        Err(Denial {
+            tool: tool_name.to_string(),
+            roles: roles.to_vec(),
+            rule: None,
+            reason: "No policy allows this tool for the user's roles".to_string(),
+        })
+    }
+}
+
+/// Match a name against a pattern where `*` stands for any run of characters
+pub fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No wildcard: the whole name must match
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+/// What an external policy engine decides on: who invokes which tool, with
+/// the arguments the tool would receive
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthorizationRequest<'a> {
+    pub user: AuthorizationUser<'a>,
+    pub tool: &'a str,
+    pub arguments: &'a Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client: Option<&'a ClientInfo>,
+}
+
+/// The invoking user, without any secrets
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthorizationUser<'a> {
+    pub username: &'a str,
+    pub roles: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<&'a str>,
+    pub admin: bool,
+}
+
+impl<'a> AuthorizationRequest<'a> {
+    pub fn new(
+        user: &'a AuthenticatedUser,
+        tool: &'a str,
+        arguments: &'a Value,
+        client: Option<&'a ClientInfo>,
+    ) -> Self {
+        Self {
+            user: AuthorizationUser {
+                username: &user.credentials().username,
+                roles: user.roles(),
+                tenant: user.tenant(),
+                admin: user.is_admin(),
+            },
+            tool,
+            arguments,
+            client,
+        }
+    }
+
+    /// Denial of this request with `reason`
+    pub fn deny(&self, reason: impl Into<String>) -> Denial {
+        Denial {
+            tool: self.tool.to_string(),
+            roles: self.user.roles.to_vec(),
+            rule: None,
+            reason: reason.into(),
+        }
+    }
+}
+
+/// Tool-access decision made outside the policies file
+///
+/// Implement this to evaluate embedded Rego or CEL, or to call a central
+/// policy service; register it with `AppOptions::with_authorizer`. Consulted
+/// after the policies file allowed the invocation. An error fails the
+/// invocation without running the tool.
+pub trait Authorizer: Send + Sync {
+    fn authorize<'a>(
+        &'a self,
+        request: &'a AuthorizationRequest<'a>,
+    ) -> AuthFuture<'a, Result<(), Denial>>;
+}
+
+/// Asks an Open Policy Agent decision endpoint, e.g.
+/// `http://localhost:8181/v1/data/mcp/allow`
+///
+/// The request is posted as OPA `input`. The decision is either a boolean or
+/// an object with an `allow` boolean and an optional `reason`.
+pub struct OpaAuthorizer {
+    client: reqwest::Client,
+    url: String,
+    fail_open: bool,
+}
+
+impl OpaAuthorizer {
+    pub fn new(url: &str, config: &OpaConfig) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.timeout_ms))
+            .build()?;
+        Ok(Self {
+            client,
+            url: url.to_string(),
+            fail_open: config.fail_open,
+        })
+    }
+
+    async fn query(&self, request: &AuthorizationRequest<'_>) -> Result<Result<(), Denial>> {
+        let response = self
+            .client
+            .post(&self.url)
+            .json(&json!({ "input": request }))
+            .send()
+            .await
+            .context("Policy engine unreachable")?;
+        let status = response.status();
+        if !status.is_success() {
+            bail!("Policy engine returned HTTP {}", status.as_u16());
+        }
+        let body: Value = response
+            .json()
+            .await
+            .context("Invalid policy engine response")?;
+
+        // An undefined decision (no `result`) denies like `false`
+        let (allow, reason) = match &body["result"] {
+            Value::Bool(allow) => (*allow, None),
+            Value::Null => (false, None),
+            Value::Object(decision) => (
+                decision
+                    .get("allow")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false),
+                decision.get("reason").and_then(Value::as_str),
+            ),
+            other => return Err(anyhow!("Unexpected policy decision: {}", other)),
+        };
+        if allow {
+            return Ok(Ok(()));
+        }
+        Ok(Err(request.deny(
+            reason.unwrap_or("Denied by the external policy engine"),
+        )))
+    }
+}
+
+impl Authorizer for OpaAuthorizer {
+    fn authorize<'a>(
+        &'a self,
+        request: &'a AuthorizationRequest<'a>,
+    ) -> AuthFuture<'a, Result<(), Denial>> {
+        Box::pin(async move {
+            match self.query(request).await {
+                Err(e) if self.fail_open => {
+                    tracing::warn!(tool = request.tool, "{:#}; allowing (fail_open)", e);
+                    Ok(Ok(()))
+                }
+                decision => decision,
+            }
+        })
+    }
+}
@@ -0,0 +1,85 @@
+use anyhow::Result;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Connections to the backends users configure in their external keys
+/// (databases, caches), kept across invocations
+///
+/// Pools are keyed by a digest of the connection URL, so users sharing a
+/// backend share its pool and no credentials are kept as map keys. Pools
+/// unused for `idle_ttl` are dropped on the next lookup; beyond `max_entries`
+/// the least recently used one is.
+pub struct PoolManager<P> {
+    entries: Mutex<HashMap<String, PoolEntry<P>>>,
+    max_entries: usize,
+    idle_ttl: Duration,
+}
+
+struct PoolEntry<P> {
+    pool: P,
+    last_used: Instant,
+}
+
+impl<P: Clone> PoolManager<P> {
+    pub fn new(max_entries: usize, idle_ttl: Duration) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            max_entries,
+            idle_ttl,
+        }
+    }
+
+    /// The pool for `url`, connecting with `connect` when there is none
+    ///
+    /// The lock is not held while connecting: concurrent first uses of a URL
+    /// may each connect, and the last one is kept.
+    pub async fn get_or_connect<F, Fut>(&self, url: &str, connect: F) -> Result<P>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<P>>,
+    {
+        let key = hex::encode(Sha256::digest(url.as_bytes()));
+        if let Some(entry) = self.lock().get_mut(&key) {
+            entry.last_used = Instant::now();
+            return Ok(entry.pool.clone());
+        }
+
+        let pool = connect().await?;
+        let mut entries = self.lock();
+        let now = Instant::now();
+        entries.retain(|_, entry| now.duration_since(entry.last_used) < self.idle_ttl);
+        if entries.len() >= self.max_entries {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(
+            key,
+            PoolEntry {
+                pool: pool.clone(),
+                last_used: now,
+            },
+        );
+        Ok(pool)
+    }
+
+    /// Number of pools currently kept
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, PoolEntry<P>>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
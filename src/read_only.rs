@@ -0,0 +1,46 @@
+use crate::ToolDefinition;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Server-wide switch refusing invocations of tools that may modify state
+///
+/// Discovery and tools annotated as read-only keep working, e.g. during an
+/// incident or a maintenance window.
+#[derive(Debug, Default)]
+pub struct ReadOnlyMode {
+    enabled: AtomicBool,
+}
+
+impl ReadOnlyMode {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled: AtomicBool::new(enabled),
+        }
+    }
+
+    pub fn set(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::SeqCst);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::SeqCst)
+    }
+
+    /// Whether an invocation of the tool must be refused right now
+    pub fn blocks(&self, definition: Option<&ToolDefinition>) -> bool {
+        self.is_enabled() && definition.is_none_or(is_destructive)
+    }
+}
+
+/// Whether a tool may destroy or overwrite data, following the MCP annotation
+/// defaults: tools are destructive unless they declare `readOnlyHint: true` or
+/// `destructiveHint: false`
+pub fn is_destructive(definition: &ToolDefinition) -> bool {
+    let hint = |name: &str| {
+        definition
+            .annotations
+            .as_ref()
+            .and_then(|annotations| annotations.get(name))
+            .and_then(|value| value.as_bool())
+    };
+    hint("readOnlyHint") != Some(true) && hint("destructiveHint") != Some(false)
+}
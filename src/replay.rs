@@ -0,0 +1,160 @@
+use crate::auth::{AuthError, AuthenticatedUser};
+use crate::store::SharedStore;
+use axum::{
+    Extension,
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::time::Duration;
+
+/// Unix timestamp (seconds) of the request
+pub const TIMESTAMP_HEADER: &str = "x-request-timestamp";
+/// Single-use random value chosen by the client
+pub const NONCE_HEADER: &str = "x-request-nonce";
+/// Hex HMAC-SHA256 of the timestamp, nonce, method and path, keyed by the API key
+pub const SIGNATURE_HEADER: &str = "x-request-signature";
+
+const MAX_NONCE_LEN: usize = 128;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Sign a request for replay protection
+///
+/// The signed message is `"{timestamp}\n{nonce}\n{METHOD}\n{path_and_query}"`.
+pub fn sign_request(
+    api_key: &str,
+    timestamp: i64,
+    nonce: &str,
+    method: &str,
+    path: &str,
+) -> String {
+    hex::encode(
+        mac(api_key, timestamp, nonce, method, path)
+            .finalize()
+            .into_bytes(),
+    )
+}
+
+fn mac(api_key: &str, timestamp: i64, nonce: &str, method: &str, path: &str) -> HmacSha256 {
+    let mut mac =
+        HmacSha256::new_from_slice(api_key.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(format!("{}\n{}\n{}\n{}", timestamp, nonce, method, path).as_bytes());
+    mac
+}
+
+/// Timestamp, nonce and signature sent with a request
+pub struct RequestProof {
+    pub timestamp: i64,
+    pub nonce: String,
+    pub signature: Vec<u8>,
+    pub method: String,
+    pub path: String,
+}
+
+impl RequestProof {
+    /// Read the replay protection headers of a request
+    pub fn from_request(req: &Request) -> Result<Self, AuthError> {
+        let header = |name: &str| {
+            req.headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .ok_or(AuthError::MissingReplayHeaders)
+        };
+
+        let timestamp = header(TIMESTAMP_HEADER)?
+            .parse()
+            .map_err(|_| AuthError::MissingReplayHeaders)?;
+        let nonce = header(NONCE_HEADER)?;
+        if nonce.is_empty() || nonce.len() > MAX_NONCE_LEN {
+            return Err(AuthError::MissingReplayHeaders);
+        }
+        let signature =
+            hex::decode(header(SIGNATURE_HEADER)?).map_err(|_| AuthError::MissingReplayHeaders)?;
+
+        let uri = req.uri();
+        Ok(Self {
+            timestamp,
+            nonce: nonce.to_string(),
+            signature,
+            method: req.method().to_string(),
+            path: uri
+                .path_and_query()
+                .map_or_else(|| uri.path().to_string(), |p| p.to_string()),
+        })
+    }
+}
+
+/// Rejects stale, replayed or unsigned requests
+///
+/// Used nonces are remembered in the shared store for twice the accepted skew,
+/// which covers every timestamp that could still pass the window check.
+#[derive(Clone)]
+pub struct ReplayGuard {
+    store: SharedStore,
+    max_skew: Duration,
+}
+
+impl ReplayGuard {
+    pub fn new(store: SharedStore, max_skew: Duration) -> Self {
+        Self { store, max_skew }
+    }
+
+    /// Verify a request's proof for the authenticated user
+    pub async fn verify(
+        &self,
+        user: &AuthenticatedUser,
+        proof: &RequestProof,
+    ) -> Result<(), AuthError> {
+        let skew = Utc::now().timestamp().abs_diff(proof.timestamp);
+        if skew > self.max_skew.as_secs() {
+            return Err(AuthError::StaleRequest);
+        }
+
+        let credentials = user.credentials();
+        mac(
+            &credentials.api_key,
+            proof.timestamp,
+            &proof.nonce,
+            &proof.method,
+            &proof.path,
+        )
+        .verify_slice(&proof.signature)
+        .map_err(|_| AuthError::InvalidSignature)?;
+
+        // Only signed requests may consume a nonce
+        let key = format!("nonce:{}:{}", credentials.username, proof.nonce);
+        match self
+            .store
+            .set_if_absent(&key, String::new(), Some(self.max_skew * 2))
+            .await
+        {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(AuthError::ReplayedNonce),
+            Err(e) => {
+                tracing::warn!("Replay protection store error: {:#}", e);
+                Err(AuthError::Unavailable)
+            }
+        }
+    }
+}
+
+/// Middleware enforcing replay protection, installed inside the auth layer
+pub async fn replay_protection(
+    State(guard): State<ReplayGuard>,
+    Extension(user): Extension<AuthenticatedUser>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let proof = match RequestProof::from_request(&req) {
+        Ok(proof) => proof,
+        Err(e) => return e.into_response(),
+    };
+    match guard.verify(&user, &proof).await {
+        Ok(()) => next.run(req).await,
+        Err(e) => e.into_response(),
+    }
+}
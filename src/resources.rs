@@ -0,0 +1,72 @@
+use crate::store::SharedStore;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::time::Duration;
+
+/// URI scheme and path of offloaded tool results
+pub const RESULT_URI_PREFIX: &str = "mcp://results/";
+
+/// Resource content readable with `resources/read`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredResource {
+    pub username: String,
+    pub mime_type: String,
+    pub text: String,
+}
+
+/// Resources kept in the shared store, readable only by the user they belong to
+#[derive(Clone, Default)]
+pub struct ResourceStore {
+    store: SharedStore,
+    ttl: Option<Duration>,
+}
+
+impl ResourceStore {
+    /// Resources expire after `ttl`; zero keeps them until the store is cleared
+    pub fn new(store: SharedStore, ttl: Duration) -> Self {
+        Self {
+            store,
+            ttl: (!ttl.is_zero()).then_some(ttl),
+        }
+    }
+
+    /// Store a tool result for a user and return a `resource_link` content block
+    pub async fn offload(&self, username: &str, tool_name: &str, text: String) -> Result<Value> {
+        let uri = format!("{}{:032x}", RESULT_URI_PREFIX, rand::random::<u128>());
+        let size = text.len();
+        let resource = StoredResource {
+            username: username.to_string(),
+            mime_type: "application/json".to_string(),
+            text,
+        };
+        self.store
+            .set(
+                &Self::key(&uri),
+                serde_json::to_string(&resource)?,
+                self.ttl,
+            )
+            .await?;
+
+        Ok(json!({
+            "type": "resource_link",
+            "uri": uri,
+            "name": format!("{} result", tool_name),
+            "mimeType": resource.mime_type,
+            "size": size,
+        }))
+    }
+
+    /// Read a user's resource; `None` when missing, expired or owned by someone else
+    pub async fn read(&self, uri: &str, username: &str) -> Result<Option<StoredResource>> {
+        let Some(stored) = self.store.get(&Self::key(uri)).await? else {
+            return Ok(None);
+        };
+        let resource: StoredResource = serde_json::from_str(&stored)?;
+        Ok((resource.username == username).then_some(resource))
+    }
+
+    fn key(uri: &str) -> String {
+        format!("resource:{}", uri)
+    }
+}
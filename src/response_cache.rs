@@ -0,0 +1,111 @@
+//! Caching of repeated identical requests
+//!
+//! Agent loops often ask the same question several times in a row. Tools that
+//! opt in with a `cacheTtlSecs` annotation have their successful results kept
+//! in the shared store, keyed by a hash of the user, tool, tool version and
+//! arguments, and identical invocations get the stored result until it
+//! expires. Invocations are still authorized as usual; a cache hit only skips
+//! the queue, the budget and the tool itself.
+//!
+//! Over HTTP, discover and cacheable invoke responses carry a weak `ETag` of
+//! their result, and requests whose `If-None-Match` lists it are answered
+//! with `304 Not Modified` and no body.
+
+use crate::ToolDefinition;
+use crate::config::ResponseCacheConfig;
+use crate::store::SharedStore;
+use anyhow::Result;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+
+/// Annotation opting a tool into the cache, giving how long results are kept
+pub const CACHE_TTL_ANNOTATION: &str = "cacheTtlSecs";
+
+/// How long results of the tool may be cached, `None` unless it declares a
+/// positive `cacheTtlSecs`
+pub fn cache_ttl(definition: &ToolDefinition) -> Option<Duration> {
+    definition
+        .annotations
+        .as_ref()
+        .and_then(|annotations| annotations.get(CACHE_TTL_ANNOTATION))
+        .and_then(Value::as_u64)
+        .filter(|&secs| secs > 0)
+        .map(Duration::from_secs)
+}
+
+/// Results of cacheable tools, in the shared store so replicas share them
+#[derive(Clone)]
+pub struct ResponseCache {
+    store: SharedStore,
+    max_entry_bytes: usize,
+}
+
+impl Default for ResponseCache {
+    fn default() -> Self {
+        Self::new(SharedStore::default(), &ResponseCacheConfig::default())
+    }
+}
+
+impl ResponseCache {
+    pub fn new(store: SharedStore, config: &ResponseCacheConfig) -> Self {
+        Self {
+            store,
+            max_entry_bytes: config.max_entry_bytes,
+        }
+    }
+
+    /// Store key of an invocation; arguments are hashed in their serialized
+    /// form, whose object keys are sorted
+    pub fn key(username: &str, definition: &ToolDefinition, arguments: Option<&Value>) -> String {
+        let mut hasher = Sha256::new();
+        for part in [
+            definition.name.as_str(),
+            definition.version.as_deref().unwrap_or_default(),
+            arguments.map(Value::to_string).unwrap_or_default().as_str(),
+        ] {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        format!(
+            "response_cache:{}:{}",
+            username,
+            hex::encode(hasher.finalize())
+        )
+    }
+
+    /// The cached result for `key`, if any
+    pub async fn get(&self, key: &str) -> Result<Option<Value>> {
+        match self.store.get(key).await? {
+            Some(entry) => Ok(Some(serde_json::from_str(&entry)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Cache a result for `ttl`; returns whether it was small enough to keep
+    pub async fn put(&self, key: &str, result: &Value, ttl: Duration) -> Result<bool> {
+        let entry = result.to_string();
+        if entry.len() > self.max_entry_bytes {
+            return Ok(false);
+        }
+        self.store.set(key, entry, Some(ttl)).await?;
+        Ok(true)
+    }
+}
+
+/// Weak entity tag of a result: equal results get equal tags whatever the
+/// body encoding
+pub fn etag(result: &Value) -> String {
+    let digest = Sha256::digest(result.to_string().as_bytes());
+    format!("W/\"{}\"", &hex::encode(digest)[..32])
+}
+
+/// Whether an `If-None-Match` header value lists `etag` (or is `*`), using
+/// the weak comparison HTTP prescribes for it
+pub fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    let etag = opaque(etag);
+    if_none_match
+        .split(',')
+        .any(|candidate| candidate.trim() == "*" || opaque(candidate) == etag)
+}
@@ -0,0 +1,163 @@
+use crate::config::SchedulingConfig;
+use crate::metrics::Metrics;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+
+/// Returned when the waiting queue is at capacity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueueFull;
+
+/// Invocation waiting for a free execution slot
+struct Waiter {
+    priority: i32,
+    seq: u64,
+    wake: oneshot::Sender<()>,
+}
+
+impl PartialEq for Waiter {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Waiter {}
+
+impl PartialOrd for Waiter {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Waiter {
+    /// Higher priority first, FIFO within the same priority
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+#[derive(Default)]
+struct SchedulerState {
+    in_flight: usize,
+    next_seq: u64,
+    waiting: BinaryHeap<Waiter>,
+}
+
+/// Bounded-concurrency scheduler with a priority-ordered waiting queue
+///
+/// When all `max_concurrent` slots are busy, invocations wait in a queue of at
+/// most `max_queue` entries and are released highest priority first.
+pub struct InvocationScheduler {
+    config: SchedulingConfig,
+    metrics: Arc<Metrics>,
+    state: Mutex<SchedulerState>,
+}
+
+/// Execution slot, released (and handed to the next waiter) on drop
+pub struct SchedulerPermit {
+    scheduler: Option<Arc<InvocationScheduler>>,
+}
+
+impl InvocationScheduler {
+    pub fn new(config: SchedulingConfig, metrics: Arc<Metrics>) -> Self {
+        Self {
+            config,
+            metrics,
+            state: Mutex::new(SchedulerState::default()),
+        }
+    }
+
+    /// Wait for an execution slot; fails immediately when the queue is full
+    pub async fn acquire(self: &Arc<Self>, priority: i32) -> Result<SchedulerPermit, QueueFull> {
+        if self.config.max_concurrent == 0 {
+            return Ok(SchedulerPermit { scheduler: None });
+        }
+
+        let wait = {
+            let mut state = self.lock();
+            if state.in_flight < self.config.max_concurrent {
+                state.in_flight += 1;
+                self.publish(&state);
+                None
+            } else if state.waiting.len() >= self.config.max_queue {
+                self.metrics
+                    .increment_counter("mcp_invocation_queue_rejected_total", &[]);
+                return Err(QueueFull);
+            } else {
+                let (wake, woken) = oneshot::channel();
+                let seq = state.next_seq;
+                state.next_seq += 1;
+                state.waiting.push(Waiter {
+                    priority,
+                    seq,
+                    wake,
+                });
+                self.publish(&state);
+                Some(woken)
+            }
+        };
+
+        if let Some(woken) = wait {
+            // The slot is transferred by the releasing permit, in_flight is unchanged
+            let _ = woken.await;
+        }
+
+        Ok(SchedulerPermit {
+            scheduler: Some(self.clone()),
+        })
+    }
+
+    /// Number of invocations currently executing
+    pub fn in_flight(&self) -> usize {
+        self.lock().in_flight
+    }
+
+    /// Number of invocations waiting for a slot
+    pub fn queue_depth(&self) -> usize {
+        self.lock().waiting.len()
+    }
+
+    fn release(&self) {
+        let mut state = self.lock();
+        // Hand the slot to the best waiter still listening (it may have disconnected)
+        while let Some(waiter) = state.waiting.pop() {
+            if waiter.wake.send(()).is_ok() {
+                self.publish(&state);
+                return;
+            }
+        }
+        state.in_flight -= 1;
+        self.publish(&state);
+    }
+
+    fn publish(&self, state: &SchedulerState) {
+        self.metrics
+            .set_gauge("mcp_invocations_in_flight", &[], state.in_flight as i64);
+        self.metrics.set_gauge(
+            "mcp_invocation_queue_depth",
+            &[],
+            state.waiting.len() as i64,
+        );
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, SchedulerState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for InvocationScheduler {
+    fn default() -> Self {
+        Self::new(SchedulingConfig::default(), Arc::default())
+    }
+}
+
+impl Drop for SchedulerPermit {
+    fn drop(&mut self) {
+        if let Some(scheduler) = self.scheduler.take() {
+            scheduler.release();
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use crate::logging::LogLevel;
+use crate::store::SharedStore;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::OwnedMutexGuard;
+
+/// Header carrying the session id issued by `initialize`
+pub const SESSION_HEADER: &str = "mcp-session-id";
+
+/// Client identification from `initialize` and the `User-Agent` header
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClientInfo {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+    /// Address of the current request, resolved through the trusted proxies;
+    /// never taken from what the client reports
+    #[serde(default, skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub ip: Option<IpAddr>,
+}
+
+impl ClientInfo {
+    /// Client name used as a metrics label, `unknown` when the client did not say
+    pub fn label(&self) -> &str {
+        self.name.as_deref().unwrap_or("unknown")
+    }
+}
+
+/// A client session opened with `initialize`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub id: String,
+    pub username: String,
+    pub protocol_version: String,
+    pub client: ClientInfo,
+    /// Invocations in this session run one at a time, in arrival order
+    #[serde(default)]
+    pub ordered: bool,
+    /// Least severe log notification level set with `logging/setLevel`;
+    /// `[logging] level` applies until the client sets one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_level: Option<LogLevel>,
+}
+
+/// Sessions kept in the shared store, so any replica can serve them
+#[derive(Clone, Default)]
+pub struct SessionManager {
+    store: SharedStore,
+    ttl: Option<Duration>,
+    queues: Arc<SessionQueues>,
+}
+
+impl SessionManager {
+    /// Sessions expire after `ttl` without use; zero keeps them forever
+    pub fn new(store: SharedStore, ttl: Duration) -> Self {
+        Self {
+            store,
+            ttl: (!ttl.is_zero()).then_some(ttl),
+            queues: Arc::default(),
+        }
+    }
+
+    /// Open a new session for a user
+    pub async fn create(
+        &self,
+        username: &str,
+        protocol_version: &str,
+        client: ClientInfo,
+    ) -> Result<Session> {
+        self.create_with_ordering(username, protocol_version, client, false)
+            .await
+    }
+
+    /// Open a new session, optionally running its invocations in arrival order
+    pub async fn create_with_ordering(
+        &self,
+        username: &str,
+        protocol_version: &str,
+        client: ClientInfo,
+        ordered: bool,
+    ) -> Result<Session> {
+        let session = Session {
+            id: format!("{:032x}", rand::random::<u128>()),
+            username: username.to_string(),
+            protocol_version: protocol_version.to_string(),
+            client,
+            ordered,
+            log_level: None,
+        };
+        self.save(&session).await?;
+        Ok(session)
+    }
+
+    /// Look up a user's session, refreshing its expiry
+    ///
+    /// Returns `None` for unknown or expired ids and for sessions opened by
+    /// another user.
+    pub async fn get(&self, id: &str, username: &str) -> Result<Option<Session>> {
+        let Some(stored) = self.store.get(&Self::key(id)).await? else {
+            return Ok(None);
+        };
+        let session: Session = serde_json::from_str(&stored)?;
+        if session.username != username {
+            return Ok(None);
+        }
+        if self.ttl.is_some() {
+            self.save(&session).await?;
+        }
+        Ok(Some(session))
+    }
+
+    /// Change the log notification level of a session
+    pub async fn set_log_level(&self, session: &Session, level: LogLevel) -> Result<Session> {
+        let session = Session {
+            log_level: Some(level),
+            ..session.clone()
+        };
+        self.save(&session).await?;
+        Ok(session)
+    }
+
+    async fn save(&self, session: &Session) -> Result<()> {
+        self.store
+            .set(
+                &Self::key(&session.id),
+                serde_json::to_string(session)?,
+                self.ttl,
+            )
+            .await
+    }
+
+    fn key(id: &str) -> String {
+        format!("session:{}", id)
+    }
+
+    /// Wait until earlier invocations of an ordered session have finished
+    /// Returns immediately for unordered sessions
+    pub async fn acquire_turn(&self, session: &Session) -> Option<SessionTurn> {
+        if !session.ordered {
+            return None;
+        }
+        Some(self.queues.acquire(&session.id).await)
+    }
+}
+
+/// FIFO queues serializing the invocations of ordered sessions
+///
+/// Queues are local to the instance, so ordering across replicas requires
+/// routing a session to a single replica.
+#[derive(Default)]
+struct SessionQueues {
+    queues: Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl SessionQueues {
+    async fn acquire(self: &Arc<Self>, session_id: &str) -> SessionTurn {
+        let queue = self
+            .queues
+            .lock()
+            .unwrap()
+            .entry(session_id.to_string())
+            .or_default()
+            .clone();
+        // tokio's mutex wakes waiters in the order they started waiting
+        let guard = queue.lock_owned().await;
+        SessionTurn {
+            guard: Some(guard),
+            session_id: session_id.to_string(),
+            queues: self.clone(),
+        }
+    }
+}
+
+/// An ordered session's turn to run an invocation; the next one starts when dropped
+pub struct SessionTurn {
+    guard: Option<OwnedMutexGuard<()>>,
+    session_id: String,
+    queues: Arc<SessionQueues>,
+}
+
+impl Drop for SessionTurn {
+    fn drop(&mut self) {
+        self.guard.take();
+        let mut queues = self.queues.queues.lock().unwrap();
+        // Only the map still holds the queue: nobody is waiting, so forget it
+        if queues
+            .get(&self.session_id)
+            .is_some_and(|queue| Arc::strong_count(queue) == 1)
+        {
+            queues.remove(&self.session_id);
+        }
+    }
+}
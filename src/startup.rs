@@ -0,0 +1,181 @@
+//! Configuration summary logged on boot, and printed by `--check`
+
+use crate::external_keys::find_missing_external_keys;
+use crate::{AppState, ToolDefinition};
+use serde::Serialize;
+use serde_json::Value;
+
+/// What the server is about to serve, and what looks wrong with it
+#[derive(Debug, Clone, Serialize)]
+pub struct StartupReport {
+    pub transports: Vec<Transport>,
+    /// Users in the credentials file, `None` with a custom auth provider
+    pub users: Option<usize>,
+    pub tenants: usize,
+    pub tools: Vec<ToolSummary>,
+    /// Problems that do not prevent startup but will fail invocations
+    pub warnings: Vec<String>,
+}
+
+/// A listener the server opens
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Transport {
+    pub name: &'static str,
+    pub bind: String,
+}
+
+/// A registered tool
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ToolSummary {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Annotation hints the tool sets, e.g. `readOnlyHint`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub hints: Vec<String>,
+}
+
+impl StartupReport {
+    /// Summarize `state` as served on its configured listeners
+    pub fn new(state: &AppState) -> Self {
+        let mut warnings = Vec::new();
+
+        let mut transports: Vec<Transport> = state
+            .config
+            .listeners()
+            .iter()
+            .map(|listener| Transport {
+                name: listener.scheme(),
+                bind: listener.address(),
+            })
+            .collect();
+        if state.config.grpc.enabled {
+            if cfg!(feature = "grpc") {
+                transports.push(Transport {
+                    name: "grpc",
+                    bind: state.config.grpc.bind.clone(),
+                });
+            } else {
+                warnings.push(
+                    "[grpc] is enabled but the server was built without the grpc feature"
+                        .to_string(),
+                );
+            }
+        }
+
+        let definitions = state.tool_definitions.load();
+        let mut sorted: Vec<&ToolDefinition> = definitions.iter().collect();
+        sorted.sort_by(|a, b| a.name.cmp(&b.name));
+        let tools = sorted.iter().map(|def| ToolSummary::new(def)).collect();
+        warnings.extend(sorted.into_iter().flat_map(schema_warnings));
+
+        let credentials = state.credentials.as_ref().map(|p| p.credentials());
+        if let Some(credentials) = &credentials {
+            warnings.extend(
+                find_missing_external_keys(credentials, &definitions, &state.tenant_tools)
+                    .iter()
+                    .map(ToString::to_string),
+            );
+        }
+
+        Self {
+            transports,
+            users: credentials.map(|credentials| credentials.len()),
+            tenants: state.tenant_tools.len(),
+            tools,
+            warnings,
+        }
+    }
+
+    /// Emit the report as structured tracing events
+    pub fn log(&self) {
+        let transports: Vec<String> = self
+            .transports
+            .iter()
+            .map(|t| format!("{}={}", t.name, t.bind))
+            .collect();
+        tracing::info!(
+            transports = %transports.join(","),
+            users = ?self.users,
+            tenants = self.tenants,
+            tools = self.tools.len(),
+            warnings = self.warnings.len(),
+            "server configuration"
+        );
+        for tool in &self.tools {
+            tracing::info!(
+                name = %tool.name,
+                version = tool.version.as_deref().unwrap_or("-"),
+                hints = %tool.hints.join(","),
+                "registered tool"
+            );
+        }
+        for warning in &self.warnings {
+            tracing::warn!("{}", warning);
+        }
+    }
+}
+
+impl ToolSummary {
+    fn new(definition: &ToolDefinition) -> Self {
+        let hints = definition
+            .annotations
+            .as_ref()
+            .and_then(Value::as_object)
+            .map(|annotations| {
+                annotations
+                    .iter()
+                    .filter(|(_, value)| value.as_bool() == Some(true))
+                    .map(|(key, _)| key.clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            name: definition.name.clone(),
+            version: definition.version.clone(),
+            hints,
+        }
+    }
+}
+
+/// Structural problems in a tool's parameter and output schemas
+///
+/// Clients rely on `parameters` being an object schema whose `required`
+/// entries are declared properties; anything else is reported.
+pub fn schema_warnings(definition: &ToolDefinition) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let mut warn = |message: String| {
+        warnings.push(format!("tool '{}': {}", definition.name, message));
+    };
+
+    let parameters = &definition.parameters;
+    if parameters.get("type").and_then(Value::as_str) != Some("object") {
+        warn("parameters schema must have \"type\": \"object\"".to_string());
+    }
+    let properties = parameters.get("properties");
+    if properties.is_some_and(|p| !p.is_object()) {
+        warn("parameters \"properties\" must be an object".to_string());
+    }
+    match parameters.get("required") {
+        None => {}
+        Some(Value::Array(required)) => {
+            for name in required {
+                match name.as_str() {
+                    Some(name) if properties.and_then(|p| p.get(name)).is_some() => {}
+                    Some(name) => warn(format!("required parameter '{}' is not declared", name)),
+                    None => warn("parameters \"required\" must list names".to_string()),
+                }
+            }
+        }
+        Some(_) => warn("parameters \"required\" must be an array".to_string()),
+    }
+    if definition
+        .output_schema
+        .as_ref()
+        .is_some_and(|schema| !schema.is_object())
+    {
+        warn("output schema must be an object".to_string());
+    }
+
+    warnings
+}
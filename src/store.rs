@@ -0,0 +1,536 @@
+use crate::config::{StoreBackend, StoreConfig};
+use crate::database::Storage;
+use anyhow::Result;
+use std::collections::HashMap;
+use std::future::Future;
+use std::ops::Deref;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+pub type StoreFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+/// Key-value state shared between server replicas
+///
+/// Backs rate limits, idempotency keys, sessions and job state. The in-memory
+/// implementation is process-local; the Redis implementation (`redis` feature)
+/// lets replicas behind a load balancer behave consistently.
+pub trait DistributedStore: Send + Sync {
+    /// Get a value, `None` when missing or expired
+    fn get<'a>(&'a self, key: &'a str) -> StoreFuture<'a, Option<String>>;
+
+    /// Set a value, optionally expiring after `ttl`
+    fn set<'a>(&'a self, key: &'a str, value: String, ttl: Option<Duration>)
+    -> StoreFuture<'a, ()>;
+
+    /// Set a value only if the key does not exist; returns whether it was set
+    fn set_if_absent<'a>(
+        &'a self,
+        key: &'a str,
+        value: String,
+        ttl: Option<Duration>,
+    ) -> StoreFuture<'a, bool>;
+
+    /// Delete a key; returns whether it existed
+    fn delete<'a>(&'a self, key: &'a str) -> StoreFuture<'a, bool>;
+
+    /// Atomically add `amount` (possibly negative) to a counter, starting a
+    /// `ttl` window on first increment; returns the new value
+    fn increment_by<'a>(&'a self, key: &'a str, amount: i64, ttl: Duration)
+    -> StoreFuture<'a, i64>;
+
+    /// Atomically increment a counter, starting a `ttl` window on first increment
+    fn increment<'a>(&'a self, key: &'a str, ttl: Duration) -> StoreFuture<'a, i64> {
+        self.increment_by(key, 1, ttl)
+    }
+
+    /// Remove expired entries, returning how many were removed
+    /// Backends that expire keys on their own (Redis) keep the default no-op
+    fn purge_expired(&self) -> StoreFuture<'_, u64> {
+        Box::pin(async { Ok(0) })
+    }
+}
+
+/// Handle to the configured store, shared across the application
+#[derive(Clone)]
+pub struct SharedStore(Arc<dyn DistributedStore>);
+
+impl SharedStore {
+    pub fn new(store: Arc<dyn DistributedStore>) -> Self {
+        Self(store)
+    }
+
+    /// Build the store selected in the server configuration; the SQLite
+    /// backend uses `storage` unless it has a path of its own
+    #[cfg_attr(not(feature = "sqlite"), allow(unused_variables))]
+    pub fn from_config(config: &StoreConfig, storage: &Storage) -> Result<Self> {
+        match config.backend {
+            StoreBackend::Memory => Ok(Self::default()),
+            #[cfg(feature = "redis")]
+            StoreBackend::Redis => {
+                let url = config
+                    .url
+                    .as_deref()
+                    .ok_or_else(|| anyhow::anyhow!("Redis store requires `store.url`"))?;
+                Ok(Self::new(Arc::new(RedisStore::new(
+                    url,
+                    config.key_prefix.clone(),
+                )?)))
+            }
+            #[cfg(not(feature = "redis"))]
+            StoreBackend::Redis => {
+                anyhow::bail!("Redis store backend requires building with the `redis` feature")
+            }
+            #[cfg(feature = "sqlite")]
+            StoreBackend::Sqlite => match (&config.path, storage.connection()) {
+                (Some(path), _) => Ok(Self::new(Arc::new(SqliteStore::open(path)?))),
+                (None, Some(connection)) => Ok(Self::new(Arc::new(SqliteStore::new(connection)))),
+                (None, None) => anyhow::bail!(
+                    "SQLite store requires `store.path` or the shared `[database] path`"
+                ),
+            },
+            #[cfg(not(feature = "sqlite"))]
+            StoreBackend::Sqlite => {
+                anyhow::bail!("SQLite store backend requires building with the `sqlite` feature")
+            }
+        }
+    }
+
+    /// Periodically remove expired entries in the background
+    pub fn spawn_cleanup(&self, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let store = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                match store.purge_expired().await {
+                    Ok(0) => {}
+                    Ok(removed) => tracing::debug!("Purged {} expired store entries", removed),
+                    Err(e) => tracing::warn!("Failed to purge expired store entries: {:#}", e),
+                }
+            }
+        })
+    }
+}
+
+impl Default for SharedStore {
+    fn default() -> Self {
+        Self(Arc::new(MemoryStore::new()))
+    }
+}
+
+impl Deref for SharedStore {
+    type Target = dyn DistributedStore;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref()
+    }
+}
+
+/// Entry value plus optional expiry deadline
+type MemoryEntry = (String, Option<Instant>);
+
+/// Process-local store; expired entries are dropped lazily on access
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    entries: Mutex<HashMap<String, MemoryEntry>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remove all expired entries, returning how many were removed
+    pub fn purge_expired(&self) -> u64 {
+        let now = Instant::now();
+        let mut entries = self.lock();
+        let before = entries.len();
+        entries.retain(|_, (_, expires)| expires.is_none_or(|t| t > now));
+        (before - entries.len()) as u64
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, MemoryEntry>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Live entry for a key, removing it if expired
+    fn live<'m>(
+        entries: &'m mut HashMap<String, MemoryEntry>,
+        key: &str,
+    ) -> Option<&'m mut MemoryEntry> {
+        if entries
+            .get(key)
+            .is_some_and(|(_, expires)| expires.is_some_and(|t| t <= Instant::now()))
+        {
+            entries.remove(key);
+        }
+        entries.get_mut(key)
+    }
+}
+
+fn deadline(ttl: Option<Duration>) -> Option<Instant> {
+    ttl.map(|ttl| Instant::now() + ttl)
+}
+
+impl DistributedStore for MemoryStore {
+    fn get<'a>(&'a self, key: &'a str) -> StoreFuture<'a, Option<String>> {
+        let mut entries = self.lock();
+        let value = Self::live(&mut entries, key).map(|(value, _)| value.clone());
+        Box::pin(async move { Ok(value) })
+    }
+
+    fn set<'a>(
+        &'a self,
+        key: &'a str,
+        value: String,
+        ttl: Option<Duration>,
+    ) -> StoreFuture<'a, ()> {
+        self.lock().insert(key.to_string(), (value, deadline(ttl)));
+        Box::pin(async { Ok(()) })
+    }
+
+    fn set_if_absent<'a>(
+        &'a self,
+        key: &'a str,
+        value: String,
+        ttl: Option<Duration>,
+    ) -> StoreFuture<'a, bool> {
+        let mut entries = self.lock();
+        let inserted = Self::live(&mut entries, key).is_none();
+        if inserted {
+            entries.insert(key.to_string(), (value, deadline(ttl)));
+        }
+        Box::pin(async move { Ok(inserted) })
+    }
+
+    fn delete<'a>(&'a self, key: &'a str) -> StoreFuture<'a, bool> {
+        let mut entries = self.lock();
+        let existed = Self::live(&mut entries, key).is_some();
+        entries.remove(key);
+        Box::pin(async move { Ok(existed) })
+    }
+
+    fn increment_by<'a>(
+        &'a self,
+        key: &'a str,
+        amount: i64,
+        ttl: Duration,
+    ) -> StoreFuture<'a, i64> {
+        let mut entries = self.lock();
+        let result = match Self::live(&mut entries, key) {
+            Some((value, _)) => value
+                .parse::<i64>()
+                .map(|count| {
+                    *value = (count + amount).to_string();
+                    count + amount
+                })
+                .map_err(|_| anyhow::anyhow!("Value at '{}' is not an integer", key)),
+            None => {
+                entries.insert(key.to_string(), (amount.to_string(), deadline(Some(ttl))));
+                Ok(amount)
+            }
+        };
+        Box::pin(async move { result })
+    }
+
+    fn purge_expired(&self) -> StoreFuture<'_, u64> {
+        let removed = MemoryStore::purge_expired(self);
+        Box::pin(async move { Ok(removed) })
+    }
+}
+
+/// Redis-backed store shared by all replicas pointing at the same server
+#[cfg(feature = "redis")]
+pub struct RedisStore {
+    client: redis::Client,
+    connection: tokio::sync::OnceCell<redis::aio::ConnectionManager>,
+    key_prefix: String,
+}
+
+#[cfg(feature = "redis")]
+impl RedisStore {
+    /// Create a store for a `redis://` URL; the connection is opened on first use
+    pub fn new(url: &str, key_prefix: String) -> Result<Self> {
+        use anyhow::Context;
+
+        let client = redis::Client::open(url).context("Invalid Redis URL")?;
+        Ok(Self {
+            client,
+            connection: tokio::sync::OnceCell::new(),
+            key_prefix,
+        })
+    }
+
+    async fn connection(&self) -> Result<redis::aio::ConnectionManager> {
+        let connection = self
+            .connection
+            .get_or_try_init(|| redis::aio::ConnectionManager::new(self.client.clone()))
+            .await?;
+        Ok(connection.clone())
+    }
+
+    fn key(&self, key: &str) -> String {
+        format!("{}{}", self.key_prefix, key)
+    }
+}
+
+#[cfg(feature = "redis")]
+fn set_command(key: String, value: String, ttl: Option<Duration>) -> redis::Cmd {
+    let mut cmd = redis::cmd("SET");
+    cmd.arg(key).arg(value);
+    if let Some(ttl) = ttl {
+        cmd.arg("PX").arg(ttl.as_millis() as u64);
+    }
+    cmd
+}
+
+#[cfg(feature = "redis")]
+impl DistributedStore for RedisStore {
+    fn get<'a>(&'a self, key: &'a str) -> StoreFuture<'a, Option<String>> {
+        Box::pin(async move {
+            let mut conn = self.connection().await?;
+            Ok(redis::cmd("GET")
+                .arg(self.key(key))
+                .query_async(&mut conn)
+                .await?)
+        })
+    }
+
+    fn set<'a>(
+        &'a self,
+        key: &'a str,
+        value: String,
+        ttl: Option<Duration>,
+    ) -> StoreFuture<'a, ()> {
+        Box::pin(async move {
+            let mut conn = self.connection().await?;
+            set_command(self.key(key), value, ttl)
+                .query_async::<()>(&mut conn)
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn set_if_absent<'a>(
+        &'a self,
+        key: &'a str,
+        value: String,
+        ttl: Option<Duration>,
+    ) -> StoreFuture<'a, bool> {
+        Box::pin(async move {
+            let mut conn = self.connection().await?;
+            let reply: Option<String> = set_command(self.key(key), value, ttl)
+                .arg("NX")
+                .query_async(&mut conn)
+                .await?;
+            Ok(reply.is_some())
+        })
+    }
+
+    fn delete<'a>(&'a self, key: &'a str) -> StoreFuture<'a, bool> {
+        Box::pin(async move {
+            let mut conn = self.connection().await?;
+            let removed: i64 = redis::cmd("DEL")
+                .arg(self.key(key))
+                .query_async(&mut conn)
+                .await?;
+            Ok(removed > 0)
+        })
+    }
+
+    fn increment_by<'a>(
+        &'a self,
+        key: &'a str,
+        amount: i64,
+        ttl: Duration,
+    ) -> StoreFuture<'a, i64> {
+        Box::pin(async move {
+            let mut conn = self.connection().await?;
+            let key = self.key(key);
+            // Create the counter with its window first; INCRBY keeps the expiry
+            let (count,): (i64,) = redis::pipe()
+                .atomic()
+                .cmd("SET")
+                .arg(&key)
+                .arg(0)
+                .arg("PX")
+                .arg(ttl.as_millis() as u64)
+                .arg("NX")
+                .ignore()
+                .cmd("INCRBY")
+                .arg(&key)
+                .arg(amount)
+                .query_async(&mut conn)
+                .await?;
+            Ok(count)
+        })
+    }
+}
+
+/// SQLite-backed store persisting state across restarts of a single replica
+#[cfg(feature = "sqlite")]
+pub struct SqliteStore {
+    connection: Arc<Mutex<rusqlite::Connection>>,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteStore {
+    /// Open a database of its own at `path`, creating and migrating it if needed
+    pub fn open(path: &str) -> Result<Self> {
+        let storage = Storage::open_path(path)?;
+        Ok(Self::new(
+            storage
+                .connection()
+                .expect("opened database has a connection"),
+        ))
+    }
+
+    /// Keep entries in an open (migrated) database, such as the shared one
+    pub fn new(connection: Arc<Mutex<rusqlite::Connection>>) -> Self {
+        Self { connection }
+    }
+
+    /// Run statements in one transaction on the blocking thread pool
+    fn transaction<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&rusqlite::Transaction) -> rusqlite::Result<T> + Send + 'static,
+    ) -> StoreFuture<'_, T> {
+        let connection = self.connection.clone();
+        Box::pin(async move {
+            let result = tokio::task::spawn_blocking(move || {
+                let mut connection = connection.lock().unwrap_or_else(|e| e.into_inner());
+                let tx = connection.transaction()?;
+                let value = f(&tx)?;
+                tx.commit()?;
+                Ok::<_, rusqlite::Error>(value)
+            })
+            .await??;
+            Ok(result)
+        })
+    }
+}
+
+/// Current time in milliseconds since the epoch, the unit of `expires_at`
+#[cfg(feature = "sqlite")]
+fn now_millis() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+#[cfg(feature = "sqlite")]
+fn expires_at(ttl: Option<Duration>) -> Option<i64> {
+    ttl.map(|ttl| now_millis() + ttl.as_millis() as i64)
+}
+
+/// Drop a key if it has expired, so it behaves as missing
+#[cfg(feature = "sqlite")]
+fn drop_if_expired(tx: &rusqlite::Transaction, key: &str) -> rusqlite::Result<()> {
+    tx.execute(
+        "DELETE FROM entries WHERE key = ?1 AND expires_at <= ?2",
+        rusqlite::params![key, now_millis()],
+    )?;
+    Ok(())
+}
+
+#[cfg(feature = "sqlite")]
+impl DistributedStore for SqliteStore {
+    fn get<'a>(&'a self, key: &'a str) -> StoreFuture<'a, Option<String>> {
+        use rusqlite::OptionalExtension;
+
+        let key = key.to_string();
+        self.transaction(move |tx| {
+            drop_if_expired(tx, &key)?;
+            tx.query_row("SELECT value FROM entries WHERE key = ?1", [&key], |row| {
+                row.get(0)
+            })
+            .optional()
+        })
+    }
+
+    fn set<'a>(
+        &'a self,
+        key: &'a str,
+        value: String,
+        ttl: Option<Duration>,
+    ) -> StoreFuture<'a, ()> {
+        let key = key.to_string();
+        self.transaction(move |tx| {
+            tx.execute(
+                "INSERT INTO entries (key, value, expires_at) VALUES (?1, ?2, ?3)
+                 ON CONFLICT(key) DO UPDATE SET value = excluded.value, expires_at = excluded.expires_at",
+                rusqlite::params![key, value, expires_at(ttl)],
+            )?;
+            Ok(())
+        })
+    }
+
+    fn set_if_absent<'a>(
+        &'a self,
+        key: &'a str,
+        value: String,
+        ttl: Option<Duration>,
+    ) -> StoreFuture<'a, bool> {
+        let key = key.to_string();
+        self.transaction(move |tx| {
+            drop_if_expired(tx, &key)?;
+            let inserted = tx.execute(
+                "INSERT OR IGNORE INTO entries (key, value, expires_at) VALUES (?1, ?2, ?3)",
+                rusqlite::params![key, value, expires_at(ttl)],
+            )?;
+            Ok(inserted > 0)
+        })
+    }
+
+    fn delete<'a>(&'a self, key: &'a str) -> StoreFuture<'a, bool> {
+        let key = key.to_string();
+        self.transaction(move |tx| {
+            drop_if_expired(tx, &key)?;
+            let removed = tx.execute("DELETE FROM entries WHERE key = ?1", [&key])?;
+            Ok(removed > 0)
+        })
+    }
+
+    fn increment_by<'a>(
+        &'a self,
+        key: &'a str,
+        amount: i64,
+        ttl: Duration,
+    ) -> StoreFuture<'a, i64> {
+        use rusqlite::OptionalExtension;
+
+        let key = key.to_string();
+        let result = self.transaction(move |tx| {
+            drop_if_expired(tx, &key)?;
+            let current: Option<String> = tx
+                .query_row("SELECT value FROM entries WHERE key = ?1", [&key], |row| {
+                    row.get(0)
+                })
+                .optional()?;
+            let Some(current) = current else {
+                tx.execute(
+                    "INSERT INTO entries (key, value, expires_at) VALUES (?1, ?2, ?3)",
+                    rusqlite::params![key, amount.to_string(), expires_at(Some(ttl))],
+                )?;
+                return Ok(Ok(amount));
+            };
+            let Ok(count) = current.parse::<i64>() else {
+                return Ok(Err(anyhow::anyhow!("Value at '{}' is not an integer", key)));
+            };
+            tx.execute(
+                "UPDATE entries SET value = ?2 WHERE key = ?1",
+                rusqlite::params![key, (count + amount).to_string()],
+            )?;
+            Ok(Ok(count + amount))
+        });
+        Box::pin(async move { result.await? })
+    }
+
+    fn purge_expired(&self) -> StoreFuture<'_, u64> {
+        self.transaction(|tx| {
+            let removed =
+                tx.execute("DELETE FROM entries WHERE expires_at <= ?1", [now_millis()])?;
+            Ok(removed as u64)
+        })
+    }
+}
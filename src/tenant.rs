@@ -0,0 +1,85 @@
+use crate::ToolDefinition;
+use crate::auth::CredentialsStore;
+use crate::config::{DuplicateToolNames, ServerConfig, TenantConfig};
+use crate::tools::{ToolFunction, initialize_tools};
+use anyhow::{Result, bail};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Tools visible to a group of users: executable functions plus their definitions
+#[derive(Clone, Default)]
+pub struct ToolSet {
+    pub functions: Arc<HashMap<String, ToolFunction>>,
+    pub definitions: Arc<Vec<ToolDefinition>>,
+}
+
+impl ToolSet {
+    /// Build a tool set from every registered tool, restricted to an optional allowlist
+    pub fn from_registry(
+        allowed: Option<&[String]>,
+        duplicates: DuplicateToolNames,
+    ) -> Result<Self> {
+        let (mut functions, mut definitions) = initialize_tools(duplicates)?;
+
+        if let Some(allowed) = allowed {
+            if let Some(unknown) = allowed.iter().find(|name| !functions.contains_key(*name)) {
+                bail!("Unknown tool '{}'", unknown);
+            }
+            functions.retain(|name, _| allowed.contains(name));
+            definitions.retain(|def| allowed.contains(&def.name));
+        }
+
+        Ok(Self {
+            functions: Arc::new(functions),
+            definitions: Arc::new(definitions),
+        })
+    }
+}
+
+/// Build one tool set per configured tenant
+pub fn build_tenant_tool_sets(config: &ServerConfig) -> Result<HashMap<String, ToolSet>> {
+    config
+        .tenants
+        .iter()
+        .map(|(name, tenant)| {
+            let tools =
+                ToolSet::from_registry(tenant.tools.as_deref(), config.tools.duplicate_names)
+                    .map_err(|e| {
+                        anyhow::anyhow!("Invalid tool list for tenant '{}': {}", name, e)
+                    })?;
+            Ok((name.clone(), tools))
+        })
+        .collect()
+}
+
+/// Check that every user's tenant exists and merge the tenant's shared external keys
+/// into the user's credentials (user-level keys take precedence)
+pub fn apply_tenant_config(
+    credentials: CredentialsStore,
+    tenants: &HashMap<String, TenantConfig>,
+) -> Result<CredentialsStore> {
+    if credentials.values().all(|user| user.tenant.is_none()) {
+        return Ok(credentials);
+    }
+
+    let mut store = (*credentials).clone();
+    for user in store.values_mut() {
+        let Some(tenant_name) = &user.tenant else {
+            continue;
+        };
+        let Some(tenant) = tenants.get(tenant_name) else {
+            bail!(
+                "User '{}' belongs to unknown tenant '{}'",
+                user.username,
+                tenant_name
+            );
+        };
+        for (key, value) in &tenant.external_keys {
+            user.external_keys
+                .entry(key.clone())
+                .or_insert_with(|| value.clone());
+        }
+    }
+
+    Ok(Arc::new(store))
+}
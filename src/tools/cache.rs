@@ -0,0 +1,181 @@
+use super::{InvalidInput, McpTool, PinBoxedFuture, ToolContext, mcp_tool, validate_tool_args};
+use crate::auth::{AuthenticatedUser, ExposeSecret};
+use crate::config::CacheConfig;
+use crate::pools::PoolManager;
+use anyhow::{Context, Error, Result};
+use redis::AsyncCommands;
+use redis::aio::ConnectionManager;
+use serde_json::{Value, json};
+use std::sync::LazyLock;
+use std::time::Duration;
+
+/// Redis connections shared by all users pointing at the same server
+static REDIS_POOLS: LazyLock<PoolManager<ConnectionManager>> =
+    LazyLock::new(|| PoolManager::new(64, Duration::from_secs(600)));
+
+/// Connection to the Redis server in the user's `redis_url` external key
+async fn connection(user: &AuthenticatedUser) -> Result<ConnectionManager> {
+    let url = user.external_secret("redis_url")?;
+    REDIS_POOLS
+        .get_or_connect(url.expose_secret(), || async {
+            // The URL may hold a password: keep it out of error messages
+            let client = redis::Client::open(url.expose_secret())
+                .map_err(|_| anyhow::anyhow!("Invalid credential 'redis_url'"))?;
+            ConnectionManager::new(client)
+                .await
+                .context("Failed to connect to Redis")
+        })
+        .await
+}
+
+/// Redis key of a user's cache entry; users cannot reach each other's keys
+pub fn namespaced_key(config: &CacheConfig, username: &str, key: &str) -> String {
+    format!("{}:{}:{}", config.key_prefix, username, key)
+}
+
+/// TTL of a `cache_set`, refusing values beyond `[cache] max_ttl_secs`
+pub fn ttl_secs(config: &CacheConfig, requested: Option<u64>) -> Result<u64, InvalidInput> {
+    let ttl = requested.unwrap_or(config.default_ttl_secs.min(config.max_ttl_secs));
+    if ttl == 0 || ttl > config.max_ttl_secs {
+        return Err(InvalidInput::new(format!(
+            "ttl_secs must be between 1 and {}",
+            config.max_ttl_secs
+        )));
+    }
+    Ok(ttl)
+}
+
+fn key_schema() -> Value {
+    json!({ "type": "string", "minLength": 1, "maxLength": 512 })
+}
+
+/// Reads a value from the user's Redis cache
+#[mcp_tool]
+pub struct CacheGetTool;
+
+impl McpTool for CacheGetTool {
+    fn name(&self) -> &'static str {
+        "cache_get"
+    }
+
+    fn description(&self) -> &'static str {
+        "Reads a value from the user's cache. Returns null when the key is missing or expired."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": { "key": key_schema() },
+            "required": ["key"],
+            "additionalProperties": false
+        })
+    }
+
+    fn annotations(&self) -> Option<Value> {
+        Some(json!({ "readOnlyHint": true }))
+    }
+
+    fn execute(
+        &self,
+        args: Option<Value>,
+        user: AuthenticatedUser,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        self.execute_with_context(args, ToolContext::new(user))
+    }
+
+    fn execute_with_context(
+        &self,
+        args: Option<Value>,
+        ctx: ToolContext,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        let schema = self.parameters_schema();
+
+        Box::pin(async move {
+            validate_tool_args(&schema, &args)?;
+            let args = args.unwrap_or_default();
+            let key = args["key"].as_str().unwrap_or_default();
+            let redis_key =
+                namespaced_key(&ctx.config().cache, &ctx.user().credentials().username, key);
+
+            let mut redis = connection(ctx.user()).await?;
+            let value: Option<String> = redis.get(&redis_key).await?;
+            // -2 (missing) and -1 (no expiry) both report no TTL
+            let ttl: i64 = redis.ttl(&redis_key).await?;
+
+            Ok(json!({
+                "key": key,
+                "value": value,
+                "ttl_secs": (value.is_some() && ttl >= 0).then_some(ttl),
+            }))
+        })
+    }
+}
+
+/// Stores a value in the user's Redis cache with a bounded TTL
+#[mcp_tool]
+pub struct CacheSetTool;
+
+impl McpTool for CacheSetTool {
+    fn name(&self) -> &'static str {
+        "cache_set"
+    }
+
+    fn description(&self) -> &'static str {
+        "Stores a string in the user's cache for ttl_secs (default and maximum set by the server)."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "key": key_schema(),
+                "value": { "type": "string" },
+                "ttl_secs": { "type": "integer", "minimum": 1 }
+            },
+            "required": ["key", "value"],
+            "additionalProperties": false
+        })
+    }
+
+    fn annotations(&self) -> Option<Value> {
+        Some(json!({ "destructiveHint": true, "idempotentHint": true }))
+    }
+
+    fn execute(
+        &self,
+        args: Option<Value>,
+        user: AuthenticatedUser,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        self.execute_with_context(args, ToolContext::new(user))
+    }
+
+    fn execute_with_context(
+        &self,
+        args: Option<Value>,
+        ctx: ToolContext,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        let schema = self.parameters_schema();
+
+        Box::pin(async move {
+            validate_tool_args(&schema, &args)?;
+            let args = args.unwrap_or_default();
+            let config = &ctx.config().cache;
+            let key = args["key"].as_str().unwrap_or_default();
+            let value = args["value"].as_str().unwrap_or_default();
+            let ttl = ttl_secs(config, args["ttl_secs"].as_u64())?;
+            if value.len() > config.max_value_bytes {
+                return Err(InvalidInput::new(format!(
+                    "value exceeds maximum size of {} bytes",
+                    config.max_value_bytes
+                ))
+                .into());
+            }
+            let redis_key = namespaced_key(config, &ctx.user().credentials().username, key);
+
+            let mut redis = connection(ctx.user()).await?;
+            let () = redis.set_ex(&redis_key, value, ttl).await?;
+
+            Ok(json!({ "key": key, "ttl_secs": ttl }))
+        })
+    }
+}
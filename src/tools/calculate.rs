@@ -0,0 +1,369 @@
+use super::{InvalidInput, McpTool, PinBoxedFuture, mcp_tool, validate_tool_args};
+use crate::auth::AuthenticatedUser;
+use anyhow::{Error, Result};
+use serde_json::{Map, Value, json};
+
+/// Deepest nesting of parentheses, unary operators and calls
+const MAX_DEPTH: usize = 64;
+
+/// Evaluates arithmetic expressions with a small parser; nothing is executed
+#[mcp_tool]
+pub struct CalculateTool;
+
+impl McpTool for CalculateTool {
+    fn name(&self) -> &'static str {
+        "calculate"
+    }
+
+    fn description(&self) -> &'static str {
+        "Evaluates an arithmetic expression with + - * / % ^, parentheses, the constants pi and e, \
+         the functions sqrt abs ln log10 exp sin cos tan floor ceil round min max, \
+         and numeric variables passed in `variables`."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "expression": {
+                    "type": "string",
+                    "minLength": 1,
+                    "maxLength": 1000,
+                    "description": "Expression, e.g. \"price * (1 + vat) ^ 2\""
+                },
+                "variables": {
+                    "type": "object",
+                    "description": "Numeric values for names used in the expression"
+                }
+            },
+            "required": ["expression"],
+            "additionalProperties": false
+        })
+    }
+
+    fn output_schema(&self) -> Option<Value> {
+        Some(json!({
+            "type": "object",
+            "properties": { "result": { "type": "number" } },
+            "required": ["result"]
+        }))
+    }
+
+    fn annotations(&self) -> Option<Value> {
+        Some(json!({ "readOnlyHint": true }))
+    }
+
+    fn execute(
+        &self,
+        args: Option<Value>,
+        _user: AuthenticatedUser,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        let schema = self.parameters_schema();
+
+        Box::pin(async move {
+            validate_tool_args(&schema, &args)?;
+            let args = args.unwrap_or_default();
+            let expression = args["expression"].as_str().unwrap_or_default();
+            let empty = Map::new();
+            let variables = args["variables"].as_object().unwrap_or(&empty);
+
+            let result = evaluate(expression, variables)?;
+            Ok(json!({ "result": result }))
+        })
+    }
+}
+
+/// Evaluate an expression; errors are `InvalidInput` carrying the failing position
+pub fn evaluate(expression: &str, variables: &Map<String, Value>) -> Result<f64, InvalidInput> {
+    let mut parser = Parser {
+        tokens: tokenize(expression)?,
+        pos: 0,
+        depth: 0,
+        variables,
+    };
+    let value = parser.expression()?;
+    if let Some(token) = parser.tokens.get(parser.pos) {
+        return Err(error(format!("unexpected '{}'", token.kind), token.offset));
+    }
+    if !value.is_finite() {
+        return Err(InvalidInput::new(
+            "expression has no finite result (e.g. division by zero)",
+        ));
+    }
+    Ok(value)
+}
+
+fn error(message: String, offset: usize) -> InvalidInput {
+    InvalidInput::new(format!("{} at position {}", message, offset))
+        .with_data(json!({ "position": offset }))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Number(f64),
+    Ident(String),
+    Op(char),
+}
+
+impl std::fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Number(n) => write!(f, "{}", n),
+            Self::Ident(name) => f.write_str(name),
+            Self::Op(op) => write!(f, "{}", op),
+        }
+    }
+}
+
+struct Token {
+    kind: TokenKind,
+    /// Character offset in the expression, reported in errors
+    offset: usize,
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, InvalidInput> {
+    let chars: Vec<char> = expression.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        let kind = if c.is_ascii_digit() || c == '.' {
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            // Exponent, e.g. 1.5e-3
+            if i < chars.len() && matches!(chars[i], 'e' | 'E') {
+                let mut j = i + 1;
+                if j < chars.len() && matches!(chars[j], '+' | '-') {
+                    j += 1;
+                }
+                if j < chars.len() && chars[j].is_ascii_digit() {
+                    i = j;
+                    while i < chars.len() && chars[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+            }
+            let text: String = chars[start..i].iter().collect();
+            let number = text
+                .parse()
+                .map_err(|_| error(format!("invalid number '{}'", text), start))?;
+            TokenKind::Number(number)
+        } else if c.is_alphabetic() || c == '_' {
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            TokenKind::Ident(chars[start..i].iter().collect())
+        } else if "+-*/%^(),".contains(c) {
+            i += 1;
+            TokenKind::Op(c)
+        } else {
+            return Err(error(format!("unexpected character '{}'", c), start));
+        };
+        tokens.push(Token {
+            kind,
+            offset: start,
+        });
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent over:
+/// expression = term (("+" | "-") term)*
+/// term       = unary (("*" | "/" | "%") unary)*
+/// unary      = ("-" | "+") unary | power
+/// power      = primary ("^" unary)?
+/// primary    = number | name | name "(" arguments ")" | "(" expression ")"
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+    variables: &'a Map<String, Value>,
+}
+
+impl Parser<'_> {
+    fn peek_op(&self) -> Option<char> {
+        match self.tokens.get(self.pos) {
+            Some(Token {
+                kind: TokenKind::Op(op),
+                ..
+            }) => Some(*op),
+            _ => None,
+        }
+    }
+
+    /// Offset of the current token, or the end of the expression
+    fn offset(&self) -> usize {
+        self.tokens.get(self.pos).map_or_else(
+            || self.tokens.last().map_or(0, |token| token.offset + 1),
+            |token| token.offset,
+        )
+    }
+
+    fn expect(&mut self, op: char) -> Result<(), InvalidInput> {
+        if self.peek_op() == Some(op) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(error(format!("expected '{}'", op), self.offset()))
+        }
+    }
+
+    fn enter(&mut self) -> Result<(), InvalidInput> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(error(
+                "expression is nested too deeply".to_string(),
+                self.offset(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn expression(&mut self) -> Result<f64, InvalidInput> {
+        let mut value = self.term()?;
+        while let Some(op @ ('+' | '-')) = self.peek_op() {
+            self.pos += 1;
+            let rhs = self.term()?;
+            value = if op == '+' { value + rhs } else { value - rhs };
+        }
+        Ok(value)
+    }
+
+    fn term(&mut self) -> Result<f64, InvalidInput> {
+        let mut value = self.unary()?;
+        while let Some(op @ ('*' | '/' | '%')) = self.peek_op() {
+            let offset = self.offset();
+            self.pos += 1;
+            let rhs = self.unary()?;
+            if op != '*' && rhs == 0.0 {
+                return Err(error("division by zero".to_string(), offset));
+            }
+            value = match op {
+                '*' => value * rhs,
+                '/' => value / rhs,
+                _ => value % rhs,
+            };
+        }
+        Ok(value)
+    }
+
+    fn unary(&mut self) -> Result<f64, InvalidInput> {
+        match self.peek_op() {
+            Some(op @ ('-' | '+')) => {
+                self.pos += 1;
+                self.enter()?;
+                let value = self.unary()?;
+                self.depth -= 1;
+                Ok(if op == '-' { -value } else { value })
+            }
+            _ => self.power(),
+        }
+    }
+
+    fn power(&mut self) -> Result<f64, InvalidInput> {
+        let base = self.primary()?;
+        if self.peek_op() == Some('^') {
+            self.pos += 1;
+            self.enter()?;
+            // Right-associative: 2 ^ 3 ^ 2 = 2 ^ 9
+            let exponent = self.unary()?;
+            self.depth -= 1;
+            return Ok(base.powf(exponent));
+        }
+        Ok(base)
+    }
+
+    fn primary(&mut self) -> Result<f64, InvalidInput> {
+        let offset = self.offset();
+        let Some(token) = self.tokens.get(self.pos) else {
+            return Err(error("unexpected end of expression".to_string(), offset));
+        };
+        match token.kind.clone() {
+            TokenKind::Number(n) => {
+                self.pos += 1;
+                Ok(n)
+            }
+            TokenKind::Op('(') => {
+                self.pos += 1;
+                self.enter()?;
+                let value = self.expression()?;
+                self.expect(')')?;
+                self.depth -= 1;
+                Ok(value)
+            }
+            TokenKind::Ident(name) => {
+                self.pos += 1;
+                if self.peek_op() == Some('(') {
+                    self.pos += 1;
+                    self.enter()?;
+                    let args = self.arguments()?;
+                    self.depth -= 1;
+                    return call(&name, &args).map_err(|message| error(message, offset));
+                }
+                self.variable(&name)
+                    .map_err(|message| error(message, offset))
+            }
+            TokenKind::Op(op) => Err(error(format!("unexpected '{}'", op), offset)),
+        }
+    }
+
+    fn arguments(&mut self) -> Result<Vec<f64>, InvalidInput> {
+        let mut args = Vec::new();
+        if self.peek_op() == Some(')') {
+            self.pos += 1;
+            return Ok(args);
+        }
+        loop {
+            args.push(self.expression()?);
+            if self.peek_op() == Some(',') {
+                self.pos += 1;
+            } else {
+                self.expect(')')?;
+                return Ok(args);
+            }
+        }
+    }
+
+    fn variable(&self, name: &str) -> Result<f64, String> {
+        if let Some(value) = self.variables.get(name) {
+            return value
+                .as_f64()
+                .ok_or_else(|| format!("variable '{}' is not a number", name));
+        }
+        match name {
+            "pi" => Ok(std::f64::consts::PI),
+            "e" => Ok(std::f64::consts::E),
+            _ => Err(format!("unknown variable '{}'", name)),
+        }
+    }
+}
+
+fn call(name: &str, args: &[f64]) -> Result<f64, String> {
+    let unary = |f: fn(f64) -> f64| match args {
+        [x] => Ok(f(*x)),
+        _ => Err(format!("{}() takes 1 argument, got {}", name, args.len())),
+    };
+    match name {
+        "sqrt" => unary(f64::sqrt),
+        "abs" => unary(f64::abs),
+        "ln" => unary(f64::ln),
+        "log10" => unary(f64::log10),
+        "exp" => unary(f64::exp),
+        "sin" => unary(f64::sin),
+        "cos" => unary(f64::cos),
+        "tan" => unary(f64::tan),
+        "floor" => unary(f64::floor),
+        "ceil" => unary(f64::ceil),
+        "round" => unary(f64::round),
+        "min" | "max" if args.is_empty() => Err(format!("{}() needs at least 1 argument", name)),
+        "min" => Ok(args.iter().copied().fold(f64::INFINITY, f64::min)),
+        "max" => Ok(args.iter().copied().fold(f64::NEG_INFINITY, f64::max)),
+        _ => Err(format!("unknown function '{}'", name)),
+    }
+}
@@ -0,0 +1,129 @@
+use anyhow::{Result, bail};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use base64::write::EncoderStringWriter;
+use serde_json::{Value, json};
+use std::io::Write;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// Read size for streamed encoding, a multiple of 3 so chunks encode without padding
+const CHUNK_SIZE: usize = 48 * 1024;
+
+/// URI of embedded resources created without an explicit one
+const DEFAULT_RESOURCE_URI: &str = "attachment://binary";
+
+/// Base64-encoded binary data (images, PDFs, ...) returned by a tool
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BinaryContent {
+    pub mime_type: String,
+    /// Standard base64 encoding of the data
+    pub data: String,
+    /// Size of the decoded data in bytes
+    pub size: usize,
+    /// URI of the embedded resource for non-image, non-audio data
+    pub uri: Option<String>,
+}
+
+impl BinaryContent {
+    /// Encode bytes, failing when they exceed `max_bytes` (0 = unlimited)
+    pub fn from_bytes(bytes: &[u8], mime_type: &str, max_bytes: usize) -> Result<Self> {
+        check_limit(bytes.len(), max_bytes)?;
+        Ok(Self {
+            mime_type: mime_type.to_string(),
+            data: STANDARD.encode(bytes),
+            size: bytes.len(),
+            uri: None,
+        })
+    }
+
+    /// Encode a reader chunk by chunk, failing as soon as `max_bytes` is exceeded
+    /// instead of buffering the raw data first
+    pub async fn from_reader(
+        mut reader: impl AsyncRead + Unpin,
+        mime_type: &str,
+        max_bytes: usize,
+    ) -> Result<Self> {
+        let mut encoder = EncoderStringWriter::new(&STANDARD);
+        let mut buffer = vec![0; CHUNK_SIZE];
+        let mut size = 0;
+
+        loop {
+            let read = reader.read(&mut buffer).await?;
+            if read == 0 {
+                break;
+            }
+            size += read;
+            check_limit(size, max_bytes)?;
+            encoder.write_all(&buffer[..read])?;
+        }
+
+        Ok(Self {
+            mime_type: mime_type.to_string(),
+            data: encoder.into_inner(),
+            size,
+            uri: None,
+        })
+    }
+
+    /// Set the URI used when the data is embedded as a resource
+    pub fn with_uri(mut self, uri: impl Into<String>) -> Self {
+        self.uri = Some(uri.into());
+        self
+    }
+
+    /// MCP content block: `image` or `audio` for those MIME types, an embedded
+    /// `resource` with a base64 `blob` otherwise
+    pub fn into_content(self) -> Value {
+        let kind = self.mime_type.split('/').next().unwrap_or_default();
+        match kind {
+            "image" | "audio" => json!({
+                "type": kind,
+                "data": self.data,
+                "mimeType": self.mime_type,
+            }),
+            _ => json!({
+                "type": "resource",
+                "resource": {
+                    "uri": self.uri.as_deref().unwrap_or(DEFAULT_RESOURCE_URI),
+                    "mimeType": self.mime_type,
+                    "blob": self.data,
+                }
+            }),
+        }
+    }
+}
+
+/// Check every base64 block in a result's `content` array against `max_bytes`
+/// (0 = unlimited), so tools building content blocks by hand obey the limit too
+pub fn check_binary_size(result: &Value, max_bytes: usize) -> Result<()> {
+    if max_bytes == 0 {
+        return Ok(());
+    }
+
+    let Some(blocks) = result.get("content").and_then(Value::as_array) else {
+        return Ok(());
+    };
+    for block in blocks {
+        let encoded = block
+            .get("data")
+            .or_else(|| block.get("resource").and_then(|r| r.get("blob")))
+            .and_then(Value::as_str);
+        if let Some(encoded) = encoded {
+            check_limit(decoded_len(encoded), max_bytes)?;
+        }
+    }
+    Ok(())
+}
+
+/// Decoded size of a padded base64 string
+fn decoded_len(encoded: &str) -> usize {
+    let padding = encoded.bytes().rev().take_while(|&b| b == b'=').count();
+    (encoded.len() * 3 / 4).saturating_sub(padding)
+}
+
+fn check_limit(size: usize, max_bytes: usize) -> Result<()> {
+    if max_bytes > 0 && size > max_bytes {
+        bail!("Binary content is larger than the {} byte limit", max_bytes);
+    }
+    Ok(())
+}
@@ -0,0 +1,361 @@
+use crate::auth::AuthenticatedUser;
+use crate::config::ServerConfig;
+use crate::elicitation::{ElicitationResponse, PendingElicitations};
+use crate::http_client::HttpClient;
+use crate::logging::{LogLevel, log_notification};
+use crate::session::ClientInfo;
+use crate::store::SharedStore;
+use crate::tools::content::BinaryContent;
+use crate::uploads::{Upload, UploadStore};
+use anyhow::{Result, anyhow, bail};
+use serde_json::{Map, Value, json};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::AsyncRead;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+/// Channel for server-to-client messages during a streamed invocation
+pub type ClientChannel = mpsc::UnboundedSender<Value>;
+
+/// Method of the notifications carrying partial results
+pub const PARTIAL_RESULT_METHOD: &str = "notifications/partial_result";
+
+/// Server-to-client messaging available while the invocation is streamed
+#[derive(Clone)]
+struct ClientConnection {
+    channel: ClientChannel,
+    elicitations: Arc<PendingElicitations>,
+    elicitation_timeout: Duration,
+    /// Partial results sent so far
+    partials: Arc<AtomicU64>,
+}
+
+/// Per-invocation context handed to tools
+///
+/// Carries the authenticated user, the calling client, the request's `_meta`
+/// object and, when the client opened a stream (`Accept: text/event-stream`), the ability to send
+/// requests back to it.
+#[derive(Clone)]
+pub struct ToolContext {
+    user: AuthenticatedUser,
+    client: Option<ClientInfo>,
+    connection: Option<ClientConnection>,
+    meta: Map<String, Value>,
+    /// Maximum decoded size of binary content blocks (0 = unlimited)
+    binary_limit: usize,
+    uploads: Option<UploadStore>,
+    /// `_meta` entries the tool attaches to its result, shared across clones
+    result_meta: Arc<Mutex<Map<String, Value>>>,
+    request_id: String,
+    config: Arc<ServerConfig>,
+    store: SharedStore,
+    http: HttpClient,
+    cancellation: CancellationToken,
+    /// Name the tool was invoked by, the `logger` of its log notifications
+    tool_name: String,
+    /// Least severe log level sent to the client
+    log_level: LogLevel,
+}
+
+impl ToolContext {
+    /// Context for a plain request/response invocation
+    pub fn new(user: AuthenticatedUser) -> Self {
+        Self {
+            user,
+            client: None,
+            connection: None,
+            meta: Map::new(),
+            binary_limit: 0,
+            uploads: None,
+            result_meta: Arc::default(),
+            request_id: format!("{:032x}", rand::random::<u128>()),
+            config: Arc::default(),
+            store: SharedStore::default(),
+            http: HttpClient::default(),
+            cancellation: CancellationToken::new(),
+            tool_name: String::new(),
+            log_level: LogLevel::default(),
+        }
+    }
+
+    /// Attach the calling client's identification
+    pub fn with_client(mut self, client: Option<ClientInfo>) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Attach the request's `_meta` object
+    pub fn with_meta(mut self, meta: Map<String, Value>) -> Self {
+        self.meta = meta;
+        self
+    }
+
+    /// Limit the size of binary content encoded through this context
+    pub fn with_binary_limit(mut self, max_bytes: usize) -> Self {
+        self.binary_limit = max_bytes;
+        self
+    }
+
+    /// Give the tool access to files uploaded through `POST /uploads`
+    pub fn with_uploads(mut self, uploads: UploadStore) -> Self {
+        self.uploads = Some(uploads);
+        self
+    }
+
+    /// Give the tool access to the server configuration (defaults otherwise)
+    pub fn with_config(mut self, config: Arc<ServerConfig>) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Share the server's store with the tool (a private in-memory one otherwise)
+    pub fn with_store(mut self, store: SharedStore) -> Self {
+        self.store = store;
+        self
+    }
+
+    /// Share the server's HTTP client with the tool (an unconfigured one otherwise)
+    pub fn with_http(mut self, http: HttpClient) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Cancel the invocation through `token`, e.g. when its client disconnects
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+
+    /// Record the name the tool was invoked by
+    pub fn with_tool_name(mut self, tool_name: &str) -> Self {
+        self.tool_name = tool_name.to_string();
+        self
+    }
+
+    /// Send the client only log entries at least as severe as `level`
+    pub fn with_log_level(mut self, level: LogLevel) -> Self {
+        self.log_level = level;
+        self
+    }
+
+    /// Attach a client stream over which server requests can be sent
+    pub fn with_client_channel(
+        mut self,
+        channel: ClientChannel,
+        elicitations: Arc<PendingElicitations>,
+        elicitation_timeout: Duration,
+    ) -> Self {
+        self.connection = Some(ClientConnection {
+            channel,
+            elicitations,
+            elicitation_timeout,
+            partials: Arc::default(),
+        });
+        self
+    }
+
+    /// The authenticated user the tool runs for
+    pub fn user(&self) -> &AuthenticatedUser {
+        &self.user
+    }
+
+    /// Server-generated id of this invocation, also reported in the response `meta`
+    pub fn request_id(&self) -> &str {
+        &self.request_id
+    }
+
+    /// The server configuration, for tools with settings of their own
+    pub fn config(&self) -> &ServerConfig {
+        &self.config
+    }
+
+    /// Key-value store shared between replicas, for tool state such as rate limits
+    pub fn store(&self) -> &SharedStore {
+        &self.store
+    }
+
+    /// HTTP client for outbound requests, pooling connections across invocations
+    pub fn http(&self) -> &HttpClient {
+        &self.http
+    }
+
+    /// The calling client, when it initialized a session or sent a `User-Agent`
+    pub fn client(&self) -> Option<&ClientInfo> {
+        self.client.as_ref()
+    }
+
+    /// Token cancelled when the client disconnects, for work the tool hands
+    /// off (spawned tasks, child processes) that should stop with it
+    ///
+    /// Never cancelled for tools configured to run to completion.
+    pub fn cancellation(&self) -> &CancellationToken {
+        &self.cancellation
+    }
+
+    /// Whether the invocation was cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation.is_cancelled()
+    }
+
+    /// The request's `_meta` object (empty when the client sent none)
+    pub fn meta(&self) -> &Map<String, Value> {
+        &self.meta
+    }
+
+    /// Progress token the client sent in `_meta`, if any
+    pub fn progress_token(&self) -> Option<&Value> {
+        self.meta.get("progressToken")
+    }
+
+    /// Attach an entry to the `_meta` object of the tool's result
+    pub fn attach_meta(&self, key: impl Into<String>, value: Value) {
+        self.result_meta
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key.into(), value);
+    }
+
+    /// Take the `_meta` entries attached so far
+    pub fn take_result_meta(&self) -> Map<String, Value> {
+        std::mem::take(&mut *self.result_meta.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// Encode binary data as base64 content within the configured size limit
+    pub fn binary(&self, bytes: &[u8], mime_type: &str) -> Result<BinaryContent> {
+        BinaryContent::from_bytes(bytes, mime_type, self.binary_limit)
+    }
+
+    /// Encode binary data from a reader, e.g. a file, without buffering it raw
+    pub async fn binary_from_reader(
+        &self,
+        reader: impl AsyncRead + Unpin,
+        mime_type: &str,
+    ) -> Result<BinaryContent> {
+        BinaryContent::from_reader(reader, mime_type, self.binary_limit).await
+    }
+
+    /// Fetch a file the user uploaded, by the handle passed as a tool argument
+    pub async fn upload(&self, handle: &str) -> Result<Upload> {
+        let Some(uploads) = &self.uploads else {
+            bail!("File uploads are not available");
+        };
+        uploads
+            .load(handle, &self.user.credentials().username)
+            .await?
+            .ok_or_else(|| anyhow!("Upload '{}' not found or expired", handle))
+    }
+
+    /// Log a diagnostic for the end user
+    ///
+    /// Always written to the server log. Streamed invocations also send it to
+    /// the client as a `notifications/message` notification when `level` is
+    /// at least `[logging] level`; `fields` (an object) are added to
+    /// the notification data next to `message`.
+    pub fn log(&self, level: LogLevel, message: &str, fields: Value) {
+        let mut data = Map::new();
+        data.insert("message".to_string(), json!(message));
+        match fields {
+            Value::Object(fields) => data.extend(fields),
+            Value::Null => {}
+            other => {
+                data.insert("fields".to_string(), other);
+            }
+        }
+        let data = Value::Object(data);
+
+        let username = &self.user.credentials().username;
+        macro_rules! trace {
+            ($macro:ident) => {
+                tracing::$macro!(
+                    tool = %self.tool_name,
+                    user = %username,
+                    request_id = %self.request_id,
+                    data = %data,
+                    "{}",
+                    message
+                )
+            };
+        }
+        match level {
+            LogLevel::Debug => trace!(debug),
+            LogLevel::Info | LogLevel::Notice => trace!(info),
+            LogLevel::Warning => trace!(warn),
+            _ => trace!(error),
+        }
+
+        if level >= self.log_level
+            && let Some(connection) = &self.connection
+        {
+            // A disconnected client no longer needs its logs
+            let _ = connection
+                .channel
+                .send(log_notification(level, &self.tool_name, data));
+        }
+    }
+
+    /// Send a chunk of output ahead of the final result
+    ///
+    /// Streamed invocations deliver it to the client right away as a
+    /// `partial` event with the chunk's position; otherwise it is dropped and
+    /// the client only gets the result the tool returns. Chunks are sent as
+    /// they are, without the result post-processing.
+    pub fn send_partial(&self, chunk: Value) {
+        let Some(connection) = &self.connection else {
+            return;
+        };
+        let index = connection.partials.fetch_add(1, Ordering::Relaxed);
+        // A disconnected client no longer needs its output
+        let _ = connection.channel.send(json!({
+            "jsonrpc": "2.0",
+            "method": PARTIAL_RESULT_METHOD,
+            "params": {
+                "index": index,
+                "chunk": chunk,
+            }
+        }));
+    }
+
+    /// Whether the client can receive server requests (elicitation)
+    pub fn is_streaming(&self) -> bool {
+        self.connection.is_some()
+    }
+
+    /// Ask the user for structured input and wait for the answer
+    ///
+    /// Sends an `elicitation/create` request to the client; the client answers
+    /// with the `elicitation/respond` method. Fails when the client is not
+    /// streaming or does not answer within the configured timeout.
+    pub async fn elicit(&self, schema: Value, message: &str) -> Result<ElicitationResponse> {
+        let Some(connection) = &self.connection else {
+            bail!("Client does not support elicitation: invoke with Accept: text/event-stream");
+        };
+
+        let username = &self.user.credentials().username;
+        let (id, answer) = connection.elicitations.register(username, schema.clone());
+
+        let request = json!({
+            "jsonrpc": "2.0",
+            "method": "elicitation/create",
+            "params": {
+                "elicitation_id": id,
+                "message": message,
+                "requestedSchema": schema,
+            }
+        });
+        if connection.channel.send(request).is_err() {
+            connection.elicitations.remove(&id);
+            bail!("Client disconnected before elicitation");
+        }
+
+        match tokio::time::timeout(connection.elicitation_timeout, answer).await {
+            Ok(Ok(response)) => Ok(response),
+            Ok(Err(_)) => Err(anyhow!("Elicitation '{}' was abandoned", id)),
+            Err(_) => {
+                connection.elicitations.remove(&id);
+                Err(anyhow!("Timed out waiting for elicitation response"))
+            }
+        }
+    }
+}
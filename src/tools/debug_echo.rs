@@ -0,0 +1,75 @@
+use super::{McpTool, PinBoxedFuture, ToolContext, mcp_tool};
+use crate::auth::AuthenticatedUser;
+use anyhow::{Error, Result};
+use chrono::Utc;
+use serde_json::{Value, json};
+
+/// Diagnostic tool echoing what the server saw of the invocation
+///
+/// Only compiled with the `debug-tools` feature. External key values are never
+/// returned, only their names.
+#[mcp_tool]
+pub struct DebugEchoTool;
+
+impl McpTool for DebugEchoTool {
+    fn name(&self) -> &'static str {
+        "debug_echo"
+    }
+
+    fn description(&self) -> &'static str {
+        "Returns the arguments, the authenticated user, the names of their external keys, \
+         the request id and server timestamps. For debugging client integrations."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {},
+            "additionalProperties": true
+        })
+    }
+
+    fn annotations(&self) -> Option<Value> {
+        Some(json!({ "readOnlyHint": true }))
+    }
+
+    fn execute(
+        &self,
+        args: Option<Value>,
+        user: AuthenticatedUser,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        self.execute_with_context(args, ToolContext::new(user))
+    }
+
+    fn execute_with_context(
+        &self,
+        args: Option<Value>,
+        ctx: ToolContext,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        Box::pin(async move {
+            let credentials = ctx.user().credentials();
+            let mut external_keys: Vec<&str> = credentials
+                .external_keys
+                .keys()
+                .map(String::as_str)
+                .collect();
+            external_keys.sort_unstable();
+            let now = Utc::now();
+
+            Ok(json!({
+                "arguments": args,
+                "user": {
+                    "username": credentials.username,
+                    "admin": credentials.admin,
+                    "tenant": credentials.tenant,
+                    "external_keys": external_keys,
+                },
+                "client": ctx.client(),
+                "request_id": ctx.request_id(),
+                "meta": ctx.meta(),
+                "server_time": now.to_rfc3339(),
+                "server_time_unix_ms": now.timestamp_millis(),
+            }))
+        })
+    }
+}
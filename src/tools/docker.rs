@@ -0,0 +1,506 @@
+use super::{InvalidInput, McpTool, PinBoxedFuture, ToolContext, mcp_tool, validate_tool_args};
+use crate::auth::AuthenticatedUser;
+use crate::config::{DockerConfig, DockerOperation};
+use anyhow::{Error, Result, anyhow, bail};
+use bollard::models::ContainerSummary;
+use bollard::query_parameters::{
+    InspectContainerOptions, KillContainerOptions, ListContainersOptionsBuilder,
+    LogsOptionsBuilder, RemoveContainerOptions, RestartContainerOptions, StartContainerOptions,
+    StopContainerOptions,
+};
+use bollard::{API_DEFAULT_VERSION, Docker};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use tokio_stream::StreamExt;
+
+/// Client of the `[docker] host` daemon
+pub fn docker_client(config: &DockerConfig) -> Result<Docker> {
+    let Some(host) = &config.host else {
+        bail!("docker tools are disabled: [docker] host is not set");
+    };
+    let docker = if host.starts_with("tcp://") || host.starts_with("http://") {
+        Docker::connect_with_http(host, config.timeout_secs, API_DEFAULT_VERSION)
+    } else {
+        Docker::connect_with_socket(host, config.timeout_secs, API_DEFAULT_VERSION)
+    };
+    docker.map_err(|e| anyhow!("Failed to connect to Docker: {}", e))
+}
+
+/// Map a daemon error, reporting unknown containers and refused state
+/// changes as invalid input
+fn docker_error(error: bollard::errors::Error) -> Error {
+    match error {
+        bollard::errors::Error::DockerResponseServerError {
+            status_code: 404 | 409,
+            message,
+        } => InvalidInput::new(message).into(),
+        bollard::errors::Error::DockerResponseServerError {
+            status_code,
+            message,
+        } => anyhow!("Docker returned HTTP {}: {}", status_code, message),
+        error => anyhow!("Docker request failed: {}", error),
+    }
+}
+
+/// Whether the daemon reported that the container already was in the
+/// requested state (HTTP 304)
+fn not_modified(error: &bollard::errors::Error) -> bool {
+    matches!(
+        error,
+        bollard::errors::Error::DockerResponseServerError {
+            status_code: 304,
+            ..
+        }
+    )
+}
+
+/// A container name or ID given by the caller
+fn container_name(value: &Value) -> Result<&str, InvalidInput> {
+    let name = value.as_str().unwrap_or_default();
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'));
+    if !valid {
+        return Err(InvalidInput::new(format!(
+            "invalid container name '{}'",
+            name
+        )));
+    }
+    Ok(name)
+}
+
+/// `tail_lines` of a `docker_container_logs`, bounded by `[docker] max_tail_lines`
+pub fn tail_lines(config: &DockerConfig, requested: Option<u64>) -> u32 {
+    requested
+        .map_or(config.default_tail_lines, |lines| {
+            lines.min(u64::from(u32::MAX)) as u32
+        })
+        .clamp(1, config.max_tail_lines.max(1))
+}
+
+/// Operation of a `docker_container_action`, refused unless listed in
+/// `[docker] allowed_operations`
+pub fn allowed_operation(
+    config: &DockerConfig,
+    requested: &str,
+) -> Result<DockerOperation, InvalidInput> {
+    let operation = DockerOperation::ALL
+        .into_iter()
+        .find(|operation| operation.as_str() == requested)
+        .ok_or_else(|| InvalidInput::new(format!("unknown operation '{}'", requested)))?;
+    if !config.allowed_operations.contains(&operation) {
+        return Err(InvalidInput::new(format!(
+            "operation '{}' is not allowed on this server",
+            requested
+        ))
+        .with_data(json!({
+            "allowed_operations": config
+                .allowed_operations
+                .iter()
+                .map(|operation| operation.as_str())
+                .collect::<Vec<_>>(),
+        })));
+    }
+    Ok(operation)
+}
+
+/// A container as `docker ps` shows it
+pub fn container_summary(container: &ContainerSummary) -> Value {
+    let names: Vec<&str> = container
+        .names
+        .iter()
+        .flatten()
+        .map(|name| name.trim_start_matches('/'))
+        .collect();
+    json!({
+        "id": container.id.as_deref().map(|id| &id[..id.len().min(12)]),
+        "names": names,
+        "image": container.image,
+        "command": container.command,
+        "created": container.created,
+        "state": container.state.map(|state| state.to_string()),
+        "status": container.status,
+        "ports": container.ports.iter().flatten().map(|port| json!({
+            "private_port": port.private_port,
+            "public_port": port.public_port,
+            "type": port.typ.map(|typ| typ.to_string()),
+        })).collect::<Vec<_>>(),
+        "labels": container.labels,
+    })
+}
+
+/// Replace `NAME=value` environment entries of an inspect response by their
+/// names; they routinely hold credentials
+pub fn redact_env(inspect: &mut Value) {
+    if let Some(env) = inspect
+        .pointer_mut("/Config/Env")
+        .and_then(Value::as_array_mut)
+    {
+        for entry in env {
+            if let Some(name) = entry.as_str().and_then(|e| e.split('=').next()) {
+                *entry = Value::String(name.to_string());
+            }
+        }
+    }
+}
+
+fn container_schema() -> Value {
+    json!({
+        "type": "string",
+        "minLength": 1,
+        "maxLength": 128,
+        "description": "Container name or ID"
+    })
+}
+
+/// Lists containers of the Docker daemon
+#[mcp_tool]
+pub struct DockerListContainersTool;
+
+impl McpTool for DockerListContainersTool {
+    fn name(&self) -> &'static str {
+        "docker_list_containers"
+    }
+
+    fn description(&self) -> &'static str {
+        "Lists Docker containers with their image, state and ports. Only running containers \
+         unless all is true; filter by name substring or label."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "all": { "type": "boolean" },
+                "name": { "type": "string", "minLength": 1, "maxLength": 128 },
+                "label": {
+                    "type": "string",
+                    "minLength": 1,
+                    "maxLength": 256,
+                    "description": "`key` or `key=value`"
+                },
+                "limit": { "type": "integer", "minimum": 1 }
+            },
+            "additionalProperties": false
+        })
+    }
+
+    fn annotations(&self) -> Option<Value> {
+        Some(json!({ "readOnlyHint": true }))
+    }
+
+    fn execute(
+        &self,
+        args: Option<Value>,
+        user: AuthenticatedUser,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        self.execute_with_context(args, ToolContext::new(user))
+    }
+
+    fn execute_with_context(
+        &self,
+        args: Option<Value>,
+        ctx: ToolContext,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        let schema = self.parameters_schema();
+
+        Box::pin(async move {
+            validate_tool_args(&schema, &args)?;
+            let args = args.unwrap_or_default();
+            let config = &ctx.config().docker;
+            let docker = docker_client(config)?;
+            let limit = args["limit"]
+                .as_u64()
+                .map_or(config.max_containers, |limit| {
+                    limit.min(u64::from(config.max_containers)) as u32
+                });
+
+            let mut filters: HashMap<&str, Vec<&str>> = HashMap::new();
+            if let Some(name) = args["name"].as_str() {
+                filters.insert("name", vec![name]);
+            }
+            if let Some(label) = args["label"].as_str() {
+                filters.insert("label", vec![label]);
+            }
+            let options = ListContainersOptionsBuilder::new()
+                .all(args["all"].as_bool().unwrap_or(false))
+                .limit(limit.min(i32::MAX as u32) as i32)
+                .filters(&filters)
+                .build();
+            let containers = docker
+                .list_containers(Some(options))
+                .await
+                .map_err(docker_error)?;
+
+            Ok(json!({
+                "containers": containers.iter().map(container_summary).collect::<Vec<_>>(),
+            }))
+        })
+    }
+}
+
+/// Returns the low-level details of a container
+#[mcp_tool]
+pub struct DockerInspectContainerTool;
+
+impl McpTool for DockerInspectContainerTool {
+    fn name(&self) -> &'static str {
+        "docker_inspect_container"
+    }
+
+    fn description(&self) -> &'static str {
+        "Returns the configuration and state of a Docker container, as docker inspect does. \
+         Environment variable values are redacted."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": { "container": container_schema() },
+            "required": ["container"],
+            "additionalProperties": false
+        })
+    }
+
+    fn annotations(&self) -> Option<Value> {
+        Some(json!({ "readOnlyHint": true }))
+    }
+
+    fn execute(
+        &self,
+        args: Option<Value>,
+        user: AuthenticatedUser,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        self.execute_with_context(args, ToolContext::new(user))
+    }
+
+    fn execute_with_context(
+        &self,
+        args: Option<Value>,
+        ctx: ToolContext,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        let schema = self.parameters_schema();
+
+        Box::pin(async move {
+            validate_tool_args(&schema, &args)?;
+            let args = args.unwrap_or_default();
+            let container = container_name(&args["container"])?;
+            let docker = docker_client(&ctx.config().docker)?;
+
+            let inspect = docker
+                .inspect_container(container, None::<InspectContainerOptions>)
+                .await
+                .map_err(docker_error)?;
+            let mut inspect = serde_json::to_value(inspect)?;
+            redact_env(&mut inspect);
+            Ok(inspect)
+        })
+    }
+}
+
+/// Fetches the last lines of a container's logs
+#[mcp_tool]
+pub struct DockerContainerLogsTool;
+
+impl McpTool for DockerContainerLogsTool {
+    fn name(&self) -> &'static str {
+        "docker_container_logs"
+    }
+
+    fn description(&self) -> &'static str {
+        "Returns the last tail_lines lines (bounded by the server) of a Docker container's \
+         stdout and stderr."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "container": container_schema(),
+                "tail_lines": { "type": "integer", "minimum": 1 },
+                "since_seconds": { "type": "integer", "minimum": 1 },
+                "timestamps": { "type": "boolean" },
+                "stream": {
+                    "type": "string",
+                    "enum": ["both", "stdout", "stderr"],
+                    "description": "Output streams to return (default both)"
+                }
+            },
+            "required": ["container"],
+            "additionalProperties": false
+        })
+    }
+
+    fn annotations(&self) -> Option<Value> {
+        Some(json!({ "readOnlyHint": true }))
+    }
+
+    fn execute(
+        &self,
+        args: Option<Value>,
+        user: AuthenticatedUser,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        self.execute_with_context(args, ToolContext::new(user))
+    }
+
+    fn execute_with_context(
+        &self,
+        args: Option<Value>,
+        ctx: ToolContext,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        let schema = self.parameters_schema();
+
+        Box::pin(async move {
+            validate_tool_args(&schema, &args)?;
+            let args = args.unwrap_or_default();
+            let config = &ctx.config().docker;
+            let container = container_name(&args["container"])?;
+            let tail_lines = tail_lines(config, args["tail_lines"].as_u64());
+            let stream = args["stream"].as_str().unwrap_or("both");
+            let docker = docker_client(config)?;
+
+            let mut options = LogsOptionsBuilder::new()
+                .stdout(stream != "stderr")
+                .stderr(stream != "stdout")
+                .timestamps(args["timestamps"].as_bool().unwrap_or(false))
+                .tail(&tail_lines.to_string());
+            if let Some(seconds) = args["since_seconds"].as_i64() {
+                let since = chrono::Utc::now().timestamp().saturating_sub(seconds);
+                options = options.since(since.clamp(0, i64::from(i32::MAX)) as i32);
+            }
+
+            let mut output = docker.logs(container, Some(options.build()));
+            let mut logs = Vec::new();
+            let mut truncated = false;
+            while let Some(chunk) = output.next().await {
+                logs.extend_from_slice(chunk.map_err(docker_error)?.as_ref());
+                if logs.len() > config.max_log_bytes {
+                    logs.truncate(config.max_log_bytes);
+                    truncated = true;
+                    break;
+                }
+            }
+
+            Ok(json!({
+                "container": container,
+                "tail_lines": tail_lines,
+                "truncated": truncated,
+                "logs": String::from_utf8_lossy(&logs),
+            }))
+        })
+    }
+}
+
+/// Starts, stops or otherwise changes a container, within the operations the
+/// server allows
+#[mcp_tool]
+pub struct DockerContainerActionTool;
+
+impl McpTool for DockerContainerActionTool {
+    fn name(&self) -> &'static str {
+        "docker_container_action"
+    }
+
+    fn description(&self) -> &'static str {
+        "Performs an operation (start, stop, restart, kill, pause, unpause, remove) on a Docker \
+         container. Only operations enabled by the server are accepted."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "container": container_schema(),
+                "operation": {
+                    "type": "string",
+                    "enum": DockerOperation::ALL.map(DockerOperation::as_str)
+                },
+                "timeout_secs": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "maximum": 300,
+                    "description": "Grace period before stop and restart kill the container"
+                }
+            },
+            "required": ["container", "operation"],
+            "additionalProperties": false
+        })
+    }
+
+    fn annotations(&self) -> Option<Value> {
+        Some(json!({ "destructiveHint": true }))
+    }
+
+    fn execute(
+        &self,
+        args: Option<Value>,
+        user: AuthenticatedUser,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        self.execute_with_context(args, ToolContext::new(user))
+    }
+
+    fn execute_with_context(
+        &self,
+        args: Option<Value>,
+        ctx: ToolContext,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        let schema = self.parameters_schema();
+
+        Box::pin(async move {
+            validate_tool_args(&schema, &args)?;
+            let args = args.unwrap_or_default();
+            let config = &ctx.config().docker;
+            let container = container_name(&args["container"])?;
+            let operation =
+                allowed_operation(config, args["operation"].as_str().unwrap_or_default())?;
+            let grace = args["timeout_secs"].as_i64().map(|secs| secs as i32);
+            let docker = docker_client(config)?;
+
+            let result = match operation {
+                DockerOperation::Start => {
+                    docker
+                        .start_container(container, None::<StartContainerOptions>)
+                        .await
+                }
+                DockerOperation::Stop => {
+                    let options = StopContainerOptions {
+                        t: grace,
+                        signal: None,
+                    };
+                    docker.stop_container(container, Some(options)).await
+                }
+                DockerOperation::Restart => {
+                    let options = RestartContainerOptions {
+                        t: grace,
+                        signal: None,
+                    };
+                    docker.restart_container(container, Some(options)).await
+                }
+                DockerOperation::Kill => {
+                    docker
+                        .kill_container(container, None::<KillContainerOptions>)
+                        .await
+                }
+                DockerOperation::Pause => docker.pause_container(container).await,
+                DockerOperation::Unpause => docker.unpause_container(container).await,
+                DockerOperation::Remove => {
+                    docker
+                        .remove_container(container, None::<RemoveContainerOptions>)
+                        .await
+                }
+            };
+            let changed = match result {
+                Ok(()) => true,
+                Err(error) if not_modified(&error) => false,
+                Err(error) => return Err(docker_error(error)),
+            };
+
+            Ok(json!({
+                "container": container,
+                "operation": operation.as_str(),
+                "changed": changed,
+            }))
+        })
+    }
+}
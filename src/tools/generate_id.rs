@@ -0,0 +1,112 @@
+use super::{McpTool, PinBoxedFuture, mcp_tool, validate_tool_args};
+use crate::auth::AuthenticatedUser;
+use anyhow::{Error, Result};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+/// URL-safe alphabet of 64 symbols used by nanoid
+const NANOID_ALPHABET: &[u8; 64] =
+    b"_-0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// Generates unique identifiers and random bytes
+#[mcp_tool]
+pub struct GenerateIdTool;
+
+impl McpTool for GenerateIdTool {
+    fn name(&self) -> &'static str {
+        "generate_id"
+    }
+
+    fn description(&self) -> &'static str {
+        "Generates identifiers: UUID v4 (random), UUID v7 (time-ordered), nanoid or random bytes."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "kind": {
+                    "type": "string",
+                    "enum": ["uuid_v4", "uuid_v7", "nanoid", "bytes"],
+                    "description": "Kind of identifier (default uuid_v4)"
+                },
+                "count": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 100,
+                    "description": "How many identifiers to generate (default 1)"
+                },
+                "length": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 256,
+                    "description": "Characters of a nanoid (default 21) or number of random bytes (default 16)"
+                },
+                "encoding": {
+                    "type": "string",
+                    "enum": ["hex", "base64"],
+                    "description": "Encoding of random bytes (default hex)"
+                }
+            },
+            "additionalProperties": false
+        })
+    }
+
+    fn output_schema(&self) -> Option<Value> {
+        Some(json!({
+            "type": "object",
+            "properties": {
+                "kind": { "type": "string" },
+                "ids": { "type": "array", "items": { "type": "string" } }
+            },
+            "required": ["kind", "ids"]
+        }))
+    }
+
+    fn annotations(&self) -> Option<Value> {
+        Some(json!({ "readOnlyHint": true }))
+    }
+
+    fn execute(
+        &self,
+        args: Option<Value>,
+        _user: AuthenticatedUser,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        let schema = self.parameters_schema();
+
+        Box::pin(async move {
+            validate_tool_args(&schema, &args)?;
+            let args = args.unwrap_or_default();
+            let kind = args["kind"].as_str().unwrap_or("uuid_v4").to_string();
+            let count = args["count"].as_u64().unwrap_or(1);
+            let length = args["length"].as_u64().map(|length| length as usize);
+            let encoding = args["encoding"].as_str().unwrap_or("hex");
+
+            let ids: Vec<String> = (0..count)
+                .map(|_| match kind.as_str() {
+                    "uuid_v7" => Uuid::now_v7().to_string(),
+                    "nanoid" => nanoid(length.unwrap_or(21)),
+                    "bytes" => {
+                        let bytes: Vec<u8> =
+                            (0..length.unwrap_or(16)).map(|_| rand::random()).collect();
+                        match encoding {
+                            "base64" => BASE64.encode(bytes),
+                            _ => hex::encode(bytes),
+                        }
+                    }
+                    _ => Uuid::new_v4().to_string(),
+                })
+                .collect();
+
+            Ok(json!({ "kind": kind, "ids": ids }))
+        })
+    }
+}
+
+fn nanoid(length: usize) -> String {
+    (0..length)
+        .map(|_| NANOID_ALPHABET[usize::from(rand::random::<u8>() & 63)] as char)
+        .collect()
+}
@@ -26,6 +26,20 @@ impl McpTool for GetTimeTool {
         })
     }
 
+    fn output_schema(&self) -> Option<Value> {
+        Some(json!({
+            "type": "object",
+            "properties": {
+                "current_time": { "type": "string", "format": "date-time" }
+            },
+            "required": ["current_time"]
+        }))
+    }
+
+    fn annotations(&self) -> Option<Value> {
+        Some(json!({ "readOnlyHint": true }))
+    }
+
     fn execute(
         &self,
         args: Option<Value>,
@@ -0,0 +1,453 @@
+use super::{InvalidInput, McpTool, PinBoxedFuture, ToolContext, mcp_tool, validate_tool_args};
+use crate::auth::AuthenticatedUser;
+use crate::config::GitConfig;
+use anyhow::{Error, Result, anyhow, bail};
+use serde_json::{Value, json};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+
+/// Workspaces of one user: `<[git] workspace_root>/<username>/<workspace>`
+pub struct UserWorkspaces {
+    dir: PathBuf,
+    timeout: Duration,
+}
+
+impl UserWorkspaces {
+    pub fn for_user(config: &GitConfig, user: &AuthenticatedUser) -> Result<Self> {
+        let Some(root) = &config.workspace_root else {
+            bail!("git tools are disabled: [git] workspace_root is not set");
+        };
+        let username = &user.credentials().username;
+        if !is_safe_name(username) {
+            bail!(
+                "username '{}' cannot be used as a workspace directory",
+                username
+            );
+        }
+        Ok(Self {
+            dir: root.join(username),
+            timeout: Duration::from_secs(config.timeout_secs),
+        })
+    }
+
+    /// Directory of an existing workspace
+    pub fn workspace(&self, name: &str) -> Result<PathBuf, InvalidInput> {
+        let dir = self.dir.join(workspace_name(name)?);
+        if !dir.join(".git").is_dir() {
+            return Err(InvalidInput::new(format!(
+                "workspace '{}' does not exist",
+                name
+            )));
+        }
+        Ok(dir)
+    }
+
+    /// Run git in `dir`, returning its standard output
+    async fn git(&self, dir: &Path, args: &[&str]) -> Result<Vec<u8>> {
+        let child = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            // Never ask for credentials or pick up the server's git configuration
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .env("GIT_CONFIG_GLOBAL", "/dev/null")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| anyhow!("Failed to run git: {}", e))?;
+        let output = tokio::time::timeout(self.timeout, child.wait_with_output())
+            .await
+            .map_err(|_| anyhow!("git timed out after {}s", self.timeout.as_secs()))??;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            bail!("{}", stderr.trim().trim_start_matches("fatal: "));
+        }
+        Ok(output.stdout)
+    }
+
+    /// Run a read-only git command whose failures stem from the caller's refs or paths
+    async fn query(&self, dir: &Path, args: &[&str]) -> Result<Vec<u8>> {
+        self.git(dir, args)
+            .await
+            .map_err(|e| InvalidInput::new(e.to_string()).into())
+    }
+}
+
+/// Names usable as a single directory component
+fn is_safe_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+}
+
+fn workspace_name(name: &str) -> Result<&str, InvalidInput> {
+    if is_safe_name(name) {
+        Ok(name)
+    } else {
+        Err(InvalidInput::new(format!(
+            "invalid workspace name '{}'",
+            name
+        )))
+    }
+}
+
+/// A revision given by the caller; anything starting with `-` would be read
+/// as an option
+fn revision(value: &Value) -> Result<&str, InvalidInput> {
+    let revision = value.as_str().unwrap_or("HEAD");
+    if revision.is_empty() || revision.starts_with('-') || revision.contains(':') {
+        return Err(InvalidInput::new(format!(
+            "invalid revision '{}'",
+            revision
+        )));
+    }
+    Ok(revision)
+}
+
+/// A repository-relative path given by the caller
+fn repo_path(value: &str) -> Result<&str, InvalidInput> {
+    let path = value.trim_matches('/');
+    let valid = path
+        .split('/')
+        .all(|segment| !segment.is_empty() && segment != "." && segment != "..");
+    if !valid {
+        return Err(InvalidInput::new(format!("invalid path '{}'", value)));
+    }
+    Ok(path)
+}
+
+/// Truncate command output to `limit` bytes on a character boundary
+fn text(mut output: Vec<u8>, limit: usize) -> (String, bool) {
+    let truncated = output.len() > limit;
+    output.truncate(limit);
+    let mut text = String::from_utf8_lossy(&output).into_owned();
+    if truncated && text.ends_with('\u{FFFD}') {
+        text.pop();
+    }
+    (text, truncated)
+}
+
+/// Shallow-clones a repository into a new workspace
+#[mcp_tool]
+pub struct GitCloneTool;
+
+impl McpTool for GitCloneTool {
+    fn name(&self) -> &'static str {
+        "git_clone"
+    }
+
+    fn description(&self) -> &'static str {
+        "Shallow-clones a git repository into a new workspace of the user. \
+         Only remotes allowed by the server can be cloned."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "url": { "type": "string", "minLength": 1, "maxLength": 2048 },
+                "workspace": {
+                    "type": "string",
+                    "maxLength": 100,
+                    "description": "Workspace name (default: repository name)"
+                },
+                "branch": { "type": "string", "maxLength": 255, "description": "Branch or tag to check out" },
+                "depth": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 1000,
+                    "description": "Commits of history to fetch (default 1); diffs need the revisions compared"
+                }
+            },
+            "required": ["url"],
+            "additionalProperties": false
+        })
+    }
+
+    fn annotations(&self) -> Option<Value> {
+        Some(json!({ "destructiveHint": false, "openWorldHint": true }))
+    }
+
+    fn execute(
+        &self,
+        args: Option<Value>,
+        user: AuthenticatedUser,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        self.execute_with_context(args, ToolContext::new(user))
+    }
+
+    fn execute_with_context(
+        &self,
+        args: Option<Value>,
+        ctx: ToolContext,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        let schema = self.parameters_schema();
+
+        Box::pin(async move {
+            validate_tool_args(&schema, &args)?;
+            let args = args.unwrap_or_default();
+            let config = &ctx.config().git;
+            let workspaces = UserWorkspaces::for_user(config, ctx.user())?;
+
+            let url = args["url"].as_str().unwrap_or_default();
+            if !config
+                .allowed_remotes
+                .iter()
+                .any(|prefix| url.starts_with(prefix.as_str()))
+            {
+                return Err(InvalidInput::new(format!("remote '{}' is not allowed", url)).into());
+            }
+            let default_name = url
+                .trim_end_matches('/')
+                .rsplit('/')
+                .next()
+                .unwrap_or_default()
+                .trim_end_matches(".git");
+            let name = workspace_name(args["workspace"].as_str().unwrap_or(default_name))?;
+            let dir = workspaces.dir.join(name);
+            if dir.exists() {
+                return Err(
+                    InvalidInput::new(format!("workspace '{}' already exists", name)).into(),
+                );
+            }
+            tokio::fs::create_dir_all(&workspaces.dir).await?;
+
+            let dir_arg = dir.to_string_lossy();
+            let depth = args["depth"].as_u64().unwrap_or(1).to_string();
+            let mut clone = vec!["clone", "--depth", &depth, "--no-tags"];
+            if let Some(branch) = args.get("branch").map(revision).transpose()? {
+                clone.extend(["--branch", branch]);
+            }
+            clone.extend(["--", url, &dir_arg]);
+            if let Err(e) = workspaces.git(&workspaces.dir, &clone).await {
+                // Leave no half-cloned workspace behind
+                let _ = tokio::fs::remove_dir_all(&dir).await;
+                return Err(e.context("git clone failed"));
+            }
+
+            let head = workspaces.git(&dir, &["rev-parse", "HEAD"]).await?;
+            Ok(json!({
+                "workspace": name,
+                "head": String::from_utf8_lossy(&head).trim(),
+            }))
+        })
+    }
+}
+
+/// Lists the files of a workspace at a revision
+#[mcp_tool]
+pub struct GitListFilesTool;
+
+impl McpTool for GitListFilesTool {
+    fn name(&self) -> &'static str {
+        "git_list_files"
+    }
+
+    fn description(&self) -> &'static str {
+        "Lists the files of a workspace at a revision (default HEAD), optionally under a directory."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "workspace": { "type": "string", "minLength": 1, "maxLength": 100 },
+                "ref": { "type": "string", "maxLength": 255 },
+                "path": { "type": "string", "maxLength": 1024 },
+                "limit": { "type": "integer", "minimum": 1, "maximum": 10000 }
+            },
+            "required": ["workspace"],
+            "additionalProperties": false
+        })
+    }
+
+    fn annotations(&self) -> Option<Value> {
+        Some(json!({ "readOnlyHint": true }))
+    }
+
+    fn execute(
+        &self,
+        args: Option<Value>,
+        user: AuthenticatedUser,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        self.execute_with_context(args, ToolContext::new(user))
+    }
+
+    fn execute_with_context(
+        &self,
+        args: Option<Value>,
+        ctx: ToolContext,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        let schema = self.parameters_schema();
+
+        Box::pin(async move {
+            validate_tool_args(&schema, &args)?;
+            let args = args.unwrap_or_default();
+            let workspaces = UserWorkspaces::for_user(&ctx.config().git, ctx.user())?;
+            let dir = workspaces.workspace(args["workspace"].as_str().unwrap_or_default())?;
+            let revision = revision(&args["ref"])?;
+            let limit = args["limit"].as_u64().unwrap_or(1000) as usize;
+
+            let mut command = vec!["ls-tree", "-r", "--name-only", revision, "--"];
+            if let Some(path) = args["path"].as_str() {
+                command.push(repo_path(path)?);
+            }
+            let output = workspaces.query(&dir, &command).await?;
+            let listing = String::from_utf8_lossy(&output);
+            let files: Vec<&str> = listing.lines().collect();
+
+            Ok(json!({
+                "ref": revision,
+                "files": files.iter().take(limit).collect::<Vec<_>>(),
+                "truncated": files.len() > limit,
+            }))
+        })
+    }
+}
+
+/// Reads a file of a workspace at a revision
+#[mcp_tool]
+pub struct GitReadFileTool;
+
+impl McpTool for GitReadFileTool {
+    fn name(&self) -> &'static str {
+        "git_read_file"
+    }
+
+    fn description(&self) -> &'static str {
+        "Reads a file of a workspace as of a revision (default HEAD)."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "workspace": { "type": "string", "minLength": 1, "maxLength": 100 },
+                "path": { "type": "string", "minLength": 1, "maxLength": 1024 },
+                "ref": { "type": "string", "maxLength": 255 }
+            },
+            "required": ["workspace", "path"],
+            "additionalProperties": false
+        })
+    }
+
+    fn annotations(&self) -> Option<Value> {
+        Some(json!({ "readOnlyHint": true }))
+    }
+
+    fn execute(
+        &self,
+        args: Option<Value>,
+        user: AuthenticatedUser,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        self.execute_with_context(args, ToolContext::new(user))
+    }
+
+    fn execute_with_context(
+        &self,
+        args: Option<Value>,
+        ctx: ToolContext,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        let schema = self.parameters_schema();
+
+        Box::pin(async move {
+            validate_tool_args(&schema, &args)?;
+            let args = args.unwrap_or_default();
+            let config = &ctx.config().git;
+            let workspaces = UserWorkspaces::for_user(config, ctx.user())?;
+            let dir = workspaces.workspace(args["workspace"].as_str().unwrap_or_default())?;
+            let revision = revision(&args["ref"])?;
+            let path = repo_path(args["path"].as_str().unwrap_or_default())?;
+
+            let object = format!("{}:{}", revision, path);
+            let output = workspaces.query(&dir, &["show", &object]).await?;
+            let (content, truncated) = text(output, config.max_output_bytes);
+
+            Ok(json!({
+                "path": path,
+                "ref": revision,
+                "content": content,
+                "truncated": truncated,
+            }))
+        })
+    }
+}
+
+/// Shows the changes between two revisions of a workspace
+#[mcp_tool]
+pub struct GitDiffTool;
+
+impl McpTool for GitDiffTool {
+    fn name(&self) -> &'static str {
+        "git_diff"
+    }
+
+    fn description(&self) -> &'static str {
+        "Unified diff of a workspace between two revisions (to defaults to HEAD), optionally \
+         limited to a path. Shallow clones only contain the revisions fetched."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "workspace": { "type": "string", "minLength": 1, "maxLength": 100 },
+                "from": { "type": "string", "minLength": 1, "maxLength": 255 },
+                "to": { "type": "string", "maxLength": 255 },
+                "path": { "type": "string", "maxLength": 1024 }
+            },
+            "required": ["workspace", "from"],
+            "additionalProperties": false
+        })
+    }
+
+    fn annotations(&self) -> Option<Value> {
+        Some(json!({ "readOnlyHint": true }))
+    }
+
+    fn execute(
+        &self,
+        args: Option<Value>,
+        user: AuthenticatedUser,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        self.execute_with_context(args, ToolContext::new(user))
+    }
+
+    fn execute_with_context(
+        &self,
+        args: Option<Value>,
+        ctx: ToolContext,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        let schema = self.parameters_schema();
+
+        Box::pin(async move {
+            validate_tool_args(&schema, &args)?;
+            let args = args.unwrap_or_default();
+            let config = &ctx.config().git;
+            let workspaces = UserWorkspaces::for_user(config, ctx.user())?;
+            let dir = workspaces.workspace(args["workspace"].as_str().unwrap_or_default())?;
+            let from = revision(&args["from"])?;
+            let to = revision(&args["to"])?;
+
+            let mut command = vec!["diff", "--no-color", "--no-ext-diff", from, to, "--"];
+            if let Some(path) = args["path"].as_str() {
+                command.push(repo_path(path)?);
+            }
+            let output = workspaces.query(&dir, &command).await?;
+            let (diff, truncated) = text(output, config.max_output_bytes);
+
+            Ok(json!({
+                "from": from,
+                "to": to,
+                "diff": diff,
+                "truncated": truncated,
+            }))
+        })
+    }
+}
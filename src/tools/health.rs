@@ -0,0 +1,110 @@
+use super::PinBoxedFuture;
+use crate::ToolDefinition;
+use serde::Serialize;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
+
+/// Result of a tool's health check
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", content = "message", rename_all = "snake_case")]
+pub enum HealthStatus {
+    Healthy,
+    /// Working with reduced functionality; the instance stays ready
+    Degraded(String),
+    /// The tool's dependencies are broken; fails readiness when tool checks are enabled
+    Unhealthy(String),
+}
+
+impl HealthStatus {
+    fn severity(&self) -> u8 {
+        match self {
+            Self::Healthy => 0,
+            Self::Degraded(_) => 1,
+            Self::Unhealthy(_) => 2,
+        }
+    }
+
+    /// Status label without the message
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Healthy => "healthy",
+            Self::Degraded(_) => "degraded",
+            Self::Unhealthy(_) => "unhealthy",
+        }
+    }
+}
+
+/// Health check of a registered tool, kept alongside its definition
+#[derive(Clone)]
+pub struct HealthCheck(Arc<dyn Fn() -> PinBoxedFuture<HealthStatus> + Send + Sync>);
+
+impl HealthCheck {
+    pub fn new<F>(check: F) -> Self
+    where
+        F: Fn() -> PinBoxedFuture<HealthStatus> + Send + Sync + 'static,
+    {
+        Self(Arc::new(check))
+    }
+
+    /// Run the check; one that does not answer in time counts as unhealthy
+    pub async fn run(&self, timeout: Duration) -> HealthStatus {
+        match tokio::time::timeout(timeout, (self.0)()).await {
+            Ok(status) => status,
+            Err(_) => HealthStatus::Unhealthy(format!(
+                "health check timed out after {}ms",
+                timeout.as_millis()
+            )),
+        }
+    }
+}
+
+impl fmt::Debug for HealthCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("HealthCheck")
+    }
+}
+
+/// Health of one tool as reported by `/admin/health/detailed`
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolHealth {
+    pub tool: String,
+    #[serde(flatten)]
+    pub status: HealthStatus,
+    pub duration_ms: f64,
+}
+
+/// Run the health checks of all tools concurrently, sorted by tool name
+/// Tools without a check (e.g. plugins) are left out
+pub async fn check_tools(definitions: &[ToolDefinition], timeout: Duration) -> Vec<ToolHealth> {
+    let mut checks = JoinSet::new();
+    for definition in definitions {
+        let Some(check) = definition.health_check.clone() else {
+            continue;
+        };
+        let tool = definition.name.clone();
+        checks.spawn(async move {
+            let started = Instant::now();
+            let status = check.run(timeout).await;
+            ToolHealth {
+                tool,
+                status,
+                duration_ms: started.elapsed().as_secs_f64() * 1000.0,
+            }
+        });
+    }
+
+    let mut results = checks.join_all().await;
+    results.sort_by(|a, b| a.tool.cmp(&b.tool));
+    results
+}
+
+/// The worst status among the results (healthy when there are none)
+pub fn overall_status(results: &[ToolHealth]) -> &'static str {
+    results
+        .iter()
+        .map(|result| &result.status)
+        .max_by_key(|status| status.severity())
+        .map_or("healthy", HealthStatus::label)
+}
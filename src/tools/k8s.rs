@@ -0,0 +1,480 @@
+use super::{InvalidInput, McpTool, PinBoxedFuture, ToolContext, mcp_tool, validate_tool_args};
+use crate::auth::{AuthenticatedUser, ExposeSecret};
+use crate::config::K8sConfig;
+use anyhow::{Error, Result, anyhow, bail};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use k8s_openapi::api::apps::v1::Deployment;
+use k8s_openapi::api::core::v1::{Container, Pod};
+use kube::api::{ListParams, LogParams};
+use kube::config::{
+    AuthInfo, Cluster, Context, KubeConfigOptions, Kubeconfig, NamedAuthInfo, NamedCluster,
+    NamedContext,
+};
+use kube::{Api, Client, Config};
+use serde_json::{Value, json};
+use std::time::Duration;
+
+/// Namespaces a user may inspect, from their `k8s_namespaces` external key
+///
+/// The key is a comma-separated list; `*` allows every namespace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Namespaces {
+    Any,
+    Only(Vec<String>),
+}
+
+impl Namespaces {
+    pub fn parse(value: &str) -> Self {
+        let names: Vec<String> = value
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect();
+        if names.iter().any(|name| name == "*") {
+            Self::Any
+        } else {
+            Self::Only(names)
+        }
+    }
+
+    /// Namespace of an invocation; without one the first allowed namespace
+    /// (or `default`) is used
+    pub fn resolve(&self, requested: Option<&str>) -> Result<String, InvalidInput> {
+        match (self, requested) {
+            (Self::Any, Some(namespace)) => Ok(namespace.to_string()),
+            (Self::Any, None) => Ok("default".to_string()),
+            (Self::Only(names), Some(namespace)) if names.iter().any(|n| n == namespace) => {
+                Ok(namespace.to_string())
+            }
+            (Self::Only(names), None) if !names.is_empty() => Ok(names[0].clone()),
+            (Self::Only(_), Some(namespace)) => Err(InvalidInput::new(format!(
+                "namespace '{}' is not allowed",
+                namespace
+            ))),
+            (Self::Only(_), None) => Err(InvalidInput::new("no namespace is allowed")),
+        }
+    }
+}
+
+/// Kubeconfig of the calling user
+///
+/// Taken from the `k8s_kubeconfig` external key, or built from `k8s_api_url`,
+/// `k8s_token` and the optional PEM `k8s_ca_cert`. Kubeconfigs pointing at
+/// local files or commands are refused: they would run with the server's
+/// filesystem and privileges.
+pub fn user_kubeconfig(user: &AuthenticatedUser) -> Result<Kubeconfig> {
+    if let Some(yaml) = user.get_external_key("k8s_kubeconfig") {
+        // Parse errors may quote the document: keep it out of messages
+        let kubeconfig = Kubeconfig::from_yaml(yaml)
+            .map_err(|_| anyhow!("Invalid credential 'k8s_kubeconfig'"))?;
+        check_kubeconfig(&kubeconfig)?;
+        return Ok(kubeconfig);
+    }
+
+    let url = user.external_url("k8s_api_url")?;
+    let token = user.external_secret("k8s_token")?;
+    let cluster = Cluster {
+        server: Some(url.to_string()),
+        certificate_authority_data: user
+            .get_external_key("k8s_ca_cert")
+            .map(|pem| BASE64.encode(pem)),
+        ..Default::default()
+    };
+    let auth_info = AuthInfo {
+        token: Some(token.expose_secret().into()),
+        ..Default::default()
+    };
+    Ok(Kubeconfig {
+        clusters: vec![NamedCluster {
+            name: "mcp".to_string(),
+            cluster: Some(cluster),
+        }],
+        auth_infos: vec![NamedAuthInfo {
+            name: "mcp".to_string(),
+            auth_info: Some(auth_info),
+        }],
+        contexts: vec![NamedContext {
+            name: "mcp".to_string(),
+            context: Some(Context {
+                cluster: "mcp".to_string(),
+                user: Some("mcp".to_string()),
+                ..Default::default()
+            }),
+        }],
+        current_context: Some("mcp".to_string()),
+        ..Default::default()
+    })
+}
+
+fn check_kubeconfig(kubeconfig: &Kubeconfig) -> Result<()> {
+    for cluster in kubeconfig
+        .clusters
+        .iter()
+        .filter_map(|c| c.cluster.as_ref())
+    {
+        if cluster.certificate_authority.is_some() {
+            bail!("k8s_kubeconfig must embed certificate-authority-data instead of a file");
+        }
+    }
+    for auth in kubeconfig
+        .auth_infos
+        .iter()
+        .filter_map(|a| a.auth_info.as_ref())
+    {
+        if auth.exec.is_some() || auth.auth_provider.is_some() {
+            bail!("k8s_kubeconfig must not use exec or auth-provider credentials");
+        }
+        if auth.token_file.is_some()
+            || auth.client_certificate.is_some()
+            || auth.client_key.is_some()
+        {
+            bail!("k8s_kubeconfig must embed credentials instead of referencing files");
+        }
+    }
+    Ok(())
+}
+
+/// Kubernetes API client of the calling user
+async fn client(user: &AuthenticatedUser, config: &K8sConfig) -> Result<Client> {
+    let kubeconfig = user_kubeconfig(user)?;
+    let mut kube_config = Config::from_custom_kubeconfig(kubeconfig, &KubeConfigOptions::default())
+        .await
+        .map_err(|e| anyhow!("Invalid Kubernetes credentials: {}", e))?;
+    let timeout = Duration::from_secs(config.timeout_secs);
+    kube_config.connect_timeout = Some(timeout);
+    kube_config.read_timeout = Some(timeout);
+    Client::try_from(kube_config).map_err(|e| anyhow!("Invalid Kubernetes credentials: {}", e))
+}
+
+/// Namespace of an invocation, checked against the user's `k8s_namespaces`
+fn namespace(user: &AuthenticatedUser, args: &Value) -> Result<String> {
+    let allowed = user.get_external_key_parsed::<String>("k8s_namespaces")?;
+    Ok(Namespaces::parse(&allowed).resolve(args["namespace"].as_str())?)
+}
+
+/// Map an API error, reporting unknown objects as invalid input
+fn k8s_error(error: kube::Error) -> Error {
+    match error {
+        kube::Error::Api(response) if response.code == 404 => {
+            InvalidInput::new(response.message).into()
+        }
+        kube::Error::Api(response) => anyhow!(
+            "Kubernetes API returned HTTP {}: {}",
+            response.code,
+            response.message
+        ),
+        error => anyhow!("Kubernetes request failed: {}", error),
+    }
+}
+
+/// `tail_lines` of a `k8s_pod_logs`, bounded by `[k8s] max_tail_lines`
+pub fn tail_lines(config: &K8sConfig, requested: Option<u64>) -> u32 {
+    requested
+        .map_or(config.default_tail_lines, |lines| {
+            lines.min(u64::from(u32::MAX)) as u32
+        })
+        .clamp(1, config.max_tail_lines.max(1))
+}
+
+/// Status line of a pod as `kubectl get pods` shows it
+pub fn pod_summary(pod: &Pod) -> Value {
+    let status = pod.status.as_ref();
+    let containers = status
+        .and_then(|status| status.container_statuses.as_deref())
+        .unwrap_or_default();
+    let total = pod.spec.as_ref().map_or(0, |spec| spec.containers.len());
+    let ready = containers.iter().filter(|c| c.ready).count();
+    let restarts: i32 = containers.iter().map(|c| c.restart_count).sum();
+
+    json!({
+        "name": pod.metadata.name,
+        "phase": status.and_then(|status| status.phase.as_deref()),
+        "ready": format!("{}/{}", ready, total),
+        "restarts": restarts,
+        "node": pod.spec.as_ref().and_then(|spec| spec.node_name.as_deref()),
+        "pod_ip": status.and_then(|status| status.pod_ip.as_deref()),
+        "created": pod.metadata.creation_timestamp.as_ref().map(|time| time.0.to_rfc3339()),
+        "labels": pod.metadata.labels,
+    })
+}
+
+fn container_summary(container: &Container) -> Value {
+    let resources = container.resources.as_ref();
+    json!({
+        "name": container.name,
+        "image": container.image,
+        "ports": container.ports.iter().flatten().map(|port| json!({
+            "name": port.name,
+            "container_port": port.container_port,
+            "protocol": port.protocol,
+        })).collect::<Vec<_>>(),
+        "requests": resources.and_then(|r| r.requests.as_ref()),
+        "limits": resources.and_then(|r| r.limits.as_ref()),
+    })
+}
+
+/// Deployment as `kubectl describe deployment` reports it, without events
+pub fn deployment_summary(deployment: &Deployment) -> Value {
+    let spec = deployment.spec.as_ref();
+    let status = deployment.status.as_ref();
+    let template = spec.and_then(|spec| spec.template.spec.as_ref());
+
+    json!({
+        "name": deployment.metadata.name,
+        "namespace": deployment.metadata.namespace,
+        "created": deployment
+            .metadata
+            .creation_timestamp
+            .as_ref()
+            .map(|time| time.0.to_rfc3339()),
+        "labels": deployment.metadata.labels,
+        "selector": spec.and_then(|spec| spec.selector.match_labels.as_ref()),
+        "strategy": spec
+            .and_then(|spec| spec.strategy.as_ref())
+            .and_then(|strategy| strategy.type_.as_deref()),
+        "replicas": {
+            "desired": spec.and_then(|spec| spec.replicas).unwrap_or(1),
+            "updated": status.and_then(|status| status.updated_replicas).unwrap_or(0),
+            "ready": status.and_then(|status| status.ready_replicas).unwrap_or(0),
+            "available": status.and_then(|status| status.available_replicas).unwrap_or(0),
+            "unavailable": status.and_then(|status| status.unavailable_replicas).unwrap_or(0),
+        },
+        "containers": template
+            .map(|pod| pod.containers.iter().map(container_summary).collect::<Vec<_>>())
+            .unwrap_or_default(),
+        "conditions": status
+            .and_then(|status| status.conditions.as_ref())
+            .into_iter()
+            .flatten()
+            .map(|condition| json!({
+                "type": condition.type_,
+                "status": condition.status,
+                "reason": condition.reason,
+                "message": condition.message,
+            }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+fn namespace_schema() -> Value {
+    json!({
+        "type": "string",
+        "minLength": 1,
+        "maxLength": 63,
+        "description": "Namespace (default: the first one the user may access)"
+    })
+}
+
+/// Lists the pods of a namespace
+#[mcp_tool]
+pub struct K8sListPodsTool;
+
+impl McpTool for K8sListPodsTool {
+    fn name(&self) -> &'static str {
+        "k8s_list_pods"
+    }
+
+    fn description(&self) -> &'static str {
+        "Lists the pods of a Kubernetes namespace with their phase, readiness and restarts, \
+         optionally filtered by a label selector."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "namespace": namespace_schema(),
+                "label_selector": { "type": "string", "maxLength": 1024 },
+                "limit": { "type": "integer", "minimum": 1 }
+            },
+            "additionalProperties": false
+        })
+    }
+
+    fn annotations(&self) -> Option<Value> {
+        Some(json!({ "readOnlyHint": true }))
+    }
+
+    fn execute(
+        &self,
+        args: Option<Value>,
+        user: AuthenticatedUser,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        self.execute_with_context(args, ToolContext::new(user))
+    }
+
+    fn execute_with_context(
+        &self,
+        args: Option<Value>,
+        ctx: ToolContext,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        let schema = self.parameters_schema();
+
+        Box::pin(async move {
+            validate_tool_args(&schema, &args)?;
+            let args = args.unwrap_or_default();
+            let config = &ctx.config().k8s;
+            let namespace = namespace(ctx.user(), &args)?;
+            let limit = args["limit"].as_u64().map_or(config.max_pods, |limit| {
+                limit.min(u64::from(config.max_pods)) as u32
+            });
+
+            let mut params = ListParams::default().limit(limit);
+            if let Some(selector) = args["label_selector"].as_str() {
+                params = params.labels(selector);
+            }
+            let pods: Api<Pod> = Api::namespaced(client(ctx.user(), config).await?, &namespace);
+            let list = pods.list(&params).await.map_err(k8s_error)?;
+
+            Ok(json!({
+                "namespace": namespace,
+                "pods": list.items.iter().map(pod_summary).collect::<Vec<_>>(),
+                "truncated": list.metadata.continue_.is_some_and(|token| !token.is_empty()),
+            }))
+        })
+    }
+}
+
+/// Fetches the last lines of a pod's logs
+#[mcp_tool]
+pub struct K8sPodLogsTool;
+
+impl McpTool for K8sPodLogsTool {
+    fn name(&self) -> &'static str {
+        "k8s_pod_logs"
+    }
+
+    fn description(&self) -> &'static str {
+        "Returns the last tail_lines lines of a pod's logs (bounded by the server), for one \
+         container or the previous instance of a restarted one."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "namespace": namespace_schema(),
+                "pod": { "type": "string", "minLength": 1, "maxLength": 253 },
+                "container": { "type": "string", "minLength": 1, "maxLength": 63 },
+                "tail_lines": { "type": "integer", "minimum": 1 },
+                "since_seconds": { "type": "integer", "minimum": 1 },
+                "previous": { "type": "boolean" },
+                "timestamps": { "type": "boolean" }
+            },
+            "required": ["pod"],
+            "additionalProperties": false
+        })
+    }
+
+    fn annotations(&self) -> Option<Value> {
+        Some(json!({ "readOnlyHint": true }))
+    }
+
+    fn execute(
+        &self,
+        args: Option<Value>,
+        user: AuthenticatedUser,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        self.execute_with_context(args, ToolContext::new(user))
+    }
+
+    fn execute_with_context(
+        &self,
+        args: Option<Value>,
+        ctx: ToolContext,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        let schema = self.parameters_schema();
+
+        Box::pin(async move {
+            validate_tool_args(&schema, &args)?;
+            let args = args.unwrap_or_default();
+            let config = &ctx.config().k8s;
+            let namespace = namespace(ctx.user(), &args)?;
+            let pod = args["pod"].as_str().unwrap_or_default();
+            let tail_lines = tail_lines(config, args["tail_lines"].as_u64());
+
+            let params = LogParams {
+                container: args["container"].as_str().map(str::to_string),
+                tail_lines: Some(i64::from(tail_lines)),
+                since_seconds: args["since_seconds"].as_i64(),
+                previous: args["previous"].as_bool().unwrap_or(false),
+                timestamps: args["timestamps"].as_bool().unwrap_or(false),
+                limit_bytes: Some(config.max_log_bytes as i64),
+                ..Default::default()
+            };
+            let pods: Api<Pod> = Api::namespaced(client(ctx.user(), config).await?, &namespace);
+            let logs = pods.logs(pod, &params).await.map_err(k8s_error)?;
+
+            Ok(json!({
+                "namespace": namespace,
+                "pod": pod,
+                "container": params.container,
+                "tail_lines": tail_lines,
+                "truncated": logs.len() >= config.max_log_bytes,
+                "logs": logs,
+            }))
+        })
+    }
+}
+
+/// Describes a deployment: replicas, containers and conditions
+#[mcp_tool]
+pub struct K8sDescribeDeploymentTool;
+
+impl McpTool for K8sDescribeDeploymentTool {
+    fn name(&self) -> &'static str {
+        "k8s_describe_deployment"
+    }
+
+    fn description(&self) -> &'static str {
+        "Describes a Kubernetes deployment: desired and ready replicas, rollout strategy, \
+         container images and resources, and conditions."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "namespace": namespace_schema(),
+                "name": { "type": "string", "minLength": 1, "maxLength": 253 }
+            },
+            "required": ["name"],
+            "additionalProperties": false
+        })
+    }
+
+    fn annotations(&self) -> Option<Value> {
+        Some(json!({ "readOnlyHint": true }))
+    }
+
+    fn execute(
+        &self,
+        args: Option<Value>,
+        user: AuthenticatedUser,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        self.execute_with_context(args, ToolContext::new(user))
+    }
+
+    fn execute_with_context(
+        &self,
+        args: Option<Value>,
+        ctx: ToolContext,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        let schema = self.parameters_schema();
+
+        Box::pin(async move {
+            validate_tool_args(&schema, &args)?;
+            let args = args.unwrap_or_default();
+            let config = &ctx.config().k8s;
+            let namespace = namespace(ctx.user(), &args)?;
+            let name = args["name"].as_str().unwrap_or_default();
+
+            let deployments: Api<Deployment> =
+                Api::namespaced(client(ctx.user(), config).await?, &namespace);
+            let deployment = deployments.get(name).await.map_err(k8s_error)?;
+            Ok(deployment_summary(&deployment))
+        })
+    }
+}
@@ -1,6 +1,7 @@
 use crate::ToolDefinition;
 use crate::auth::AuthenticatedUser;
-use anyhow::{Error, Result, anyhow};
+use crate::config::DuplicateToolNames;
+use anyhow::{Error, Result, anyhow, bail};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::future::Future;
@@ -9,11 +10,35 @@ use std::pin::Pin;
 // Re-export the macro for convenience
 pub use mcp_server_macros::mcp_tool;
 
+#[cfg(feature = "redis")]
+pub mod cache;
+pub mod calculate;
+pub mod content;
+pub mod docker;
+pub mod context;
+#[cfg(feature = "debug-tools")]
+pub mod debug_echo;
+pub mod generate_id;
 pub mod get_time;
+pub mod git;
+pub mod health;
+#[cfg(feature = "k8s")]
+pub mod k8s;
+pub mod sanitize;
+pub mod send_email;
+pub mod storage;
+pub mod transform;
+pub mod vectors;
+pub mod weather;
+pub mod web_search;
+
+pub use content::BinaryContent;
+pub use context::ToolContext;
+pub use health::{HealthCheck, HealthStatus};
 
 pub type PinBoxedFuture<T> = Pin<Box<dyn Future<Output = T> + Send + 'static>>;
 pub type ToolFunction = Box<
-    dyn Fn(Option<Value>, AuthenticatedUser) -> PinBoxedFuture<Result<Value, Error>> + Send + Sync,
+    dyn Fn(Option<Value>, ToolContext) -> PinBoxedFuture<Result<Value, Error>> + Send + Sync,
 >;
 
 /// Trait for MCP tools
@@ -28,12 +53,61 @@ pub trait McpTool {
     /// JSON Schema for parameters
     fn parameters_schema(&self) -> Value;
 
+    /// JSON Schema for the result, published through `GET /schema`
+    fn output_schema(&self) -> Option<Value> {
+        None
+    }
+
+    /// Behaviour hints for clients (`readOnlyHint`, `destructiveHint`, `cacheTtlSecs`, ...)
+    fn annotations(&self) -> Option<Value> {
+        None
+    }
+
+    /// Version of the tool's interface
+    fn version(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Budget units an invocation consumes, e.g. more for tools calling paid
+    /// APIs; `None` counts as `budget::DEFAULT_TOOL_COST`
+    fn cost(&self) -> Option<u64> {
+        None
+    }
+
+    /// Longest an invocation may run in milliseconds, on top of the server's
+    /// `[timeouts] max_ms`
+    fn timeout_ms(&self) -> Option<u64> {
+        None
+    }
+
+    /// Names of the user external keys the tool reads (e.g. `postgres_url`)
+    /// Users lacking one are reported when credentials are loaded
+    fn required_external_keys(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Check the tool's dependencies (database, upstream API, ...)
+    /// Reported by `/admin/health/detailed` and, when enabled, `/ready`
+    fn health(&self) -> PinBoxedFuture<HealthStatus> {
+        Box::pin(async { HealthStatus::Healthy })
+    }
+
     /// Execute the tool with given arguments and authenticated user
     fn execute(
         &self,
         args: Option<Value>,
         user: AuthenticatedUser,
     ) -> PinBoxedFuture<Result<Value, Error>>;
+
+    /// Execute with access to the invocation context (e.g. `ctx.elicit(..)`)
+    /// Defaults to `execute` with the context's user
+    fn execute_with_context(
+        &self,
+        args: Option<Value>,
+        ctx: ToolContext,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        self.execute(args, ctx.user().clone())
+    }
 }
 
 /// Helper trait for tool registration (used by the #[mcp_tool] macro)
@@ -44,11 +118,46 @@ pub trait ToolRegistration {
 /// Entry in the inventory for tool collection
 pub struct ToolEntry {
     pub constructor: fn() -> Box<dyn McpTool + Send + Sync>,
+    /// Module path of the tool type, used to disambiguate duplicate names
+    pub module: &'static str,
 }
 
 // Collect all tools annotated with #[mcp_tool]
 inventory::collect!(ToolEntry);
 
+/// Error for a mistake in the caller's input found while executing (a bad
+/// expression, an unknown identifier, ...)
+///
+/// Reported as `-32002` Invalid parameters with `data` attached, instead of a
+/// tool execution error.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidInput {
+    pub message: String,
+    pub data: Option<Value>,
+}
+
+impl InvalidInput {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    pub fn with_data(mut self, data: Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+}
+
+impl std::fmt::Display for InvalidInput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for InvalidInput {}
+
 /// Validate tool arguments against JSON Schema
 pub fn validate_tool_args(schema: &Value, args: &Option<Value>) -> Result<()> {
     let properties = schema.get("properties").and_then(|p| p.as_object());
@@ -131,6 +240,17 @@ fn validate_value(name: &str, value: &Value, schema: &Value) -> Result<()> {
         }
     }
 
+    if let Some(allowed) = schema.get("enum").and_then(|v| v.as_array())
+        && !allowed.contains(value)
+    {
+        let choices: Vec<String> = allowed.iter().map(Value::to_string).collect();
+        return Err(anyhow!(
+            "Parameter '{}' must be one of: {}",
+            name,
+            choices.join(", ")
+        ));
+    }
+
     if let Some(s) = value.as_str() {
         if let Some(min_len) = schema.get("minLength").and_then(|v| v.as_u64())
             && s.len() < min_len as usize
@@ -195,50 +315,124 @@ fn validate_value(name: &str, value: &Value, schema: &Value) -> Result<()> {
 }
 
 /// Initialize all tools and return registry and definitions
-/// Tools are automatically discovered via the inventory system
-pub fn initialize_all_tools() -> (HashMap<String, ToolFunction>, Vec<ToolDefinition>) {
+/// Tools are automatically discovered via the inventory system; duplicate
+/// names are an error
+pub fn initialize_all_tools() -> Result<(HashMap<String, ToolFunction>, Vec<ToolDefinition>)> {
+    initialize_tools(DuplicateToolNames::default())
+}
+
+/// Initialize all tools, resolving duplicate names with the given strategy
+pub fn initialize_tools(
+    duplicates: DuplicateToolNames,
+) -> Result<(HashMap<String, ToolFunction>, Vec<ToolDefinition>)> {
+    build_registry(inventory::iter::<ToolEntry>, duplicates)
+}
+
+/// Build a registry from tool entries
+///
+/// With `DuplicateToolNames::Error` every duplicated name is reported at once.
+/// With `PrefixModule` each tool sharing a name is registered as
+/// `<module>_<name>`, where `<module>` is the last segment of its module path.
+pub fn build_registry<'a>(
+    entries: impl IntoIterator<Item = &'a ToolEntry>,
+    duplicates: DuplicateToolNames,
+) -> Result<(HashMap<String, ToolFunction>, Vec<ToolDefinition>)> {
+    let tools: Vec<(&'static str, Box<dyn McpTool + Send + Sync>)> = entries
+        .into_iter()
+        .map(|entry| (entry.module, (entry.constructor)()))
+        .collect();
+
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for (_, tool) in &tools {
+        *counts.entry(tool.name()).or_default() += 1;
+    }
+    let mut duplicated: Vec<(&str, usize)> = counts
+        .iter()
+        .filter(|(_, count)| **count > 1)
+        .map(|(name, count)| (*name, *count))
+        .collect();
+    duplicated.sort();
+
+    if !duplicated.is_empty() && duplicates == DuplicateToolNames::Error {
+        let names: Vec<String> = duplicated
+            .iter()
+            .map(|(name, count)| format!("'{}' ({} tools)", name, count))
+            .collect();
+        bail!(
+            "Duplicate tool names detected: {}. Each tool must have a unique name.",
+            names.join(", ")
+        );
+    }
+
+    // Every tool sharing a name is renamed, so the result does not depend on
+    // the (unspecified) inventory order
+    let names: Vec<String> = tools
+        .iter()
+        .map(|(module, tool)| {
+            if counts[tool.name()] > 1 {
+                let module = module.rsplit("::").next().unwrap_or(module);
+                format!("{}_{}", module, tool.name())
+            } else {
+                tool.name().to_string()
+            }
+        })
+        .collect();
+
+    let mut seen = std::collections::HashSet::new();
+    let mut conflicts: Vec<&str> = names
+        .iter()
+        .filter(|name| !seen.insert(name.as_str()))
+        .map(String::as_str)
+        .collect();
+    if !conflicts.is_empty() {
+        conflicts.sort();
+        conflicts.dedup();
+        bail!(
+            "Duplicate tool names remain after prefixing with the module name: {}",
+            conflicts.join(", ")
+        );
+    }
+
     let mut func_registry = HashMap::new();
     let mut tool_definitions = Vec::new();
-    let mut seen_names = std::collections::HashSet::new();
-
-    // Auto-discover all tools annotated with #[mcp_tool]
-    for entry in inventory::iter::<ToolEntry> {
-        let tool = (entry.constructor)();
-        let name = tool.name();
-
-        // Check for duplicate tool names
-        if !seen_names.insert(name.to_string()) {
-            panic!(
-                "Duplicate tool name detected: '{}'. Each tool must have a unique name.",
-                name
-            );
-        }
-
-        register_tool_boxed(tool, &mut func_registry, &mut tool_definitions);
+    for ((_, tool), name) in tools.into_iter().zip(names) {
+        register_tool_boxed(tool, name, &mut func_registry, &mut tool_definitions);
     }
 
-    (func_registry, tool_definitions)
+    Ok((func_registry, tool_definitions))
 }
 
 /// Register a boxed tool instance (used internally for auto-registration)
 fn register_tool_boxed(
     tool: Box<dyn McpTool + Send + Sync>,
+    name: String,
     func_reg: &mut HashMap<String, ToolFunction>,
     def_vec: &mut Vec<ToolDefinition>,
 ) {
-    let name = tool.name().to_string();
+    let tool_arc: std::sync::Arc<dyn McpTool + Send + Sync> = std::sync::Arc::from(tool);
 
     // Add to definitions (for discover endpoint)
+    let health_tool = tool_arc.clone();
     def_vec.push(ToolDefinition {
         name: name.clone(),
-        description: tool.description().to_string(),
-        parameters: tool.parameters_schema(),
+        description: tool_arc.description().to_string(),
+        parameters: tool_arc.parameters_schema(),
+        output_schema: tool_arc.output_schema(),
+        annotations: tool_arc.annotations(),
+        version: tool_arc.version().map(str::to_string),
+        cost: tool_arc.cost(),
+        timeout_ms: tool_arc.timeout_ms(),
+        required_external_keys: tool_arc
+            .required_external_keys()
+            .iter()
+            .map(|key| key.to_string())
+            .collect(),
+        health_check: Some(HealthCheck::new(move || health_tool.health())),
     });
 
     // Add to function registry (for invoke endpoint)
-    let tool_arc: std::sync::Arc<dyn McpTool + Send + Sync> = std::sync::Arc::from(tool);
     let execution_closure =
-        move |args: Option<Value>, user: AuthenticatedUser| tool_arc.execute(args, user);
+        move |args: Option<Value>, ctx: ToolContext| tool_arc.execute_with_context(args, ctx);
 
     func_reg.insert(name, Box::new(execution_closure));
 }
@@ -0,0 +1,240 @@
+use crate::config::SanitizationConfig;
+use anyhow::{Context, Result};
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Patterns used for injection screening when none are configured
+pub const DEFAULT_INJECTION_PATTERNS: &[&str] = &[
+    r"ignore\s+(all\s+)?(the\s+)?(previous|prior|above)\s+instructions",
+    r"disregard\s+(all\s+)?(the\s+)?(previous|prior|above|system)\s+(instructions|prompt)",
+    r"forget\s+(all\s+)?(your|the)\s+(previous\s+)?instructions",
+    r"you\s+are\s+now\s+(in\s+)?(developer|dan|jailbreak)",
+    r"reveal\s+(your|the)\s+system\s+prompt",
+];
+
+/// What a sanitizer did to an argument
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FindingAction {
+    /// The value was rewritten
+    Modified,
+    /// The value was left as-is but looks suspicious
+    Flagged,
+    /// The invocation must not proceed
+    Rejected,
+}
+
+/// Single sanitizer finding, attached to the audit record of the invocation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SanitizerFinding {
+    pub sanitizer: String,
+    /// Path of the string inside the arguments (e.g. `query` or `filters[0].name`)
+    pub argument: String,
+    pub action: FindingAction,
+    pub detail: String,
+}
+
+/// Pluggable sanitizer applied to every string argument before tool execution
+pub trait ArgumentSanitizer: Send + Sync {
+    /// Sanitizer name reported in findings
+    fn name(&self) -> &'static str;
+
+    /// Inspect and optionally rewrite a string value, returning any findings
+    fn sanitize(
+        &self,
+        tool_name: &str,
+        argument: &str,
+        value: &mut String,
+    ) -> Vec<SanitizerFinding>;
+}
+
+/// Strips control characters, keeping newlines, carriage returns and tabs
+pub struct ControlCharSanitizer {
+    config: SanitizationConfig,
+}
+
+impl ControlCharSanitizer {
+    pub fn new(config: SanitizationConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl ArgumentSanitizer for ControlCharSanitizer {
+    fn name(&self) -> &'static str {
+        "control_chars"
+    }
+
+    fn sanitize(
+        &self,
+        tool_name: &str,
+        argument: &str,
+        value: &mut String,
+    ) -> Vec<SanitizerFinding> {
+        if !self.config.strip_control_chars_for(tool_name) {
+            return Vec::new();
+        }
+
+        let is_stripped = |c: char| c.is_control() && !matches!(c, '\n' | '\r' | '\t');
+        let removed = value.chars().filter(|c| is_stripped(*c)).count();
+        if removed == 0 {
+            return Vec::new();
+        }
+
+        value.retain(|c| !is_stripped(c));
+        vec![SanitizerFinding {
+            sanitizer: self.name().to_string(),
+            argument: argument.to_string(),
+            action: FindingAction::Modified,
+            detail: format!("Removed {} control character(s)", removed),
+        }]
+    }
+}
+
+/// Pattern-based prompt-injection screening
+pub struct InjectionScreen {
+    config: SanitizationConfig,
+    patterns: Vec<Regex>,
+}
+
+impl InjectionScreen {
+    /// Compile the configured patterns (or the defaults) case-insensitively
+    pub fn new(config: SanitizationConfig) -> Result<Self> {
+        let sources: Vec<&str> = if config.injection_patterns.is_empty() {
+            DEFAULT_INJECTION_PATTERNS.to_vec()
+        } else {
+            config
+                .injection_patterns
+                .iter()
+                .map(String::as_str)
+                .collect()
+        };
+
+        let patterns = sources
+            .iter()
+            .map(|p| {
+                RegexBuilder::new(p)
+                    .case_insensitive(true)
+                    .build()
+                    .with_context(|| format!("Invalid injection pattern: {}", p))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { config, patterns })
+    }
+}
+
+impl ArgumentSanitizer for InjectionScreen {
+    fn name(&self) -> &'static str {
+        "injection_screen"
+    }
+
+    fn sanitize(
+        &self,
+        tool_name: &str,
+        argument: &str,
+        value: &mut String,
+    ) -> Vec<SanitizerFinding> {
+        if !self.config.screen_injections_for(tool_name) {
+            return Vec::new();
+        }
+
+        let action = if self.config.reject_injections_for(tool_name) {
+            FindingAction::Rejected
+        } else {
+            FindingAction::Flagged
+        };
+
+        self.patterns
+            .iter()
+            .filter(|p| p.is_match(value))
+            .map(|p| SanitizerFinding {
+                sanitizer: self.name().to_string(),
+                argument: argument.to_string(),
+                action,
+                detail: format!("Matched injection pattern: {}", p.as_str()),
+            })
+            .collect()
+    }
+}
+
+/// Ordered set of sanitizers applied to invocation arguments
+#[derive(Clone)]
+pub struct SanitizerChain {
+    config: SanitizationConfig,
+    sanitizers: Vec<Arc<dyn ArgumentSanitizer>>,
+}
+
+impl Default for SanitizerChain {
+    fn default() -> Self {
+        Self::new(SanitizationConfig::default(), Vec::new())
+            .expect("default injection patterns are valid")
+    }
+}
+
+impl SanitizerChain {
+    /// Build the chain: built-in sanitizers first, then custom ones in order
+    pub fn new(
+        config: SanitizationConfig,
+        custom: Vec<Arc<dyn ArgumentSanitizer>>,
+    ) -> Result<Self> {
+        let mut sanitizers: Vec<Arc<dyn ArgumentSanitizer>> = vec![
+            Arc::new(ControlCharSanitizer::new(config.clone())),
+            Arc::new(InjectionScreen::new(config.clone())?),
+        ];
+        sanitizers.extend(custom);
+
+        Ok(Self { config, sanitizers })
+    }
+
+    /// Apply all sanitizers to every string in the arguments, returning findings
+    pub fn apply(&self, tool_name: &str, args: &mut Option<Value>) -> Vec<SanitizerFinding> {
+        let mut findings = Vec::new();
+        if !self.config.enabled_for(tool_name) {
+            return findings;
+        }
+
+        if let Some(value) = args.as_mut() {
+            self.walk(tool_name, "", value, &mut findings);
+        }
+        findings
+    }
+
+    fn walk(
+        &self,
+        tool_name: &str,
+        path: &str,
+        value: &mut Value,
+        findings: &mut Vec<SanitizerFinding>,
+    ) {
+        match value {
+            Value::String(s) => {
+                for sanitizer in &self.sanitizers {
+                    findings.extend(sanitizer.sanitize(tool_name, path, s));
+                }
+            }
+            Value::Array(items) => {
+                for (i, item) in items.iter_mut().enumerate() {
+                    self.walk(tool_name, &format!("{}[{}]", path, i), item, findings);
+                }
+            }
+            Value::Object(map) => {
+                for (key, item) in map.iter_mut() {
+                    let child = if path.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{}.{}", path, key)
+                    };
+                    self.walk(tool_name, &child, item, findings);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Whether any finding requires rejecting the invocation
+pub fn is_rejected(findings: &[SanitizerFinding]) -> bool {
+    findings.iter().any(|f| f.action == FindingAction::Rejected)
+}
@@ -0,0 +1,347 @@
+use super::{InvalidInput, McpTool, PinBoxedFuture, ToolContext, mcp_tool, validate_tool_args};
+use crate::auth::{AuthenticatedUser, ExposeSecret};
+use anyhow::{Error, Result};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use object_store::aws::{AmazonS3, AmazonS3Builder};
+use object_store::path::Path;
+use object_store::signer::Signer;
+use object_store::{ObjectStore, PutPayload};
+use reqwest::Method;
+use serde_json::{Value, json};
+use std::time::Duration;
+use tokio_stream::StreamExt;
+
+/// Object storage of the calling user
+///
+/// Built from the user's external keys: `s3_bucket`, `s3_access_key_id` and
+/// `s3_secret_access_key`, plus the optional `s3_region`, `s3_endpoint` (for
+/// S3-compatible services such as MinIO or R2) and `s3_prefix`. Every key the
+/// tools accept is relative to `s3_prefix`, which confines the user to their
+/// part of a shared bucket.
+pub struct UserBucket {
+    store: AmazonS3,
+    prefix: String,
+}
+
+impl UserBucket {
+    pub fn for_user(user: &AuthenticatedUser) -> Result<Self> {
+        let bucket = user.get_external_key_parsed::<String>("s3_bucket")?;
+        let access_key_id = user.external_secret("s3_access_key_id")?;
+        let secret_access_key = user.external_secret("s3_secret_access_key")?;
+
+        let mut builder = AmazonS3Builder::new()
+            .with_bucket_name(bucket)
+            .with_access_key_id(access_key_id.expose_secret())
+            .with_secret_access_key(secret_access_key.expose_secret())
+            .with_region(
+                user.get_external_key("s3_region")
+                    .map_or("us-east-1", String::as_str),
+            );
+        if let Some(endpoint) = user.get_external_key("s3_endpoint") {
+            builder = builder
+                .with_endpoint(endpoint)
+                .with_allow_http(endpoint.starts_with("http://"));
+        }
+        let store = builder
+            .build()
+            .map_err(|e| anyhow::anyhow!("Invalid S3 credentials: {}", e))?;
+
+        let prefix = user
+            .get_external_key("s3_prefix")
+            .map(|prefix| prefix.trim_matches('/').to_string())
+            .unwrap_or_default();
+        Ok(Self { store, prefix })
+    }
+
+    /// Full object path of a user-relative key, refusing keys that could
+    /// escape the prefix
+    pub fn path(&self, key: &str) -> Result<Path, InvalidInput> {
+        let key = key.trim_start_matches('/');
+        let valid = !key.is_empty()
+            && key
+                .split('/')
+                .all(|segment| !segment.is_empty() && segment != "." && segment != "..");
+        if !valid {
+            return Err(InvalidInput::new(format!("invalid object key '{}'", key)));
+        }
+        let full = if self.prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}/{}", self.prefix, key)
+        };
+        Path::parse(&full).map_err(|_| InvalidInput::new(format!("invalid object key '{}'", key)))
+    }
+
+    /// Key of an object path as the user knows it, without the prefix
+    pub fn relative_key<'a>(&self, path: &'a Path) -> &'a str {
+        let path = path.as_ref();
+        if self.prefix.is_empty() {
+            return path;
+        }
+        path.strip_prefix(self.prefix.as_str())
+            .and_then(|rest| rest.strip_prefix('/'))
+            .unwrap_or(path)
+    }
+}
+
+/// Map a storage error on `key`, reporting unknown objects as invalid input
+fn storage_error(error: object_store::Error, key: &str) -> Error {
+    match error {
+        object_store::Error::NotFound { .. } => {
+            InvalidInput::new(format!("object '{}' not found", key)).into()
+        }
+        error => anyhow::anyhow!("Storage request failed: {}", error),
+    }
+}
+
+/// Uploads an object
+#[mcp_tool]
+pub struct StoragePutTool;
+
+impl McpTool for StoragePutTool {
+    fn name(&self) -> &'static str {
+        "storage_put"
+    }
+
+    fn description(&self) -> &'static str {
+        "Stores an object in the user's bucket, overwriting any object with the same key."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "key": { "type": "string", "minLength": 1, "maxLength": 1024 },
+                "content": { "type": "string" },
+                "encoding": {
+                    "type": "string",
+                    "enum": ["utf8", "base64"],
+                    "description": "Encoding of content (default utf8)"
+                },
+                "content_type": { "type": "string", "maxLength": 255 }
+            },
+            "required": ["key", "content"],
+            "additionalProperties": false
+        })
+    }
+
+    fn annotations(&self) -> Option<Value> {
+        Some(json!({ "destructiveHint": true, "idempotentHint": true }))
+    }
+
+    fn execute(
+        &self,
+        args: Option<Value>,
+        user: AuthenticatedUser,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        let schema = self.parameters_schema();
+
+        Box::pin(async move {
+            validate_tool_args(&schema, &args)?;
+            let args = args.unwrap_or_default();
+            let key = args["key"].as_str().unwrap_or_default();
+            let content = args["content"].as_str().unwrap_or_default();
+            let bytes = match args["encoding"].as_str() {
+                Some("base64") => BASE64.decode(content).map_err(|e| {
+                    InvalidInput::new(format!("content is not valid base64: {}", e))
+                })?,
+                _ => content.as_bytes().to_vec(),
+            };
+
+            let bucket = UserBucket::for_user(&user)?;
+            let path = bucket.path(key)?;
+            let size = bytes.len();
+            let mut attributes = object_store::Attributes::new();
+            if let Some(content_type) = args["content_type"].as_str() {
+                attributes.insert(
+                    object_store::Attribute::ContentType,
+                    content_type.to_string().into(),
+                );
+            }
+            let result = bucket
+                .store
+                .put_opts(
+                    &path,
+                    PutPayload::from(bytes),
+                    object_store::PutOptions::from(attributes),
+                )
+                .await
+                .map_err(|e| storage_error(e, key))?;
+
+            Ok(json!({ "key": key, "size": size, "e_tag": result.e_tag }))
+        })
+    }
+}
+
+/// Downloads an object, inline when small and as a presigned URL otherwise
+#[mcp_tool]
+pub struct StorageGetTool;
+
+impl McpTool for StorageGetTool {
+    fn name(&self) -> &'static str {
+        "storage_get"
+    }
+
+    fn description(&self) -> &'static str {
+        "Reads an object from the user's bucket. Small objects are returned inline; large ones \
+         (or any, with presign) as a temporary download URL."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "key": { "type": "string", "minLength": 1, "maxLength": 1024 },
+                "presign": {
+                    "type": "boolean",
+                    "description": "Always return a download URL instead of the content"
+                }
+            },
+            "required": ["key"],
+            "additionalProperties": false
+        })
+    }
+
+    fn annotations(&self) -> Option<Value> {
+        Some(json!({ "readOnlyHint": true }))
+    }
+
+    fn execute(
+        &self,
+        args: Option<Value>,
+        user: AuthenticatedUser,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        self.execute_with_context(args, ToolContext::new(user))
+    }
+
+    fn execute_with_context(
+        &self,
+        args: Option<Value>,
+        ctx: ToolContext,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        let schema = self.parameters_schema();
+
+        Box::pin(async move {
+            validate_tool_args(&schema, &args)?;
+            let args = args.unwrap_or_default();
+            let key = args["key"].as_str().unwrap_or_default();
+            let config = &ctx.config().storage;
+
+            let bucket = UserBucket::for_user(ctx.user())?;
+            let path = bucket.path(key)?;
+            let meta = bucket
+                .store
+                .head(&path)
+                .await
+                .map_err(|e| storage_error(e, key))?;
+
+            if args["presign"].as_bool().unwrap_or(false) || meta.size > config.inline_max_bytes {
+                let expires_in = Duration::from_secs(config.presign_expiry_secs);
+                let url = bucket
+                    .store
+                    .signed_url(Method::GET, &path, expires_in)
+                    .await
+                    .map_err(|e| storage_error(e, key))?;
+                return Ok(json!({
+                    "key": key,
+                    "size": meta.size,
+                    "url": url.to_string(),
+                    "expires_in_secs": config.presign_expiry_secs,
+                }));
+            }
+
+            let bytes = bucket
+                .store
+                .get(&path)
+                .await
+                .map_err(|e| storage_error(e, key))?
+                .bytes()
+                .await
+                .map_err(|e| storage_error(e, key))?;
+            let (encoding, content) = match std::str::from_utf8(&bytes) {
+                Ok(text) => ("utf8", text.to_string()),
+                Err(_) => ("base64", BASE64.encode(&bytes)),
+            };
+            Ok(json!({
+                "key": key,
+                "size": meta.size,
+                "encoding": encoding,
+                "content": content,
+            }))
+        })
+    }
+}
+
+/// Lists objects under a prefix
+#[mcp_tool]
+pub struct StorageListTool;
+
+impl McpTool for StorageListTool {
+    fn name(&self) -> &'static str {
+        "storage_list"
+    }
+
+    fn description(&self) -> &'static str {
+        "Lists objects in the user's bucket, optionally under a key prefix (directory)."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "prefix": { "type": "string", "maxLength": 1024 },
+                "limit": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 1000,
+                    "description": "Maximum number of objects (default 100)"
+                }
+            },
+            "additionalProperties": false
+        })
+    }
+
+    fn annotations(&self) -> Option<Value> {
+        Some(json!({ "readOnlyHint": true }))
+    }
+
+    fn execute(
+        &self,
+        args: Option<Value>,
+        user: AuthenticatedUser,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        let schema = self.parameters_schema();
+
+        Box::pin(async move {
+            validate_tool_args(&schema, &args)?;
+            let args = args.unwrap_or_default();
+            let limit = args["limit"].as_u64().unwrap_or(100) as usize;
+
+            let bucket = UserBucket::for_user(&user)?;
+            let prefix = match args["prefix"].as_str().map(|p| p.trim_matches('/')) {
+                Some(prefix) if !prefix.is_empty() => Some(bucket.path(prefix)?),
+                _ if bucket.prefix.is_empty() => None,
+                _ => Some(Path::from(bucket.prefix.as_str())),
+            };
+
+            let mut listing = bucket.store.list(prefix.as_ref());
+            let mut objects = Vec::new();
+            let mut truncated = false;
+            while let Some(meta) = listing.next().await {
+                let meta = meta.map_err(|e| anyhow::anyhow!("Storage request failed: {}", e))?;
+                if objects.len() == limit {
+                    truncated = true;
+                    break;
+                }
+                objects.push(json!({
+                    "key": bucket.relative_key(&meta.location),
+                    "size": meta.size,
+                    "last_modified": meta.last_modified.to_rfc3339(),
+                }));
+            }
+
+            Ok(json!({ "objects": objects, "truncated": truncated }))
+        })
+    }
+}
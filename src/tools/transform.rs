@@ -0,0 +1,124 @@
+use crate::auth::AuthenticatedUser;
+use anyhow::Result;
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Post-processing hook applied to every successful tool result before serialization
+pub trait ResultTransformer: Send + Sync {
+    /// Rewrite or augment a tool result; returning an error fails the invocation
+    fn transform(&self, tool_name: &str, user: &AuthenticatedUser, result: Value) -> Result<Value>;
+}
+
+/// Truncates string values longer than a character limit, appending a marker
+pub struct TruncateStrings {
+    max_chars: usize,
+}
+
+impl TruncateStrings {
+    /// Marker appended to truncated strings
+    pub const MARKER: &'static str = "…[truncated]";
+
+    pub fn new(max_chars: usize) -> Self {
+        Self { max_chars }
+    }
+
+    fn truncate(&self, value: &mut Value) {
+        match value {
+            Value::String(s) => {
+                if let Some((byte_index, _)) = s.char_indices().nth(self.max_chars) {
+                    s.truncate(byte_index);
+                    s.push_str(Self::MARKER);
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.truncate(item)),
+            Value::Object(map) => map.values_mut().for_each(|item| self.truncate(item)),
+            _ => {}
+        }
+    }
+}
+
+impl ResultTransformer for TruncateStrings {
+    fn transform(
+        &self,
+        _tool_name: &str,
+        _user: &AuthenticatedUser,
+        mut result: Value,
+    ) -> Result<Value> {
+        self.truncate(&mut result);
+        Ok(result)
+    }
+}
+
+/// Shorten a result until its JSON fits in `max_bytes`
+///
+/// Strings are cut (with `TruncateStrings::MARKER`) and arrays shortened to
+/// the largest common length that fits. `None` when even empty strings and
+/// arrays are too large, e.g. an object with too many keys.
+pub fn truncate_to_size(value: &Value, max_bytes: usize) -> Option<Value> {
+    let fits = |limit: usize| {
+        let truncated = cap(value, limit);
+        (serialized_len(&truncated) <= max_bytes).then_some(truncated)
+    };
+    let mut best = fits(0)?;
+    // Binary search for the largest limit that still fits
+    let (mut low, mut high) = (0, max_bytes);
+    while low < high {
+        let mid = low + (high - low).div_ceil(2);
+        match fits(mid) {
+            Some(truncated) => {
+                best = truncated;
+                low = mid;
+            }
+            None => high = mid - 1,
+        }
+    }
+    Some(best)
+}
+
+/// Copy of `value` with strings cut to `limit` characters and arrays to `limit` items
+fn cap(value: &Value, limit: usize) -> Value {
+    match value {
+        Value::String(s) => match s.char_indices().nth(limit) {
+            Some((byte_index, _)) => {
+                Value::String(format!("{}{}", &s[..byte_index], TruncateStrings::MARKER))
+            }
+            None => value.clone(),
+        },
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .take(limit)
+                .map(|item| cap(item, limit))
+                .collect(),
+        ),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, item)| (key.clone(), cap(item, limit)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+fn serialized_len(value: &Value) -> usize {
+    serde_json::to_vec(value).map_or(usize::MAX, |bytes| bytes.len())
+}
+
+/// Ordered list of result transformers, applied in registration order
+#[derive(Clone, Default)]
+pub struct TransformerChain {
+    transformers: Vec<Arc<dyn ResultTransformer>>,
+}
+
+impl TransformerChain {
+    pub fn new(transformers: Vec<Arc<dyn ResultTransformer>>) -> Self {
+        Self { transformers }
+    }
+
+    /// Run the result through every transformer, stopping at the first error
+    pub fn apply(&self, tool_name: &str, user: &AuthenticatedUser, result: Value) -> Result<Value> {
+        self.transformers
+            .iter()
+            .try_fold(result, |acc, t| t.transform(tool_name, user, acc))
+    }
+}
@@ -0,0 +1,452 @@
+use super::{InvalidInput, McpTool, PinBoxedFuture, ToolContext, mcp_tool, validate_tool_args};
+use crate::auth::{AuthenticatedUser, ExposeSecret, SecretString};
+use crate::config::VectorsConfig;
+use crate::http_client::HttpClient;
+use anyhow::{Context, Error, Result, bail};
+use reqwest::{Method, StatusCode};
+use serde_json::{Map, Value, json};
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+
+/// Points accepted by a single `vector_upsert`
+const MAX_POINTS: usize = 100;
+
+/// A document as stored by `vector_upsert`
+#[derive(Debug, Clone)]
+pub struct Point {
+    pub id: String,
+    pub vector: Vec<f64>,
+    pub text: Option<String>,
+    pub metadata: Value,
+}
+
+/// Qdrant collection of the calling user
+///
+/// The server comes from the user's external keys `qdrant_url` and the
+/// optional `qdrant_api_key`. Points are tagged with the owner's username and
+/// every search filters on it, so users sharing a collection only see their
+/// own points.
+pub struct VectorStore {
+    client: HttpClient,
+    timeout: Duration,
+    url: String,
+    api_key: Option<SecretString>,
+    collection: String,
+    owner: String,
+}
+
+impl VectorStore {
+    pub fn for_user(ctx: &ToolContext, config: &VectorsConfig) -> Result<Self> {
+        let user = ctx.user();
+        let url = user.external_url("qdrant_url")?;
+        let api_key = user
+            .get_external_key("qdrant_api_key")
+            .map(|key| SecretString::from(key.as_str()));
+
+        Ok(Self {
+            client: ctx.http().clone(),
+            timeout: Duration::from_millis(config.timeout_ms),
+            url: url.as_str().trim_end_matches('/').to_string(),
+            api_key,
+            collection: config.collection.clone(),
+            owner: user.credentials().username.clone(),
+        })
+    }
+
+    async fn request(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<Value>,
+    ) -> Result<(StatusCode, Value)> {
+        let url = format!("{}/collections/{}{}", self.url, self.collection, path);
+        let mut request = self.client.request(method, &url)?.timeout(self.timeout);
+        if let Some(api_key) = &self.api_key {
+            request = request.header("api-key", api_key.expose_secret());
+        }
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+        let response = request.send().await.context("qdrant request failed")?;
+        let status = response.status();
+        Ok((status, response.json().await.unwrap_or_default()))
+    }
+
+    async fn send(&self, method: Method, path: &str, body: Option<Value>) -> Result<Value> {
+        let (status, body) = self.request(method, path, body).await?;
+        if !status.is_success() {
+            return Err(qdrant_error(status, &body));
+        }
+        Ok(body)
+    }
+
+    /// Create the collection, sized for `dimensions`, unless it exists
+    async fn ensure_collection(&self, dimensions: usize, distance: &str) -> Result<()> {
+        let (status, body) = self.request(Method::GET, "", None).await?;
+        if status.is_success() {
+            return Ok(());
+        }
+        if status != StatusCode::NOT_FOUND {
+            return Err(qdrant_error(status, &body));
+        }
+
+        let create = json!({ "vectors": { "size": dimensions, "distance": distance } });
+        let (status, body) = self.request(Method::PUT, "", Some(create)).await?;
+        // Another request may have created it meanwhile
+        if !status.is_success() && status != StatusCode::CONFLICT {
+            return Err(qdrant_error(status, &body));
+        }
+        self.send(
+            Method::PUT,
+            "/index",
+            Some(json!({ "field_name": "owner", "field_schema": "keyword" })),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Insert or replace points, creating the collection on first use
+    pub async fn upsert(&self, points: Vec<Point>, distance: &str) -> Result<()> {
+        let dimensions = points.first().map_or(0, |point| point.vector.len());
+        self.ensure_collection(dimensions, distance).await?;
+
+        let points: Vec<Value> = points
+            .into_iter()
+            .map(|point| {
+                json!({
+                    "id": point_id(&self.owner, &point.id),
+                    "vector": point.vector,
+                    "payload": {
+                        "owner": self.owner,
+                        "id": point.id,
+                        "text": point.text,
+                        "metadata": point.metadata,
+                    },
+                })
+            })
+            .collect();
+        self.send(
+            Method::PUT,
+            "/points?wait=true",
+            Some(json!({ "points": points })),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Nearest points of the user, optionally restricted to exact metadata values
+    pub async fn search(
+        &self,
+        vector: Vec<f64>,
+        limit: usize,
+        filter: &Map<String, Value>,
+    ) -> Result<Vec<Value>> {
+        let mut must = vec![json!({ "key": "owner", "match": { "value": self.owner } })];
+        must.extend(filter.iter().map(|(key, value)| {
+            json!({ "key": format!("metadata.{}", key), "match": { "value": value } })
+        }));
+        let body = json!({
+            "vector": vector,
+            "limit": limit,
+            "filter": { "must": must },
+            "with_payload": true,
+        });
+
+        let (status, response) = self
+            .request(Method::POST, "/points/search", Some(body))
+            .await?;
+        // Nothing was ever upserted
+        if status == StatusCode::NOT_FOUND {
+            return Ok(Vec::new());
+        }
+        if !status.is_success() {
+            return Err(qdrant_error(status, &response));
+        }
+        Ok(response["result"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|hit| {
+                let payload = &hit["payload"];
+                json!({
+                    "id": payload["id"],
+                    "score": hit["score"],
+                    "text": payload["text"],
+                    "metadata": payload["metadata"],
+                })
+            })
+            .collect())
+    }
+}
+
+fn qdrant_error(status: StatusCode, body: &Value) -> Error {
+    match body["status"]["error"].as_str() {
+        Some(message) => anyhow::anyhow!("qdrant returned HTTP {}: {}", status, message),
+        None => anyhow::anyhow!("qdrant returned HTTP {}", status),
+    }
+}
+
+/// Qdrant point ID of a user's document ID
+///
+/// Qdrant only accepts UUIDs and integers; hashing the owner in keeps two
+/// users' documents with the same ID apart.
+pub fn point_id(owner: &str, id: &str) -> String {
+    let digest = Sha256::digest(format!("{}\0{}", owner, id).as_bytes());
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&digest[..16]);
+    uuid::Uuid::from_bytes(bytes).to_string()
+}
+
+/// Embeddings of `texts` from the OpenAI-compatible `[vectors] embedding_endpoint`
+///
+/// The user's `embedding_api_key` external key, when present, is sent as a
+/// bearer token.
+pub async fn embed(
+    ctx: &ToolContext,
+    config: &VectorsConfig,
+    texts: Vec<String>,
+) -> Result<Vec<Vec<f64>>> {
+    let Some(endpoint) = &config.embedding_endpoint else {
+        bail!(
+            "embeddings are disabled: [vectors] embedding_endpoint is not set, pass vectors instead"
+        );
+    };
+    let count = texts.len();
+    let mut request = ctx
+        .http()
+        .post(endpoint)?
+        .timeout(Duration::from_millis(config.timeout_ms))
+        .json(&json!({ "model": config.embedding_model, "input": texts }));
+    if let Some(api_key) = ctx.user().get_external_key("embedding_api_key") {
+        request = request.bearer_auth(api_key);
+    }
+    let response = request.send().await.context("embedding request failed")?;
+    let status = response.status();
+    if !status.is_success() {
+        bail!("embedding endpoint returned HTTP {}", status);
+    }
+    let body: Value = response
+        .json()
+        .await
+        .context("embedding endpoint returned invalid JSON")?;
+
+    let mut data: Vec<&Value> = body["data"].as_array().into_iter().flatten().collect();
+    data.sort_by_key(|item| item["index"].as_u64().unwrap_or_default());
+    let embeddings: Vec<Vec<f64>> = data
+        .into_iter()
+        .filter_map(|item| serde_json::from_value(item["embedding"].clone()).ok())
+        .collect();
+    if embeddings.len() != count {
+        bail!(
+            "embedding endpoint returned {} embeddings for {} inputs",
+            embeddings.len(),
+            count
+        );
+    }
+    Ok(embeddings)
+}
+
+fn vector_schema() -> Value {
+    json!({ "type": "array", "items": { "type": "number" }, "minItems": 1, "maxItems": 8192 })
+}
+
+/// Stores documents in the user's vector collection
+#[mcp_tool]
+pub struct VectorUpsertTool;
+
+impl McpTool for VectorUpsertTool {
+    fn name(&self) -> &'static str {
+        "vector_upsert"
+    }
+
+    fn description(&self) -> &'static str {
+        "Stores documents for later retrieval with vector_query. Each point has an id and a \
+         vector, or a text to embed on the server; upserting an existing id replaces it."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "points": {
+                    "type": "array",
+                    "minItems": 1,
+                    "maxItems": MAX_POINTS,
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "id": { "type": "string", "minLength": 1, "maxLength": 256 },
+                            "text": { "type": "string" },
+                            "vector": vector_schema(),
+                            "metadata": { "type": "object" }
+                        },
+                        "required": ["id"],
+                        "additionalProperties": false
+                    }
+                }
+            },
+            "required": ["points"],
+            "additionalProperties": false
+        })
+    }
+
+    fn annotations(&self) -> Option<Value> {
+        Some(json!({ "destructiveHint": true, "idempotentHint": true }))
+    }
+
+    fn execute(
+        &self,
+        args: Option<Value>,
+        user: AuthenticatedUser,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        self.execute_with_context(args, ToolContext::new(user))
+    }
+
+    fn execute_with_context(
+        &self,
+        args: Option<Value>,
+        ctx: ToolContext,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        let schema = self.parameters_schema();
+
+        Box::pin(async move {
+            validate_tool_args(&schema, &args)?;
+            let args = args.unwrap_or_default();
+            let config = &ctx.config().vectors;
+            let points = args["points"].as_array().cloned().unwrap_or_default();
+
+            // Texts without a vector are embedded in a single request
+            let mut to_embed = Vec::new();
+            for (index, point) in points.iter().enumerate() {
+                match (point["vector"].is_array(), point["text"].as_str()) {
+                    (true, _) => {}
+                    (false, Some(text)) => to_embed.push(text.to_string()),
+                    (false, None) => {
+                        return Err(InvalidInput::new(format!(
+                            "point {} needs a vector or a text",
+                            index
+                        ))
+                        .with_data(json!({ "index": index }))
+                        .into());
+                    }
+                }
+            }
+            let mut embeddings = if to_embed.is_empty() {
+                Vec::new()
+            } else {
+                embed(&ctx, config, to_embed).await?
+            }
+            .into_iter();
+
+            let mut rows: Vec<Point> = Vec::with_capacity(points.len());
+            for (index, point) in points.into_iter().enumerate() {
+                let vector: Vec<f64> = match serde_json::from_value(point["vector"].clone()) {
+                    Ok(vector) => vector,
+                    Err(_) => embeddings.next().unwrap_or_default(),
+                };
+                if let Some(first) = rows.first()
+                    && first.vector.len() != vector.len()
+                {
+                    return Err(InvalidInput::new(format!(
+                        "point {} has {} dimensions, expected {}",
+                        index,
+                        vector.len(),
+                        first.vector.len()
+                    ))
+                    .with_data(json!({ "index": index }))
+                    .into());
+                }
+                rows.push(Point {
+                    id: point["id"].as_str().unwrap_or_default().to_string(),
+                    vector,
+                    text: point["text"].as_str().map(str::to_string),
+                    metadata: point.get("metadata").cloned().unwrap_or(json!({})),
+                });
+            }
+
+            let upserted = rows.len();
+            let store = VectorStore::for_user(&ctx, config)?;
+            store.upsert(rows, config.distance.as_str()).await?;
+            Ok(json!({ "collection": config.collection, "upserted": upserted }))
+        })
+    }
+}
+
+/// Nearest-neighbour search in the user's vector collection
+#[mcp_tool]
+pub struct VectorQueryTool;
+
+impl McpTool for VectorQueryTool {
+    fn name(&self) -> &'static str {
+        "vector_query"
+    }
+
+    fn description(&self) -> &'static str {
+        "Finds the stored documents closest to a text or a vector, optionally restricted to \
+         exact metadata values."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "text": { "type": "string", "minLength": 1 },
+                "vector": vector_schema(),
+                "limit": { "type": "integer", "minimum": 1, "maximum": 100 },
+                "filter": {
+                    "type": "object",
+                    "description": "Metadata fields that must equal the given values",
+                    "additionalProperties": { "type": ["string", "number", "boolean"] }
+                }
+            },
+            "additionalProperties": false
+        })
+    }
+
+    fn annotations(&self) -> Option<Value> {
+        Some(json!({ "readOnlyHint": true }))
+    }
+
+    fn execute(
+        &self,
+        args: Option<Value>,
+        user: AuthenticatedUser,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        self.execute_with_context(args, ToolContext::new(user))
+    }
+
+    fn execute_with_context(
+        &self,
+        args: Option<Value>,
+        ctx: ToolContext,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        let schema = self.parameters_schema();
+
+        Box::pin(async move {
+            validate_tool_args(&schema, &args)?;
+            let args = args.unwrap_or_default();
+            let config = &ctx.config().vectors;
+            let limit = args["limit"]
+                .as_u64()
+                .map_or(config.default_results, |limit| limit as usize)
+                .min(config.max_results)
+                .max(1);
+            let filter = args["filter"].as_object().cloned().unwrap_or_default();
+
+            let store = VectorStore::for_user(&ctx, config)?;
+            let vector = match (&args["vector"], args["text"].as_str()) {
+                (Value::Array(_), None) => serde_json::from_value(args["vector"].clone())?,
+                (Value::Null, Some(text)) => embed(&ctx, config, vec![text.to_string()])
+                    .await?
+                    .pop()
+                    .unwrap_or_default(),
+                _ => {
+                    return Err(InvalidInput::new("pass exactly one of text or vector").into());
+                }
+            };
+
+            let results = store.search(vector, limit, &filter).await?;
+            Ok(json!({ "collection": config.collection, "results": results }))
+        })
+    }
+}
@@ -0,0 +1,294 @@
+use super::{InvalidInput, McpTool, PinBoxedFuture, ToolContext, mcp_tool, validate_tool_args};
+use crate::auth::{AuthenticatedUser, ExposeSecret};
+use anyhow::{Context, Error, Result, bail};
+use chrono::DateTime;
+use reqwest::StatusCode;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+const DEFAULT_ENDPOINT: &str = "https://api.openweathermap.org/data/2.5/weather";
+
+/// Conditions fetched by any user, keyed by location
+static CACHE: LazyLock<WeatherCache> = LazyLock::new(WeatherCache::default);
+
+/// Recent OpenWeatherMap responses, in metric units
+///
+/// The TTL and capacity are passed on each call so that a configuration
+/// reload applies to entries already cached.
+#[derive(Debug, Default)]
+pub struct WeatherCache {
+    entries: Mutex<HashMap<String, (Value, Instant)>>,
+}
+
+impl WeatherCache {
+    /// Response cached for `key` less than `ttl` ago
+    pub fn get(&self, key: &str, ttl: Duration) -> Option<Value> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        match entries.get(key) {
+            Some((value, fetched)) if fetched.elapsed() < ttl => Some(value.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Cache a response, evicting the oldest entry when `max_entries` are held
+    pub fn insert(&self, key: String, value: Value, max_entries: usize) {
+        if max_entries == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries.len() >= max_entries
+            && !entries.contains_key(&key)
+            && let Some(oldest) = entries
+                .iter()
+                .min_by_key(|(_, (_, fetched))| *fetched)
+                .map(|(key, _)| key.clone())
+        {
+            entries.remove(&oldest);
+        }
+        entries.insert(key, (value, Instant::now()));
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Temperature in `unit` (`celsius`, `fahrenheit` or `kelvin`) of a Celsius value
+pub fn convert_temperature(celsius: f64, unit: &str) -> f64 {
+    let value = match unit {
+        "fahrenheit" => celsius * 9.0 / 5.0 + 32.0,
+        "kelvin" => celsius + 273.15,
+        _ => celsius,
+    };
+    round(value)
+}
+
+/// Speed in `unit` (`mps`, `kmh`, `mph` or `knots`) of a value in metres per second
+pub fn convert_speed(mps: f64, unit: &str) -> f64 {
+    let value = match unit {
+        "kmh" => mps * 3.6,
+        "mph" => mps * 2.236_936,
+        "knots" => mps * 1.943_844,
+        _ => mps,
+    };
+    round(value)
+}
+
+fn round(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+/// Where the caller asked for the weather
+enum Location {
+    Name(String),
+    Coordinates(f64, f64),
+}
+
+impl Location {
+    fn from_args(args: &Value) -> Result<Self, InvalidInput> {
+        match (
+            args["location"].as_str(),
+            args["latitude"].as_f64(),
+            args["longitude"].as_f64(),
+        ) {
+            (Some(name), None, None) => Ok(Self::Name(name.trim().to_string())),
+            (None, Some(latitude), Some(longitude)) => Ok(Self::Coordinates(latitude, longitude)),
+            _ => Err(InvalidInput::new(
+                "pass either location or both latitude and longitude",
+            )),
+        }
+    }
+
+    fn cache_key(&self) -> String {
+        match self {
+            Self::Name(name) => format!("q:{}", name.to_lowercase()),
+            // ~100 m apart report the same weather
+            Self::Coordinates(latitude, longitude) => {
+                format!("coord:{:.3},{:.3}", latitude, longitude)
+            }
+        }
+    }
+
+    fn query(&self) -> Vec<(&'static str, String)> {
+        match self {
+            Self::Name(name) => vec![("q", name.clone())],
+            Self::Coordinates(latitude, longitude) => vec![
+                ("lat", latitude.to_string()),
+                ("lon", longitude.to_string()),
+            ],
+        }
+    }
+}
+
+/// Current weather for a location from OpenWeatherMap
+///
+/// The API key is read from the caller's `openweather_api_key` external key.
+/// Responses are fetched in metric units and cached per location for
+/// `[weather] cache_ttl_secs`; the requested units are applied afterwards, so
+/// every unit combination shares one cache entry.
+#[mcp_tool]
+pub struct GetWeatherTool;
+
+impl McpTool for GetWeatherTool {
+    fn name(&self) -> &'static str {
+        "get_weather"
+    }
+
+    fn description(&self) -> &'static str {
+        "Returns the current weather for a city (e.g. \"Paris,FR\") or coordinates: conditions, \
+         temperature, humidity and wind, in the requested units."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "location": {
+                    "type": "string",
+                    "minLength": 1,
+                    "maxLength": 200,
+                    "description": "City name, optionally with a country code"
+                },
+                "latitude": { "type": "number", "minimum": -90, "maximum": 90 },
+                "longitude": { "type": "number", "minimum": -180, "maximum": 180 },
+                "temperature_unit": {
+                    "type": "string",
+                    "enum": ["celsius", "fahrenheit", "kelvin"],
+                    "description": "Default celsius"
+                },
+                "wind_speed_unit": {
+                    "type": "string",
+                    "enum": ["mps", "kmh", "mph", "knots"],
+                    "description": "Default mps (metres per second)"
+                }
+            },
+            "additionalProperties": false
+        })
+    }
+
+    fn output_schema(&self) -> Option<Value> {
+        Some(json!({
+            "type": "object",
+            "properties": {
+                "location": { "type": "string" },
+                "country": { "type": ["string", "null"] },
+                "latitude": { "type": "number" },
+                "longitude": { "type": "number" },
+                "conditions": { "type": "string" },
+                "temperature": { "type": "number" },
+                "feels_like": { "type": "number" },
+                "humidity_percent": { "type": "number" },
+                "pressure_hpa": { "type": "number" },
+                "wind_speed": { "type": "number" },
+                "wind_direction_deg": { "type": ["number", "null"] },
+                "units": { "type": "object" },
+                "observed_at": { "type": ["string", "null"], "format": "date-time" },
+                "cached": { "type": "boolean" }
+            },
+            "required": ["location", "conditions", "temperature", "units", "cached"]
+        }))
+    }
+
+    fn annotations(&self) -> Option<Value> {
+        Some(json!({ "readOnlyHint": true, "openWorldHint": true }))
+    }
+
+    fn execute(
+        &self,
+        args: Option<Value>,
+        user: AuthenticatedUser,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        self.execute_with_context(args, ToolContext::new(user))
+    }
+
+    fn execute_with_context(
+        &self,
+        args: Option<Value>,
+        ctx: ToolContext,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        let schema = self.parameters_schema();
+
+        Box::pin(async move {
+            validate_tool_args(&schema, &args)?;
+            let args = args.unwrap_or_default();
+            let config = &ctx.config().weather;
+            let location = Location::from_args(&args)?;
+            let temperature_unit = args["temperature_unit"].as_str().unwrap_or("celsius");
+            let wind_speed_unit = args["wind_speed_unit"].as_str().unwrap_or("mps");
+            // Required even when the answer is cached
+            let api_key = ctx.user().external_secret("openweather_api_key")?;
+
+            let key = location.cache_key();
+            let ttl = Duration::from_secs(config.cache_ttl_secs);
+            let (body, cached) = match CACHE.get(&key, ttl) {
+                Some(body) => (body, true),
+                None => {
+                    let response = ctx
+                        .http()
+                        .get(config.endpoint.as_deref().unwrap_or(DEFAULT_ENDPOINT))?
+                        .query(&location.query())
+                        .query(&[("units", "metric"), ("appid", api_key.expose_secret())])
+                        .timeout(Duration::from_millis(config.timeout_ms))
+                        .send()
+                        .await
+                        .context("weather request failed")?;
+                    let status = response.status();
+                    if status == StatusCode::NOT_FOUND {
+                        return Err(InvalidInput::new("location not found").into());
+                    }
+                    if !status.is_success() {
+                        bail!("weather API returned HTTP {}", status);
+                    }
+                    let body: Value = response
+                        .json()
+                        .await
+                        .context("weather API returned invalid JSON")?;
+                    if !ttl.is_zero() {
+                        CACHE.insert(key, body.clone(), config.cache_max_entries);
+                    }
+                    (body, false)
+                }
+            };
+
+            let main = &body["main"];
+            let celsius = |value: &Value| {
+                value
+                    .as_f64()
+                    .map(|c| convert_temperature(c, temperature_unit))
+            };
+            Ok(json!({
+                "location": body["name"],
+                "country": body["sys"]["country"],
+                "latitude": body["coord"]["lat"],
+                "longitude": body["coord"]["lon"],
+                "conditions": body["weather"][0]["description"].as_str().unwrap_or_default(),
+                "temperature": celsius(&main["temp"]),
+                "feels_like": celsius(&main["feels_like"]),
+                "temperature_min": celsius(&main["temp_min"]),
+                "temperature_max": celsius(&main["temp_max"]),
+                "humidity_percent": main["humidity"],
+                "pressure_hpa": main["pressure"],
+                "wind_speed": body["wind"]["speed"]
+                    .as_f64()
+                    .map(|speed| convert_speed(speed, wind_speed_unit)),
+                "wind_direction_deg": body["wind"]["deg"],
+                "units": { "temperature": temperature_unit, "wind_speed": wind_speed_unit },
+                "observed_at": body["dt"]
+                    .as_i64()
+                    .and_then(|dt| DateTime::from_timestamp(dt, 0))
+                    .map(|time| time.to_rfc3339()),
+                "cached": cached,
+            }))
+        })
+    }
+}
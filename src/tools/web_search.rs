@@ -0,0 +1,216 @@
+use super::{McpTool, PinBoxedFuture, ToolContext, mcp_tool, validate_tool_args};
+use crate::auth::{AuthenticatedUser, ExposeSecret};
+use crate::config::SearchProvider;
+use crate::http_client::HttpClient;
+use anyhow::{Context, Error, Result, bail};
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::time::Duration;
+
+/// Searches the web through the provider selected in `[web_search]`
+///
+/// The provider's API key is read from the caller's external keys, so usage
+/// is billed to (and limited by) each user's own subscription.
+#[mcp_tool]
+pub struct WebSearchTool;
+
+/// A search hit, identical for every provider
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SearchResult {
+    pub title: String,
+    pub url: String,
+    pub snippet: String,
+}
+
+impl SearchProvider {
+    /// Label reported in results and errors
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Brave => "brave",
+            Self::SerpApi => "serpapi",
+            Self::Bing => "bing",
+        }
+    }
+
+    /// External key holding the user's API key for the provider
+    pub fn key_name(self) -> &'static str {
+        match self {
+            Self::Brave => "brave_api_key",
+            Self::SerpApi => "serpapi_key",
+            Self::Bing => "bing_api_key",
+        }
+    }
+
+    fn default_endpoint(self) -> &'static str {
+        match self {
+            Self::Brave => "https://api.search.brave.com/res/v1/web/search",
+            Self::SerpApi => "https://serpapi.com/search.json",
+            Self::Bing => "https://api.bing.microsoft.com/v7.0/search",
+        }
+    }
+
+    fn request(
+        self,
+        client: &HttpClient,
+        endpoint: &str,
+        api_key: &str,
+        query: &str,
+        count: usize,
+    ) -> Result<reqwest::RequestBuilder> {
+        let count = count.to_string();
+        let request = client.get(endpoint)?.header("Accept", "application/json");
+        Ok(match self {
+            Self::Brave => request
+                .header("X-Subscription-Token", api_key)
+                .query(&[("q", query), ("count", &count)]),
+            Self::SerpApi => request.query(&[
+                ("engine", "google"),
+                ("q", query),
+                ("num", &count),
+                ("api_key", api_key),
+            ]),
+            Self::Bing => request
+                .header("Ocp-Apim-Subscription-Key", api_key)
+                .query(&[("q", query), ("count", &count)]),
+        })
+    }
+
+    /// Extract the web results from a provider response
+    pub fn parse_results(self, body: &Value) -> Vec<SearchResult> {
+        let (items, title, url, snippet) = match self {
+            Self::Brave => (&body["web"]["results"], "title", "url", "description"),
+            Self::SerpApi => (&body["organic_results"], "title", "link", "snippet"),
+            Self::Bing => (&body["webPages"]["value"], "name", "url", "snippet"),
+        };
+        let field = |item: &Value, name: &str| item[name].as_str().unwrap_or_default().to_string();
+
+        items
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|item| item[url].is_string())
+            .map(|item| SearchResult {
+                title: field(item, title),
+                url: field(item, url),
+                snippet: field(item, snippet),
+            })
+            .collect()
+    }
+}
+
+impl McpTool for WebSearchTool {
+    fn name(&self) -> &'static str {
+        "web_search"
+    }
+
+    fn description(&self) -> &'static str {
+        "Searches the web and returns the top results with title, URL and snippet."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "minLength": 1,
+                    "maxLength": 400,
+                    "description": "Search terms"
+                },
+                "count": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 50,
+                    "description": "Number of results, capped by the server's [web_search] max_results"
+                }
+            },
+            "required": ["query"],
+            "additionalProperties": false
+        })
+    }
+
+    fn output_schema(&self) -> Option<Value> {
+        Some(json!({
+            "type": "object",
+            "properties": {
+                "provider": { "type": "string" },
+                "query": { "type": "string" },
+                "results": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "title": { "type": "string" },
+                            "url": { "type": "string" },
+                            "snippet": { "type": "string" }
+                        },
+                        "required": ["title", "url", "snippet"]
+                    }
+                }
+            },
+            "required": ["provider", "query", "results"]
+        }))
+    }
+
+    fn annotations(&self) -> Option<Value> {
+        Some(json!({ "readOnlyHint": true, "openWorldHint": true }))
+    }
+
+    fn execute(
+        &self,
+        args: Option<Value>,
+        user: AuthenticatedUser,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        self.execute_with_context(args, ToolContext::new(user))
+    }
+
+    fn execute_with_context(
+        &self,
+        args: Option<Value>,
+        ctx: ToolContext,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        let schema = self.parameters_schema();
+
+        Box::pin(async move {
+            validate_tool_args(&schema, &args)?;
+            let args = args.unwrap_or_default();
+            let query = args["query"].as_str().unwrap_or_default();
+            let config = &ctx.config().web_search;
+            let count = args["count"]
+                .as_u64()
+                .map_or(config.default_results, |count| count as usize)
+                .min(config.max_results)
+                .max(1);
+
+            let provider = config.provider;
+            let api_key = ctx.user().external_secret(provider.key_name())?;
+            let endpoint = config
+                .endpoint
+                .as_deref()
+                .unwrap_or(provider.default_endpoint());
+
+            let response = provider
+                .request(ctx.http(), endpoint, api_key.expose_secret(), query, count)?
+                .timeout(Duration::from_millis(config.timeout_ms))
+                .send()
+                .await
+                .with_context(|| format!("{} search request failed", provider.name()))?;
+            let status = response.status();
+            if !status.is_success() {
+                bail!("{} search returned HTTP {}", provider.name(), status);
+            }
+            let body: Value = response
+                .json()
+                .await
+                .with_context(|| format!("{} search returned invalid JSON", provider.name()))?;
+
+            let mut results = provider.parse_results(&body);
+            results.truncate(count);
+            Ok(json!({
+                "provider": provider.name(),
+                "query": query,
+                "results": results,
+            }))
+        })
+    }
+}
@@ -0,0 +1,102 @@
+use crate::store::SharedStore;
+use anyhow::{Result, bail};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Prefix of upload handles passed as tool arguments
+pub const UPLOAD_HANDLE_PREFIX: &str = "upload_";
+
+/// A file uploaded through `POST /uploads`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Upload {
+    pub handle: String,
+    pub filename: Option<String>,
+    pub mime_type: String,
+    pub data: Vec<u8>,
+}
+
+/// Upload as kept in the store, data base64-encoded
+#[derive(Serialize, Deserialize)]
+struct StoredUpload {
+    username: String,
+    filename: Option<String>,
+    mime_type: String,
+    data: String,
+}
+
+/// Uploaded files kept in the shared store until they expire
+#[derive(Clone, Default)]
+pub struct UploadStore {
+    store: SharedStore,
+    ttl: Option<Duration>,
+    max_bytes: usize,
+}
+
+impl UploadStore {
+    /// Uploads expire after `ttl` (zero keeps them) and are limited to `max_bytes` (0 = unlimited)
+    pub fn new(store: SharedStore, ttl: Duration, max_bytes: usize) -> Self {
+        Self {
+            store,
+            ttl: (!ttl.is_zero()).then_some(ttl),
+            max_bytes,
+        }
+    }
+
+    /// Maximum accepted upload size in bytes, 0 when unlimited
+    pub fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    /// Store a user's file and return its handle
+    pub async fn save(
+        &self,
+        username: &str,
+        filename: Option<String>,
+        mime_type: &str,
+        data: &[u8],
+    ) -> Result<String> {
+        if self.max_bytes > 0 && data.len() > self.max_bytes {
+            bail!("Upload is larger than the {} byte limit", self.max_bytes);
+        }
+
+        let handle = format!("{}{:032x}", UPLOAD_HANDLE_PREFIX, rand::random::<u128>());
+        let upload = StoredUpload {
+            username: username.to_string(),
+            filename,
+            mime_type: mime_type.to_string(),
+            data: STANDARD.encode(data),
+        };
+        self.store
+            .set(
+                &Self::key(&handle),
+                serde_json::to_string(&upload)?,
+                self.ttl,
+            )
+            .await?;
+        Ok(handle)
+    }
+
+    /// Load a user's upload; `None` when unknown, expired or owned by someone else
+    pub async fn load(&self, handle: &str, username: &str) -> Result<Option<Upload>> {
+        let Some(stored) = self.store.get(&Self::key(handle)).await? else {
+            return Ok(None);
+        };
+        let upload: StoredUpload = serde_json::from_str(&stored)?;
+        if upload.username != username {
+            return Ok(None);
+        }
+
+        Ok(Some(Upload {
+            handle: handle.to_string(),
+            filename: upload.filename,
+            mime_type: upload.mime_type,
+            data: STANDARD.decode(upload.data)?,
+        }))
+    }
+
+    fn key(handle: &str) -> String {
+        format!("upload:{}", handle)
+    }
+}
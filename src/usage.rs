@@ -0,0 +1,345 @@
+//! Per-user, per-tool usage reports
+//!
+//! Finished invocations are counted in memory per user, tool and hour. Every
+//! `[usage] flush_interval_secs` the counts are added to the snapshot table:
+//! in memory by default, or in a SQLite database (`[usage] path` or the
+//! shared `[database]`, `sqlite` feature) that survives restarts and can be
+//! shared by replicas.
+//! `GET /admin/usage` adds up the hours in a time range for billing and
+//! capacity planning.
+
+use crate::audit::AuditStatus;
+use crate::config::UsageConfig;
+use crate::database::Storage;
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::mem;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Length of the periods usage is kept for; reports are aligned to them
+pub const BUCKET_SECS: i64 = 3600;
+
+/// What one user did with one tool
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct UsageCounts {
+    pub invocations: u64,
+    pub succeeded: u64,
+    /// Failed, timed out or cancelled
+    pub failed: u64,
+    /// Refused before the tool ran (policies, budgets, full queue, ...)
+    pub rejected: u64,
+    /// Time spent on all invocations
+    pub duration_ms: u64,
+}
+
+impl UsageCounts {
+    fn record(&mut self, status: AuditStatus, duration_ms: u64) {
+        self.invocations += 1;
+        match status {
+            AuditStatus::Success => self.succeeded += 1,
+            AuditStatus::Rejected => self.rejected += 1,
+            AuditStatus::Error | AuditStatus::Cancelled | AuditStatus::TimedOut => self.failed += 1,
+        }
+        self.duration_ms += duration_ms;
+    }
+
+    pub fn add(&mut self, other: &Self) {
+        self.invocations += other.invocations;
+        self.succeeded += other.succeeded;
+        self.failed += other.failed;
+        self.rejected += other.rejected;
+        self.duration_ms += other.duration_ms;
+    }
+}
+
+/// Usage of one tool by one user over a report's time range
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UsageRow {
+    pub user: String,
+    pub tool: String,
+    #[serde(flatten)]
+    pub counts: UsageCounts,
+}
+
+/// Start of the hour, user and tool
+type BucketKey = (i64, String, String);
+
+/// Where flushed usage is kept
+enum Snapshots {
+    Memory(Mutex<BTreeMap<BucketKey, UsageCounts>>),
+    #[cfg(feature = "sqlite")]
+    Sqlite(Arc<Mutex<rusqlite::Connection>>),
+}
+
+/// Counts finished invocations and writes them out as hourly snapshots
+pub struct UsageRecorder {
+    /// Counted since the last flush
+    pending: Mutex<BTreeMap<BucketKey, UsageCounts>>,
+    snapshots: Snapshots,
+    retention: Option<Duration>,
+}
+
+impl Default for UsageRecorder {
+    fn default() -> Self {
+        Self::new(&UsageConfig::default(), &Storage::default())
+            .expect("in-memory usage needs no setup")
+    }
+}
+
+impl UsageRecorder {
+    /// Keep usage in the SQLite database at `[usage] path`, else in the
+    /// shared `storage` when enabled, else in memory
+    #[cfg_attr(not(feature = "sqlite"), allow(unused_variables))]
+    pub fn new(config: &UsageConfig, storage: &Storage) -> Result<Self> {
+        #[cfg(feature = "sqlite")]
+        let snapshots = match (&config.path, storage.connection()) {
+            (Some(path), _) => Snapshots::Sqlite(
+                Storage::open_path(path)?
+                    .connection()
+                    .expect("opened database has a connection"),
+            ),
+            (None, Some(connection)) => Snapshots::Sqlite(connection),
+            (None, None) => Snapshots::Memory(Mutex::default()),
+        };
+        #[cfg(not(feature = "sqlite"))]
+        let snapshots = match &config.path {
+            None => Snapshots::Memory(Mutex::default()),
+            Some(_) => {
+                anyhow::bail!("`[usage] path` requires building with the `sqlite` feature")
+            }
+        };
+        let retention = Duration::from_secs(config.retention_days * 24 * 60 * 60);
+        Ok(Self {
+            pending: Mutex::default(),
+            snapshots,
+            retention: (!retention.is_zero()).then_some(retention),
+        })
+    }
+
+    /// Count an invocation that finished at `finished_at`
+    pub fn record(
+        &self,
+        user: &str,
+        tool: &str,
+        finished_at: DateTime<Utc>,
+        status: AuditStatus,
+        duration_ms: u64,
+    ) {
+        let key = (bucket(finished_at), user.to_string(), tool.to_string());
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        pending.entry(key).or_default().record(status, duration_ms);
+    }
+
+    /// Add the pending counts to the snapshots and drop snapshots past
+    /// `[usage] retention_days`; counts that could not be written are kept
+    /// for the next flush
+    pub async fn flush(&self) -> Result<()> {
+        let pending = mem::take(&mut *self.pending.lock().unwrap_or_else(|e| e.into_inner()));
+        let expired_before = self.retention.map_or(i64::MIN, |retention| {
+            bucket(Utc::now()) - retention.as_secs() as i64
+        });
+        match &self.snapshots {
+            Snapshots::Memory(snapshots) => {
+                let mut snapshots = snapshots.lock().unwrap_or_else(|e| e.into_inner());
+                for (key, counts) in pending {
+                    snapshots.entry(key).or_default().add(&counts);
+                }
+                snapshots.retain(|(start, _, _), _| *start >= expired_before);
+                Ok(())
+            }
+            #[cfg(feature = "sqlite")]
+            Snapshots::Sqlite(connection) => {
+                let connection = connection.clone();
+                let rows = pending.clone();
+                let written = tokio::task::spawn_blocking(move || {
+                    let mut connection = connection.lock().unwrap_or_else(|e| e.into_inner());
+                    write_snapshots(&mut connection, &rows, expired_before)
+                })
+                .await
+                .map_err(anyhow::Error::from)
+                .and_then(|written| written.context("Failed to write usage snapshots"));
+                if written.is_err() {
+                    let mut current = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+                    for (key, counts) in pending {
+                        current.entry(key).or_default().add(&counts);
+                    }
+                }
+                written
+            }
+        }
+    }
+
+    /// Usage per user and tool in the hours starting within `[from, to)`,
+    /// optionally for one user, sorted by user and tool
+    pub async fn report(
+        &self,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        user: Option<&str>,
+    ) -> Result<Vec<UsageRow>> {
+        self.flush().await?;
+        let from = from.map_or(i64::MIN, |from| from.timestamp());
+        let to = to.map_or(i64::MAX, |to| to.timestamp());
+        match &self.snapshots {
+            Snapshots::Memory(snapshots) => {
+                let snapshots = snapshots.lock().unwrap_or_else(|e| e.into_inner());
+                let mut totals: BTreeMap<(&str, &str), UsageCounts> = BTreeMap::new();
+                for ((start, row_user, tool), counts) in snapshots.iter() {
+                    if (from..to).contains(start) && user.is_none_or(|user| user == row_user) {
+                        totals.entry((row_user, tool)).or_default().add(counts);
+                    }
+                }
+                Ok(totals
+                    .into_iter()
+                    .map(|((user, tool), counts)| UsageRow {
+                        user: user.to_string(),
+                        tool: tool.to_string(),
+                        counts,
+                    })
+                    .collect())
+            }
+            #[cfg(feature = "sqlite")]
+            Snapshots::Sqlite(connection) => {
+                let connection = connection.clone();
+                let user = user.map(str::to_string);
+                tokio::task::spawn_blocking(move || {
+                    let connection = connection.lock().unwrap_or_else(|e| e.into_inner());
+                    read_report(&connection, from, to, user.as_deref())
+                })
+                .await?
+                .context("Failed to read usage snapshots")
+            }
+        }
+    }
+
+    /// Periodically flush the pending counts in the background
+    pub fn spawn_flush(self: &Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let usage = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                if let Err(e) = usage.flush().await {
+                    tracing::warn!("Failed to flush usage: {:#}", e);
+                }
+            }
+        })
+    }
+}
+
+/// Start of the hour containing `time`, in seconds since the epoch
+fn bucket(time: DateTime<Utc>) -> i64 {
+    time.timestamp().div_euclid(BUCKET_SECS) * BUCKET_SECS
+}
+
+/// Parse a report bound: an RFC 3339 timestamp or a `YYYY-MM-DD` date,
+/// meaning its midnight UTC
+pub fn parse_time(value: &str) -> Result<DateTime<Utc>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|date| date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc())
+        .with_context(|| format!("'{}' is neither an RFC 3339 timestamp nor a date", value))
+}
+
+/// Rows of a report as CSV with a header line
+pub fn to_csv(rows: &[UsageRow]) -> String {
+    let mut out = String::from("user,tool,invocations,succeeded,failed,rejected,duration_ms\n");
+    for row in rows {
+        let counts = &row.counts;
+        let _ = writeln!(
+            out,
+            "{},{},{},{},{},{},{}",
+            csv_field(&row.user),
+            csv_field(&row.tool),
+            counts.invocations,
+            counts.succeeded,
+            counts.failed,
+            counts.rejected,
+            counts.duration_ms
+        );
+    }
+    out
+}
+
+/// Quote a field containing separators, quotes or line breaks
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Add counts to their hours, so replicas sharing the database add up
+#[cfg(feature = "sqlite")]
+fn write_snapshots(
+    connection: &mut rusqlite::Connection,
+    rows: &BTreeMap<BucketKey, UsageCounts>,
+    expired_before: i64,
+) -> rusqlite::Result<()> {
+    let tx = connection.transaction()?;
+    for ((start, user, tool), counts) in rows {
+        tx.execute(
+            "INSERT INTO usage
+                 (bucket, user, tool, invocations, succeeded, failed, rejected, duration_ms)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+             ON CONFLICT(bucket, user, tool) DO UPDATE SET
+                 invocations = invocations + excluded.invocations,
+                 succeeded = succeeded + excluded.succeeded,
+                 failed = failed + excluded.failed,
+                 rejected = rejected + excluded.rejected,
+                 duration_ms = duration_ms + excluded.duration_ms",
+            rusqlite::params![
+                start,
+                user,
+                tool,
+                counts.invocations,
+                counts.succeeded,
+                counts.failed,
+                counts.rejected,
+                counts.duration_ms
+            ],
+        )?;
+    }
+    tx.execute("DELETE FROM usage WHERE bucket < ?1", [expired_before])?;
+    tx.commit()
+}
+
+#[cfg(feature = "sqlite")]
+fn read_report(
+    connection: &rusqlite::Connection,
+    from: i64,
+    to: i64,
+    user: Option<&str>,
+) -> rusqlite::Result<Vec<UsageRow>> {
+    let mut statement = connection.prepare(
+        "SELECT user, tool, SUM(invocations), SUM(succeeded), SUM(failed), SUM(rejected),
+                SUM(duration_ms)
+         FROM usage
+         WHERE bucket >= ?1 AND bucket < ?2 AND (?3 IS NULL OR user = ?3)
+         GROUP BY user, tool
+         ORDER BY user, tool",
+    )?;
+    statement
+        .query_map(rusqlite::params![from, to, user], |row| {
+            Ok(UsageRow {
+                user: row.get(0)?,
+                tool: row.get(1)?,
+                counts: UsageCounts {
+                    invocations: row.get(2)?,
+                    succeeded: row.get(3)?,
+                    failed: row.get(4)?,
+                    rejected: row.get(5)?,
+                    duration_ms: row.get(6)?,
+                },
+            })
+        })?
+        .collect()
+}
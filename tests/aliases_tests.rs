@@ -0,0 +1,148 @@
+mod common;
+
+use axum_test::TestServer;
+use common::*;
+use mcp_server::aliases::{edit_distance, resolve, suggestions};
+use mcp_server::config::ServerConfig;
+use mcp_server::tenant::ToolSet;
+use mcp_server::tools::initialize_all_tools;
+use mcp_server::{AppOptions, ERROR_METHOD_NOT_FOUND, create_app_with_options};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+fn aliases() -> HashMap<String, String> {
+    HashMap::from([
+        ("current_time".to_string(), "get_current_time".to_string()),
+        ("calc".to_string(), "calculate".to_string()),
+        ("forecast".to_string(), "no_such_tool".to_string()),
+    ])
+}
+
+fn built_in_tools() -> ToolSet {
+    let (functions, definitions) = initialize_all_tools().unwrap();
+    ToolSet {
+        functions: Arc::new(functions),
+        definitions: Arc::new(definitions),
+    }
+}
+
+fn server() -> TestServer {
+    let mut config = ServerConfig::default();
+    config.tools.aliases = aliases();
+    let app =
+        create_app_with_options(create_test_credentials_store(), AppOptions::new(config)).unwrap();
+    TestServer::new(app).unwrap()
+}
+
+async fn invoke(server: &TestServer, tool_name: &str, arguments: Value) -> Value {
+    server
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .json(&json!({
+            "method": "invoke",
+            "params": {"tool_name": tool_name, "arguments": arguments}
+        }))
+        .await
+        .json()
+}
+
+// ============================================================================
+// Config Tests
+// ============================================================================
+
+#[test]
+fn test_aliases_config_parsed() {
+    assert!(ServerConfig::default().tools.aliases.is_empty());
+
+    let config: ServerConfig =
+        toml::from_str("[tools.aliases]\ncurrent_time = \"get_current_time\"\n").unwrap();
+    assert_eq!(config.tools.aliases["current_time"], "get_current_time");
+}
+
+// ============================================================================
+// Resolution Tests
+// ============================================================================
+
+#[test]
+fn test_alias_resolved_to_registered_tool() {
+    let tools = built_in_tools();
+    let resolve = |name: &str| resolve(&aliases(), &tools, name.to_string());
+
+    assert_eq!(resolve("current_time"), "get_current_time");
+    assert_eq!(resolve("get_current_time"), "get_current_time");
+    // Aliases of tools outside the set are left alone
+    assert_eq!(resolve("forecast"), "forecast");
+    assert_eq!(resolve("unknown"), "unknown");
+}
+
+#[test]
+fn test_registered_tool_takes_precedence_over_alias() {
+    let tools = built_in_tools();
+    let aliases = HashMap::from([("calculate".to_string(), "get_current_time".to_string())]);
+
+    assert_eq!(
+        resolve(&aliases, &tools, "calculate".to_string()),
+        "calculate"
+    );
+}
+
+// ============================================================================
+// Suggestion Tests
+// ============================================================================
+
+#[test]
+fn test_edit_distance() {
+    assert_eq!(edit_distance("", ""), 0);
+    assert_eq!(edit_distance("calculate", "calculate"), 0);
+    assert_eq!(edit_distance("calculat", "calculate"), 1);
+    assert_eq!(edit_distance("calcluate", "calculate"), 2);
+    assert_eq!(edit_distance("kitten", "sitting"), 3);
+    assert_eq!(edit_distance("", "abc"), 3);
+}
+
+#[test]
+fn test_suggestions_for_typos() {
+    let tools = built_in_tools();
+    let suggest = |name: &str| suggestions(&aliases(), &tools, name);
+
+    assert_eq!(suggest("get_curent_time"), vec!["get_current_time"]);
+    assert_eq!(suggest("Calculate"), vec!["calculate"]);
+    // Close to an alias, suggesting the tool it names
+    assert_eq!(suggest("current_tim"), vec!["get_current_time"]);
+    assert!(suggest("completely_different").is_empty());
+    assert!(suggest("forecasts").is_empty());
+}
+
+// ============================================================================
+// Invocation Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_invoke_through_alias() {
+    let server = server();
+
+    let body = invoke(&server, "calc", json!({"expression": "6 * 7"})).await;
+    assert_eq!(body["result"], json!({ "result": 42.0 }));
+
+    let body = invoke(&server, "current_time", json!({})).await;
+    assert!(body["result"].is_object(), "{}", body);
+}
+
+#[tokio::test]
+async fn test_unknown_tool_error_suggests_close_names() {
+    let server = server();
+
+    let body = invoke(&server, "calculat", json!({"expression": "6 * 7"})).await;
+    assert_eq!(body["error"]["code"], ERROR_METHOD_NOT_FOUND);
+    assert_eq!(
+        body["error"]["message"],
+        "Tool 'calculat' not found; did you mean 'calculate'?"
+    );
+    assert_eq!(body["error"]["data"]["suggestions"], json!(["calculate"]));
+    assert!(body["error"]["data"]["available_tools"].is_array());
+
+    let body = invoke(&server, "xyzzy", json!({})).await;
+    assert_eq!(body["error"]["message"], "Tool 'xyzzy' not found");
+    assert_eq!(body["error"]["data"]["suggestions"], json!([]));
+}
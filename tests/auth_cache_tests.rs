@@ -0,0 +1,278 @@
+mod common;
+
+use anyhow::anyhow;
+use axum_test::TestServer;
+use common::*;
+use mcp_server::auth::{
+    AuthCache, AuthFuture, AuthProvider, Authenticator, StaticAuthProvider, UserCredentials,
+};
+use mcp_server::config::{AuthConfig, ServerConfig};
+use mcp_server::{AppOptions, create_app, create_app_with_options};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::env;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+const ADMIN_API_KEY: &str = "admin-api-key-000";
+
+/// Provider that counts lookups, standing in for a database or JWKS backend
+#[derive(Clone, Default)]
+struct CountingProvider {
+    lookups: Arc<AtomicUsize>,
+    fail: bool,
+}
+
+impl AuthProvider for CountingProvider {
+    fn authenticate<'a>(&'a self, token: &'a str) -> AuthFuture<'a, Option<UserCredentials>> {
+        self.lookups.fetch_add(1, Ordering::SeqCst);
+        Box::pin(async move {
+            if self.fail {
+                return Err(anyhow!("backend down"));
+            }
+            Ok(match token {
+                TEST_API_KEY => Some(create_test_user()),
+                ADMIN_API_KEY => Some(
+                    UserCredentials::new(
+                        "admin".to_string(),
+                        ADMIN_API_KEY.to_string(),
+                        HashMap::new(),
+                    )
+                    .with_admin(true),
+                ),
+                _ => None,
+            })
+        })
+    }
+}
+
+fn cached_config() -> ServerConfig {
+    ServerConfig {
+        auth: AuthConfig {
+            cache_ttl_secs: 60,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+async fn discover(server: &TestServer, api_key: &str) -> axum_test::TestResponse {
+    server
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", api_key))
+        .json(&json!({"method": "discover"}))
+        .await
+}
+
+// ============================================================================
+// AuthCache Tests
+// ============================================================================
+
+#[test]
+fn test_default_cache_is_disabled() {
+    let cache = AuthCache::default();
+    cache.insert("token", create_test_user());
+
+    assert!(!cache.is_enabled());
+    assert!(cache.get("token").is_none());
+}
+
+#[test]
+fn test_cache_hit_and_invalidation() {
+    let cache = AuthCache::new(Duration::from_secs(60), 10);
+    cache.insert("a", create_test_user());
+    cache.insert("b", create_test_user());
+
+    assert_eq!(cache.get("a").unwrap().username, TEST_USERNAME);
+
+    cache.invalidate("a");
+    assert!(cache.get("a").is_none());
+    assert!(cache.get("b").is_some());
+
+    cache.invalidate_all();
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn test_cache_entries_expire() {
+    let cache = AuthCache::new(Duration::from_millis(10), 10);
+    cache.insert("a", create_test_user());
+    std::thread::sleep(Duration::from_millis(30));
+
+    assert!(cache.get("a").is_none());
+}
+
+#[test]
+fn test_cache_is_bounded() {
+    let cache = AuthCache::new(Duration::from_secs(60), 2);
+    for token in ["a", "b", "c"] {
+        cache.insert(token, create_test_user());
+    }
+
+    assert_eq!(cache.len(), 2);
+    assert!(cache.get("c").is_some());
+}
+
+// ============================================================================
+// Authenticator Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_authenticator_caches_successful_lookups() {
+    let provider = CountingProvider::default();
+    let authenticator = Authenticator::new(
+        Arc::new(provider.clone()),
+        Arc::new(AuthCache::new(Duration::from_secs(60), 10)),
+    );
+
+    for _ in 0..3 {
+        assert!(
+            authenticator
+                .authenticate(TEST_API_KEY)
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+    assert_eq!(provider.lookups.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_authenticator_does_not_cache_failures() {
+    let provider = CountingProvider::default();
+    let authenticator = Authenticator::new(
+        Arc::new(provider.clone()),
+        Arc::new(AuthCache::new(Duration::from_secs(60), 10)),
+    );
+
+    for _ in 0..3 {
+        assert!(authenticator.authenticate("bogus").await.unwrap().is_none());
+    }
+    assert_eq!(provider.lookups.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_static_provider_replace() {
+    let provider = StaticAuthProvider::new(create_test_credentials_store());
+    assert!(provider.authenticate(TEST_API_KEY).await.unwrap().is_some());
+
+    provider.replace(Arc::new(HashMap::new()));
+    assert!(provider.authenticate(TEST_API_KEY).await.unwrap().is_none());
+}
+
+// ============================================================================
+// Integration Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_custom_provider_is_cached_per_token() {
+    let provider = CountingProvider::default();
+    let options = AppOptions::new(cached_config()).with_auth_provider(provider.clone());
+    let server =
+        TestServer::new(create_app_with_options(Arc::new(HashMap::new()), options).unwrap())
+            .unwrap();
+
+    for _ in 0..3 {
+        discover(&server, TEST_API_KEY).await.assert_status_ok();
+    }
+    assert_eq!(provider.lookups.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_provider_failure_returns_503() {
+    let provider = CountingProvider {
+        fail: true,
+        ..Default::default()
+    };
+    let options = AppOptions::new(cached_config()).with_auth_provider(provider);
+    let server =
+        TestServer::new(create_app_with_options(Arc::new(HashMap::new()), options).unwrap())
+            .unwrap();
+
+    discover(&server, TEST_API_KEY)
+        .await
+        .assert_status_service_unavailable();
+}
+
+#[tokio::test]
+async fn test_reload_invalidates_cache_for_custom_provider() {
+    let provider = CountingProvider::default();
+    let options = AppOptions::new(cached_config()).with_auth_provider(provider.clone());
+    let server =
+        TestServer::new(create_app_with_options(Arc::new(HashMap::new()), options).unwrap())
+            .unwrap();
+
+    discover(&server, TEST_API_KEY).await.assert_status_ok();
+
+    let body: Value = server
+        .post("/admin/credentials/reload")
+        .add_header("Authorization", format!("Bearer {}", ADMIN_API_KEY))
+        .await
+        .json();
+    assert_eq!(body["result"]["reloaded"], false);
+    assert_eq!(body["result"]["cache_invalidated"], true);
+
+    discover(&server, TEST_API_KEY).await.assert_status_ok();
+    // TEST_API_KEY twice (before and after invalidation) plus the admin key
+    assert_eq!(provider.lookups.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_reload_requires_admin() {
+    let server = TestServer::new(create_app(create_test_credentials_store()).unwrap()).unwrap();
+
+    server
+        .post("/admin/credentials/reload")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .await
+        .assert_status_forbidden();
+}
+
+#[tokio::test]
+async fn test_reload_picks_up_new_credentials_file() {
+    let file = create_temp_credentials_file_with_suffix(
+        ".toml",
+        &format!(
+            "[admin]\napi_key = \"{}\"\nadmin = true\n\n[carol]\napi_key = \"carol-key-123\"\n",
+            ADMIN_API_KEY
+        ),
+    )
+    .expect("Failed to create temp file");
+
+    let mut initial = HashMap::new();
+    initial.insert(
+        ADMIN_API_KEY.to_string(),
+        UserCredentials::new(
+            "admin".to_string(),
+            ADMIN_API_KEY.to_string(),
+            HashMap::new(),
+        )
+        .with_admin(true),
+    );
+    let app = create_app_with_options(Arc::new(initial), AppOptions::new(cached_config())).unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    discover(&server, "carol-key-123")
+        .await
+        .assert_status_unauthorized();
+
+    let body: Value = {
+        // The only test in this binary touching MCP_CREDENTIALS_PATH
+        unsafe {
+            env::set_var("MCP_CREDENTIALS_PATH", file.path());
+        }
+        let response = server
+            .post("/admin/credentials/reload")
+            .add_header("Authorization", format!("Bearer {}", ADMIN_API_KEY))
+            .await;
+        unsafe {
+            env::remove_var("MCP_CREDENTIALS_PATH");
+        }
+        response.json()
+    };
+    assert_eq!(body["result"]["reloaded"], true);
+    assert_eq!(body["result"]["users"], 2);
+
+    discover(&server, "carol-key-123").await.assert_status_ok();
+}
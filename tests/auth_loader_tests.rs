@@ -1,7 +1,9 @@
 mod common;
 
 use common::*;
-use mcp_server::auth::{get_credentials_path, load_credentials};
+use mcp_server::auth::{
+    Budget, CredentialsFormat, get_credentials_path, is_age_encrypted, load_credentials,
+};
 use std::env;
 use std::sync::Mutex;
 
@@ -244,3 +246,528 @@ fn test_load_credentials_preserves_usernames() {
     // Cleanup
     unsafe { env::remove_var("MCP_CREDENTIALS_PATH"); }
 }
+
+// ============================================================================
+// Alternative Format Tests (YAML / JSON)
+// ============================================================================
+
+#[test]
+fn test_credentials_format_from_path() {
+    assert_eq!(CredentialsFormat::from_path("creds.toml"), CredentialsFormat::Toml);
+    assert_eq!(CredentialsFormat::from_path("creds.yaml"), CredentialsFormat::Yaml);
+    assert_eq!(CredentialsFormat::from_path("creds.YML"), CredentialsFormat::Yaml);
+    assert_eq!(CredentialsFormat::from_path("creds.json"), CredentialsFormat::Json);
+    assert_eq!(CredentialsFormat::from_path("creds"), CredentialsFormat::Toml);
+}
+
+#[test]
+fn test_load_credentials_yaml_file() {
+    let _lock = ENV_MUTEX.lock().unwrap();
+
+    let file = create_temp_credentials_file_with_suffix(
+        ".yaml",
+        r#"alice:
+  api_key: alice-key-123
+bob:
+  api_key: bob-key-456
+  external_keys:
+    postgres_url: postgresql://localhost/bobdb
+"#,
+    )
+    .expect("Failed to create temp file");
+    unsafe { env::set_var("MCP_CREDENTIALS_PATH", file.path()); }
+
+    let store = load_credentials().expect("YAML credentials should load");
+    assert_eq!(store.len(), 2);
+    assert_eq!(store.get("alice-key-123").unwrap().username, "alice");
+    assert_eq!(
+        store.get("bob-key-456").unwrap().get_external_key("postgres_url"),
+        Some(&"postgresql://localhost/bobdb".to_string())
+    );
+
+    // Cleanup
+    unsafe { env::remove_var("MCP_CREDENTIALS_PATH"); }
+}
+
+#[test]
+fn test_load_credentials_json_file() {
+    let _lock = ENV_MUTEX.lock().unwrap();
+
+    let file = create_temp_credentials_file_with_suffix(
+        ".json",
+        r#"{
+  "alice": { "api_key": "alice-key-123" },
+  "bob": {
+    "api_key": "bob-key-456",
+    "external_keys": { "stripe_key": "sk_test_123" }
+  }
+}"#,
+    )
+    .expect("Failed to create temp file");
+    unsafe { env::set_var("MCP_CREDENTIALS_PATH", file.path()); }
+
+    let store = load_credentials().expect("JSON credentials should load");
+    assert_eq!(store.len(), 2);
+    assert_eq!(
+        store.get("bob-key-456").unwrap().get_external_key("stripe_key"),
+        Some(&"sk_test_123".to_string())
+    );
+
+    // Cleanup
+    unsafe { env::remove_var("MCP_CREDENTIALS_PATH"); }
+}
+
+#[test]
+fn test_load_credentials_invalid_json() {
+    let _lock = ENV_MUTEX.lock().unwrap();
+
+    let file = create_temp_credentials_file_with_suffix(".json", "{ not json")
+        .expect("Failed to create temp file");
+    unsafe { env::set_var("MCP_CREDENTIALS_PATH", file.path()); }
+
+    let err_msg = load_credentials().unwrap_err().to_string();
+    assert!(err_msg.contains("Failed to parse credentials file"));
+
+    // Cleanup
+    unsafe { env::remove_var("MCP_CREDENTIALS_PATH"); }
+}
+
+#[test]
+fn test_load_credentials_empty_yaml_file() {
+    let _lock = ENV_MUTEX.lock().unwrap();
+
+    let file = create_temp_credentials_file_with_suffix(".yml", "")
+        .expect("Failed to create temp file");
+    unsafe { env::set_var("MCP_CREDENTIALS_PATH", file.path()); }
+
+    let err_msg = load_credentials().unwrap_err().to_string();
+    assert!(err_msg.contains("No users found"));
+
+    // Cleanup
+    unsafe { env::remove_var("MCP_CREDENTIALS_PATH"); }
+}
+
+#[test]
+fn test_load_credentials_yaml_duplicate_api_keys() {
+    let _lock = ENV_MUTEX.lock().unwrap();
+
+    let file = create_temp_credentials_file_with_suffix(
+        ".yaml",
+        "alice:\n  api_key: same\nbob:\n  api_key: same\n",
+    )
+    .expect("Failed to create temp file");
+    unsafe { env::set_var("MCP_CREDENTIALS_PATH", file.path()); }
+
+    let err_msg = load_credentials().unwrap_err().to_string();
+    assert!(err_msg.contains("Duplicate API key"));
+
+    // Cleanup
+    unsafe { env::remove_var("MCP_CREDENTIALS_PATH"); }
+}
+
+// ============================================================================
+// Encrypted Credentials Tests (age)
+// ============================================================================
+
+const PLAINTEXT_CREDENTIALS: &str = r#"[alice]
+api_key = "alice-key-123"
+
+[alice.external_keys]
+postgres_url = "postgresql://localhost/alicedb"
+"#;
+
+fn age_secret_key(identity: &age::x25519::Identity) -> String {
+    use age::secrecy::ExposeSecret;
+    identity.to_string().expose_secret().to_string()
+}
+
+fn clear_age_env() {
+    unsafe {
+        env::remove_var("MCP_CREDENTIALS_PATH");
+        env::remove_var("MCP_CREDENTIALS_AGE_KEY");
+        env::remove_var("MCP_CREDENTIALS_AGE_KEY_FILE");
+    }
+}
+
+#[test]
+fn test_is_age_encrypted_detection() {
+    assert!(is_age_encrypted(b"age-encryption.org/v1\n-> X25519 abc"));
+    assert!(is_age_encrypted(b"\n-----BEGIN AGE ENCRYPTED FILE-----\nYWdl"));
+    assert!(!is_age_encrypted(PLAINTEXT_CREDENTIALS.as_bytes()));
+}
+
+#[test]
+fn test_load_credentials_age_armored_with_inline_key() {
+    let _lock = ENV_MUTEX.lock().unwrap();
+
+    let identity = age::x25519::Identity::generate();
+    let encrypted =
+        age::encrypt_and_armor(&identity.to_public(), PLAINTEXT_CREDENTIALS.as_bytes()).unwrap();
+    let file = create_temp_credentials_file_with_suffix(".toml.age", &encrypted)
+        .expect("Failed to create temp file");
+
+    unsafe {
+        env::set_var("MCP_CREDENTIALS_PATH", file.path());
+        env::set_var("MCP_CREDENTIALS_AGE_KEY", age_secret_key(&identity));
+    }
+
+    let store = load_credentials().expect("Encrypted credentials should load");
+    let alice = store.get("alice-key-123").unwrap();
+    assert_eq!(alice.username, "alice");
+    assert_eq!(
+        alice.get_external_key("postgres_url"),
+        Some(&"postgresql://localhost/alicedb".to_string())
+    );
+
+    clear_age_env();
+}
+
+#[test]
+fn test_load_credentials_age_binary_with_key_file() {
+    let _lock = ENV_MUTEX.lock().unwrap();
+
+    let identity = age::x25519::Identity::generate();
+    let encrypted = age::encrypt(&identity.to_public(), PLAINTEXT_CREDENTIALS.as_bytes()).unwrap();
+
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    std::io::Write::write_all(&mut file, &encrypted).unwrap();
+
+    let key_file = create_temp_credentials_file_with_suffix(
+        ".txt",
+        &format!("# created: today\n# public key: {}\n{}\n", identity.to_public(), age_secret_key(&identity)),
+    )
+    .unwrap();
+
+    unsafe {
+        env::set_var("MCP_CREDENTIALS_PATH", file.path());
+        env::set_var("MCP_CREDENTIALS_AGE_KEY_FILE", key_file.path());
+    }
+
+    let store = load_credentials().expect("Encrypted credentials should load");
+    assert!(store.contains_key("alice-key-123"));
+
+    clear_age_env();
+}
+
+#[test]
+fn test_load_credentials_age_without_key_fails() {
+    let _lock = ENV_MUTEX.lock().unwrap();
+
+    let identity = age::x25519::Identity::generate();
+    let encrypted =
+        age::encrypt_and_armor(&identity.to_public(), PLAINTEXT_CREDENTIALS.as_bytes()).unwrap();
+    let file = create_temp_credentials_file_with_suffix(".age", &encrypted).unwrap();
+
+    clear_age_env();
+    unsafe { env::set_var("MCP_CREDENTIALS_PATH", file.path()); }
+
+    let err = format!("{:#}", load_credentials().unwrap_err());
+    assert!(err.contains("Failed to decrypt credentials file"));
+    assert!(err.contains("MCP_CREDENTIALS_AGE_KEY"));
+
+    clear_age_env();
+}
+
+#[test]
+fn test_load_credentials_age_with_wrong_key_fails() {
+    let _lock = ENV_MUTEX.lock().unwrap();
+
+    let identity = age::x25519::Identity::generate();
+    let other = age::x25519::Identity::generate();
+    let encrypted =
+        age::encrypt_and_armor(&identity.to_public(), PLAINTEXT_CREDENTIALS.as_bytes()).unwrap();
+    let file = create_temp_credentials_file_with_suffix(".age", &encrypted).unwrap();
+
+    unsafe {
+        env::set_var("MCP_CREDENTIALS_PATH", file.path());
+        env::set_var("MCP_CREDENTIALS_AGE_KEY", age_secret_key(&other));
+    }
+
+    let err = load_credentials().unwrap_err().to_string();
+    assert!(err.contains("Failed to decrypt credentials file"));
+
+    clear_age_env();
+}
+
+#[test]
+fn test_load_credentials_sops_file_is_rejected() {
+    let _lock = ENV_MUTEX.lock().unwrap();
+
+    let file = create_temp_credentials_file_with_suffix(
+        ".yaml",
+        "alice:\n  api_key: ENC[AES256_GCM,data:abc,iv:def,tag:ghi,type:str]\nsops:\n  version: 3.8.1\n",
+    )
+    .unwrap();
+    unsafe { env::set_var("MCP_CREDENTIALS_PATH", file.path()); }
+
+    let err = load_credentials().unwrap_err().to_string();
+    assert!(err.contains("SOPS-encrypted"));
+
+    clear_age_env();
+}
+
+// ============================================================================
+// Directory Loading and Include Tests
+// ============================================================================
+
+fn write_file(dir: &std::path::Path, name: &str, contents: &str) {
+    std::fs::write(dir.join(name), contents).expect("Failed to write credentials file");
+}
+
+#[test]
+fn test_load_credentials_from_directory() {
+    let _lock = ENV_MUTEX.lock().unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    write_file(dir.path(), "alice.toml", "[alice]\napi_key = \"alice-key-123\"\n");
+    write_file(dir.path(), "bob.yaml", "bob:\n  api_key: bob-key-456\n");
+    write_file(dir.path(), "carol.json", r#"{"carol": {"api_key": "carol-key-789"}}"#);
+    // Non-credential files are ignored
+    write_file(dir.path(), "README.md", "# users");
+    write_file(dir.path(), "credentials.toml.example", "[x]\napi_key = \"x\"\n");
+
+    unsafe { env::set_var("MCP_CREDENTIALS_PATH", dir.path()); }
+
+    let store = load_credentials().expect("Directory credentials should load");
+    assert_eq!(store.len(), 3);
+    assert_eq!(store.get("alice-key-123").unwrap().username, "alice");
+    assert_eq!(store.get("bob-key-456").unwrap().username, "bob");
+    assert_eq!(store.get("carol-key-789").unwrap().username, "carol");
+
+    // Cleanup
+    unsafe { env::remove_var("MCP_CREDENTIALS_PATH"); }
+}
+
+#[test]
+fn test_load_credentials_empty_directory() {
+    let _lock = ENV_MUTEX.lock().unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    unsafe { env::set_var("MCP_CREDENTIALS_PATH", dir.path()); }
+
+    let err_msg = load_credentials().unwrap_err().to_string();
+    assert!(err_msg.contains("No users found"));
+
+    // Cleanup
+    unsafe { env::remove_var("MCP_CREDENTIALS_PATH"); }
+}
+
+#[test]
+fn test_load_credentials_directory_duplicate_username() {
+    let _lock = ENV_MUTEX.lock().unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    write_file(dir.path(), "a.toml", "[alice]\napi_key = \"key-1\"\n");
+    write_file(dir.path(), "b.toml", "[alice]\napi_key = \"key-2\"\n");
+    unsafe { env::set_var("MCP_CREDENTIALS_PATH", dir.path()); }
+
+    let err_msg = load_credentials().unwrap_err().to_string();
+    assert!(err_msg.contains("Duplicate user 'alice'"));
+    assert!(err_msg.contains("a.toml"));
+    assert!(err_msg.contains("b.toml"));
+
+    // Cleanup
+    unsafe { env::remove_var("MCP_CREDENTIALS_PATH"); }
+}
+
+#[test]
+fn test_load_credentials_directory_duplicate_api_key() {
+    let _lock = ENV_MUTEX.lock().unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    write_file(dir.path(), "alice.toml", "[alice]\napi_key = \"shared\"\n");
+    write_file(dir.path(), "bob.toml", "[bob]\napi_key = \"shared\"\n");
+    unsafe { env::set_var("MCP_CREDENTIALS_PATH", dir.path()); }
+
+    let err_msg = load_credentials().unwrap_err().to_string();
+    assert!(err_msg.contains("Duplicate API key"));
+
+    // Cleanup
+    unsafe { env::remove_var("MCP_CREDENTIALS_PATH"); }
+}
+
+#[test]
+fn test_load_credentials_with_include_directive() {
+    let _lock = ENV_MUTEX.lock().unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("users")).unwrap();
+    write_file(&dir.path().join("users"), "bob.toml", "[bob]\napi_key = \"bob-key-456\"\n");
+    write_file(dir.path(), "extra.yaml", "carol:\n  api_key: carol-key-789\n");
+    write_file(
+        dir.path(),
+        "main.toml",
+        "include = [\"users\", \"extra.yaml\"]\n\n[alice]\napi_key = \"alice-key-123\"\n",
+    );
+
+    unsafe { env::set_var("MCP_CREDENTIALS_PATH", dir.path().join("main.toml")); }
+
+    let store = load_credentials().expect("Included credentials should load");
+    assert_eq!(store.len(), 3);
+    assert!(store.contains_key("alice-key-123"));
+    assert!(store.contains_key("bob-key-456"));
+    assert!(store.contains_key("carol-key-789"));
+
+    // Cleanup
+    unsafe { env::remove_var("MCP_CREDENTIALS_PATH"); }
+}
+
+#[test]
+fn test_load_credentials_include_missing_file() {
+    let _lock = ENV_MUTEX.lock().unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    write_file(
+        dir.path(),
+        "main.toml",
+        "include = [\"missing.toml\"]\n\n[alice]\napi_key = \"alice-key-123\"\n",
+    );
+    unsafe { env::set_var("MCP_CREDENTIALS_PATH", dir.path().join("main.toml")); }
+
+    let err_msg = format!("{:#}", load_credentials().unwrap_err());
+    assert!(err_msg.contains("Failed to include 'missing.toml'"));
+
+    // Cleanup
+    unsafe { env::remove_var("MCP_CREDENTIALS_PATH"); }
+}
+
+#[test]
+fn test_load_credentials_include_cycle_detected() {
+    let _lock = ENV_MUTEX.lock().unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    write_file(dir.path(), "a.toml", "include = [\"b.toml\"]\n\n[alice]\napi_key = \"a\"\n");
+    write_file(dir.path(), "b.toml", "include = [\"a.toml\"]\n\n[bob]\napi_key = \"b\"\n");
+    unsafe { env::set_var("MCP_CREDENTIALS_PATH", dir.path().join("a.toml")); }
+
+    let err_msg = format!("{:#}", load_credentials().unwrap_err());
+    assert!(err_msg.contains("included more than once"));
+
+    // Cleanup
+    unsafe { env::remove_var("MCP_CREDENTIALS_PATH"); }
+}
+
+#[test]
+fn test_load_credentials_admin_flag() {
+    let _lock = ENV_MUTEX.lock().unwrap();
+
+    let file = create_temp_credentials_file_with_suffix(
+        ".toml",
+        "[root]\napi_key = \"root-key\"\nadmin = true\n\n[alice]\napi_key = \"alice-key-123\"\n",
+    )
+    .expect("Failed to create temp file");
+    unsafe { env::set_var("MCP_CREDENTIALS_PATH", file.path()); }
+
+    let store = load_credentials().unwrap();
+    assert!(store.get("root-key").unwrap().admin);
+    assert!(!store.get("alice-key-123").unwrap().admin);
+
+    // Cleanup
+    unsafe { env::remove_var("MCP_CREDENTIALS_PATH"); }
+}
+
+#[test]
+fn test_load_credentials_priority() {
+    let _lock = ENV_MUTEX.lock().unwrap();
+
+    let file = create_temp_credentials_file_with_suffix(
+        ".toml",
+        "[batch]\napi_key = \"batch-key\"\npriority = -5\n\n[alice]\napi_key = \"alice-key-123\"\n",
+    )
+    .expect("Failed to create temp file");
+    unsafe { env::set_var("MCP_CREDENTIALS_PATH", file.path()); }
+
+    let store = load_credentials().unwrap();
+    assert_eq!(store.get("batch-key").unwrap().priority, -5);
+    assert_eq!(store.get("alice-key-123").unwrap().priority, 0);
+
+    // Cleanup
+    unsafe { env::remove_var("MCP_CREDENTIALS_PATH"); }
+}
+
+#[test]
+fn test_load_credentials_tenant() {
+    let _lock = ENV_MUTEX.lock().unwrap();
+
+    let file = create_temp_credentials_file_with_suffix(
+        ".toml",
+        "[acme_user]\napi_key = \"acme-key\"\ntenant = \"acme\"\n\n[alice]\napi_key = \"alice-key-123\"\n",
+    )
+    .expect("Failed to create temp file");
+    unsafe { env::set_var("MCP_CREDENTIALS_PATH", file.path()); }
+
+    let store = load_credentials().unwrap();
+    assert_eq!(store.get("acme-key").unwrap().tenant.as_deref(), Some("acme"));
+    assert!(store.get("alice-key-123").unwrap().tenant.is_none());
+
+    // Cleanup
+    unsafe { env::remove_var("MCP_CREDENTIALS_PATH"); }
+}
+
+#[test]
+fn test_load_credentials_signing_secret() {
+    let _lock = ENV_MUTEX.lock().unwrap();
+
+    let file = create_temp_credentials_file_with_suffix(
+        ".toml",
+        "[signer]\napi_key = \"signer-key\"\nsigning_secret = \"s3cret\"\n\n[alice]\napi_key = \"alice-key-123\"\n",
+    )
+    .expect("Failed to create temp file");
+    unsafe { env::set_var("MCP_CREDENTIALS_PATH", file.path()); }
+
+    let store = load_credentials().unwrap();
+    assert_eq!(store.get("signer-key").unwrap().signing_secret.as_deref(), Some("s3cret"));
+    assert!(store.get("alice-key-123").unwrap().signing_secret.is_none());
+
+    // Cleanup
+    unsafe { env::remove_var("MCP_CREDENTIALS_PATH"); }
+}
+
+#[test]
+fn test_load_credentials_tool_arguments() {
+    let _lock = ENV_MUTEX.lock().unwrap();
+
+    let file = create_temp_credentials_file_with_suffix(
+        ".toml",
+        "[alice]\napi_key = \"alice-key-123\"\n\n[alice.tool_arguments.sql_query.defaults]\nlimit = 100\n\n[alice.tool_arguments.sql_query.forced]\ndatabase = \"analytics\"\n",
+    )
+    .expect("Failed to create temp file");
+    unsafe { env::set_var("MCP_CREDENTIALS_PATH", file.path()); }
+
+    let store = load_credentials().unwrap();
+    let arguments = &store.get("alice-key-123").unwrap().tool_arguments["sql_query"];
+    assert_eq!(arguments.defaults["limit"], 100);
+    assert_eq!(arguments.forced["database"], "analytics");
+
+    // Cleanup
+    unsafe { env::remove_var("MCP_CREDENTIALS_PATH"); }
+}
+
+#[test]
+fn test_load_credentials_budget() {
+    let _lock = ENV_MUTEX.lock().unwrap();
+
+    let file = create_temp_credentials_file_with_suffix(
+        ".toml",
+        "[alice]\napi_key = \"alice-key-123\"\n\n[alice.budget]\nlimit = 500\n\n[bob]\napi_key = \"bob-key-4567\"\n\n[bob.budget]\nlimit = 10\nwindow_secs = 0\n",
+    )
+    .expect("Failed to create temp file");
+    unsafe { env::set_var("MCP_CREDENTIALS_PATH", file.path()); }
+
+    let err = load_credentials().unwrap_err();
+    assert!(err.to_string().contains("Invalid budget for user 'bob'"));
+
+    std::fs::write(
+        file.path(),
+        "[alice]\napi_key = \"alice-key-123\"\n\n[alice.budget]\nlimit = 500\n",
+    )
+    .unwrap();
+    let store = load_credentials().unwrap();
+    assert_eq!(
+        store.get("alice-key-123").unwrap().budget,
+        Some(Budget { limit: 500, window_secs: 86_400 })
+    );
+
+    // Cleanup
+    unsafe { env::remove_var("MCP_CREDENTIALS_PATH"); }
+}
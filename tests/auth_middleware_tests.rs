@@ -1,7 +1,7 @@
 mod common;
 
 use common::*;
-use mcp_server::auth::{AuthError, AuthLayer, AuthenticatedUser};
+use mcp_server::auth::{AuthError, AuthLayer, AuthenticatedUser, TokenSources};
 use mcp_server::ERROR_AUTH;
 use axum::{
     body::Body,
@@ -371,3 +371,82 @@ async fn test_middleware_case_insensitive_authorization_header() {
     // Should succeed
     assert_eq!(response.status(), StatusCode::OK);
 }
+
+// ============================================================================
+// Alternative Token Source Tests
+// ============================================================================
+
+fn layer_with_token_sources() -> AuthLayer {
+    AuthLayer::new(create_test_credentials_store()).with_token_sources(TokenSources {
+        header: Some(axum::http::HeaderName::from_static("x-api-key")),
+        query_param: Some("access_token".to_string()),
+    })
+}
+
+async fn call_with(layer: &AuthLayer, request: Request<Body>) -> StatusCode {
+    let mut service = layer.layer(MockService::new(true));
+    service.ready().await.unwrap().call(request).await.unwrap().status()
+}
+
+#[tokio::test]
+async fn test_middleware_fallbacks_disabled_by_default() {
+    let layer = AuthLayer::new(create_test_credentials_store());
+
+    let request = Request::builder()
+        .uri(format!("/test?access_token={}", TEST_API_KEY))
+        .header("X-Api-Key", TEST_API_KEY)
+        .body(Body::empty())
+        .unwrap();
+
+    assert_eq!(call_with(&layer, request).await, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_middleware_accepts_api_key_header() {
+    let request = Request::builder()
+        .uri("/test")
+        .header("X-Api-Key", TEST_API_KEY)
+        .body(Body::empty())
+        .unwrap();
+
+    assert_eq!(call_with(&layer_with_token_sources(), request).await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_middleware_accepts_query_token() {
+    let request = Request::builder()
+        .uri(format!("/test?stream=1&access_token={}", TEST_API_KEY))
+        .body(Body::empty())
+        .unwrap();
+
+    assert_eq!(call_with(&layer_with_token_sources(), request).await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_middleware_invalid_fallback_token_rejected() {
+    let request = Request::builder()
+        .uri("/test?access_token=wrong")
+        .body(Body::empty())
+        .unwrap();
+
+    assert_eq!(
+        call_with(&layer_with_token_sources(), request).await,
+        StatusCode::UNAUTHORIZED
+    );
+}
+
+#[tokio::test]
+async fn test_middleware_authorization_header_takes_precedence() {
+    // A malformed Authorization header is not silently replaced by a fallback
+    let request = Request::builder()
+        .uri("/test")
+        .header("Authorization", "Basic abc")
+        .header("X-Api-Key", TEST_API_KEY)
+        .body(Body::empty())
+        .unwrap();
+
+    assert_eq!(
+        call_with(&layer_with_token_sources(), request).await,
+        StatusCode::UNAUTHORIZED
+    );
+}
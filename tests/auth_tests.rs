@@ -2,7 +2,7 @@ mod common;
 
 use common::*;
 use mcp_server::auth::{validate_api_key, AuthenticatedUser, UserCredentials};
-use mcp_server::auth::{CredentialsConfig, UserConfig};
+use mcp_server::auth::{CredentialsConfig, ExposeSecret, UserConfig};
 use std::collections::HashMap;
 
 #[test]
@@ -254,3 +254,43 @@ fn test_authenticated_user_clone() {
         cloned.credentials().username
     );
 }
+
+#[test]
+fn test_typed_external_key_accessors() {
+    let mut user = create_test_user_with_external_keys();
+    user.external_keys
+        .insert("smtp_port".to_string(), "587".to_string());
+    let user = AuthenticatedUser(user);
+
+    assert_eq!(user.get_external_key_parsed::<u16>("smtp_port").unwrap(), 587);
+    let url = user.postgres_url().unwrap();
+    assert_eq!(url.scheme(), "postgresql");
+    assert_eq!(url.path(), "/testdb");
+
+    let missing = user.get_external_key_parsed::<u16>("redis_port").unwrap_err();
+    assert_eq!(missing.to_string(), "User missing credential 'redis_port'");
+
+    // Parse errors name the key without echoing the secret
+    let invalid = user.external_url("stripe_key").unwrap_err().to_string();
+    assert!(invalid.starts_with("Invalid credential 'stripe_key'"));
+    assert!(!invalid.contains("sk_test_123"));
+}
+
+#[test]
+fn test_secrets_are_not_debug_printed() {
+    let user = AuthenticatedUser(
+        create_test_user_with_external_keys().with_signing_secret(Some("hmac-secret".to_string())),
+    );
+
+    let secret = user.external_secret("stripe_key").unwrap();
+    assert_eq!(secret.expose_secret(), "sk_test_123");
+    assert!(!format!("{:?}", secret).contains("sk_test_123"));
+    assert!(user.external_secret("missing").is_err());
+
+    let debug = format!("{:?}", user);
+    assert!(debug.contains(TEST_USERNAME));
+    assert!(debug.contains("stripe_key"));
+    for secret in [TEST_API_KEY, "sk_test_123", "postgresql://", "hmac-secret"] {
+        assert!(!debug.contains(secret), "{} leaked", secret);
+    }
+}
@@ -0,0 +1,177 @@
+mod common;
+
+use arc_swap::ArcSwap;
+use axum::extract::State;
+use axum::{Extension, Json};
+use common::*;
+use mcp_server::auth::{AuthenticatedUser, Budget, UserCredentials};
+use mcp_server::budget::Budgets;
+use mcp_server::tools::{ToolContext, ToolFunction};
+use mcp_server::{
+    AppState, ERROR_BUDGET_EXCEEDED, McpRequest, McpResponse, ToolDefinition, handle_mcp_request,
+};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const BUDGET: Budget = Budget {
+    limit: 5,
+    window_secs: 3600,
+};
+
+fn ok_tool() -> ToolFunction {
+    Box::new(|_args, _ctx: ToolContext| Box::pin(async { Ok(json!("ok")) }))
+}
+
+/// A state with `paid` (cost 3) and `free` (cost 0) tools, and `plain`
+/// declaring no cost
+fn test_state() -> AppState {
+    let definition = |name: &str, cost: Option<u64>| ToolDefinition {
+        name: name.to_string(),
+        parameters: json!({ "type": "object" }),
+        cost,
+        ..Default::default()
+    };
+    AppState {
+        tool_registry: Arc::new(ArcSwap::from_pointee(HashMap::from([
+            ("paid".to_string(), ok_tool()),
+            ("free".to_string(), ok_tool()),
+            ("plain".to_string(), ok_tool()),
+        ]))),
+        tool_definitions: Arc::new(ArcSwap::from_pointee(vec![
+            definition("paid", Some(3)),
+            definition("free", Some(0)),
+            definition("plain", None),
+        ])),
+        ..Default::default()
+    }
+}
+
+fn budgeted_user() -> UserCredentials {
+    create_test_user().with_budget(Some(BUDGET))
+}
+
+async fn send(state: &AppState, user: UserCredentials, request: Value) -> McpResponse {
+    let request: McpRequest = serde_json::from_value(request).unwrap();
+    let Json(response) = handle_mcp_request(
+        State(state.clone()),
+        Extension(AuthenticatedUser(user)),
+        Json(request),
+    )
+    .await;
+    response
+}
+
+async fn invoke(state: &AppState, user: UserCredentials, tool_name: &str) -> McpResponse {
+    send(
+        state,
+        user,
+        json!({ "method": "invoke", "params": { "tool_name": tool_name } }),
+    )
+    .await
+}
+
+// ============================================================================
+// Budgets Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_charge_until_limit() {
+    let budgets = Budgets::new(Default::default());
+
+    let usage = budgets.charge("alice", &BUDGET, 4).await.unwrap().unwrap();
+    assert_eq!((usage.used, usage.remaining), (4, 1));
+    assert!(usage.resets_in_secs <= 3600);
+
+    let refused = budgets.charge("alice", &BUDGET, 2).await.unwrap();
+    assert_eq!(refused.unwrap_err().used, 4);
+    assert_eq!(budgets.usage("alice", &BUDGET).await.unwrap().used, 4);
+
+    budgets.charge("alice", &BUDGET, 1).await.unwrap().unwrap();
+    assert_eq!(budgets.usage("alice", &BUDGET).await.unwrap().remaining, 0);
+    assert_eq!(budgets.usage("bob", &BUDGET).await.unwrap().used, 0);
+}
+
+// ============================================================================
+// Invocation Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_invocation_exceeding_budget_is_rejected() {
+    let state = test_state();
+
+    assert!(
+        invoke(&state, budgeted_user(), "paid")
+            .await
+            .error
+            .is_none()
+    );
+    let error = invoke(&state, budgeted_user(), "paid").await.error.unwrap();
+    assert_eq!(error.code, ERROR_BUDGET_EXCEEDED);
+    let data = error.data.unwrap();
+    assert_eq!(data["tool"], "paid");
+    assert_eq!(data["cost"], 3);
+    assert_eq!(data["usage"]["remaining"], 2);
+
+    // Cheaper and free tools still fit
+    assert!(
+        invoke(&state, budgeted_user(), "plain")
+            .await
+            .error
+            .is_none()
+    );
+    assert!(
+        invoke(&state, budgeted_user(), "free")
+            .await
+            .error
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn test_users_without_budget_are_not_limited() {
+    let state = test_state();
+    for _ in 0..3 {
+        assert!(
+            invoke(&state, create_test_user(), "paid")
+                .await
+                .error
+                .is_none()
+        );
+    }
+
+    let response = send(&state, create_test_user(), json!({ "method": "usage" })).await;
+    assert_eq!(response.result.unwrap(), json!({ "budget": null }));
+}
+
+#[tokio::test]
+async fn test_usage_reports_remaining_budget() {
+    let state = test_state();
+    invoke(&state, budgeted_user(), "paid").await;
+    invoke(&state, budgeted_user(), "plain").await;
+
+    let response = send(&state, budgeted_user(), json!({ "method": "usage" })).await;
+    let mut budget = response.result.unwrap()["budget"].take();
+    assert!(budget["resets_in_secs"].as_u64().unwrap() <= 3600);
+    budget.as_object_mut().unwrap().remove("resets_in_secs");
+    assert_eq!(
+        budget,
+        json!({ "limit": 5, "used": 4, "remaining": 1, "window_secs": 3600 })
+    );
+}
+
+#[test]
+fn test_declared_cost_is_discoverable() {
+    let definition = ToolDefinition {
+        name: "paid".to_string(),
+        cost: Some(3),
+        ..Default::default()
+    };
+    assert_eq!(serde_json::to_value(&definition).unwrap()["cost"], 3);
+    assert!(
+        serde_json::to_value(ToolDefinition::default())
+            .unwrap()
+            .get("cost")
+            .is_none()
+    );
+}
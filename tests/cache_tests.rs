@@ -0,0 +1,173 @@
+mod common;
+
+use mcp_server::pools::PoolManager;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+// ============================================================================
+// PoolManager Tests
+// ============================================================================
+
+async fn connect(pools: &PoolManager<usize>, url: &str, connects: &AtomicUsize) -> usize {
+    pools
+        .get_or_connect(url, || async {
+            Ok(connects.fetch_add(1, Ordering::SeqCst))
+        })
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_pool_reused_per_url() {
+    let pools = PoolManager::new(8, Duration::from_secs(60));
+    let connects = AtomicUsize::new(0);
+
+    assert_eq!(connect(&pools, "redis://a", &connects).await, 0);
+    assert_eq!(connect(&pools, "redis://a", &connects).await, 0);
+    assert_eq!(connect(&pools, "redis://b", &connects).await, 1);
+    assert_eq!(connects.load(Ordering::SeqCst), 2);
+    assert_eq!(pools.len(), 2);
+}
+
+#[tokio::test]
+async fn test_failed_connect_is_not_kept() {
+    let pools: PoolManager<usize> = PoolManager::new(8, Duration::from_secs(60));
+
+    let result = pools
+        .get_or_connect("redis://down", || async { anyhow::bail!("refused") })
+        .await;
+    assert!(result.is_err());
+    assert!(pools.is_empty());
+}
+
+#[tokio::test]
+async fn test_least_recently_used_pool_evicted_when_full() {
+    let pools = PoolManager::new(2, Duration::from_secs(60));
+    let connects = AtomicUsize::new(0);
+
+    connect(&pools, "redis://a", &connects).await;
+    connect(&pools, "redis://b", &connects).await;
+    // Touch `a` so `b` is the oldest
+    connect(&pools, "redis://a", &connects).await;
+    connect(&pools, "redis://c", &connects).await;
+    assert_eq!(pools.len(), 2);
+
+    assert_eq!(connect(&pools, "redis://a", &connects).await, 0);
+    assert_eq!(connect(&pools, "redis://b", &connects).await, 3);
+}
+
+#[tokio::test]
+async fn test_idle_pools_dropped() {
+    let pools = PoolManager::new(8, Duration::from_millis(20));
+    let connects = AtomicUsize::new(0);
+
+    connect(&pools, "redis://a", &connects).await;
+    tokio::time::sleep(Duration::from_millis(40)).await;
+    connect(&pools, "redis://b", &connects).await;
+    assert_eq!(pools.len(), 1);
+}
+
+// ============================================================================
+// Cache Tool Tests
+// ============================================================================
+
+#[cfg(feature = "redis")]
+mod tools {
+    use super::common::*;
+    use mcp_server::auth::{AuthenticatedUser, UserCredentials};
+    use mcp_server::config::{CacheConfig, ServerConfig};
+    use mcp_server::tools::cache::{CacheGetTool, CacheSetTool, namespaced_key, ttl_secs};
+    use mcp_server::tools::{InvalidInput, McpTool, ToolContext};
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    fn context(external_keys: HashMap<String, String>) -> ToolContext {
+        let mut config = ServerConfig::default();
+        config.cache.max_ttl_secs = 60;
+        config.cache.max_value_bytes = 8;
+        let user =
+            UserCredentials::new("alice".to_string(), TEST_API_KEY.to_string(), external_keys);
+        ToolContext::new(AuthenticatedUser(user)).with_config(Arc::new(config))
+    }
+
+    fn invalid_input(error: anyhow::Error) -> String {
+        error
+            .downcast_ref::<InvalidInput>()
+            .unwrap()
+            .message
+            .clone()
+    }
+
+    #[test]
+    fn test_keys_namespaced_per_user() {
+        let config = CacheConfig::default();
+        assert_eq!(
+            namespaced_key(&config, "alice", "session"),
+            "mcp-cache:alice:session"
+        );
+        assert_ne!(
+            namespaced_key(&config, "alice", "k"),
+            namespaced_key(&config, "bob", "k")
+        );
+    }
+
+    #[test]
+    fn test_ttl_defaults_and_bounds() {
+        let config = CacheConfig::default();
+        assert_eq!(ttl_secs(&config, None).unwrap(), 3600);
+        assert_eq!(ttl_secs(&config, Some(86400)).unwrap(), 86400);
+        assert_eq!(
+            ttl_secs(&config, Some(86401)).unwrap_err().message,
+            "ttl_secs must be between 1 and 86400"
+        );
+        assert!(ttl_secs(&config, Some(0)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_limits_checked_before_connecting() {
+        // No redis_url: reaching Redis would fail with a missing credential
+        let ctx = context(HashMap::new());
+
+        let error = CacheSetTool
+            .execute_with_context(
+                Some(json!({"key": "k", "value": "v", "ttl_secs": 61})),
+                ctx.clone(),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(invalid_input(error), "ttl_secs must be between 1 and 60");
+
+        let error = CacheSetTool
+            .execute_with_context(Some(json!({"key": "k", "value": "123456789"})), ctx.clone())
+            .await
+            .unwrap_err();
+        assert_eq!(
+            invalid_input(error),
+            "value exceeds maximum size of 8 bytes"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_requires_redis_url() {
+        let error = CacheGetTool
+            .execute_with_context(Some(json!({"key": "k"})), context(HashMap::new()))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("redis_url"));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_redis_url_not_echoed() {
+        let keys = HashMap::from([(
+            "redis_url".to_string(),
+            "nope://:hunter2@localhost".to_string(),
+        )]);
+        let error = CacheGetTool
+            .execute_with_context(Some(json!({"key": "k"})), context(keys))
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "Invalid credential 'redis_url'");
+        assert!(!format!("{:#}", error).contains("hunter2"));
+    }
+}
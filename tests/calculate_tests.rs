@@ -0,0 +1,114 @@
+mod common;
+
+use axum_test::TestServer;
+use common::*;
+use mcp_server::auth::AuthenticatedUser;
+use mcp_server::tools::calculate::{CalculateTool, evaluate};
+use mcp_server::tools::{InvalidInput, McpTool};
+use mcp_server::{AppOptions, AppState, ERROR_INVALID_PARAMS, mcp_router};
+use serde_json::{Map, Value, json};
+
+fn eval(expression: &str) -> Result<f64, InvalidInput> {
+    evaluate(expression, &Map::new())
+}
+
+fn eval_with(expression: &str, variables: Value) -> Result<f64, InvalidInput> {
+    evaluate(expression, variables.as_object().unwrap())
+}
+
+#[test]
+fn test_precedence_and_associativity() {
+    assert_eq!(eval("1 + 2 * 3").unwrap(), 7.0);
+    assert_eq!(eval("(1 + 2) * 3").unwrap(), 9.0);
+    assert_eq!(eval("10 - 4 - 3").unwrap(), 3.0);
+    assert_eq!(eval("2 ^ 3 ^ 2").unwrap(), 512.0);
+    assert_eq!(eval("-2 ^ 2").unwrap(), -4.0);
+    assert_eq!(eval("2 ^ -1").unwrap(), 0.5);
+    assert_eq!(eval("7 % 4 + --1").unwrap(), 4.0);
+    assert_eq!(eval("1.5e2 / .5").unwrap(), 300.0);
+}
+
+#[test]
+fn test_functions_constants_and_variables() {
+    assert_eq!(
+        eval("sqrt(16) + abs(-2) + max(1, 5, 3) - min(4, 2)").unwrap(),
+        9.0
+    );
+    assert_eq!(eval("round(pi * 100)").unwrap(), 314.0);
+    assert_eq!(
+        eval_with("price * (1 + vat)", json!({"price": 20, "vat": 0.25})).unwrap(),
+        25.0
+    );
+    // Variables shadow constants
+    assert_eq!(eval_with("e * 2", json!({"e": 3})).unwrap(), 6.0);
+}
+
+#[test]
+fn test_errors_report_the_position() {
+    let cases = [
+        ("1 +", "unexpected end of expression at position 3", 3),
+        ("(1 + 2", "expected ')' at position 6", 6),
+        ("1 + 2)", "unexpected ')' at position 5", 5),
+        ("2 * x", "unknown variable 'x' at position 4", 4),
+        ("system(1)", "unknown function 'system' at position 0", 0),
+        (
+            "sqrt(1, 2)",
+            "sqrt() takes 1 argument, got 2 at position 0",
+            0,
+        ),
+        ("1 / (2 - 2)", "division by zero at position 2", 2),
+        ("1; 2", "unexpected character ';' at position 1", 1),
+    ];
+    for (expression, message, position) in cases {
+        let error = eval(expression).unwrap_err();
+        assert_eq!(error.message, message, "{}", expression);
+        assert_eq!(error.data, Some(json!({ "position": position })));
+    }
+
+    let error = eval_with("n + 1", json!({"n": "ten"})).unwrap_err();
+    assert_eq!(error.message, "variable 'n' is not a number at position 0");
+    assert!(eval("10 ^ 400").is_err());
+}
+
+#[test]
+fn test_deep_nesting_is_rejected() {
+    let error = eval(&format!("{}1{}", "(".repeat(100), ")".repeat(100))).unwrap_err();
+    assert!(error.message.contains("nested too deeply"));
+    assert!(eval(&"-".repeat(1000)).is_err());
+}
+
+#[tokio::test]
+async fn test_execute_returns_result() {
+    let result = CalculateTool
+        .execute(
+            Some(json!({"expression": "x ^ 2", "variables": {"x": 3}})),
+            AuthenticatedUser(create_test_user()),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result, json!({ "result": 9.0 }));
+}
+
+#[tokio::test]
+async fn test_input_errors_map_to_invalid_params() {
+    let state = AppState::new(create_test_credentials_store(), AppOptions::default()).unwrap();
+    let server = TestServer::new(mcp_router(state)).unwrap();
+
+    let response: Value = server
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .json(&json!({
+            "method": "invoke",
+            "params": {"tool_name": "calculate", "arguments": {"expression": "2 * y"}}
+        }))
+        .await
+        .json();
+
+    assert_eq!(response["error"]["code"], ERROR_INVALID_PARAMS);
+    assert_eq!(
+        response["error"]["message"],
+        "Invalid parameters: unknown variable 'y' at position 4"
+    );
+    assert_eq!(response["error"]["data"], json!({ "position": 4 }));
+}
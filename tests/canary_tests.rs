@@ -0,0 +1,148 @@
+mod common;
+
+use arc_swap::ArcSwap;
+use axum::extract::State;
+use axum::{Extension, Json};
+use common::*;
+use mcp_server::auth::AuthenticatedUser;
+use mcp_server::canary::{CanaryRouter, Variant};
+use mcp_server::config::CanaryConfig;
+use mcp_server::metrics::Metrics;
+use mcp_server::tools::ToolFunction;
+use mcp_server::{AppState, McpRequest, handle_mcp_request};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Tool function that reports which implementation ran
+fn named_tool(implementation: &'static str) -> ToolFunction {
+    Box::new(move |_args, _user| {
+        Box::pin(async move { Ok(json!({ "implementation": implementation })) })
+    })
+}
+
+fn registry() -> HashMap<String, ToolFunction> {
+    HashMap::from([
+        ("search".to_string(), named_tool("v1")),
+        ("search_v2".to_string(), named_tool("v2")),
+    ])
+}
+
+fn route(percent: f64, sticky: bool) -> HashMap<String, CanaryConfig> {
+    HashMap::from([(
+        "search".to_string(),
+        CanaryConfig {
+            variant: "search_v2".to_string(),
+            percent,
+            sticky,
+        },
+    )])
+}
+
+// ============================================================================
+// CanaryRouter Tests
+// ============================================================================
+
+#[test]
+fn test_uncanaried_tool_is_stable() {
+    let router = CanaryRouter::new(route(100.0, false), &registry()).unwrap();
+    assert_eq!(
+        router.select("search_v2", "alice"),
+        ("search_v2", Variant::Stable)
+    );
+}
+
+#[test]
+fn test_zero_and_full_percent() {
+    let never = CanaryRouter::new(route(0.0, false), &registry()).unwrap();
+    let always = CanaryRouter::new(route(100.0, false), &registry()).unwrap();
+
+    for _ in 0..100 {
+        assert_eq!(never.select("search", "alice"), ("search", Variant::Stable));
+        assert_eq!(
+            always.select("search", "alice"),
+            ("search_v2", Variant::Canary)
+        );
+    }
+}
+
+#[test]
+fn test_partial_rollout_splits_traffic() {
+    let router = CanaryRouter::new(route(50.0, false), &registry()).unwrap();
+
+    let canary = (0..1000)
+        .filter(|_| router.select("search", "alice").1 == Variant::Canary)
+        .count();
+    assert!((300..700).contains(&canary), "canary share was {}", canary);
+}
+
+#[test]
+fn test_sticky_rollout_is_consistent_per_user() {
+    let router = CanaryRouter::new(route(50.0, true), &registry()).unwrap();
+
+    for user in ["alice", "bob", "carol", "dave"] {
+        let first = router.select("search", user);
+        for _ in 0..20 {
+            assert_eq!(router.select("search", user), first);
+        }
+    }
+}
+
+#[test]
+fn test_invalid_routes_fail() {
+    let mut unknown_variant = route(10.0, false);
+    unknown_variant.get_mut("search").unwrap().variant = "missing".to_string();
+    assert!(CanaryRouter::new(unknown_variant, &registry()).is_err());
+
+    let mut unknown_tool = route(10.0, false);
+    let config = unknown_tool.remove("search").unwrap();
+    unknown_tool.insert("missing".to_string(), config);
+    assert!(CanaryRouter::new(unknown_tool, &registry()).is_err());
+
+    assert!(CanaryRouter::new(route(150.0, false), &registry()).is_err());
+
+    let mut self_variant = route(10.0, false);
+    self_variant.get_mut("search").unwrap().variant = "search".to_string();
+    assert!(CanaryRouter::new(self_variant, &registry()).is_err());
+}
+
+// ============================================================================
+// Integration Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_invoke_routes_to_canary_with_metrics() {
+    let registry = registry();
+    let canary = CanaryRouter::new(route(100.0, false), &registry).unwrap();
+    let metrics = Arc::new(Metrics::new());
+    let state = AppState {
+        tool_registry: Arc::new(ArcSwap::from_pointee(registry)),
+        canary: Arc::new(canary),
+        metrics: metrics.clone(),
+        ..Default::default()
+    };
+
+    let request: McpRequest =
+        serde_json::from_value(json!({"method": "invoke", "params": {"tool_name": "search"}}))
+            .unwrap();
+    let Json(response) = handle_mcp_request(
+        State(state),
+        Extension(AuthenticatedUser(create_test_user())),
+        Json(request),
+    )
+    .await;
+
+    assert_eq!(response.result.unwrap()["implementation"], "v2");
+    assert_eq!(
+        metrics.counter(
+            "mcp_tool_invocations_total",
+            &[
+                ("tool", "search"),
+                ("variant", "canary"),
+                ("status", "success"),
+                ("client", "unknown")
+            ]
+        ),
+        1
+    );
+}
@@ -0,0 +1,173 @@
+mod common;
+
+use arc_swap::ArcSwap;
+use axum::extract::State;
+use axum::{Extension, Json};
+use common::*;
+use mcp_server::audit::AuditStatus;
+use mcp_server::auth::AuthenticatedUser;
+use mcp_server::config::{CancellationConfig, ServerConfig, ToolCancellationConfig};
+use mcp_server::tools::ToolFunction;
+use mcp_server::{AppState, McpRequest, handle_mcp_request};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// Tool that takes a while, raising `finished` once it completes and
+/// `cancelled` when its context's token is cancelled
+fn slow_tool(finished: Arc<AtomicBool>, cancelled: Arc<AtomicBool>) -> ToolFunction {
+    Box::new(move |_args, ctx| {
+        let finished = finished.clone();
+        let cancelled = cancelled.clone();
+        Box::pin(async move {
+            let token = ctx.cancellation().clone();
+            tokio::spawn(async move {
+                token.cancelled().await;
+                cancelled.store(true, Ordering::SeqCst);
+            });
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            finished.store(true, Ordering::SeqCst);
+            Ok(json!({ "done": true }))
+        })
+    })
+}
+
+fn state(
+    config: ServerConfig,
+    finished: &Arc<AtomicBool>,
+    cancelled: &Arc<AtomicBool>,
+) -> AppState {
+    let registry = HashMap::from([(
+        "slow".to_string(),
+        slow_tool(finished.clone(), cancelled.clone()),
+    )]);
+    AppState {
+        tool_registry: Arc::new(ArcSwap::from_pointee(registry)),
+        config: Arc::new(config),
+        ..Default::default()
+    }
+}
+
+/// Start invoking `slow`, then drop the request as the server does when the
+/// client disconnects
+async fn invoke_and_disconnect(state: &AppState) {
+    let request: McpRequest =
+        serde_json::from_value(json!({"method": "invoke", "params": {"tool_name": "slow"}}))
+            .unwrap();
+    let handler = tokio::spawn(handle_mcp_request(
+        State(state.clone()),
+        Extension(AuthenticatedUser(create_test_user())),
+        Json(request),
+    ));
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    handler.abort();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+}
+
+fn keep_running(tool_name: &str) -> CancellationConfig {
+    CancellationConfig {
+        tools: HashMap::from([(
+            tool_name.to_string(),
+            ToolCancellationConfig {
+                abort_on_disconnect: Some(false),
+            },
+        )]),
+        ..Default::default()
+    }
+}
+
+// ============================================================================
+// CancellationConfig Tests
+// ============================================================================
+
+#[test]
+fn test_aborts_by_default_with_per_tool_overrides() {
+    let config = keep_running("send_email");
+    assert!(config.aborts("calculate"));
+    assert!(!config.aborts("send_email"));
+
+    let config = CancellationConfig {
+        abort_on_disconnect: false,
+        ..keep_running("send_email")
+    };
+    assert!(!config.aborts("calculate"));
+}
+
+#[test]
+fn test_cancellation_section_parsed() {
+    let config: ServerConfig = toml::from_str(
+        r#"
+        [cancellation]
+        abort_on_disconnect = false
+
+        [cancellation.tools.calculate]
+        abort_on_disconnect = true
+        "#,
+    )
+    .unwrap();
+    assert!(!config.cancellation.aborts("send_email"));
+    assert!(config.cancellation.aborts("calculate"));
+}
+
+// ============================================================================
+// Disconnect Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_disconnect_aborts_tool() {
+    let finished = Arc::new(AtomicBool::new(false));
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let state = state(ServerConfig::default(), &finished, &cancelled);
+
+    invoke_and_disconnect(&state).await;
+
+    assert!(cancelled.load(Ordering::SeqCst));
+    assert!(!finished.load(Ordering::SeqCst));
+    let history = state.history.list(TEST_USERNAME, None);
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].status, AuditStatus::Cancelled);
+    assert_eq!(history[0].error.as_deref(), Some("Client disconnected"));
+}
+
+#[tokio::test]
+async fn test_tool_configured_to_keep_running_completes() {
+    let finished = Arc::new(AtomicBool::new(false));
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let config = ServerConfig {
+        cancellation: keep_running("slow"),
+        ..Default::default()
+    };
+    let state = state(config, &finished, &cancelled);
+
+    invoke_and_disconnect(&state).await;
+
+    assert!(finished.load(Ordering::SeqCst));
+    assert!(!cancelled.load(Ordering::SeqCst));
+    let history = state.history.list(TEST_USERNAME, None);
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].status, AuditStatus::Success);
+}
+
+#[tokio::test]
+async fn test_completed_invocation_not_cancelled() {
+    let finished = Arc::new(AtomicBool::new(false));
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let state = state(ServerConfig::default(), &finished, &cancelled);
+
+    let request: McpRequest =
+        serde_json::from_value(json!({"method": "invoke", "params": {"tool_name": "slow"}}))
+            .unwrap();
+    let Json(response) = handle_mcp_request(
+        State(state.clone()),
+        Extension(AuthenticatedUser(create_test_user())),
+        Json(request),
+    )
+    .await;
+
+    assert_eq!(response.result.unwrap(), json!({ "done": true }));
+    assert!(finished.load(Ordering::SeqCst));
+    let history = state.history.list(TEST_USERNAME, None);
+    assert_eq!(history[0].status, AuditStatus::Success);
+}
@@ -0,0 +1,161 @@
+mod common;
+
+use axum::extract::connect_info::MockConnectInfo;
+use axum::http::{HeaderMap, HeaderValue};
+use axum_test::TestServer;
+use common::*;
+use mcp_server::client_ip::{TrustedProxies, forwarded_chain};
+use mcp_server::config::ServerConfig;
+use mcp_server::tools::{ToolContext, ToolFunction};
+use mcp_server::{AppOptions, AppState, ToolDefinition, mcp_router};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+
+fn proxies(entries: &[&str]) -> TrustedProxies {
+    let entries: Vec<String> = entries.iter().map(|e| e.to_string()).collect();
+    TrustedProxies::new(&entries).unwrap()
+}
+
+fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for (name, value) in pairs {
+        headers.append(*name, HeaderValue::from_str(value).unwrap());
+    }
+    headers
+}
+
+fn ip(addr: &str) -> IpAddr {
+    addr.parse().unwrap()
+}
+
+// ============================================================================
+// Resolution Tests
+// ============================================================================
+
+#[test]
+fn test_invalid_proxy_rejected() {
+    let error = TrustedProxies::new(&["10.0.0.0/33".to_string()]).unwrap_err();
+    assert_eq!(error.to_string(), "Invalid trusted proxy '10.0.0.0/33'");
+}
+
+#[test]
+fn test_untrusted_peer_headers_ignored() {
+    let proxies = proxies(&["10.0.0.0/8"]);
+    let headers = headers(&[("x-forwarded-for", "1.2.3.4")]);
+
+    assert_eq!(
+        proxies.client_ip(ip("203.0.113.7"), &headers),
+        ip("203.0.113.7")
+    );
+    assert_eq!(
+        TrustedProxies::default().client_ip(ip("10.0.0.1"), &headers),
+        ip("10.0.0.1")
+    );
+}
+
+#[test]
+fn test_nearest_untrusted_hop_is_client() {
+    let proxies = proxies(&["10.0.0.0/8", "192.168.1.1"]);
+    // The client forged the first entry; the trusted proxies appended the rest
+    let headers = headers(&[("x-forwarded-for", "6.6.6.6, 198.51.100.2, 192.168.1.1")]);
+
+    assert_eq!(
+        proxies.client_ip(ip("10.1.2.3"), &headers),
+        ip("198.51.100.2")
+    );
+}
+
+#[test]
+fn test_all_hops_trusted() {
+    let proxies = proxies(&["10.0.0.0/8"]);
+    let headers = headers(&[("x-forwarded-for", "10.0.0.5, 10.0.0.6")]);
+
+    assert_eq!(proxies.client_ip(ip("10.0.0.7"), &headers), ip("10.0.0.5"));
+}
+
+#[test]
+fn test_unparseable_hop_stops_walk() {
+    let proxies = proxies(&["10.0.0.0/8"]);
+    let headers = headers(&[("x-forwarded-for", "198.51.100.2, garbage, 10.0.0.5")]);
+
+    assert_eq!(proxies.client_ip(ip("10.0.0.7"), &headers), ip("10.0.0.5"));
+}
+
+#[test]
+fn test_forwarded_header_preferred() {
+    let headers = headers(&[
+        (
+            "forwarded",
+            r#"for=192.0.2.60;proto=http;by=203.0.113.43, For="[2001:db8:cafe::17]:4711""#,
+        ),
+        ("forwarded", "for=unknown"),
+        ("x-forwarded-for", "6.6.6.6"),
+    ]);
+
+    assert_eq!(
+        forwarded_chain(&headers),
+        vec![Some(ip("192.0.2.60")), Some(ip("2001:db8:cafe::17")), None]
+    );
+}
+
+#[test]
+fn test_mapped_ipv4_peer_matches_ipv4_network() {
+    let proxies = proxies(&["127.0.0.1"]);
+    let headers = headers(&[("x-forwarded-for", "198.51.100.2:5123")]);
+
+    assert_eq!(
+        proxies.client_ip(ip("::ffff:127.0.0.1"), &headers),
+        ip("198.51.100.2")
+    );
+}
+
+// ============================================================================
+// Endpoint Tests
+// ============================================================================
+
+/// Tool returning the client info it was invoked with
+fn client_tool() -> ToolFunction {
+    Box::new(|_args, ctx: ToolContext| {
+        let client = ctx.client().cloned();
+        Box::pin(async move { Ok(json!({ "client": client })) })
+    })
+}
+
+fn server(peer: &str) -> TestServer {
+    let mut config = ServerConfig::default();
+    config.client_ip.trusted_proxies = vec!["10.0.0.0/8".to_string()];
+    let state = AppState::new(create_test_credentials_store(), AppOptions::new(config)).unwrap();
+    state.replace_tools(
+        HashMap::from([("client_tool".to_string(), client_tool())]),
+        vec![ToolDefinition {
+            name: "client_tool".to_string(),
+            ..Default::default()
+        }],
+    );
+    let peer: SocketAddr = peer.parse().unwrap();
+    TestServer::new(mcp_router(state).layer(MockConnectInfo(peer))).unwrap()
+}
+
+async fn invoked_client(server: &TestServer, forwarded_for: &str) -> Value {
+    let response: Value = server
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .add_header("X-Forwarded-For", forwarded_for)
+        .json(&json!({"method": "invoke", "params": {"tool_name": "client_tool"}}))
+        .await
+        .json();
+    response["result"]["client"].clone()
+}
+
+#[tokio::test]
+async fn test_tools_see_forwarded_ip_behind_trusted_proxy() {
+    let client = invoked_client(&server("10.0.0.2:40000"), "198.51.100.2").await;
+    assert_eq!(client["ip"], "198.51.100.2");
+}
+
+#[tokio::test]
+async fn test_tools_see_peer_ip_from_untrusted_peer() {
+    let client = invoked_client(&server("203.0.113.9:40000"), "198.51.100.2").await;
+    assert_eq!(client["ip"], "203.0.113.9");
+}
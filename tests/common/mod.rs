@@ -10,12 +10,17 @@ use std::sync::Arc;
 use tempfile::NamedTempFile;
 
 // Test constants
+#[allow(dead_code)]
 pub const TEST_API_KEY: &str = "test-api-key-12345";
+#[allow(dead_code)]
 pub const TEST_API_KEY_2: &str = "test-api-key-67890";
+#[allow(dead_code)]
 pub const TEST_USERNAME: &str = "testuser";
+#[allow(dead_code)]
 pub const TEST_USERNAME_2: &str = "testuser2";
 
 /// Create a test credentials store with one user
+#[allow(dead_code)]
 pub fn create_test_credentials_store() -> CredentialsStore {
     let mut store = HashMap::new();
     store.insert(
@@ -30,6 +35,7 @@ pub fn create_test_credentials_store() -> CredentialsStore {
 }
 
 /// Create a test credentials store with multiple users
+#[allow(dead_code)]
 pub fn create_multi_user_credentials_store() -> CredentialsStore {
     let mut store = HashMap::new();
 
@@ -62,6 +68,7 @@ pub fn create_multi_user_credentials_store() -> CredentialsStore {
 }
 
 /// Create a test user with no external keys
+#[allow(dead_code)]
 pub fn create_test_user() -> UserCredentials {
     UserCredentials::new(
         TEST_USERNAME.to_string(),
@@ -71,6 +78,7 @@ pub fn create_test_user() -> UserCredentials {
 }
 
 /// Create a test user with external keys
+#[allow(dead_code)]
 pub fn create_test_user_with_external_keys() -> UserCredentials {
     let mut external_keys = HashMap::new();
     external_keys.insert(
@@ -106,6 +114,18 @@ postgres_url = "postgresql://localhost/bobdb"
     Ok(file)
 }
 
+/// Create a temporary credentials file with the given extension and content
+#[allow(dead_code)]
+pub fn create_temp_credentials_file_with_suffix(
+    suffix: &str,
+    contents: &str,
+) -> Result<NamedTempFile> {
+    let mut file = tempfile::Builder::new().suffix(suffix).tempfile()?;
+    write!(file, "{}", contents)?;
+    file.flush()?;
+    Ok(file)
+}
+
 /// Create a temporary credentials file with a single user
 #[allow(dead_code)]
 pub fn create_temp_single_user_file() -> Result<NamedTempFile> {
@@ -147,12 +167,14 @@ pub fn create_temp_invalid_toml_file() -> Result<NamedTempFile> {
 }
 
 /// Create a temporary empty credentials file
+#[allow(dead_code)]
 pub fn create_temp_empty_file() -> Result<NamedTempFile> {
     let file = NamedTempFile::new()?;
     Ok(file)
 }
 
 /// Mock tool for testing
+#[allow(dead_code)]
 pub struct MockTool {
     pub name: &'static str,
     pub description: &'static str,
@@ -190,6 +212,7 @@ impl McpTool for MockTool {
     }
 }
 
+#[allow(dead_code)]
 impl MockTool {
     pub fn new(name: &'static str, description: &'static str) -> Self {
         Self { name, description }
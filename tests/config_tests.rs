@@ -0,0 +1,113 @@
+use mcp_server::config::{ServerConfig, get_config_path, load_config};
+use std::env;
+use std::io::Write;
+use std::sync::Mutex;
+use tempfile::NamedTempFile;
+
+// Mutex to ensure env var tests run sequentially (prevents race conditions)
+static ENV_MUTEX: Mutex<()> = Mutex::new(());
+
+fn write_config(contents: &str) -> NamedTempFile {
+    let mut file = NamedTempFile::new().expect("Failed to create temp file");
+    write!(file, "{}", contents).unwrap();
+    file.flush().unwrap();
+    file
+}
+
+#[test]
+fn test_get_config_path_default() {
+    let _lock = ENV_MUTEX.lock().unwrap();
+
+    unsafe { env::remove_var("MCP_CONFIG_PATH"); }
+
+    assert_eq!(get_config_path(), "config/server.toml");
+}
+
+#[test]
+fn test_load_config_missing_default_file_uses_defaults() {
+    let _lock = ENV_MUTEX.lock().unwrap();
+
+    unsafe { env::remove_var("MCP_CONFIG_PATH"); }
+
+    let config = load_config().expect("Missing default config should not fail");
+    assert!(config.sanitization.strip_control_chars);
+    assert!(!config.sanitization.screen_injections);
+}
+
+#[test]
+fn test_load_config_explicit_missing_file_fails() {
+    let _lock = ENV_MUTEX.lock().unwrap();
+
+    unsafe { env::set_var("MCP_CONFIG_PATH", "/nonexistent/server.toml"); }
+
+    let err_msg = load_config().unwrap_err().to_string();
+    assert!(err_msg.contains("Failed to read config file"));
+
+    // Cleanup
+    unsafe { env::remove_var("MCP_CONFIG_PATH"); }
+}
+
+#[test]
+fn test_load_config_unknown_field_fails() {
+    let _lock = ENV_MUTEX.lock().unwrap();
+
+    let file = write_config("[sanitization]\nstrip_controls = true\n");
+    unsafe { env::set_var("MCP_CONFIG_PATH", file.path()); }
+
+    let err_msg = load_config().unwrap_err().to_string();
+    assert!(err_msg.contains("Failed to parse config file"));
+
+    // Cleanup
+    unsafe { env::remove_var("MCP_CONFIG_PATH"); }
+}
+
+#[test]
+fn test_load_config_sanitization_section() {
+    let _lock = ENV_MUTEX.lock().unwrap();
+
+    let file = write_config(
+        r#"[sanitization]
+strip_control_chars = false
+screen_injections = true
+injection_patterns = ["drop\\s+table"]
+
+[sanitization.tools.get_current_time]
+enabled = false
+
+[sanitization.tools.sql_query]
+reject_injections = true
+"#,
+    );
+    unsafe { env::set_var("MCP_CONFIG_PATH", file.path()); }
+
+    let config = load_config().expect("Config should parse");
+    let sanitization = &config.sanitization;
+    assert!(!sanitization.strip_control_chars);
+    assert_eq!(sanitization.injection_patterns, vec!["drop\\s+table"]);
+    assert!(!sanitization.enabled_for("get_current_time"));
+    assert!(sanitization.enabled_for("sql_query"));
+    assert!(sanitization.reject_injections_for("sql_query"));
+    assert!(!sanitization.reject_injections_for("other_tool"));
+    assert!(sanitization.screen_injections_for("sql_query"));
+
+    // Cleanup
+    unsafe { env::remove_var("MCP_CONFIG_PATH"); }
+}
+
+#[test]
+fn test_server_config_default() {
+    let config = ServerConfig::default();
+    assert!(config.sanitization.tools.is_empty());
+    assert!(config.sanitization.injection_patterns.is_empty());
+}
+
+#[test]
+fn test_invalid_auth_header_name_fails() {
+    let config: ServerConfig = toml::from_str("[auth]\napi_key_header = \"bad header\"\n").unwrap();
+
+    let result = mcp_server::create_app_with_options(
+        std::sync::Arc::new(std::collections::HashMap::new()),
+        mcp_server::AppOptions::new(config),
+    );
+    assert!(result.err().unwrap().to_string().contains("auth.api_key_header"));
+}
@@ -0,0 +1,169 @@
+mod common;
+
+use arc_swap::ArcSwap;
+use axum::extract::State;
+use axum::{Extension, Json};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use common::*;
+use mcp_server::auth::AuthenticatedUser;
+use mcp_server::config::{ResultsConfig, ServerConfig};
+use mcp_server::tools::content::check_binary_size;
+use mcp_server::tools::{BinaryContent, ToolContext, ToolFunction};
+use mcp_server::{AppState, ERROR_TOOL_EXECUTION, McpRequest, McpResponse, handle_mcp_request};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const PNG_HEADER: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+// ============================================================================
+// BinaryContent Tests
+// ============================================================================
+
+#[test]
+fn test_image_content_block() {
+    let content = BinaryContent::from_bytes(PNG_HEADER, "image/png", 0).unwrap();
+    assert_eq!(content.size, PNG_HEADER.len());
+
+    let block = content.into_content();
+    assert_eq!(block["type"], "image");
+    assert_eq!(block["mimeType"], "image/png");
+    assert_eq!(
+        STANDARD.decode(block["data"].as_str().unwrap()).unwrap(),
+        PNG_HEADER
+    );
+}
+
+#[test]
+fn test_other_mime_types_are_embedded_resources() {
+    let block = BinaryContent::from_bytes(b"%PDF-1.7", "application/pdf", 0)
+        .unwrap()
+        .with_uri("file:///report.pdf")
+        .into_content();
+
+    assert_eq!(block["type"], "resource");
+    assert_eq!(block["resource"]["uri"], "file:///report.pdf");
+    assert_eq!(block["resource"]["mimeType"], "application/pdf");
+    assert_eq!(block["resource"]["blob"], STANDARD.encode(b"%PDF-1.7"));
+}
+
+#[test]
+fn test_from_bytes_enforces_limit() {
+    let err = BinaryContent::from_bytes(&[0; 11], "image/png", 10).unwrap_err();
+    assert!(err.to_string().contains("10 byte limit"));
+    assert!(BinaryContent::from_bytes(&[0; 10], "image/png", 10).is_ok());
+}
+
+#[tokio::test]
+async fn test_from_reader_matches_from_bytes() {
+    // Larger than one read chunk so the encoding spans several writes
+    let data: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+
+    let streamed = BinaryContent::from_reader(data.as_slice(), "image/png", 0)
+        .await
+        .unwrap();
+    let direct = BinaryContent::from_bytes(&data, "image/png", 0).unwrap();
+
+    assert_eq!(streamed, direct);
+}
+
+#[tokio::test]
+async fn test_from_reader_enforces_limit() {
+    let data = vec![0u8; 100_000];
+    let result = BinaryContent::from_reader(data.as_slice(), "image/png", 50_000).await;
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_check_binary_size() {
+    let result = json!({
+        "content": [
+            { "type": "text", "text": "chart" },
+            { "type": "image", "data": STANDARD.encode([0; 12]), "mimeType": "image/png" }
+        ]
+    });
+
+    assert!(check_binary_size(&result, 12).is_ok());
+    assert!(check_binary_size(&result, 11).is_err());
+    assert!(check_binary_size(&result, 0).is_ok());
+}
+
+// ============================================================================
+// Invocation Tests
+// ============================================================================
+
+fn chart_tool() -> ToolFunction {
+    Box::new(|args, ctx: ToolContext| {
+        Box::pin(async move {
+            let size = args.unwrap()["size"].as_u64().unwrap() as usize;
+            let image = ctx.binary(&vec![7; size], "image/png")?;
+            Ok(json!({ "content": [image.into_content()] }))
+        })
+    })
+}
+
+fn raw_tool() -> ToolFunction {
+    Box::new(|_args, _ctx: ToolContext| {
+        Box::pin(async move {
+            Ok(json!({
+                "content": [{ "type": "image", "data": STANDARD.encode([0; 64]), "mimeType": "image/png" }]
+            }))
+        })
+    })
+}
+
+async fn invoke(tool_name: &str, size: usize) -> McpResponse {
+    let state = AppState {
+        tool_registry: Arc::new(ArcSwap::from_pointee(HashMap::from([
+            ("chart".to_string(), chart_tool()),
+            ("raw".to_string(), raw_tool()),
+        ]))),
+        config: Arc::new(ServerConfig {
+            results: ResultsConfig {
+                max_binary_bytes: 32,
+                ..Default::default()
+            },
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    let request: McpRequest = serde_json::from_value(json!({
+        "method": "invoke",
+        "params": { "tool_name": tool_name, "arguments": { "size": size } }
+    }))
+    .unwrap();
+    let Json(response) = handle_mcp_request(
+        State(state),
+        Extension(AuthenticatedUser(create_test_user())),
+        Json(request),
+    )
+    .await;
+    response
+}
+
+#[tokio::test]
+async fn test_tool_returns_image_content() {
+    let response = invoke("chart", 16).await;
+    let block = &response.result.unwrap()["content"][0];
+    assert_eq!(block["type"], "image");
+    assert_eq!(
+        STANDARD.decode(block["data"].as_str().unwrap()).unwrap(),
+        vec![7; 16]
+    );
+}
+
+#[tokio::test]
+async fn test_context_applies_configured_limit() {
+    let response = invoke("chart", 33).await;
+    let error = response.error.unwrap();
+    assert_eq!(error.code, ERROR_TOOL_EXECUTION);
+    assert!(error.message.contains("32 byte limit"));
+}
+
+#[tokio::test]
+async fn test_hand_built_content_is_checked() {
+    let response = invoke("raw", 0).await;
+    assert_eq!(response.error.unwrap().code, ERROR_TOOL_EXECUTION);
+}
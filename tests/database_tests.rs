@@ -0,0 +1,239 @@
+use mcp_server::config::{DatabaseConfig, ServerConfig};
+use mcp_server::database::Storage;
+use mcp_server::database::migrations::{MIGRATIONS, latest_version};
+
+// ============================================================================
+// Config Tests
+// ============================================================================
+
+#[test]
+fn test_database_config_parsed() {
+    assert_eq!(ServerConfig::default().database.path, None);
+
+    let config: ServerConfig = toml::from_str("[database]\npath = \"data/mcp.db\"\n").unwrap();
+    assert_eq!(config.database.path.as_deref(), Some("data/mcp.db"));
+}
+
+#[test]
+fn test_storage_disabled_without_path() {
+    let storage = Storage::open(&DatabaseConfig::default()).unwrap();
+    assert!(!storage.is_enabled());
+}
+
+#[test]
+fn test_migration_versions_are_sequential() {
+    for (expected, migration) in (1..).zip(MIGRATIONS) {
+        assert_eq!(migration.version, expected, "{}", migration.description);
+    }
+    assert_eq!(latest_version(MIGRATIONS), MIGRATIONS.len() as u32);
+    assert_eq!(latest_version(&[]), 0);
+}
+
+#[cfg(not(feature = "sqlite"))]
+#[test]
+fn test_database_requires_feature() {
+    let config = DatabaseConfig {
+        path: Some("mcp.db".to_string()),
+    };
+    let err = Storage::open(&config).err().unwrap();
+    assert!(err.to_string().contains("`sqlite` feature"));
+}
+
+#[cfg(feature = "sqlite")]
+mod sqlite {
+    use super::*;
+    use chrono::Utc;
+    use mcp_server::audit::AuditStatus;
+    use mcp_server::config::{StoreBackend, StoreConfig, UsageConfig};
+    use mcp_server::database::migrations::{Migration, current_version, migrate};
+    use mcp_server::store::SharedStore;
+    use mcp_server::usage::UsageRecorder;
+    use tempfile::TempDir;
+
+    fn database_path(dir: &TempDir) -> String {
+        dir.path().join("mcp.db").to_str().unwrap().to_string()
+    }
+
+    fn table_names(connection: &rusqlite::Connection) -> Vec<String> {
+        let mut statement = connection
+            .prepare("SELECT name FROM sqlite_master WHERE type = 'table' ORDER BY name")
+            .unwrap();
+        statement
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap()
+    }
+
+    const NOTES: Migration = Migration {
+        version: 1,
+        description: "notes",
+        sql: "CREATE TABLE notes (id INTEGER PRIMARY KEY, text TEXT NOT NULL);",
+    };
+
+    const NOTE_AUTHORS: Migration = Migration {
+        version: 2,
+        description: "note authors",
+        sql: "ALTER TABLE notes ADD COLUMN author TEXT;",
+    };
+
+    // ========================================================================
+    // Migration Tests
+    // ========================================================================
+
+    #[test]
+    fn test_migrations_applied_once_in_order() {
+        let mut connection = rusqlite::Connection::open_in_memory().unwrap();
+        assert_eq!(current_version(&connection).unwrap(), 0);
+
+        assert_eq!(migrate(&mut connection, &[NOTES]).unwrap(), 1);
+        assert_eq!(migrate(&mut connection, &[NOTES]).unwrap(), 0);
+        assert_eq!(migrate(&mut connection, &[NOTES, NOTE_AUTHORS]).unwrap(), 1);
+        assert_eq!(current_version(&connection).unwrap(), 2);
+
+        connection
+            .execute(
+                "INSERT INTO notes (text, author) VALUES ('hi', 'alice')",
+                [],
+            )
+            .unwrap();
+        let description: String = connection
+            .query_row(
+                "SELECT description FROM schema_migrations WHERE version = 2",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(description, "note authors");
+    }
+
+    #[test]
+    fn test_newer_database_refused() {
+        let mut connection = rusqlite::Connection::open_in_memory().unwrap();
+        migrate(&mut connection, &[NOTES, NOTE_AUTHORS]).unwrap();
+
+        let err = migrate(&mut connection, &[NOTES]).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("schema version 2 is newer than this server supports (1)")
+        );
+    }
+
+    #[test]
+    fn test_out_of_sequence_migrations_refused() {
+        let mut connection = rusqlite::Connection::open_in_memory().unwrap();
+        let err = migrate(&mut connection, &[NOTE_AUTHORS]).unwrap_err();
+        assert!(err.to_string().contains("has version 2, expected 1"));
+        assert_eq!(current_version(&connection).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_failed_migration_rolled_back() {
+        let mut connection = rusqlite::Connection::open_in_memory().unwrap();
+        let broken = Migration {
+            version: 2,
+            description: "broken",
+            sql: "CREATE TABLE tags (id INTEGER); ALTER TABLE missing ADD COLUMN x;",
+        };
+
+        let err = migrate(&mut connection, &[NOTES, broken]).unwrap_err();
+        assert!(format!("{:#}", err).contains("Migration 2 (broken) failed"));
+        // The first migration stays applied; none of the broken one does
+        assert_eq!(current_version(&connection).unwrap(), 1);
+        assert!(!table_names(&connection).contains(&"tags".to_string()));
+    }
+
+    // ========================================================================
+    // Shared Database Tests
+    // ========================================================================
+
+    #[test]
+    fn test_open_brings_schema_up_to_date() {
+        let dir = TempDir::new().unwrap();
+        let config = DatabaseConfig {
+            path: Some(database_path(&dir)),
+        };
+
+        let storage = Storage::open(&config).unwrap();
+        assert!(storage.is_enabled());
+        assert_eq!(
+            storage.schema_version().unwrap(),
+            Some(latest_version(MIGRATIONS))
+        );
+        drop(storage);
+
+        // Reopening applies nothing new
+        let storage = Storage::open(&config).unwrap();
+        let connection = storage.connection().unwrap();
+        let connection = connection.lock().unwrap();
+        let applied: u32 = connection
+            .query_row("SELECT COUNT(*) FROM schema_migrations", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(applied, latest_version(MIGRATIONS));
+        let tables = table_names(&connection);
+        for table in ["entries", "schema_migrations", "usage"] {
+            assert!(tables.contains(&table.to_string()), "{:?}", tables);
+        }
+    }
+
+    #[test]
+    fn test_database_from_before_migrations_adopted() {
+        let dir = TempDir::new().unwrap();
+        let path = database_path(&dir);
+        {
+            let connection = rusqlite::Connection::open(&path).unwrap();
+            connection
+                .execute_batch(
+                    "CREATE TABLE entries (key TEXT PRIMARY KEY, value TEXT NOT NULL, expires_at INTEGER);
+                     INSERT INTO entries VALUES ('kept', 'yes', NULL);",
+                )
+                .unwrap();
+        }
+
+        let storage = Storage::open_path(&path).unwrap();
+        assert_eq!(
+            storage.schema_version().unwrap(),
+            Some(latest_version(MIGRATIONS))
+        );
+        let connection = storage.connection().unwrap();
+        let value: String = connection
+            .lock()
+            .unwrap()
+            .query_row("SELECT value FROM entries WHERE key = 'kept'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(value, "yes");
+    }
+
+    #[tokio::test]
+    async fn test_subsystems_share_the_database() {
+        let dir = TempDir::new().unwrap();
+        let storage = Storage::open(&DatabaseConfig {
+            path: Some(database_path(&dir)),
+        })
+        .unwrap();
+
+        let store_config = StoreConfig {
+            backend: StoreBackend::Sqlite,
+            ..Default::default()
+        };
+        let store = SharedStore::from_config(&store_config, &storage).unwrap();
+        store.set("key", "value".to_string(), None).await.unwrap();
+
+        let usage = UsageRecorder::new(&UsageConfig::default(), &storage).unwrap();
+        usage.record("alice", "search", Utc::now(), AuditStatus::Success, 5);
+        usage.flush().await.unwrap();
+        drop((store, usage, storage));
+
+        // Both are found in the one file after a restart
+        let storage = Storage::open_path(&database_path(&dir)).unwrap();
+        let store = SharedStore::from_config(&store_config, &storage).unwrap();
+        assert_eq!(store.get("key").await.unwrap().as_deref(), Some("value"));
+        let usage = UsageRecorder::new(&UsageConfig::default(), &storage).unwrap();
+        let report = usage.report(None, None, None).await.unwrap();
+        assert_eq!(report[0].counts.invocations, 1);
+    }
+}
@@ -0,0 +1,55 @@
+#![cfg(feature = "debug-tools")]
+
+mod common;
+
+use axum_test::TestServer;
+use common::*;
+use mcp_server::{AppOptions, AppState, mcp_router};
+use serde_json::{Value, json};
+use std::sync::Arc;
+
+fn server() -> TestServer {
+    let mut store = (*create_test_credentials_store()).clone();
+    let user = create_test_user_with_external_keys();
+    store.insert(user.api_key.clone(), user);
+    let mut options = AppOptions::default();
+    options.config.results.include_meta = true;
+    let state = AppState::new(Arc::new(store), options).unwrap();
+    TestServer::new(mcp_router(state)).unwrap()
+}
+
+#[tokio::test]
+async fn test_debug_echo_reports_invocation() {
+    let response = server()
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .add_header("User-Agent", "echo-test/1.0")
+        .json(&json!({
+            "method": "invoke",
+            "params": {
+                "tool_name": "debug_echo",
+                "arguments": {"query": "ping", "limit": 3},
+                "_meta": {"progressToken": "p1"}
+            }
+        }))
+        .await;
+    let body: Value = response.json();
+    let result = &body["result"];
+
+    assert_eq!(result["arguments"], json!({"query": "ping", "limit": 3}));
+    assert_eq!(result["user"]["username"], TEST_USERNAME);
+    assert_eq!(
+        result["user"]["external_keys"],
+        json!(["postgres_url", "stripe_key"])
+    );
+    assert_eq!(result["client"]["user_agent"], "echo-test/1.0");
+    assert_eq!(result["meta"]["progressToken"], "p1");
+    assert!(result["server_time"].is_string());
+    assert!(result["server_time_unix_ms"].is_i64());
+    // Same id as in the response envelope
+    assert_eq!(result["request_id"], body["meta"]["request_id"]);
+
+    let text = body.to_string();
+    assert!(!text.contains("sk_test_123"));
+    assert!(!text.contains("postgresql://"));
+}
@@ -0,0 +1,192 @@
+mod common;
+
+use axum::body::Bytes;
+use axum::http::StatusCode;
+use axum_test::{TestResponse, TestServer};
+use common::*;
+use flate2::Compression;
+use flate2::write::{GzEncoder, ZlibEncoder};
+use mcp_server::config::{RequestBodyConfig, ServerConfig};
+use mcp_server::decompression::{DecompressionError, decompress};
+use mcp_server::{AppOptions, create_app_with_options};
+use serde_json::{Value, json};
+use std::io::Write;
+
+fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn zlib(data: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn server(request_body: RequestBodyConfig) -> TestServer {
+    let config = ServerConfig {
+        request_body,
+        ..Default::default()
+    };
+    let app =
+        create_app_with_options(create_test_credentials_store(), AppOptions::new(config)).unwrap();
+    TestServer::new(app).unwrap()
+}
+
+async fn post(server: &TestServer, encoding: &str, body: Vec<u8>) -> TestResponse {
+    server
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .add_header("Content-Encoding", encoding)
+        .content_type("application/json")
+        .bytes(Bytes::from(body))
+        .expect_success()
+        .await
+}
+
+/// Invocation whose arguments carry `padding` filler bytes
+fn padded_request(padding: usize) -> Vec<u8> {
+    json!({
+        "method": "invoke",
+        "params": {
+            "tool_name": "calculate",
+            "arguments": {"expression": "6 * 7", "variables": {"padding": "a".repeat(padding)}}
+        }
+    })
+    .to_string()
+    .into_bytes()
+}
+
+// ============================================================================
+// Decompression Helper Tests
+// ============================================================================
+
+#[test]
+fn test_decompress_within_limit() {
+    let data = b"hello hello hello";
+    assert_eq!(decompress("gzip", &gzip(data), 100).unwrap(), data);
+    assert_eq!(decompress("deflate", &zlib(data), 100).unwrap(), data);
+    assert_eq!(decompress("gzip", &gzip(data), data.len()).unwrap(), data);
+}
+
+#[test]
+fn test_decompress_stops_at_limit() {
+    // A few kilobytes expanding to 10 MB
+    let bomb = gzip(&vec![0; 10 * 1024 * 1024]);
+    assert!(bomb.len() < 100 * 1024);
+    assert_eq!(
+        decompress("gzip", &bomb, 1024 * 1024),
+        Err(DecompressionError::TooLarge)
+    );
+}
+
+#[test]
+fn test_decompress_invalid_data() {
+    assert!(matches!(
+        decompress("gzip", b"not gzip", 100),
+        Err(DecompressionError::Invalid(_))
+    ));
+    assert!(matches!(
+        decompress("br", b"", 100),
+        Err(DecompressionError::Invalid(_))
+    ));
+}
+
+// ============================================================================
+// Endpoint Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_gzip_and_deflate_bodies_accepted() {
+    let server = server(RequestBodyConfig::default());
+
+    for (encoding, body) in [
+        ("gzip", gzip(&padded_request(10))),
+        ("deflate", zlib(&padded_request(10))),
+        ("identity", padded_request(10)),
+    ] {
+        let response: Value = post(&server, encoding, body).await.json();
+        assert_eq!(
+            response["result"],
+            json!({ "result": 42.0 }),
+            "{}",
+            encoding
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_large_payload_within_limit() {
+    let server = server(RequestBodyConfig {
+        max_bytes: 8 * 1024 * 1024,
+        ..Default::default()
+    });
+
+    // Larger than axum's 2 MB default once decompressed
+    let response: Value = post(&server, "gzip", gzip(&padded_request(4 * 1024 * 1024)))
+        .await
+        .json();
+    assert_eq!(response["result"], json!({ "result": 42.0 }));
+}
+
+#[tokio::test]
+async fn test_decompressed_size_capped() {
+    let server = server(RequestBodyConfig {
+        max_bytes: 64 * 1024,
+        ..Default::default()
+    });
+
+    let response = server
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .add_header("Content-Encoding", "gzip")
+        .content_type("application/json")
+        .bytes(Bytes::from(gzip(&padded_request(1024 * 1024))))
+        .expect_failure()
+        .await;
+    response.assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(response.text(), "Decompressed request body too large");
+}
+
+#[tokio::test]
+async fn test_invalid_and_unsupported_encodings() {
+    let server = server(RequestBodyConfig::default());
+
+    let response = server
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .add_header("Content-Encoding", "gzip")
+        .content_type("application/json")
+        .bytes(Bytes::from_static(b"{\"method\":\"discover\"}"))
+        .expect_failure()
+        .await;
+    response.assert_status_bad_request();
+
+    let response = server
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .add_header("Content-Encoding", "br")
+        .content_type("application/json")
+        .bytes(Bytes::from_static(b"{}"))
+        .expect_failure()
+        .await;
+    response.assert_status(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+}
+
+#[tokio::test]
+async fn test_uncompressed_body_limit() {
+    let server = server(RequestBodyConfig {
+        max_bytes: 1024,
+        ..Default::default()
+    });
+
+    let response = server
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .content_type("application/json")
+        .bytes(Bytes::from(padded_request(4096)))
+        .expect_failure()
+        .await;
+    response.assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+}
@@ -0,0 +1,206 @@
+mod common;
+
+use arc_swap::ArcSwap;
+use axum::extract::State;
+use axum::{Extension, Json};
+use chrono::NaiveDate;
+use common::*;
+use mcp_server::auth::AuthenticatedUser;
+use mcp_server::config::{DeprecationConfig, ServerConfig};
+use mcp_server::deprecation::deprecation_warning;
+use mcp_server::tools::ToolFunction;
+use mcp_server::{AppState, McpRequest, McpResponse, ToolDefinition, handle_mcp_request};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+fn definition(name: &str, version: Option<&str>) -> ToolDefinition {
+    ToolDefinition {
+        name: name.to_string(),
+        version: version.map(str::to_string),
+        ..Default::default()
+    }
+}
+
+fn deprecations(deprecation: DeprecationConfig) -> HashMap<String, DeprecationConfig> {
+    HashMap::from([("old_report".to_string(), deprecation)])
+}
+
+/// State serving `old_report` 1.0.0, deprecated in favour of `report`, and
+/// `report`; both fail when called with `{"fail": true}`
+fn test_state(include_meta: bool) -> AppState {
+    let mut config = ServerConfig::default();
+    config.results.include_meta = include_meta;
+    config.deprecations = deprecations(DeprecationConfig {
+        replacement: Some("report".to_string()),
+        sunset: NaiveDate::from_ymd_opt(2027, 1, 1),
+        ..Default::default()
+    });
+    let tool = || -> ToolFunction {
+        Box::new(|args, _ctx| {
+            Box::pin(async move {
+                if args.is_some_and(|args| args["fail"] == true) {
+                    anyhow::bail!("failed");
+                }
+                Ok(json!({ "ok": true }))
+            })
+        })
+    };
+    AppState {
+        tool_registry: Arc::new(ArcSwap::from_pointee(HashMap::from([
+            ("old_report".to_string(), tool()),
+            ("report".to_string(), tool()),
+        ]))),
+        tool_definitions: Arc::new(ArcSwap::from_pointee(vec![
+            definition("old_report", Some("1.0.0")),
+            definition("report", Some("2.0.0")),
+        ])),
+        config: Arc::new(config),
+        ..Default::default()
+    }
+}
+
+async fn invoke(state: &AppState, params: Value) -> McpResponse {
+    let request: McpRequest =
+        serde_json::from_value(json!({ "method": "invoke", "params": params })).unwrap();
+    let Json(response) = handle_mcp_request(
+        State(state.clone()),
+        Extension(AuthenticatedUser(create_test_user())),
+        Json(request),
+    )
+    .await;
+    response
+}
+
+// ============================================================================
+// Config Tests
+// ============================================================================
+
+#[test]
+fn test_deprecations_config_parsed() {
+    assert!(ServerConfig::default().deprecations.is_empty());
+
+    let config: ServerConfig = toml::from_str(
+        "[deprecations.get_weather]\nversions = [\"1.0.0\"]\nreplacement = \"weather_forecast\"\nsunset = \"2027-01-01\"\n",
+    )
+    .unwrap();
+    let deprecation = &config.deprecations["get_weather"];
+    assert_eq!(deprecation.versions, vec!["1.0.0"]);
+    assert_eq!(deprecation.replacement.as_deref(), Some("weather_forecast"));
+    assert_eq!(deprecation.sunset, NaiveDate::from_ymd_opt(2027, 1, 1));
+    assert_eq!(deprecation.message, None);
+}
+
+// ============================================================================
+// Warning Tests
+// ============================================================================
+
+#[test]
+fn test_warning_message_generated() {
+    let warning = deprecation_warning(
+        &deprecations(DeprecationConfig {
+            replacement: Some("report".to_string()),
+            sunset: NaiveDate::from_ymd_opt(2027, 1, 1),
+            ..Default::default()
+        }),
+        &definition("old_report", Some("1.0.0")),
+    )
+    .unwrap();
+    assert_eq!(
+        warning.message,
+        "Tool 'old_report' is deprecated and will be removed on 2027-01-01; use 'report' instead"
+    );
+    assert_eq!(
+        serde_json::to_value(&warning).unwrap(),
+        json!({
+            "code": "deprecated",
+            "message": warning.message,
+            "tool": "old_report",
+            "version": "1.0.0",
+            "replacement": "report",
+            "sunset": "2027-01-01",
+        })
+    );
+
+    let custom = deprecation_warning(
+        &deprecations(DeprecationConfig {
+            message: Some("Migrate by March".to_string()),
+            ..Default::default()
+        }),
+        &definition("old_report", None),
+    )
+    .unwrap();
+    assert_eq!(custom.message, "Migrate by March");
+    assert!(deprecation_warning(&HashMap::new(), &definition("old_report", None)).is_none());
+}
+
+#[test]
+fn test_only_listed_versions_deprecated() {
+    let deprecations = deprecations(DeprecationConfig {
+        versions: vec!["1.0.0".to_string()],
+        ..Default::default()
+    });
+
+    let warning =
+        deprecation_warning(&deprecations, &definition("old_report", Some("1.0.0"))).unwrap();
+    assert_eq!(
+        warning.message,
+        "Version 1.0.0 of tool 'old_report' is deprecated"
+    );
+    assert!(deprecation_warning(&deprecations, &definition("old_report", Some("2.0.0"))).is_none());
+    assert!(deprecation_warning(&deprecations, &definition("old_report", None)).is_none());
+}
+
+// ============================================================================
+// Invocation Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_deprecated_invocation_carries_warning() {
+    let state = test_state(false);
+
+    let response = invoke(&state, json!({"tool_name": "old_report"})).await;
+    assert_eq!(response.result, Some(json!({ "ok": true })));
+    let meta = response.meta.unwrap();
+    // Only the warnings without `results.include_meta`
+    assert_eq!(meta.as_object().unwrap().len(), 1);
+    let warnings = meta["warnings"].as_array().unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0]["code"], "deprecated");
+    assert_eq!(warnings[0]["replacement"], "report");
+
+    // Failed invocations are warned about too
+    let failed = invoke(
+        &state,
+        json!({"tool_name": "old_report", "arguments": {"fail": true}}),
+    )
+    .await;
+    assert!(failed.error.is_some());
+    assert_eq!(failed.meta.unwrap()["warnings"][0]["tool"], "old_report");
+
+    assert_eq!(
+        state.metrics.counter(
+            "mcp_deprecated_invocations_total",
+            &[("tool", "old_report"), ("version", "1.0.0")]
+        ),
+        2
+    );
+}
+
+#[tokio::test]
+async fn test_current_tool_has_no_warning() {
+    let response = invoke(&test_state(false), json!({"tool_name": "report"})).await;
+    assert!(response.meta.is_none());
+
+    let response = invoke(&test_state(true), json!({"tool_name": "report"})).await;
+    assert!(response.meta.unwrap().get("warnings").is_none());
+}
+
+#[tokio::test]
+async fn test_warnings_added_to_invocation_meta() {
+    let response = invoke(&test_state(true), json!({"tool_name": "old_report"})).await;
+
+    let meta = response.meta.unwrap();
+    assert_eq!(meta["tool_version"], "1.0.0");
+    assert_eq!(meta["warnings"][0]["version"], "1.0.0");
+}
@@ -0,0 +1,191 @@
+mod common;
+
+use bollard::models::ContainerSummary;
+use common::*;
+use mcp_server::auth::{AuthenticatedUser, UserCredentials};
+use mcp_server::config::{DockerConfig, DockerOperation, ServerConfig};
+use mcp_server::tools::docker::{
+    DockerContainerActionTool, DockerContainerLogsTool, DockerListContainersTool,
+    allowed_operation, container_summary, redact_env, tail_lines,
+};
+use mcp_server::tools::{InvalidInput, McpTool, ToolContext};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+fn context(config: DockerConfig) -> ToolContext {
+    let server = ServerConfig {
+        docker: config,
+        ..Default::default()
+    };
+    let user = UserCredentials::new(
+        "alice".to_string(),
+        TEST_API_KEY.to_string(),
+        HashMap::new(),
+    );
+    ToolContext::new(AuthenticatedUser(user)).with_config(Arc::new(server))
+}
+
+fn unreachable_daemon() -> DockerConfig {
+    DockerConfig {
+        host: Some("unix:///nonexistent/docker.sock".to_string()),
+        ..Default::default()
+    }
+}
+
+fn invalid_input(error: anyhow::Error) -> InvalidInput {
+    error.downcast_ref::<InvalidInput>().unwrap().clone()
+}
+
+// ============================================================================
+// Configuration Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_disabled_without_host() {
+    let error = DockerListContainersTool
+        .execute_with_context(None, context(DockerConfig::default()))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "docker tools are disabled: [docker] host is not set"
+    );
+}
+
+#[test]
+fn test_allowed_operations_parsed() {
+    let config: ServerConfig = toml::from_str(
+        r#"
+        [docker]
+        host = "unix:///var/run/docker.sock"
+        allowed_operations = ["start", "restart"]
+        "#,
+    )
+    .unwrap();
+    assert_eq!(
+        config.docker.allowed_operations,
+        vec![DockerOperation::Start, DockerOperation::Restart]
+    );
+
+    let result: Result<ServerConfig, _> = toml::from_str(
+        r#"
+        [docker]
+        allowed_operations = ["exec"]
+        "#,
+    );
+    assert!(result.is_err());
+}
+
+// ============================================================================
+// Mutation Allowlist Tests
+// ============================================================================
+
+#[test]
+fn test_mutations_refused_by_default() {
+    let error = allowed_operation(&DockerConfig::default(), "stop").unwrap_err();
+    assert_eq!(
+        error.message,
+        "operation 'stop' is not allowed on this server"
+    );
+    assert_eq!(error.data.unwrap(), json!({ "allowed_operations": [] }));
+}
+
+#[test]
+fn test_allowlisted_operation_accepted() {
+    let config = DockerConfig {
+        allowed_operations: vec![DockerOperation::Restart],
+        ..Default::default()
+    };
+    assert_eq!(
+        allowed_operation(&config, "restart").unwrap(),
+        DockerOperation::Restart
+    );
+    assert!(allowed_operation(&config, "remove").is_err());
+    assert_eq!(
+        allowed_operation(&config, "exec").unwrap_err().message,
+        "unknown operation 'exec'"
+    );
+}
+
+#[tokio::test]
+async fn test_action_checked_before_connecting() {
+    let error = DockerContainerActionTool
+        .execute_with_context(
+            Some(json!({"container": "web", "operation": "remove"})),
+            context(unreachable_daemon()),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        invalid_input(error).message,
+        "operation 'remove' is not allowed on this server"
+    );
+}
+
+#[tokio::test]
+async fn test_invalid_container_name_refused() {
+    let error = DockerContainerLogsTool
+        .execute_with_context(
+            Some(json!({"container": "../images/json"})),
+            context(unreachable_daemon()),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        invalid_input(error).message,
+        "invalid container name '../images/json'"
+    );
+}
+
+#[test]
+fn test_action_tool_is_destructive() {
+    let annotations = DockerContainerActionTool.annotations().unwrap();
+    assert_eq!(annotations["destructiveHint"], true);
+    assert_eq!(
+        DockerListContainersTool.annotations().unwrap()["readOnlyHint"],
+        true
+    );
+}
+
+// ============================================================================
+// Limits and Output Tests
+// ============================================================================
+
+#[test]
+fn test_tail_lines_defaults_and_bounds() {
+    let config = DockerConfig::default();
+    assert_eq!(tail_lines(&config, None), 100);
+    assert_eq!(tail_lines(&config, Some(10)), 10);
+    assert_eq!(tail_lines(&config, Some(u64::MAX)), 2000);
+}
+
+#[test]
+fn test_container_summary() {
+    let container: ContainerSummary = serde_json::from_value(json!({
+        "Id": "0123456789abcdef0123",
+        "Names": ["/web"],
+        "Image": "nginx:1.27",
+        "State": "running",
+        "Status": "Up 2 hours",
+        "Ports": [{ "PrivatePort": 80, "PublicPort": 8080, "Type": "tcp" }]
+    }))
+    .unwrap();
+
+    let summary = container_summary(&container);
+    assert_eq!(summary["id"], "0123456789ab");
+    assert_eq!(summary["names"], json!(["web"]));
+    assert_eq!(summary["state"], "running");
+    assert_eq!(summary["ports"][0]["public_port"], 8080);
+    assert_eq!(summary["ports"][0]["type"], "tcp");
+}
+
+#[test]
+fn test_inspect_env_values_redacted() {
+    let mut inspect = json!({
+        "Id": "abc",
+        "Config": { "Env": ["PATH=/usr/bin", "DATABASE_URL=postgres://u:hunter2@db/app"] }
+    });
+    redact_env(&mut inspect);
+    assert_eq!(inspect["Config"]["Env"], json!(["PATH", "DATABASE_URL"]));
+}
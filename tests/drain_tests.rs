@@ -0,0 +1,128 @@
+mod common;
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use common::*;
+use mcp_server::auth::UserCredentials;
+use mcp_server::drain::DrainController;
+use mcp_server::{ERROR_SERVER_BUSY, create_app};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+const ADMIN_API_KEY: &str = "admin-api-key-000";
+
+fn server() -> TestServer {
+    let mut store = (*create_test_credentials_store()).clone();
+    store.insert(
+        ADMIN_API_KEY.to_string(),
+        UserCredentials::new(
+            "admin".to_string(),
+            ADMIN_API_KEY.to_string(),
+            HashMap::new(),
+        )
+        .with_admin(true),
+    );
+    TestServer::new(create_app(Arc::new(store)).unwrap()).unwrap()
+}
+
+// ============================================================================
+// DrainController Tests
+// ============================================================================
+
+#[test]
+fn test_in_flight_tracking() {
+    let drain = Arc::new(DrainController::new());
+
+    let first = drain.begin().unwrap();
+    let second = drain.begin().unwrap();
+    assert_eq!(drain.in_flight(), 2);
+
+    drop(first);
+    drop(second);
+    assert_eq!(drain.in_flight(), 0);
+}
+
+#[test]
+fn test_draining_refuses_new_work() {
+    let drain = Arc::new(DrainController::new());
+    drain.start();
+
+    assert!(drain.is_draining());
+    assert!(drain.begin().is_none());
+    assert_eq!(drain.in_flight(), 0);
+}
+
+#[tokio::test]
+async fn test_wait_idle_returns_when_work_finishes() {
+    let drain = Arc::new(DrainController::new());
+    let guard = drain.begin().unwrap();
+    drain.start();
+
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        drop(guard);
+    });
+
+    assert!(drain.wait_idle(Duration::from_secs(5)).await);
+}
+
+#[tokio::test]
+async fn test_wait_idle_times_out() {
+    let drain = Arc::new(DrainController::new());
+    let _guard = drain.begin().unwrap();
+    drain.start();
+
+    assert!(!drain.wait_idle(Duration::from_millis(20)).await);
+    assert_eq!(drain.in_flight(), 1);
+}
+
+// ============================================================================
+// Endpoint Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_drain_requires_admin() {
+    let server = server();
+    server
+        .post("/admin/drain")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .await
+        .assert_status_forbidden();
+    server.get("/ready").await.assert_status_ok();
+}
+
+#[tokio::test]
+async fn test_drain_marks_not_ready_and_refuses_invocations() {
+    let server = server();
+    server.get("/ready").await.assert_text("READY");
+
+    let response = server
+        .post("/admin/drain")
+        .add_header("Authorization", format!("Bearer {}", ADMIN_API_KEY))
+        .await;
+    response.assert_status_ok();
+    let body: Value = response.json();
+    assert_eq!(body["result"]["drained"], true);
+    assert_eq!(body["result"]["in_flight"], 0);
+
+    let ready = server.get("/ready").await;
+    ready.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+    ready.assert_text("DRAINING");
+    server.get("/health").await.assert_status_ok();
+
+    let invoke = server
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .json(&json!({"method": "invoke", "params": {"tool_name": "get_current_time"}}))
+        .await;
+    let body: Value = invoke.json();
+    assert_eq!(body["error"]["code"], ERROR_SERVER_BUSY);
+    assert!(
+        body["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("draining")
+    );
+}
@@ -0,0 +1,204 @@
+mod common;
+
+use axum_test::TestServer;
+use common::*;
+use mcp_server::auth::AuthenticatedUser;
+use mcp_server::elicitation::{ElicitationAction, ElicitationResponse, PendingElicitations};
+use mcp_server::tools::ToolContext;
+use mcp_server::{ERROR_INVALID_PARAMS, create_app};
+use serde_json::{Value, json};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+fn confirm_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": { "confirm": { "type": "boolean" } },
+        "required": ["confirm"]
+    })
+}
+
+fn accept(content: Value) -> ElicitationResponse {
+    ElicitationResponse {
+        action: ElicitationAction::Accept,
+        content: Some(content),
+    }
+}
+
+// ============================================================================
+// PendingElicitations Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_resolve_delivers_response() {
+    let pending = PendingElicitations::new();
+    let (id, answer) = pending.register("alice", confirm_schema());
+
+    pending
+        .resolve("alice", &id, accept(json!({"confirm": true})))
+        .unwrap();
+
+    let response = answer.await.unwrap();
+    assert!(response.is_accepted());
+    assert_eq!(response.content.unwrap()["confirm"], true);
+    assert!(pending.is_empty());
+}
+
+#[test]
+fn test_resolve_rejects_other_users_and_unknown_ids() {
+    let pending = PendingElicitations::new();
+    let (id, _answer) = pending.register("alice", confirm_schema());
+
+    assert!(
+        pending
+            .resolve("bob", &id, accept(json!({"confirm": true})))
+            .is_err()
+    );
+    assert!(
+        pending
+            .resolve("alice", "unknown", accept(json!({"confirm": true})))
+            .is_err()
+    );
+    assert_eq!(pending.len(), 1);
+}
+
+#[test]
+fn test_invalid_content_keeps_request_pending() {
+    let pending = PendingElicitations::new();
+    let (id, _answer) = pending.register("alice", confirm_schema());
+
+    let err = pending
+        .resolve("alice", &id, accept(json!({"confirm": "yes"})))
+        .unwrap_err();
+    assert!(err.to_string().contains("must be of type 'boolean'"));
+
+    let missing = ElicitationResponse {
+        action: ElicitationAction::Accept,
+        content: None,
+    };
+    assert!(pending.resolve("alice", &id, missing).is_err());
+    assert_eq!(pending.len(), 1);
+}
+
+#[tokio::test]
+async fn test_decline_needs_no_content() {
+    let pending = PendingElicitations::new();
+    let (id, answer) = pending.register("alice", confirm_schema());
+
+    let decline = ElicitationResponse {
+        action: ElicitationAction::Decline,
+        content: None,
+    };
+    pending.resolve("alice", &id, decline).unwrap();
+
+    assert_eq!(answer.await.unwrap().action, ElicitationAction::Decline);
+}
+
+// ============================================================================
+// ToolContext Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_elicit_without_stream_fails() {
+    let ctx = ToolContext::new(AuthenticatedUser(create_test_user()));
+
+    let err = ctx.elicit(confirm_schema(), "Delete?").await.unwrap_err();
+    assert!(err.to_string().contains("does not support elicitation"));
+}
+
+#[tokio::test]
+async fn test_elicit_round_trip() {
+    let pending = Arc::new(PendingElicitations::new());
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let ctx = ToolContext::new(AuthenticatedUser(create_test_user())).with_client_channel(
+        tx,
+        pending.clone(),
+        Duration::from_secs(5),
+    );
+
+    let tool = tokio::spawn(async move { ctx.elicit(confirm_schema(), "Delete all rows?").await });
+
+    let request = rx.recv().await.unwrap();
+    assert_eq!(request["method"], "elicitation/create");
+    assert_eq!(request["params"]["message"], "Delete all rows?");
+    assert_eq!(request["params"]["requestedSchema"], confirm_schema());
+
+    let id = request["params"]["elicitation_id"].as_str().unwrap();
+    pending
+        .resolve(TEST_USERNAME, id, accept(json!({"confirm": true})))
+        .unwrap();
+
+    let response = tool.await.unwrap().unwrap();
+    assert!(response.is_accepted());
+}
+
+#[tokio::test]
+async fn test_elicit_times_out() {
+    let pending = Arc::new(PendingElicitations::new());
+    let (tx, _rx) = mpsc::unbounded_channel();
+    let ctx = ToolContext::new(AuthenticatedUser(create_test_user())).with_client_channel(
+        tx,
+        pending.clone(),
+        Duration::from_millis(10),
+    );
+
+    let err = ctx.elicit(confirm_schema(), "Delete?").await.unwrap_err();
+    assert!(err.to_string().contains("Timed out"));
+    assert!(pending.is_empty());
+}
+
+// ============================================================================
+// Integration Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_streamed_invocation_ends_with_response() {
+    let server = TestServer::new(create_app(create_test_credentials_store()).unwrap()).unwrap();
+
+    let response = server
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .add_header("Accept", "text/event-stream")
+        .json(&json!({"method": "invoke", "params": {"tool_name": "get_current_time"}}))
+        .await;
+
+    response.assert_status_ok();
+    assert!(
+        response
+            .header("content-type")
+            .to_str()
+            .unwrap()
+            .starts_with("text/event-stream")
+    );
+    let data = response
+        .text()
+        .lines()
+        .find_map(|line| line.strip_prefix("data: ").map(str::to_string))
+        .unwrap();
+    let body: Value = serde_json::from_str(&data).unwrap();
+    assert!(body["result"]["current_time"].is_string());
+}
+
+#[tokio::test]
+async fn test_respond_to_unknown_elicitation() {
+    let server = TestServer::new(create_app(create_test_credentials_store()).unwrap()).unwrap();
+
+    let body: Value = server
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .json(&json!({
+            "method": "elicitation/respond",
+            "params": {"elicitation_id": "nope", "action": "cancel"}
+        }))
+        .await
+        .json();
+
+    assert_eq!(body["error"]["code"], ERROR_INVALID_PARAMS);
+    assert!(
+        body["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("Unknown elicitation")
+    );
+}
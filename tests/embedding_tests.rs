@@ -0,0 +1,90 @@
+mod common;
+
+use axum::extract::State;
+use axum::routing::get;
+use axum::{Extension, Json, Router};
+use axum_test::TestServer;
+use common::*;
+use mcp_server::auth::AuthenticatedUser;
+use mcp_server::{AppOptions, AppState, mcp_router};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+
+/// Custom route reading the shared state
+async fn tool_count(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+) -> Json<Value> {
+    Json(json!({
+        "user": user.credentials().username,
+        "tools": state.tools_for(&user).definitions.len(),
+    }))
+}
+
+fn embedded_app(state: AppState) -> Router {
+    let custom = Router::new()
+        .route("/api/tools/count", get(tool_count))
+        .with_state(state.clone())
+        .layer(state.auth.clone());
+
+    Router::new()
+        .route("/", get(|| async { "home" }))
+        .merge(custom)
+        .merge(mcp_router(state))
+}
+
+fn state() -> AppState {
+    AppState::new(create_test_credentials_store(), AppOptions::default()).unwrap()
+}
+
+#[tokio::test]
+async fn test_mcp_routes_work_inside_host_application() {
+    let server = TestServer::new(embedded_app(state())).unwrap();
+
+    server.get("/").await.assert_text("home");
+    server.get("/health").await.assert_status_ok();
+
+    let response = server
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .json(&json!({"method": "discover"}))
+        .await;
+    response.assert_status_ok();
+    let body: Value = response.json();
+    assert!(body["result"]["tools"].is_array());
+}
+
+#[tokio::test]
+async fn test_custom_routes_share_state_and_auth() {
+    let state = state();
+    let server = TestServer::new(embedded_app(state.clone())).unwrap();
+
+    server
+        .get("/api/tools/count")
+        .await
+        .assert_status_unauthorized();
+
+    let count = || async {
+        let body: Value = server
+            .get("/api/tools/count")
+            .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+            .await
+            .json();
+        body
+    };
+    let before = count().await;
+    assert_eq!(before["user"], TEST_USERNAME);
+    assert!(before["tools"].as_u64().unwrap() > 0);
+
+    // Changes made through the embedder's handle are visible to both routers
+    state.replace_tools(HashMap::new(), Vec::new());
+    assert_eq!(count().await["tools"], 0);
+}
+
+#[test]
+fn test_state_construction_reports_invalid_options() {
+    let mut options = AppOptions::default();
+    options.config.auth.api_key_header = Some("not a header".to_string());
+
+    assert!(AppState::new(create_test_credentials_store(), options).is_err());
+}
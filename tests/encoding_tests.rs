@@ -0,0 +1,253 @@
+mod common;
+
+use axum::body::Bytes;
+use axum_test::TestServer;
+use common::*;
+use mcp_server::encoding::{DecodeLimits, WireFormat};
+use mcp_server::{AppOptions, AppState, ERROR_INVALID_REQUEST, mcp_router};
+use serde_json::{Value, json};
+
+fn server() -> TestServer {
+    let state = AppState::new(create_test_credentials_store(), AppOptions::default()).unwrap();
+    TestServer::new(mcp_router(state)).unwrap()
+}
+
+/// `depth` arrays nested in each other
+fn nested(depth: usize) -> Value {
+    (0..depth).fold(json!(1), |inner, _| json!([inner]))
+}
+
+/// Object with `keys` keys
+fn wide(keys: usize) -> Value {
+    Value::Object((0..keys).map(|i| (format!("k{}", i), json!(i))).collect())
+}
+
+fn invoke_calculate() -> Value {
+    json!({
+        "method": "invoke",
+        "params": {"tool_name": "calculate", "arguments": {"expression": "6 * 7"}}
+    })
+}
+
+// ============================================================================
+// Negotiation Tests
+// ============================================================================
+
+#[test]
+fn test_media_types() {
+    assert_eq!(
+        WireFormat::from_media_type("application/msgpack"),
+        Some(WireFormat::MessagePack)
+    );
+    assert_eq!(
+        WireFormat::from_media_type("Application/X-MsgPack; charset=binary"),
+        Some(WireFormat::MessagePack)
+    );
+    assert_eq!(
+        WireFormat::from_media_type("application/cbor"),
+        Some(WireFormat::Cbor)
+    );
+    assert_eq!(
+        WireFormat::from_media_type("application/json; charset=utf-8"),
+        Some(WireFormat::Json)
+    );
+    assert_eq!(
+        WireFormat::from_media_type("application/vnd.api+json"),
+        Some(WireFormat::Json)
+    );
+    assert_eq!(WireFormat::from_media_type("text/plain"), None);
+}
+
+#[test]
+fn test_round_trip() {
+    let value = json!({"result": {"text": "é", "n": -3, "x": 1.5, "list": [true, null]}});
+    for format in [WireFormat::Json, WireFormat::MessagePack, WireFormat::Cbor] {
+        let bytes = format.encode(&value).unwrap();
+        assert_eq!(format.decode(&bytes).unwrap(), value, "{}", format.name());
+    }
+}
+
+// ============================================================================
+// Decode Limit Tests
+// ============================================================================
+
+#[test]
+fn test_depth_limit() {
+    let limits = DecodeLimits {
+        max_depth: 8,
+        max_object_keys: 0,
+    };
+    for format in [WireFormat::Json, WireFormat::MessagePack, WireFormat::Cbor] {
+        let at_limit = format.encode(&nested(8)).unwrap();
+        assert_eq!(format.decode_limited(&at_limit, limits).unwrap(), nested(8));
+
+        let too_deep = format.encode(&nested(9)).unwrap();
+        let error = format.decode_limited(&too_deep, limits).unwrap_err();
+        assert!(
+            error.contains("nesting deeper than 8 levels"),
+            "{}: {}",
+            format.name(),
+            error
+        );
+        // Limits only apply to the decode they were given to
+        assert!(format.decode(&too_deep).is_ok());
+    }
+}
+
+#[test]
+fn test_object_key_limit() {
+    let limits = DecodeLimits {
+        max_depth: 0,
+        max_object_keys: 16,
+    };
+    for format in [WireFormat::Json, WireFormat::MessagePack, WireFormat::Cbor] {
+        let nested_wide = format.encode(&json!({ "arguments": wide(16) })).unwrap();
+        assert!(format.decode_limited(&nested_wide, limits).is_ok());
+
+        let too_wide = format.encode(&json!({ "arguments": wide(17) })).unwrap();
+        let error = format.decode_limited(&too_wide, limits).unwrap_err();
+        assert!(
+            error.contains("object with more than 16 keys"),
+            "{}: {}",
+            format.name(),
+            error
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_request_over_limits_rejected() {
+    let mut options = AppOptions::default();
+    options.config.request_body.max_depth = 16;
+    let state = AppState::new(create_test_credentials_store(), options).unwrap();
+    let server = TestServer::new(mcp_router(state)).unwrap();
+
+    let response = server
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .json(&json!({
+            "method": "invoke",
+            "params": {"tool_name": "calculate", "arguments": {"expression": nested(16)}}
+        }))
+        .expect_failure()
+        .await;
+    response.assert_status_bad_request();
+    assert!(
+        response
+            .text()
+            .starts_with("Failed to parse the request body as JSON: nesting deeper than 16 levels")
+    );
+
+    let response = server
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .json(&json!({"method": "discover", "params": wide(2000)}))
+        .expect_failure()
+        .await;
+    response.assert_status_bad_request();
+    assert!(response.text().contains("object with more than 1024 keys"));
+}
+
+// ============================================================================
+// Endpoint Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_msgpack_request_answered_in_msgpack() {
+    let body = WireFormat::MessagePack.encode(&invoke_calculate()).unwrap();
+
+    let response = server()
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .content_type("application/msgpack")
+        .bytes(Bytes::from(body))
+        .await;
+
+    response.assert_status_ok();
+    assert_eq!(response.header("content-type"), "application/msgpack");
+    let decoded = WireFormat::MessagePack.decode(response.as_bytes()).unwrap();
+    assert_eq!(decoded["result"], json!({ "result": 42.0 }));
+    assert_eq!(decoded["jsonrpc"], "2.0");
+}
+
+#[tokio::test]
+async fn test_cbor_request_with_json_accept() {
+    let body = WireFormat::Cbor.encode(&invoke_calculate()).unwrap();
+
+    let response = server()
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .add_header("Accept", "application/json")
+        .content_type("application/cbor")
+        .bytes(Bytes::from(body))
+        .await;
+
+    let decoded: Value = response.json();
+    assert_eq!(decoded["result"], json!({ "result": 42.0 }));
+}
+
+#[tokio::test]
+async fn test_json_request_with_cbor_accept() {
+    let response = server()
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .add_header("Accept", "application/cbor, application/json;q=0.5")
+        .json(&json!({"method": "discover"}))
+        .await;
+
+    assert_eq!(response.header("content-type"), "application/cbor");
+    let decoded = WireFormat::Cbor.decode(response.as_bytes()).unwrap();
+    assert!(decoded["result"]["tools"].as_array().unwrap().len() > 1);
+}
+
+#[tokio::test]
+async fn test_undecodable_body_rejected() {
+    let response = server()
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .content_type("application/msgpack")
+        .bytes(Bytes::from_static(&[0xc1]))
+        .expect_failure()
+        .await;
+
+    response.assert_status_bad_request();
+    assert!(
+        response
+            .text()
+            .starts_with("Failed to parse the request body as MessagePack")
+    );
+}
+
+#[tokio::test]
+async fn test_unsupported_content_type_rejected() {
+    let response = server()
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .content_type("text/plain")
+        .bytes(Bytes::from_static(b"{}"))
+        .expect_failure()
+        .await;
+
+    response.assert_status(axum::http::StatusCode::UNSUPPORTED_MEDIA_TYPE);
+}
+
+#[tokio::test]
+async fn test_strict_rejection_in_request_format() {
+    let mut options = AppOptions::default();
+    options.config.jsonrpc.strict = true;
+    let state = AppState::new(create_test_credentials_store(), options).unwrap();
+    let server = TestServer::new(mcp_router(state)).unwrap();
+    let body = WireFormat::MessagePack
+        .encode(&json!({"method": "discover"}))
+        .unwrap();
+
+    let response = server
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .content_type("application/msgpack")
+        .bytes(Bytes::from(body))
+        .await;
+
+    let decoded = WireFormat::MessagePack.decode(response.as_bytes()).unwrap();
+    assert_eq!(decoded["error"]["code"], ERROR_INVALID_REQUEST);
+}
@@ -0,0 +1,178 @@
+mod common;
+
+use arc_swap::ArcSwap;
+use axum::extract::State;
+use axum::{Extension, Json};
+use common::*;
+use mcp_server::auth::{AuthenticatedUser, UserCredentials};
+use mcp_server::external_keys::{
+    MissingExternalKey, check_external_keys, find_missing_external_keys,
+};
+use mcp_server::tenant::ToolSet;
+use mcp_server::tools::{ToolContext, ToolFunction};
+use mcp_server::{
+    AppOptions, AppState, ERROR_MISSING_CREDENTIAL, McpRequest, ToolDefinition, handle_mcp_request,
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+fn tool(name: &str, keys: &[&str]) -> ToolDefinition {
+    ToolDefinition {
+        name: name.to_string(),
+        required_external_keys: keys.iter().map(|key| key.to_string()).collect(),
+        ..Default::default()
+    }
+}
+
+fn missing(username: &str, tool: &str, key: &str) -> MissingExternalKey {
+    MissingExternalKey {
+        username: username.to_string(),
+        tool: tool.to_string(),
+        key: key.to_string(),
+    }
+}
+
+#[test]
+fn test_reports_users_lacking_required_keys() {
+    // TEST_USERNAME has no keys, TEST_USERNAME_2 has postgres_url and api_key
+    let credentials = create_multi_user_credentials_store();
+    let tools = vec![
+        tool("query_db", &["postgres_url"]),
+        tool("charge", &["stripe_key"]),
+        tool("get_time", &[]),
+    ];
+
+    let found = find_missing_external_keys(&credentials, &tools, &HashMap::new());
+
+    assert_eq!(
+        found,
+        vec![
+            missing(TEST_USERNAME, "charge", "stripe_key"),
+            missing(TEST_USERNAME, "query_db", "postgres_url"),
+            missing(TEST_USERNAME_2, "charge", "stripe_key"),
+        ]
+    );
+    assert_eq!(
+        found[0].to_string(),
+        "user 'testuser' is missing external key 'stripe_key' required by tool 'charge'"
+    );
+}
+
+#[test]
+fn test_tenant_users_are_checked_against_their_tool_set() {
+    let mut user = create_test_user();
+    user.tenant = Some("acme".to_string());
+    let credentials = Arc::new(HashMap::from([(TEST_API_KEY.to_string(), user)]));
+    let global = vec![tool("query_db", &["postgres_url"])];
+    let tenants = HashMap::from([(
+        "acme".to_string(),
+        ToolSet {
+            definitions: Arc::new(vec![tool("charge", &["stripe_key"])]),
+            ..Default::default()
+        },
+    )]);
+
+    assert_eq!(
+        find_missing_external_keys(&credentials, &global, &tenants),
+        vec![missing(TEST_USERNAME, "charge", "stripe_key")]
+    );
+}
+
+#[test]
+fn test_strict_mode_fails_and_lists_every_user() {
+    let credentials = create_multi_user_credentials_store();
+    let tools = vec![tool("charge", &["stripe_key"])];
+
+    assert!(check_external_keys(&credentials, &tools, &HashMap::new(), false).is_ok());
+
+    let error = check_external_keys(&credentials, &tools, &HashMap::new(), true)
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains(&format!("user '{}'", TEST_USERNAME)));
+    assert!(error.contains(&format!("user '{}'", TEST_USERNAME_2)));
+}
+
+#[test]
+fn test_state_checks_against_registered_tools() {
+    let mut options = AppOptions::default();
+    options.config.tools.strict_external_keys = true;
+    // Built-in tools need no external keys
+    let state = AppState::new(create_test_credentials_store(), options).unwrap();
+
+    state.replace_tools(HashMap::new(), vec![tool("query_db", &["postgres_url"])]);
+    assert!(
+        state
+            .check_external_keys(&create_test_credentials_store())
+            .is_err()
+    );
+
+    let user = UserCredentials::new(
+        TEST_USERNAME.to_string(),
+        TEST_API_KEY.to_string(),
+        HashMap::from([("postgres_url".to_string(), "postgresql://db".to_string())]),
+    );
+    let complete = Arc::new(HashMap::from([(TEST_API_KEY.to_string(), user)]));
+    assert!(state.check_external_keys(&complete).is_ok());
+}
+
+#[tokio::test]
+async fn test_invocation_without_required_key_is_rejected() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+    let query_db: ToolFunction = Box::new(move |_args, ctx: ToolContext| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        let url = ctx.user().get_external_key("postgres_url").cloned();
+        Box::pin(async move { Ok(json!({ "url": url })) })
+    });
+    let state = AppState {
+        tool_registry: Arc::new(ArcSwap::from_pointee(HashMap::from([(
+            "query_db".to_string(),
+            query_db,
+        )]))),
+        tool_definitions: Arc::new(ArcSwap::from_pointee(vec![tool(
+            "query_db",
+            &["postgres_url", "github_token"],
+        )])),
+        ..Default::default()
+    };
+    let invoke = |user: UserCredentials| {
+        let request: McpRequest = serde_json::from_value(json!({
+            "method": "invoke",
+            "params": {"tool_name": "query_db"}
+        }))
+        .unwrap();
+        handle_mcp_request(
+            State(state.clone()),
+            Extension(AuthenticatedUser(user)),
+            Json(request),
+        )
+    };
+
+    let Json(response) = invoke(create_test_user_with_external_keys()).await;
+    let error = response.error.unwrap();
+    assert_eq!(error.code, ERROR_MISSING_CREDENTIAL);
+    assert_eq!(error.message, "User missing credential 'github_token'");
+    assert_eq!(
+        error.data.unwrap(),
+        json!({"tool": "query_db", "missing_keys": ["github_token"]})
+    );
+
+    let Json(response) = invoke(create_test_user()).await;
+    assert_eq!(
+        response.error.unwrap().message,
+        "User missing credential 'postgres_url', 'github_token'"
+    );
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+    let mut user = create_test_user_with_external_keys();
+    user.external_keys
+        .insert("github_token".to_string(), "ghp_test".to_string());
+    let Json(response) = invoke(user).await;
+    assert_eq!(
+        response.result.unwrap()["url"],
+        "postgresql://localhost/testdb"
+    );
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,108 @@
+use mcp_server::extract::{Path, apply_mapping, check_mapping};
+use serde_json::{Value, json};
+
+fn document() -> Value {
+    json!({
+        "location": { "name": "Oslo", "country code": "NO" },
+        "current": { "temp_c": 4.5, "condition": { "text": "Cloudy" } },
+        "forecast": [
+            { "day": "Mon", "max_c": 6, "hours": [{ "t": 1 }, { "t": 2 }] },
+            { "day": "Tue", "max_c": 8, "hours": [{ "t": 3 }] }
+        ]
+    })
+}
+
+fn select(expression: &str) -> Value {
+    Path::parse(expression).unwrap().select(&document())
+}
+
+// ============================================================================
+// Path Tests
+// ============================================================================
+
+#[test]
+fn test_jq_style_paths() {
+    assert_eq!(select("."), document());
+    assert_eq!(select(".location.name"), json!("Oslo"));
+    assert_eq!(select(".location.\"country code\""), json!("NO"));
+    assert_eq!(select(".current.condition.text"), json!("Cloudy"));
+    assert_eq!(select(".forecast[1].day"), json!("Tue"));
+    assert_eq!(select(".forecast[-1].max_c"), json!(8));
+    assert_eq!(select(".forecast.[0].day"), json!("Mon"));
+}
+
+#[test]
+fn test_iteration_collects_per_element() {
+    assert_eq!(select(".forecast[].day"), json!(["Mon", "Tue"]));
+    assert_eq!(select(".forecast[].hours[].t"), json!([[1, 2], [3]]));
+    assert_eq!(select(".location[]"), json!(["NO", "Oslo"]));
+    assert_eq!(select(".current.temp_c[]"), json!([]));
+}
+
+#[test]
+fn test_json_pointers() {
+    assert_eq!(select("/current/temp_c"), json!(4.5));
+    assert_eq!(select("/forecast/0/hours/1/t"), json!(2));
+    assert_eq!(select(""), document());
+}
+
+#[test]
+fn test_missing_values_are_null() {
+    assert_eq!(select(".location.population"), Value::Null);
+    assert_eq!(select(".forecast[5].day"), Value::Null);
+    assert_eq!(select(".forecast[-3]"), Value::Null);
+    assert_eq!(select(".location.name.first"), Value::Null);
+    assert_eq!(select("/nope/0"), Value::Null);
+}
+
+#[test]
+fn test_invalid_paths_rejected() {
+    for (expression, error) in [
+        ("location.name", "expected a jq-style path"),
+        (".a..b", "empty field name"),
+        (".a.", "empty field name"),
+        (".a[1", "unterminated '['"),
+        (".a[x]", "bad index 'x'"),
+        (".\"a", "unterminated quote"),
+        (".a[0]b", "unexpected 'b'"),
+    ] {
+        let err = Path::parse(expression).unwrap_err();
+        assert!(err.to_string().contains(error), "{}: {}", expression, err);
+    }
+}
+
+// ============================================================================
+// Mapping Tests
+// ============================================================================
+
+#[test]
+fn test_mapping_reshapes_document() {
+    let mapping = json!({
+        "city": ".location.name",
+        "temperature": "/current/temp_c",
+        "days": { "names": ".forecast[].day", "highs": ".forecast[].max_c" },
+        "summary": [".current.condition.text", ".forecast[0].max_c"],
+        "source": 1,
+        "metric": true
+    });
+    check_mapping(&mapping).unwrap();
+
+    assert_eq!(
+        apply_mapping(&mapping, &document()).unwrap(),
+        json!({
+            "city": "Oslo",
+            "temperature": 4.5,
+            "days": { "names": ["Mon", "Tue"], "highs": [6, 8] },
+            "summary": ["Cloudy", 6],
+            "source": 1,
+            "metric": true
+        })
+    );
+}
+
+#[test]
+fn test_mapping_with_invalid_path_rejected() {
+    let mapping = json!({ "ok": ".a", "nested": [{ "bad": "a.b" }] });
+    assert!(check_mapping(&mapping).is_err());
+    assert!(apply_mapping(&mapping, &document()).is_err());
+}
@@ -0,0 +1,91 @@
+mod common;
+
+use common::*;
+use mcp_server::auth::AuthenticatedUser;
+use mcp_server::tools::McpTool;
+use mcp_server::tools::generate_id::GenerateIdTool;
+use serde_json::{Value, json};
+use std::collections::HashSet;
+
+async fn generate(args: Value) -> anyhow::Result<Value> {
+    GenerateIdTool
+        .execute(Some(args), AuthenticatedUser(create_test_user()))
+        .await
+}
+
+fn ids(result: &Value) -> Vec<String> {
+    serde_json::from_value(result["ids"].clone()).unwrap()
+}
+
+/// The first generated id
+fn ids_of(result: &Value) -> String {
+    ids(result).remove(0)
+}
+
+#[tokio::test]
+async fn test_defaults_to_one_uuid_v4() {
+    let result = generate(json!({})).await.unwrap();
+
+    assert_eq!(result["kind"], "uuid_v4");
+    let ids = ids(&result);
+    assert_eq!(ids.len(), 1);
+    let uuid = uuid::Uuid::parse_str(&ids[0]).unwrap();
+    assert_eq!(uuid.get_version_num(), 4);
+}
+
+#[tokio::test]
+async fn test_uuid_v7_is_time_ordered() {
+    let result = generate(json!({"kind": "uuid_v7", "count": 20}))
+        .await
+        .unwrap();
+
+    let ids = ids(&result);
+    assert_eq!(ids.len(), 20);
+    assert!(
+        ids.iter()
+            .all(|id| uuid::Uuid::parse_str(id).unwrap().get_version_num() == 7)
+    );
+    let mut sorted = ids.clone();
+    sorted.sort();
+    assert_eq!(ids, sorted);
+}
+
+#[tokio::test]
+async fn test_nanoid_and_bytes() {
+    let result = generate(json!({"kind": "nanoid", "count": 50, "length": 10}))
+        .await
+        .unwrap();
+    let ids = ids(&result);
+    assert!(ids.iter().all(|id| {
+        id.len() == 10
+            && id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    }));
+    assert_eq!(ids.iter().collect::<HashSet<_>>().len(), 50);
+
+    let hex = generate(json!({"kind": "bytes", "length": 8}))
+        .await
+        .unwrap();
+    assert_eq!(ids_of(&hex).len(), 16);
+    assert!(ids_of(&hex).chars().all(|c| c.is_ascii_hexdigit()));
+
+    let base64 = generate(json!({"kind": "bytes", "encoding": "base64"}))
+        .await
+        .unwrap();
+    assert_eq!(ids_of(&base64).len(), 24);
+}
+
+#[tokio::test]
+async fn test_choices_and_limits_are_validated() {
+    let error = generate(json!({"kind": "ulid"})).await.unwrap_err();
+    assert!(error.to_string().contains("must be one of"));
+
+    assert!(generate(json!({"count": 0})).await.is_err());
+    assert!(generate(json!({"count": 101})).await.is_err());
+    assert!(
+        generate(json!({"kind": "bytes", "encoding": "base32"}))
+            .await
+            .is_err()
+    );
+}
@@ -0,0 +1,234 @@
+mod common;
+
+use common::*;
+use mcp_server::auth::{AuthenticatedUser, UserCredentials};
+use mcp_server::config::ServerConfig;
+use mcp_server::tools::git::{GitCloneTool, GitDiffTool, GitListFilesTool, GitReadFileTool};
+use mcp_server::tools::{InvalidInput, McpTool, ToolContext};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .current_dir(dir)
+        .status()
+        .unwrap();
+    assert!(status.success(), "git {:?}", args);
+}
+
+/// Repository `demo.git` with two commits tagged `v1` and `v2`
+fn upstream() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    let path = &dir.path().join("demo.git");
+    std::fs::create_dir(path).unwrap();
+    git(path, &["init", "--quiet", "--initial-branch=main"]);
+    std::fs::create_dir(path.join("src")).unwrap();
+    std::fs::write(path.join("README.md"), "# Demo\n").unwrap();
+    std::fs::write(path.join("src/main.rs"), "fn main() {}\n").unwrap();
+    git(path, &["add", "."]);
+    git(path, &["commit", "--quiet", "-m", "first"]);
+    git(path, &["tag", "v1"]);
+    std::fs::write(path.join("src/main.rs"), "fn main() {\n    run();\n}\n").unwrap();
+    git(path, &["commit", "--quiet", "-am", "second"]);
+    git(path, &["tag", "v2"]);
+    dir
+}
+
+fn url(upstream: &TempDir) -> String {
+    format!("file://{}/demo.git", upstream.path().display())
+}
+
+fn context(root: &Path, username: &str) -> ToolContext {
+    let mut config = ServerConfig::default();
+    config.git.workspace_root = Some(root.to_path_buf());
+    config.git.allowed_remotes = vec!["file://".to_string()];
+    let user = UserCredentials::new(
+        username.to_string(),
+        TEST_API_KEY.to_string(),
+        HashMap::new(),
+    );
+    ToolContext::new(AuthenticatedUser(user)).with_config(Arc::new(config))
+}
+
+async fn run(tool: &dyn McpTool, ctx: &ToolContext, args: Value) -> anyhow::Result<Value> {
+    tool.execute_with_context(Some(args), ctx.clone()).await
+}
+
+fn invalid_input(error: anyhow::Error) -> String {
+    error
+        .downcast_ref::<InvalidInput>()
+        .unwrap()
+        .message
+        .clone()
+}
+
+#[tokio::test]
+async fn test_clone_list_read_and_diff() {
+    let upstream = upstream();
+    let url = url(&upstream);
+    let root = TempDir::new().unwrap();
+    let ctx = context(root.path(), "alice");
+
+    let cloned = run(
+        &GitCloneTool,
+        &ctx,
+        json!({"url": url, "workspace": "demo", "depth": 2}),
+    )
+    .await
+    .unwrap();
+    assert_eq!(cloned["workspace"], "demo");
+    assert_eq!(cloned["head"].as_str().unwrap().len(), 40);
+    assert!(root.path().join("alice/demo/.git").is_dir());
+
+    let files = run(&GitListFilesTool, &ctx, json!({"workspace": "demo"}))
+        .await
+        .unwrap();
+    assert_eq!(files["files"], json!(["README.md", "src/main.rs"]));
+    let files = run(
+        &GitListFilesTool,
+        &ctx,
+        json!({"workspace": "demo", "path": "src"}),
+    )
+    .await
+    .unwrap();
+    assert_eq!(files["files"], json!(["src/main.rs"]));
+
+    let file = run(
+        &GitReadFileTool,
+        &ctx,
+        json!({"workspace": "demo", "path": "src/main.rs", "ref": "HEAD~1"}),
+    )
+    .await
+    .unwrap();
+    assert_eq!(file["content"], "fn main() {}\n");
+    assert_eq!(file["truncated"], false);
+
+    let diff = run(
+        &GitDiffTool,
+        &ctx,
+        json!({"workspace": "demo", "from": "HEAD~1"}),
+    )
+    .await
+    .unwrap();
+    let diff = diff["diff"].as_str().unwrap();
+    assert!(diff.contains("--- a/src/main.rs"));
+    assert!(diff.contains("+    run();"));
+
+    // Workspaces belong to their user
+    let error = run(
+        &GitListFilesTool,
+        &context(root.path(), "bob"),
+        json!({"workspace": "demo"}),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(invalid_input(error), "workspace 'demo' does not exist");
+}
+
+#[tokio::test]
+async fn test_clone_defaults_and_limits() {
+    let upstream = upstream();
+    let url = format!("{}/", url(&upstream));
+    let root = TempDir::new().unwrap();
+    let ctx = context(root.path(), "alice");
+
+    let cloned = run(&GitCloneTool, &ctx, json!({"url": url, "branch": "v1"}))
+        .await
+        .unwrap();
+    assert_eq!(cloned["workspace"], "demo");
+    let file = run(
+        &GitReadFileTool,
+        &ctx,
+        json!({"workspace": "demo", "path": "src/main.rs"}),
+    )
+    .await
+    .unwrap();
+    assert_eq!(file["content"], "fn main() {}\n");
+
+    let error = run(&GitCloneTool, &ctx, json!({"url": url}))
+        .await
+        .unwrap_err();
+    assert_eq!(invalid_input(error), "workspace 'demo' already exists");
+
+    let error = run(
+        &GitCloneTool,
+        &ctx,
+        json!({"url": "https://example.com/repo.git"}),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        invalid_input(error),
+        "remote 'https://example.com/repo.git' is not allowed"
+    );
+
+    // A failed clone leaves no workspace behind
+    let missing = format!("file://{}/missing", root.path().display());
+    let error = run(&GitCloneTool, &ctx, json!({"url": missing}))
+        .await
+        .unwrap_err();
+    assert!(error.to_string().starts_with("git clone failed"));
+    assert!(!root.path().join("alice/missing").exists());
+}
+
+#[tokio::test]
+async fn test_arguments_cannot_escape_or_inject_options() {
+    let upstream = upstream();
+    let url = url(&upstream);
+    let root = TempDir::new().unwrap();
+    let ctx = context(root.path(), "alice");
+    run(
+        &GitCloneTool,
+        &ctx,
+        json!({"url": url, "workspace": "demo"}),
+    )
+    .await
+    .unwrap();
+
+    let cases = [
+        (
+            json!({"workspace": "../bob"}),
+            "invalid workspace name '../bob'",
+        ),
+        (
+            json!({"workspace": "demo", "ref": "--output=/tmp/x"}),
+            "invalid revision '--output=/tmp/x'",
+        ),
+        (
+            json!({"workspace": "demo", "path": "../../etc"}),
+            "invalid path '../../etc'",
+        ),
+    ];
+    for (args, message) in cases {
+        let error = run(&GitListFilesTool, &ctx, args).await.unwrap_err();
+        assert_eq!(invalid_input(error), message);
+    }
+
+    let error = run(
+        &GitReadFileTool,
+        &ctx,
+        json!({"workspace": "demo", "path": "nope.txt"}),
+    )
+    .await
+    .unwrap_err();
+    assert!(invalid_input(error).contains("nope.txt"));
+}
+
+#[tokio::test]
+async fn test_disabled_without_workspace_root() {
+    let ctx = ToolContext::new(AuthenticatedUser(create_test_user()));
+
+    let error = run(&GitListFilesTool, &ctx, json!({"workspace": "demo"}))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "git tools are disabled: [git] workspace_root is not set"
+    );
+}
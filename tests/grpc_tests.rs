@@ -0,0 +1,198 @@
+#![cfg(feature = "grpc")]
+
+mod common;
+
+use common::*;
+use mcp_server::grpc::{
+    DiscoverRequest, ERROR_CODE_METADATA, InvokeRequest, McpClient, error_status, serve,
+};
+use mcp_server::{
+    AppOptions, AppState, ERROR_AUTH, ERROR_INVALID_PARAMS, ERROR_METHOD_NOT_FOUND, ERROR_TIMEOUT,
+    ErrorDetails,
+};
+use serde_json::{Value, json};
+use tonic::{Code, Request, Status};
+
+/// Serve the gRPC service for the test credentials and connect a client
+async fn client() -> McpClient {
+    let state = AppState::new(create_test_credentials_store(), AppOptions::default()).unwrap();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(serve(state, listener));
+    McpClient::connect(format!("http://{}", addr))
+        .await
+        .unwrap()
+}
+
+fn authorized<T>(message: T, api_key: &str) -> Request<T> {
+    let mut request = Request::new(message);
+    request.metadata_mut().insert(
+        "authorization",
+        format!("Bearer {}", api_key).parse().unwrap(),
+    );
+    request
+}
+
+fn invoke_request(tool_name: &str, arguments: Value) -> InvokeRequest {
+    InvokeRequest {
+        tool_name: tool_name.to_string(),
+        arguments_json: arguments.to_string(),
+        ..Default::default()
+    }
+}
+
+fn mcp_error_code(status: &Status) -> i32 {
+    status
+        .metadata()
+        .get(ERROR_CODE_METADATA)
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap()
+}
+
+// ============================================================================
+// Authentication Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_missing_token_unauthenticated() {
+    let mut client = client().await;
+
+    let status = client.discover(DiscoverRequest {}).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+    assert_eq!(status.message(), "Missing authorization metadata");
+    assert_eq!(mcp_error_code(&status), ERROR_AUTH);
+}
+
+#[tokio::test]
+async fn test_invalid_token_unauthenticated() {
+    let mut client = client().await;
+
+    let status = client
+        .invoke(authorized(
+            invoke_request("calculate", json!({"expression": "1 + 1"})),
+            "wrong-key",
+        ))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+    assert_eq!(status.message(), "Invalid or expired API key");
+}
+
+// ============================================================================
+// Discover and Invoke Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_discover_lists_registered_tools() {
+    let mut client = client().await;
+
+    let response = client
+        .discover(authorized(DiscoverRequest {}, TEST_API_KEY))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(response.instructions, None);
+    let tools = response.tools;
+    let calculate = tools.iter().find(|tool| tool.name == "calculate").unwrap();
+    let parameters: Value = serde_json::from_str(&calculate.parameters_json).unwrap();
+    assert_eq!(parameters["type"], "object");
+    assert!(!calculate.description.is_empty());
+}
+
+#[tokio::test]
+async fn test_invoke_returns_result_json() {
+    let mut client = client().await;
+
+    let response = client
+        .invoke(authorized(
+            invoke_request("calculate", json!({"expression": "(1 + 2) * 3"})),
+            TEST_API_KEY,
+        ))
+        .await
+        .unwrap()
+        .into_inner();
+    let result: Value = serde_json::from_str(&response.result_json).unwrap();
+    assert_eq!(result, json!({ "result": 9.0 }));
+    assert_eq!(response.meta_json, None);
+}
+
+#[tokio::test]
+async fn test_invoke_errors_mapped_to_status() {
+    let mut client = client().await;
+
+    let status = client
+        .invoke(authorized(
+            invoke_request("calculate", json!({"expression": "2 * y"})),
+            TEST_API_KEY,
+        ))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+    assert_eq!(mcp_error_code(&status), ERROR_INVALID_PARAMS);
+    let data: Value = serde_json::from_slice(status.details()).unwrap();
+    assert_eq!(data, json!({ "position": 4 }));
+
+    let status = client
+        .invoke(authorized(
+            invoke_request("no_such_tool", json!({})),
+            TEST_API_KEY,
+        ))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::NotFound);
+    assert_eq!(mcp_error_code(&status), ERROR_METHOD_NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_malformed_arguments_json_rejected() {
+    let mut client = client().await;
+
+    let mut request = invoke_request("calculate", json!({}));
+    request.arguments_json = "{not json".to_string();
+    let status = client
+        .invoke(authorized(request, TEST_API_KEY))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+    assert!(status.message().starts_with("Invalid arguments_json"));
+}
+
+#[tokio::test]
+async fn test_deeply_nested_arguments_json_rejected() {
+    let mut client = client().await;
+
+    let nested = (0..100).fold(json!(1), |inner, _| json!([inner]));
+    let request = invoke_request("calculate", json!({ "expression": nested }));
+    let status = client
+        .invoke(authorized(request, TEST_API_KEY))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+    assert!(status.message().contains("nesting deeper than 64 levels"));
+}
+
+#[test]
+fn test_error_status_without_data() {
+    let status = error_status(ErrorDetails {
+        code: -32603,
+        message: "Internal error".to_string(),
+        data: None,
+    });
+    assert_eq!(status.code(), Code::Internal);
+    assert!(status.details().is_empty());
+    assert_eq!(mcp_error_code(&status), -32603);
+}
+
+#[test]
+fn test_timeout_maps_to_deadline_exceeded() {
+    let status = error_status(ErrorDetails {
+        code: ERROR_TIMEOUT,
+        message: "Timed out".to_string(),
+        data: Some(json!({ "limit": "client" })),
+    });
+    assert_eq!(status.code(), Code::DeadlineExceeded);
+    assert_eq!(mcp_error_code(&status), ERROR_TIMEOUT);
+}
@@ -0,0 +1,172 @@
+mod common;
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use common::*;
+use mcp_server::auth::UserCredentials;
+use mcp_server::tools::health::{check_tools, overall_status};
+use mcp_server::tools::{HealthCheck, HealthStatus, initialize_all_tools};
+use mcp_server::{AppOptions, AppState, ToolDefinition, mcp_router};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+const ADMIN_API_KEY: &str = "admin-api-key-000";
+
+fn tool(name: &str, status: HealthStatus, delay: Duration) -> ToolDefinition {
+    ToolDefinition {
+        name: name.to_string(),
+        health_check: Some(HealthCheck::new(move || {
+            let status = status.clone();
+            Box::pin(async move {
+                tokio::time::sleep(delay).await;
+                status
+            })
+        })),
+        ..Default::default()
+    }
+}
+
+fn server(check_tools_on_ready: bool, definitions: Vec<ToolDefinition>) -> TestServer {
+    let mut store = (*create_test_credentials_store()).clone();
+    store.insert(
+        ADMIN_API_KEY.to_string(),
+        UserCredentials::new(
+            "admin".to_string(),
+            ADMIN_API_KEY.to_string(),
+            HashMap::new(),
+        )
+        .with_admin(true),
+    );
+    let mut options = AppOptions::default();
+    options.config.health.check_tools_on_ready = check_tools_on_ready;
+    options.config.health.timeout_ms = 100;
+    let state = AppState::new(Arc::new(store), options).unwrap();
+    state.replace_tools(HashMap::new(), definitions);
+    TestServer::new(mcp_router(state)).unwrap()
+}
+
+#[tokio::test]
+async fn test_registered_tools_default_to_healthy() {
+    let (_, definitions) = initialize_all_tools().unwrap();
+
+    let results = check_tools(&definitions, Duration::from_secs(1)).await;
+
+    assert!(
+        results
+            .iter()
+            .any(|result| result.tool == "get_current_time")
+    );
+    assert!(
+        results
+            .iter()
+            .all(|result| result.status == HealthStatus::Healthy)
+    );
+    assert_eq!(overall_status(&results), "healthy");
+}
+
+#[tokio::test]
+async fn test_slow_checks_time_out_and_worst_status_wins() {
+    let definitions = vec![
+        tool("slow", HealthStatus::Healthy, Duration::from_secs(5)),
+        tool(
+            "cache",
+            HealthStatus::Degraded("high latency".into()),
+            Duration::ZERO,
+        ),
+        // Tools without a check are not reported
+        ToolDefinition {
+            name: "plugin".to_string(),
+            ..Default::default()
+        },
+    ];
+
+    let results = check_tools(&definitions, Duration::from_millis(50)).await;
+
+    let tools: Vec<&str> = results.iter().map(|result| result.tool.as_str()).collect();
+    assert_eq!(tools, vec!["cache", "slow"]);
+    assert_eq!(
+        results[1].status,
+        HealthStatus::Unhealthy("health check timed out after 50ms".into())
+    );
+    assert_eq!(overall_status(&results), "unhealthy");
+    assert_eq!(overall_status(&results[..1]), "degraded");
+}
+
+#[tokio::test]
+async fn test_ready_fails_on_unhealthy_tool_when_enabled() {
+    let definitions = || {
+        vec![
+            tool(
+                "db",
+                HealthStatus::Unhealthy("connection refused".into()),
+                Duration::ZERO,
+            ),
+            tool(
+                "cache",
+                HealthStatus::Degraded("high latency".into()),
+                Duration::ZERO,
+            ),
+        ]
+    };
+
+    let ready = server(true, definitions()).get("/ready").await;
+    ready.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+    ready.assert_text("UNHEALTHY");
+
+    server(false, definitions())
+        .get("/ready")
+        .await
+        .assert_text("READY");
+
+    let degraded = vec![tool(
+        "cache",
+        HealthStatus::Degraded("slow".into()),
+        Duration::ZERO,
+    )];
+    server(true, degraded)
+        .get("/ready")
+        .await
+        .assert_text("READY");
+}
+
+#[tokio::test]
+async fn test_detailed_health_reports_each_tool() {
+    let server = server(
+        false,
+        vec![
+            tool(
+                "db",
+                HealthStatus::Unhealthy("connection refused".into()),
+                Duration::ZERO,
+            ),
+            tool("search", HealthStatus::Healthy, Duration::ZERO),
+        ],
+    );
+
+    server
+        .get("/admin/health/detailed")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .await
+        .assert_status_forbidden();
+
+    let response = server
+        .get("/admin/health/detailed")
+        .add_header("Authorization", format!("Bearer {}", ADMIN_API_KEY))
+        .await;
+    response.assert_status_ok();
+    let body: Value = response.json();
+    let result = &body["result"];
+
+    assert_eq!(result["status"], "unhealthy");
+    assert_eq!(result["draining"], false);
+    let tools = result["tools"].as_array().unwrap();
+    assert_eq!(tools[0]["tool"], "db");
+    assert_eq!(tools[0]["status"], "unhealthy");
+    assert_eq!(tools[0]["message"], "connection refused");
+    assert!(tools[0]["duration_ms"].is_number());
+    assert_eq!(tools[1]["status"], "healthy");
+    assert!(tools[1].get("message").is_none());
+    assert_eq!(tools.len(), 2, "{}", json!(tools));
+}
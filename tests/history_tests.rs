@@ -0,0 +1,225 @@
+mod common;
+
+use axum_test::TestServer;
+use chrono::Utc;
+use common::*;
+use mcp_server::audit::AuditStatus;
+use mcp_server::auth::UserCredentials;
+use mcp_server::config::HistoryConfig;
+use mcp_server::history::{InvocationHistory, InvocationRecord};
+use mcp_server::{ERROR_AUTH, create_app};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const ADMIN_API_KEY: &str = "admin-api-key-000";
+
+fn record(username: &str, tool_name: &str) -> InvocationRecord {
+    let now = Utc::now();
+    InvocationRecord {
+        tool_name: tool_name.to_string(),
+        username: username.to_string(),
+        started_at: now,
+        finished_at: now,
+        duration_ms: 0,
+        status: AuditStatus::Success,
+        error: None,
+    }
+}
+
+fn credentials_with_admin() -> mcp_server::auth::CredentialsStore {
+    let mut store = (*create_multi_user_credentials_store()).clone();
+    store.insert(
+        ADMIN_API_KEY.to_string(),
+        UserCredentials::new("admin".to_string(), ADMIN_API_KEY.to_string(), HashMap::new())
+            .with_admin(true),
+    );
+    Arc::new(store)
+}
+
+// ============================================================================
+// InvocationHistory Tests
+// ============================================================================
+
+#[test]
+fn test_history_lists_newest_first() {
+    let history = InvocationHistory::new(HistoryConfig::default());
+    history.record(record("alice", "first"));
+    history.record(record("alice", "second"));
+
+    let entries = history.list("alice", None);
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].tool_name, "second");
+    assert_eq!(entries[1].tool_name, "first");
+}
+
+#[test]
+fn test_history_is_bounded_per_user() {
+    let history = InvocationHistory::new(HistoryConfig {
+        enabled: true,
+        capacity: 2,
+    });
+    for tool in ["a", "b", "c"] {
+        history.record(record("alice", tool));
+    }
+    history.record(record("bob", "x"));
+
+    let alice = history.list("alice", None);
+    assert_eq!(alice.len(), 2);
+    assert_eq!(alice[0].tool_name, "c");
+    assert_eq!(alice[1].tool_name, "b");
+    // Other users have their own buffer
+    assert_eq!(history.list("bob", None).len(), 1);
+}
+
+#[test]
+fn test_history_limit_and_unknown_user() {
+    let history = InvocationHistory::new(HistoryConfig::default());
+    for tool in ["a", "b", "c"] {
+        history.record(record("alice", tool));
+    }
+
+    assert_eq!(history.list("alice", Some(1)).len(), 1);
+    assert!(history.list("nobody", None).is_empty());
+}
+
+#[test]
+fn test_history_disabled_records_nothing() {
+    let history = InvocationHistory::new(HistoryConfig {
+        enabled: false,
+        capacity: 10,
+    });
+    history.record(record("alice", "a"));
+
+    assert!(history.list("alice", None).is_empty());
+}
+
+#[test]
+fn test_history_list_all_spans_users() {
+    let history = InvocationHistory::new(HistoryConfig::default());
+    history.record(record("alice", "a"));
+    history.record(record("bob", "b"));
+
+    assert_eq!(history.list_all(None).len(), 2);
+    assert_eq!(history.list_all(Some(1)).len(), 1);
+}
+
+// ============================================================================
+// history/list Method Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_history_list_method_returns_own_invocations() {
+    let app = create_app(create_multi_user_credentials_store()).unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    for key in [TEST_API_KEY, TEST_API_KEY, TEST_API_KEY_2] {
+        server
+            .post("/mcp")
+            .add_header("Authorization", format!("Bearer {}", key))
+            .json(&json!({"method": "invoke", "params": {"tool_name": "get_current_time"}}))
+            .await;
+    }
+    // Failed invocations are recorded too
+    server
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .json(&json!({
+            "method": "invoke",
+            "params": {"tool_name": "get_current_time", "arguments": {"bad": 1}}
+        }))
+        .await;
+
+    let body: Value = server
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .json(&json!({"method": "history/list"}))
+        .await
+        .json();
+
+    let invocations = body["result"]["invocations"].as_array().unwrap();
+    assert_eq!(invocations.len(), 3);
+    assert!(invocations.iter().all(|i| i["username"] == TEST_USERNAME));
+    assert_eq!(invocations[0]["status"], "error");
+    assert!(invocations[0]["error"].as_str().unwrap().contains("Unexpected parameter"));
+    assert_eq!(invocations[1]["status"], "success");
+    assert_eq!(invocations[1]["tool_name"], "get_current_time");
+    assert!(invocations[1]["duration_ms"].is_u64());
+}
+
+#[tokio::test]
+async fn test_history_list_method_with_limit() {
+    let server = TestServer::new(create_app(create_test_credentials_store()).unwrap()).unwrap();
+
+    for _ in 0..3 {
+        server
+            .post("/mcp")
+            .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+            .json(&json!({"method": "invoke", "params": {"tool_name": "get_current_time"}}))
+            .await;
+    }
+
+    let body: Value = server
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .json(&json!({"method": "history/list", "params": {"limit": 2}}))
+        .await
+        .json();
+
+    assert_eq!(body["result"]["invocations"].as_array().unwrap().len(), 2);
+}
+
+// ============================================================================
+// Admin Endpoint Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_admin_history_requires_admin() {
+    let server = TestServer::new(create_app(credentials_with_admin()).unwrap()).unwrap();
+
+    let response = server
+        .get("/admin/history")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .await;
+
+    response.assert_status_forbidden();
+    let body: Value = response.json();
+    assert_eq!(body["error"]["code"], ERROR_AUTH);
+}
+
+#[tokio::test]
+async fn test_admin_history_requires_authentication() {
+    let server = TestServer::new(create_app(credentials_with_admin()).unwrap()).unwrap();
+
+    server.get("/admin/history").await.assert_status_unauthorized();
+}
+
+#[tokio::test]
+async fn test_admin_history_lists_all_users_and_filters() {
+    let server = TestServer::new(create_app(credentials_with_admin()).unwrap()).unwrap();
+
+    for key in [TEST_API_KEY, TEST_API_KEY_2] {
+        server
+            .post("/mcp")
+            .add_header("Authorization", format!("Bearer {}", key))
+            .json(&json!({"method": "invoke", "params": {"tool_name": "get_current_time"}}))
+            .await;
+    }
+
+    let body: Value = server
+        .get("/admin/history")
+        .add_header("Authorization", format!("Bearer {}", ADMIN_API_KEY))
+        .await
+        .json();
+    assert_eq!(body["result"]["invocations"].as_array().unwrap().len(), 2);
+
+    let body: Value = server
+        .get("/admin/history")
+        .add_query_param("user", TEST_USERNAME_2)
+        .add_header("Authorization", format!("Bearer {}", ADMIN_API_KEY))
+        .await
+        .json();
+    let invocations = body["result"]["invocations"].as_array().unwrap();
+    assert_eq!(invocations.len(), 1);
+    assert_eq!(invocations[0]["username"], TEST_USERNAME_2);
+}
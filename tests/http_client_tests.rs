@@ -0,0 +1,386 @@
+mod common;
+
+use axum::Router;
+use axum::http::HeaderMap;
+use axum::response::Redirect;
+use axum::routing::get;
+use common::*;
+use mcp_server::auth::AuthenticatedUser;
+use mcp_server::config::{EgressConfig, HttpConfig, ServerConfig};
+use mcp_server::http_client::{EgressDenied, EgressPolicy, HttpClient, is_private};
+use mcp_server::tools::ToolContext;
+use std::error::Error;
+use std::io::Write;
+use std::time::Duration;
+use tempfile::NamedTempFile;
+
+/// Server answering `/agent` with the request's `User-Agent`, `/slow` after
+/// 300 ms, and `/leave` and `/metadata` with redirects to `metadata.internal`
+/// and `169.254.169.254`
+async fn mock_server() -> String {
+    async fn agent(headers: HeaderMap) -> String {
+        headers["user-agent"].to_str().unwrap().to_string()
+    }
+
+    async fn slow() -> &'static str {
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        "done"
+    }
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = Router::new()
+        .route("/agent", get(agent))
+        .route("/slow", get(slow))
+        .route(
+            "/leave",
+            get(|| async { Redirect::temporary("http://metadata.internal/") }),
+        )
+        .route(
+            "/metadata",
+            get(|| async { Redirect::temporary("http://169.254.169.254/latest") }),
+        );
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    format!("http://{}", addr)
+}
+
+/// Host rules only, without the private address guard
+fn egress(allow: &[&str], deny: &[&str]) -> EgressConfig {
+    EgressConfig {
+        allow: allow.iter().map(|host| host.to_string()).collect(),
+        deny: deny.iter().map(|host| host.to_string()).collect(),
+        block_private_addresses: false,
+        ..Default::default()
+    }
+}
+
+/// Default config, except that the mock server on 127.0.0.1 is reachable
+fn local_config() -> HttpConfig {
+    HttpConfig {
+        egress: EgressConfig {
+            private_allow: vec!["127.0.0.1".to_string()],
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+fn check(policy: &EgressPolicy, url: &str) -> Result<(), EgressDenied> {
+    policy.check(&url.parse().unwrap())
+}
+
+// ============================================================================
+// Config Tests
+// ============================================================================
+
+#[test]
+fn test_http_config_defaults_and_parsing() {
+    let defaults = HttpConfig::default();
+    assert_eq!(defaults.timeout_ms, 30_000);
+    assert!(defaults.user_agent.starts_with("mcp-server/"));
+    assert!(!defaults.accept_invalid_certs);
+
+    let config: ServerConfig = toml::from_str(
+        r#"
+        [http]
+        timeout_ms = 5000
+        pool_max_idle_per_host = 4
+        user_agent = "acme-mcp"
+        proxy = "http://proxy.internal:3128"
+
+        [http.egress]
+        deny = ["169.254.169.254"]
+        "#,
+    )
+    .unwrap();
+    assert_eq!(config.http.timeout_ms, 5000);
+    assert_eq!(config.http.pool_max_idle_per_host, 4);
+    assert_eq!(config.http.user_agent, "acme-mcp");
+    assert_eq!(config.http.connect_timeout_ms, 10_000);
+    assert_eq!(
+        config.http.proxy.as_deref(),
+        Some("http://proxy.internal:3128")
+    );
+    assert_eq!(config.http.egress.deny, vec!["169.254.169.254"]);
+    assert!(config.http.egress.allow.is_empty());
+}
+
+#[test]
+fn test_invalid_ca_certs_rejected() {
+    let missing = HttpConfig {
+        ca_certs: vec!["/nonexistent/ca.pem".into()],
+        ..Default::default()
+    };
+    let error = HttpClient::new(&missing).err().unwrap();
+    assert!(error.to_string().contains("Failed to read CA certificate"));
+
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(b"not a certificate").unwrap();
+    let invalid = HttpConfig {
+        ca_certs: vec![file.path().to_path_buf()],
+        ..Default::default()
+    };
+    assert!(HttpClient::new(&invalid).is_err());
+}
+
+// ============================================================================
+// Request Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_configured_user_agent_sent() {
+    let url = mock_server().await;
+    let client = HttpClient::new(&HttpConfig {
+        user_agent: "acme-mcp/1.0".to_string(),
+        ..local_config()
+    })
+    .unwrap();
+
+    let ctx = ToolContext::new(AuthenticatedUser(create_test_user())).with_http(client);
+    let agent = ctx
+        .http()
+        .get(&format!("{}/agent", url))
+        .unwrap()
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert_eq!(agent, "acme-mcp/1.0");
+}
+
+#[tokio::test]
+async fn test_config_timeout_overridden_per_request() {
+    let url = format!("{}/slow", mock_server().await);
+    let client = HttpClient::new(&HttpConfig {
+        timeout_ms: 50,
+        ..local_config()
+    })
+    .unwrap();
+
+    let error = client.get(&url).unwrap().send().await.unwrap_err();
+    assert!(error.is_timeout());
+
+    let response = client
+        .get(&url)
+        .unwrap()
+        .timeout(Duration::from_secs(5))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.text().await.unwrap(), "done");
+}
+
+// ============================================================================
+// Egress Tests
+// ============================================================================
+
+#[test]
+fn test_empty_egress_rules_allow_everything() {
+    let policy = EgressPolicy::new(&egress(&[], &[])).unwrap();
+    assert!(check(&policy, "https://api.example.com/v1").is_ok());
+    assert!(check(&policy, "http://10.0.0.1:8080/").is_ok());
+}
+
+#[test]
+fn test_allowlist_with_wildcards() {
+    let policy = EgressPolicy::new(&egress(&["*.example.com", "127.0.0.1"], &[])).unwrap();
+    assert!(check(&policy, "https://api.example.com/v1").is_ok());
+    assert!(check(&policy, "https://API.Example.COM/").is_ok());
+    assert!(check(&policy, "http://127.0.0.1:9000/").is_ok());
+
+    let denied = check(&policy, "https://example.org/").unwrap_err();
+    assert_eq!(
+        denied,
+        EgressDenied {
+            host: "example.org".to_string(),
+            rule: None,
+            address: None,
+        }
+    );
+    assert_eq!(denied.to_string(), "Egress to 'example.org' is not allowed");
+}
+
+#[test]
+fn test_denylist_wins_over_allowlist() {
+    let policy =
+        EgressPolicy::new(&egress(&["*"], &["169.254.169.254", "*.internal", "::1"])).unwrap();
+    assert!(check(&policy, "https://api.example.com/").is_ok());
+
+    let denied = check(&policy, "http://169.254.169.254/latest/meta-data").unwrap_err();
+    assert_eq!(denied.rule.as_deref(), Some("169.254.169.254"));
+    assert!(check(&policy, "http://vault.internal/").is_err());
+    assert!(check(&policy, "http://[::1]:8080/").is_err());
+}
+
+#[tokio::test]
+async fn test_denied_request_fails_before_sending() {
+    let client = HttpClient::new(&HttpConfig {
+        egress: egress(&["api.example.com"], &[]),
+        ..Default::default()
+    })
+    .unwrap();
+
+    let error = client.get("http://127.0.0.1:1/").unwrap_err();
+    let denied = error.downcast_ref::<EgressDenied>().unwrap();
+    assert_eq!(denied.host, "127.0.0.1");
+    assert!(client.get("not a url").is_err());
+}
+
+#[tokio::test]
+async fn test_redirect_to_denied_host_refused() {
+    let url = mock_server().await;
+    let client = HttpClient::new(&HttpConfig {
+        egress: egress(&[], &["*.internal"]),
+        ..Default::default()
+    })
+    .unwrap();
+
+    let error = client
+        .get(&format!("{}/leave", url))
+        .unwrap()
+        .send()
+        .await
+        .unwrap_err();
+    assert!(error.is_redirect());
+}
+
+// ============================================================================
+// Proxy Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_requests_sent_through_proxy() {
+    // The mock server routes by path, so it answers proxied requests too
+    let proxy = mock_server().await;
+    let client = HttpClient::new(&HttpConfig {
+        proxy: Some(proxy),
+        user_agent: "proxied".to_string(),
+        ..Default::default()
+    })
+    .unwrap();
+
+    let agent = client
+        .get("http://upstream.invalid/agent")
+        .unwrap()
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert_eq!(agent, "proxied");
+}
+
+#[test]
+fn test_invalid_proxy_rejected() {
+    let config = HttpConfig {
+        proxy: Some("not a proxy url".to_string()),
+        ..Default::default()
+    };
+    let error = HttpClient::new(&config).err().unwrap();
+    assert!(error.to_string().contains("Invalid http.proxy"));
+}
+
+// ============================================================================
+// Private Address Tests
+// ============================================================================
+
+#[test]
+fn test_private_addresses_classified() {
+    for address in [
+        "127.0.0.1",
+        "10.1.2.3",
+        "172.16.0.1",
+        "192.168.1.1",
+        "169.254.169.254",
+        "100.64.0.1",
+        "0.0.0.0",
+        "::1",
+        "fd00::1",
+        "fe80::1",
+        "::ffff:10.0.0.1",
+    ] {
+        assert!(is_private(address.parse().unwrap()), "{}", address);
+    }
+    for address in ["8.8.8.8", "100.128.0.1", "2606:4700::1111"] {
+        assert!(!is_private(address.parse().unwrap()), "{}", address);
+    }
+}
+
+#[test]
+fn test_private_address_literals_blocked_by_default() {
+    let client = HttpClient::new(&HttpConfig::default()).unwrap();
+    for url in [
+        "http://10.0.0.1/",
+        "http://[::1]:8080/",
+        "http://169.254.169.254/",
+    ] {
+        let error = client.get(url).unwrap_err();
+        let denied = error.downcast_ref::<EgressDenied>().unwrap();
+        assert!(denied.address.is_some(), "{}", url);
+    }
+    assert_eq!(
+        client.get("http://10.0.0.1/").unwrap_err().to_string(),
+        "Egress to '10.0.0.1' denied: 10.0.0.1 is a private address"
+    );
+    assert!(client.get("https://api.example.com/").is_ok());
+}
+
+#[test]
+fn test_private_allow_exempts_networks() {
+    let policy = EgressPolicy::new(&EgressConfig {
+        private_allow: vec!["10.1.0.0/16".to_string()],
+        ..Default::default()
+    })
+    .unwrap();
+    assert!(check(&policy, "http://10.1.2.3/").is_ok());
+    assert!(check(&policy, "http://10.2.0.1/").is_err());
+
+    let invalid = EgressConfig {
+        private_allow: vec!["10.1.0.0/99".to_string()],
+        ..Default::default()
+    };
+    assert!(EgressPolicy::new(&invalid).is_err());
+}
+
+#[tokio::test]
+async fn test_names_resolving_to_private_addresses_blocked() {
+    let url = mock_server().await.replace("127.0.0.1", "localhost");
+    let client = HttpClient::new(&HttpConfig::default()).unwrap();
+
+    // The name passes the URL check and is refused once resolved
+    let request = client.get(&format!("{}/agent", url)).unwrap();
+    let error = request.send().await.unwrap_err();
+    let denied = std::iter::successors(Some(&error as &dyn Error), |e| (*e).source())
+        .find_map(|e| e.downcast_ref::<EgressDenied>())
+        .unwrap();
+    assert_eq!(denied.host, "localhost");
+    assert!(denied.address.unwrap().is_loopback());
+
+    let client = HttpClient::new(&local_config()).unwrap();
+    let response = client
+        .get(&format!("{}/agent", url))
+        .unwrap()
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+}
+
+#[tokio::test]
+async fn test_redirect_to_private_address_refused() {
+    let url = mock_server().await;
+    let client = HttpClient::new(&local_config()).unwrap();
+
+    let error = client
+        .get(&format!("{}/metadata", url))
+        .unwrap()
+        .send()
+        .await
+        .unwrap_err();
+    assert!(error.is_redirect());
+}
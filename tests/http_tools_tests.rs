@@ -0,0 +1,325 @@
+mod common;
+
+use axum::Router;
+use axum::body::Bytes;
+use axum::http::{HeaderMap, Method, StatusCode, Uri};
+use axum::response::IntoResponse;
+use axum::routing::{any, get};
+use common::*;
+use mcp_server::auth::{AuthenticatedUser, UserCredentials};
+use mcp_server::http_tools::encode_url_component;
+use mcp_server::plugins::{PluginManifest, load_plugins};
+use mcp_server::tools::{InvalidInput, ToolContext};
+use serde_json::{Value, json};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use tempfile::TempDir;
+
+/// Endpoint answering every request with what it received
+async fn mock_api() -> String {
+    async fn echo(method: Method, uri: Uri, headers: HeaderMap, body: Bytes) -> impl IntoResponse {
+        axum::Json(json!({
+            "method": method.as_str(),
+            "path": uri.path(),
+            "query": uri.query(),
+            "token": headers.get("x-api-token").and_then(|v| v.to_str().ok()),
+            "user": headers.get("x-user").and_then(|v| v.to_str().ok()),
+            "body": serde_json::from_slice::<Value>(&body).ok(),
+        }))
+    }
+
+    let app = Router::new()
+        .route(
+            "/fail",
+            get(|| async { (StatusCode::BAD_GATEWAY, "upstream down") }),
+        )
+        .route("/text", get(|| async { "plain text\n" }))
+        .route("/{*rest}", any(echo));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}", addr)
+}
+
+fn lookup_manifest(base: &str) -> String {
+    format!(
+        r#"
+name = "lookup_city"
+description = "Look up a city"
+
+[parameters]
+type = "object"
+required = ["city"]
+
+[parameters.properties.city]
+type = "string"
+
+[parameters.properties.units]
+type = "string"
+
+[http]
+url = "{}/cities/{{{{args.city}}}}"
+query = {{ units = "{{{{args.units}}}}", v = "2" }}
+headers = {{ X-Api-Token = "{{{{external_keys.city_api_key}}}}", X-User = "{{{{username}}}}" }}
+"#,
+        base
+    )
+}
+
+const SEARCH_MANIFEST_YAML: &str = r#"
+name: search_docs
+description: Search the documentation
+parameters:
+  type: object
+  properties:
+    query: { type: string }
+    limit: { type: integer }
+    tags: { type: array }
+http:
+  method: post
+  url: BASE/search
+  body:
+    q: "{{ args.query }}"
+    limit: "{{ args.limit }}"
+    filter: { tags: "{{ args.tags }}", owner: "{{ username }}" }
+    label: "query: {{ args.query }}"
+"#;
+
+/// Load the manifests and return the function of the (only) tool they define
+fn load(files: &[(&str, String)]) -> (TempDir, mcp_server::tools::ToolFunction) {
+    let dir = TempDir::new().unwrap();
+    for (file, contents) in files {
+        fs::write(dir.path().join(file), contents).unwrap();
+    }
+    let (mut functions, _) = load_plugins(dir.path(), &HashSet::new()).unwrap();
+    let function = functions.drain().next().unwrap().1;
+    (dir, function)
+}
+
+fn context(api_key: Option<&str>) -> ToolContext {
+    let mut external_keys = HashMap::new();
+    if let Some(api_key) = api_key {
+        external_keys.insert("city_api_key".to_string(), api_key.to_string());
+    }
+    let user = UserCredentials::new(
+        TEST_USERNAME.to_string(),
+        TEST_API_KEY.to_string(),
+        external_keys,
+    );
+    ToolContext::new(AuthenticatedUser(user))
+}
+
+// ============================================================================
+// Manifest Tests
+// ============================================================================
+
+#[test]
+fn test_http_manifest_loaded() {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        dir.path().join("lookup.toml"),
+        lookup_manifest("http://localhost"),
+    )
+    .unwrap();
+    fs::write(dir.path().join("search.yaml"), SEARCH_MANIFEST_YAML).unwrap();
+
+    let manifest = PluginManifest::load(&dir.path().join("lookup.toml")).unwrap();
+    let http = manifest.http.unwrap();
+    assert_eq!(http.method, "GET");
+    assert!(manifest.command.is_empty());
+    assert_eq!(http.external_keys(), vec!["city_api_key"]);
+
+    let (_, definitions) = load_plugins(dir.path(), &HashSet::new()).unwrap();
+    let mut names: Vec<&str> = definitions.iter().map(|def| def.name.as_str()).collect();
+    names.sort();
+    assert_eq!(names, vec!["lookup_city", "search_docs"]);
+    let lookup = definitions
+        .iter()
+        .find(|def| def.name == "lookup_city")
+        .unwrap();
+    assert_eq!(lookup.required_external_keys, vec!["city_api_key"]);
+}
+
+#[test]
+fn test_invalid_http_manifests_rejected() {
+    let dir = TempDir::new().unwrap();
+    let base = "name = \"bad\"\ndescription = \"test\"\n";
+    for (file, contents, error) in [
+        (
+            "both.toml",
+            format!(
+                "{}command = [\"cat\"]\n[http]\nurl = \"http://localhost\"\n",
+                base
+            ),
+            "both a command and an HTTP request",
+        ),
+        (
+            "variable.toml",
+            format!(
+                "{}[http]\nurl = \"http://localhost/{{{{secret}}}}\"\n",
+                base
+            ),
+            "Unknown template variable 'secret'",
+        ),
+        (
+            "method.toml",
+            format!(
+                "{}[http]\nmethod = \"NOT A METHOD\"\nurl = \"http://localhost\"\n",
+                base
+            ),
+            "Invalid HTTP method",
+        ),
+        (
+            "mapping.toml",
+            format!(
+                "{}[http]\nurl = \"http://localhost\"\nresponse = {{ a = \"a.b\" }}\n",
+                base
+            ),
+            "expected a jq-style path",
+        ),
+        ("neither.toml", base.to_string(), "has an empty command"),
+    ] {
+        let path = dir.path().join(file);
+        fs::write(&path, contents).unwrap();
+        let err = PluginManifest::load(&path).unwrap_err();
+        assert!(format!("{:#}", err).contains(error), "{}: {:#}", file, err);
+    }
+}
+
+#[test]
+fn test_url_component_encoding() {
+    assert_eq!(encode_url_component("Oslo"), "Oslo");
+    assert_eq!(encode_url_component("São Paulo"), "S%C3%A3o%20Paulo");
+    assert_eq!(
+        encode_url_component("../admin?x=1&y"),
+        "..%2Fadmin%3Fx%3D1%26y"
+    );
+}
+
+// ============================================================================
+// Invocation Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_get_request_filled_in_from_arguments_and_credentials() {
+    let base = mock_api().await;
+    let (_dir, lookup) = load(&[("lookup.toml", lookup_manifest(&base))]);
+
+    let result = lookup(
+        Some(json!({ "city": "São Paulo/../admin", "units": "metric" })),
+        context(Some("secret-key")),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["method"], "GET");
+    assert_eq!(result["path"], "/cities/S%C3%A3o%20Paulo%2F..%2Fadmin");
+    assert_eq!(result["query"], "units=metric&v=2");
+    assert_eq!(result["token"], "secret-key");
+    assert_eq!(result["user"], TEST_USERNAME);
+
+    // Optional arguments used as a whole query value are left out
+    let result = lookup(Some(json!({ "city": "Oslo" })), context(Some("secret-key")))
+        .await
+        .unwrap();
+    assert_eq!(result["query"], "v=2");
+}
+
+#[tokio::test]
+async fn test_post_body_keeps_argument_types() {
+    let base = mock_api().await;
+    let manifest = SEARCH_MANIFEST_YAML.replace("BASE", &base);
+    let (_dir, search) = load(&[("search.yml", manifest)]);
+
+    let result = search(
+        Some(json!({ "query": "async", "limit": 5, "tags": ["rust", "tokio"] })),
+        context(None),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["method"], "POST");
+    assert_eq!(
+        result["body"],
+        json!({
+            "q": "async",
+            "limit": 5,
+            "filter": { "tags": ["rust", "tokio"], "owner": TEST_USERNAME },
+            "label": "query: async",
+        })
+    );
+
+    // Missing optional arguments are left out of the body
+    let result = search(Some(json!({ "query": "async" })), context(None))
+        .await
+        .unwrap();
+    assert_eq!(
+        result["body"],
+        json!({ "q": "async", "filter": { "owner": TEST_USERNAME }, "label": "query: async" })
+    );
+
+    // Interpolating a missing argument is an invalid input
+    let err = search(Some(json!({})), context(None)).await.unwrap_err();
+    let input = err.downcast_ref::<InvalidInput>().unwrap();
+    assert_eq!(input.message, "missing argument 'query'");
+}
+
+#[tokio::test]
+async fn test_responses_and_upstream_errors() {
+    let base = mock_api().await;
+    let manifest = |name: &str, path: &str| {
+        format!(
+            "name = \"{}\"\ndescription = \"test\"\n[http]\nurl = \"{}{}\"\n",
+            name, base, path
+        )
+    };
+
+    let (_dir, text) = load(&[("text.toml", manifest("text", "/text"))]);
+    assert_eq!(
+        text(None, context(None)).await.unwrap(),
+        json!("plain text")
+    );
+
+    let (_dir, fail) = load(&[("fail.toml", manifest("fail", "/fail"))]);
+    let err = fail(None, context(None)).await.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "HTTP tool 'fail' got 502 Bad Gateway: upstream down"
+    );
+}
+
+#[tokio::test]
+async fn test_response_mapped_into_result() {
+    let base = mock_api().await;
+    let manifest = |name: &str, method: &str, path: &str| {
+        format!(
+            r#"
+name = "{}"
+description = "test"
+
+[http]
+method = "{}"
+url = "{}{}"
+body = {{ items = ["a", "b"] }}
+
+[http.response]
+verb = ".method"
+first = ".body.items[0]"
+items = "/body/items"
+missing = ".body.nope"
+"#,
+            name, method, base, path
+        )
+    };
+
+    let (_dir, mapped) = load(&[("mapped.toml", manifest("mapped", "PUT", "/items"))]);
+    assert_eq!(
+        mapped(None, context(None)).await.unwrap(),
+        json!({ "verb": "PUT", "first": "a", "items": ["a", "b"], "missing": null })
+    );
+
+    let (_dir, text) = load(&[("text.toml", manifest("text", "GET", "/text"))]);
+    let err = text(None, context(None)).await.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "HTTP tool 'text' expected a JSON response to map"
+    );
+}
@@ -0,0 +1,188 @@
+mod common;
+
+use arc_swap::ArcSwap;
+use axum::extract::State;
+use axum::{Extension, Json};
+use common::*;
+use mcp_server::auth::AuthenticatedUser;
+use mcp_server::idempotency::{Claim, IdempotencyCache};
+use mcp_server::store::SharedStore;
+use mcp_server::tools::{ToolContext, ToolFunction};
+use mcp_server::{
+    AppState, ERROR_INVALID_PARAMS, ERROR_SERVER_BUSY, McpRequest, McpResponse, handle_mcp_request,
+};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Tool counting its executions; fails when called with `{"fail": true}`
+fn counting_tool(calls: Arc<AtomicUsize>) -> ToolFunction {
+    Box::new(move |args, _ctx: ToolContext| {
+        let calls = calls.clone();
+        Box::pin(async move {
+            let call = calls.fetch_add(1, Ordering::SeqCst) + 1;
+            if args.is_some_and(|args| args["fail"] == true) {
+                anyhow::bail!("transient failure");
+            }
+            Ok(json!({ "call": call }))
+        })
+    })
+}
+
+fn test_state(store: SharedStore, calls: Arc<AtomicUsize>) -> AppState {
+    AppState {
+        tool_registry: Arc::new(ArcSwap::from_pointee(HashMap::from([
+            ("charge".to_string(), counting_tool(calls.clone())),
+            ("refund".to_string(), counting_tool(calls)),
+        ]))),
+        idempotency: IdempotencyCache::new(
+            store.clone(),
+            Duration::from_secs(3600),
+            Duration::from_secs(60),
+        ),
+        store,
+        ..Default::default()
+    }
+}
+
+async fn invoke(state: &AppState, tool_name: &str, key: &str, arguments: Value) -> McpResponse {
+    let request: McpRequest = serde_json::from_value(json!({
+        "method": "invoke",
+        "params": {"tool_name": tool_name, "arguments": arguments, "idempotency_key": key}
+    }))
+    .unwrap();
+    let Json(response) = handle_mcp_request(
+        State(state.clone()),
+        Extension(AuthenticatedUser(create_test_user())),
+        Json(request),
+    )
+    .await;
+    response
+}
+
+#[tokio::test]
+async fn test_retry_returns_stored_result() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let state = test_state(SharedStore::default(), calls.clone());
+
+    let first = invoke(&state, "charge", "order-1", json!({})).await;
+    let retry = invoke(&state, "charge", "order-1", json!({})).await;
+
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!(first.result, Some(json!({"call": 1})));
+    assert_eq!(retry.result, first.result);
+    assert_eq!(
+        state
+            .metrics
+            .counter("mcp_idempotency_hits_total", &[("tool", "charge")]),
+        1
+    );
+
+    invoke(&state, "charge", "order-2", json!({})).await;
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_results_survive_restart_with_shared_store() {
+    let store = SharedStore::default();
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    invoke(
+        &test_state(store.clone(), calls.clone()),
+        "charge",
+        "order-1",
+        json!({}),
+    )
+    .await;
+    // A fresh state over the same store stands in for a restarted server
+    let restarted = test_state(store, calls.clone());
+    let retry = invoke(&restarted, "charge", "order-1", json!({})).await;
+
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!(retry.result, Some(json!({"call": 1})));
+}
+
+#[tokio::test]
+async fn test_failed_invocation_releases_key() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let state = test_state(SharedStore::default(), calls.clone());
+
+    let failed = invoke(&state, "charge", "order-1", json!({"fail": true})).await;
+    assert!(failed.error.is_some());
+
+    let retry = invoke(&state, "charge", "order-1", json!({})).await;
+    assert_eq!(retry.result, Some(json!({"call": 2})));
+}
+
+#[tokio::test]
+async fn test_key_reused_for_other_tool_is_rejected() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let state = test_state(SharedStore::default(), calls.clone());
+
+    invoke(&state, "charge", "order-1", json!({})).await;
+    let response = invoke(&state, "refund", "order-1", json!({})).await;
+
+    let error = response.error.unwrap();
+    assert_eq!(error.code, ERROR_INVALID_PARAMS);
+    assert!(error.message.contains("'charge'"));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_key_in_progress_is_busy() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let state = test_state(SharedStore::default(), calls.clone());
+    assert_eq!(
+        state
+            .idempotency
+            .claim(TEST_USERNAME, "order-1", "charge")
+            .await
+            .unwrap(),
+        Claim::Acquired
+    );
+
+    let response = invoke(&state, "charge", "order-1", json!({})).await;
+
+    assert_eq!(response.error.unwrap().code, ERROR_SERVER_BUSY);
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn test_keys_are_scoped_per_user() {
+    let cache = IdempotencyCache::new(
+        SharedStore::default(),
+        Duration::from_secs(3600),
+        Duration::from_secs(60),
+    );
+    cache
+        .complete("alice", "key", "charge", &json!({"ok": true}))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        cache.claim("bob", "key", "charge").await.unwrap(),
+        Claim::Acquired
+    );
+    assert_eq!(
+        cache.claim("alice", "key", "charge").await.unwrap(),
+        Claim::Completed(json!({"ok": true}))
+    );
+}
+
+#[tokio::test]
+async fn test_pending_key_expires() {
+    let cache = IdempotencyCache::new(
+        SharedStore::default(),
+        Duration::from_secs(3600),
+        Duration::from_millis(10),
+    );
+    cache.claim("alice", "key", "charge").await.unwrap();
+    tokio::time::sleep(Duration::from_millis(30)).await;
+
+    assert_eq!(
+        cache.claim("alice", "key", "charge").await.unwrap(),
+        Claim::Acquired
+    );
+}
@@ -12,7 +12,7 @@ use serde_json::{json, Value};
 #[tokio::test]
 async fn test_health_endpoint_returns_ok() {
     let credentials = create_test_credentials_store();
-    let app = create_app(credentials);
+    let app = create_app(credentials).unwrap();
     let server = TestServer::new(app).unwrap();
 
     let response = server.get("/health").await;
@@ -28,7 +28,7 @@ async fn test_health_endpoint_returns_ok() {
 #[tokio::test]
 async fn test_mcp_without_authorization_header() {
     let credentials = create_test_credentials_store();
-    let app = create_app(credentials);
+    let app = create_app(credentials).unwrap();
     let server = TestServer::new(app).unwrap();
 
     let response = server
@@ -47,7 +47,7 @@ async fn test_mcp_without_authorization_header() {
 #[tokio::test]
 async fn test_mcp_with_invalid_token() {
     let credentials = create_test_credentials_store();
-    let app = create_app(credentials);
+    let app = create_app(credentials).unwrap();
     let server = TestServer::new(app).unwrap();
 
     let response = server
@@ -66,7 +66,7 @@ async fn test_mcp_with_invalid_token() {
 #[tokio::test]
 async fn test_mcp_with_malformed_auth_header() {
     let credentials = create_test_credentials_store();
-    let app = create_app(credentials);
+    let app = create_app(credentials).unwrap();
     let server = TestServer::new(app).unwrap();
 
     let response = server
@@ -84,7 +84,7 @@ async fn test_mcp_with_malformed_auth_header() {
 #[tokio::test]
 async fn test_mcp_with_valid_token_proceeds() {
     let credentials = create_test_credentials_store();
-    let app = create_app(credentials);
+    let app = create_app(credentials).unwrap();
     let server = TestServer::new(app).unwrap();
 
     let response = server
@@ -107,7 +107,7 @@ async fn test_mcp_with_valid_token_proceeds() {
 #[tokio::test]
 async fn test_discover_returns_tools_list() {
     let credentials = create_test_credentials_store();
-    let app = create_app(credentials);
+    let app = create_app(credentials).unwrap();
     let server = TestServer::new(app).unwrap();
 
     let response = server
@@ -126,7 +126,7 @@ async fn test_discover_returns_tools_list() {
 #[tokio::test]
 async fn test_discover_includes_get_current_time() {
     let credentials = create_test_credentials_store();
-    let app = create_app(credentials);
+    let app = create_app(credentials).unwrap();
     let server = TestServer::new(app).unwrap();
 
     let response = server
@@ -149,7 +149,7 @@ async fn test_discover_includes_get_current_time() {
 #[tokio::test]
 async fn test_discover_tool_definition_structure() {
     let credentials = create_test_credentials_store();
-    let app = create_app(credentials);
+    let app = create_app(credentials).unwrap();
     let server = TestServer::new(app).unwrap();
 
     let response = server
@@ -175,7 +175,7 @@ async fn test_discover_tool_definition_structure() {
 #[tokio::test]
 async fn test_invoke_get_current_time_success() {
     let credentials = create_test_credentials_store();
-    let app = create_app(credentials);
+    let app = create_app(credentials).unwrap();
     let server = TestServer::new(app).unwrap();
 
     let response = server
@@ -205,7 +205,7 @@ async fn test_invoke_get_current_time_success() {
 #[tokio::test]
 async fn test_invoke_returns_proper_json_rpc_response() {
     let credentials = create_test_credentials_store();
-    let app = create_app(credentials);
+    let app = create_app(credentials).unwrap();
     let server = TestServer::new(app).unwrap();
 
     let response = server
@@ -233,7 +233,7 @@ async fn test_invoke_returns_proper_json_rpc_response() {
 #[tokio::test]
 async fn test_invoke_nonexistent_tool() {
     let credentials = create_test_credentials_store();
-    let app = create_app(credentials);
+    let app = create_app(credentials).unwrap();
     let server = TestServer::new(app).unwrap();
 
     let response = server
@@ -260,7 +260,7 @@ async fn test_invoke_nonexistent_tool() {
 #[tokio::test]
 async fn test_invoke_nonexistent_tool_includes_available_tools() {
     let credentials = create_test_credentials_store();
-    let app = create_app(credentials);
+    let app = create_app(credentials).unwrap();
     let server = TestServer::new(app).unwrap();
 
     let response = server
@@ -289,7 +289,7 @@ async fn test_invoke_nonexistent_tool_includes_available_tools() {
 #[tokio::test]
 async fn test_full_discovery_then_invoke_flow() {
     let credentials = create_test_credentials_store();
-    let app = create_app(credentials);
+    let app = create_app(credentials).unwrap();
     let server = TestServer::new(app).unwrap();
 
     // First, discover tools
@@ -302,7 +302,12 @@ async fn test_full_discovery_then_invoke_flow() {
     discover_response.assert_status_ok();
     let discover_body: Value = discover_response.json();
     let tools = discover_body["result"]["tools"].as_array().unwrap();
-    let tool_name = tools[0]["name"].as_str().unwrap();
+    // A discovered tool that needs neither arguments nor external keys
+    let tool_name = tools
+        .iter()
+        .map(|tool| tool["name"].as_str().unwrap())
+        .find(|name| *name == "get_current_time")
+        .unwrap();
 
     // Then, invoke the discovered tool
     let invoke_response = server
@@ -325,7 +330,7 @@ async fn test_full_discovery_then_invoke_flow() {
 #[tokio::test]
 async fn test_multiple_requests_same_credentials() {
     let credentials = create_test_credentials_store();
-    let app = create_app(credentials);
+    let app = create_app(credentials).unwrap();
     let server = TestServer::new(app).unwrap();
 
     // First request
@@ -356,7 +361,7 @@ async fn test_multiple_requests_same_credentials() {
 #[tokio::test]
 async fn test_requests_from_different_users() {
     let credentials = create_multi_user_credentials_store();
-    let app = create_app(credentials);
+    let app = create_app(credentials).unwrap();
     let server = TestServer::new(app).unwrap();
 
     // Request from first user
@@ -385,7 +390,7 @@ async fn test_requests_from_different_users() {
 #[tokio::test]
 async fn test_all_responses_have_jsonrpc_2_0() {
     let credentials = create_test_credentials_store();
-    let app = create_app(credentials);
+    let app = create_app(credentials).unwrap();
     let server = TestServer::new(app).unwrap();
 
     // Test discover
@@ -428,7 +433,7 @@ async fn test_all_responses_have_jsonrpc_2_0() {
 #[tokio::test]
 async fn test_error_responses_have_proper_structure() {
     let credentials = create_test_credentials_store();
-    let app = create_app(credentials);
+    let app = create_app(credentials).unwrap();
     let server = TestServer::new(app).unwrap();
 
     let response = server
@@ -447,7 +452,7 @@ async fn test_error_responses_have_proper_structure() {
 #[tokio::test]
 async fn test_success_responses_have_proper_structure() {
     let credentials = create_test_credentials_store();
-    let app = create_app(credentials);
+    let app = create_app(credentials).unwrap();
     let server = TestServer::new(app).unwrap();
 
     let response = server
@@ -0,0 +1,232 @@
+mod common;
+
+use axum_test::TestServer;
+use common::*;
+use mcp_server::config::{JsonRpcConfig, ServerConfig};
+use mcp_server::{
+    AppOptions, ERROR_INVALID_PARAMS, ERROR_INVALID_REQUEST, McpEnvelope, McpRequest, create_app,
+    create_app_with_options,
+};
+use serde_json::{Value, json};
+
+fn strict_server() -> TestServer {
+    server_with(JsonRpcConfig {
+        strict: true,
+        ..Default::default()
+    })
+}
+
+fn lenient_arguments_server() -> TestServer {
+    server_with(JsonRpcConfig {
+        lenient_arguments: true,
+        ..Default::default()
+    })
+}
+
+fn server_with(jsonrpc: JsonRpcConfig) -> TestServer {
+    let config = ServerConfig {
+        jsonrpc,
+        ..Default::default()
+    };
+    let app =
+        create_app_with_options(create_test_credentials_store(), AppOptions::new(config)).unwrap();
+    TestServer::new(app).unwrap()
+}
+
+async fn post(server: &TestServer, body: Value) -> axum_test::TestResponse {
+    server
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .json(&body)
+        .await
+}
+
+// ============================================================================
+// Envelope Parsing Tests
+// ============================================================================
+
+#[test]
+fn test_envelope_deserialization() {
+    let envelope: McpEnvelope = serde_json::from_value(json!({
+        "jsonrpc": "2.0",
+        "id": 7,
+        "method": "history/list"
+    }))
+    .unwrap();
+
+    assert_eq!(envelope.jsonrpc.as_deref(), Some("2.0"));
+    assert_eq!(envelope.id, Some(json!(7)));
+    assert!(matches!(envelope.request, McpRequest::HistoryList(None)));
+}
+
+#[test]
+fn test_envelope_fields_are_optional() {
+    let envelope: McpEnvelope = serde_json::from_value(json!({"method": "discover"})).unwrap();
+
+    assert!(envelope.jsonrpc.is_none());
+    assert!(envelope.id.is_none());
+}
+
+// ============================================================================
+// Lenient Mode (default)
+// ============================================================================
+
+#[tokio::test]
+async fn test_lenient_mode_accepts_bare_requests() {
+    let server = TestServer::new(create_app(create_test_credentials_store()).unwrap()).unwrap();
+
+    let body: Value = post(&server, json!({"method": "discover"})).await.json();
+    assert!(body["result"]["tools"].is_array());
+    assert!(body.get("id").is_none());
+}
+
+#[tokio::test]
+async fn test_lenient_mode_echoes_id() {
+    let server = TestServer::new(create_app(create_test_credentials_store()).unwrap()).unwrap();
+
+    let body: Value = post(&server, json!({"id": "req-1", "method": "discover"}))
+        .await
+        .json();
+    assert_eq!(body["id"], "req-1");
+}
+
+#[tokio::test]
+async fn test_lenient_mode_rejects_unknown_method_with_422() {
+    let server = TestServer::new(create_app(create_test_credentials_store()).unwrap()).unwrap();
+
+    post(&server, json!({"method": "nope"}))
+        .await
+        .assert_status_unprocessable_entity();
+}
+
+// ============================================================================
+// Strict Mode
+// ============================================================================
+
+#[tokio::test]
+async fn test_strict_mode_accepts_conforming_requests() {
+    let server = strict_server();
+
+    let body: Value = post(
+        &server,
+        json!({"jsonrpc": "2.0", "id": 1, "method": "discover"}),
+    )
+    .await
+    .json();
+    assert!(body["result"]["tools"].is_array());
+    assert_eq!(body["id"], 1);
+}
+
+#[tokio::test]
+async fn test_strict_mode_requires_jsonrpc_version() {
+    let server = strict_server();
+
+    for envelope in [
+        json!({"id": 1, "method": "discover"}),
+        json!({"jsonrpc": "1.0", "id": 1, "method": "discover"}),
+    ] {
+        let body: Value = post(&server, envelope).await.json();
+        assert_eq!(body["error"]["code"], ERROR_INVALID_REQUEST);
+        assert_eq!(body["id"], 1);
+    }
+}
+
+#[tokio::test]
+async fn test_strict_mode_requires_valid_id() {
+    let server = strict_server();
+
+    for id in [
+        None,
+        Some(json!(null)),
+        Some(json!(1.5)),
+        Some(json!({"a": 1})),
+    ] {
+        let mut envelope = json!({"jsonrpc": "2.0", "method": "discover"});
+        if let Some(id) = id {
+            envelope["id"] = id;
+        }
+        let body: Value = post(&server, envelope).await.json();
+        assert_eq!(body["error"]["code"], ERROR_INVALID_REQUEST);
+        assert!(body.get("id").is_none());
+    }
+}
+
+#[tokio::test]
+async fn test_strict_mode_rejects_unknown_method_with_invalid_request() {
+    let server = strict_server();
+
+    let response = post(
+        &server,
+        json!({"jsonrpc": "2.0", "id": "x", "method": "nope"}),
+    )
+    .await;
+    response.assert_status_ok();
+    let body: Value = response.json();
+    assert_eq!(body["error"]["code"], ERROR_INVALID_REQUEST);
+    assert_eq!(body["id"], "x");
+}
+
+// ============================================================================
+// String Arguments
+// ============================================================================
+
+fn invoke_calculate(arguments: Value) -> Value {
+    json!({
+        "method": "invoke",
+        "params": {"tool_name": "calculate", "arguments": arguments}
+    })
+}
+
+#[tokio::test]
+async fn test_string_arguments_parsed_in_lenient_arguments_mode() {
+    let server = lenient_arguments_server();
+
+    let body: Value = post(
+        &server,
+        invoke_calculate(json!(r#"{"expression": "6 * 7"}"#)),
+    )
+    .await
+    .json();
+    assert_eq!(body["result"], json!({ "result": 42.0 }));
+
+    // Objects are passed through as before
+    let body: Value = post(&server, invoke_calculate(json!({"expression": "6 * 7"})))
+        .await
+        .json();
+    assert_eq!(body["result"], json!({ "result": 42.0 }));
+}
+
+#[tokio::test]
+async fn test_invalid_string_arguments_rejected() {
+    let server = lenient_arguments_server();
+
+    for (arguments, reason) in [
+        (r#"{"expression": "6 * 7""#, "is not valid JSON"),
+        (r#"["6 * 7"]"#, "must encode an object"),
+        ("6 * 7", "is not valid JSON"),
+    ] {
+        let body: Value = post(&server, invoke_calculate(json!(arguments)))
+            .await
+            .json();
+        assert_eq!(body["error"]["code"], ERROR_INVALID_PARAMS, "{}", arguments);
+        assert!(
+            body["error"]["message"].as_str().unwrap().contains(reason),
+            "{}",
+            body
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_string_arguments_not_parsed_by_default() {
+    let server = TestServer::new(create_app(create_test_credentials_store()).unwrap()).unwrap();
+
+    let body: Value = post(
+        &server,
+        invoke_calculate(json!(r#"{"expression": "6 * 7"}"#)),
+    )
+    .await
+    .json();
+    assert!(body["result"].is_null());
+    assert!(body["error"].is_object());
+}
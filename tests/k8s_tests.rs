@@ -0,0 +1,249 @@
+#![cfg(feature = "k8s")]
+
+mod common;
+
+use common::*;
+use k8s_openapi::api::apps::v1::Deployment;
+use k8s_openapi::api::core::v1::Pod;
+use mcp_server::auth::{AuthenticatedUser, UserCredentials};
+use mcp_server::config::{K8sConfig, ServerConfig};
+use mcp_server::tools::k8s::{
+    K8sListPodsTool, K8sPodLogsTool, Namespaces, deployment_summary, pod_summary, tail_lines,
+    user_kubeconfig,
+};
+use mcp_server::tools::{InvalidInput, McpTool, ToolContext};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+fn user(external_keys: &[(&str, &str)]) -> AuthenticatedUser {
+    let keys: HashMap<String, String> = external_keys
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+    AuthenticatedUser(UserCredentials::new(
+        "alice".to_string(),
+        TEST_API_KEY.to_string(),
+        keys,
+    ))
+}
+
+fn context(external_keys: &[(&str, &str)]) -> ToolContext {
+    ToolContext::new(user(external_keys)).with_config(Arc::new(ServerConfig::default()))
+}
+
+// ============================================================================
+// Namespace Restriction Tests
+// ============================================================================
+
+#[test]
+fn test_namespaces_parsed_from_list() {
+    assert_eq!(
+        Namespaces::parse(" team-a, team-b ,,"),
+        Namespaces::Only(vec!["team-a".to_string(), "team-b".to_string()])
+    );
+    assert_eq!(Namespaces::parse("team-a,*"), Namespaces::Any);
+}
+
+#[test]
+fn test_namespace_resolved_against_allowed_list() {
+    let namespaces = Namespaces::parse("team-a,team-b");
+    assert_eq!(namespaces.resolve(None).unwrap(), "team-a");
+    assert_eq!(namespaces.resolve(Some("team-b")).unwrap(), "team-b");
+    assert_eq!(
+        namespaces.resolve(Some("kube-system")).unwrap_err().message,
+        "namespace 'kube-system' is not allowed"
+    );
+
+    assert!(Namespaces::parse("").resolve(None).is_err());
+    assert_eq!(Namespaces::Any.resolve(None).unwrap(), "default");
+    assert_eq!(
+        Namespaces::Any.resolve(Some("kube-system")).unwrap(),
+        "kube-system"
+    );
+}
+
+#[tokio::test]
+async fn test_disallowed_namespace_refused_before_connecting() {
+    // No cluster credentials: reaching the API would fail with a missing credential
+    let ctx = context(&[("k8s_namespaces", "team-a")]);
+
+    let error = K8sListPodsTool
+        .execute_with_context(Some(json!({"namespace": "kube-system"})), ctx)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.downcast_ref::<InvalidInput>().unwrap().message,
+        "namespace 'kube-system' is not allowed"
+    );
+}
+
+#[tokio::test]
+async fn test_requires_namespaces_key() {
+    let ctx = context(&[("k8s_api_url", "https://k8s.example.com")]);
+
+    let error = K8sPodLogsTool
+        .execute_with_context(Some(json!({"pod": "web-0"})), ctx)
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("k8s_namespaces"));
+}
+
+// ============================================================================
+// Credential Tests
+// ============================================================================
+
+#[test]
+fn test_kubeconfig_built_from_url_and_token() {
+    let user = user(&[
+        ("k8s_api_url", "https://k8s.example.com:6443"),
+        ("k8s_token", "secret-token"),
+    ]);
+
+    let kubeconfig = user_kubeconfig(&user).unwrap();
+    let cluster = kubeconfig.clusters[0].cluster.as_ref().unwrap();
+    assert_eq!(
+        cluster.server.as_deref(),
+        Some("https://k8s.example.com:6443/")
+    );
+    assert!(cluster.certificate_authority_data.is_none());
+    assert_eq!(kubeconfig.current_context.as_deref(), Some("mcp"));
+}
+
+#[test]
+fn test_kubeconfig_requires_credentials() {
+    let error = user_kubeconfig(&user(&[])).unwrap_err();
+    assert!(error.to_string().contains("k8s_api_url"));
+}
+
+#[test]
+fn test_kubeconfig_with_exec_refused() {
+    let kubeconfig = r#"
+apiVersion: v1
+kind: Config
+clusters:
+- name: prod
+  cluster:
+    server: https://k8s.example.com
+contexts:
+- name: prod
+  context:
+    cluster: prod
+    user: prod
+current-context: prod
+users:
+- name: prod
+  user:
+    exec:
+      apiVersion: client.authentication.k8s.io/v1
+      command: /bin/sh
+      args: ["-c", "id"]
+"#;
+    let error = user_kubeconfig(&user(&[("k8s_kubeconfig", kubeconfig)])).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "k8s_kubeconfig must not use exec or auth-provider credentials"
+    );
+}
+
+#[test]
+fn test_kubeconfig_with_local_files_refused() {
+    let kubeconfig = r#"
+clusters:
+- name: prod
+  cluster:
+    server: https://k8s.example.com
+users:
+- name: prod
+  user:
+    tokenFile: /var/run/secrets/kubernetes.io/serviceaccount/token
+"#;
+    let error = user_kubeconfig(&user(&[("k8s_kubeconfig", kubeconfig)])).unwrap_err();
+    assert!(error.to_string().contains("referencing files"));
+}
+
+#[test]
+fn test_invalid_kubeconfig_not_echoed() {
+    let error = user_kubeconfig(&user(&[("k8s_kubeconfig", "token: hunter2: [")])).unwrap_err();
+    assert_eq!(error.to_string(), "Invalid credential 'k8s_kubeconfig'");
+}
+
+// ============================================================================
+// Limits and Summaries Tests
+// ============================================================================
+
+#[test]
+fn test_tail_lines_defaults_and_bounds() {
+    let config = K8sConfig::default();
+    assert_eq!(tail_lines(&config, None), 100);
+    assert_eq!(tail_lines(&config, Some(50)), 50);
+    assert_eq!(tail_lines(&config, Some(1_000_000)), 2000);
+    assert_eq!(tail_lines(&config, Some(0)), 1);
+}
+
+#[test]
+fn test_pod_summary() {
+    let pod: Pod = serde_json::from_value(json!({
+        "metadata": { "name": "web-0", "labels": { "app": "web" } },
+        "spec": {
+            "nodeName": "node-1",
+            "containers": [{ "name": "web" }, { "name": "sidecar" }]
+        },
+        "status": {
+            "phase": "Running",
+            "containerStatuses": [
+                { "name": "web", "ready": true, "restartCount": 2, "image": "web", "imageID": "" },
+                { "name": "sidecar", "ready": false, "restartCount": 1, "image": "s", "imageID": "" }
+            ]
+        }
+    }))
+    .unwrap();
+
+    let summary = pod_summary(&pod);
+    assert_eq!(summary["name"], "web-0");
+    assert_eq!(summary["phase"], "Running");
+    assert_eq!(summary["ready"], "1/2");
+    assert_eq!(summary["restarts"], 3);
+    assert_eq!(summary["node"], "node-1");
+    assert_eq!(summary["labels"]["app"], "web");
+}
+
+#[test]
+fn test_deployment_summary() {
+    let deployment: Deployment = serde_json::from_value(json!({
+        "metadata": { "name": "web", "namespace": "team-a" },
+        "spec": {
+            "replicas": 3,
+            "selector": { "matchLabels": { "app": "web" } },
+            "strategy": { "type": "RollingUpdate" },
+            "template": {
+                "spec": {
+                    "containers": [{
+                        "name": "web",
+                        "image": "nginx:1.27",
+                        "ports": [{ "containerPort": 80 }],
+                        "resources": { "limits": { "memory": "128Mi" } }
+                    }]
+                }
+            }
+        },
+        "status": {
+            "readyReplicas": 2,
+            "updatedReplicas": 3,
+            "conditions": [{ "type": "Available", "status": "True" }]
+        }
+    }))
+    .unwrap();
+
+    let summary = deployment_summary(&deployment);
+    assert_eq!(summary["name"], "web");
+    assert_eq!(summary["strategy"], "RollingUpdate");
+    assert_eq!(summary["selector"]["app"], "web");
+    assert_eq!(summary["replicas"]["desired"], 3);
+    assert_eq!(summary["replicas"]["ready"], 2);
+    assert_eq!(summary["replicas"]["available"], 0);
+    assert_eq!(summary["containers"][0]["image"], "nginx:1.27");
+    assert_eq!(summary["containers"][0]["ports"][0]["container_port"], 80);
+    assert_eq!(summary["containers"][0]["limits"]["memory"], "128Mi");
+    assert_eq!(summary["conditions"][0]["type"], "Available");
+}
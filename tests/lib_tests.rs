@@ -1,4 +1,7 @@
+use arc_swap::ArcSwap;
 use axum::http::StatusCode;
+use mcp_server::auth::{AuthenticatedUser, UserCredentials};
+use mcp_server::tools::ToolFunction;
 use mcp_server::{
     AppState, ERROR_AUTH, ERROR_INVALID_PARAMS, ERROR_TOOL_EXECUTION, ErrorDetails, McpResponse,
     ToolDefinition, health_check, is_param_validation_error,
@@ -192,12 +195,13 @@ fn test_app_state_construction() {
     let tool_definitions = Vec::new();
 
     let state = AppState {
-        tool_registry: Arc::new(func_registry),
-        tool_definitions: Arc::new(tool_definitions),
+        tool_registry: Arc::new(ArcSwap::from_pointee(func_registry)),
+        tool_definitions: Arc::new(ArcSwap::from_pointee(tool_definitions)),
+        ..Default::default()
     };
 
-    assert_eq!(state.tool_registry.len(), 0);
-    assert_eq!(state.tool_definitions.len(), 0);
+    assert_eq!(state.tool_registry.load().len(), 0);
+    assert_eq!(state.tool_definitions.load().len(), 0);
 }
 
 #[test]
@@ -206,8 +210,9 @@ fn test_app_state_arc_wrapped() {
     let tool_definitions = Vec::new();
 
     let state = AppState {
-        tool_registry: Arc::new(func_registry),
-        tool_definitions: Arc::new(tool_definitions),
+        tool_registry: Arc::new(ArcSwap::from_pointee(func_registry)),
+        tool_definitions: Arc::new(ArcSwap::from_pointee(tool_definitions)),
+        ..Default::default()
     };
 
     // Should be able to clone cheaply (Arc increments reference count)
@@ -222,16 +227,45 @@ fn test_app_state_with_definitions() {
         name: "test_tool".to_string(),
         description: "A test tool".to_string(),
         parameters: json!({}),
+        ..Default::default()
     }];
 
     let state = AppState {
-        tool_registry: Arc::new(func_registry),
-        tool_definitions: Arc::new(tool_definitions),
+        tool_registry: Arc::new(ArcSwap::from_pointee(func_registry)),
+        tool_definitions: Arc::new(ArcSwap::from_pointee(tool_definitions)),
+        ..Default::default()
     };
 
-    assert_eq!(state.tool_registry.len(), 0);
-    assert_eq!(state.tool_definitions.len(), 1);
-    assert_eq!(state.tool_definitions[0].name, "test_tool");
+    assert_eq!(state.tool_registry.load().len(), 0);
+    assert_eq!(state.tool_definitions.load().len(), 1);
+    assert_eq!(state.tool_definitions.load()[0].name, "test_tool");
+}
+
+#[test]
+fn test_replace_tools_swaps_snapshot() {
+    let state = AppState::default();
+    let clone = state.clone();
+    let before = state.tools_for(&AuthenticatedUser(UserCredentials::new(
+        "user".to_string(),
+        "key".to_string(),
+        HashMap::new(),
+    )));
+
+    let tool: ToolFunction = Box::new(|_args, _ctx| Box::pin(async { Ok(json!(null)) }));
+    state.replace_tools(
+        HashMap::from([("new_tool".to_string(), tool)]),
+        vec![ToolDefinition {
+            name: "new_tool".to_string(),
+            description: "Added at runtime".to_string(),
+            parameters: json!({}),
+            ..Default::default()
+        }],
+    );
+
+    // Clones share the swapped registry; earlier snapshots are unaffected
+    assert!(clone.tool_registry.load().contains_key("new_tool"));
+    assert_eq!(clone.tool_definitions.load()[0].name, "new_tool");
+    assert!(before.functions.is_empty());
 }
 
 // ============================================================================
@@ -244,6 +278,7 @@ fn test_tool_definition_construction() {
         name: "my_tool".to_string(),
         description: "Does something useful".to_string(),
         parameters: json!({"type": "object"}),
+        ..Default::default()
     };
 
     assert_eq!(def.name, "my_tool");
@@ -257,6 +292,7 @@ fn test_tool_definition_clone() {
         name: "tool".to_string(),
         description: "desc".to_string(),
         parameters: json!({}),
+        ..Default::default()
     };
 
     let cloned = def.clone();
@@ -290,6 +326,7 @@ fn test_mcp_request_invoke_deserialization() {
         mcp_server::McpRequest::Invoke {
             tool_name,
             arguments,
+            ..
         } => {
             assert_eq!(tool_name, "test");
             assert_eq!(arguments, None);
@@ -308,6 +345,7 @@ fn test_mcp_request_invoke_with_arguments() {
         mcp_server::McpRequest::Invoke {
             tool_name,
             arguments,
+            ..
         } => {
             assert_eq!(tool_name, "get_time");
             assert!(arguments.is_some());
@@ -0,0 +1,337 @@
+use axum::Router;
+use axum::extract::ConnectInfo;
+use axum::routing::get;
+use mcp_server::config::{ListenerConfig, ServerConfig, TlsConfig};
+use mcp_server::listeners::{BoundListener, bind, serve_all};
+use std::net::SocketAddr;
+use std::path::Path;
+use std::time::Duration;
+use tempfile::TempDir;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+fn router() -> Router {
+    Router::new()
+        .route("/health", get(|| async { "ok" }))
+        .route(
+            "/peer",
+            get(|ConnectInfo(peer): ConnectInfo<SocketAddr>| async move { peer.ip().to_string() }),
+        )
+        .route(
+            "/slow",
+            get(|| async {
+                tokio::time::sleep(Duration::from_millis(300)).await;
+                "done"
+            }),
+        )
+        .route("/hang", get(std::future::pending::<&'static str>))
+}
+
+fn tcp(bind: &str) -> ListenerConfig {
+    ListenerConfig {
+        bind: Some(bind.to_string()),
+        ..Default::default()
+    }
+}
+
+fn unix(path: &Path) -> ListenerConfig {
+    ListenerConfig {
+        socket_path: Some(path.to_str().unwrap().to_string()),
+        ..Default::default()
+    }
+}
+
+/// Self-signed certificate for `localhost`, written to `dir`; returns the
+/// TLS settings and the certificate PEM
+fn certificate(dir: &TempDir) -> (TlsConfig, String) {
+    let certified = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    let cert_pem = certified.cert.pem();
+    let cert_path = dir.path().join("cert.pem");
+    let key_path = dir.path().join("key.pem");
+    std::fs::write(&cert_path, &cert_pem).unwrap();
+    std::fs::write(&key_path, certified.signing_key.serialize_pem()).unwrap();
+    let tls = TlsConfig {
+        cert_path: cert_path.to_str().unwrap().to_string(),
+        key_path: key_path.to_str().unwrap().to_string(),
+    };
+    (tls, cert_pem)
+}
+
+fn start(
+    listeners: Vec<BoundListener>,
+    grace: Duration,
+) -> (CancellationToken, JoinHandle<anyhow::Result<()>>) {
+    let shutdown = CancellationToken::new();
+    let server = tokio::spawn(serve_all(listeners, router(), shutdown.clone(), grace));
+    (shutdown, server)
+}
+
+/// GET `path` over the Unix socket, returning the raw response
+async fn get_unix(socket: &Path, path: &str) -> String {
+    let mut stream = tokio::net::UnixStream::connect(socket).await.unwrap();
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+        path
+    );
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    response
+}
+
+// ============================================================================
+// Configuration Tests
+// ============================================================================
+
+#[test]
+fn test_default_listener() {
+    let config = ServerConfig::default();
+    assert_eq!(config.listeners(), vec![tcp("0.0.0.0:3000")]);
+    assert_eq!(config.listeners()[0].scheme(), "http");
+}
+
+#[test]
+fn test_listeners_config_parses() {
+    let config: ServerConfig = toml::from_str(
+        r#"
+        [[listeners]]
+        bind = "127.0.0.1:3000"
+
+        [[listeners]]
+        bind = "0.0.0.0:3443"
+        tls = { cert_path = "cert.pem", key_path = "key.pem" }
+
+        [[listeners]]
+        socket_path = "/run/mcp/mcp.sock"
+        socket_mode = 0o660
+        "#,
+    )
+    .unwrap();
+
+    let listeners = config.listeners();
+    assert_eq!(listeners.len(), 3);
+    assert_eq!(listeners[0], tcp("127.0.0.1:3000"));
+    assert_eq!(listeners[1].scheme(), "https");
+    assert_eq!(listeners[1].tls.as_ref().unwrap().key_path, "key.pem");
+    assert_eq!(listeners[2].scheme(), "unix");
+    assert_eq!(listeners[2].address(), "unix:/run/mcp/mcp.sock");
+    assert_eq!(listeners[2].socket_mode, Some(0o660));
+}
+
+#[test]
+fn test_listener_checks() {
+    assert!(tcp("127.0.0.1:3000").check().is_ok());
+    assert!(unix(Path::new("/run/mcp.sock")).check().is_ok());
+
+    let both = ListenerConfig {
+        socket_path: Some("/run/mcp.sock".to_string()),
+        ..tcp("127.0.0.1:3000")
+    };
+    assert!(both.check().unwrap_err().to_string().contains("both"));
+
+    let neither = ListenerConfig::default();
+    assert!(neither.check().unwrap_err().to_string().contains("needs"));
+
+    let tls_socket = ListenerConfig {
+        tls: Some(TlsConfig {
+            cert_path: "cert.pem".to_string(),
+            key_path: "key.pem".to_string(),
+        }),
+        ..unix(Path::new("/run/mcp.sock"))
+    };
+    assert!(
+        tls_socket
+            .check()
+            .unwrap_err()
+            .to_string()
+            .contains("`tls`")
+    );
+
+    let mode_tcp = ListenerConfig {
+        socket_mode: Some(0o600),
+        ..tcp("127.0.0.1:3000")
+    };
+    assert!(
+        mode_tcp
+            .check()
+            .unwrap_err()
+            .to_string()
+            .contains("`socket_mode`")
+    );
+}
+
+// ============================================================================
+// Binding Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_bind_reports_actual_address() {
+    let listener = bind(&tcp("127.0.0.1:0")).await.unwrap();
+    assert_eq!(listener.scheme(), "http");
+    assert!(!listener.address().ends_with(":0"));
+    assert_eq!(listener.url(), format!("http://{}", listener.address()));
+}
+
+#[tokio::test]
+async fn test_bind_fails_on_missing_certificate() {
+    let listener = ListenerConfig {
+        tls: Some(TlsConfig {
+            cert_path: "/nonexistent/cert.pem".to_string(),
+            key_path: "/nonexistent/key.pem".to_string(),
+        }),
+        ..tcp("127.0.0.1:0")
+    };
+    let error = bind(&listener).await.err().unwrap();
+    assert!(format!("{:#}", error).contains("/nonexistent/cert.pem"));
+}
+
+#[tokio::test]
+async fn test_bind_fails_on_taken_port() {
+    let first = bind(&tcp("127.0.0.1:0")).await.unwrap();
+    let error = bind(&tcp(first.address())).await.err().unwrap();
+    assert!(error.to_string().contains("Failed to bind"));
+}
+
+#[tokio::test]
+async fn test_unix_socket_replaces_stale_socket_only() {
+    let dir = TempDir::new().unwrap();
+    let socket = dir.path().join("mcp.sock");
+
+    // Left behind by a previous run
+    drop(std::os::unix::net::UnixListener::bind(&socket).unwrap());
+    let listener = bind(&unix(&socket)).await.unwrap();
+    assert_eq!(listener.url(), format!("unix:{}", socket.display()));
+    drop(listener);
+
+    let file = dir.path().join("data.db");
+    std::fs::write(&file, "keep").unwrap();
+    let error = bind(&unix(&file)).await.err().unwrap();
+    assert!(error.to_string().contains("not a socket"));
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), "keep");
+}
+
+#[tokio::test]
+async fn test_unix_socket_mode() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = TempDir::new().unwrap();
+    let socket = dir.path().join("mcp.sock");
+    let config = ListenerConfig {
+        socket_mode: Some(0o600),
+        ..unix(&socket)
+    };
+    let _listener = bind(&config).await.unwrap();
+    let mode = std::fs::metadata(&socket).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+}
+
+// ============================================================================
+// Serving Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_serves_on_every_listener() {
+    let dir = TempDir::new().unwrap();
+    let (tls, cert_pem) = certificate(&dir);
+    let socket = dir.path().join("mcp.sock");
+
+    let plain = bind(&tcp("127.0.0.1:0")).await.unwrap();
+    let secure = bind(&ListenerConfig {
+        tls: Some(tls),
+        ..tcp("127.0.0.1:0")
+    })
+    .await
+    .unwrap();
+    let local = bind(&unix(&socket)).await.unwrap();
+    assert_eq!(secure.scheme(), "https");
+
+    let plain_url = plain.url();
+    let secure_addr: SocketAddr = secure.address().parse().unwrap();
+    let (shutdown, server) = start(vec![plain, secure, local], Duration::from_secs(5));
+
+    let body = reqwest::get(format!("{}/health", plain_url))
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert_eq!(body, "ok");
+
+    let client = reqwest::Client::builder()
+        .add_root_certificate(reqwest::Certificate::from_pem(cert_pem.as_bytes()).unwrap())
+        .resolve("localhost", secure_addr)
+        .build()
+        .unwrap();
+    let url = format!("https://localhost:{}", secure_addr.port());
+    let response = client.get(format!("{}/health", url)).send().await.unwrap();
+    assert_eq!(response.text().await.unwrap(), "ok");
+    // The peer address is available for client IP resolution over TLS too
+    let response = client.get(format!("{}/peer", url)).send().await.unwrap();
+    assert_eq!(response.text().await.unwrap(), "127.0.0.1");
+
+    let response = get_unix(&socket, "/health").await;
+    assert!(response.starts_with("HTTP/1.1 200 OK"), "{}", response);
+    assert!(response.ends_with("ok"));
+
+    shutdown.cancel();
+    server.await.unwrap().unwrap();
+    assert!(!socket.exists());
+    assert!(reqwest::get(format!("{}/health", plain_url)).await.is_err());
+}
+
+#[tokio::test]
+async fn test_plain_client_refused_by_tls_listener() {
+    let dir = TempDir::new().unwrap();
+    let (tls, _) = certificate(&dir);
+    let secure = bind(&ListenerConfig {
+        tls: Some(tls),
+        ..tcp("127.0.0.1:0")
+    })
+    .await
+    .unwrap();
+    let address = secure.address().to_string();
+    let (shutdown, server) = start(vec![secure], Duration::from_secs(5));
+
+    assert!(
+        reqwest::get(format!("http://{}/health", address))
+            .await
+            .is_err()
+    );
+
+    shutdown.cancel();
+    server.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn test_shutdown_finishes_requests_in_progress() {
+    let listener = bind(&tcp("127.0.0.1:0")).await.unwrap();
+    let url = listener.url();
+    let (shutdown, server) = start(vec![listener], Duration::from_secs(5));
+
+    let request = tokio::spawn(async move { reqwest::get(format!("{}/slow", url)).await });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    shutdown.cancel();
+
+    let response = request.await.unwrap().unwrap();
+    assert_eq!(response.text().await.unwrap(), "done");
+    server.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn test_shutdown_closes_connections_after_grace_period() {
+    let listener = bind(&tcp("127.0.0.1:0")).await.unwrap();
+    let url = listener.url();
+    let (shutdown, server) = start(vec![listener], Duration::from_millis(200));
+
+    let request = tokio::spawn(async move { reqwest::get(format!("{}/hang", url)).await });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    shutdown.cancel();
+
+    tokio::time::timeout(Duration::from_secs(5), server)
+        .await
+        .expect("shutdown should not wait past the grace period")
+        .unwrap()
+        .unwrap();
+    request.abort();
+}
@@ -0,0 +1,218 @@
+mod common;
+
+use arc_swap::ArcSwap;
+use axum::Extension;
+use axum::body::to_bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, HeaderValue, header};
+use axum::response::Response;
+use common::*;
+use mcp_server::auth::AuthenticatedUser;
+use mcp_server::config::ServerConfig;
+use mcp_server::elicitation::PendingElicitations;
+use mcp_server::encoding::{McpBody, WireFormat};
+use mcp_server::logging::LogLevel;
+use mcp_server::session::SESSION_HEADER;
+use mcp_server::tools::{ToolContext, ToolFunction};
+use mcp_server::{AppState, ERROR_INVALID_REQUEST, mcp_endpoint};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// A streamed context for the `query` tool and the receiving end of its stream
+fn streamed_context(level: LogLevel) -> (ToolContext, mpsc::UnboundedReceiver<Value>) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let ctx = ToolContext::new(AuthenticatedUser(create_test_user()))
+        .with_tool_name("query")
+        .with_log_level(level)
+        .with_client_channel(
+            tx,
+            Arc::new(PendingElicitations::new()),
+            Duration::from_secs(5),
+        );
+    (ctx, rx)
+}
+
+// ============================================================================
+// LogLevel Tests
+// ============================================================================
+
+#[test]
+fn test_levels_are_ordered_by_severity() {
+    assert!(LogLevel::Debug < LogLevel::Info);
+    assert!(LogLevel::Warning < LogLevel::Error);
+    assert!(LogLevel::Alert < LogLevel::Emergency);
+    assert_eq!(json!(LogLevel::Warning), json!("warning"));
+    assert_eq!(LogLevel::Critical.as_str(), "critical");
+}
+
+#[test]
+fn test_logging_config() {
+    assert_eq!(ServerConfig::default().logging.level, LogLevel::Info);
+    let config: ServerConfig = toml::from_str("[logging]\nlevel = \"debug\"\n").unwrap();
+    assert_eq!(config.logging.level, LogLevel::Debug);
+    assert!(toml::from_str::<ServerConfig>("[logging]\nlevel = \"verbose\"\n").is_err());
+}
+
+// ============================================================================
+// ToolContext::log Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_log_sends_message_notification() {
+    let (ctx, mut rx) = streamed_context(LogLevel::Info);
+
+    ctx.log(
+        LogLevel::Warning,
+        "slow query",
+        json!({ "duration_ms": 1200, "rows": 3 }),
+    );
+
+    assert_eq!(
+        rx.try_recv().unwrap(),
+        json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/message",
+            "params": {
+                "level": "warning",
+                "logger": "query",
+                "data": { "message": "slow query", "duration_ms": 1200, "rows": 3 }
+            }
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_log_below_level_is_not_sent() {
+    let (ctx, mut rx) = streamed_context(LogLevel::Error);
+
+    ctx.log(LogLevel::Warning, "ignored", Value::Null);
+    ctx.log(LogLevel::Critical, "disk full", json!(["sda1"]));
+
+    let notification = rx.try_recv().unwrap();
+    assert_eq!(
+        notification["params"]["data"],
+        json!({ "message": "disk full", "fields": ["sda1"] })
+    );
+    assert!(rx.try_recv().is_err());
+}
+
+#[test]
+fn test_log_without_stream_only_traces() {
+    let ctx = ToolContext::new(AuthenticatedUser(create_test_user()));
+    ctx.log(LogLevel::Error, "no client stream", json!({}));
+}
+
+// ============================================================================
+// logging/setLevel Tests
+// ============================================================================
+
+/// Tool logging one entry per level from `info` to `error`
+fn chatty_tool() -> ToolFunction {
+    Box::new(|_args, ctx: ToolContext| {
+        Box::pin(async move {
+            for level in [LogLevel::Info, LogLevel::Warning, LogLevel::Error] {
+                ctx.log(level, level.as_str(), Value::Null);
+            }
+            Ok(json!("done"))
+        })
+    })
+}
+
+fn test_state() -> AppState {
+    AppState {
+        tool_registry: Arc::new(ArcSwap::from_pointee(HashMap::from([(
+            "chatty".to_string(),
+            chatty_tool(),
+        )]))),
+        ..Default::default()
+    }
+}
+
+async fn post(state: &AppState, headers: HeaderMap, body: Value) -> Response {
+    mcp_endpoint(
+        State(state.clone()),
+        Extension(AuthenticatedUser(create_test_user())),
+        None,
+        headers,
+        McpBody {
+            body,
+            format: WireFormat::Json,
+        },
+    )
+    .await
+}
+
+async fn body_text(response: Response) -> String {
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+/// Levels of the log notifications a streamed `chatty` invocation receives
+async fn streamed_levels(state: &AppState, mut headers: HeaderMap) -> Vec<String> {
+    headers.insert(
+        header::ACCEPT,
+        HeaderValue::from_static("text/event-stream"),
+    );
+    let body = json!({ "method": "invoke", "params": { "tool_name": "chatty" } });
+    body_text(post(state, headers, body).await)
+        .await
+        .lines()
+        .filter_map(|line| line.strip_prefix("data: "))
+        .map(|data| serde_json::from_str::<Value>(data).unwrap())
+        .filter(|event| event["method"] == "notifications/message")
+        .map(|event| event["params"]["level"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_set_level_filters_session_notifications() {
+    let state = test_state();
+    let response = post(&state, HeaderMap::new(), json!({ "method": "initialize" })).await;
+    let mut headers = HeaderMap::new();
+    headers.insert(SESSION_HEADER, response.headers()[SESSION_HEADER].clone());
+
+    // `[logging] level` applies until the client sets a level
+    assert_eq!(
+        streamed_levels(&state, headers.clone()).await,
+        vec!["info", "warning", "error"]
+    );
+
+    let body = json!({ "id": 2, "method": "logging/setLevel", "params": { "level": "warning" } });
+    let response = body_text(post(&state, headers.clone(), body).await).await;
+    let response: Value = serde_json::from_str(&response).unwrap();
+    assert_eq!(response["result"], json!({}));
+    assert_eq!(response["id"], 2);
+
+    assert_eq!(
+        streamed_levels(&state, headers).await,
+        vec!["warning", "error"]
+    );
+}
+
+#[tokio::test]
+async fn test_set_level_requires_session() {
+    let state = test_state();
+    let body = json!({ "method": "logging/setLevel", "params": { "level": "error" } });
+    let response = body_text(post(&state, HeaderMap::new(), body).await).await;
+    let response: Value = serde_json::from_str(&response).unwrap();
+    assert_eq!(response["error"]["code"], ERROR_INVALID_REQUEST);
+
+    let body = json!({ "method": "logging/setLevel", "params": { "level": "loud" } });
+    assert!(
+        post(&state, HeaderMap::new(), body)
+            .await
+            .status()
+            .is_client_error()
+    );
+}
+
+#[tokio::test]
+async fn test_initialize_advertises_logging() {
+    let state = test_state();
+    let response = post(&state, HeaderMap::new(), json!({ "method": "initialize" })).await;
+    let response: Value = serde_json::from_str(&body_text(response).await).unwrap();
+    assert_eq!(response["result"]["capabilities"]["logging"], json!({}));
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use arc_swap::ArcSwap;
+use axum::extract::State;
+use axum::{Extension, Json};
+use common::*;
+use mcp_server::auth::AuthenticatedUser;
+use mcp_server::tools::{ToolContext, ToolFunction};
+use mcp_server::{AppState, McpRequest, handle_mcp_request};
+use serde_json::{Map, Value, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Tool that echoes the request's progress token and attaches result metadata
+fn meta_tool() -> ToolFunction {
+    Box::new(|_args, ctx: ToolContext| {
+        let token = ctx.progress_token().cloned();
+        ctx.attach_meta("cache", json!("miss"));
+        Box::pin(async move {
+            Ok(json!({
+                "token": token,
+                "_meta": { "source": "tool" }
+            }))
+        })
+    })
+}
+
+fn scalar_tool() -> ToolFunction {
+    Box::new(|_args, ctx: ToolContext| {
+        ctx.attach_meta("cache", json!("miss"));
+        Box::pin(async move { Ok(json!(42)) })
+    })
+}
+
+async fn invoke(tool_name: &str, params: Value) -> Value {
+    let state = AppState {
+        tool_registry: Arc::new(ArcSwap::from_pointee(HashMap::from([
+            ("meta_tool".to_string(), meta_tool()),
+            ("scalar_tool".to_string(), scalar_tool()),
+        ]))),
+        ..Default::default()
+    };
+
+    let mut params = params;
+    params["tool_name"] = json!(tool_name);
+    let request: McpRequest =
+        serde_json::from_value(json!({"method": "invoke", "params": params})).unwrap();
+    let Json(response) = handle_mcp_request(
+        State(state),
+        Extension(AuthenticatedUser(create_test_user())),
+        Json(request),
+    )
+    .await;
+    response.result.expect("invocation should succeed")
+}
+
+// ============================================================================
+// Request _meta Tests
+// ============================================================================
+
+#[test]
+fn test_invoke_meta_deserialization() {
+    let request: McpRequest = serde_json::from_value(json!({
+        "method": "invoke",
+        "params": {
+            "tool_name": "get_current_time",
+            "_meta": {"progressToken": "abc", "traceId": 7}
+        }
+    }))
+    .unwrap();
+
+    match request {
+        McpRequest::Invoke { options, .. } => {
+            let meta = options.meta.unwrap();
+            assert_eq!(meta["progressToken"], "abc");
+            assert_eq!(meta["traceId"], 7);
+        }
+        _ => panic!("Expected Invoke"),
+    }
+}
+
+#[test]
+fn test_context_exposes_request_meta() {
+    let mut meta = Map::new();
+    meta.insert("progressToken".to_string(), json!(5));
+    let ctx = ToolContext::new(AuthenticatedUser(create_test_user())).with_meta(meta);
+
+    assert_eq!(ctx.progress_token(), Some(&json!(5)));
+    assert_eq!(ctx.meta().len(), 1);
+}
+
+#[test]
+fn test_context_result_meta_is_shared_between_clones() {
+    let ctx = ToolContext::new(AuthenticatedUser(create_test_user()));
+    ctx.clone().attach_meta("rows", json!(10));
+
+    let attached = ctx.take_result_meta();
+    assert_eq!(attached["rows"], 10);
+    assert!(ctx.take_result_meta().is_empty());
+}
+
+// ============================================================================
+// Integration Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_tool_sees_progress_token() {
+    let result = invoke("meta_tool", json!({"_meta": {"progressToken": "tok-1"}})).await;
+    assert_eq!(result["token"], "tok-1");
+}
+
+#[tokio::test]
+async fn test_attached_meta_is_merged_into_result() {
+    let result = invoke("meta_tool", json!({})).await;
+
+    assert_eq!(result["_meta"]["source"], "tool");
+    assert_eq!(result["_meta"]["cache"], "miss");
+}
+
+#[tokio::test]
+async fn test_non_object_results_are_unchanged() {
+    let result = invoke("scalar_tool", json!({})).await;
+    assert_eq!(result, json!(42));
+}
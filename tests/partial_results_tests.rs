@@ -0,0 +1,151 @@
+mod common;
+
+use arc_swap::ArcSwap;
+use axum::Extension;
+use axum::body::to_bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, HeaderValue, header};
+use common::*;
+use mcp_server::auth::AuthenticatedUser;
+use mcp_server::elicitation::PendingElicitations;
+use mcp_server::encoding::{McpBody, WireFormat};
+use mcp_server::tools::context::PARTIAL_RESULT_METHOD;
+use mcp_server::tools::{ToolContext, ToolFunction};
+use mcp_server::{AppState, mcp_endpoint};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Tool sending each line of its output as a partial result
+fn lines_tool() -> ToolFunction {
+    Box::new(|_args, ctx| {
+        Box::pin(async move {
+            let lines = ["first", "second", "third"];
+            for line in lines {
+                ctx.send_partial(json!({ "line": line }));
+            }
+            Ok(json!({ "lines": lines }))
+        })
+    })
+}
+
+fn test_state() -> AppState {
+    AppState {
+        tool_registry: Arc::new(ArcSwap::from_pointee(HashMap::from([(
+            "lines".to_string(),
+            lines_tool(),
+        )]))),
+        ..Default::default()
+    }
+}
+
+/// Invoke `lines`, returning the raw response body
+async fn invoke(headers: HeaderMap) -> String {
+    let response = mcp_endpoint(
+        State(test_state()),
+        Extension(AuthenticatedUser(create_test_user())),
+        None,
+        headers,
+        McpBody {
+            body: json!({ "id": 7, "method": "invoke", "params": { "tool_name": "lines" } }),
+            format: WireFormat::Json,
+        },
+    )
+    .await;
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+/// Name and JSON data of each server-sent event
+fn parse_events(body: &str) -> Vec<(String, Value)> {
+    body.split("\n\n")
+        .filter(|event| !event.trim().is_empty())
+        .map(|event| {
+            let mut name = "message".to_string();
+            let mut data = Value::Null;
+            for line in event.lines() {
+                if let Some(value) = line.strip_prefix("event: ") {
+                    name = value.to_string();
+                } else if let Some(value) = line.strip_prefix("data: ") {
+                    data = serde_json::from_str(value).unwrap();
+                }
+            }
+            (name, data)
+        })
+        .collect()
+}
+
+// ============================================================================
+// ToolContext::send_partial Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_send_partial_numbers_chunks() {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let ctx = ToolContext::new(AuthenticatedUser(create_test_user())).with_client_channel(
+        tx,
+        Arc::new(PendingElicitations::new()),
+        Duration::from_secs(5),
+    );
+
+    ctx.send_partial(json!("a"));
+    ctx.clone().send_partial(json!("b"));
+
+    assert_eq!(
+        rx.try_recv().unwrap(),
+        json!({
+            "jsonrpc": "2.0",
+            "method": PARTIAL_RESULT_METHOD,
+            "params": { "index": 0, "chunk": "a" }
+        })
+    );
+    assert_eq!(rx.try_recv().unwrap()["params"]["index"], 1);
+}
+
+#[tokio::test]
+async fn test_send_partial_without_stream_is_dropped() {
+    let ctx = ToolContext::new(AuthenticatedUser(create_test_user()));
+    assert!(!ctx.is_streaming());
+    ctx.send_partial(json!("ignored"));
+}
+
+// ============================================================================
+// Endpoint Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_streamed_invocation_sends_partials_then_result() {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::ACCEPT,
+        HeaderValue::from_static("text/event-stream"),
+    );
+    let events = parse_events(&invoke(headers).await);
+
+    let names: Vec<&str> = events.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, vec!["partial", "partial", "partial", "result"]);
+    let chunks: Vec<&Value> = events[..3]
+        .iter()
+        .map(|(_, data)| &data["params"]["chunk"]["line"])
+        .collect();
+    assert_eq!(chunks, vec!["first", "second", "third"]);
+    assert_eq!(events[2].1["params"]["index"], 2);
+
+    let response = &events[3].1;
+    assert_eq!(response["id"], 7);
+    assert_eq!(
+        response["result"],
+        json!({ "lines": ["first", "second", "third"] })
+    );
+}
+
+#[tokio::test]
+async fn test_plain_invocation_returns_only_result() {
+    let body: Value = serde_json::from_str(&invoke(HeaderMap::new()).await).unwrap();
+    assert_eq!(
+        body["result"],
+        json!({ "lines": ["first", "second", "third"] })
+    );
+}
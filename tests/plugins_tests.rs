@@ -0,0 +1,231 @@
+mod common;
+
+use axum::extract::State;
+use axum::{Extension, Json};
+use common::*;
+use mcp_server::auth::AuthenticatedUser;
+use mcp_server::plugins::{PluginManifest, load_plugins, reload_plugins, watch_plugins};
+use mcp_server::tools::ToolContext;
+use mcp_server::{AppState, McpRequest, handle_mcp_request};
+use serde_json::{Value, json};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use tempfile::TempDir;
+
+/// Echoes its JSON arguments back
+const ECHO_MANIFEST: &str = r#"
+name = "echo_plugin"
+description = "Echo the arguments"
+command = ["cat"]
+
+[parameters]
+type = "object"
+required = ["message"]
+
+[parameters.properties.message]
+type = "string"
+"#;
+
+fn write_manifest(dir: &Path, file: &str, contents: &str) {
+    fs::write(dir.join(file), contents).unwrap();
+}
+
+fn manifest(name: &str, command: &str) -> String {
+    format!(
+        "name = \"{}\"\ndescription = \"test\"\ncommand = [\"sh\", \"-c\", \"{}\"]\n",
+        name, command
+    )
+}
+
+fn user() -> AuthenticatedUser {
+    AuthenticatedUser(create_test_user())
+}
+
+async fn invoke(state: &AppState, tool_name: &str, arguments: Value) -> Value {
+    let request: McpRequest = serde_json::from_value(json!({
+        "method": "invoke",
+        "params": {"tool_name": tool_name, "arguments": arguments}
+    }))
+    .unwrap();
+    let Json(response) =
+        handle_mcp_request(State(state.clone()), Extension(user()), Json(request)).await;
+    serde_json::to_value(response).unwrap()
+}
+
+fn tool_names(state: &AppState) -> Vec<String> {
+    state
+        .tool_definitions
+        .load()
+        .iter()
+        .map(|def| def.name.clone())
+        .collect()
+}
+
+// ============================================================================
+// Manifest Loading Tests
+// ============================================================================
+
+#[test]
+fn test_load_manifest() {
+    let dir = TempDir::new().unwrap();
+    write_manifest(dir.path(), "echo.toml", ECHO_MANIFEST);
+
+    let manifest = PluginManifest::load(&dir.path().join("echo.toml")).unwrap();
+    assert_eq!(manifest.name, "echo_plugin");
+    assert_eq!(manifest.command, vec!["cat"]);
+    assert_eq!(manifest.timeout_secs, 30);
+    assert_eq!(manifest.parameters["required"], json!(["message"]));
+}
+
+#[test]
+fn test_load_plugins_skips_collisions_and_invalid_manifests() {
+    let dir = TempDir::new().unwrap();
+    write_manifest(dir.path(), "a.toml", &manifest("first", "echo 1"));
+    write_manifest(dir.path(), "b.toml", &manifest("first", "echo 2"));
+    write_manifest(dir.path(), "c.toml", &manifest("get_current_time", "date"));
+    write_manifest(dir.path(), "d.toml", "name = \"broken\"");
+    write_manifest(dir.path(), "notes.txt", "not a manifest");
+
+    let reserved = HashSet::from(["get_current_time".to_string()]);
+    let (functions, definitions) = load_plugins(dir.path(), &reserved).unwrap();
+
+    assert_eq!(functions.len(), 1);
+    assert_eq!(definitions.len(), 1);
+    assert_eq!(definitions[0].name, "first");
+}
+
+#[test]
+fn test_missing_directory_is_an_error() {
+    let dir = TempDir::new().unwrap();
+    assert!(load_plugins(&dir.path().join("missing"), &HashSet::new()).is_err());
+}
+
+// ============================================================================
+// Execution Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_plugin_receives_arguments_on_stdin() {
+    let dir = TempDir::new().unwrap();
+    write_manifest(dir.path(), "echo.toml", ECHO_MANIFEST);
+    let (functions, _) = load_plugins(dir.path(), &HashSet::new()).unwrap();
+
+    let result =
+        functions["echo_plugin"](Some(json!({"message": "hello"})), ToolContext::new(user()))
+            .await
+            .unwrap();
+    assert_eq!(result, json!({"message": "hello"}));
+}
+
+#[tokio::test]
+async fn test_plugin_arguments_are_validated() {
+    let dir = TempDir::new().unwrap();
+    write_manifest(dir.path(), "echo.toml", ECHO_MANIFEST);
+    let (functions, _) = load_plugins(dir.path(), &HashSet::new()).unwrap();
+
+    let error = functions["echo_plugin"](Some(json!({})), ToolContext::new(user()))
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("Missing required parameter"));
+}
+
+#[tokio::test]
+async fn test_plain_text_output_and_failures() {
+    let dir = TempDir::new().unwrap();
+    write_manifest(
+        dir.path(),
+        "user.toml",
+        &manifest("whoami", "echo $MCP_USERNAME"),
+    );
+    write_manifest(
+        dir.path(),
+        "fail.toml",
+        &manifest("failing", "echo boom >&2; exit 3"),
+    );
+    let (functions, _) = load_plugins(dir.path(), &HashSet::new()).unwrap();
+
+    let result = functions["whoami"](None, ToolContext::new(user()))
+        .await
+        .unwrap();
+    assert_eq!(result, json!(TEST_USERNAME));
+
+    let error = functions["failing"](None, ToolContext::new(user()))
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("boom"));
+}
+
+#[tokio::test]
+async fn test_plugin_timeout() {
+    let dir = TempDir::new().unwrap();
+    write_manifest(
+        dir.path(),
+        "slow.toml",
+        &format!("{}timeout_secs = 1\n", manifest("slow", "sleep 10")),
+    );
+    let (functions, _) = load_plugins(dir.path(), &HashSet::new()).unwrap();
+
+    let error = functions["slow"](None, ToolContext::new(user()))
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("timed out"));
+}
+
+// ============================================================================
+// Reload Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_reload_registers_plugins_and_notifies() {
+    let dir = TempDir::new().unwrap();
+    write_manifest(dir.path(), "echo.toml", ECHO_MANIFEST);
+    let state = AppState::default();
+    let mut notifications = state.notifications.subscribe();
+
+    assert_eq!(reload_plugins(&state, dir.path()).unwrap(), 1);
+
+    let names = tool_names(&state);
+    assert!(names.contains(&"echo_plugin".to_string()));
+    assert!(names.contains(&"get_current_time".to_string()));
+    assert_eq!(
+        notifications.recv().await.unwrap()["method"],
+        "notifications/tools/list_changed"
+    );
+
+    let response = invoke(&state, "echo_plugin", json!({"message": "hi"})).await;
+    assert_eq!(response["result"]["message"], "hi");
+}
+
+#[tokio::test]
+async fn test_watcher_picks_up_added_and_removed_plugins() {
+    let dir = TempDir::new().unwrap();
+    let state = AppState::default();
+    reload_plugins(&state, dir.path()).unwrap();
+    let mut notifications = state.notifications.subscribe();
+    let watcher = watch_plugins(
+        state.clone(),
+        dir.path().to_path_buf(),
+        Duration::from_millis(50),
+    );
+
+    write_manifest(dir.path(), "echo.toml", ECHO_MANIFEST);
+    tokio::time::timeout(Duration::from_secs(5), notifications.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(tool_names(&state).contains(&"echo_plugin".to_string()));
+
+    fs::remove_file(dir.path().join("echo.toml")).unwrap();
+    tokio::time::timeout(Duration::from_secs(5), notifications.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(!tool_names(&state).contains(&"echo_plugin".to_string()));
+
+    let response = invoke(&state, "echo_plugin", json!({"message": "hi"})).await;
+    assert_eq!(response["error"]["message"], "Tool 'echo_plugin' not found");
+
+    watcher.abort();
+}
@@ -0,0 +1,395 @@
+mod common;
+
+use axum::extract::State;
+use axum::routing::post;
+use axum::{Json, Router};
+use axum_test::TestServer;
+use common::*;
+use mcp_server::auth::{AuthFuture, AuthenticatedUser, CredentialsFormat, UserCredentials};
+use mcp_server::config::OpaConfig;
+use mcp_server::policy::{
+    AuthorizationRequest, Authorizer, Denial, OpaAuthorizer, PolicyEngine, PolicyFile,
+    matches_pattern,
+};
+use mcp_server::{AppOptions, AppState, ERROR_FORBIDDEN, ERROR_INTERNAL, mcp_router};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use tempfile::NamedTempFile;
+
+const ANALYST_API_KEY: &str = "analyst-api-key-000";
+
+const POLICIES: &str = r#"
+default = "deny"
+
+[[rules]]
+roles = ["analyst"]
+tools = ["db.*", "calculate"]
+effect = "allow"
+
+[[rules]]
+roles = ["analyst"]
+tools = ["db.admin_*"]
+effect = "deny"
+reason = "Analysts have read-only database access"
+
+[[rules]]
+roles = ["*"]
+tools = ["get_current_time"]
+effect = "allow"
+"#;
+
+fn engine() -> PolicyEngine {
+    PolicyEngine::new(toml::from_str::<PolicyFile>(POLICIES).unwrap()).unwrap()
+}
+
+fn analyst() -> Vec<String> {
+    vec!["analyst".to_string()]
+}
+
+// ============================================================================
+// Pattern Tests
+// ============================================================================
+
+#[test]
+fn test_matches_pattern() {
+    assert!(matches_pattern("db.*", "db.query"));
+    assert!(matches_pattern("db.*", "db."));
+    assert!(!matches_pattern("db.*", "dbx.query"));
+    assert!(matches_pattern("*", "anything"));
+    assert!(matches_pattern(
+        "docker_*_container",
+        "docker_inspect_container"
+    ));
+    assert!(!matches_pattern(
+        "docker_*_container",
+        "docker_list_containers"
+    ));
+    assert!(matches_pattern("calculate", "calculate"));
+    assert!(!matches_pattern("calculate", "calculate_v2"));
+}
+
+// ============================================================================
+// PolicyEngine Tests
+// ============================================================================
+
+#[test]
+fn test_empty_engine_allows_everything() {
+    assert!(PolicyEngine::default().evaluate(&[], "db.drop").is_ok());
+}
+
+#[test]
+fn test_allow_rules_grant_namespaces() {
+    let engine = engine();
+    assert!(engine.evaluate(&analyst(), "db.query").is_ok());
+    assert!(engine.evaluate(&analyst(), "calculate").is_ok());
+    assert!(engine.evaluate(&[], "get_current_time").is_ok());
+}
+
+#[test]
+fn test_deny_rule_wins_with_reason() {
+    let denial = engine().evaluate(&analyst(), "db.admin_drop").unwrap_err();
+    assert_eq!(
+        denial,
+        Denial {
+            tool: "db.admin_drop".to_string(),
+            roles: analyst(),
+            rule: Some("db.admin_*".to_string()),
+            reason: "Analysts have read-only database access".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_default_deny_for_unmatched_tools() {
+    let denial = engine().evaluate(&analyst(), "admin.reset").unwrap_err();
+    assert_eq!(denial.rule, None);
+    assert_eq!(
+        denial.reason,
+        "No policy allows this tool for the user's roles"
+    );
+
+    assert!(engine().evaluate(&[], "db.query").is_err());
+}
+
+#[test]
+fn test_rules_without_roles_or_tools_rejected() {
+    let policies: PolicyFile = toml::from_str(
+        r#"
+        [[rules]]
+        roles = []
+        tools = ["db.*"]
+        effect = "allow"
+        "#,
+    )
+    .unwrap();
+    let error = PolicyEngine::new(policies).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Policy rule 1 must list at least one role and one tool"
+    );
+}
+
+#[test]
+fn test_load_policies_file() {
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(POLICIES.as_bytes()).unwrap();
+    let engine = PolicyEngine::load(file.path()).unwrap();
+    assert!(engine.evaluate(&analyst(), "db.query").is_ok());
+
+    let mut invalid = NamedTempFile::new().unwrap();
+    invalid.write_all(b"default = \"maybe\"").unwrap();
+    assert!(PolicyEngine::load(invalid.path()).is_err());
+}
+
+#[test]
+fn test_example_policies_file_loads() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("config/policies.toml.example");
+    let engine = PolicyEngine::load(&path).unwrap();
+    let admin = vec!["admin".to_string()];
+    assert!(engine.evaluate(&admin, "docker_container_action").is_ok());
+    assert!(
+        engine
+            .evaluate(&analyst(), "docker_container_action")
+            .is_err()
+    );
+    assert!(engine.evaluate(&[], "generate_id").is_ok());
+}
+
+#[test]
+fn test_roles_parsed_from_credentials() {
+    let config = CredentialsFormat::Toml
+        .parse(
+            r#"
+            [carol]
+            api_key = "carol-key"
+            roles = ["analyst", "oncall"]
+            "#,
+        )
+        .unwrap();
+    assert_eq!(config.users["carol"].roles, vec!["analyst", "oncall"]);
+}
+
+// ============================================================================
+// Integration Tests
+// ============================================================================
+
+fn server() -> TestServer {
+    let mut store = (*create_test_credentials_store()).clone();
+    store.insert(
+        ANALYST_API_KEY.to_string(),
+        UserCredentials::new(
+            "analyst".to_string(),
+            ANALYST_API_KEY.to_string(),
+            HashMap::new(),
+        )
+        .with_roles(analyst()),
+    );
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(POLICIES.as_bytes()).unwrap();
+    let mut options = AppOptions::default();
+    options.config.policies.path = Some(file.path().to_path_buf());
+    let state = AppState::new(Arc::new(store), options).unwrap();
+    TestServer::new(mcp_router(state)).unwrap()
+}
+
+async fn invoke(server: &TestServer, api_key: &str, tool_name: &str) -> Value {
+    server
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", api_key))
+        .json(&json!({
+            "method": "invoke",
+            "params": {"tool_name": tool_name, "arguments": {"expression": "1 + 1"}}
+        }))
+        .await
+        .json()
+}
+
+#[tokio::test]
+async fn test_invoke_allowed_by_role() {
+    let server = server();
+    let body = invoke(&server, ANALYST_API_KEY, "calculate").await;
+    assert_eq!(body["result"]["result"], 2.0);
+}
+
+#[tokio::test]
+async fn test_invoke_denied_with_reason_in_error_data() {
+    let server = server();
+    let body = invoke(&server, TEST_API_KEY, "calculate").await;
+    assert_eq!(body["error"]["code"], ERROR_FORBIDDEN);
+    assert_eq!(
+        body["error"]["message"],
+        "Forbidden: tool 'calculate' denied by policy: No policy allows this tool for the user's roles"
+    );
+    assert_eq!(
+        body["error"]["data"],
+        json!({
+            "tool": "calculate",
+            "roles": [],
+            "reason": "No policy allows this tool for the user's roles"
+        })
+    );
+}
+
+// ============================================================================
+// External Authorizer Tests
+// ============================================================================
+
+type MockOpaState = (Arc<Mutex<Vec<Value>>>, fn(&Value) -> Value);
+
+/// OPA-like decision endpoint answering `decision(input)` and recording
+/// every input
+async fn mock_opa(decision: fn(&Value) -> Value) -> (String, Arc<Mutex<Vec<Value>>>) {
+    async fn decide(
+        State((inputs, decision)): State<MockOpaState>,
+        Json(body): Json<Value>,
+    ) -> Json<Value> {
+        let result = decision(&body["input"]);
+        inputs.lock().unwrap().push(body["input"].clone());
+        Json(json!({ "result": result }))
+    }
+
+    let inputs = Arc::new(Mutex::new(Vec::new()));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = Router::new()
+        .route("/v1/data/mcp/allow", post(decide))
+        .with_state((inputs.clone(), decision));
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    (format!("http://{}/v1/data/mcp/allow", addr), inputs)
+}
+
+fn no_multiplication(input: &Value) -> Value {
+    let expression = input["arguments"]["expression"]
+        .as_str()
+        .unwrap_or_default();
+    if expression.contains('*') {
+        json!({ "allow": false, "reason": "Multiplication is not allowed" })
+    } else {
+        json!(true)
+    }
+}
+
+fn analyst_user() -> AuthenticatedUser {
+    AuthenticatedUser(
+        UserCredentials::new(
+            "analyst".to_string(),
+            ANALYST_API_KEY.to_string(),
+            HashMap::new(),
+        )
+        .with_roles(analyst()),
+    )
+}
+
+#[tokio::test]
+async fn test_opa_decisions() {
+    let (url, inputs) = mock_opa(no_multiplication).await;
+    let opa = OpaAuthorizer::new(&url, &OpaConfig::default()).unwrap();
+    let user = analyst_user();
+
+    let arguments = json!({ "expression": "1 + 1" });
+    let request = AuthorizationRequest::new(&user, "calculate", &arguments, None);
+    assert!(opa.authorize(&request).await.unwrap().is_ok());
+    assert_eq!(
+        inputs.lock().unwrap()[0],
+        json!({
+            "user": { "username": "analyst", "roles": ["analyst"], "admin": false },
+            "tool": "calculate",
+            "arguments": { "expression": "1 + 1" }
+        })
+    );
+
+    let arguments = json!({ "expression": "2 * 3" });
+    let request = AuthorizationRequest::new(&user, "calculate", &arguments, None);
+    let denial = opa.authorize(&request).await.unwrap().unwrap_err();
+    assert_eq!(denial.reason, "Multiplication is not allowed");
+    assert_eq!(denial.roles, analyst());
+}
+
+#[tokio::test]
+async fn test_opa_undefined_decision_denies() {
+    let (url, _) = mock_opa(|_| Value::Null).await;
+    let opa = OpaAuthorizer::new(&url, &OpaConfig::default()).unwrap();
+    let user = analyst_user();
+    let request = AuthorizationRequest::new(&user, "calculate", &Value::Null, None);
+    let denial = opa.authorize(&request).await.unwrap().unwrap_err();
+    assert_eq!(denial.reason, "Denied by the external policy engine");
+}
+
+#[tokio::test]
+async fn test_opa_unreachable_fails_closed_unless_fail_open() {
+    let url = "http://127.0.0.1:9/v1/data/mcp/allow";
+    let user = analyst_user();
+    let request = AuthorizationRequest::new(&user, "calculate", &Value::Null, None);
+
+    let closed = OpaAuthorizer::new(url, &OpaConfig::default()).unwrap();
+    assert!(closed.authorize(&request).await.is_err());
+
+    let open = OpaAuthorizer::new(
+        url,
+        &OpaConfig {
+            fail_open: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert!(open.authorize(&request).await.unwrap().is_ok());
+}
+
+#[tokio::test]
+async fn test_invoke_consults_opa_after_policies() {
+    let (url, inputs) = mock_opa(no_multiplication).await;
+    let mut options = AppOptions::default();
+    options.config.policies.opa.url = Some(url);
+    let state = AppState::new(create_test_credentials_store(), options).unwrap();
+    let server = TestServer::new(mcp_router(state)).unwrap();
+
+    let body = invoke(&server, TEST_API_KEY, "calculate").await;
+    assert_eq!(body["result"]["result"], 2.0);
+
+    let body: Value = server
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .json(&json!({
+            "method": "invoke",
+            "params": {"tool_name": "calculate", "arguments": {"expression": "2 * 3"}}
+        }))
+        .await
+        .json();
+    assert_eq!(body["error"]["code"], ERROR_FORBIDDEN);
+    assert_eq!(
+        body["error"]["data"]["reason"],
+        "Multiplication is not allowed"
+    );
+    assert_eq!(inputs.lock().unwrap().len(), 2);
+}
+
+/// Authorizer that cannot reach its backend
+struct Broken;
+
+impl Authorizer for Broken {
+    fn authorize<'a>(
+        &'a self,
+        _request: &'a AuthorizationRequest<'a>,
+    ) -> AuthFuture<'a, Result<(), Denial>> {
+        Box::pin(async { Err(anyhow::anyhow!("backend down")) })
+    }
+}
+
+#[tokio::test]
+async fn test_custom_authorizer_error_fails_invocation() {
+    let options = AppOptions::default().with_authorizer(Broken);
+    let state = AppState::new(create_test_credentials_store(), options).unwrap();
+    let server = TestServer::new(mcp_router(state)).unwrap();
+
+    let body = invoke(&server, TEST_API_KEY, "calculate").await;
+    assert_eq!(body["error"]["code"], ERROR_INTERNAL);
+    assert_eq!(
+        body["error"]["message"],
+        "Authorization failed: backend down"
+    );
+}
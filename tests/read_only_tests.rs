@@ -0,0 +1,148 @@
+mod common;
+
+use axum_test::TestServer;
+use common::*;
+use mcp_server::auth::UserCredentials;
+use mcp_server::read_only::{ReadOnlyMode, is_destructive};
+use mcp_server::tools::{ToolFunction, initialize_all_tools};
+use mcp_server::{AppOptions, AppState, ERROR_READ_ONLY, ToolDefinition, mcp_router};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const ADMIN_API_KEY: &str = "admin-api-key-000";
+
+fn definition(name: &str, annotations: Option<Value>) -> ToolDefinition {
+    ToolDefinition {
+        name: name.to_string(),
+        annotations,
+        ..Default::default()
+    }
+}
+
+/// Built-in tools plus a destructive `drop_table`
+fn server(read_only: bool) -> TestServer {
+    let mut store = (*create_test_credentials_store()).clone();
+    store.insert(
+        ADMIN_API_KEY.to_string(),
+        UserCredentials::new(
+            "admin".to_string(),
+            ADMIN_API_KEY.to_string(),
+            HashMap::new(),
+        )
+        .with_admin(true),
+    );
+    let mut options = AppOptions::default();
+    options.config.read_only.enabled = read_only;
+    let state = AppState::new(Arc::new(store), options).unwrap();
+
+    let (mut functions, mut definitions) = initialize_all_tools().unwrap();
+    let drop_table: ToolFunction =
+        Box::new(|_args, _ctx| Box::pin(async { Ok(json!({ "dropped": true })) }));
+    functions.insert("drop_table".to_string(), drop_table);
+    definitions.push(definition(
+        "drop_table",
+        Some(json!({ "destructiveHint": true })),
+    ));
+    state.replace_tools(functions, definitions);
+
+    TestServer::new(mcp_router(state)).unwrap()
+}
+
+async fn invoke(server: &TestServer, tool_name: &str) -> Value {
+    server
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .json(&json!({"method": "invoke", "params": {"tool_name": tool_name}}))
+        .await
+        .json()
+}
+
+async fn set_read_only(
+    server: &TestServer,
+    api_key: &str,
+    enabled: bool,
+) -> axum_test::TestResponse {
+    server
+        .post("/admin/read-only")
+        .add_query_param("enabled", enabled)
+        .add_header("Authorization", format!("Bearer {}", api_key))
+        .await
+}
+
+#[test]
+fn test_destructive_follows_annotation_defaults() {
+    assert!(is_destructive(&definition("unannotated", None)));
+    assert!(is_destructive(&definition(
+        "writer",
+        Some(json!({ "readOnlyHint": false }))
+    )));
+    assert!(!is_destructive(&definition(
+        "reader",
+        Some(json!({ "readOnlyHint": true }))
+    )));
+    assert!(!is_destructive(&definition(
+        "appender",
+        Some(json!({ "destructiveHint": false }))
+    )));
+
+    let mode = ReadOnlyMode::new(false);
+    assert!(!mode.blocks(None));
+    mode.set(true);
+    assert!(mode.blocks(None));
+}
+
+#[tokio::test]
+async fn test_read_only_blocks_destructive_tools_only() {
+    let server = server(true);
+
+    let blocked = invoke(&server, "drop_table").await;
+    assert_eq!(blocked["error"]["code"], ERROR_READ_ONLY);
+    assert_eq!(
+        blocked["error"]["message"],
+        "Server is in read-only mode: tool 'drop_table' may modify data"
+    );
+
+    assert!(invoke(&server, "get_current_time").await["result"].is_object());
+
+    let discover: Value = server
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .json(&json!({"method": "discover"}))
+        .await
+        .json();
+    assert!(
+        discover["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|tool| tool["name"] == "drop_table")
+    );
+}
+
+#[tokio::test]
+async fn test_admin_toggles_read_only() {
+    let server = server(false);
+    assert_eq!(
+        invoke(&server, "drop_table").await["result"]["dropped"],
+        true
+    );
+
+    set_read_only(&server, TEST_API_KEY, true)
+        .await
+        .assert_status_forbidden();
+
+    let response = set_read_only(&server, ADMIN_API_KEY, true).await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<Value>()["result"]["read_only"], true);
+    assert_eq!(
+        invoke(&server, "drop_table").await["error"]["code"],
+        ERROR_READ_ONLY
+    );
+
+    set_read_only(&server, ADMIN_API_KEY, false).await;
+    assert_eq!(
+        invoke(&server, "drop_table").await["result"]["dropped"],
+        true
+    );
+}
@@ -0,0 +1,145 @@
+mod common;
+
+use axum::http::StatusCode;
+use axum_test::{TestResponse, TestServer};
+use chrono::Utc;
+use common::*;
+use mcp_server::config::{ReplayConfig, ServerConfig};
+use mcp_server::replay::{NONCE_HEADER, SIGNATURE_HEADER, TIMESTAMP_HEADER, sign_request};
+use mcp_server::{AppOptions, create_app, create_app_with_options};
+use serde_json::{Value, json};
+
+fn protected_server() -> TestServer {
+    let config = ServerConfig {
+        replay: ReplayConfig {
+            enabled: true,
+            max_skew_secs: 60,
+        },
+        ..Default::default()
+    };
+    let app =
+        create_app_with_options(create_test_credentials_store(), AppOptions::new(config)).unwrap();
+    TestServer::new(app).unwrap()
+}
+
+async fn signed_discover(
+    server: &TestServer,
+    timestamp: i64,
+    nonce: &str,
+    signature: &str,
+) -> TestResponse {
+    server
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .add_header(TIMESTAMP_HEADER, timestamp.to_string())
+        .add_header(NONCE_HEADER, nonce)
+        .add_header(SIGNATURE_HEADER, signature)
+        .json(&json!({"method": "discover"}))
+        .await
+}
+
+fn sign(timestamp: i64, nonce: &str) -> String {
+    sign_request(TEST_API_KEY, timestamp, nonce, "POST", "/mcp")
+}
+
+fn error_message(response: &TestResponse) -> String {
+    let body: Value = response.json();
+    body["error"]["message"].as_str().unwrap().to_string()
+}
+
+// ============================================================================
+// Replay Protection Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_signed_request_is_accepted() {
+    let server = protected_server();
+    let now = Utc::now().timestamp();
+
+    let response = signed_discover(&server, now, "nonce-1", &sign(now, "nonce-1")).await;
+
+    response.assert_status_ok();
+    let body: Value = response.json();
+    assert!(body["result"]["tools"].is_array());
+}
+
+#[tokio::test]
+async fn test_repeated_nonce_is_rejected() {
+    let server = protected_server();
+    let now = Utc::now().timestamp();
+    let signature = sign(now, "nonce-2");
+
+    signed_discover(&server, now, "nonce-2", &signature)
+        .await
+        .assert_status_ok();
+    let replayed = signed_discover(&server, now, "nonce-2", &signature).await;
+
+    replayed.assert_status(StatusCode::UNAUTHORIZED);
+    assert!(error_message(&replayed).contains("nonce already used"));
+}
+
+#[tokio::test]
+async fn test_stale_timestamp_is_rejected() {
+    let server = protected_server();
+    let stale = Utc::now().timestamp() - 120;
+
+    let response = signed_discover(&server, stale, "nonce-3", &sign(stale, "nonce-3")).await;
+
+    response.assert_status(StatusCode::UNAUTHORIZED);
+    assert!(error_message(&response).contains("outside the accepted window"));
+}
+
+#[tokio::test]
+async fn test_bad_signature_is_rejected_without_consuming_nonce() {
+    let server = protected_server();
+    let now = Utc::now().timestamp();
+
+    let forged = signed_discover(&server, now, "nonce-4", &sign(now, "other")).await;
+    forged.assert_status(StatusCode::UNAUTHORIZED);
+    assert!(error_message(&forged).contains("Invalid request signature"));
+
+    signed_discover(&server, now, "nonce-4", &sign(now, "nonce-4"))
+        .await
+        .assert_status_ok();
+}
+
+#[tokio::test]
+async fn test_signature_is_bound_to_path() {
+    let server = protected_server();
+    let now = Utc::now().timestamp();
+    let signature = sign_request(TEST_API_KEY, now, "nonce-5", "GET", "/admin/history");
+
+    let response = signed_discover(&server, now, "nonce-5", &signature).await;
+    response.assert_status(StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_missing_headers_are_rejected() {
+    let server = protected_server();
+
+    let response = server
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .json(&json!({"method": "discover"}))
+        .await;
+
+    response.assert_status(StatusCode::UNAUTHORIZED);
+    assert!(error_message(&response).contains("Missing or malformed"));
+}
+
+#[tokio::test]
+async fn test_disabled_by_default() {
+    let server = TestServer::new(create_app(create_test_credentials_store()).unwrap()).unwrap();
+
+    server
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .json(&json!({"method": "discover"}))
+        .await
+        .assert_status_ok();
+}
+
+#[tokio::test]
+async fn test_health_is_not_protected() {
+    protected_server().get("/health").await.assert_status_ok();
+}
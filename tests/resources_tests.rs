@@ -0,0 +1,231 @@
+mod common;
+
+use arc_swap::ArcSwap;
+use axum::extract::State;
+use axum::{Extension, Json};
+use common::*;
+use mcp_server::auth::{AuthenticatedUser, UserCredentials};
+use mcp_server::config::{OversizedResult, ResultsConfig, ServerConfig};
+use mcp_server::resources::{RESULT_URI_PREFIX, ResourceStore};
+use mcp_server::store::SharedStore;
+use mcp_server::tools::transform::TruncateStrings;
+use mcp_server::tools::{ToolContext, ToolFunction};
+use mcp_server::{
+    AppState, ERROR_INVALID_PARAMS, ERROR_RESULT_TOO_LARGE, McpRequest, McpResponse,
+    handle_mcp_request,
+};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Tool returning a result of roughly `size` bytes
+fn sized_tool() -> ToolFunction {
+    Box::new(|args, _ctx: ToolContext| {
+        Box::pin(async move {
+            let size = args.unwrap()["size"].as_u64().unwrap() as usize;
+            Ok(json!({ "data": "x".repeat(size) }))
+        })
+    })
+}
+
+fn test_state(threshold: usize) -> AppState {
+    state_with(ResultsConfig {
+        offload_threshold_bytes: threshold,
+        ..Default::default()
+    })
+}
+
+/// State limiting results to 1000 bytes
+fn limited_state(oversized: OversizedResult) -> AppState {
+    state_with(ResultsConfig {
+        max_bytes: 1000,
+        oversized,
+        ..Default::default()
+    })
+}
+
+fn state_with(results: ResultsConfig) -> AppState {
+    AppState {
+        tool_registry: Arc::new(ArcSwap::from_pointee(HashMap::from([(
+            "sized".to_string(),
+            sized_tool(),
+        )]))),
+        config: Arc::new(ServerConfig {
+            results,
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+async fn call(state: &AppState, user: UserCredentials, request: Value) -> McpResponse {
+    let request: McpRequest = serde_json::from_value(request).unwrap();
+    let Json(response) = handle_mcp_request(
+        State(state.clone()),
+        Extension(AuthenticatedUser(user)),
+        Json(request),
+    )
+    .await;
+    response
+}
+
+async fn invoke_sized(state: &AppState, size: usize) -> Value {
+    call_sized(state, size)
+        .await
+        .result
+        .expect("invocation should succeed")
+}
+
+async fn call_sized(state: &AppState, size: usize) -> McpResponse {
+    call(
+        state,
+        create_test_user(),
+        json!({"method": "invoke", "params": {"tool_name": "sized", "arguments": {"size": size}}}),
+    )
+    .await
+}
+
+// ============================================================================
+// Offloading Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_small_result_is_inlined() {
+    let state = test_state(1024);
+    let result = invoke_sized(&state, 10).await;
+    assert_eq!(result["data"], "x".repeat(10));
+}
+
+#[tokio::test]
+async fn test_offloading_disabled_by_default() {
+    let state = test_state(0);
+    let result = invoke_sized(&state, 100_000).await;
+    assert_eq!(result["data"].as_str().unwrap().len(), 100_000);
+}
+
+#[tokio::test]
+async fn test_large_result_becomes_resource_link() {
+    let state = test_state(1024);
+    let result = invoke_sized(&state, 4096).await;
+
+    let link = &result["content"][0];
+    assert_eq!(link["type"], "resource_link");
+    assert_eq!(link["mimeType"], "application/json");
+    assert!(link["uri"].as_str().unwrap().starts_with(RESULT_URI_PREFIX));
+    assert!(link["size"].as_u64().unwrap() > 4096);
+
+    let read = call(
+        &state,
+        create_test_user(),
+        json!({"method": "resources/read", "params": {"uri": link["uri"]}}),
+    )
+    .await;
+    let contents = &read.result.unwrap()["contents"][0];
+    assert_eq!(contents["uri"], link["uri"]);
+    let original: Value = serde_json::from_str(contents["text"].as_str().unwrap()).unwrap();
+    assert_eq!(original["data"].as_str().unwrap().len(), 4096);
+}
+
+#[tokio::test]
+async fn test_resource_is_private_to_its_user() {
+    let state = test_state(1024);
+    let result = invoke_sized(&state, 4096).await;
+    let uri = result["content"][0]["uri"].clone();
+
+    let other = UserCredentials::new("other".to_string(), "other-key".to_string(), HashMap::new());
+    let read = call(
+        &state,
+        other,
+        json!({"method": "resources/read", "params": {"uri": uri}}),
+    )
+    .await;
+
+    assert_eq!(read.error.unwrap().code, ERROR_INVALID_PARAMS);
+}
+
+#[tokio::test]
+async fn test_unknown_resource() {
+    let state = test_state(1024);
+    let read = call(
+        &state,
+        create_test_user(),
+        json!({"method": "resources/read", "params": {"uri": "mcp://results/missing"}}),
+    )
+    .await;
+
+    let error = read.error.unwrap();
+    assert_eq!(error.code, ERROR_INVALID_PARAMS);
+    assert!(error.message.contains("not found"));
+}
+
+// ============================================================================
+// Result Size Limit Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_result_within_limit_is_unchanged() {
+    let state = limited_state(OversizedResult::Error);
+    let result = invoke_sized(&state, 900).await;
+    assert_eq!(result["data"].as_str().unwrap().len(), 900);
+}
+
+#[tokio::test]
+async fn test_oversized_result_fails() {
+    let state = limited_state(OversizedResult::Error);
+    let error = call_sized(&state, 5000).await.error.unwrap();
+
+    assert_eq!(error.code, ERROR_RESULT_TOO_LARGE);
+    let data = error.data.unwrap();
+    assert_eq!(data["tool"], "sized");
+    assert_eq!(data["max_bytes"], 1000);
+    assert!(data["size_bytes"].as_u64().unwrap() > 5000);
+}
+
+#[tokio::test]
+async fn test_oversized_result_truncated() {
+    let state = limited_state(OversizedResult::Truncate);
+    let result = invoke_sized(&state, 5000).await;
+
+    assert_eq!(result["truncated"], true);
+    assert!(result["size_bytes"].as_u64().unwrap() > 5000);
+    assert!(result.to_string().len() <= 1000);
+    let data = result["result"]["data"].as_str().unwrap();
+    assert!(data.len() > 800);
+    assert!(data.ends_with(TruncateStrings::MARKER));
+}
+
+#[tokio::test]
+async fn test_oversized_result_offloaded() {
+    let state = limited_state(OversizedResult::Offload);
+    let result = invoke_sized(&state, 5000).await;
+
+    let uri = &result["content"][0]["uri"];
+    let read = call(
+        &state,
+        create_test_user(),
+        json!({"method": "resources/read", "params": {"uri": uri}}),
+    )
+    .await;
+    let text = read.result.unwrap()["contents"][0]["text"].clone();
+    let original: Value = serde_json::from_str(text.as_str().unwrap()).unwrap();
+    assert_eq!(original["data"].as_str().unwrap().len(), 5000);
+}
+
+// ============================================================================
+// Resource Store Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_offloaded_resource_expires() {
+    let resources = ResourceStore::new(SharedStore::default(), Duration::from_millis(20));
+    let link = resources
+        .offload("alice", "tool", "{}".to_string())
+        .await
+        .unwrap();
+    let uri = link["uri"].as_str().unwrap();
+
+    assert!(resources.read(uri, "alice").await.unwrap().is_some());
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(resources.read(uri, "alice").await.unwrap().is_none());
+}
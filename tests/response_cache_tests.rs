@@ -0,0 +1,326 @@
+mod common;
+
+use arc_swap::ArcSwap;
+use axum::Extension;
+use axum::body::to_bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+use axum::response::Response;
+use common::*;
+use mcp_server::auth::{AuthenticatedUser, UserCredentials};
+use mcp_server::config::{ResponseCacheConfig, ServerConfig};
+use mcp_server::encoding::{McpBody, WireFormat};
+use mcp_server::response_cache::{ResponseCache, cache_ttl, etag, etag_matches};
+use mcp_server::store::SharedStore;
+use mcp_server::tools::{ToolContext, ToolFunction};
+use mcp_server::{AppState, ToolDefinition, mcp_endpoint};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Tool counting its executions; fails when called with `{"fail": true}`
+/// and returns `size` bytes of padding when asked to
+fn counting_tool(calls: Arc<AtomicUsize>) -> ToolFunction {
+    Box::new(move |args, _ctx: ToolContext| {
+        let calls = calls.clone();
+        Box::pin(async move {
+            let call = calls.fetch_add(1, Ordering::SeqCst) + 1;
+            let args = args.unwrap_or_default();
+            if args["fail"] == true {
+                anyhow::bail!("transient failure");
+            }
+            let size = args["size"].as_u64().unwrap_or(0) as usize;
+            Ok(json!({ "call": call, "padding": "x".repeat(size) }))
+        })
+    })
+}
+
+fn definition(name: &str, annotations: Option<Value>) -> ToolDefinition {
+    ToolDefinition {
+        name: name.to_string(),
+        annotations,
+        ..Default::default()
+    }
+}
+
+/// State serving `lookup`, cacheable for a minute, and `plain`, which is not
+fn test_state(config: ResponseCacheConfig, calls: Arc<AtomicUsize>) -> AppState {
+    let definitions = vec![
+        definition(
+            "lookup",
+            Some(json!({ "readOnlyHint": true, "cacheTtlSecs": 60 })),
+        ),
+        definition("plain", None),
+    ];
+    AppState {
+        tool_registry: Arc::new(ArcSwap::from_pointee(HashMap::from([
+            ("lookup".to_string(), counting_tool(calls.clone())),
+            ("plain".to_string(), counting_tool(calls)),
+        ]))),
+        tool_definitions: Arc::new(ArcSwap::from_pointee(definitions)),
+        response_cache: ResponseCache::new(SharedStore::default(), &config),
+        config: Arc::new(ServerConfig {
+            response_cache: config,
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+fn enabled() -> ResponseCacheConfig {
+    ResponseCacheConfig {
+        enabled: true,
+        ..Default::default()
+    }
+}
+
+async fn post(
+    state: &AppState,
+    user: UserCredentials,
+    body: Value,
+    if_none_match: Option<&str>,
+) -> Response {
+    let mut headers = HeaderMap::new();
+    if let Some(tag) = if_none_match {
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_str(tag).unwrap());
+    }
+    mcp_endpoint(
+        State(state.clone()),
+        Extension(AuthenticatedUser(user)),
+        None,
+        headers,
+        McpBody {
+            body,
+            format: WireFormat::Json,
+        },
+    )
+    .await
+}
+
+/// Invoke a tool as the test user, returning the response's `ETag` and body
+async fn invoke(state: &AppState, tool_name: &str, arguments: Value) -> (Option<String>, Value) {
+    let body = json!({
+        "id": 1,
+        "method": "invoke",
+        "params": { "tool_name": tool_name, "arguments": arguments }
+    });
+    read(post(state, create_test_user(), body, None).await).await
+}
+
+async fn read(response: Response) -> (Option<String>, Value) {
+    let etag = response
+        .headers()
+        .get(header::ETAG)
+        .map(|v| v.to_str().unwrap().to_string());
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (etag, serde_json::from_slice(&bytes).unwrap())
+}
+
+// ============================================================================
+// Config Tests
+// ============================================================================
+
+#[test]
+fn test_response_cache_config_parsed() {
+    let defaults = ServerConfig::default().response_cache;
+    assert!(!defaults.enabled);
+    assert_eq!(defaults.max_entry_bytes, 1024 * 1024);
+
+    let config: ServerConfig =
+        toml::from_str("[response_cache]\nenabled = true\nmax_entry_bytes = 4096\n").unwrap();
+    assert!(config.response_cache.enabled);
+    assert_eq!(config.response_cache.max_entry_bytes, 4096);
+}
+
+#[test]
+fn test_cache_ttl_from_annotations() {
+    let ttl = |annotations| cache_ttl(&definition("tool", annotations));
+    assert_eq!(
+        ttl(Some(json!({ "cacheTtlSecs": 30 }))),
+        Some(Duration::from_secs(30))
+    );
+    assert_eq!(ttl(Some(json!({ "cacheTtlSecs": 0 }))), None);
+    assert_eq!(ttl(Some(json!({ "cacheTtlSecs": "30" }))), None);
+    assert_eq!(ttl(Some(json!({ "readOnlyHint": true }))), None);
+    assert_eq!(ttl(None), None);
+}
+
+// ============================================================================
+// Key and ETag Tests
+// ============================================================================
+
+#[test]
+fn test_key_ignores_argument_order() {
+    let lookup = definition("lookup", None);
+    let a: Value = serde_json::from_str(r#"{"city": "Oslo", "units": "metric"}"#).unwrap();
+    let b: Value = serde_json::from_str(r#"{"units": "metric", "city": "Oslo"}"#).unwrap();
+    let key = ResponseCache::key(TEST_USERNAME, &lookup, Some(&a));
+    assert_eq!(key, ResponseCache::key(TEST_USERNAME, &lookup, Some(&b)));
+
+    let other_user = ResponseCache::key(TEST_USERNAME_2, &lookup, Some(&a));
+    let other_arguments = ResponseCache::key(TEST_USERNAME, &lookup, Some(&json!({})));
+    let other_version = ResponseCache::key(
+        TEST_USERNAME,
+        &ToolDefinition {
+            version: Some("2.0.0".to_string()),
+            ..lookup.clone()
+        },
+        Some(&a),
+    );
+    for other in [other_user, other_arguments, other_version] {
+        assert_ne!(key, other);
+    }
+}
+
+#[test]
+fn test_etag_matching() {
+    let tag = etag(&json!({ "answer": 42 }));
+    assert!(tag.starts_with("W/\""));
+    assert_eq!(tag, etag(&json!({ "answer": 42 })));
+    assert_ne!(tag, etag(&json!({ "answer": 43 })));
+
+    assert!(etag_matches(&tag, &tag));
+    assert!(etag_matches(tag.trim_start_matches("W/"), &tag));
+    assert!(etag_matches(&format!("\"other\", {}", tag), &tag));
+    assert!(etag_matches("*", &tag));
+    assert!(!etag_matches("\"other\"", &tag));
+}
+
+// ============================================================================
+// Invocation Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_identical_invocations_served_from_cache() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let state = test_state(enabled(), calls.clone());
+
+    let (first_tag, first) = invoke(&state, "lookup", json!({ "q": "rust" })).await;
+    let (second_tag, second) = invoke(&state, "lookup", json!({ "q": "rust" })).await;
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!(first["result"]["call"], 1);
+    assert_eq!(second["result"], first["result"]);
+    assert!(first_tag.is_some());
+    assert_eq!(second_tag, first_tag);
+    assert_eq!(
+        state
+            .metrics
+            .counter("mcp_response_cache_hits_total", &[("tool", "lookup")]),
+        1
+    );
+
+    // Other arguments and other users miss the cache
+    invoke(&state, "lookup", json!({ "q": "go" })).await;
+    let body = json!({
+        "method": "invoke",
+        "params": { "tool_name": "lookup", "arguments": { "q": "rust" } }
+    });
+    let user = UserCredentials::new(
+        TEST_USERNAME_2.to_string(),
+        TEST_API_KEY_2.to_string(),
+        HashMap::new(),
+    );
+    let (_, other_user) = read(post(&state, user, body, None).await).await;
+    assert_eq!(other_user["result"]["call"], 3);
+}
+
+#[tokio::test]
+async fn test_tools_without_annotation_not_cached() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let state = test_state(enabled(), calls.clone());
+
+    let (tag, _) = invoke(&state, "plain", json!({})).await;
+    invoke(&state, "plain", json!({})).await;
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert!(tag.is_none());
+}
+
+#[tokio::test]
+async fn test_disabled_cache_runs_every_invocation() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let state = test_state(ResponseCacheConfig::default(), calls.clone());
+
+    let (tag, _) = invoke(&state, "lookup", json!({})).await;
+    invoke(&state, "lookup", json!({})).await;
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert!(tag.is_none());
+}
+
+#[tokio::test]
+async fn test_failures_and_large_results_not_cached() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let config = ResponseCacheConfig {
+        enabled: true,
+        max_entry_bytes: 256,
+    };
+    let state = test_state(config, calls.clone());
+
+    let (tag, failed) = invoke(&state, "lookup", json!({ "fail": true })).await;
+    assert!(failed["error"].is_object());
+    assert!(tag.is_none());
+    invoke(&state, "lookup", json!({ "fail": true })).await;
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    invoke(&state, "lookup", json!({ "size": 1000 })).await;
+    invoke(&state, "lookup", json!({ "size": 1000 })).await;
+    assert_eq!(calls.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test]
+async fn test_cached_results_still_authorized() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let mut state = test_state(enabled(), calls.clone());
+    let mut definitions = (**state.tool_definitions.load()).clone();
+    definitions[0].annotations = Some(json!({ "cacheTtlSecs": 60 }));
+    state.tool_definitions = Arc::new(ArcSwap::from_pointee(definitions));
+
+    invoke(&state, "lookup", json!({})).await;
+    state.read_only.set(true);
+    let (_, refused) = invoke(&state, "lookup", json!({})).await;
+    assert!(refused["error"].is_object());
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+// ============================================================================
+// Conditional Request Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_if_none_match_answered_with_not_modified() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let state = test_state(enabled(), calls.clone());
+    let body = json!({ "method": "invoke", "params": { "tool_name": "lookup" } });
+
+    let (tag, _) = read(post(&state, create_test_user(), body.clone(), None).await).await;
+    let tag = tag.unwrap();
+
+    let response = post(&state, create_test_user(), body.clone(), Some(&tag)).await;
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(response.headers()[header::ETAG], tag.as_str());
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert!(bytes.is_empty());
+
+    let response = post(&state, create_test_user(), body, Some("W/\"stale\"")).await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_discover_tagged_and_revalidated() {
+    let state = test_state(enabled(), Arc::new(AtomicUsize::new(0)));
+    let discover = json!({ "method": "discover" });
+
+    let (tag, result) = read(post(&state, create_test_user(), discover.clone(), None).await).await;
+    let tag = tag.unwrap();
+    assert_eq!(tag, etag(&result["result"]));
+
+    let response = post(&state, create_test_user(), discover, Some(&tag)).await;
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+    // The tag changes with the tool set
+    state.replace_tools(HashMap::new(), Vec::new());
+    let discover = json!({ "method": "discover" });
+    let response = post(&state, create_test_user(), discover, Some(&tag)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use arc_swap::ArcSwap;
+use axum::extract::State;
+use axum::{Extension, Json};
+use common::*;
+use mcp_server::auth::AuthenticatedUser;
+use mcp_server::config::ServerConfig;
+use mcp_server::tools::ToolFunction;
+use mcp_server::{AppState, McpRequest, McpResponse, ToolDefinition, handle_mcp_request};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+fn test_state(include_meta: bool) -> AppState {
+    let mut config = ServerConfig::default();
+    config.results.include_meta = include_meta;
+    let tool: ToolFunction = Box::new(|args, _ctx| {
+        Box::pin(async move {
+            if args.is_some_and(|args| args["fail"] == true) {
+                anyhow::bail!("failed");
+            }
+            Ok(json!({ "ok": true }))
+        })
+    });
+    AppState {
+        tool_registry: Arc::new(ArcSwap::from_pointee(HashMap::from([(
+            "report".to_string(),
+            tool,
+        )]))),
+        tool_definitions: Arc::new(ArcSwap::from_pointee(vec![ToolDefinition {
+            name: "report".to_string(),
+            version: Some("1.2.0".to_string()),
+            ..Default::default()
+        }])),
+        config: Arc::new(config),
+        ..Default::default()
+    }
+}
+
+async fn invoke(state: &AppState, params: Value) -> McpResponse {
+    let request: McpRequest =
+        serde_json::from_value(json!({ "method": "invoke", "params": params })).unwrap();
+    let Json(response) = handle_mcp_request(
+        State(state.clone()),
+        Extension(AuthenticatedUser(create_test_user())),
+        Json(request),
+    )
+    .await;
+    response
+}
+
+#[tokio::test]
+async fn test_meta_is_off_by_default() {
+    let response = invoke(&test_state(false), json!({"tool_name": "report"})).await;
+
+    assert!(response.meta.is_none());
+    assert!(
+        serde_json::to_value(&response)
+            .unwrap()
+            .get("meta")
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn test_successful_responses_carry_meta() {
+    let state = test_state(true);
+    let first = invoke(&state, json!({"tool_name": "report"})).await;
+    let second = invoke(&state, json!({"tool_name": "report"})).await;
+
+    let meta = serde_json::to_value(&first).unwrap()["meta"].clone();
+    assert_eq!(meta["tool_version"], "1.2.0");
+    assert_eq!(meta["cache_hit"], false);
+    assert!(meta["duration_ms"].as_f64().unwrap() >= 0.0);
+    assert_eq!(meta["request_id"].as_str().unwrap().len(), 32);
+    assert_ne!(meta["request_id"], second.meta.unwrap()["request_id"]);
+}
+
+#[tokio::test]
+async fn test_idempotent_replay_is_a_cache_hit() {
+    let state = test_state(true);
+    let params = json!({"tool_name": "report", "idempotency_key": "k1"});
+
+    let first = invoke(&state, params.clone()).await;
+    let replay = invoke(&state, params).await;
+
+    assert_eq!(first.meta.unwrap()["cache_hit"], false);
+    assert_eq!(replay.meta.unwrap()["cache_hit"], true);
+}
+
+#[tokio::test]
+async fn test_errors_have_no_meta() {
+    let state = test_state(true);
+
+    let failed = invoke(
+        &state,
+        json!({"tool_name": "report", "arguments": {"fail": true}}),
+    )
+    .await;
+    assert!(failed.error.is_some());
+    assert!(failed.meta.is_none());
+
+    let missing = invoke(&state, json!({"tool_name": "missing"})).await;
+    assert!(missing.meta.is_none());
+}
@@ -0,0 +1,192 @@
+mod common;
+
+use axum_test::TestServer;
+use common::*;
+use mcp_server::auth::UserCredentials;
+use mcp_server::config::SchedulingConfig;
+use mcp_server::metrics::Metrics;
+use mcp_server::scheduler::{InvocationScheduler, QueueFull};
+use mcp_server::{McpRequest, create_app};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const ADMIN_API_KEY: &str = "admin-api-key-000";
+
+fn scheduler(max_concurrent: usize, max_queue: usize) -> Arc<InvocationScheduler> {
+    Arc::new(InvocationScheduler::new(
+        SchedulingConfig {
+            max_concurrent,
+            max_queue,
+        },
+        Arc::new(Metrics::new()),
+    ))
+}
+
+/// Let spawned tasks run until they block
+async fn settle() {
+    tokio::time::sleep(Duration::from_millis(20)).await;
+}
+
+// ============================================================================
+// InvocationScheduler Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_unlimited_scheduler_never_queues() {
+    let scheduler = scheduler(0, 0);
+
+    let _a = scheduler.acquire(0).await.unwrap();
+    let _b = scheduler.acquire(0).await.unwrap();
+    assert_eq!(scheduler.queue_depth(), 0);
+}
+
+#[tokio::test]
+async fn test_queue_full_is_rejected() {
+    let scheduler = scheduler(1, 1);
+
+    let _running = scheduler.acquire(0).await.unwrap();
+    let waiting = tokio::spawn({
+        let scheduler = scheduler.clone();
+        async move { scheduler.acquire(0).await.is_ok() }
+    });
+    settle().await;
+    assert_eq!(scheduler.queue_depth(), 1);
+
+    assert_eq!(scheduler.acquire(0).await.err(), Some(QueueFull));
+
+    drop(_running);
+    assert!(waiting.await.unwrap());
+}
+
+#[tokio::test]
+async fn test_higher_priority_is_scheduled_first() {
+    let scheduler = scheduler(1, 10);
+    let order = Arc::new(Mutex::new(Vec::new()));
+
+    let running = scheduler.acquire(0).await.unwrap();
+
+    let mut handles = Vec::new();
+    for (label, priority) in [("low", -1), ("normal-1", 0), ("high", 5), ("normal-2", 0)] {
+        let scheduler = scheduler.clone();
+        let order = order.clone();
+        handles.push(tokio::spawn(async move {
+            let _permit = scheduler.acquire(priority).await.unwrap();
+            order.lock().unwrap().push(label);
+        }));
+        // Enqueue deterministically so FIFO order within a priority is observable
+        settle().await;
+    }
+    assert_eq!(scheduler.queue_depth(), 4);
+
+    drop(running);
+    for handle in handles {
+        handle.await.unwrap();
+    }
+
+    assert_eq!(
+        *order.lock().unwrap(),
+        vec!["high", "normal-1", "normal-2", "low"]
+    );
+    assert_eq!(scheduler.in_flight(), 0);
+}
+
+#[tokio::test]
+async fn test_abandoned_waiter_does_not_leak_slot() {
+    let scheduler = scheduler(1, 10);
+
+    let running = scheduler.acquire(0).await.unwrap();
+    let abandoned = tokio::spawn({
+        let scheduler = scheduler.clone();
+        async move { scheduler.acquire(0).await.is_ok() }
+    });
+    settle().await;
+    abandoned.abort();
+    let _ = abandoned.await;
+
+    drop(running);
+    assert_eq!(scheduler.in_flight(), 0);
+    let _permit = scheduler.acquire(0).await.unwrap();
+    assert_eq!(scheduler.in_flight(), 1);
+}
+
+#[tokio::test]
+async fn test_scheduler_publishes_metrics() {
+    let metrics = Arc::new(Metrics::new());
+    let scheduler = Arc::new(InvocationScheduler::new(
+        SchedulingConfig {
+            max_concurrent: 1,
+            max_queue: 0,
+        },
+        metrics.clone(),
+    ));
+
+    let _running = scheduler.acquire(0).await.unwrap();
+    assert!(scheduler.acquire(0).await.is_err());
+
+    assert_eq!(metrics.gauge("mcp_invocations_in_flight", &[]), 1);
+    assert_eq!(metrics.gauge("mcp_invocation_queue_depth", &[]), 0);
+    assert_eq!(
+        metrics.counter("mcp_invocation_queue_rejected_total", &[]),
+        1
+    );
+
+    let rendered = metrics.render();
+    assert!(rendered.contains("# TYPE mcp_invocation_queue_rejected_total counter"));
+    assert!(rendered.contains("mcp_invocations_in_flight 1"));
+}
+
+#[test]
+fn test_metrics_render_labels() {
+    let metrics = Metrics::new();
+    metrics.increment_counter("requests_total", &[("tool", "echo")]);
+    metrics.increment_counter("requests_total", &[("tool", "echo")]);
+
+    assert!(metrics.render().contains("requests_total{tool=\"echo\"} 2"));
+}
+
+// ============================================================================
+// Integration Tests
+// ============================================================================
+
+#[test]
+fn test_invoke_priority_deserialization() {
+    let request: McpRequest = serde_json::from_value(json!({
+        "method": "invoke",
+        "params": {"tool_name": "get_current_time", "priority": 3}
+    }))
+    .unwrap();
+
+    match request {
+        McpRequest::Invoke { options, .. } => assert_eq!(options.priority, Some(3)),
+        _ => panic!("Expected Invoke"),
+    }
+}
+
+#[tokio::test]
+async fn test_admin_metrics_endpoint() {
+    let mut store = (*create_test_credentials_store()).clone();
+    store.insert(
+        ADMIN_API_KEY.to_string(),
+        UserCredentials::new(
+            "admin".to_string(),
+            ADMIN_API_KEY.to_string(),
+            HashMap::new(),
+        )
+        .with_admin(true),
+    );
+    let server = TestServer::new(create_app(Arc::new(store)).unwrap()).unwrap();
+
+    server
+        .get("/admin/metrics")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .await
+        .assert_status_forbidden();
+
+    server
+        .get("/admin/metrics")
+        .add_header("Authorization", format!("Bearer {}", ADMIN_API_KEY))
+        .await
+        .assert_status_ok();
+}
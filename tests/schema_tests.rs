@@ -0,0 +1,69 @@
+mod common;
+
+use axum_test::TestServer;
+use common::*;
+use mcp_server::{SCHEMA_BUNDLE_VERSION, create_app};
+use serde_json::Value;
+
+fn server() -> TestServer {
+    TestServer::new(create_app(create_test_credentials_store()).unwrap()).unwrap()
+}
+
+#[tokio::test]
+async fn test_schema_requires_auth() {
+    server().get("/schema").await.assert_status_unauthorized();
+}
+
+#[tokio::test]
+async fn test_schema_bundle_format() {
+    let response = server()
+        .get("/schema")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .await;
+    response.assert_status_ok();
+    let bundle: Value = response.json();
+
+    assert_eq!(bundle["schemaVersion"], SCHEMA_BUNDLE_VERSION);
+    assert_eq!(bundle["server"]["name"], env!("CARGO_PKG_NAME"));
+    assert_eq!(bundle["server"]["version"], env!("CARGO_PKG_VERSION"));
+    assert!(bundle["protocolVersion"].is_string());
+
+    let tools = bundle["tools"].as_array().unwrap();
+    let names: Vec<&str> = tools.iter().map(|t| t["name"].as_str().unwrap()).collect();
+    let mut sorted = names.clone();
+    sorted.sort();
+    assert_eq!(names, sorted, "tools are sorted by name");
+
+    let time_tool = tools
+        .iter()
+        .find(|t| t["name"] == "get_current_time")
+        .unwrap();
+    assert_eq!(time_tool["inputSchema"]["type"], "object");
+    assert_eq!(
+        time_tool["outputSchema"]["required"][0],
+        Value::from("current_time")
+    );
+    assert_eq!(time_tool["annotations"]["readOnlyHint"], true);
+    // Undeclared fields are left out rather than null
+    assert!(time_tool.get("version").is_none());
+}
+
+#[tokio::test]
+async fn test_discover_includes_declared_output_schema() {
+    let response = server()
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .json(&serde_json::json!({"method": "discover"}))
+        .await;
+    let body: Value = response.json();
+    let time_tool = body["result"]["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|t| t["name"] == "get_current_time")
+        .unwrap()
+        .clone();
+
+    assert!(time_tool["output_schema"].is_object());
+    assert!(time_tool.get("version").is_none());
+}
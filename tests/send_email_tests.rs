@@ -0,0 +1,201 @@
+mod common;
+
+use common::*;
+use mcp_server::auth::{AuthenticatedUser, UserCredentials};
+use mcp_server::config::ServerConfig;
+use mcp_server::store::SharedStore;
+use mcp_server::tools::send_email::{SendEmailTool, is_allowed_recipient};
+use mcp_server::tools::{InvalidInput, McpTool, ToolContext};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+
+/// Minimal SMTP server accepting every message; delivers each DATA payload
+/// on the returned channel
+async fn mock_smtp() -> (String, mpsc::UnboundedReceiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("smtp://{}", listener.local_addr().unwrap());
+    let (sender, receiver) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        loop {
+            let (socket, _) = listener.accept().await.unwrap();
+            let sender = sender.clone();
+            tokio::spawn(async move {
+                let (read, mut write) = socket.into_split();
+                let mut lines = BufReader::new(read).lines();
+                write.write_all(b"220 mock ESMTP\r\n").await.unwrap();
+                let mut data: Option<String> = None;
+                while let Ok(Some(line)) = lines.next_line().await {
+                    if let Some(message) = data.as_mut() {
+                        if line == "." {
+                            sender.send(data.take().unwrap()).unwrap();
+                            write.write_all(b"250 queued\r\n").await.unwrap();
+                        } else {
+                            message.push_str(&line);
+                            message.push('\n');
+                        }
+                        continue;
+                    }
+                    let reply: &[u8] = match line.get(..4).unwrap_or_default() {
+                        "EHLO" => b"250 mock\r\n",
+                        "DATA" => {
+                            data = Some(String::new());
+                            b"354 go ahead\r\n"
+                        }
+                        "QUIT" => {
+                            let _ = write.write_all(b"221 bye\r\n").await;
+                            break;
+                        }
+                        _ => b"250 ok\r\n",
+                    };
+                    write.write_all(reply).await.unwrap();
+                }
+            });
+        }
+    });
+    (url, receiver)
+}
+
+fn context(
+    smtp_url: &str,
+    store: &SharedStore,
+    configure: impl FnOnce(&mut ServerConfig),
+) -> ToolContext {
+    let user = UserCredentials::new(
+        "mailer".to_string(),
+        TEST_API_KEY.to_string(),
+        HashMap::from([
+            ("smtp_url".to_string(), smtp_url.to_string()),
+            (
+                "smtp_from".to_string(),
+                "Agent <agent@example.com>".to_string(),
+            ),
+        ]),
+    );
+    let mut config = ServerConfig::default();
+    config.email.allowed_recipients = vec!["@example.com".to_string()];
+    configure(&mut config);
+
+    ToolContext::new(AuthenticatedUser(user))
+        .with_config(Arc::new(config))
+        .with_store(store.clone())
+}
+
+async fn send(ctx: ToolContext, args: Value) -> anyhow::Result<Value> {
+    SendEmailTool.execute_with_context(Some(args), ctx).await
+}
+
+fn message(to: &str) -> Value {
+    json!({"to": [to], "subject": "Report", "body": "All green."})
+}
+
+#[test]
+fn test_recipient_allowlist() {
+    let allowed = vec!["alice@partner.org".to_string(), "*@Example.com".to_string()];
+
+    assert!(is_allowed_recipient("alice@partner.org", &allowed));
+    assert!(is_allowed_recipient("bob@example.COM", &allowed));
+    assert!(!is_allowed_recipient("bob@partner.org", &allowed));
+    assert!(!is_allowed_recipient("eve@notexample.com", &allowed));
+    assert!(!is_allowed_recipient("alice@partner.org", &[]));
+    assert!(is_allowed_recipient(
+        "anyone@anywhere.net",
+        &["*".to_string()]
+    ));
+}
+
+#[tokio::test]
+async fn test_sends_through_the_users_smtp_server() {
+    let (url, mut delivered) = mock_smtp().await;
+    let store = SharedStore::default();
+
+    let result = send(
+        context(&url, &store, |_| {}),
+        message("Bob <bob@example.com>"),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(result["sent"], true);
+    assert_eq!(result["draft"]["from"], "Agent <agent@example.com>");
+    assert_eq!(result["draft"]["to"], json!(["Bob <bob@example.com>"]));
+    let data = delivered.recv().await.unwrap();
+    assert!(data.contains("Subject: Report"));
+    assert!(data.contains("To: Bob <bob@example.com>"));
+    assert!(data.contains("All green."));
+}
+
+#[tokio::test]
+async fn test_dry_run_drafts_without_sending() {
+    // Nothing listens on this URL: a send attempt would fail
+    let store = SharedStore::default();
+    let mut args = message("bob@example.com");
+    args["dry_run"] = json!(true);
+
+    let result = send(context("smtp://127.0.0.1:1", &store, |_| {}), args)
+        .await
+        .unwrap();
+    assert_eq!(result["sent"], false);
+    assert_eq!(result["draft"]["body"], "All green.");
+
+    // Server-wide dry run overrides the caller
+    let ctx = context("smtp://127.0.0.1:1", &store, |config| {
+        config.email.dry_run = true
+    });
+    let result = send(ctx, message("bob@example.com")).await.unwrap();
+    assert_eq!(result["sent"], false);
+}
+
+#[tokio::test]
+async fn test_recipients_outside_the_allowlist_are_invalid_input() {
+    let store = SharedStore::default();
+    let mut args = message("bob@example.com");
+    args["cc"] = json!(["eve@elsewhere.net"]);
+
+    let error = send(context("smtp://127.0.0.1:1", &store, |_| {}), args)
+        .await
+        .unwrap_err();
+    let input = error.downcast_ref::<InvalidInput>().unwrap();
+    assert_eq!(
+        input.message,
+        "recipient 'eve@elsewhere.net' is not allowed"
+    );
+    assert_eq!(input.data, Some(json!({"recipient": "eve@elsewhere.net"})));
+
+    let error = send(
+        context("smtp://127.0.0.1:1", &store, |_| {}),
+        message("not an address"),
+    )
+    .await
+    .unwrap_err();
+    assert!(error.downcast_ref::<InvalidInput>().is_some());
+}
+
+#[tokio::test]
+async fn test_rate_limit_per_user() {
+    let (url, _delivered) = mock_smtp().await;
+    let store = SharedStore::default();
+    let limited = |config: &mut ServerConfig| config.email.max_per_hour = 2;
+
+    for _ in 0..2 {
+        send(context(&url, &store, limited), message("bob@example.com"))
+            .await
+            .unwrap();
+    }
+    let error = send(context(&url, &store, limited), message("bob@example.com"))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Email rate limit reached: at most 2 messages per hour"
+    );
+
+    // Dry runs are not counted
+    let mut args = message("bob@example.com");
+    args["dry_run"] = json!(true);
+    assert!(send(context(&url, &store, limited), args).await.is_ok());
+}
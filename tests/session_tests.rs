@@ -0,0 +1,374 @@
+mod common;
+
+use arc_swap::ArcSwap;
+use axum::body::to_bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+use axum::response::Response;
+use axum::Extension;
+use axum_test::TestServer;
+use common::*;
+use mcp_server::auth::AuthenticatedUser;
+use mcp_server::config::ServerConfig;
+use mcp_server::encoding::{McpBody, WireFormat};
+use mcp_server::metrics::Metrics;
+use mcp_server::session::{ClientInfo, SESSION_HEADER, SessionManager};
+use mcp_server::store::SharedStore;
+use mcp_server::tools::{ToolContext, ToolFunction};
+use mcp_server::{AppState, ERROR_INVALID_REQUEST, PROTOCOL_VERSION, create_app, mcp_endpoint};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Tool returning the client info it was invoked with
+fn client_tool() -> ToolFunction {
+    Box::new(|_args, ctx: ToolContext| {
+        let client = ctx.client().cloned();
+        Box::pin(async move { Ok(json!({ "client": client })) })
+    })
+}
+
+fn test_state() -> AppState {
+    AppState {
+        tool_registry: Arc::new(ArcSwap::from_pointee(HashMap::from([(
+            "client_tool".to_string(),
+            client_tool(),
+        )]))),
+        ..Default::default()
+    }
+}
+
+async fn post(state: &AppState, headers: HeaderMap, body: Value) -> Response {
+    mcp_endpoint(
+        State(state.clone()),
+        Extension(AuthenticatedUser(create_test_user())),
+        None,
+        headers,
+        McpBody {
+            body,
+            format: WireFormat::Json,
+        },
+    )
+    .await
+}
+
+async fn body_json(response: Response) -> Value {
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    serde_json::from_slice(&bytes).unwrap()
+}
+
+fn user_agent(value: &'static str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(header::USER_AGENT, HeaderValue::from_static(value));
+    headers
+}
+
+async fn initialize(state: &AppState) -> String {
+    let response = post(
+        state,
+        user_agent("test-agent/1.0"),
+        json!({
+            "method": "initialize",
+            "params": {
+                "protocolVersion": "2025-03-26",
+                "clientInfo": { "name": "test-client", "version": "0.3.1" }
+            }
+        }),
+    )
+    .await;
+    response.headers()[SESSION_HEADER]
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+// ============================================================================
+// Session Manager Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_session_roundtrip() {
+    let sessions = SessionManager::new(SharedStore::default(), Duration::from_secs(60));
+    let client = ClientInfo {
+        name: Some("client".to_string()),
+        ..ClientInfo::default()
+    };
+
+    let session = sessions
+        .create("alice", "2025-06-18", client.clone())
+        .await
+        .unwrap();
+    let loaded = sessions.get(&session.id, "alice").await.unwrap().unwrap();
+
+    assert_eq!(loaded.client, client);
+    assert_eq!(loaded.protocol_version, "2025-06-18");
+}
+
+#[tokio::test]
+async fn test_session_is_private_to_its_user() {
+    let sessions = SessionManager::new(SharedStore::default(), Duration::from_secs(60));
+    let session = sessions
+        .create("alice", "2025-06-18", ClientInfo::default())
+        .await
+        .unwrap();
+
+    assert!(sessions.get(&session.id, "bob").await.unwrap().is_none());
+    assert!(sessions.get("missing", "alice").await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_session_expires() {
+    let sessions = SessionManager::new(SharedStore::default(), Duration::from_millis(20));
+    let session = sessions
+        .create("alice", "2025-06-18", ClientInfo::default())
+        .await
+        .unwrap();
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(sessions.get(&session.id, "alice").await.unwrap().is_none());
+}
+
+#[test]
+fn test_client_label() {
+    let named = ClientInfo {
+        name: Some("cli".to_string()),
+        ..ClientInfo::default()
+    };
+    assert_eq!(named.label(), "cli");
+    assert_eq!(ClientInfo::default().label(), "unknown");
+}
+
+// ============================================================================
+// Initialize Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_initialize_returns_session_header() {
+    let server = TestServer::new(create_app(create_test_credentials_store()).unwrap()).unwrap();
+
+    let response = server
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .json(&json!({"jsonrpc": "2.0", "id": 1, "method": "initialize"}))
+        .await;
+
+    response.assert_status_ok();
+    let body: Value = response.json();
+    let session_id = response.header(SESSION_HEADER);
+    assert_eq!(body["id"], 1);
+    assert_eq!(body["result"]["sessionId"], session_id.to_str().unwrap());
+    assert_eq!(body["result"]["protocolVersion"], PROTOCOL_VERSION);
+    assert_eq!(body["result"]["serverInfo"]["name"], "mcp-server");
+}
+
+#[tokio::test]
+async fn test_instructions_returned_when_configured() {
+    let state = test_state();
+    let response = post(&state, HeaderMap::new(), json!({"method": "initialize"})).await;
+    let body = body_json(response).await;
+    assert!(body["result"].get("instructions").is_none());
+
+    let state = AppState {
+        config: Arc::new(ServerConfig {
+            instructions: Some("Call client_tool first".to_string()),
+            ..Default::default()
+        }),
+        ..test_state()
+    };
+    for method in ["initialize", "discover"] {
+        let response = post(&state, HeaderMap::new(), json!({"method": method})).await;
+        let body = body_json(response).await;
+        assert_eq!(body["result"]["instructions"], "Call client_tool first");
+    }
+}
+
+#[tokio::test]
+async fn test_unknown_session_is_rejected() {
+    let state = test_state();
+    let mut headers = HeaderMap::new();
+    headers.insert(SESSION_HEADER, HeaderValue::from_static("unknown"));
+
+    let response = post(&state, headers, json!({"id": 3, "method": "discover"})).await;
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let body = body_json(response).await;
+    assert_eq!(body["error"]["code"], ERROR_INVALID_REQUEST);
+    assert_eq!(body["id"], 3);
+}
+
+// ============================================================================
+// Client Info Propagation Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_session_client_info_reaches_tools() {
+    let state = test_state();
+    let session_id = initialize(&state).await;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(SESSION_HEADER, HeaderValue::from_str(&session_id).unwrap());
+    let response = post(
+        &state,
+        headers,
+        json!({"method": "invoke", "params": {"tool_name": "client_tool"}}),
+    )
+    .await;
+
+    let body = body_json(response).await;
+    assert_eq!(
+        body["result"]["client"],
+        json!({
+            "name": "test-client",
+            "version": "0.3.1",
+            "user_agent": "test-agent/1.0"
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_user_agent_without_session() {
+    let state = test_state();
+
+    let response = post(
+        &state,
+        user_agent("curl/8.0"),
+        json!({"method": "invoke", "params": {"tool_name": "client_tool"}}),
+    )
+    .await;
+
+    let body = body_json(response).await;
+    assert_eq!(
+        body["result"]["client"],
+        json!({ "user_agent": "curl/8.0" })
+    );
+}
+
+#[tokio::test]
+async fn test_client_name_is_a_metrics_label() {
+    let metrics = Arc::new(Metrics::new());
+    let state = AppState {
+        metrics: metrics.clone(),
+        ..test_state()
+    };
+    let session_id = initialize(&state).await;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(SESSION_HEADER, HeaderValue::from_str(&session_id).unwrap());
+    post(
+        &state,
+        headers,
+        json!({"method": "invoke", "params": {"tool_name": "client_tool"}}),
+    )
+    .await;
+
+    assert_eq!(
+        metrics.counter(
+            "mcp_tool_invocations_total",
+            &[
+                ("tool", "client_tool"),
+                ("variant", "stable"),
+                ("status", "success"),
+                ("client", "test-client"),
+            ]
+        ),
+        1
+    );
+}
+
+// ============================================================================
+// Ordered Invocation Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_ordered_session_turns_are_fifo() {
+    let sessions = SessionManager::new(SharedStore::default(), Duration::from_secs(60));
+    let unordered = sessions
+        .create("alice", "2025-06-18", ClientInfo::default())
+        .await
+        .unwrap();
+    assert!(sessions.acquire_turn(&unordered).await.is_none());
+
+    let session = sessions
+        .create_with_ordering("alice", "2025-06-18", ClientInfo::default(), true)
+        .await
+        .unwrap();
+    let first = sessions.acquire_turn(&session).await.unwrap();
+
+    let order = Arc::new(Mutex::new(Vec::new()));
+    let mut waiters = Vec::new();
+    for i in 0..3 {
+        let (sessions, session, order) = (sessions.clone(), session.clone(), order.clone());
+        waiters.push(tokio::spawn(async move {
+            let _turn = sessions.acquire_turn(&session).await;
+            order.lock().unwrap().push(i);
+        }));
+        // Let the task start waiting before the next one is queued
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert!(order.lock().unwrap().is_empty());
+
+    drop(first);
+    for waiter in waiters {
+        waiter.await.unwrap();
+    }
+    assert_eq!(*order.lock().unwrap(), vec![0, 1, 2]);
+}
+
+/// Invokes a slow tool three times at once; returns the peak concurrency
+async fn peak_concurrency(ordered: bool) -> usize {
+    let active = Arc::new(AtomicUsize::new(0));
+    let peak = Arc::new(AtomicUsize::new(0));
+    let (tool_active, tool_peak) = (active.clone(), peak.clone());
+    let slow_tool: ToolFunction = Box::new(move |_args, _ctx| {
+        let (active, peak) = (tool_active.clone(), tool_peak.clone());
+        Box::pin(async move {
+            let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(30)).await;
+            active.fetch_sub(1, Ordering::SeqCst);
+            Ok(json!({}))
+        })
+    });
+    let state = AppState {
+        tool_registry: Arc::new(ArcSwap::from_pointee(HashMap::from([(
+            "slow_tool".to_string(),
+            slow_tool,
+        )]))),
+        ..Default::default()
+    };
+
+    let response = post(
+        &state,
+        HeaderMap::new(),
+        json!({"method": "initialize", "params": {"orderedInvocations": ordered}}),
+    )
+    .await;
+    let session_id = response.headers()[SESSION_HEADER].clone();
+
+    let invocations = (0..3).map(|_| {
+        let mut headers = HeaderMap::new();
+        headers.insert(SESSION_HEADER, session_id.clone());
+        let state = state.clone();
+        tokio::spawn(async move {
+            post(
+                &state,
+                headers,
+                json!({"method": "invoke", "params": {"tool_name": "slow_tool"}}),
+            )
+            .await
+        })
+    });
+    for invocation in invocations.collect::<Vec<_>>() {
+        let body = body_json(invocation.await.unwrap()).await;
+        assert!(body["result"].is_object());
+    }
+    peak.load(Ordering::SeqCst)
+}
+
+#[tokio::test]
+async fn test_ordered_session_invocations_do_not_overlap() {
+    assert_eq!(peak_concurrency(true).await, 1);
+    assert!(peak_concurrency(false).await > 1);
+}
@@ -0,0 +1,133 @@
+mod common;
+
+use axum::http::StatusCode;
+use axum_test::{TestResponse, TestServer};
+use common::*;
+use mcp_server::auth::UserCredentials;
+use mcp_server::config::{ServerConfig, SigningConfig};
+use mcp_server::signing::{SIGNATURE_HEADER, sign_body, verify_body};
+use mcp_server::{AppOptions, create_app_with_options};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const SIGNING_KEY: &str = "signing-user-key";
+const SECRET: &str = "body-secret";
+const BODY: &str = r#"{"method":"discover"}"#;
+
+fn server(required: bool) -> TestServer {
+    let signer = UserCredentials::new(
+        "signer".to_string(),
+        SIGNING_KEY.to_string(),
+        HashMap::new(),
+    )
+    .with_signing_secret(Some(SECRET.to_string()));
+    let plain = create_test_user();
+    let credentials = Arc::new(HashMap::from([
+        (SIGNING_KEY.to_string(), signer),
+        (plain.api_key.clone(), plain),
+    ]));
+
+    let config = ServerConfig {
+        signing: SigningConfig {
+            required,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    TestServer::new(create_app_with_options(credentials, AppOptions::new(config)).unwrap()).unwrap()
+}
+
+async fn post(
+    server: &TestServer,
+    api_key: &str,
+    body: &str,
+    signature: Option<String>,
+) -> TestResponse {
+    let mut request = server
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", api_key))
+        .text(body.to_string())
+        .content_type("application/json");
+    if let Some(signature) = signature {
+        request = request.add_header(SIGNATURE_HEADER, signature);
+    }
+    request.await
+}
+
+// ============================================================================
+// Signature Helper Tests
+// ============================================================================
+
+#[test]
+fn test_sign_and_verify_body() {
+    let signature = sign_body(SECRET, BODY.as_bytes());
+    assert!(signature.starts_with("sha256="));
+    assert!(verify_body(SECRET, BODY.as_bytes(), &signature));
+    // The prefix is optional
+    assert!(verify_body(
+        SECRET,
+        BODY.as_bytes(),
+        signature.trim_start_matches("sha256=")
+    ));
+    assert!(!verify_body("other", BODY.as_bytes(), &signature));
+    assert!(!verify_body(SECRET, b"{}", &signature));
+    assert!(!verify_body(SECRET, BODY.as_bytes(), "not-hex"));
+}
+
+// ============================================================================
+// Middleware Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_signed_body_is_accepted() {
+    let server = server(false);
+    let response = post(
+        &server,
+        SIGNING_KEY,
+        BODY,
+        Some(sign_body(SECRET, BODY.as_bytes())),
+    )
+    .await;
+    response.assert_status_ok();
+}
+
+#[tokio::test]
+async fn test_tampered_body_is_rejected() {
+    let server = server(false);
+    let signature = sign_body(SECRET, BODY.as_bytes());
+
+    let response = post(
+        &server,
+        SIGNING_KEY,
+        r#"{"method":"history/list"}"#,
+        Some(signature),
+    )
+    .await;
+
+    response.assert_status(StatusCode::UNAUTHORIZED);
+    response.assert_text_contains("Invalid request signature");
+}
+
+#[tokio::test]
+async fn test_user_with_secret_must_sign() {
+    let server = server(false);
+    let response = post(&server, SIGNING_KEY, BODY, None).await;
+    response.assert_status(StatusCode::UNAUTHORIZED);
+    response.assert_text_contains("Missing X-Signature header");
+}
+
+#[tokio::test]
+async fn test_user_without_secret_is_not_checked() {
+    let server = server(false);
+    post(&server, TEST_API_KEY, BODY, None)
+        .await
+        .assert_status_ok();
+}
+
+#[tokio::test]
+async fn test_required_mode_rejects_users_without_secret() {
+    let server = server(true);
+    post(&server, TEST_API_KEY, BODY, None)
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,213 @@
+mod common;
+
+use common::*;
+use mcp_server::config::{ListenerConfig, ServerConfig, TlsConfig};
+use mcp_server::startup::{StartupReport, ToolSummary, Transport, schema_warnings};
+use mcp_server::{AppOptions, AppState, ToolDefinition};
+use serde_json::json;
+use std::sync::Arc;
+
+fn tool(name: &str, parameters: serde_json::Value) -> ToolDefinition {
+    ToolDefinition {
+        name: name.to_string(),
+        description: "test tool".to_string(),
+        parameters,
+        ..Default::default()
+    }
+}
+
+// ============================================================================
+// Schema Validation Tests
+// ============================================================================
+
+#[test]
+fn test_valid_schema_has_no_warnings() {
+    let definition = tool(
+        "ok",
+        json!({
+            "type": "object",
+            "properties": { "query": { "type": "string" } },
+            "required": ["query"]
+        }),
+    );
+    assert!(schema_warnings(&definition).is_empty());
+}
+
+#[test]
+fn test_invalid_schemas_reported() {
+    let not_object = tool("a", json!({ "type": "string" }));
+    assert_eq!(
+        schema_warnings(&not_object),
+        vec!["tool 'a': parameters schema must have \"type\": \"object\""]
+    );
+
+    let undeclared = tool(
+        "b",
+        json!({
+            "type": "object",
+            "properties": { "query": { "type": "string" } },
+            "required": ["query", "limit"]
+        }),
+    );
+    assert_eq!(
+        schema_warnings(&undeclared),
+        vec!["tool 'b': required parameter 'limit' is not declared"]
+    );
+
+    let mut bad_output = tool("c", json!({ "type": "object" }));
+    bad_output.output_schema = Some(json!("object"));
+    bad_output.parameters["required"] = json!("query");
+    assert_eq!(
+        schema_warnings(&bad_output),
+        vec![
+            "tool 'c': parameters \"required\" must be an array",
+            "tool 'c': output schema must be an object"
+        ]
+    );
+}
+
+// ============================================================================
+// StartupReport Tests
+// ============================================================================
+
+#[test]
+fn test_report_summarizes_state() {
+    let state = AppState::new(create_test_credentials_store(), AppOptions::default()).unwrap();
+    let report = StartupReport::new(&state);
+
+    assert_eq!(
+        report.transports,
+        vec![Transport {
+            name: "http",
+            bind: "0.0.0.0:3000".to_string()
+        }]
+    );
+    assert_eq!(report.users, Some(1));
+    assert_eq!(report.tenants, 0);
+    let calculate = report
+        .tools
+        .iter()
+        .find(|tool| tool.name == "calculate")
+        .unwrap();
+    assert_eq!(calculate.hints, vec!["readOnlyHint"]);
+
+    let names: Vec<&str> = report.tools.iter().map(|tool| tool.name.as_str()).collect();
+    let mut sorted = names.clone();
+    sorted.sort();
+    assert_eq!(names, sorted);
+}
+
+#[test]
+fn test_report_warns_about_schemas_and_missing_keys() {
+    let state = AppState::new(create_test_credentials_store(), AppOptions::default()).unwrap();
+    let mut keyed = tool("keyed", json!({ "type": "object" }));
+    keyed.required_external_keys = vec!["api_token".to_string()];
+    keyed.version = Some("2.1.0".to_string());
+    state.tool_definitions.store(Arc::new(vec![
+        keyed,
+        tool("broken", json!({ "type": "array" })),
+    ]));
+
+    let report = StartupReport::new(&state);
+    assert_eq!(
+        report.tools[1],
+        ToolSummary {
+            name: "keyed".to_string(),
+            version: Some("2.1.0".to_string()),
+            hints: Vec::new(),
+        }
+    );
+    assert_eq!(
+        report.warnings,
+        vec![
+            "tool 'broken': parameters schema must have \"type\": \"object\"".to_string(),
+            format!(
+                "user '{}' is missing external key 'api_token' required by tool 'keyed'",
+                TEST_USERNAME
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_report_without_credentials_file() {
+    let state = AppState::default();
+    let report = StartupReport::new(&state);
+    assert_eq!(report.users, None);
+    assert!(report.tools.is_empty());
+    assert!(report.warnings.is_empty());
+}
+
+#[test]
+fn test_every_listener_reported() {
+    let config = ServerConfig {
+        listeners: vec![
+            ListenerConfig {
+                bind: Some("127.0.0.1:3000".to_string()),
+                ..Default::default()
+            },
+            ListenerConfig {
+                bind: Some("0.0.0.0:3443".to_string()),
+                tls: Some(TlsConfig {
+                    cert_path: "cert.pem".to_string(),
+                    key_path: "key.pem".to_string(),
+                }),
+                ..Default::default()
+            },
+            ListenerConfig {
+                socket_path: Some("/run/mcp.sock".to_string()),
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    };
+    let state = AppState {
+        config: Arc::new(config),
+        ..Default::default()
+    };
+
+    let transports: Vec<(&str, String)> = StartupReport::new(&state)
+        .transports
+        .into_iter()
+        .map(|transport| (transport.name, transport.bind))
+        .collect();
+    assert_eq!(
+        transports,
+        vec![
+            ("http", "127.0.0.1:3000".to_string()),
+            ("https", "0.0.0.0:3443".to_string()),
+            ("unix", "unix:/run/mcp.sock".to_string()),
+        ]
+    );
+}
+
+#[cfg(not(feature = "grpc"))]
+#[test]
+fn test_grpc_enabled_without_feature_warns() {
+    let mut config = ServerConfig::default();
+    config.grpc.enabled = true;
+    let state = AppState {
+        config: Arc::new(config),
+        ..Default::default()
+    };
+    let report = StartupReport::new(&state);
+    assert_eq!(report.transports.len(), 1);
+    assert_eq!(
+        report.warnings,
+        vec!["[grpc] is enabled but the server was built without the grpc feature"]
+    );
+}
+
+#[cfg(feature = "grpc")]
+#[test]
+fn test_grpc_transport_reported() {
+    let mut config = ServerConfig::default();
+    config.grpc.enabled = true;
+    let state = AppState {
+        config: Arc::new(config),
+        ..Default::default()
+    };
+    let report = StartupReport::new(&state);
+    assert_eq!(report.transports[1].name, "grpc");
+    assert_eq!(report.transports[1].bind, "0.0.0.0:50051");
+}
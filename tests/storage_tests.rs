@@ -0,0 +1,213 @@
+mod common;
+
+use axum::Router;
+use axum::body::Bytes;
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::response::IntoResponse;
+use axum::routing::get;
+use common::*;
+use mcp_server::auth::{AuthenticatedUser, UserCredentials};
+use mcp_server::config::ServerConfig;
+use mcp_server::tools::storage::{StorageGetTool, StorageListTool, StoragePutTool, UserBucket};
+use mcp_server::tools::{InvalidInput, McpTool, ToolContext};
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+type Objects = Arc<Mutex<BTreeMap<String, Vec<u8>>>>;
+
+const LAST_MODIFIED: &str = "Wed, 01 Jan 2025 00:00:00 GMT";
+
+/// Path-style S3 endpoint keeping objects of the bucket `data` in memory
+async fn mock_s3() -> (String, Objects) {
+    async fn put(
+        State(objects): State<Objects>,
+        Path(key): Path<String>,
+        body: Bytes,
+    ) -> impl IntoResponse {
+        objects.lock().unwrap().insert(key, body.to_vec());
+        [(header::ETAG, "\"etag\"")]
+    }
+
+    async fn fetch(State(objects): State<Objects>, Path(key): Path<String>) -> impl IntoResponse {
+        let Some(body) = objects.lock().unwrap().get(&key).cloned() else {
+            return StatusCode::NOT_FOUND.into_response();
+        };
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ETAG, "\"etag\"".parse().unwrap());
+        headers.insert(header::LAST_MODIFIED, LAST_MODIFIED.parse().unwrap());
+        headers.insert(header::CONTENT_LENGTH, body.len().into());
+        (headers, body).into_response()
+    }
+
+    async fn list(
+        State(objects): State<Objects>,
+        Query(params): Query<HashMap<String, String>>,
+    ) -> impl IntoResponse {
+        let prefix = params.get("prefix").cloned().unwrap_or_default();
+        let contents: String = objects
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(key, _)| key.starts_with(&prefix))
+            .map(|(key, body)| {
+                format!(
+                    "<Contents><Key>{}</Key><Size>{}</Size><LastModified>2025-01-01T00:00:00.000Z</LastModified></Contents>",
+                    key,
+                    body.len()
+                )
+            })
+            .collect();
+        format!("<ListBucketResult>{}</ListBucketResult>", contents)
+    }
+
+    let objects = Objects::default();
+    let router = Router::new()
+        .route("/data", get(list))
+        .route("/data/{*key}", get(fetch).put(put))
+        .with_state(objects.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    (format!("http://{}", addr), objects)
+}
+
+fn user(endpoint: &str, prefix: Option<&str>) -> AuthenticatedUser {
+    let mut external_keys = HashMap::from([
+        ("s3_endpoint".to_string(), endpoint.to_string()),
+        ("s3_bucket".to_string(), "data".to_string()),
+        ("s3_access_key_id".to_string(), "AKIDEXAMPLE".to_string()),
+        ("s3_secret_access_key".to_string(), "secret".to_string()),
+    ]);
+    if let Some(prefix) = prefix {
+        external_keys.insert("s3_prefix".to_string(), prefix.to_string());
+    }
+    AuthenticatedUser(UserCredentials::new(
+        "alice".to_string(),
+        TEST_API_KEY.to_string(),
+        external_keys,
+    ))
+}
+
+#[test]
+fn test_keys_are_confined_to_the_prefix() {
+    let bucket = UserBucket::for_user(&user("http://127.0.0.1:1", Some("/users/alice/"))).unwrap();
+
+    assert_eq!(
+        bucket.path("notes/a.txt").unwrap().as_ref(),
+        "users/alice/notes/a.txt"
+    );
+    assert_eq!(bucket.path("/a.txt").unwrap().as_ref(), "users/alice/a.txt");
+    for key in ["../bob/a.txt", "notes/../../bob", "a//b", "./a", ""] {
+        assert!(bucket.path(key).is_err(), "{}", key);
+    }
+    let path = bucket.path("notes/a.txt").unwrap();
+    assert_eq!(bucket.relative_key(&path), "notes/a.txt");
+}
+
+#[test]
+fn test_missing_credentials_are_reported() {
+    let user = AuthenticatedUser(create_test_user());
+    let error = UserBucket::for_user(&user).err().unwrap();
+    assert_eq!(error.to_string(), "User missing credential 's3_bucket'");
+}
+
+#[tokio::test]
+async fn test_put_get_and_list_within_the_prefix() {
+    let (endpoint, objects) = mock_s3().await;
+    let alice = user(&endpoint, Some("users/alice"));
+
+    let put = StoragePutTool
+        .execute(
+            Some(json!({"key": "notes/todo.txt", "content": "buy milk"})),
+            alice.clone(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(put["size"], 8);
+    let binary = StoragePutTool
+        .execute(
+            Some(json!({"key": "blob.bin", "content": "/wA=", "encoding": "base64"})),
+            alice.clone(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(binary["size"], 2);
+    assert!(
+        objects
+            .lock()
+            .unwrap()
+            .contains_key("users/alice/notes/todo.txt")
+    );
+    objects
+        .lock()
+        .unwrap()
+        .insert("users/bob/secret.txt".to_string(), b"bob".to_vec());
+
+    let text = StorageGetTool
+        .execute(Some(json!({"key": "notes/todo.txt"})), alice.clone())
+        .await
+        .unwrap();
+    assert_eq!(text["encoding"], "utf8");
+    assert_eq!(text["content"], "buy milk");
+    let blob = StorageGetTool
+        .execute(Some(json!({"key": "blob.bin"})), alice.clone())
+        .await
+        .unwrap();
+    assert_eq!(blob["encoding"], "base64");
+    assert_eq!(blob["content"], "/wA=");
+
+    let listing = StorageListTool.execute(None, alice.clone()).await.unwrap();
+    let keys: Vec<&str> = listing["objects"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|object| object["key"].as_str().unwrap())
+        .collect();
+    assert_eq!(keys, ["blob.bin", "notes/todo.txt"]);
+    assert_eq!(listing["truncated"], false);
+
+    let limited = StorageListTool
+        .execute(Some(json!({"limit": 1})), alice.clone())
+        .await
+        .unwrap();
+    assert_eq!(limited["objects"].as_array().unwrap().len(), 1);
+    assert_eq!(limited["truncated"], true);
+
+    let error = StorageGetTool
+        .execute(Some(json!({"key": "missing.txt"})), alice)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.downcast_ref::<InvalidInput>().unwrap().message,
+        "object 'missing.txt' not found"
+    );
+}
+
+#[tokio::test]
+async fn test_large_objects_are_returned_as_presigned_urls() {
+    let (endpoint, objects) = mock_s3().await;
+    objects
+        .lock()
+        .unwrap()
+        .insert("report.csv".to_string(), vec![b'x'; 64]);
+
+    let mut config = ServerConfig::default();
+    config.storage.inline_max_bytes = 32;
+    config.storage.presign_expiry_secs = 60;
+    let ctx = ToolContext::new(user(&endpoint, None)).with_config(Arc::new(config));
+
+    let result: Value = StorageGetTool
+        .execute_with_context(Some(json!({"key": "report.csv"})), ctx)
+        .await
+        .unwrap();
+
+    assert_eq!(result["size"], 64);
+    assert_eq!(result["expires_in_secs"], 60);
+    assert!(result.get("content").is_none());
+    let url = result["url"].as_str().unwrap();
+    assert!(url.starts_with(&format!("{}/data/report.csv?", endpoint)));
+    assert!(url.contains("X-Amz-Signature="));
+    assert!(url.contains("X-Amz-Expires=60"));
+}
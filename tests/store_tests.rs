@@ -0,0 +1,316 @@
+use mcp_server::config::{ServerConfig, StoreBackend, StoreConfig};
+use mcp_server::database::Storage;
+use mcp_server::store::{DistributedStore, MemoryStore, SharedStore};
+use std::time::Duration;
+
+// ============================================================================
+// MemoryStore Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_memory_store_set_get_delete() {
+    let store = MemoryStore::new();
+
+    assert_eq!(store.get("job:1").await.unwrap(), None);
+    store
+        .set("job:1", "running".to_string(), None)
+        .await
+        .unwrap();
+    assert_eq!(
+        store.get("job:1").await.unwrap().as_deref(),
+        Some("running")
+    );
+
+    assert!(store.delete("job:1").await.unwrap());
+    assert!(!store.delete("job:1").await.unwrap());
+    assert_eq!(store.get("job:1").await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_memory_store_entries_expire() {
+    let store = MemoryStore::new();
+
+    store
+        .set(
+            "session",
+            "abc".to_string(),
+            Some(Duration::from_millis(10)),
+        )
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(30)).await;
+
+    assert_eq!(store.get("session").await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_memory_store_set_if_absent() {
+    let store = MemoryStore::new();
+
+    assert!(
+        store
+            .set_if_absent("idem", "first".to_string(), None)
+            .await
+            .unwrap()
+    );
+    assert!(
+        !store
+            .set_if_absent("idem", "second".to_string(), None)
+            .await
+            .unwrap()
+    );
+    assert_eq!(store.get("idem").await.unwrap().as_deref(), Some("first"));
+}
+
+#[tokio::test]
+async fn test_memory_store_set_if_absent_after_expiry() {
+    let store = MemoryStore::new();
+
+    store
+        .set_if_absent("idem", "first".to_string(), Some(Duration::from_millis(10)))
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(30)).await;
+
+    assert!(
+        store
+            .set_if_absent("idem", "second".to_string(), None)
+            .await
+            .unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_memory_store_increment_window() {
+    let store = MemoryStore::new();
+    let window = Duration::from_millis(20);
+
+    assert_eq!(store.increment("rate:alice", window).await.unwrap(), 1);
+    assert_eq!(store.increment("rate:alice", window).await.unwrap(), 2);
+    tokio::time::sleep(Duration::from_millis(40)).await;
+
+    // A new window starts once the previous one expires
+    assert_eq!(store.increment("rate:alice", window).await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn test_memory_store_increment_by() {
+    let store = MemoryStore::new();
+    let window = Duration::from_secs(60);
+
+    assert_eq!(store.increment_by("cost", 5, window).await.unwrap(), 5);
+    assert_eq!(store.increment_by("cost", -2, window).await.unwrap(), 3);
+    assert_eq!(store.increment("cost", window).await.unwrap(), 4);
+}
+
+#[tokio::test]
+async fn test_memory_store_increment_non_integer_fails() {
+    let store = MemoryStore::new();
+    store.set("name", "alice".to_string(), None).await.unwrap();
+
+    assert!(
+        store
+            .increment("name", Duration::from_secs(1))
+            .await
+            .is_err()
+    );
+}
+
+// ============================================================================
+// Configuration Tests
+// ============================================================================
+
+#[test]
+fn test_store_config_defaults_to_memory() {
+    let config = ServerConfig::default();
+    assert_eq!(config.store.backend, StoreBackend::Memory);
+    assert_eq!(config.store.key_prefix, "mcp:");
+}
+
+#[test]
+fn test_store_config_parses_redis() {
+    let config: ServerConfig =
+        toml::from_str("[store]\nbackend = \"redis\"\nurl = \"redis://127.0.0.1:6379\"\n").unwrap();
+    assert_eq!(config.store.backend, StoreBackend::Redis);
+    assert_eq!(config.store.url.as_deref(), Some("redis://127.0.0.1:6379"));
+}
+
+#[tokio::test]
+async fn test_shared_store_from_memory_config() {
+    let store = SharedStore::from_config(&StoreConfig::default(), &Storage::default()).unwrap();
+    store.set("key", "value".to_string(), None).await.unwrap();
+    assert_eq!(store.get("key").await.unwrap().as_deref(), Some("value"));
+}
+
+#[cfg(not(feature = "redis"))]
+#[test]
+fn test_redis_backend_requires_feature() {
+    let config = StoreConfig {
+        backend: StoreBackend::Redis,
+        url: Some("redis://127.0.0.1:6379".to_string()),
+        ..Default::default()
+    };
+    let err = SharedStore::from_config(&config, &Storage::default())
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("`redis` feature"));
+}
+
+#[cfg(feature = "redis")]
+#[test]
+fn test_redis_backend_requires_url() {
+    let config = StoreConfig {
+        backend: StoreBackend::Redis,
+        ..Default::default()
+    };
+    let err = SharedStore::from_config(&config, &Storage::default())
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("store.url"));
+}
+
+#[tokio::test]
+async fn test_memory_store_purge_expired() {
+    let store = MemoryStore::new();
+    store
+        .set("short", "a".to_string(), Some(Duration::from_millis(10)))
+        .await
+        .unwrap();
+    store.set("long", "b".to_string(), None).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(30)).await;
+
+    assert_eq!(DistributedStore::purge_expired(&store).await.unwrap(), 1);
+    assert_eq!(store.get("long").await.unwrap().as_deref(), Some("b"));
+}
+
+#[cfg(not(feature = "sqlite"))]
+#[test]
+fn test_sqlite_backend_requires_feature() {
+    let config = StoreConfig {
+        backend: StoreBackend::Sqlite,
+        path: Some("state.db".to_string()),
+        ..Default::default()
+    };
+    let err = SharedStore::from_config(&config, &Storage::default())
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("`sqlite` feature"));
+}
+
+// ============================================================================
+// SqliteStore Tests
+// ============================================================================
+
+#[cfg(feature = "sqlite")]
+mod sqlite {
+    use super::*;
+    use mcp_server::store::SqliteStore;
+    use tempfile::TempDir;
+
+    fn open(dir: &TempDir) -> SqliteStore {
+        SqliteStore::open(dir.path().join("state.db").to_str().unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_store_operations() {
+        let dir = TempDir::new().unwrap();
+        let store = open(&dir);
+
+        store.set("key", "one".to_string(), None).await.unwrap();
+        store.set("key", "two".to_string(), None).await.unwrap();
+        assert_eq!(store.get("key").await.unwrap().as_deref(), Some("two"));
+
+        assert!(
+            !store
+                .set_if_absent("key", "three".to_string(), None)
+                .await
+                .unwrap()
+        );
+        assert!(
+            store
+                .set_if_absent("other", "x".to_string(), None)
+                .await
+                .unwrap()
+        );
+
+        assert_eq!(
+            store
+                .increment("hits", Duration::from_secs(60))
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            store
+                .increment("hits", Duration::from_secs(60))
+                .await
+                .unwrap(),
+            2
+        );
+        assert_eq!(
+            store
+                .increment_by("hits", 10, Duration::from_secs(60))
+                .await
+                .unwrap(),
+            12
+        );
+        assert!(
+            store
+                .increment("key", Duration::from_secs(60))
+                .await
+                .is_err()
+        );
+
+        assert!(store.delete("key").await.unwrap());
+        assert!(!store.delete("key").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_store_survives_reopen() {
+        let dir = TempDir::new().unwrap();
+        open(&dir)
+            .set("persisted", "yes".to_string(), None)
+            .await
+            .unwrap();
+
+        let reopened = open(&dir);
+        assert_eq!(
+            reopened.get("persisted").await.unwrap().as_deref(),
+            Some("yes")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_store_expiry_and_purge() {
+        let dir = TempDir::new().unwrap();
+        let store = open(&dir);
+        let ttl = Some(Duration::from_millis(10));
+
+        store.set("a", "1".to_string(), ttl).await.unwrap();
+        store.set("b", "2".to_string(), ttl).await.unwrap();
+        store.set("c", "3".to_string(), None).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(30)).await;
+
+        assert_eq!(store.get("a").await.unwrap(), None);
+        assert!(
+            store
+                .set_if_absent("a", "new".to_string(), None)
+                .await
+                .unwrap()
+        );
+        assert_eq!(store.purge_expired().await.unwrap(), 1);
+        assert_eq!(store.get("c").await.unwrap().as_deref(), Some("3"));
+    }
+
+    #[test]
+    fn test_sqlite_backend_requires_path() {
+        let config = StoreConfig {
+            backend: StoreBackend::Sqlite,
+            ..Default::default()
+        };
+        let err = SharedStore::from_config(&config, &Storage::default())
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("store.path"));
+    }
+}
@@ -0,0 +1,165 @@
+mod common;
+
+use axum_test::TestServer;
+use common::*;
+use mcp_server::auth::UserCredentials;
+use mcp_server::config::{DuplicateToolNames, ServerConfig, TenantConfig};
+use mcp_server::tenant::{ToolSet, apply_tenant_config};
+use mcp_server::{AppOptions, ERROR_METHOD_NOT_FOUND, create_app_with_options};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const TENANT_API_KEY: &str = "tenant-api-key-000";
+
+fn tenant_config(tools: Option<Vec<&str>>) -> ServerConfig {
+    let tenant = TenantConfig {
+        tools: tools.map(|t| t.into_iter().map(String::from).collect()),
+        external_keys: HashMap::from([
+            ("schema_registry".to_string(), "acme-registry".to_string()),
+            ("region".to_string(), "eu".to_string()),
+        ]),
+    };
+    ServerConfig {
+        tenants: HashMap::from([("acme".to_string(), tenant)]),
+        ..Default::default()
+    }
+}
+
+fn credentials_with_tenant_user() -> mcp_server::auth::CredentialsStore {
+    let mut store = (*create_test_credentials_store()).clone();
+    store.insert(
+        TENANT_API_KEY.to_string(),
+        UserCredentials::new(
+            "acme_user".to_string(),
+            TENANT_API_KEY.to_string(),
+            HashMap::from([("region".to_string(), "us".to_string())]),
+        )
+        .with_tenant(Some("acme".to_string())),
+    );
+    Arc::new(store)
+}
+
+async fn discover(server: &TestServer, api_key: &str) -> Vec<String> {
+    let body: Value = server
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", api_key))
+        .json(&json!({"method": "discover"}))
+        .await
+        .json();
+    body["result"]["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["name"].as_str().unwrap().to_string())
+        .collect()
+}
+
+// ============================================================================
+// Tool Set Tests
+// ============================================================================
+
+#[test]
+fn test_tool_set_without_allowlist_has_all_tools() {
+    let tools = ToolSet::from_registry(None, DuplicateToolNames::Error).unwrap();
+    assert!(tools.functions.contains_key("get_current_time"));
+    assert_eq!(tools.functions.len(), tools.definitions.len());
+}
+
+#[test]
+fn test_tool_set_allowlist_filters_tools() {
+    let tools = ToolSet::from_registry(Some(&[]), DuplicateToolNames::Error).unwrap();
+    assert!(tools.functions.is_empty());
+    assert!(tools.definitions.is_empty());
+}
+
+#[test]
+fn test_tool_set_unknown_tool_fails() {
+    let err = ToolSet::from_registry(
+        Some(&["no_such_tool".to_string()]),
+        DuplicateToolNames::Error,
+    )
+    .err()
+    .unwrap();
+    assert!(err.to_string().contains("Unknown tool 'no_such_tool'"));
+}
+
+// ============================================================================
+// Tenant Configuration Tests
+// ============================================================================
+
+#[test]
+fn test_tenant_external_keys_are_merged() {
+    let config = tenant_config(None);
+    let store = apply_tenant_config(credentials_with_tenant_user(), &config.tenants).unwrap();
+
+    let user = store.get(TENANT_API_KEY).unwrap();
+    assert_eq!(
+        user.get_external_key("schema_registry"),
+        Some(&"acme-registry".to_string())
+    );
+    // User-level keys take precedence over tenant keys
+    assert_eq!(user.get_external_key("region"), Some(&"us".to_string()));
+
+    // Users without a tenant are untouched
+    let other = store.get(TEST_API_KEY).unwrap();
+    assert!(other.get_external_key("schema_registry").is_none());
+}
+
+#[test]
+fn test_unknown_tenant_fails() {
+    let result = apply_tenant_config(credentials_with_tenant_user(), &HashMap::new());
+    let err_msg = result.err().unwrap().to_string();
+    assert!(err_msg.contains("unknown tenant 'acme'"));
+}
+
+#[test]
+fn test_create_app_with_unknown_tenant_tool_fails() {
+    let result = create_app_with_options(
+        create_test_credentials_store(),
+        AppOptions::new(tenant_config(Some(vec!["no_such_tool"]))),
+    );
+    let err_msg = result.err().unwrap().to_string();
+    assert!(err_msg.contains("tenant 'acme'"));
+}
+
+// ============================================================================
+// Integration Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_tenant_users_see_their_tool_set() {
+    let app = create_app_with_options(
+        credentials_with_tenant_user(),
+        AppOptions::new(tenant_config(Some(vec![]))),
+    )
+    .unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    assert!(discover(&server, TENANT_API_KEY).await.is_empty());
+    assert!(
+        discover(&server, TEST_API_KEY)
+            .await
+            .contains(&"get_current_time".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_tenant_users_cannot_invoke_hidden_tools() {
+    let app = create_app_with_options(
+        credentials_with_tenant_user(),
+        AppOptions::new(tenant_config(Some(vec![]))),
+    )
+    .unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    let body: Value = server
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", TENANT_API_KEY))
+        .json(&json!({"method": "invoke", "params": {"tool_name": "get_current_time"}}))
+        .await
+        .json();
+
+    assert_eq!(body["error"]["code"], ERROR_METHOD_NOT_FOUND);
+    assert_eq!(body["error"]["data"]["available_tools"], json!([]));
+}
@@ -0,0 +1,144 @@
+mod common;
+
+use arc_swap::ArcSwap;
+use axum::extract::State;
+use axum::{Extension, Json};
+use common::*;
+use mcp_server::audit::AuditStatus;
+use mcp_server::auth::AuthenticatedUser;
+use mcp_server::config::{ServerConfig, TimeoutsConfig};
+use mcp_server::tools::ToolFunction;
+use mcp_server::{
+    AppState, ERROR_INVALID_PARAMS, ERROR_TIMEOUT, McpRequest, McpResponse, ToolDefinition,
+    handle_mcp_request,
+};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Tool that sleeps for 300 ms
+fn slow_tool() -> ToolFunction {
+    Box::new(|_args, _ctx| {
+        Box::pin(async {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            Ok(json!({ "done": true }))
+        })
+    })
+}
+
+/// State serving `slow`, declaring `tool_timeout_ms`, with a server limit
+/// of `max_ms`
+fn state(tool_timeout_ms: Option<u64>, max_ms: u64) -> AppState {
+    let definitions = vec![ToolDefinition {
+        name: "slow".to_string(),
+        timeout_ms: tool_timeout_ms,
+        ..Default::default()
+    }];
+    AppState {
+        tool_registry: Arc::new(ArcSwap::from_pointee(HashMap::from([(
+            "slow".to_string(),
+            slow_tool(),
+        )]))),
+        tool_definitions: Arc::new(ArcSwap::from_pointee(definitions)),
+        config: Arc::new(ServerConfig {
+            timeouts: TimeoutsConfig { max_ms },
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+async fn invoke(state: &AppState, params: Value) -> McpResponse {
+    let mut params = params;
+    params["tool_name"] = json!("slow");
+    let request: McpRequest =
+        serde_json::from_value(json!({ "method": "invoke", "params": params })).unwrap();
+    let Json(response) = handle_mcp_request(
+        State(state.clone()),
+        Extension(AuthenticatedUser(create_test_user())),
+        Json(request),
+    )
+    .await;
+    response
+}
+
+/// Invoke `slow` expecting a timeout, returning its error data
+async fn invoke_timing_out(state: &AppState, params: Value) -> Value {
+    let error = invoke(state, params).await.error.expect("should time out");
+    assert_eq!(error.code, ERROR_TIMEOUT);
+    error.data.unwrap()
+}
+
+// ============================================================================
+// Config Tests
+// ============================================================================
+
+#[test]
+fn test_timeouts_section_parsed() {
+    let config: ServerConfig = toml::from_str("[timeouts]\nmax_ms = 5000\n").unwrap();
+    assert_eq!(config.timeouts.max_ms, 5000);
+    assert_eq!(ServerConfig::default().timeouts.max_ms, 0);
+}
+
+// ============================================================================
+// Limit Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_no_limit_completes() {
+    let state = state(None, 0);
+    let response = invoke(&state, json!({})).await;
+    assert_eq!(response.result.unwrap(), json!({ "done": true }));
+}
+
+#[tokio::test]
+async fn test_client_timeout_fires() {
+    let state = state(Some(10_000), 0);
+    let data = invoke_timing_out(&state, json!({ "timeout_ms": 50 })).await;
+
+    assert_eq!(
+        data,
+        json!({ "tool": "slow", "timeout_ms": 50, "limit": "client" })
+    );
+    let history = state.history.list(TEST_USERNAME, None);
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].status, AuditStatus::TimedOut);
+}
+
+#[tokio::test]
+async fn test_tool_timeout_fires() {
+    let state = state(Some(50), 10_000);
+    let data = invoke_timing_out(&state, json!({ "timeout_ms": 5000 })).await;
+    assert_eq!(data["limit"], "tool");
+    assert_eq!(data["timeout_ms"], 50);
+}
+
+#[tokio::test]
+async fn test_server_limit_caps_client_timeout() {
+    let state = state(None, 50);
+    let data = invoke_timing_out(&state, json!({ "timeout_ms": 60_000 })).await;
+    assert_eq!(data["limit"], "server");
+    assert_eq!(data["timeout_ms"], 50);
+
+    // Asking for exactly the server's limit reports the server's limit
+    let data = invoke_timing_out(&state, json!({ "timeout_ms": 50 })).await;
+    assert_eq!(data["limit"], "server");
+}
+
+#[tokio::test]
+async fn test_client_timeout_above_run_time_completes() {
+    let state = state(None, 0);
+    let response = invoke(&state, json!({ "timeout_ms": 5000 })).await;
+    assert!(response.result.is_some());
+}
+
+#[tokio::test]
+async fn test_zero_client_timeout_rejected() {
+    let state = state(None, 0);
+    let error = invoke(&state, json!({ "timeout_ms": 0 }))
+        .await
+        .error
+        .unwrap();
+    assert_eq!(error.code, ERROR_INVALID_PARAMS);
+}
@@ -0,0 +1,186 @@
+mod common;
+
+use arc_swap::ArcSwap;
+use axum::extract::State;
+use axum::{Extension, Json};
+use common::*;
+use mcp_server::auth::templates::TemplateError;
+use mcp_server::auth::{AuthenticatedUser, ToolArguments, UserCredentials};
+use mcp_server::tools::{ToolContext, ToolFunction};
+use mcp_server::{AppState, McpRequest, McpResponse, handle_mcp_request};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+fn sql_query_arguments() -> ToolArguments {
+    serde_json::from_value(json!({
+        "defaults": {"limit": 100},
+        "forced": {"database": "analytics"}
+    }))
+    .unwrap()
+}
+
+async fn invoke_response(
+    user: UserCredentials,
+    tool_name: &str,
+    arguments: Option<Value>,
+) -> McpResponse {
+    // Echoes the arguments it received
+    let echo: ToolFunction =
+        Box::new(|args, _ctx: ToolContext| Box::pin(async move { Ok(json!(args)) }));
+    let state = AppState {
+        tool_registry: Arc::new(ArcSwap::from_pointee(HashMap::from([(
+            tool_name.to_string(),
+            echo,
+        )]))),
+        ..Default::default()
+    };
+    let mut params = json!({ "tool_name": tool_name });
+    if let Some(arguments) = arguments {
+        params["arguments"] = arguments;
+    }
+    let request: McpRequest =
+        serde_json::from_value(json!({ "method": "invoke", "params": params })).unwrap();
+    let Json(response) = handle_mcp_request(
+        State(state),
+        Extension(AuthenticatedUser(user)),
+        Json(request),
+    )
+    .await;
+    response
+}
+
+async fn invoke(user: UserCredentials, tool_name: &str, arguments: Option<Value>) -> Value {
+    invoke_response(user, tool_name, arguments)
+        .await
+        .result
+        .unwrap()
+}
+
+fn user() -> UserCredentials {
+    create_test_user().with_tool_arguments(HashMap::from([(
+        "sql_query".to_string(),
+        sql_query_arguments(),
+    )]))
+}
+
+#[tokio::test]
+async fn test_defaults_fill_missing_and_forced_values_win() {
+    let result = invoke(
+        user(),
+        "sql_query",
+        Some(json!({"query": "SELECT 1", "database": "billing"})),
+    )
+    .await;
+    assert_eq!(
+        result,
+        json!({"query": "SELECT 1", "database": "analytics", "limit": 100})
+    );
+
+    let result = invoke(user(), "sql_query", Some(json!({"limit": 5}))).await;
+    assert_eq!(result, json!({"limit": 5, "database": "analytics"}));
+}
+
+#[tokio::test]
+async fn test_missing_arguments_become_an_object() {
+    let result = invoke(user(), "sql_query", None).await;
+    assert_eq!(result, json!({"limit": 100, "database": "analytics"}));
+}
+
+#[tokio::test]
+async fn test_other_tools_and_users_are_unaffected() {
+    assert_eq!(
+        invoke(user(), "other_tool", Some(json!({"database": "billing"}))).await,
+        json!({"database": "billing"})
+    );
+    assert_eq!(
+        invoke(create_test_user(), "sql_query", None).await,
+        Value::Null
+    );
+}
+
+#[test]
+fn test_non_object_arguments_are_left_for_validation() {
+    let mut arguments = Some(json!(["SELECT 1"]));
+    sql_query_arguments().apply(&mut arguments);
+    assert_eq!(arguments, Some(json!(["SELECT 1"])));
+
+    let mut arguments = None;
+    ToolArguments::default().apply(&mut arguments);
+    assert_eq!(arguments, None);
+}
+
+// ============================================================================
+// Template Tests
+// ============================================================================
+
+fn templated_user() -> UserCredentials {
+    let arguments: ToolArguments = serde_json::from_value(json!({
+        "defaults": {"label": "{{ username }}-report"},
+        "forced": {"org_id": "{{external_keys.org_id}}", "tags": ["{{username}}"]}
+    }))
+    .unwrap();
+    UserCredentials::new(
+        "alice".to_string(),
+        TEST_API_KEY.to_string(),
+        HashMap::from([("org_id".to_string(), "org-42".to_string())]),
+    )
+    .with_tool_arguments(HashMap::from([("report".to_string(), arguments)]))
+}
+
+#[tokio::test]
+async fn test_placeholders_rendered_from_credentials() {
+    let result = invoke(templated_user(), "report", Some(json!({"org_id": "other"}))).await;
+    assert_eq!(
+        result,
+        json!({"org_id": "org-42", "tags": ["alice"], "label": "alice-report"})
+    );
+}
+
+#[tokio::test]
+async fn test_client_placeholders_are_not_rendered() {
+    let result = invoke(
+        templated_user(),
+        "report",
+        Some(json!({"label": "{{external_keys.org_id}}"})),
+    )
+    .await;
+    assert_eq!(result["label"], json!("{{external_keys.org_id}}"));
+}
+
+#[tokio::test]
+async fn test_missing_external_key_rejects_invocation() {
+    let user = templated_user();
+    let user = UserCredentials::new(user.username.clone(), user.api_key.clone(), HashMap::new())
+        .with_tool_arguments(user.tool_arguments.clone());
+    let response = invoke_response(user, "report", None).await;
+
+    let error = response.error.unwrap();
+    assert_eq!(error.code, -32006);
+    assert_eq!(
+        error.data,
+        Some(json!({"tool": "report", "missing_keys": ["org_id"]}))
+    );
+}
+
+#[test]
+fn test_check_templates() {
+    assert_eq!(
+        templated_user().tool_arguments["report"].check_templates(),
+        Ok(())
+    );
+
+    let unknown: ToolArguments =
+        serde_json::from_value(json!({"forced": {"a": "{{password}}"}})).unwrap();
+    assert_eq!(
+        unknown.check_templates(),
+        Err(TemplateError::UnknownVariable("password".to_string()))
+    );
+
+    let unterminated: ToolArguments =
+        serde_json::from_value(json!({"defaults": {"a": "{{username"}})).unwrap();
+    assert_eq!(
+        unterminated.check_templates(),
+        Err(TemplateError::Unterminated)
+    );
+}
@@ -1,5 +1,10 @@
-use mcp_server::tools::{initialize_all_tools, validate_tool_args};
-use serde_json::json;
+use anyhow::{Error, Result};
+use mcp_server::auth::AuthenticatedUser;
+use mcp_server::config::DuplicateToolNames;
+use mcp_server::tools::{
+    McpTool, PinBoxedFuture, ToolEntry, build_registry, initialize_all_tools, validate_tool_args,
+};
+use serde_json::{Value, json};
 
 // ============================================================================
 // Type Validation Tests
@@ -733,7 +738,7 @@ fn test_arguments_array_not_object() {
 
 #[test]
 fn test_initialize_all_tools_returns_hashmap_and_vec() {
-    let (func_registry, tool_definitions) = initialize_all_tools();
+    let (func_registry, tool_definitions) = initialize_all_tools().unwrap();
 
     assert!(!func_registry.is_empty());
     assert!(!tool_definitions.is_empty());
@@ -741,7 +746,7 @@ fn test_initialize_all_tools_returns_hashmap_and_vec() {
 
 #[test]
 fn test_initialize_all_tools_includes_get_time_tool() {
-    let (func_registry, tool_definitions) = initialize_all_tools();
+    let (func_registry, tool_definitions) = initialize_all_tools().unwrap();
 
     assert!(func_registry.contains_key("get_current_time"));
     assert!(tool_definitions.iter().any(|def| def.name == "get_current_time"));
@@ -749,7 +754,7 @@ fn test_initialize_all_tools_includes_get_time_tool() {
 
 #[test]
 fn test_initialize_all_tools_registry_and_definitions_match() {
-    let (func_registry, tool_definitions) = initialize_all_tools();
+    let (func_registry, tool_definitions) = initialize_all_tools().unwrap();
 
     assert_eq!(func_registry.len(), tool_definitions.len());
 
@@ -764,7 +769,7 @@ fn test_initialize_all_tools_registry_and_definitions_match() {
 
 #[test]
 fn test_initialize_all_tools_get_time_definition() {
-    let (_func_registry, tool_definitions) = initialize_all_tools();
+    let (_func_registry, tool_definitions) = initialize_all_tools().unwrap();
 
     let get_time_def = tool_definitions
         .iter()
@@ -774,3 +779,121 @@ fn test_initialize_all_tools_get_time_definition() {
     assert!(!get_time_def.description.is_empty());
     assert!(get_time_def.parameters.is_object());
 }
+
+// ============================================================================
+// Duplicate Tool Name Tests
+// ============================================================================
+
+/// Minimal tool whose name is chosen per test
+struct NamedTool(&'static str);
+
+impl McpTool for NamedTool {
+    fn name(&self) -> &'static str {
+        self.0
+    }
+
+    fn description(&self) -> &'static str {
+        "test tool"
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({"type": "object"})
+    }
+
+    fn execute(
+        &self,
+        _args: Option<Value>,
+        _user: AuthenticatedUser,
+    ) -> PinBoxedFuture<Result<Value, Error>> {
+        Box::pin(async { Ok(json!({})) })
+    }
+}
+
+fn search() -> Box<dyn McpTool + Send + Sync> {
+    Box::new(NamedTool("search"))
+}
+
+fn fetch() -> Box<dyn McpTool + Send + Sync> {
+    Box::new(NamedTool("fetch"))
+}
+
+fn entry(constructor: fn() -> Box<dyn McpTool + Send + Sync>, module: &'static str) -> ToolEntry {
+    ToolEntry {
+        constructor,
+        module,
+    }
+}
+
+#[test]
+fn test_duplicate_names_are_all_reported() {
+    let entries = [
+        entry(search, "app::tools::web"),
+        entry(search, "app::tools::docs"),
+        entry(fetch, "app::tools::web"),
+        entry(fetch, "app::tools::http"),
+        entry(fetch, "app::tools::s3"),
+    ];
+
+    let err = build_registry(&entries, DuplicateToolNames::Error)
+        .err()
+        .unwrap()
+        .to_string();
+
+    assert!(err.contains("'fetch' (3 tools)"), "{}", err);
+    assert!(err.contains("'search' (2 tools)"), "{}", err);
+}
+
+#[test]
+fn test_prefix_module_renames_every_colliding_tool() {
+    let entries = [
+        entry(search, "app::tools::web"),
+        entry(search, "app::tools::docs"),
+        entry(fetch, "app::tools::http"),
+    ];
+
+    let (registry, definitions) =
+        build_registry(&entries, DuplicateToolNames::PrefixModule).unwrap();
+
+    let mut names: Vec<&str> = definitions.iter().map(|def| def.name.as_str()).collect();
+    names.sort();
+    assert_eq!(names, vec!["docs_search", "fetch", "web_search"]);
+    assert!(registry.contains_key("web_search"));
+    assert!(registry.contains_key("docs_search"));
+}
+
+#[test]
+fn test_prefix_module_fails_when_names_still_collide() {
+    let entries = [
+        entry(search, "app::tools::web"),
+        entry(search, "other::web"),
+    ];
+
+    let err = build_registry(&entries, DuplicateToolNames::PrefixModule)
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("web_search"));
+}
+
+// ============================================================================
+// Enum Validation Tests
+// ============================================================================
+
+#[test]
+fn test_validate_enum() {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "format": {"type": "string", "enum": ["json", "csv"]},
+            "level": {"type": "integer", "enum": [1, 2, 3]}
+        }
+    });
+
+    assert!(validate_tool_args(&schema, &Some(json!({"format": "csv", "level": 2}))).is_ok());
+
+    let error = validate_tool_args(&schema, &Some(json!({"format": "xml"}))).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Parameter 'format' must be one of: \"json\", \"csv\""
+    );
+    assert!(validate_tool_args(&schema, &Some(json!({"level": 4}))).is_err());
+}
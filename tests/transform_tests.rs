@@ -0,0 +1,200 @@
+mod common;
+
+use anyhow::{Result, anyhow};
+use axum_test::TestServer;
+use common::*;
+use mcp_server::auth::AuthenticatedUser;
+use mcp_server::tools::transform::{
+    ResultTransformer, TransformerChain, TruncateStrings, truncate_to_size,
+};
+use mcp_server::{AppOptions, ERROR_TOOL_EXECUTION, create_app_with_options};
+use serde_json::{Value, json};
+use std::sync::Arc;
+
+/// Adds the tool name and username to object results
+struct AttachSource;
+
+impl ResultTransformer for AttachSource {
+    fn transform(
+        &self,
+        tool_name: &str,
+        user: &AuthenticatedUser,
+        mut result: Value,
+    ) -> Result<Value> {
+        if let Some(obj) = result.as_object_mut() {
+            obj.insert("source".to_string(), json!(tool_name));
+            obj.insert(
+                "requested_by".to_string(),
+                json!(user.credentials().username),
+            );
+        }
+        Ok(result)
+    }
+}
+
+/// Wraps the result in an envelope
+struct Wrap;
+
+impl ResultTransformer for Wrap {
+    fn transform(
+        &self,
+        _tool_name: &str,
+        _user: &AuthenticatedUser,
+        result: Value,
+    ) -> Result<Value> {
+        Ok(json!({ "wrapped": result }))
+    }
+}
+
+struct Failing;
+
+impl ResultTransformer for Failing {
+    fn transform(
+        &self,
+        _tool_name: &str,
+        _user: &AuthenticatedUser,
+        _result: Value,
+    ) -> Result<Value> {
+        Err(anyhow!("link service unavailable"))
+    }
+}
+
+fn test_user() -> AuthenticatedUser {
+    AuthenticatedUser(create_test_user())
+}
+
+// ============================================================================
+// Chain Tests
+// ============================================================================
+
+#[test]
+fn test_empty_chain_returns_result_unchanged() {
+    let chain = TransformerChain::default();
+    let result = chain.apply("tool", &test_user(), json!({"a": 1})).unwrap();
+    assert_eq!(result, json!({"a": 1}));
+}
+
+#[test]
+fn test_chain_applies_in_registration_order() {
+    let chain = TransformerChain::new(vec![Arc::new(AttachSource), Arc::new(Wrap)]);
+    let result = chain.apply("tool", &test_user(), json!({"a": 1})).unwrap();
+    assert_eq!(result["wrapped"]["source"], "tool");
+    assert_eq!(result["wrapped"]["requested_by"], TEST_USERNAME);
+}
+
+#[test]
+fn test_chain_stops_at_first_error() {
+    let chain = TransformerChain::new(vec![Arc::new(Failing), Arc::new(Wrap)]);
+    let err = chain.apply("tool", &test_user(), json!({})).unwrap_err();
+    assert!(err.to_string().contains("link service unavailable"));
+}
+
+#[test]
+fn test_truncate_strings_nested() {
+    let truncate = TruncateStrings::new(5);
+    let result = truncate
+        .transform(
+            "tool",
+            &test_user(),
+            json!({"short": "abc", "items": ["abcdefgh"], "nested": {"text": "ąćęłńóśźż"}}),
+        )
+        .unwrap();
+
+    assert_eq!(result["short"], "abc");
+    assert_eq!(
+        result["items"][0],
+        format!("abcde{}", TruncateStrings::MARKER)
+    );
+    // Truncation counts characters, not bytes
+    assert_eq!(
+        result["nested"]["text"],
+        format!("ąćęłń{}", TruncateStrings::MARKER)
+    );
+}
+
+#[test]
+fn test_truncate_strings_exact_length_untouched() {
+    let truncate = TruncateStrings::new(3);
+    let result = truncate
+        .transform("tool", &test_user(), json!("abc"))
+        .unwrap();
+    assert_eq!(result, json!("abc"));
+}
+
+// ============================================================================
+// truncate_to_size Tests
+// ============================================================================
+
+#[test]
+fn test_truncate_to_size_fits_limit() {
+    let value = json!({
+        "rows": (0..100).map(|i| json!({ "id": i, "name": "x".repeat(50) })).collect::<Vec<_>>(),
+        "summary": "y".repeat(500)
+    });
+
+    let truncated = truncate_to_size(&value, 400).unwrap();
+    assert!(serde_json::to_string(&truncated).unwrap().len() <= 400);
+    assert!(!truncated["rows"].as_array().unwrap().is_empty());
+    assert!(
+        truncated["summary"]
+            .as_str()
+            .unwrap()
+            .ends_with(TruncateStrings::MARKER)
+    );
+
+    assert_eq!(truncate_to_size(&value, 100_000), Some(value));
+}
+
+#[test]
+fn test_truncate_to_size_gives_up_on_too_many_keys() {
+    let value: Value = (0..50).map(|i| (format!("key{}", i), json!(i))).collect();
+    assert_eq!(truncate_to_size(&value, 20), None);
+}
+
+// ============================================================================
+// Integration Tests
+// ============================================================================
+
+async fn invoke_time(options: AppOptions) -> Value {
+    let app = create_app_with_options(create_test_credentials_store(), options).unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    server
+        .post("/mcp")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .json(&json!({"method": "invoke", "params": {"tool_name": "get_current_time"}}))
+        .await
+        .json()
+}
+
+#[tokio::test]
+async fn test_transformer_applied_to_tool_result() {
+    let body = invoke_time(AppOptions::default().with_result_transformer(AttachSource)).await;
+
+    assert!(body["result"]["current_time"].is_string());
+    assert_eq!(body["result"]["source"], "get_current_time");
+    assert_eq!(body["result"]["requested_by"], TEST_USERNAME);
+}
+
+#[tokio::test]
+async fn test_transformer_error_returns_tool_execution_error() {
+    let body = invoke_time(AppOptions::default().with_result_transformer(Failing)).await;
+
+    assert_eq!(body["error"]["code"], ERROR_TOOL_EXECUTION);
+    let message = body["error"]["message"].as_str().unwrap();
+    assert!(message.contains("Result transformation error"));
+    assert!(message.contains("link service unavailable"));
+}
+
+#[tokio::test]
+async fn test_truncate_transformer_end_to_end() {
+    let body =
+        invoke_time(AppOptions::default().with_result_transformer(TruncateStrings::new(4))).await;
+
+    let current_time = body["result"]["current_time"].as_str().unwrap();
+    assert!(current_time.ends_with(TruncateStrings::MARKER));
+    assert_eq!(
+        current_time.chars().count(),
+        4 + TruncateStrings::MARKER.chars().count()
+    );
+}
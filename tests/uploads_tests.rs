@@ -0,0 +1,206 @@
+mod common;
+
+use arc_swap::ArcSwap;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::{Extension, Json};
+use axum_test::TestServer;
+use axum_test::multipart::{MultipartForm, Part};
+use common::*;
+use mcp_server::auth::{AuthenticatedUser, UserCredentials};
+use mcp_server::config::{ServerConfig, UploadsConfig};
+use mcp_server::store::SharedStore;
+use mcp_server::tools::{ToolContext, ToolFunction};
+use mcp_server::uploads::{UPLOAD_HANDLE_PREFIX, UploadStore};
+use mcp_server::{AppOptions, AppState, McpRequest, create_app_with_options, handle_mcp_request};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn server(max_bytes: usize) -> TestServer {
+    let config = ServerConfig {
+        uploads: UploadsConfig {
+            max_bytes,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let app =
+        create_app_with_options(create_test_credentials_store(), AppOptions::new(config)).unwrap();
+    TestServer::new(app).unwrap()
+}
+
+fn file_form(data: &'static [u8]) -> MultipartForm {
+    MultipartForm::new().add_part(
+        "file",
+        Part::bytes(data)
+            .file_name("report.csv")
+            .mime_type("text/csv"),
+    )
+}
+
+// ============================================================================
+// POST /uploads Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_upload_returns_handle() {
+    let server = server(1024);
+
+    let response = server
+        .post("/uploads")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .multipart(file_form(b"a,b\n1,2\n"))
+        .await;
+
+    response.assert_status_ok();
+    let body: Value = response.json();
+    let result = &body["result"];
+    assert!(
+        result["handle"]
+            .as_str()
+            .unwrap()
+            .starts_with(UPLOAD_HANDLE_PREFIX)
+    );
+    assert_eq!(result["filename"], "report.csv");
+    assert_eq!(result["mime_type"], "text/csv");
+    assert_eq!(result["size"], 8);
+}
+
+#[tokio::test]
+async fn test_upload_requires_auth() {
+    let server = server(1024);
+    let response = server.post("/uploads").multipart(file_form(b"x")).await;
+    response.assert_status(StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_upload_size_limit() {
+    let server = server(4);
+
+    let response = server
+        .post("/uploads")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .multipart(file_form(b"too large"))
+        .await;
+
+    response.assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn test_upload_without_file_field() {
+    let server = server(1024);
+
+    let response = server
+        .post("/uploads")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .multipart(MultipartForm::new().add_text("note", "no file"))
+        .await;
+
+    response.assert_status(StatusCode::BAD_REQUEST);
+    let body: Value = response.json();
+    assert!(
+        body["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("'file'")
+    );
+}
+
+// ============================================================================
+// Upload Store Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_upload_roundtrip_and_ownership() {
+    let uploads = UploadStore::new(SharedStore::default(), Duration::from_secs(60), 0);
+    let handle = uploads
+        .save("alice", None, "application/pdf", b"%PDF")
+        .await
+        .unwrap();
+
+    let upload = uploads.load(&handle, "alice").await.unwrap().unwrap();
+    assert_eq!(upload.data, b"%PDF");
+    assert_eq!(upload.mime_type, "application/pdf");
+    assert!(uploads.load(&handle, "bob").await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_upload_expires() {
+    let uploads = UploadStore::new(SharedStore::default(), Duration::from_millis(20), 0);
+    let handle = uploads
+        .save("alice", None, "text/plain", b"x")
+        .await
+        .unwrap();
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(uploads.load(&handle, "alice").await.unwrap().is_none());
+}
+
+// ============================================================================
+// Tool Access Tests
+// ============================================================================
+
+/// Tool returning the size of the uploaded file passed as `file`
+fn file_size_tool() -> ToolFunction {
+    Box::new(|args, ctx: ToolContext| {
+        Box::pin(async move {
+            let handle = args.unwrap()["file"].as_str().unwrap().to_string();
+            let upload = ctx.upload(&handle).await?;
+            Ok(json!({ "filename": upload.filename, "size": upload.data.len() }))
+        })
+    })
+}
+
+async fn invoke_with_upload(state: &AppState, user: UserCredentials, handle: &str) -> Value {
+    let request: McpRequest = serde_json::from_value(json!({
+        "method": "invoke",
+        "params": { "tool_name": "file_size", "arguments": { "file": handle } }
+    }))
+    .unwrap();
+    let Json(response) = handle_mcp_request(
+        State(state.clone()),
+        Extension(AuthenticatedUser(user)),
+        Json(request),
+    )
+    .await;
+    json!(response)
+}
+
+#[tokio::test]
+async fn test_tool_reads_upload_by_handle() {
+    let uploads = UploadStore::new(SharedStore::default(), Duration::from_secs(60), 0);
+    let handle = uploads
+        .save(
+            TEST_USERNAME,
+            Some("data.bin".to_string()),
+            "application/octet-stream",
+            &[1, 2, 3],
+        )
+        .await
+        .unwrap();
+    let state = AppState {
+        tool_registry: Arc::new(ArcSwap::from_pointee(HashMap::from([(
+            "file_size".to_string(),
+            file_size_tool(),
+        )]))),
+        uploads,
+        ..Default::default()
+    };
+
+    let response = invoke_with_upload(&state, create_test_user(), &handle).await;
+    assert_eq!(
+        response["result"],
+        json!({ "filename": "data.bin", "size": 3 })
+    );
+
+    let other = UserCredentials::new("other".to_string(), "other-key".to_string(), HashMap::new());
+    let response = invoke_with_upload(&state, other, &handle).await;
+    assert!(
+        response["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("not found or expired")
+    );
+}
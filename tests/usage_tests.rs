@@ -0,0 +1,376 @@
+mod common;
+
+use axum_test::TestServer;
+use chrono::{DateTime, Duration, Utc};
+use common::*;
+use mcp_server::audit::AuditStatus;
+use mcp_server::auth::UserCredentials;
+use mcp_server::config::{ServerConfig, UsageConfig};
+use mcp_server::database::Storage;
+use mcp_server::usage::{UsageCounts, UsageRecorder, UsageRow, parse_time, to_csv};
+use mcp_server::{AppOptions, ERROR_AUTH, ERROR_INVALID_PARAMS, create_app_with_options};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const ADMIN_API_KEY: &str = "admin-api-key-000";
+
+fn credentials_with_admin() -> mcp_server::auth::CredentialsStore {
+    let mut store = (*create_multi_user_credentials_store()).clone();
+    store.insert(
+        ADMIN_API_KEY.to_string(),
+        UserCredentials::new(
+            "admin".to_string(),
+            ADMIN_API_KEY.to_string(),
+            HashMap::new(),
+        )
+        .with_admin(true),
+    );
+    Arc::new(store)
+}
+
+fn server() -> TestServer {
+    let options = AppOptions::new(ServerConfig::default());
+    TestServer::new(create_app_with_options(credentials_with_admin(), options).unwrap()).unwrap()
+}
+
+fn at(time: &str) -> DateTime<Utc> {
+    parse_time(time).unwrap()
+}
+
+fn row(user: &str, tool: &str, counts: UsageCounts) -> UsageRow {
+    UsageRow {
+        user: user.to_string(),
+        tool: tool.to_string(),
+        counts,
+    }
+}
+
+// ============================================================================
+// Config Tests
+// ============================================================================
+
+#[test]
+fn test_usage_config_parsed() {
+    let defaults = UsageConfig::default();
+    assert_eq!(defaults.path, None);
+    assert_eq!(defaults.flush_interval_secs, 60);
+    assert_eq!(defaults.retention_days, 90);
+
+    let config: ServerConfig = toml::from_str(
+        "[usage]\npath = \"data/usage.db\"\nflush_interval_secs = 10\nretention_days = 0\n",
+    )
+    .unwrap();
+    assert_eq!(config.usage.path.as_deref(), Some("data/usage.db"));
+    assert_eq!(config.usage.flush_interval_secs, 10);
+    assert_eq!(config.usage.retention_days, 0);
+}
+
+#[cfg(not(feature = "sqlite"))]
+#[test]
+fn test_usage_database_requires_feature() {
+    let config = UsageConfig {
+        path: Some("usage.db".to_string()),
+        ..Default::default()
+    };
+    let err = UsageRecorder::new(&config, &Storage::default())
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("`sqlite` feature"));
+}
+
+#[test]
+fn test_parse_time_accepts_timestamps_and_dates() {
+    assert_eq!(
+        parse_time("2026-10-01").unwrap(),
+        at("2026-10-01T00:00:00Z")
+    );
+    assert_eq!(
+        parse_time("2026-10-01T12:00:00+02:00").unwrap(),
+        at("2026-10-01T10:00:00Z")
+    );
+    assert!(parse_time("yesterday").is_err());
+}
+
+// ============================================================================
+// UsageRecorder Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_usage_counted_per_user_and_tool() {
+    let usage = UsageRecorder::default();
+    let now = Utc::now();
+    usage.record("alice", "search", now, AuditStatus::Success, 100);
+    usage.record("alice", "search", now, AuditStatus::Error, 50);
+    usage.record("alice", "search", now, AuditStatus::TimedOut, 20);
+    usage.record("alice", "email", now, AuditStatus::Rejected, 0);
+    usage.record("bob", "search", now, AuditStatus::Success, 10);
+
+    let report = usage.report(None, None, None).await.unwrap();
+    assert_eq!(
+        report,
+        vec![
+            row(
+                "alice",
+                "email",
+                UsageCounts {
+                    invocations: 1,
+                    rejected: 1,
+                    ..Default::default()
+                }
+            ),
+            row(
+                "alice",
+                "search",
+                UsageCounts {
+                    invocations: 3,
+                    succeeded: 1,
+                    failed: 2,
+                    rejected: 0,
+                    duration_ms: 170,
+                }
+            ),
+            row(
+                "bob",
+                "search",
+                UsageCounts {
+                    invocations: 1,
+                    succeeded: 1,
+                    duration_ms: 10,
+                    ..Default::default()
+                }
+            ),
+        ]
+    );
+
+    let bob = usage.report(None, None, Some("bob")).await.unwrap();
+    assert_eq!(bob.len(), 1);
+    assert_eq!(bob[0].user, "bob");
+}
+
+#[tokio::test]
+async fn test_report_selects_hours_starting_in_range() {
+    let usage = UsageRecorder::new(
+        &UsageConfig {
+            retention_days: 0,
+            ..Default::default()
+        },
+        &Storage::default(),
+    )
+    .unwrap();
+    for time in [
+        "2026-10-01T09:59:00Z",
+        "2026-10-01T10:30:00Z",
+        "2026-10-01T11:15:00Z",
+    ] {
+        usage.record("alice", "search", at(time), AuditStatus::Success, 1);
+    }
+    // Flushed usage and usage counted since add up
+    usage.flush().await.unwrap();
+    usage.record(
+        "alice",
+        "search",
+        at("2026-10-01T10:45:00Z"),
+        AuditStatus::Success,
+        1,
+    );
+
+    let invocations = |report: Vec<UsageRow>| report.first().map_or(0, |r| r.counts.invocations);
+    let range = |from: &str, to: &str| usage.report(Some(at(from)), Some(at(to)), None);
+    assert_eq!(
+        invocations(
+            range("2026-10-01T10:00:00Z", "2026-10-01T11:00:00Z")
+                .await
+                .unwrap()
+        ),
+        2
+    );
+    // An hour is included when it starts within the range
+    assert_eq!(
+        invocations(
+            range("2026-10-01T10:30:00Z", "2026-10-01T12:00:00Z")
+                .await
+                .unwrap()
+        ),
+        1
+    );
+    assert_eq!(
+        invocations(usage.report(None, None, None).await.unwrap()),
+        4
+    );
+}
+
+#[tokio::test]
+async fn test_usage_past_retention_dropped() {
+    let usage = UsageRecorder::new(
+        &UsageConfig {
+            retention_days: 1,
+            ..Default::default()
+        },
+        &Storage::default(),
+    )
+    .unwrap();
+    let now = Utc::now();
+    usage.record(
+        "alice",
+        "old",
+        now - Duration::days(3),
+        AuditStatus::Success,
+        1,
+    );
+    usage.record("alice", "new", now, AuditStatus::Success, 1);
+
+    let report = usage.report(None, None, None).await.unwrap();
+    let tools: Vec<&str> = report.iter().map(|r| r.tool.as_str()).collect();
+    assert_eq!(tools, vec!["new"]);
+}
+
+#[test]
+fn test_csv_quotes_special_characters() {
+    let counts = UsageCounts {
+        invocations: 2,
+        succeeded: 1,
+        failed: 1,
+        rejected: 0,
+        duration_ms: 30,
+    };
+    let csv = to_csv(&[
+        row("alice", "search", counts),
+        row("acme, inc", "say \"hi\"", counts),
+    ]);
+    assert_eq!(
+        csv,
+        "user,tool,invocations,succeeded,failed,rejected,duration_ms\n\
+         alice,search,2,1,1,0,30\n\
+         \"acme, inc\",\"say \"\"hi\"\"\",2,1,1,0,30\n"
+    );
+}
+
+// ============================================================================
+// Admin Endpoint Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_admin_usage_requires_admin() {
+    let response = server()
+        .get("/admin/usage")
+        .add_header("Authorization", format!("Bearer {}", TEST_API_KEY))
+        .await;
+
+    response.assert_status_forbidden();
+    let body: Value = response.json();
+    assert_eq!(body["error"]["code"], ERROR_AUTH);
+}
+
+#[tokio::test]
+async fn test_admin_usage_reports_invocations() {
+    let server = server();
+    for key in [TEST_API_KEY, TEST_API_KEY, TEST_API_KEY_2] {
+        server
+            .post("/mcp")
+            .add_header("Authorization", format!("Bearer {}", key))
+            .json(&json!({"method": "invoke", "params": {"tool_name": "get_current_time"}}))
+            .await;
+    }
+
+    let body: Value = server
+        .get("/admin/usage")
+        .add_query_param("from", Utc::now().date_naive().to_string())
+        .add_header("Authorization", format!("Bearer {}", ADMIN_API_KEY))
+        .await
+        .json();
+    let result = &body["result"];
+    assert_eq!(result["total"]["invocations"], 3);
+    assert_eq!(result["to"], Value::Null);
+    let usage = result["usage"].as_array().unwrap();
+    assert_eq!(usage.len(), 2);
+    assert_eq!(usage[0]["user"], TEST_USERNAME);
+    assert_eq!(usage[0]["tool"], "get_current_time");
+    assert_eq!(usage[0]["invocations"], 2);
+    assert_eq!(usage[0]["succeeded"], 2);
+
+    let response = server
+        .get("/admin/usage")
+        .add_query_param("user", TEST_USERNAME_2)
+        .add_query_param("format", "csv")
+        .add_header("Authorization", format!("Bearer {}", ADMIN_API_KEY))
+        .await;
+    response.assert_status_ok();
+    assert!(
+        response
+            .header("content-type")
+            .to_str()
+            .unwrap()
+            .starts_with("text/csv")
+    );
+    let lines: Vec<String> = response.text().lines().map(str::to_string).collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[1].starts_with(&format!("{},get_current_time,1,1,0,0,", TEST_USERNAME_2)));
+}
+
+#[tokio::test]
+async fn test_admin_usage_rejects_invalid_range() {
+    let response = server()
+        .get("/admin/usage")
+        .add_query_param("from", "last week")
+        .add_header("Authorization", format!("Bearer {}", ADMIN_API_KEY))
+        .await;
+
+    response.assert_status_bad_request();
+    let body: Value = response.json();
+    assert_eq!(body["error"]["code"], ERROR_INVALID_PARAMS);
+}
+
+#[cfg(feature = "sqlite")]
+mod sqlite {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn open(dir: &TempDir) -> UsageRecorder {
+        UsageRecorder::new(
+            &UsageConfig {
+                path: Some(dir.path().join("usage.db").to_str().unwrap().to_string()),
+                ..Default::default()
+            },
+            &Storage::default(),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_usage_persisted_across_reopen() {
+        let dir = TempDir::new().unwrap();
+        let now = Utc::now();
+
+        let usage = open(&dir);
+        usage.record("alice", "search", now, AuditStatus::Success, 10);
+        usage.flush().await.unwrap();
+        drop(usage);
+
+        // A second recorder (a restart or another replica) adds to the same hour
+        let usage = open(&dir);
+        usage.record("alice", "search", now, AuditStatus::Error, 5);
+        let report = usage.report(None, None, None).await.unwrap();
+        assert_eq!(
+            report,
+            vec![row(
+                "alice",
+                "search",
+                UsageCounts {
+                    invocations: 2,
+                    succeeded: 1,
+                    failed: 1,
+                    rejected: 0,
+                    duration_ms: 15,
+                }
+            )]
+        );
+        assert!(
+            usage
+                .report(Some(now + Duration::hours(1)), None, None)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
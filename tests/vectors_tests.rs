@@ -0,0 +1,317 @@
+mod common;
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::routing::{get, post, put};
+use axum::{Json, Router};
+use common::*;
+use mcp_server::auth::{AuthenticatedUser, UserCredentials};
+use mcp_server::config::ServerConfig;
+use mcp_server::tools::vectors::{VectorQueryTool, VectorUpsertTool, point_id};
+use mcp_server::tools::{InvalidInput, McpTool, ToolContext};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Collections of the mock Qdrant: vector size and points by ID
+type Collections = Arc<Mutex<HashMap<String, (usize, HashMap<String, Value>)>>>;
+
+type Reply = (StatusCode, Json<Value>);
+
+fn error(status: StatusCode, message: &str) -> Reply {
+    (status, Json(json!({ "status": { "error": message } })))
+}
+
+fn ok(result: Value) -> Reply {
+    (
+        StatusCode::OK,
+        Json(json!({ "status": "ok", "result": result })),
+    )
+}
+
+async fn get_collection(State(db): State<Collections>, Path(name): Path<String>) -> Reply {
+    match db.lock().unwrap().get(&name) {
+        Some(_) => ok(json!({})),
+        None => error(StatusCode::NOT_FOUND, "Not found"),
+    }
+}
+
+async fn create_collection(
+    State(db): State<Collections>,
+    Path(name): Path<String>,
+    Json(body): Json<Value>,
+) -> Reply {
+    assert_eq!(body["vectors"]["distance"], "Cosine");
+    let size = body["vectors"]["size"].as_u64().unwrap() as usize;
+    db.lock().unwrap().insert(name, (size, HashMap::new()));
+    ok(json!(true))
+}
+
+async fn upsert(
+    State(db): State<Collections>,
+    Path(name): Path<String>,
+    Json(body): Json<Value>,
+) -> Reply {
+    let mut db = db.lock().unwrap();
+    let (size, points) = db.get_mut(&name).unwrap();
+    for point in body["points"].as_array().unwrap() {
+        if point["vector"].as_array().unwrap().len() != *size {
+            return error(
+                StatusCode::BAD_REQUEST,
+                "Wrong input: Vector dimension error",
+            );
+        }
+        points.insert(point["id"].as_str().unwrap().to_string(), point.clone());
+    }
+    ok(json!({ "status": "completed" }))
+}
+
+/// Dot product ranking of the points matching every `must` condition
+async fn search(
+    State(db): State<Collections>,
+    Path(name): Path<String>,
+    Json(body): Json<Value>,
+) -> Reply {
+    let db = db.lock().unwrap();
+    let Some((_, points)) = db.get(&name) else {
+        return error(StatusCode::NOT_FOUND, "Not found");
+    };
+    let query: Vec<f64> = serde_json::from_value(body["vector"].clone()).unwrap();
+    let conditions = body["filter"]["must"].as_array().unwrap();
+
+    let mut hits: Vec<Value> = points
+        .values()
+        .filter(|point| {
+            conditions.iter().all(|condition| {
+                let pointer = format!("/{}", condition["key"].as_str().unwrap().replace('.', "/"));
+                point["payload"].pointer(&pointer) == Some(&condition["match"]["value"])
+            })
+        })
+        .map(|point| {
+            let vector: Vec<f64> = serde_json::from_value(point["vector"].clone()).unwrap();
+            let score: f64 = vector.iter().zip(&query).map(|(a, b)| a * b).sum();
+            json!({ "id": point["id"], "score": score, "payload": point["payload"] })
+        })
+        .collect();
+    hits.sort_by(|a, b| {
+        b["score"]
+            .as_f64()
+            .partial_cmp(&a["score"].as_f64())
+            .unwrap()
+    });
+    hits.truncate(body["limit"].as_u64().unwrap() as usize);
+    ok(json!(hits))
+}
+
+/// Embeds a text as `[number of 'a', number of 'b', 1]`
+async fn embeddings(Json(body): Json<Value>) -> Json<Value> {
+    assert_eq!(body["model"], "text-embedding-3-small");
+    let data: Vec<Value> = body["input"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .enumerate()
+        .rev()
+        .map(|(index, text)| {
+            let text = text.as_str().unwrap();
+            let count = |c| text.matches(c).count() as f64;
+            json!({ "index": index, "embedding": [count('a'), count('b'), 1.0] })
+        })
+        .collect();
+    Json(json!({ "data": data }))
+}
+
+/// Mock Qdrant and embedding endpoint; returns their base URL
+async fn mock_server() -> String {
+    let app = Router::new()
+        .route(
+            "/collections/{name}",
+            get(get_collection).put(create_collection),
+        )
+        .route("/collections/{name}/index", put(|| async { ok(json!({})) }))
+        .route("/collections/{name}/points", put(upsert))
+        .route("/collections/{name}/points/search", post(search))
+        .route("/embeddings", post(embeddings))
+        .with_state(Collections::default());
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}", addr)
+}
+
+fn context(server: &str, username: &str, embeddings: bool) -> ToolContext {
+    let mut config = ServerConfig::default();
+    if embeddings {
+        config.vectors.embedding_endpoint = Some(format!("{}/embeddings", server));
+    }
+    let external_keys = HashMap::from([("qdrant_url".to_string(), server.to_string())]);
+    let user = UserCredentials::new(
+        username.to_string(),
+        TEST_API_KEY.to_string(),
+        external_keys,
+    );
+    ToolContext::new(AuthenticatedUser(user)).with_config(Arc::new(config))
+}
+
+async fn upsert_points(ctx: &ToolContext, points: Value) -> anyhow::Result<Value> {
+    VectorUpsertTool
+        .execute_with_context(Some(json!({ "points": points })), ctx.clone())
+        .await
+}
+
+async fn query(ctx: &ToolContext, args: Value) -> anyhow::Result<Value> {
+    VectorQueryTool
+        .execute_with_context(Some(args), ctx.clone())
+        .await
+}
+
+fn ids(result: &Value) -> Vec<&str> {
+    result["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|hit| hit["id"].as_str().unwrap())
+        .collect()
+}
+
+#[test]
+fn test_point_ids_are_uuids_per_owner() {
+    let id = point_id("alice", "doc-1");
+    assert!(uuid::Uuid::parse_str(&id).is_ok());
+    assert_eq!(id, point_id("alice", "doc-1"));
+    assert_ne!(id, point_id("bob", "doc-1"));
+}
+
+#[tokio::test]
+async fn test_upsert_and_query_with_client_vectors() {
+    let server = mock_server().await;
+    let ctx = context(&server, "alice", false);
+
+    let result = upsert_points(
+        &ctx,
+        json!([
+            {"id": "x", "vector": [1.0, 0.0], "text": "about x", "metadata": {"lang": "en"}},
+            {"id": "y", "vector": [0.0, 1.0], "metadata": {"lang": "fr"}},
+        ]),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result, json!({"collection": "mcp_vectors", "upserted": 2}));
+
+    let result = query(&ctx, json!({"vector": [0.9, 0.1]})).await.unwrap();
+    assert_eq!(ids(&result), ["x", "y"]);
+    assert_eq!(result["results"][0]["text"], "about x");
+    assert_eq!(result["results"][0]["metadata"], json!({"lang": "en"}));
+
+    let result = query(
+        &ctx,
+        json!({"vector": [0.9, 0.1], "filter": {"lang": "fr"}}),
+    )
+    .await
+    .unwrap();
+    assert_eq!(ids(&result), ["y"]);
+
+    let result = query(&ctx, json!({"vector": [0.9, 0.1], "limit": 1}))
+        .await
+        .unwrap();
+    assert_eq!(ids(&result), ["x"]);
+}
+
+#[tokio::test]
+async fn test_users_only_see_their_points() {
+    let server = mock_server().await;
+    let alice = context(&server, "alice", false);
+    let bob = context(&server, "bob", false);
+
+    upsert_points(&alice, json!([{"id": "doc", "vector": [1.0, 0.0]}]))
+        .await
+        .unwrap();
+    upsert_points(&bob, json!([{"id": "doc", "vector": [0.0, 1.0]}]))
+        .await
+        .unwrap();
+
+    let result = query(&bob, json!({"vector": [1.0, 0.0]})).await.unwrap();
+    assert_eq!(result["results"].as_array().unwrap().len(), 1);
+    assert_eq!(result["results"][0]["score"], 0.0);
+}
+
+#[tokio::test]
+async fn test_texts_embedded_by_endpoint() {
+    let server = mock_server().await;
+    let ctx = context(&server, "alice", true);
+
+    upsert_points(
+        &ctx,
+        json!([
+            {"id": "a", "text": "aaaa"},
+            {"id": "b", "text": "bbbb"},
+            {"id": "v", "vector": [0.0, 0.0, 0.5]},
+        ]),
+    )
+    .await
+    .unwrap();
+
+    let result = query(&ctx, json!({"text": "bb"})).await.unwrap();
+    assert_eq!(ids(&result), ["b", "a", "v"]);
+    assert_eq!(result["results"][0]["text"], "bbbb");
+}
+
+#[tokio::test]
+async fn test_query_before_any_upsert_is_empty() {
+    let server = mock_server().await;
+    let result = query(&context(&server, "alice", false), json!({"vector": [1.0]}))
+        .await
+        .unwrap();
+    assert_eq!(result["results"], json!([]));
+}
+
+#[tokio::test]
+async fn test_invalid_points_and_queries() {
+    let server = mock_server().await;
+    let ctx = context(&server, "alice", false);
+
+    let error = upsert_points(&ctx, json!([{"id": "a"}])).await.unwrap_err();
+    let error = error.downcast_ref::<InvalidInput>().unwrap();
+    assert_eq!(error.message, "point 0 needs a vector or a text");
+    assert_eq!(error.data, Some(json!({"index": 0})));
+
+    let error = upsert_points(
+        &ctx,
+        json!([{"id": "a", "vector": [1.0]}, {"id": "b", "vector": [1.0, 2.0]}]),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error.downcast_ref::<InvalidInput>().unwrap().message,
+        "point 1 has 2 dimensions, expected 1"
+    );
+
+    let error = query(&ctx, json!({"vector": [1.0], "text": "both"}))
+        .await
+        .unwrap_err();
+    assert!(error.downcast_ref::<InvalidInput>().is_some());
+
+    // Texts need an embedding endpoint
+    let error = query(&ctx, json!({"text": "hello"})).await.unwrap_err();
+    assert!(error.to_string().contains("embedding_endpoint is not set"));
+
+    // Dimension mismatches with the collection are reported by Qdrant
+    upsert_points(&ctx, json!([{"id": "a", "vector": [1.0]}]))
+        .await
+        .unwrap();
+    let error = upsert_points(&ctx, json!([{"id": "b", "vector": [1.0, 2.0]}]))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "qdrant returned HTTP 400 Bad Request: Wrong input: Vector dimension error"
+    );
+}
+
+#[tokio::test]
+async fn test_requires_qdrant_url() {
+    let ctx = ToolContext::new(AuthenticatedUser(create_test_user()));
+    let error = query(&ctx, json!({"vector": [1.0]})).await.unwrap_err();
+    assert!(error.to_string().contains("qdrant_url"));
+}
@@ -0,0 +1,211 @@
+mod common;
+
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use common::*;
+use mcp_server::auth::{AuthenticatedUser, UserCredentials};
+use mcp_server::config::ServerConfig;
+use mcp_server::tools::weather::{
+    GetWeatherTool, WeatherCache, convert_speed, convert_temperature,
+};
+use mcp_server::tools::{InvalidInput, McpTool, ToolContext};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// OpenWeatherMap-compatible endpoint counting its requests; knows every
+/// city except "Atlantis" and reports 20 °C with a 10 m/s wind
+async fn mock_openweather() -> (String, Arc<AtomicUsize>) {
+    async fn weather(
+        State(hits): State<Arc<AtomicUsize>>,
+        Query(params): Query<HashMap<String, String>>,
+    ) -> Result<Json<Value>, StatusCode> {
+        hits.fetch_add(1, Ordering::SeqCst);
+        if params.get("appid").map(String::as_str) != Some("owm-key") {
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+        assert_eq!(params["units"], "metric");
+        let name = match (params.get("q"), params.get("lat")) {
+            (Some(q), _) if q == "Atlantis" => return Err(StatusCode::NOT_FOUND),
+            (Some(q), _) => q.clone(),
+            (None, Some(_)) => "Somewhere".to_string(),
+            (None, None) => return Err(StatusCode::BAD_REQUEST),
+        };
+        Ok(Json(json!({
+            "name": name,
+            "coord": { "lat": 48.85, "lon": 2.35 },
+            "sys": { "country": "FR" },
+            "weather": [{ "main": "Clouds", "description": "broken clouds" }],
+            "main": { "temp": 20.0, "feels_like": 19.5, "temp_min": 18.0, "temp_max": 22.0,
+                      "humidity": 60, "pressure": 1012 },
+            "wind": { "speed": 10.0, "deg": 270 },
+            "dt": 1_700_000_000
+        })))
+    }
+
+    let hits = Arc::new(AtomicUsize::new(0));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = Router::new()
+        .route("/weather", get(weather))
+        .with_state(hits.clone());
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    (format!("http://{}/weather", addr), hits)
+}
+
+fn context(endpoint: String, api_key: Option<&str>, cache_ttl_secs: u64) -> ToolContext {
+    let mut external_keys = HashMap::new();
+    if let Some(api_key) = api_key {
+        external_keys.insert("openweather_api_key".to_string(), api_key.to_string());
+    }
+    let user = UserCredentials::new(
+        "forecaster".to_string(),
+        TEST_API_KEY.to_string(),
+        external_keys,
+    );
+
+    let mut config = ServerConfig::default();
+    config.weather.endpoint = Some(endpoint);
+    config.weather.cache_ttl_secs = cache_ttl_secs;
+    ToolContext::new(AuthenticatedUser(user)).with_config(Arc::new(config))
+}
+
+async fn weather(ctx: ToolContext, args: Value) -> anyhow::Result<Value> {
+    GetWeatherTool.execute_with_context(Some(args), ctx).await
+}
+
+#[test]
+fn test_unit_conversions() {
+    assert_eq!(convert_temperature(20.0, "celsius"), 20.0);
+    assert_eq!(convert_temperature(20.0, "fahrenheit"), 68.0);
+    assert_eq!(convert_temperature(-40.0, "fahrenheit"), -40.0);
+    assert_eq!(convert_temperature(20.0, "kelvin"), 293.2);
+
+    assert_eq!(convert_speed(10.0, "mps"), 10.0);
+    assert_eq!(convert_speed(10.0, "kmh"), 36.0);
+    assert_eq!(convert_speed(10.0, "mph"), 22.4);
+    assert_eq!(convert_speed(10.0, "knots"), 19.4);
+}
+
+#[test]
+fn test_cache_expiry_and_capacity() {
+    let cache = WeatherCache::default();
+    cache.insert("q:paris".to_string(), json!(1), 2);
+    assert_eq!(
+        cache.get("q:paris", Duration::from_secs(60)),
+        Some(json!(1))
+    );
+    assert_eq!(cache.get("q:paris", Duration::ZERO), None);
+    assert!(cache.is_empty());
+
+    cache.insert("q:a".to_string(), json!("a"), 2);
+    cache.insert("q:b".to_string(), json!("b"), 2);
+    cache.insert("q:c".to_string(), json!("c"), 2);
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.get("q:a", Duration::from_secs(60)), None);
+
+    cache.insert("q:d".to_string(), json!("d"), 0);
+    assert_eq!(cache.get("q:d", Duration::from_secs(60)), None);
+}
+
+#[tokio::test]
+async fn test_weather_in_requested_units() {
+    let (endpoint, _) = mock_openweather().await;
+
+    let result = weather(
+        context(endpoint, Some("owm-key"), 0),
+        json!({"location": "Paris,FR", "temperature_unit": "fahrenheit", "wind_speed_unit": "kmh"}),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["location"], "Paris,FR");
+    assert_eq!(result["country"], "FR");
+    assert_eq!(result["conditions"], "broken clouds");
+    assert_eq!(result["temperature"], 68.0);
+    assert_eq!(result["temperature_max"], 71.6);
+    assert_eq!(result["wind_speed"], 36.0);
+    assert_eq!(result["humidity_percent"], 60);
+    assert_eq!(
+        result["units"],
+        json!({"temperature": "fahrenheit", "wind_speed": "kmh"})
+    );
+    assert_eq!(result["observed_at"], "2023-11-14T22:13:20+00:00");
+    assert_eq!(result["cached"], false);
+}
+
+#[tokio::test]
+async fn test_responses_cached_across_units() {
+    let (endpoint, hits) = mock_openweather().await;
+    let ctx = context(endpoint, Some("owm-key"), 600);
+
+    let first = weather(ctx.clone(), json!({"location": "Cache Town"}))
+        .await
+        .unwrap();
+    let second = weather(
+        ctx.clone(),
+        json!({"location": " cache town ", "temperature_unit": "kelvin"}),
+    )
+    .await
+    .unwrap();
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+    assert_eq!(first["cached"], false);
+    assert_eq!(second["cached"], true);
+    assert_eq!(second["temperature"], 293.2);
+
+    weather(ctx, json!({"latitude": 1.5, "longitude": 2.5}))
+        .await
+        .unwrap();
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_location_errors() {
+    let (endpoint, _) = mock_openweather().await;
+    let ctx = context(endpoint, Some("owm-key"), 0);
+
+    let error = weather(ctx.clone(), json!({"location": "Atlantis"}))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.downcast_ref::<InvalidInput>().unwrap().message,
+        "location not found"
+    );
+
+    let error = weather(ctx, json!({"location": "Paris", "latitude": 1.0}))
+        .await
+        .unwrap_err();
+    assert!(error.downcast_ref::<InvalidInput>().is_some());
+}
+
+#[tokio::test]
+async fn test_missing_and_rejected_key() {
+    let (endpoint, _) = mock_openweather().await;
+
+    let error = weather(
+        context(endpoint.clone(), None, 0),
+        json!({"location": "Paris"}),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "User missing credential 'openweather_api_key'"
+    );
+
+    let error = weather(
+        context(endpoint, Some("revoked"), 0),
+        json!({"location": "Paris"}),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "weather API returned HTTP 401 Unauthorized"
+    );
+}
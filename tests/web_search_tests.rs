@@ -0,0 +1,154 @@
+mod common;
+
+use axum::extract::Query;
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::get;
+use axum::{Json, Router};
+use common::*;
+use mcp_server::auth::{AuthenticatedUser, UserCredentials};
+use mcp_server::config::{SearchProvider, ServerConfig};
+use mcp_server::tools::web_search::{SearchResult, WebSearchTool};
+use mcp_server::tools::{McpTool, ToolContext};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Brave-compatible endpoint answering with as many results as requested,
+/// echoing the query and the subscription token into each hit
+async fn mock_brave() -> String {
+    async fn search(
+        headers: HeaderMap,
+        Query(params): Query<HashMap<String, String>>,
+    ) -> Result<Json<Value>, StatusCode> {
+        let token = headers
+            .get("X-Subscription-Token")
+            .and_then(|value| value.to_str().ok())
+            .ok_or(StatusCode::UNAUTHORIZED)?;
+        if token == "revoked" {
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+        let count: usize = params["count"].parse().unwrap();
+        let results: Vec<Value> = (0..count)
+            .map(|i| {
+                json!({
+                    "title": format!("{} #{}", params["q"], i),
+                    "url": format!("https://example.com/{}", i),
+                    "description": token,
+                })
+            })
+            .collect();
+        Ok(Json(json!({ "web": { "results": results } })))
+    }
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, Router::new().route("/search", get(search)))
+            .await
+            .unwrap();
+    });
+    format!("http://{}/search", addr)
+}
+
+fn context(endpoint: String, api_key: Option<&str>) -> ToolContext {
+    let mut external_keys = HashMap::new();
+    if let Some(api_key) = api_key {
+        external_keys.insert("brave_api_key".to_string(), api_key.to_string());
+    }
+    let user = UserCredentials::new(
+        "searcher".to_string(),
+        TEST_API_KEY.to_string(),
+        external_keys,
+    );
+
+    let mut config = ServerConfig::default();
+    config.web_search.endpoint = Some(endpoint);
+    config.web_search.max_results = 3;
+    ToolContext::new(AuthenticatedUser(user)).with_config(Arc::new(config))
+}
+
+async fn search(ctx: ToolContext, args: Value) -> anyhow::Result<Value> {
+    WebSearchTool.execute_with_context(Some(args), ctx).await
+}
+
+#[test]
+fn test_providers_are_normalized() {
+    let brave = json!({"web": {"results": [
+        {"title": "Rust", "url": "https://rust-lang.org", "description": "A language"}
+    ]}});
+    let serpapi = json!({"organic_results": [
+        {"position": 1, "title": "Rust", "link": "https://rust-lang.org", "snippet": "A language"}
+    ]});
+    let bing = json!({"webPages": {"value": [
+        {"name": "Rust", "url": "https://rust-lang.org", "snippet": "A language"},
+        {"name": "No url"}
+    ]}});
+    let expected = vec![SearchResult {
+        title: "Rust".to_string(),
+        url: "https://rust-lang.org".to_string(),
+        snippet: "A language".to_string(),
+    }];
+
+    assert_eq!(SearchProvider::Brave.parse_results(&brave), expected);
+    assert_eq!(SearchProvider::SerpApi.parse_results(&serpapi), expected);
+    assert_eq!(SearchProvider::Bing.parse_results(&bing), expected);
+    assert!(SearchProvider::Bing.parse_results(&json!({})).is_empty());
+}
+
+#[test]
+fn test_provider_is_selected_in_config() {
+    let config: ServerConfig = toml::from_str("[web_search]\nprovider = \"serpapi\"").unwrap();
+    assert_eq!(config.web_search.provider, SearchProvider::SerpApi);
+    assert_eq!(
+        ServerConfig::default().web_search.provider,
+        SearchProvider::Brave
+    );
+    assert!(toml::from_str::<ServerConfig>("[web_search]\nprovider = \"altavista\"").is_err());
+}
+
+#[tokio::test]
+async fn test_search_uses_the_users_key_and_caps_the_count() {
+    let endpoint = mock_brave().await;
+
+    let result = search(
+        context(endpoint.clone(), Some("user-key")),
+        json!({"query": "rust"}),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["provider"], "brave");
+    assert_eq!(result["query"], "rust");
+    let results = result["results"].as_array().unwrap();
+    // The default of 5 results is capped by max_results = 3
+    assert_eq!(results.len(), 3);
+    assert_eq!(
+        results[0],
+        json!({"title": "rust #0", "url": "https://example.com/0", "snippet": "user-key"})
+    );
+
+    let result = search(
+        context(endpoint, Some("user-key")),
+        json!({"query": "rust", "count": 1}),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["results"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_missing_key_and_provider_errors() {
+    let endpoint = mock_brave().await;
+
+    let error = search(context(endpoint.clone(), None), json!({"query": "rust"}))
+        .await
+        .unwrap_err();
+    assert_eq!(error.to_string(), "User missing credential 'brave_api_key'");
+
+    let error = search(context(endpoint, Some("revoked")), json!({"query": "rust"}))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "brave search returned HTTP 401 Unauthorized"
+    );
+}